@@ -67,5 +67,9 @@ create_native!(
     i64,
     DataType::Int64 | DataType::Date64 | DataType::Time64(_) | DataType::Timestamp(_, _)
 );
+create_native!(i128, DataType::Int128);
+create_native!(u128, DataType::UInt128);
+create_native!(crate::datatypes::i256, DataType::Int256);
+create_native!(crate::datatypes::u256, DataType::UInt256);
 create_native!(f32, DataType::Float32);
 create_native!(f64, DataType::Float64);
@@ -24,6 +24,7 @@ use super::*;
 use crate::array::equal_json::JsonEqual;
 use crate::buffer::{Buffer, MutableBuffer};
 use crate::error::Result;
+use crate::datatypes::decimal_byte_width;
 use crate::ffi;
 
 /// Trait for dealing with different types of array at runtime when the type of the
@@ -240,6 +241,10 @@ pub fn make_array(data: ArrayData) -> ArrayRef {
         DataType::UInt16 => Arc::new(UInt16Array::from(data)) as ArrayRef,
         DataType::UInt32 => Arc::new(UInt32Array::from(data)) as ArrayRef,
         DataType::UInt64 => Arc::new(UInt64Array::from(data)) as ArrayRef,
+        DataType::Int128 => Arc::new(Int128Array::from(data)) as ArrayRef,
+        DataType::UInt128 => Arc::new(UInt128Array::from(data)) as ArrayRef,
+        DataType::Int256 => Arc::new(Int256Array::from(data)) as ArrayRef,
+        DataType::UInt256 => Arc::new(UInt256Array::from(data)) as ArrayRef,
         DataType::Float16 => panic!("Float16 datatype not supported"),
         DataType::Float32 => Arc::new(Float32Array::from(data)) as ArrayRef,
         DataType::Float64 => Arc::new(Float64Array::from(data)) as ArrayRef,
@@ -408,6 +413,10 @@ pub fn new_null_array(data_type: &DataType, length: usize) -> ArrayRef {
         DataType::Int64 => new_null_sized_array::<Int64Type>(data_type, length),
         DataType::UInt64 => new_null_sized_array::<UInt64Type>(data_type, length),
         DataType::Float64 => new_null_sized_array::<Float64Type>(data_type, length),
+        DataType::Int128 => new_null_sized_array::<Int128Type>(data_type, length),
+        DataType::UInt128 => new_null_sized_array::<UInt128Type>(data_type, length),
+        DataType::Int256 => new_null_sized_array::<Int256Type>(data_type, length),
+        DataType::UInt256 => new_null_sized_array::<UInt256Type>(data_type, length),
         DataType::Date64 => new_null_sized_array::<Date64Type>(data_type, length),
         // expanding this into Timestamp{unit}Type results in needless branching
         DataType::Timestamp(_, _) => new_null_sized_array::<Int64Type>(data_type, length),
@@ -491,9 +500,12 @@ pub fn new_null_array(data_type: &DataType, length: usize) -> ArrayRef {
                 )
             })
         }
-        DataType::Decimal(_, _) => {
-            unimplemented!("Creating null Decimal array not yet supported")
-        }
+        DataType::Decimal(precision, _) => match decimal_byte_width(*precision) {
+            4 => new_null_sized_array::<Int32Type>(data_type, length),
+            8 => new_null_sized_array::<Int64Type>(data_type, length),
+            16 => new_null_sized_array::<Int128Type>(data_type, length),
+            _ => new_null_sized_array::<Int256Type>(data_type, length),
+        },
     }
 }
 
@@ -15,7 +15,7 @@
 // specific language governing permissions and limitations
 // under the License.
 
-use std::convert::{From, TryInto};
+use std::convert::From;
 use std::fmt;
 use std::{any::Any, iter::FromIterator};
 
@@ -26,7 +26,10 @@ use super::{
 use crate::buffer::Buffer;
 use crate::error::ArrowError;
 use crate::util::bit_util;
-use crate::{buffer::MutableBuffer, datatypes::DataType};
+use crate::{
+    buffer::MutableBuffer,
+    datatypes::{decimal_byte_width, i256, DataType},
+};
 
 /// Like OffsetSizeTrait, but specialized for Binary
 // This allow us to expose a constant datatype for the GenericBinaryArray
@@ -701,7 +704,20 @@ pub struct DecimalArray {
 
 impl DecimalArray {
     /// Returns the element at index `i` as i128.
+    ///
+    /// Panics if the value of a 256-bit decimal doesn't fit in i128, use
+    /// [`DecimalArray::value_i256`] for those.
     pub fn value(&self, i: usize) -> i128 {
+        let val = self.value_i256(i);
+        match val.to_half() {
+            Some(v) => v,
+            _ => panic!("DecimalArray element {} is out of the i128 range.", val),
+        }
+    }
+
+    /// Returns the element at index `i` as i256, sign-extended from the
+    /// width of the decimal.
+    pub fn value_i256(&self, i: usize) -> i256 {
         assert!(i < self.data.len(), "DecimalArray out of bounds access");
         let offset = i.checked_add(self.data.offset()).unwrap();
         let raw_val = unsafe {
@@ -711,11 +727,7 @@ impl DecimalArray {
                 (self.value_offset_at(offset + 1) - pos) as usize,
             )
         };
-        let as_array = raw_val.try_into();
-        match as_array {
-            Ok(v) if raw_val.len() == 16 => i128::from_le_bytes(v),
-            _ => panic!("DecimalArray elements are not 128bit integers."),
-        }
+        i256::from_le_slice(raw_val)
     }
 
     /// Returns the offset for the element at index `i`.
@@ -746,13 +758,14 @@ impl DecimalArray {
 
     #[inline]
     pub fn value_as_string(&self, row: usize) -> String {
-        let value = self.value(row);
+        let value = self.value_i256(row);
         let value_str = value.to_string();
 
         if self.scale == 0 {
             value_str
         } else {
-            let (sign, rest) = value_str.split_at(if value >= 0 { 0 } else { 1 });
+            let (sign, rest) =
+                value_str.split_at(if value.is_negative() { 1 } else { 0 });
 
             if rest.len() > self.scale {
                 // Decimal separator is in the middle of the string
@@ -813,7 +826,7 @@ impl From<ArrayData> for DecimalArray {
             DataType::Decimal(precision, scale) => (*precision, *scale),
             _ => panic!("Expected data type to be Decimal"),
         };
-        let length = 16;
+        let length = decimal_byte_width(precision) as i32;
         Self {
             data,
             value_data: unsafe { RawPtrBox::new(values) },
@@ -1336,6 +1349,32 @@ mod tests {
         assert_eq!("0.000", arr.value_as_string(6));
     }
 
+    #[test]
+    fn test_decimal_array_widths() {
+        for (precision, width) in [(9, 4), (18, 8), (38, 16), (76, 32)] {
+            let mut decimal_builder = DecimalBuilder::new(3, precision, 2);
+            decimal_builder.append_value(-12345).unwrap();
+            decimal_builder.append_null().unwrap();
+            decimal_builder.append_value(999).unwrap();
+            let arr = decimal_builder.finish();
+            assert_eq!(width, arr.value_length());
+            assert_eq!(3 * width as usize, arr.value_data().len());
+            assert_eq!(-12345, arr.value(0));
+            assert_eq!("-123.45", arr.value_as_string(0));
+            assert!(arr.is_null(1));
+            assert_eq!("9.99", arr.value_as_string(2));
+        }
+
+        let mut decimal_builder = DecimalBuilder::new(2, 76, 0);
+        let max = "9".repeat(76).parse::<i256>().unwrap();
+        decimal_builder.append_i256(max).unwrap();
+        decimal_builder.append_i256(-max).unwrap();
+        assert!(decimal_builder.append_i256(max + i256::ONE).is_err());
+        let arr = decimal_builder.finish();
+        assert_eq!("9".repeat(76), arr.value_as_string(0));
+        assert_eq!(-max, arr.value_i256(1));
+    }
+
     #[test]
     fn test_decimal_array_fmt_debug() {
         let values: Vec<i128> = vec![8887000000, -8887000000];
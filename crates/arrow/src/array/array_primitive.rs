@@ -437,6 +437,10 @@ def_numeric_from_vec!(UInt32Type);
 def_numeric_from_vec!(UInt64Type);
 def_numeric_from_vec!(Float32Type);
 def_numeric_from_vec!(Float64Type);
+def_numeric_from_vec!(Int128Type);
+def_numeric_from_vec!(UInt128Type);
+def_numeric_from_vec!(Int256Type);
+def_numeric_from_vec!(UInt256Type);
 
 def_numeric_from_vec!(Timestamp32Type);
 def_numeric_from_vec!(Date16Type);
@@ -1498,7 +1498,7 @@ impl DecimalBuilder {
     /// array
     pub fn new(capacity: usize, precision: usize, scale: usize) -> Self {
         let values_builder = UInt8Builder::new(capacity);
-        let byte_width = 16;
+        let byte_width = decimal_byte_width(precision) as i32;
         Self {
             builder: FixedSizeListBuilder::new(values_builder, byte_width),
             precision,
@@ -1512,6 +1512,9 @@ impl DecimalBuilder {
     /// distinct array element.
     #[inline]
     pub fn append_value(&mut self, value: i128) -> Result<()> {
+        if self.precision > MAX_DECIMAL_FOR_EACH_PRECISION.len() {
+            return self.append_i256(i256::from(value));
+        }
         if value > MAX_DECIMAL_FOR_EACH_PRECISION[self.precision - 1]
             || value < MIN_DECIMAL_FOR_EACH_PRECISION[self.precision - 1]
         {
@@ -1535,15 +1538,31 @@ impl DecimalBuilder {
         self.builder.append(true)
     }
 
+    /// Appends an i256 value into the builder, which is the only way to
+    /// append values out of the i128 range to 256-bit decimals.
+    #[inline]
+    pub fn append_i256(&mut self, value: i256) -> Result<()> {
+        let bound = i256::ONE.checked_mul_pow10(self.precision as u32);
+        if matches!(bound, Some(b) if value >= b || value <= -b) {
+            return Err(ArrowError::InvalidArgumentError(format!(
+                "The value of {} i256 is not compatible with Decimal({},{})",
+                value, self.precision, self.scale
+            )));
+        }
+        let size = self.builder.value_length() as usize;
+        self.builder
+            .values()
+            .append_slice(&value.to_le_bytes()[..size])?;
+        self.builder.append(true)
+    }
+
     fn from_i128_to_fixed_size_bytes(v: i128, size: usize) -> Result<Vec<u8>> {
         if size > 16 {
-            return Err(ArrowError::InvalidArgumentError(
-                "DecimalBuilder only supports values up to 16 bytes.".to_string(),
-            ));
+            return Ok(i256::from(v).to_le_bytes()[..size].to_vec());
         }
-        let res = v.to_le_bytes();
-        let start_byte = 16 - size;
-        Ok(res[start_byte..16].to_vec())
+        // little-endian, so the low bytes are kept and the value is still
+        // sign-extended when read back
+        Ok(v.to_le_bytes()[..size].to_vec())
     }
 
     /// Append a null value to the array.
@@ -2036,6 +2055,10 @@ impl FieldData {
             DataType::UInt16 => self.append_null::<UInt16Type>()?,
             DataType::UInt32 => self.append_null::<UInt32Type>()?,
             DataType::UInt64 => self.append_null::<UInt64Type>()?,
+            DataType::Int128 => self.append_null::<Int128Type>()?,
+            DataType::UInt128 => self.append_null::<UInt128Type>()?,
+            DataType::Int256 => self.append_null::<Int256Type>()?,
+            DataType::UInt256 => self.append_null::<UInt256Type>()?,
             DataType::Float32 => self.append_null::<Float32Type>()?,
             DataType::Float64 => self.append_null::<Float64Type>()?,
             _ => unreachable!("All cases of types that satisfy the trait bounds over T are covered above."),
@@ -23,7 +23,7 @@ use std::mem;
 use std::ops::Range;
 use std::sync::Arc;
 
-use crate::datatypes::{DataType, IntervalUnit};
+use crate::datatypes::{decimal_byte_width, i256, u256, DataType, IntervalUnit};
 use crate::error::{ArrowError, Result};
 use crate::{bitmap::Bitmap, datatypes::ArrowNativeType};
 use crate::{
@@ -90,6 +90,22 @@ pub(crate) fn new_buffers(data_type: &DataType, capacity: usize) -> [MutableBuff
             MutableBuffer::new(capacity * mem::size_of::<i64>()),
             empty_buffer,
         ],
+        DataType::Int128 => [
+            MutableBuffer::new(capacity * mem::size_of::<i128>()),
+            empty_buffer,
+        ],
+        DataType::UInt128 => [
+            MutableBuffer::new(capacity * mem::size_of::<u128>()),
+            empty_buffer,
+        ],
+        DataType::Int256 => [
+            MutableBuffer::new(capacity * mem::size_of::<i256>()),
+            empty_buffer,
+        ],
+        DataType::UInt256 => [
+            MutableBuffer::new(capacity * mem::size_of::<u256>()),
+            empty_buffer,
+        ],
         DataType::Float32 => [
             MutableBuffer::new(capacity * mem::size_of::<f32>()),
             empty_buffer,
@@ -187,8 +203,8 @@ pub(crate) fn new_buffers(data_type: &DataType, capacity: usize) -> [MutableBuff
         DataType::FixedSizeList(_, _) | DataType::Struct(_) => {
             [empty_buffer, MutableBuffer::new(0)]
         }
-        DataType::Decimal(_, _) => [
-            MutableBuffer::new(capacity * mem::size_of::<u8>()),
+        DataType::Decimal(precision, _) => [
+            MutableBuffer::new(capacity * decimal_byte_width(*precision)),
             empty_buffer,
         ],
         DataType::Union(_) => unimplemented!(),
@@ -525,6 +541,10 @@ impl ArrayData {
             | DataType::Int16
             | DataType::Int32
             | DataType::Int64
+            | DataType::Int128
+            | DataType::UInt128
+            | DataType::Int256
+            | DataType::UInt256
             | DataType::Float32
             | DataType::Float64
             | DataType::Date32
@@ -1167,6 +1187,10 @@ fn layout(data_type: &DataType) -> DataTypeLayout {
         DataType::UInt16 => DataTypeLayout::new_fixed_width(size_of::<u16>()),
         DataType::UInt32 => DataTypeLayout::new_fixed_width(size_of::<u32>()),
         DataType::UInt64 => DataTypeLayout::new_fixed_width(size_of::<u64>()),
+        DataType::Int128 => DataTypeLayout::new_fixed_width(size_of::<i128>()),
+        DataType::UInt128 => DataTypeLayout::new_fixed_width(size_of::<u128>()),
+        DataType::Int256 => DataTypeLayout::new_fixed_width(size_of::<i256>()),
+        DataType::UInt256 => DataTypeLayout::new_fixed_width(size_of::<u256>()),
         DataType::Float16 => unimplemented!(),
         DataType::Float32 => DataTypeLayout::new_fixed_width(size_of::<f32>()),
         DataType::Float64 => DataTypeLayout::new_fixed_width(size_of::<f64>()),
@@ -1205,10 +1229,10 @@ fn layout(data_type: &DataType) -> DataTypeLayout {
             // https://github.com/apache/arrow-rs/issues/85
         }
         DataType::Dictionary(key_type, _value_type) => layout(key_type),
-        DataType::Decimal(_, _) => {
-            // Decimals are always some fixed width; The rust implemenation
-            // always uses 16 bytes / size of i128
-            DataTypeLayout::new_fixed_width(size_of::<i128>())
+        DataType::Decimal(precision, _) => {
+            // Decimals are always some fixed width, which is the width of the
+            // integers holding the values of the precision, like ClickHouse
+            DataTypeLayout::new_fixed_width(decimal_byte_width(*precision))
         }
         DataType::Map(_, _) => {
             // same as ListType
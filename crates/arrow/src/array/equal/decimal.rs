@@ -17,7 +17,7 @@
 
 use crate::array::{data::count_nulls, ArrayData};
 use crate::buffer::Buffer;
-use crate::datatypes::DataType;
+use crate::datatypes::{decimal_byte_width, DataType};
 use crate::util::bit_util::get_bit;
 
 use super::utils::equal_len;
@@ -32,7 +32,7 @@ pub(super) fn decimal_equal(
     len: usize,
 ) -> bool {
     let size = match lhs.data_type() {
-        DataType::Decimal(_, _) => 16,
+        DataType::Decimal(precision, _) => decimal_byte_width(*precision),
         _ => unreachable!(),
     };
 
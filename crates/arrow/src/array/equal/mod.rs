@@ -28,7 +28,7 @@ use super::{
 
 use crate::{
     buffer::Buffer,
-    datatypes::{ArrowPrimitiveType, DataType, IntervalUnit},
+    datatypes::{i256, u256, ArrowPrimitiveType, DataType, IntervalUnit},
 };
 
 mod boolean;
@@ -181,6 +181,18 @@ fn equal_values(
         DataType::Int64 => primitive_equal::<i64>(
             lhs, rhs, lhs_nulls, rhs_nulls, lhs_start, rhs_start, len,
         ),
+        DataType::Int128 => primitive_equal::<i128>(
+            lhs, rhs, lhs_nulls, rhs_nulls, lhs_start, rhs_start, len,
+        ),
+        DataType::UInt128 => primitive_equal::<u128>(
+            lhs, rhs, lhs_nulls, rhs_nulls, lhs_start, rhs_start, len,
+        ),
+        DataType::Int256 => primitive_equal::<i256>(
+            lhs, rhs, lhs_nulls, rhs_nulls, lhs_start, rhs_start, len,
+        ),
+        DataType::UInt256 => primitive_equal::<u256>(
+            lhs, rhs, lhs_nulls, rhs_nulls, lhs_start, rhs_start, len,
+        ),
         DataType::Float32 => primitive_equal::<f32>(
             lhs, rhs, lhs_nulls, rhs_nulls, lhs_start, rhs_start, len,
         ),
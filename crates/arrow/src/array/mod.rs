@@ -383,6 +383,10 @@ pub type DurationMicrosecondArray = PrimitiveArray<DurationMicrosecondType>;
 pub type DurationNanosecondArray = PrimitiveArray<DurationNanosecondType>;
 pub type Timestamp32Array = PrimitiveArray<Timestamp32Type>;
 pub type Date16Array = PrimitiveArray<Date16Type>;
+pub type Int128Array = PrimitiveArray<Int128Type>;
+pub type UInt128Array = PrimitiveArray<UInt128Type>;
+pub type Int256Array = PrimitiveArray<Int256Type>;
+pub type UInt256Array = PrimitiveArray<UInt256Type>;
 pub use self::array_binary::BinaryOffsetSizeTrait;
 pub use self::array_binary::GenericBinaryArray;
 pub use self::array_list::GenericListArray;
@@ -424,6 +428,10 @@ pub type DurationMicrosecondBufferBuilder = BufferBuilder<DurationMicrosecondTyp
 pub type DurationNanosecondBufferBuilder = BufferBuilder<DurationNanosecondType>;
 pub type Date16Builder = PrimitiveBuilder<Date16Type>;
 pub type Date16BufferBuilder = BufferBuilder<Date16Type>;
+pub type Int128Builder = PrimitiveBuilder<Int128Type>;
+pub type UInt128Builder = PrimitiveBuilder<UInt128Type>;
+pub type Int256Builder = PrimitiveBuilder<Int256Type>;
+pub type UInt256Builder = PrimitiveBuilder<UInt256Type>;
 pub use self::builder::ArrayBuilder;
 pub use self::builder::BinaryBuilder;
 pub use self::builder::BooleanBuilder;
@@ -51,6 +51,12 @@ where
     Box::new(move |i, j| left.value(i).cmp(&right.value(j)))
 }
 
+fn compare_decimal(left: &dyn Array, right: &dyn Array) -> DynComparator {
+    let left = DecimalArray::from(left.data().clone());
+    let right = DecimalArray::from(right.data().clone());
+    Box::new(move |i, j| left.value_i256(i).cmp(&right.value_i256(j)))
+}
+
 fn compare_boolean(left: &dyn Array, right: &dyn Array) -> DynComparator {
     let left: BooleanArray = BooleanArray::from(left.data().clone());
     let right: BooleanArray = BooleanArray::from(right.data().clone());
@@ -140,6 +146,11 @@ pub fn build_compare(left: &dyn Array, right: &dyn Array) -> Result<DynComparato
         (Int16, Int16) => compare_primitives::<Int16Type>(left, right),
         (Int32, Int32) => compare_primitives::<Int32Type>(left, right),
         (Int64, Int64) => compare_primitives::<Int64Type>(left, right),
+        (Int128, Int128) => compare_primitives::<Int128Type>(left, right),
+        (UInt128, UInt128) => compare_primitives::<UInt128Type>(left, right),
+        (Int256, Int256) => compare_primitives::<Int256Type>(left, right),
+        (UInt256, UInt256) => compare_primitives::<UInt256Type>(left, right),
+        (Decimal(_, _), Decimal(_, _)) => compare_decimal(left, right),
         (Float32, Float32) => compare_float::<Float32Type>(left, right),
         (Float64, Float64) => compare_float::<Float64Type>(left, right),
         (Date32, Date32) => compare_primitives::<Date32Type>(left, right),
@@ -248,6 +248,16 @@ fn build_extend(array: &ArrayData) -> Extend {
         DataType::Int16 => primitive::build_extend::<i16>(array),
         DataType::Int32 => primitive::build_extend::<i32>(array),
         DataType::Int64 => primitive::build_extend::<i64>(array),
+        DataType::Int128 => primitive::build_extend::<i128>(array),
+        DataType::UInt128 => primitive::build_extend::<u128>(array),
+        DataType::Int256 => primitive::build_extend::<i256>(array),
+        DataType::UInt256 => primitive::build_extend::<u256>(array),
+        DataType::Decimal(precision, _) => match decimal_byte_width(*precision) {
+            4 => primitive::build_extend::<i32>(array),
+            8 => primitive::build_extend::<i64>(array),
+            16 => primitive::build_extend::<i128>(array),
+            _ => primitive::build_extend::<i256>(array),
+        },
         DataType::Float32 => primitive::build_extend::<f32>(array),
         DataType::Float64 => primitive::build_extend::<f64>(array),
         DataType::Date32
@@ -294,6 +304,16 @@ fn build_extend_nulls(data_type: &DataType) -> ExtendNulls {
         DataType::Int16 => primitive::extend_nulls::<i16>,
         DataType::Int32 => primitive::extend_nulls::<i32>,
         DataType::Int64 => primitive::extend_nulls::<i64>,
+        DataType::Int128 => primitive::extend_nulls::<i128>,
+        DataType::UInt128 => primitive::extend_nulls::<u128>,
+        DataType::Int256 => primitive::extend_nulls::<i256>,
+        DataType::UInt256 => primitive::extend_nulls::<u256>,
+        DataType::Decimal(precision, _) => match decimal_byte_width(*precision) {
+            4 => primitive::extend_nulls::<i32>,
+            8 => primitive::extend_nulls::<i64>,
+            16 => primitive::extend_nulls::<i128>,
+            _ => primitive::extend_nulls::<i256>,
+        },
         DataType::Float32 => primitive::extend_nulls::<f32>,
         DataType::Float64 => primitive::extend_nulls::<f64>,
         DataType::Date32
@@ -436,6 +456,11 @@ impl<'a> MutableArrayData<'a> {
             | DataType::Int16
             | DataType::Int32
             | DataType::Int64
+            | DataType::Int128
+            | DataType::UInt128
+            | DataType::Int256
+            | DataType::UInt256
+            | DataType::Decimal(_, _)
             | DataType::Float32
             | DataType::Float64
             | DataType::Date32
@@ -15,6 +15,7 @@
 // specific language governing permissions and limitations
 // under the License.
 
+use std::any::Any;
 use std::fmt::Debug;
 use std::iter::FromIterator;
 use std::ptr::NonNull;
@@ -43,6 +44,28 @@ pub struct Buffer {
 }
 
 impl Buffer {
+    /// Creates a buffer from an existing memory region (must already be byte-aligned), which
+    /// is owned by `owner`. The region is kept valid until the buffer and all the buffers
+    /// sliced from it are dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `ptr` - Pointer to raw parts
+    /// * `len` - Length of raw parts in **bytes**
+    /// * `owner` - The owner of the region, which releases it when dropped
+    ///
+    /// # Safety
+    ///
+    /// This function is unsafe as there is no guarantee that the given pointer is valid for `len`
+    /// bytes as long as the `owner` is alive.
+    pub unsafe fn from_custom_allocation(
+        ptr: NonNull<u8>,
+        len: usize,
+        owner: Arc<dyn Any + Send + Sync>,
+    ) -> Self {
+        Buffer::build_with_arguments(ptr, len, Deallocation::Custom(owner))
+    }
+
     /// Auxiliary method to create a new Buffer
     #[inline]
     pub fn from_bytes(bytes: Bytes) -> Self {
@@ -20,6 +20,7 @@
 //! Note that this is a low-level functionality of this crate.
 
 use core::slice;
+use std::any::Any;
 use std::ptr::NonNull;
 use std::sync::Arc;
 use std::{fmt::Debug, fmt::Formatter};
@@ -32,6 +33,8 @@ pub enum Deallocation {
     Native(usize),
     /// Foreign interface, via a callback
     Foreign(Arc<ffi::FFI_ArrowArray>),
+    /// Custom owner of the region, which keeps the region valid until it is dropped
+    Custom(Arc<dyn Any + Send + Sync>),
 }
 
 impl Debug for Deallocation {
@@ -43,6 +46,9 @@ impl Debug for Deallocation {
             Deallocation::Foreign(_) => {
                 write!(f, "Deallocation::Foreign {{ capacity: unknown }}")
             }
+            Deallocation::Custom(_) => {
+                write!(f, "Deallocation::Custom {{ capacity: unknown }}")
+            }
         }
     }
 }
@@ -116,7 +122,7 @@ impl Bytes {
             Deallocation::Native(capacity) => capacity,
             // we cannot determine this in general,
             // and thus we state that this is externally-owned memory
-            Deallocation::Foreign(_) => 0,
+            Deallocation::Foreign(_) | Deallocation::Custom(_) => 0,
         }
     }
 }
@@ -130,6 +136,8 @@ impl Drop for Bytes {
             }
             // foreign interface knows how to deallocate itself.
             Deallocation::Foreign(_) => (),
+            // the region is released when the last reference of the owner is dropped
+            Deallocation::Custom(_) => (),
         }
     }
 }
@@ -43,6 +43,7 @@ use crate::compute::kernels::arithmetic::{divide, multiply};
 use crate::compute::kernels::arity::unary;
 use crate::compute::kernels::cast_utils::string_to_timestamp_nanos;
 use crate::compute::kernels::cast_utils::string_to_timestamp32;
+use crate::compute::kernels::wide::rescale_decimal;
 use crate::datatypes::*;
 use crate::error::{ArrowError, Result};
 use crate::{array::*, compute::take};
@@ -107,6 +108,8 @@ pub fn can_cast_types(from_type: &DataType, to_type: &DataType) -> bool {
             | Dictionary(_, _),
             Null,
         ) => true,
+        (Decimal(_, _), _) | (_, Decimal(_, _)) => can_cast_decimal(from_type, to_type),
+        (_, _) if is_wide_cast(from_type, to_type) => true,
         //
         (Utf8, Timestamp32(None)) => true,
         (LargeUtf8, Date16) => true,
@@ -387,6 +390,12 @@ pub fn cast_with_options(
             | Dictionary(_, _),
             Null,
         ) => Ok(new_null_array(to_type, array.len())),
+        (Decimal(_, _), _) | (_, Decimal(_, _))
+            if can_cast_decimal(from_type, to_type) =>
+        {
+            cast_decimal(array, to_type, cast_options)
+        }
+        (_, _) if is_wide_cast(from_type, to_type) => cast_wide_integer(array, to_type),
         (Struct(_), _) => Err(ArrowError::CastError(
             "Cannot cast from struct to other types".to_string(),
         )),
@@ -1100,8 +1109,8 @@ where
 /// Convert Array into a PrimitiveArray of type, and apply numeric cast
 fn cast_numeric_arrays<FROM, TO>(from: &ArrayRef) -> Result<ArrayRef>
 where
-    FROM: ArrowNumericType,
-    TO: ArrowNumericType,
+    FROM: ArrowPrimitiveType,
+    TO: ArrowPrimitiveType,
     FROM::Native: num::NumCast,
     TO::Native: num::NumCast,
 {
@@ -1112,11 +1121,243 @@ where
     )))
 }
 
+/// Calls `$body` with the type alias `$T` of the arrow type of the numeric or
+/// the wide integer data type
+macro_rules! with_numeric_type {
+    ($data_type:expr, $T:ident => $body:expr) => {{
+        match $data_type {
+            DataType::Int8 => {
+                type $T = Int8Type;
+                $body
+            }
+            DataType::Int16 => {
+                type $T = Int16Type;
+                $body
+            }
+            DataType::Int32 => {
+                type $T = Int32Type;
+                $body
+            }
+            DataType::Int64 => {
+                type $T = Int64Type;
+                $body
+            }
+            DataType::Int128 => {
+                type $T = Int128Type;
+                $body
+            }
+            DataType::Int256 => {
+                type $T = Int256Type;
+                $body
+            }
+            DataType::UInt8 => {
+                type $T = UInt8Type;
+                $body
+            }
+            DataType::UInt16 => {
+                type $T = UInt16Type;
+                $body
+            }
+            DataType::UInt32 => {
+                type $T = UInt32Type;
+                $body
+            }
+            DataType::UInt64 => {
+                type $T = UInt64Type;
+                $body
+            }
+            DataType::UInt128 => {
+                type $T = UInt128Type;
+                $body
+            }
+            DataType::UInt256 => {
+                type $T = UInt256Type;
+                $body
+            }
+            DataType::Float32 => {
+                type $T = Float32Type;
+                $body
+            }
+            DataType::Float64 => {
+                type $T = Float64Type;
+                $body
+            }
+            t => Err(ArrowError::CastError(format!(
+                "Casting from/to {:?} is not supported",
+                t
+            ))),
+        }
+    }};
+}
+
+fn is_numeric_or_wide(t: &DataType) -> bool {
+    DataType::is_numeric(t) || DataType::is_wide_integer(t)
+}
+
+/// Whether it is a cast between the numeric types which at least one is a wide
+/// integer, or from a wide integer to string
+fn is_wide_cast(from_type: &DataType, to_type: &DataType) -> bool {
+    use DataType::*;
+    (DataType::is_wide_integer(from_type)
+        && (is_numeric_or_wide(to_type) || matches!(to_type, Utf8 | LargeUtf8)))
+        || (DataType::is_wide_integer(to_type) && is_numeric_or_wide(from_type))
+}
+
+fn cast_wide_integer(array: &ArrayRef, to_type: &DataType) -> Result<ArrayRef> {
+    with_numeric_type!(array.data_type(), FROM => match to_type {
+        DataType::Utf8 => cast_display_to_string::<FROM, i32>(array),
+        DataType::LargeUtf8 => cast_display_to_string::<FROM, i64>(array),
+        _ => with_numeric_type!(to_type, TO => cast_numeric_arrays::<FROM, TO>(array)),
+    })
+}
+
+fn cast_display_to_string<FROM, OffsetSize>(array: &ArrayRef) -> Result<ArrayRef>
+where
+    FROM: ArrowPrimitiveType,
+    FROM::Native: std::fmt::Display,
+    OffsetSize: StringOffsetSizeTrait,
+{
+    let array = array
+        .as_any()
+        .downcast_ref::<PrimitiveArray<FROM>>()
+        .unwrap();
+    Ok(Arc::new(
+        array
+            .iter()
+            .map(|v| v.map(|v| v.to_string()))
+            .collect::<GenericStringArray<OffsetSize>>(),
+    ))
+}
+
+fn can_cast_decimal(from_type: &DataType, to_type: &DataType) -> bool {
+    use DataType::*;
+    match (from_type, to_type) {
+        (Decimal(_, _), Decimal(_, _)) => true,
+        (Decimal(_, _), Utf8 | LargeUtf8) => true,
+        (Decimal(_, _), t) | (t, Decimal(_, _)) => is_numeric_or_wide(t),
+        _ => false,
+    }
+}
+
+/// Cast decimals from or to the numeric types, or to other decimals or strings.
+/// The dropped digits are truncated, and the values out of the range of the
+/// target type are null, or an error if not `safe`.
+fn cast_decimal(
+    array: &ArrayRef,
+    to_type: &DataType,
+    cast_options: &CastOptions,
+) -> Result<ArrayRef> {
+    use DataType::*;
+    let overflow = |v: &dyn std::fmt::Display| {
+        ArrowError::CastError(format!("Can not cast {} to {:?}", v, to_type))
+    };
+    match (array.data_type(), to_type) {
+        (Decimal(_, _), Utf8) => {
+            let array = array.as_any().downcast_ref::<DecimalArray>().unwrap();
+            Ok(Arc::new(
+                (0..array.len())
+                    .map(|i| Some(array.value_as_string(i)).filter(|_| array.is_valid(i)))
+                    .collect::<StringArray>(),
+            ))
+        }
+        (Decimal(_, _), LargeUtf8) => {
+            let array = array.as_any().downcast_ref::<DecimalArray>().unwrap();
+            Ok(Arc::new(
+                (0..array.len())
+                    .map(|i| Some(array.value_as_string(i)).filter(|_| array.is_valid(i)))
+                    .collect::<LargeStringArray>(),
+            ))
+        }
+        (Decimal(_, _), Decimal(precision, scale)) => {
+            let array = array.as_any().downcast_ref::<DecimalArray>().unwrap();
+            let mut builder = DecimalBuilder::new(array.len(), *precision, *scale);
+            for i in 0..array.len() {
+                if array.is_null(i) {
+                    builder.append_null()?;
+                    continue;
+                }
+                let v = array.value_i256(i);
+                match rescale_decimal(v, array.scale(), *scale)
+                    .ok_or_else(|| overflow(&v))
+                    .and_then(|v| builder.append_i256(v))
+                {
+                    Err(_) if cast_options.safe => builder.append_null()?,
+                    r => r?,
+                }
+            }
+            Ok(Arc::new(builder.finish()))
+        }
+        (Decimal(_, _), _) => {
+            let array = array.as_any().downcast_ref::<DecimalArray>().unwrap();
+            with_numeric_type!(to_type, TO => {
+                let is_float = matches!(to_type, Float32 | Float64);
+                let divisor = i256::ONE.checked_mul_pow10(array.scale() as u32).unwrap();
+                let mut values = Vec::with_capacity(array.len());
+                for i in 0..array.len() {
+                    if array.is_null(i) {
+                        values.push(None);
+                        continue;
+                    }
+                    let v = array.value_i256(i);
+                    let to = if is_float {
+                        num::cast::cast(v.as_f64() / divisor.as_f64())
+                    } else {
+                        num::cast::cast(v.checked_div(divisor).unwrap())
+                    };
+                    match to {
+                        None if !cast_options.safe => return Err(overflow(&v)),
+                        to => values.push(to),
+                    }
+                }
+                let array: PrimitiveArray<TO> = values.into_iter().collect();
+                Ok(Arc::new(array) as ArrayRef)
+            })
+        }
+        (_, Decimal(precision, scale)) => {
+            let is_float = matches!(array.data_type(), Float32 | Float64);
+            let mut builder = DecimalBuilder::new(array.len(), *precision, *scale);
+            with_numeric_type!(array.data_type(), FROM => {
+                let array = array
+                    .as_any()
+                    .downcast_ref::<PrimitiveArray<FROM>>()
+                    .unwrap();
+                for v in array.iter() {
+                    let v = match v {
+                        Some(v) => v,
+                        None => {
+                            builder.append_null()?;
+                            continue;
+                        }
+                    };
+                    let to = if is_float {
+                        v.to_f64()
+                            .map(|v| (v * 10f64.powi(*scale as i32)).round())
+                            .and_then(num::cast::cast::<f64, i256>)
+                    } else {
+                        num::cast::cast::<_, i256>(v)
+                            .and_then(|v| v.checked_mul_pow10(*scale as u32))
+                    };
+                    match to
+                        .ok_or_else(|| overflow(&v))
+                        .and_then(|v| builder.append_i256(v))
+                    {
+                        Err(_) if cast_options.safe => builder.append_null()?,
+                        r => r?,
+                    }
+                }
+                Ok(())
+            })?;
+            Ok(Arc::new(builder.finish()))
+        }
+        _ => unreachable!(),
+    }
+}
+
 /// Natural cast between numeric types
 fn numeric_cast<T, R>(from: &PrimitiveArray<T>) -> PrimitiveArray<R>
 where
-    T: ArrowNumericType,
-    R: ArrowNumericType,
+    T: ArrowPrimitiveType,
+    R: ArrowPrimitiveType,
     T::Native: num::NumCast,
     R::Native: num::NumCast,
 {
@@ -27,8 +27,8 @@ use crate::buffer::{bitwise_bin_op_helper, buffer_unary_not, Buffer, MutableBuff
 use crate::compute::binary_boolean_kernel;
 use crate::compute::util::combine_option_bitmap;
 use crate::datatypes::{
-    ArrowNumericType, DataType, Float32Type, Float64Type, Int16Type, Int32Type,
-    Int64Type, Int8Type, UInt16Type, UInt32Type, UInt64Type, UInt8Type,
+    ArrowNumericType, ArrowPrimitiveType, DataType, Float32Type, Float64Type, Int16Type,
+    Int32Type, Int64Type, Int8Type, UInt16Type, UInt32Type, UInt64Type, UInt8Type,
 };
 use crate::error::{ArrowError, Result};
 use crate::util::bit_util;
@@ -208,7 +208,7 @@ pub fn no_simd_compare_op<T, F>(
     op: F,
 ) -> Result<BooleanArray>
 where
-    T: ArrowNumericType,
+    T: ArrowPrimitiveType,
     F: Fn(T::Native, T::Native) -> bool,
 {
     compare_op_primitive!(left, right, op)
@@ -222,7 +222,7 @@ pub fn no_simd_compare_op_scalar<T, F>(
     op: F,
 ) -> Result<BooleanArray>
 where
-    T: ArrowNumericType,
+    T: ArrowPrimitiveType,
     F: Fn(T::Native, T::Native) -> bool,
 {
     compare_op_scalar_primitive!(left, right, op)
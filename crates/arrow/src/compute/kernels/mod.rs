@@ -34,5 +34,6 @@ pub mod sort;
 pub mod substring;
 pub mod take;
 pub mod temporal;
+pub mod wide;
 pub mod window;
 pub mod zip;
@@ -195,6 +195,19 @@ pub fn sort_to_indices(
         DataType::UInt64 => {
             sort_primitive::<UInt64Type, _>(values, v, n, cmp, &options, limit)
         }
+        DataType::Int128 => {
+            sort_primitive::<Int128Type, _>(values, v, n, cmp, &options, limit)
+        }
+        DataType::UInt128 => {
+            sort_primitive::<UInt128Type, _>(values, v, n, cmp, &options, limit)
+        }
+        DataType::Int256 => {
+            sort_primitive::<Int256Type, _>(values, v, n, cmp, &options, limit)
+        }
+        DataType::UInt256 => {
+            sort_primitive::<UInt256Type, _>(values, v, n, cmp, &options, limit)
+        }
+        DataType::Decimal(_, _) => sort_decimal(values, v, n, &options, limit),
         DataType::Timestamp32(_) => {
             sort_primitive::<Timestamp32Type, _>(values, v, n, cmp, &options, limit)
         }
@@ -531,6 +544,23 @@ where
     sort_primitive_inner(values, null_indices, cmp, options, limit, valids)
 }
 
+fn sort_decimal(
+    values: &ArrayRef,
+    value_indices: Vec<u32>,
+    null_indices: Vec<u32>,
+    options: &SortOptions,
+    limit: Option<usize>,
+) -> UInt32Array {
+    let valids = {
+        let values = values.as_any().downcast_ref::<DecimalArray>().unwrap();
+        value_indices
+            .into_iter()
+            .map(|index| (index, values.value_i256(index as usize)))
+            .collect::<Vec<(u32, i256)>>()
+    };
+    sort_primitive_inner(values, null_indices, cmp, options, limit, valids)
+}
+
 // sort is instantiated a lot so we only compile this inner version for each native type
 fn sort_primitive_inner<T, F>(
     values: &ArrayRef,
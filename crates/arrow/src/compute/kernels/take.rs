@@ -139,6 +139,10 @@ where
         DataType::UInt16 => downcast_take!(UInt16Type, values, indices),
         DataType::UInt32 => downcast_take!(UInt32Type, values, indices),
         DataType::UInt64 => downcast_take!(UInt64Type, values, indices),
+        DataType::Int128 => downcast_take!(Int128Type, values, indices),
+        DataType::UInt128 => downcast_take!(UInt128Type, values, indices),
+        DataType::Int256 => downcast_take!(Int256Type, values, indices),
+        DataType::UInt256 => downcast_take!(UInt256Type, values, indices),
         DataType::Float32 => downcast_take!(Float32Type, values, indices),
         DataType::Float64 => downcast_take!(Float64Type, values, indices),
         DataType::Date32 => downcast_take!(Date32Type, values, indices),
@@ -284,6 +288,10 @@ DataType::Timestamp32(_) => {
                 .unwrap();
             Ok(Arc::new(take_fixed_size_binary(values, indices)?))
         }
+        DataType::Decimal(_, _) => {
+            let values = values.as_any().downcast_ref::<DecimalArray>().unwrap();
+            Ok(Arc::new(take_decimal(values, indices)?))
+        }
         DataType::Null => {
             // Take applied to a null array produces a null array.
             if values.len() >= indices.len() {
@@ -884,6 +892,33 @@ where
     FixedSizeBinaryArray::try_from_sparse_iter(array_iter)
 }
 
+/// `take` implementation for decimal arrays of any width
+fn take_decimal<IndexType>(
+    values: &DecimalArray,
+    indices: &PrimitiveArray<IndexType>,
+) -> Result<DecimalArray>
+where
+    IndexType: ArrowNumericType,
+    IndexType::Native: ToPrimitive,
+{
+    let mut builder =
+        DecimalBuilder::new(indices.len(), values.precision(), values.scale());
+    for idx in indices.iter() {
+        match idx {
+            Some(idx) => {
+                let idx = maybe_usize::<IndexType::Native>(idx)?;
+                if values.is_valid(idx) {
+                    builder.append_i256(values.value_i256(idx))?;
+                } else {
+                    builder.append_null()?;
+                }
+            }
+            None => builder.append_null()?,
+        }
+    }
+    Ok(builder.finish())
+}
+
 /// `take` implementation for dictionary arrays
 ///
 /// applies `take` to the keys of the dictionary array and returns a new dictionary array
@@ -0,0 +1,397 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Defines arithmetic and comparison kernels for the wide integers and the
+//! decimals, which are not `ArrowNumericType`s so can not use the SIMD ones.
+//!
+//! Like ClickHouse, the arithmetic of the wide integers wraps around on
+//! overflow, while the one of the decimals fails.
+
+use num::bigint::BigInt;
+use num::traits::{CheckedDiv, CheckedRem, WrappingAdd, WrappingMul, WrappingSub, Zero};
+
+use crate::array::*;
+use crate::buffer::Buffer;
+use crate::compute::util::combine_option_bitmap;
+use crate::datatypes::{i256, ArrowPrimitiveType};
+use crate::error::{ArrowError, Result};
+
+/// Helper function to perform a fallible math lambda function on values from two
+/// arrays, the lambda is not called on the null slots.
+fn try_math_op<T, F>(
+    left: &PrimitiveArray<T>,
+    right: &PrimitiveArray<T>,
+    op: F,
+) -> Result<PrimitiveArray<T>>
+where
+    T: ArrowPrimitiveType,
+    F: Fn(T::Native, T::Native) -> Result<T::Native>,
+{
+    if left.len() != right.len() {
+        return Err(ArrowError::ComputeError(
+            "Cannot perform math operation on arrays of different length".to_string(),
+        ));
+    }
+
+    let null_bit_buffer =
+        combine_option_bitmap(left.data_ref(), right.data_ref(), left.len())?;
+
+    let values = (0..left.len())
+        .map(|i| {
+            if left.is_null(i) || right.is_null(i) {
+                Ok(T::default_value())
+            } else {
+                op(left.value(i), right.value(i))
+            }
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let data = unsafe {
+        ArrayData::new_unchecked(
+            T::DATA_TYPE,
+            left.len(),
+            None,
+            null_bit_buffer,
+            0,
+            vec![Buffer::from_slice_ref(&values)],
+            vec![],
+        )
+    };
+    Ok(PrimitiveArray::<T>::from(data))
+}
+
+/// Perform `left + right` operation on two arrays, which wraps around on overflow.
+pub fn add_wrapping<T>(
+    left: &PrimitiveArray<T>,
+    right: &PrimitiveArray<T>,
+) -> Result<PrimitiveArray<T>>
+where
+    T: ArrowPrimitiveType,
+    T::Native: WrappingAdd,
+{
+    try_math_op(left, right, |a, b| Ok(a.wrapping_add(&b)))
+}
+
+/// Perform `left - right` operation on two arrays, which wraps around on overflow.
+pub fn subtract_wrapping<T>(
+    left: &PrimitiveArray<T>,
+    right: &PrimitiveArray<T>,
+) -> Result<PrimitiveArray<T>>
+where
+    T: ArrowPrimitiveType,
+    T::Native: WrappingSub,
+{
+    try_math_op(left, right, |a, b| Ok(a.wrapping_sub(&b)))
+}
+
+/// Perform `left * right` operation on two arrays, which wraps around on overflow.
+pub fn multiply_wrapping<T>(
+    left: &PrimitiveArray<T>,
+    right: &PrimitiveArray<T>,
+) -> Result<PrimitiveArray<T>>
+where
+    T: ArrowPrimitiveType,
+    T::Native: WrappingMul,
+{
+    try_math_op(left, right, |a, b| Ok(a.wrapping_mul(&b)))
+}
+
+/// Perform `left / right` operation on two arrays. If any right hand value is zero
+/// then the result of this operation will be `Err(ArrowError::DivideByZero)`.
+pub fn divide_checked<T>(
+    left: &PrimitiveArray<T>,
+    right: &PrimitiveArray<T>,
+) -> Result<PrimitiveArray<T>>
+where
+    T: ArrowPrimitiveType,
+    T::Native: CheckedDiv + Zero,
+{
+    try_math_op(left, right, |a, b| {
+        if b.is_zero() {
+            return Err(ArrowError::DivideByZero);
+        }
+        a.checked_div(&b).ok_or_else(|| {
+            ArrowError::ComputeError(format!("Overflow happened on: {:?} / {:?}", a, b))
+        })
+    })
+}
+
+/// Perform `left % right` operation on two arrays. If any right hand value is zero
+/// then the result of this operation will be `Err(ArrowError::DivideByZero)`.
+pub fn modulus_checked<T>(
+    left: &PrimitiveArray<T>,
+    right: &PrimitiveArray<T>,
+) -> Result<PrimitiveArray<T>>
+where
+    T: ArrowPrimitiveType,
+    T::Native: CheckedRem + Zero,
+{
+    try_math_op(left, right, |a, b| {
+        if b.is_zero() {
+            return Err(ArrowError::DivideByZero);
+        }
+        a.checked_rem(&b).ok_or_else(|| {
+            ArrowError::ComputeError(format!("Overflow happened on: {:?} % {:?}", a, b))
+        })
+    })
+}
+
+/// Rescales the decimal value `v` from the scale `from` to the scale `to`, the
+/// dropped digits are truncated. None if the value overflows.
+pub fn rescale_decimal(v: i256, from: usize, to: usize) -> Option<i256> {
+    if to >= from {
+        v.checked_mul_pow10((to - from) as u32)
+    } else {
+        v.checked_div(i256::ONE.checked_mul_pow10((from - to) as u32)?)
+    }
+}
+
+fn decimal_overflow(op: &str) -> ArrowError {
+    ArrowError::ComputeError(format!("Decimal math overflow on {}", op))
+}
+
+/// Helper function to perform a fallible math lambda function on values from two
+/// decimal arrays, the result is in `Decimal(precision, scale)`.
+fn try_decimal_op<F>(
+    left: &DecimalArray,
+    right: &DecimalArray,
+    precision: usize,
+    scale: usize,
+    op: F,
+) -> Result<DecimalArray>
+where
+    F: Fn(i256, i256) -> Result<i256>,
+{
+    if left.len() != right.len() {
+        return Err(ArrowError::ComputeError(
+            "Cannot perform math operation on arrays of different length".to_string(),
+        ));
+    }
+
+    let mut builder = DecimalBuilder::new(left.len(), precision, scale);
+    for i in 0..left.len() {
+        if left.is_null(i) || right.is_null(i) {
+            builder.append_null()?;
+        } else {
+            let v = op(left.value_i256(i), right.value_i256(i))?;
+            builder.append_i256(v)?;
+        }
+    }
+    Ok(builder.finish())
+}
+
+/// Perform `left + right` operation on two decimal arrays, the result is in
+/// `Decimal(precision, scale)`, which `scale` is usually the larger one of the
+/// operands. Errs if the result overflows.
+pub fn add_decimal(
+    left: &DecimalArray,
+    right: &DecimalArray,
+    precision: usize,
+    scale: usize,
+) -> Result<DecimalArray> {
+    let (ls, rs) = (left.scale(), right.scale());
+    try_decimal_op(left, right, precision, scale, |a, b| {
+        rescale_decimal(a, ls, scale)
+            .zip(rescale_decimal(b, rs, scale))
+            .and_then(|(a, b)| a.checked_add(b))
+            .ok_or_else(|| decimal_overflow("plus"))
+    })
+}
+
+/// Perform `left - right` operation on two decimal arrays, the result is in
+/// `Decimal(precision, scale)`, which `scale` is usually the larger one of the
+/// operands. Errs if the result overflows.
+pub fn subtract_decimal(
+    left: &DecimalArray,
+    right: &DecimalArray,
+    precision: usize,
+    scale: usize,
+) -> Result<DecimalArray> {
+    let (ls, rs) = (left.scale(), right.scale());
+    try_decimal_op(left, right, precision, scale, |a, b| {
+        rescale_decimal(a, ls, scale)
+            .zip(rescale_decimal(b, rs, scale))
+            .and_then(|(a, b)| a.checked_sub(b))
+            .ok_or_else(|| decimal_overflow("minus"))
+    })
+}
+
+/// Perform `left * right` operation on two decimal arrays, the result is in
+/// `Decimal(precision, scale)`, which `scale` is usually the sum of the ones of
+/// the operands. Errs if the result overflows.
+pub fn multiply_decimal(
+    left: &DecimalArray,
+    right: &DecimalArray,
+    precision: usize,
+    scale: usize,
+) -> Result<DecimalArray> {
+    let product_scale = left.scale() + right.scale();
+    try_decimal_op(left, right, precision, scale, |a, b| {
+        let product = a.to_bigint() * b.to_bigint();
+        let product = if scale >= product_scale {
+            product * BigInt::from(10u8).pow((scale - product_scale) as u32)
+        } else {
+            product / BigInt::from(10u8).pow((product_scale - scale) as u32)
+        };
+        i256::from_bigint(&product).ok_or_else(|| decimal_overflow("multiply"))
+    })
+}
+
+/// Perform `left / right` operation on two decimal arrays, the result is in
+/// `Decimal(precision, scale)`, which `scale` is usually the one of the left
+/// operand. If any right hand value is zero then the result of this operation
+/// will be `Err(ArrowError::DivideByZero)`.
+pub fn divide_decimal(
+    left: &DecimalArray,
+    right: &DecimalArray,
+    precision: usize,
+    scale: usize,
+) -> Result<DecimalArray> {
+    let (ls, rs) = (left.scale(), right.scale());
+    try_decimal_op(left, right, precision, scale, |a, b| {
+        if b.is_zero() {
+            return Err(ArrowError::DivideByZero);
+        }
+        //NOTE (a / 10^ls) / (b / 10^rs) * 10^scale
+        let exp = scale + rs;
+        let dividend = if exp >= ls {
+            a.to_bigint() * BigInt::from(10u8).pow((exp - ls) as u32)
+        } else {
+            a.to_bigint() / BigInt::from(10u8).pow((ls - exp) as u32)
+        };
+        i256::from_bigint(&(dividend / b.to_bigint()))
+            .ok_or_else(|| decimal_overflow("divide"))
+    })
+}
+
+/// Evaluate `op(left, right)` for two decimal arrays, the values are compared in
+/// the larger scale of them.
+pub fn compare_decimal<F>(
+    left: &DecimalArray,
+    right: &DecimalArray,
+    op: F,
+) -> Result<BooleanArray>
+where
+    F: Fn(i256, i256) -> bool,
+{
+    if left.len() != right.len() {
+        return Err(ArrowError::ComputeError(
+            "Cannot perform comparison operation on arrays of different length"
+                .to_string(),
+        ));
+    }
+
+    let (ls, rs) = (left.scale(), right.scale());
+    let scale = ls.max(rs);
+    (0..left.len())
+        .map(|i| {
+            if left.is_null(i) || right.is_null(i) {
+                return Ok(None);
+            }
+            let a = rescale_decimal(left.value_i256(i), ls, scale);
+            let b = rescale_decimal(right.value_i256(i), rs, scale);
+            match (a, b) {
+                (Some(a), Some(b)) => Ok(Some(op(a, b))),
+                _ => Err(decimal_overflow("compare")),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datatypes::{u256, UInt256Type};
+
+    fn decimals(vs: &[Option<i128>], precision: usize, scale: usize) -> DecimalArray {
+        let mut builder = DecimalBuilder::new(vs.len(), precision, scale);
+        for v in vs {
+            match v {
+                Some(v) => builder.append_value(*v).unwrap(),
+                None => builder.append_null().unwrap(),
+            }
+        }
+        builder.finish()
+    }
+
+    #[test]
+    fn test_wide_integer_arith() {
+        let a = Int256Array::from(vec![Some(i256::MAX), None, Some(i256::from(-7i64))]);
+        let b = Int256Array::from(vec![
+            Some(i256::ONE),
+            Some(i256::ONE),
+            Some(i256::from(2i64)),
+        ]);
+        let c = add_wrapping(&a, &b).unwrap();
+        assert_eq!(c.value(0), i256::MIN);
+        assert!(c.is_null(1));
+        assert_eq!(c.value(2), i256::from(-5i64));
+        let c = divide_checked(&a, &b).unwrap();
+        assert_eq!(c.value(2), i256::from(-3i64));
+        let c = modulus_checked(&a, &b).unwrap();
+        assert_eq!(c.value(2), i256::from(-1i64));
+
+        let a = PrimitiveArray::<UInt256Type>::from(vec![u256::ZERO]);
+        let c = subtract_wrapping(&a, &PrimitiveArray::from(vec![u256::ONE])).unwrap();
+        assert_eq!(c.value(0), u256::MAX);
+        assert!(matches!(
+            divide_checked(&a, &PrimitiveArray::from(vec![u256::ZERO])),
+            Err(ArrowError::DivideByZero)
+        ));
+
+        let a = UInt128Array::from(vec![u128::MAX]);
+        let c = multiply_wrapping(&a, &UInt128Array::from(vec![2])).unwrap();
+        assert_eq!(c.value(0), u128::MAX - 1);
+    }
+
+    #[test]
+    fn test_decimal_arith() {
+        let a = decimals(&[Some(12345), None, Some(-100)], 9, 2);
+        let b = decimals(&[Some(1000), Some(1), Some(3000)], 18, 3);
+        let c = add_decimal(&a, &b, 18, 3).unwrap();
+        assert_eq!(c.value_as_string(0), "124.450");
+        assert!(c.is_null(1));
+        assert_eq!(c.value_as_string(2), "2.000");
+        let c = subtract_decimal(&a, &b, 18, 3).unwrap();
+        assert_eq!(c.value_as_string(0), "122.450");
+        let c = multiply_decimal(&a, &b, 18, 5).unwrap();
+        assert_eq!(c.value_as_string(0), "123.45000");
+        assert_eq!(c.value_as_string(2), "-3.00000");
+        let c = divide_decimal(&a, &b, 9, 2).unwrap();
+        assert_eq!(c.value_as_string(0), "123.45");
+        assert_eq!(c.value_as_string(2), "-0.33");
+
+        let a = decimals(&[Some(999_999_999)], 9, 0);
+        assert!(add_decimal(&a, &a, 9, 0).is_err());
+        assert!(add_decimal(&a, &a, 18, 0).is_ok());
+        let z = decimals(&[Some(0)], 9, 0);
+        assert!(matches!(
+            divide_decimal(&a, &z, 9, 0),
+            Err(ArrowError::DivideByZero)
+        ));
+    }
+
+    #[test]
+    fn test_decimal_compare() {
+        let a = decimals(&[Some(150), None, Some(-1)], 9, 2);
+        let b = decimals(&[Some(15), Some(1), Some(0)], 38, 1);
+        let c = compare_decimal(&a, &b, |a, b| a == b).unwrap();
+        assert_eq!(c, BooleanArray::from(vec![Some(true), None, Some(false)]));
+        let c = compare_decimal(&a, &b, |a, b| a < b).unwrap();
+        assert_eq!(c, BooleanArray::from(vec![Some(false), None, Some(true)]));
+    }
+}
@@ -63,6 +63,14 @@ pub enum DataType {
     UInt32,
     /// An unsigned 64-bit integer.
     UInt64,
+    /// A signed 128-bit integer.
+    Int128,
+    /// An unsigned 128-bit integer.
+    UInt128,
+    /// A signed 256-bit integer.
+    Int256,
+    /// An unsigned 256-bit integer.
+    UInt256,
     /// A 16-bit floating point number.
     Float16,
     /// A 32-bit floating point number.
@@ -185,6 +193,17 @@ pub enum IntervalUnit {
     DayTime,
 }
 
+/// The byte width of the values of `Decimal` of the precision, which is the
+/// width of the smallest integer holding all the values, like ClickHouse.
+pub fn decimal_byte_width(precision: usize) -> usize {
+    match precision {
+        0..=9 => 4,
+        10..=18 => 8,
+        19..=38 => 16,
+        _ => 32,
+    }
+}
+
 impl fmt::Display for DataType {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{:?}", self)
@@ -329,6 +348,8 @@ impl DataType {
                             Some(16) => Ok(DataType::Int16),
                             Some(32) => Ok(DataType::Int32),
                             Some(64) => Ok(DataType::Int64),
+                            Some(128) => Ok(DataType::Int128),
+                            Some(256) => Ok(DataType::Int256),
                             _ => Err(ArrowError::ParseError(
                                 "int bitWidth missing or invalid".to_string(),
                             )),
@@ -343,6 +364,8 @@ impl DataType {
                             Some(16) => Ok(DataType::UInt16),
                             Some(32) => Ok(DataType::UInt32),
                             Some(64) => Ok(DataType::UInt64),
+                            Some(128) => Ok(DataType::UInt128),
+                            Some(256) => Ok(DataType::UInt256),
                             _ => Err(ArrowError::ParseError(
                                 "int bitWidth missing or invalid".to_string(),
                             )),
@@ -415,6 +438,14 @@ impl DataType {
             DataType::UInt16 => json!({"name": "int", "bitWidth": 16, "isSigned": false}),
             DataType::UInt32 => json!({"name": "int", "bitWidth": 32, "isSigned": false}),
             DataType::UInt64 => json!({"name": "int", "bitWidth": 64, "isSigned": false}),
+            DataType::Int128 => json!({"name": "int", "bitWidth": 128, "isSigned": true}),
+            DataType::UInt128 => {
+                json!({"name": "int", "bitWidth": 128, "isSigned": false})
+            }
+            DataType::Int256 => json!({"name": "int", "bitWidth": 256, "isSigned": true}),
+            DataType::UInt256 => {
+                json!({"name": "int", "bitWidth": 256, "isSigned": false})
+            }
             DataType::Float16 => json!({"name": "floatingpoint", "precision": "HALF"}),
             DataType::Float32 => json!({"name": "floatingpoint", "precision": "SINGLE"}),
             DataType::Float64 => json!({"name": "floatingpoint", "precision": "DOUBLE"}),
@@ -527,6 +558,12 @@ impl DataType {
         )
     }
 
+    /// Returns true if this type is one of the integers wider than 64 bits.
+    pub fn is_wide_integer(t: &DataType) -> bool {
+        use DataType::*;
+        matches!(t, Int128 | UInt128 | Int256 | UInt256)
+    }
+
     /// Compares the datatype with another, ignoring nested field names
     /// and metadata.
     pub(crate) fn equals_datatype(&self, other: &DataType) -> bool {
@@ -518,6 +518,10 @@ impl Field {
             | DataType::UInt16
             | DataType::UInt32
             | DataType::UInt64
+            | DataType::Int128
+            | DataType::UInt128
+            | DataType::Int256
+            | DataType::UInt256
             | DataType::Float16
             | DataType::Float32
             | DataType::Float64
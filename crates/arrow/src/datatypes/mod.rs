@@ -26,6 +26,8 @@ use std::sync::Arc;
 
 mod native;
 pub use native::*;
+mod wide;
+pub use wide::*;
 mod field;
 pub use field::*;
 mod schema;
@@ -15,7 +15,7 @@
 // specific language governing permissions and limitations
 // under the License.
 
-use super::{ArrowPrimitiveType, DataType, IntervalUnit, TimeUnit};
+use super::{i256, u256, ArrowPrimitiveType, DataType, IntervalUnit, TimeUnit};
 
 // BooleanType is special: its bit-width is not the size of the primitive type, and its `index`
 // operation assumes bit-packing.
@@ -46,6 +46,10 @@ make_type!(UInt8Type, u8, DataType::UInt8);
 make_type!(UInt16Type, u16, DataType::UInt16);
 make_type!(UInt32Type, u32, DataType::UInt32);
 make_type!(UInt64Type, u64, DataType::UInt64);
+make_type!(Int128Type, i128, DataType::Int128);
+make_type!(UInt128Type, u128, DataType::UInt128);
+make_type!(Int256Type, i256, DataType::Int256);
+make_type!(UInt256Type, u256, DataType::UInt256);
 make_type!(Float32Type, f32, DataType::Float32);
 make_type!(Float64Type, f64, DataType::Float64);
 make_type!(
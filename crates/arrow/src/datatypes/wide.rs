@@ -0,0 +1,495 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! The 256-bit integers, which are the values of `Int256`, `UInt256` and the
+//! `Decimal` of the precision larger than 38.
+//!
+//! The values are kept as the little endian 64-bit words, so they have the
+//! same in-memory representation as the ones of ClickHouse. Like the primitive
+//! integers, the arithmetic operators wrap around on overflow, and the division
+//! panics on zero.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Rem, Sub};
+use std::str::FromStr;
+
+use num::bigint::{BigInt, Sign};
+use num::traits::{
+    CheckedDiv, CheckedRem, One, WrappingAdd, WrappingMul, WrappingSub, Zero,
+};
+use num::{NumCast, ToPrimitive};
+use serde_json::Value;
+
+use super::{ArrowNativeType, JsonSerializable};
+use crate::error::ArrowError;
+
+macro_rules! make_wide_int {
+    ($name:ident, $signed:expr, $half:ty) => {
+        #[allow(non_camel_case_types)]
+        #[derive(Copy, Clone, Default, PartialEq, Eq, Hash)]
+        #[repr(C)]
+        pub struct $name([u64; 4]);
+
+        impl $name {
+            pub const ZERO: Self = $name([0; 4]);
+            pub const ONE: Self = $name([1, 0, 0, 0]);
+            pub const MIN: Self = if $signed {
+                $name([0, 0, 0, 1 << 63])
+            } else {
+                $name([0; 4])
+            };
+            pub const MAX: Self = if $signed {
+                $name([u64::MAX, u64::MAX, u64::MAX, u64::MAX >> 1])
+            } else {
+                $name([u64::MAX; 4])
+            };
+
+            pub fn from_le_bytes(bs: [u8; 32]) -> Self {
+                let mut ws = [0u64; 4];
+                for (i, w) in ws.iter_mut().enumerate() {
+                    let mut b = [0u8; 8];
+                    b.copy_from_slice(&bs[i * 8..i * 8 + 8]);
+                    *w = u64::from_le_bytes(b);
+                }
+                $name(ws)
+            }
+
+            pub fn to_le_bytes(self) -> [u8; 32] {
+                let mut bs = [0u8; 32];
+                for (i, w) in self.0.iter().enumerate() {
+                    bs[i * 8..i * 8 + 8].copy_from_slice(&w.to_le_bytes());
+                }
+                bs
+            }
+
+            /// The value of the little endian bytes, which are sign extended for
+            /// the signed type if less than 32
+            pub fn from_le_slice(bs: &[u8]) -> Self {
+                let neg = $signed && bs.last().map_or(false, |b| *b & 0x80 != 0);
+                let mut buf = [if neg { 0xFF } else { 0 }; 32];
+                let n = bs.len().min(32);
+                buf[..n].copy_from_slice(&bs[..n]);
+                Self::from_le_bytes(buf)
+            }
+
+            #[inline]
+            pub fn is_negative(self) -> bool {
+                $signed && (self.0[3] as i64) < 0
+            }
+
+            pub fn wrapping_add(self, rhs: Self) -> Self {
+                let mut ws = [0u64; 4];
+                let mut carry = false;
+                for i in 0..4 {
+                    let (s, c1) = self.0[i].overflowing_add(rhs.0[i]);
+                    let (s, c2) = s.overflowing_add(carry as u64);
+                    ws[i] = s;
+                    carry = c1 || c2;
+                }
+                $name(ws)
+            }
+
+            pub fn wrapping_sub(self, rhs: Self) -> Self {
+                let mut ws = [0u64; 4];
+                let mut borrow = false;
+                for i in 0..4 {
+                    let (s, b1) = self.0[i].overflowing_sub(rhs.0[i]);
+                    let (s, b2) = s.overflowing_sub(borrow as u64);
+                    ws[i] = s;
+                    borrow = b1 || b2;
+                }
+                $name(ws)
+            }
+
+            pub fn wrapping_mul(self, rhs: Self) -> Self {
+                //NOTE the low words of the product are the same for the two's
+                //     complement, so the signed one is multiplied in the same way
+                let mut ws = [0u64; 4];
+                for i in 0..4 {
+                    let mut carry = 0u128;
+                    for j in 0..4 - i {
+                        let t = self.0[i] as u128 * rhs.0[j] as u128
+                            + ws[i + j] as u128
+                            + carry;
+                        ws[i + j] = t as u64;
+                        carry = t >> 64;
+                    }
+                }
+                $name(ws)
+            }
+
+            pub fn wrapping_neg(self) -> Self {
+                Self::ZERO.wrapping_sub(self)
+            }
+
+            /// None if the sum overflows
+            pub fn checked_add(self, rhs: Self) -> Option<Self> {
+                Self::from_bigint(&(self.to_bigint() + rhs.to_bigint()))
+            }
+
+            /// None if the difference overflows
+            pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+                Self::from_bigint(&(self.to_bigint() - rhs.to_bigint()))
+            }
+
+            /// None if the product overflows
+            pub fn checked_mul(self, rhs: Self) -> Option<Self> {
+                Self::from_bigint(&(self.to_bigint() * rhs.to_bigint()))
+            }
+
+            /// None if rhs is zero, or the quotient overflows
+            pub fn checked_div(self, rhs: Self) -> Option<Self> {
+                if rhs == Self::ZERO {
+                    return None;
+                }
+                Self::from_bigint(&(self.to_bigint() / rhs.to_bigint()))
+            }
+
+            /// None if rhs is zero
+            pub fn checked_rem(self, rhs: Self) -> Option<Self> {
+                if rhs == Self::ZERO {
+                    return None;
+                }
+                Self::from_bigint(&(self.to_bigint() % rhs.to_bigint()))
+            }
+
+            /// The value multiplied by 10^exp, None if overflows
+            pub fn checked_mul_pow10(self, exp: u32) -> Option<Self> {
+                Self::from_bigint(&(self.to_bigint() * BigInt::from(10u8).pow(exp)))
+            }
+
+            pub fn to_bigint(self) -> BigInt {
+                let bs = self.to_le_bytes();
+                if self.is_negative() {
+                    BigInt::from_signed_bytes_le(&bs)
+                } else {
+                    BigInt::from_bytes_le(Sign::Plus, &bs)
+                }
+            }
+
+            /// None if the value is out of the range of the type
+            pub fn from_bigint(v: &BigInt) -> Option<Self> {
+                if *v < Self::MIN.to_bigint() || *v > Self::MAX.to_bigint() {
+                    return None;
+                }
+                Some(Self::from_le_slice(&v.to_signed_bytes_le()))
+            }
+
+            pub fn from_half(v: $half) -> Self {
+                Self::from_le_slice(&v.to_le_bytes())
+            }
+
+            /// None if the value is out of the range of the half type
+            pub fn to_half(self) -> Option<$half> {
+                let bs = self.to_le_bytes();
+                let v = Self::from_le_slice(&bs[..16]);
+                if v != self {
+                    return None;
+                }
+                let mut b = [0u8; 16];
+                b.copy_from_slice(&bs[..16]);
+                Some(<$half>::from_le_bytes(b))
+            }
+
+            pub fn as_f64(self) -> f64 {
+                self.to_bigint().to_f64().unwrap_or(f64::NAN)
+            }
+        }
+
+        impl Ord for $name {
+            fn cmp(&self, other: &Self) -> Ordering {
+                match (self.is_negative(), other.is_negative()) {
+                    (true, false) => Ordering::Less,
+                    (false, true) => Ordering::Greater,
+                    //NOTE the words of the same sign are ordered as unsigned
+                    _ => self.0.iter().rev().cmp(other.0.iter().rev()),
+                }
+            }
+        }
+
+        impl PartialOrd for $name {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{}", self.to_bigint())
+            }
+        }
+
+        impl fmt::Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{}", self)
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = ArrowError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let err = || {
+                    ArrowError::ParseError(format!(
+                        "can not parse {} as {}",
+                        s,
+                        stringify!($name)
+                    ))
+                };
+                let v = BigInt::from_str(s).map_err(|_| err())?;
+                Self::from_bigint(&v).ok_or_else(err)
+            }
+        }
+
+        impl From<$half> for $name {
+            fn from(v: $half) -> Self {
+                Self::from_half(v)
+            }
+        }
+
+        impl Add for $name {
+            type Output = Self;
+
+            #[inline]
+            fn add(self, rhs: Self) -> Self {
+                self.wrapping_add(rhs)
+            }
+        }
+
+        impl Sub for $name {
+            type Output = Self;
+
+            #[inline]
+            fn sub(self, rhs: Self) -> Self {
+                self.wrapping_sub(rhs)
+            }
+        }
+
+        impl Mul for $name {
+            type Output = Self;
+
+            #[inline]
+            fn mul(self, rhs: Self) -> Self {
+                self.wrapping_mul(rhs)
+            }
+        }
+
+        impl Div for $name {
+            type Output = Self;
+
+            fn div(self, rhs: Self) -> Self {
+                self.checked_div(rhs).expect("attempt to divide by zero")
+            }
+        }
+
+        impl Rem for $name {
+            type Output = Self;
+
+            fn rem(self, rhs: Self) -> Self {
+                self.checked_rem(rhs)
+                    .expect("attempt to calculate the remainder with a divisor of zero")
+            }
+        }
+
+        impl WrappingAdd for $name {
+            fn wrapping_add(&self, v: &Self) -> Self {
+                $name::wrapping_add(*self, *v)
+            }
+        }
+
+        impl WrappingSub for $name {
+            fn wrapping_sub(&self, v: &Self) -> Self {
+                $name::wrapping_sub(*self, *v)
+            }
+        }
+
+        impl WrappingMul for $name {
+            fn wrapping_mul(&self, v: &Self) -> Self {
+                $name::wrapping_mul(*self, *v)
+            }
+        }
+
+        impl CheckedDiv for $name {
+            fn checked_div(&self, v: &Self) -> Option<Self> {
+                $name::checked_div(*self, *v)
+            }
+        }
+
+        impl CheckedRem for $name {
+            fn checked_rem(&self, v: &Self) -> Option<Self> {
+                $name::checked_rem(*self, *v)
+            }
+        }
+
+        impl Zero for $name {
+            fn zero() -> Self {
+                Self::ZERO
+            }
+
+            fn is_zero(&self) -> bool {
+                *self == Self::ZERO
+            }
+        }
+
+        impl One for $name {
+            fn one() -> Self {
+                Self::ONE
+            }
+        }
+
+        impl ToPrimitive for $name {
+            fn to_i64(&self) -> Option<i64> {
+                self.to_bigint().to_i64()
+            }
+
+            fn to_u64(&self) -> Option<u64> {
+                self.to_bigint().to_u64()
+            }
+
+            fn to_i128(&self) -> Option<i128> {
+                self.to_bigint().to_i128()
+            }
+
+            fn to_u128(&self) -> Option<u128> {
+                self.to_bigint().to_u128()
+            }
+
+            fn to_f64(&self) -> Option<f64> {
+                Some(self.as_f64())
+            }
+        }
+
+        impl NumCast for $name {
+            fn from<T: ToPrimitive>(n: T) -> Option<Self> {
+                match (n.to_i128(), n.to_u128()) {
+                    (Some(v), _) => Self::from_bigint(&BigInt::from(v)),
+                    (_, Some(v)) => Self::from_bigint(&BigInt::from(v)),
+                    _ => None,
+                }
+            }
+        }
+
+        impl JsonSerializable for $name {
+            fn into_json_value(self) -> Option<Value> {
+                Some(Value::String(self.to_string()))
+            }
+        }
+
+        impl ArrowNativeType for $name {
+            #[inline]
+            fn from_usize(v: usize) -> Option<Self> {
+                Some(Self::from_half(v as $half))
+            }
+
+            #[inline]
+            fn to_usize(&self) -> Option<usize> {
+                ToPrimitive::to_usize(&self.to_half()?)
+            }
+
+            #[inline]
+            fn to_isize(&self) -> Option<isize> {
+                ToPrimitive::to_isize(&self.to_half()?)
+            }
+        }
+    };
+}
+
+make_wide_int!(i256, true, i128);
+make_wide_int!(u256, false, u128);
+
+impl Neg for i256 {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self {
+        self.wrapping_neg()
+    }
+}
+
+impl From<i64> for i256 {
+    fn from(v: i64) -> Self {
+        Self::from_half(v as i128)
+    }
+}
+
+impl From<u64> for u256 {
+    fn from(v: u64) -> Self {
+        Self::from_half(v as u128)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{i256, u256};
+    use num::ToPrimitive;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_i256_arith() {
+        let a = i256::from(-3i64);
+        let b = i256::from(7i64);
+        assert_eq!(a + b, i256::from(4i64));
+        assert_eq!(a - b, i256::from(-10i64));
+        assert_eq!(a * b, i256::from(-21i64));
+        assert_eq!(b / a, i256::from(-2i64));
+        assert_eq!(b % a, i256::from(1i64));
+        assert_eq!(i256::MAX + i256::ONE, i256::MIN);
+        assert!(a < b && i256::MIN < a && b < i256::MAX);
+        assert_eq!(a.checked_div(i256::ZERO), None);
+
+        let big =
+            i256::from_str("-123456789012345678901234567890123456789012345").unwrap();
+        assert_eq!(
+            big.to_string(),
+            "-123456789012345678901234567890123456789012345"
+        );
+        assert_eq!(big.to_half(), None);
+        assert_eq!(a.to_half(), Some(-3));
+        assert_eq!(
+            i256::from_le_slice(&(-5i32).to_le_bytes()),
+            i256::from(-5i64)
+        );
+        assert_eq!(i256::from_le_bytes(big.to_le_bytes()), big);
+        assert_eq!(b.checked_mul_pow10(2), Some(i256::from(700i64)));
+        assert_eq!(i256::MAX.checked_mul_pow10(1), None);
+        assert_eq!(i256::MAX.checked_add(i256::ONE), None);
+        assert_eq!(a.checked_mul(b), Some(i256::from(-21i64)));
+        let c = <i256 as num::NumCast>::from(u64::MAX).unwrap();
+        assert_eq!(c.to_u64(), Some(u64::MAX));
+    }
+
+    #[test]
+    fn test_u256_arith() {
+        let a = u256::from(u128::MAX);
+        assert_eq!(
+            (a + u256::ONE).to_string(),
+            "340282366920938463463374607431768211456"
+        );
+        assert!(u256::MAX > a);
+        assert_eq!(u256::ZERO - u256::ONE, u256::MAX);
+        assert_eq!(
+            u256::MAX.to_string(),
+            "115792089237316195423570985008687907853269984665640564039457584007913129639935"
+        );
+        assert!(u256::from_str("-1").is_err());
+        assert_eq!(<u256 as num::NumCast>::from(-1i8), None);
+        assert_eq!(u256::ZERO.checked_sub(u256::ONE), None);
+        assert_eq!(a.to_half(), Some(u128::MAX));
+        assert_eq!((a + u256::ONE).to_half(), None);
+    }
+}
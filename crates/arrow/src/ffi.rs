@@ -91,7 +91,7 @@ use bitflags::bitflags;
 
 use crate::array::ArrayData;
 use crate::buffer::Buffer;
-use crate::datatypes::DataType;
+use crate::datatypes::{decimal_byte_width, DataType};
 use crate::error::{ArrowError, Result};
 use crate::util::bit_util;
 
@@ -261,7 +261,7 @@ fn bit_width(data_type: &DataType, i: usize) -> Result<usize> {
         (DataType::Int64, 1) | (DataType::Date64, 1) | (DataType::Time64(_), 1) => size_of::<i64>() * 8,
         (DataType::Float32, 1) => size_of::<f32>() * 8,
         (DataType::Float64, 1) => size_of::<f64>() * 8,
-        (DataType::Decimal(..), 1) => size_of::<i128>() * 8,
+        (DataType::Decimal(precision, _), 1) => decimal_byte_width(*precision) * 8,
         (DataType::Timestamp(..), 1) => size_of::<i64>() * 8,
         // primitive types have a single buffer
         (DataType::Boolean, _) |
@@ -271,6 +271,10 @@ pub fn array_value_to_string(column: &array::ArrayRef, row: usize) -> Result<Str
         DataType::UInt16 => make_string!(array::UInt16Array, column, row),
         DataType::UInt32 => make_string!(array::UInt32Array, column, row),
         DataType::UInt64 => make_string!(array::UInt64Array, column, row),
+        DataType::Int128 => make_string!(array::Int128Array, column, row),
+        DataType::UInt128 => make_string!(array::UInt128Array, column, row),
+        DataType::Int256 => make_string!(array::Int256Array, column, row),
+        DataType::UInt256 => make_string!(array::UInt256Array, column, row),
         DataType::Float16 => make_string!(array::Float32Array, column, row),
         DataType::Float32 => make_string!(array::Float32Array, column, row),
         DataType::Float64 => make_string!(array::Float64Array, column, row),
@@ -49,6 +49,20 @@ pub fn encode_varint64(mut value: u64, buf: &mut [u8]) -> usize {
     10
 }
 
+/// Decode the varint at the start of buf.
+/// Returns the value and the length of the varint, None if it is truncated.
+#[inline(always)]
+pub fn decode_varint64(buf: &[u8]) -> Option<(u64, usize)> {
+    let mut value = 0u64;
+    for (i, b) in buf.iter().take(10).enumerate() {
+        value |= ((b & 0x7F) as u64) << (7 * i);
+        if b & 0x80 == 0 {
+            return Some((value, i + 1));
+        }
+    }
+    None
+}
+
 #[inline(always)]
 pub fn encode_ascii_bytes_vec_short(bs: &[u8], vbuf: &mut Vec<u8>) -> BaseResult<()> {
     let len = bs.len();
@@ -96,3 +110,18 @@ pub fn encode_ascii_bytes_vec_short(bs: &[u8], vbuf: &mut Vec<u8>) -> BaseResult
 //     buf[4] = value as u8;
 //     5
 // }
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    #[test]
+    fn test_varint64() {
+        let mut buf = [0u8; 10];
+        for v in [0u64, 1, 127, 128, 300, u32::MAX as u64, u64::MAX] {
+            let n = encode_varint64(v, &mut buf);
+            assert_eq!(decode_varint64(&buf[..n]), Some((v, n)));
+            assert_eq!(decode_varint64(&buf[..n - 1]), None);
+        }
+    }
+}
@@ -136,6 +136,16 @@ impl TimeZoneId {
     pub fn offset(self) -> i32 {
         self.0
     }
+
+    /// Return the offset of timezone in the quarters of an hour, which is the
+    /// granularity of the names of timezones.
+    pub fn quarters(self) -> i8 {
+        (self.0 / 900) as i8
+    }
+
+    pub fn from_quarters(quarters: i8) -> TimeZoneId {
+        TimeZoneId(quarters as i32 * 900)
+    }
 }
 
 #[inline(always)]
@@ -338,6 +348,45 @@ pub fn parse_to_epoch(s: &str, tz_offset: i32) -> BaseResult<u32> {
     ))
 }
 
+/// Parse date time string with an optional fraction of second to the ticks of
+/// `10^-precision` second since the epoch, which are the values of `DateTime64`.
+///
+/// The format is that of [`parse_to_epoch`] followed by `.` and 1 to 9 digits,
+/// the digits beyond the precision are truncated.
+///
+/// # Examples
+/// ```
+/// use base::datetimes::parse_to_epoch64;
+/// assert!(matches!(parse_to_epoch64("1970-01-01 00:00:01.5", 3, 0), Ok(1_500)));
+/// assert!(matches!(parse_to_epoch64("1970-01-01 00:00:01", 6, 0), Ok(1_000_000)));
+/// assert!(matches!(parse_to_epoch64("1970-01-01 00:00:01.1239", 3, 0), Ok(1_123)));
+/// // parse error
+/// assert!(parse_to_epoch64("1970-01-01 00:00:01.", 3, 0).is_err());
+/// ```
+pub fn parse_to_epoch64(s: &str, precision: u8, tz_offset: i32) -> BaseResult<i64> {
+    if precision > 9 {
+        return Err(BaseError::InvalidDatetimeFormat);
+    }
+    let secs = parse_to_epoch(s, tz_offset)? as i64;
+    let frac = match &s.as_bytes()[19..] {
+        [] => &[][..],
+        [b'.', frac @ ..] if !frac.is_empty() && frac.len() <= 9 => frac,
+        _ => return Err(BaseError::InvalidDatetimeFormat),
+    };
+    let mut ticks = secs;
+    for i in 0..precision as usize {
+        let d = match frac.get(i) {
+            Some(b) => one_digit(*b)?,
+            None => 0,
+        };
+        ticks = ticks * 10 + d as i64;
+    }
+    for b in frac.iter().skip(precision as usize) {
+        one_digit(*b)?;
+    }
+    Ok(ticks)
+}
+
 /// Parse date string to the unixtime epoch.
 ///
 /// Only `%Y-%m-%d`format is supported, where `%Y` requires **exactly** 4 digits,
@@ -52,6 +52,10 @@ pub trait AsInColumn: Send {
     fn offset_map(&self) -> Option<Vec<u32>> {
         None
     }
+    /// Null flags of a Nullable column, 1 - null, 0 - no null
+    fn null_map(&self) -> Option<Vec<u8>> {
+        None
+    }
 }
 
 /// Default implementation returns `Null` data
@@ -477,6 +481,16 @@ impl<'a, C: AsInColumn + ?Sized + 'a> AsInColumn for Box<C> {
     unsafe fn into_bytes(&mut self) -> Vec<u8> {
         self.as_mut().into_bytes()
     }
+
+    #[inline]
+    fn offset_map(&self) -> Option<Vec<u32>> {
+        self.as_ref().offset_map()
+    }
+
+    #[inline]
+    fn null_map(&self) -> Option<Vec<u8>> {
+        self.as_ref().null_map()
+    }
 }
 /// String data is stored in Clickhouse as arbitrary byte sequence.
 /// It's not always possible safely convert it to utf-8 rust string.
@@ -801,7 +815,15 @@ where
     }
 
     unsafe fn into_bytes(&mut self) -> Vec<u8> {
-        as_bytes_bufer_mut(&mut self.inner.data).to_vec()
+        self.inner.into_bytes()
+    }
+
+    fn offset_map(&self) -> Option<Vec<u32>> {
+        self.inner.offset_map()
+    }
+
+    fn null_map(&self) -> Option<Vec<u8>> {
+        Some(self.nulls.clone())
     }
 }
 /// Any type that implement ValueIndex trait can be loaded into FixedColumn
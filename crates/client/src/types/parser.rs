@@ -293,6 +293,12 @@ fn parse_type_flags(t: &str) -> (u8, u8, &str) {
 }
 
 pub fn parse_type_field(t: &str) -> Result<Field> {
+    // SimpleAggregateFunction(func, T) is transferred as T
+    if t.starts_with("SimpleAggregateFunction(") && t.ends_with(')') {
+        if let Some(i) = t.find(',') {
+            return parse_type_field(t[i + 1..t.len() - 1].trim());
+        }
+    }
     let (flag, depth, t) = parse_type_flags(t);
     macro_rules! field {
         ($sql_type: expr) => {
@@ -376,6 +382,10 @@ mod test {
             let t = parse_type_field(*t).unwrap();
             assert_eq!(FIELD_NONE, t.flag);
         }
+
+        let t = parse_type_field("SimpleAggregateFunction(sum, UInt64)").unwrap();
+        assert_eq!(FIELD_NONE, t.flag);
+        assert_eq!(SqlType::UInt64, t.sql_type);
     }
 
     #[test]
@@ -84,6 +84,8 @@ impl FromStr for AggregateFunction {
             "sum" => AggregateFunction::Sum,
             "approx_distinct" => AggregateFunction::ApproxDistinct,
             "array_agg" => AggregateFunction::ArrayAgg,
+            // clickhouse groupArray, which is lowercased if not quoted
+            "grouparray" | "groupArray" => AggregateFunction::ArrayAgg,
             _ => {
                 return Err(DataFusionError::Plan(format!(
                     "There is no built-in function named {}",
@@ -481,6 +483,15 @@ mod tests {
     fn test_sum_upcasts() -> Result<()> {
         let observed = return_type(&AggregateFunction::Sum, &[DataType::UInt32])?;
         assert_eq!(DataType::UInt64, observed);
+
+        let observed = return_type(&AggregateFunction::Sum, &[DataType::Int256])?;
+        assert_eq!(DataType::Int256, observed);
+
+        let observed = return_type(&AggregateFunction::Sum, &[DataType::Decimal(9, 2)])?;
+        assert_eq!(DataType::Decimal(38, 2), observed);
+
+        let observed = return_type(&AggregateFunction::Sum, &[DataType::Decimal(40, 2)])?;
+        assert_eq!(DataType::Decimal(76, 2), observed);
         Ok(())
     }
 
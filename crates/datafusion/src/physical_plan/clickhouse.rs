@@ -7,24 +7,37 @@ use super::functions::Volatility;
 use super::{ColumnarValue, PhysicalExpr};
 use crate::error::{DataFusionError, Result};
 use crate::physical_plan::functions::{ScalarFunctionImplementation, Signature};
+use crate::scalar::ScalarValue;
 use arrow::{
     array::{
         Array, ArrayRef, BooleanArray, Date16Array, FixedSizeBinaryArray,
-        GenericStringArray, Int64Array, PrimitiveArray, StringOffsetSizeTrait,
-        Timestamp32Array, UInt16Array, UInt8Array,
+        GenericStringArray, Int32Array, Int64Array, ListArray, PrimitiveArray,
+        StringOffsetSizeTrait, Timestamp32Array, UInt16Array, UInt32Array, UInt64Array,
+        UInt8Array,
     },
-    datatypes::{ArrowPrimitiveType, DataType, Schema},
+    compute::{cast, take},
+    datatypes::{ArrowPrimitiveType, DataType, Field, Schema, TimeUnit, UInt32Type},
+    record_batch::RecordBatch,
 };
 use fmt::{Debug, Formatter};
-use std::{any::type_name, fmt, lazy::SyncOnceCell, str::FromStr, sync::Arc};
+use std::{
+    any::type_name,
+    fmt,
+    lazy::SyncOnceCell,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    str::FromStr,
+    sync::Arc,
+};
 
 use base::uuid::to_hyphenated_lower;
 use base::{
+    codec::decode_varint64,
     datetimes::{
-        days_to_ordinal, days_to_unixtime, days_to_weekday, days_to_year, days_to_ymd,
-        parse_to_days, parse_to_epoch, unixtime_to_days, unixtime_to_hms,
-        unixtime_to_ordinal, unixtime_to_second, unixtime_to_weekday, unixtime_to_year,
-        unixtime_to_ymd, TimeZoneId,
+        add_tz_offset, days_to_ordinal, days_to_unixtime, days_to_weekday, days_to_year,
+        days_to_ymd, div_mod_floor, parse_to_days, parse_to_epoch, parse_to_epoch64,
+        unixtime_to_days, unixtime_to_hms, unixtime_to_ordinal, unixtime_to_second,
+        unixtime_to_weekday, unixtime_to_year, unixtime_to_ymd, ymd_to_days, TimeZoneId,
+        YMD,
     },
     uuid::{parse_uuid, uuid},
 };
@@ -58,6 +71,22 @@ pub enum BuiltinScalarFunction {
     ToMinute,
     /// toSecond,
     ToSecond,
+    /// toStartOfYear
+    ToStartOfYear,
+    /// toStartOfQuarter
+    ToStartOfQuarter,
+    /// toStartOfMonth
+    ToStartOfMonth,
+    /// toStartOfDay
+    ToStartOfDay,
+    /// toStartOfHour
+    ToStartOfHour,
+    /// toStartOfMinute
+    ToStartOfMinute,
+    /// toStartOfSecond
+    ToStartOfSecond,
+    /// toDateTime64, with the time unit from the precision and the time zone
+    ToDateTime64(TimeUnit, Option<TimeZoneId>),
     /// endsWith,
     EndsWith,
     /// generateUUIDv4
@@ -68,6 +97,22 @@ pub enum BuiltinScalarFunction {
     UUIDStringToNum,
     /// UUIDNumToString
     UUIDNumToString,
+    /// has
+    Has,
+    /// length
+    Length,
+    /// IPv4NumToString
+    IPv4NumToString,
+    /// IPv4StringToNum, 0 for the invalid addresses
+    IPv4StringToNum,
+    /// toIPv6, the IPv4 addresses are mapped into IPv6 and `::` for the invalid
+    ToIPv6,
+    /// isIPAddressInRange
+    IsIPAddressInRange,
+    /// IPv4CIDRToRange
+    // NOTE the range is in an array of the lower and the upper bounds, since
+    // there are no tuples
+    IPv4CIDRToRange,
 }
 
 /// Enum as which to treat the uuid when error in parsing uuid string
@@ -95,6 +140,14 @@ impl fmt::Display for BuiltinScalarFunction {
             BuiltinScalarFunction::ToHour => write!(f, "toHour"),
             BuiltinScalarFunction::ToMinute => write!(f, "toMinute"),
             BuiltinScalarFunction::ToSecond => write!(f, "toSecond"),
+            BuiltinScalarFunction::ToStartOfYear => write!(f, "toStartOfYear"),
+            BuiltinScalarFunction::ToStartOfQuarter => write!(f, "toStartOfQuarter"),
+            BuiltinScalarFunction::ToStartOfMonth => write!(f, "toStartOfMonth"),
+            BuiltinScalarFunction::ToStartOfDay => write!(f, "toStartOfDay"),
+            BuiltinScalarFunction::ToStartOfHour => write!(f, "toStartOfHour"),
+            BuiltinScalarFunction::ToStartOfMinute => write!(f, "toStartOfMinute"),
+            BuiltinScalarFunction::ToStartOfSecond => write!(f, "toStartOfSecond"),
+            BuiltinScalarFunction::ToDateTime64(_, _) => write!(f, "toDateTime64"),
             BuiltinScalarFunction::EndsWith => write!(f, "endsWith"),
             BuiltinScalarFunction::GenerateUUIDv4 => write!(f, "generateUUIDv4"),
             BuiltinScalarFunction::ToUUID(TreatNonUUIDAs::Error) => write!(f, "toUUID"),
@@ -106,6 +159,13 @@ impl fmt::Display for BuiltinScalarFunction {
             }
             BuiltinScalarFunction::UUIDStringToNum => write!(f, "UUIDStringToNum"),
             BuiltinScalarFunction::UUIDNumToString => write!(f, "UUIDNumToString"),
+            BuiltinScalarFunction::Has => write!(f, "has"),
+            BuiltinScalarFunction::Length => write!(f, "length"),
+            BuiltinScalarFunction::IPv4NumToString => write!(f, "IPv4NumToString"),
+            BuiltinScalarFunction::IPv4StringToNum => write!(f, "IPv4StringToNum"),
+            BuiltinScalarFunction::ToIPv6 => write!(f, "toIPv6"),
+            BuiltinScalarFunction::IsIPAddressInRange => write!(f, "isIPAddressInRange"),
+            BuiltinScalarFunction::IPv4CIDRToRange => write!(f, "IPv4CIDRToRange"),
         }
     }
 }
@@ -127,6 +187,17 @@ impl FromStr for BuiltinScalarFunction {
             "toHour" => BuiltinScalarFunction::ToHour,
             "toMinute" => BuiltinScalarFunction::ToMinute,
             "toSecond" => BuiltinScalarFunction::ToSecond,
+            "toStartOfYear" => BuiltinScalarFunction::ToStartOfYear,
+            "toStartOfQuarter" => BuiltinScalarFunction::ToStartOfQuarter,
+            "toStartOfMonth" => BuiltinScalarFunction::ToStartOfMonth,
+            "toStartOfDay" => BuiltinScalarFunction::ToStartOfDay,
+            "toStartOfHour" => BuiltinScalarFunction::ToStartOfHour,
+            "toStartOfMinute" => BuiltinScalarFunction::ToStartOfMinute,
+            "toStartOfSecond" => BuiltinScalarFunction::ToStartOfSecond,
+            //NOTE the default precision 3, see `BuiltinScalarFunction::to_datetime64`
+            "toDateTime64" => {
+                BuiltinScalarFunction::ToDateTime64(TimeUnit::Millisecond, None)
+            }
             "endsWith" => BuiltinScalarFunction::EndsWith,
             "generateUUIDv4" => BuiltinScalarFunction::GenerateUUIDv4,
             "toUUID" => BuiltinScalarFunction::ToUUID(TreatNonUUIDAs::Error),
@@ -134,6 +205,15 @@ impl FromStr for BuiltinScalarFunction {
             "toUUIDOrZero" => BuiltinScalarFunction::ToUUID(TreatNonUUIDAs::Zero),
             "UUIDStringToNum" => BuiltinScalarFunction::UUIDStringToNum,
             "UUIDNumToString" => BuiltinScalarFunction::UUIDNumToString,
+            // array functions
+            "has" => BuiltinScalarFunction::Has,
+            "length" => BuiltinScalarFunction::Length,
+            // ip address functions
+            "IPv4NumToString" => BuiltinScalarFunction::IPv4NumToString,
+            "IPv4StringToNum" => BuiltinScalarFunction::IPv4StringToNum,
+            "toIPv6" => BuiltinScalarFunction::ToIPv6,
+            "isIPAddressInRange" => BuiltinScalarFunction::IsIPAddressInRange,
+            "IPv4CIDRToRange" => BuiltinScalarFunction::IPv4CIDRToRange,
 
             _ => {
                 return Err(DataFusionError::Plan(format!(
@@ -194,6 +274,18 @@ macro_rules! wrap_datetime_fn {
         })
     }
 }
+/// wrap function calls from Timestamp array with the time zone to primitive array
+macro_rules! wrap_timestamp64_fn {
+    ( fn $OP:ident($TZ:ident) -> $OUTPUT_TY:ty ) => {{
+        let $TZ = parse_tz(&$TZ)?.map(|tz| tz.offset());
+        Arc::new(move |args: &[ColumnarValue]| {
+            let array = args[0].clone().into_array(1);
+            let res: $OUTPUT_TY = $OP(&array, $TZ)?;
+            Ok(ColumnarValue::Array(Arc::new(res)))
+        })
+    }};
+}
+
 /// wrap string function calls from [`ArrayRef`] to primitive array
 macro_rules! wrap_string_fn {
     ( fn $OP:ident $( ( $($ARG:expr),+ ) )? -> $OUTPUT_TY:ty ) => {
@@ -234,8 +326,27 @@ impl BuiltinScalarFunction {
         false
     }
 
+    /// Returns `toDateTime64` with the precision and the time zone given in its
+    /// constant arguments
+    pub fn to_datetime64(precision: u8, tz: Option<&str>) -> Result<Self> {
+        let unit = match precision {
+            0 => TimeUnit::Second,
+            3 => TimeUnit::Millisecond,
+            6 => TimeUnit::Microsecond,
+            9 => TimeUnit::Nanosecond,
+            _ => {
+                return Err(DataFusionError::Plan(format!(
+                    "Unsupported precision {} for function toDateTime64",
+                    precision
+                )))
+            }
+        };
+        let tz = parse_tz(&tz.map(|tz| tz.to_string()))?;
+        Ok(BuiltinScalarFunction::ToDateTime64(unit, tz))
+    }
+
     /// Returns the datatype of the scalar function
-    pub fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+    pub fn return_type(&self, arg_types: &[DataType]) -> Result<DataType> {
         match self {
             BuiltinScalarFunction::ToYear => Ok(DataType::UInt16),
             BuiltinScalarFunction::ToMonth => Ok(DataType::UInt8),
@@ -248,11 +359,41 @@ impl BuiltinScalarFunction {
             BuiltinScalarFunction::ToHour => Ok(DataType::UInt8),
             BuiltinScalarFunction::ToMinute => Ok(DataType::UInt8),
             BuiltinScalarFunction::ToSecond => Ok(DataType::UInt8),
+            BuiltinScalarFunction::ToStartOfYear
+            | BuiltinScalarFunction::ToStartOfQuarter
+            | BuiltinScalarFunction::ToStartOfMonth => Ok(DataType::Date16),
+            BuiltinScalarFunction::ToStartOfDay
+            | BuiltinScalarFunction::ToStartOfHour
+            | BuiltinScalarFunction::ToStartOfMinute => Ok(DataType::Timestamp32(None)),
+            BuiltinScalarFunction::ToStartOfSecond => match arg_types.first() {
+                Some(dt @ DataType::Timestamp(_, _)) => Ok(dt.clone()),
+                other => Err(DataFusionError::Plan(format!(
+                    "Unsupported data type {:?} for function toStartOfSecond",
+                    other
+                ))),
+            },
+            BuiltinScalarFunction::ToDateTime64(unit, tz) => Ok(DataType::Timestamp(
+                unit.clone(),
+                tz.map(|tz| tz.name().to_string()),
+            )),
             BuiltinScalarFunction::EndsWith => Ok(DataType::Boolean),
             BuiltinScalarFunction::GenerateUUIDv4
             | BuiltinScalarFunction::ToUUID(_)
             | BuiltinScalarFunction::UUIDStringToNum => Ok(DataType::FixedSizeBinary(16)),
             BuiltinScalarFunction::UUIDNumToString => Ok(DataType::LargeUtf8),
+            BuiltinScalarFunction::Has => Ok(DataType::Boolean),
+            BuiltinScalarFunction::Length => match arg_types.first() {
+                Some(DataType::Utf8) => Ok(DataType::Int32),
+                Some(DataType::LargeUtf8) => Ok(DataType::Int64),
+                _ => Ok(DataType::UInt64),
+            },
+            BuiltinScalarFunction::IPv4NumToString => Ok(DataType::LargeUtf8),
+            BuiltinScalarFunction::IPv4StringToNum => Ok(DataType::UInt32),
+            BuiltinScalarFunction::ToIPv6 => Ok(DataType::FixedSizeBinary(16)),
+            BuiltinScalarFunction::IsIPAddressInRange => Ok(DataType::Boolean),
+            BuiltinScalarFunction::IPv4CIDRToRange => Ok(DataType::List(Box::new(
+                Field::new("item", DataType::UInt32, true),
+            ))),
         }
     }
 
@@ -367,6 +508,108 @@ impl BuiltinScalarFunction {
                 }
                 other => wrap_type_err!(other, "toSecond"),
             },
+            BuiltinScalarFunction::ToStartOfYear => match args[0].data_type(schema) {
+                Ok(DataType::Date16) => {
+                    wrap_datetime_fn!(fn date16_to_start_of_year(Date16Array) -> Date16Array)
+                }
+                Ok(DataType::Timestamp32(tz)) => {
+                    wrap_datetime_fn!(fn timestamp32_to_start_of_year(Timestamp32Array, tz) -> Date16Array)
+                }
+                Ok(DataType::Timestamp(_, tz)) => {
+                    wrap_timestamp64_fn!(fn timestamp64_to_start_of_year(tz) -> Date16Array)
+                }
+                other => wrap_type_err!(other, "toStartOfYear"),
+            },
+            BuiltinScalarFunction::ToStartOfQuarter => match args[0].data_type(schema) {
+                Ok(DataType::Date16) => {
+                    wrap_datetime_fn!(fn date16_to_start_of_quarter(Date16Array) -> Date16Array)
+                }
+                Ok(DataType::Timestamp32(tz)) => {
+                    wrap_datetime_fn!(fn timestamp32_to_start_of_quarter(Timestamp32Array, tz) -> Date16Array)
+                }
+                Ok(DataType::Timestamp(_, tz)) => {
+                    wrap_timestamp64_fn!(fn timestamp64_to_start_of_quarter(tz) -> Date16Array)
+                }
+                other => wrap_type_err!(other, "toStartOfQuarter"),
+            },
+            BuiltinScalarFunction::ToStartOfMonth => match args[0].data_type(schema) {
+                Ok(DataType::Date16) => {
+                    wrap_datetime_fn!(fn date16_to_start_of_month(Date16Array) -> Date16Array)
+                }
+                Ok(DataType::Timestamp32(tz)) => {
+                    wrap_datetime_fn!(fn timestamp32_to_start_of_month(Timestamp32Array, tz) -> Date16Array)
+                }
+                Ok(DataType::Timestamp(_, tz)) => {
+                    wrap_timestamp64_fn!(fn timestamp64_to_start_of_month(tz) -> Date16Array)
+                }
+                other => wrap_type_err!(other, "toStartOfMonth"),
+            },
+            BuiltinScalarFunction::ToStartOfDay => match args[0].data_type(schema) {
+                Ok(DataType::Timestamp32(tz)) => {
+                    wrap_datetime_fn!(fn timestamp32_to_start_of_day(Timestamp32Array, tz) -> Timestamp32Array)
+                }
+                Ok(DataType::Timestamp(_, tz)) => {
+                    wrap_timestamp64_fn!(fn timestamp64_to_start_of_day(tz) -> Timestamp32Array)
+                }
+                other => wrap_type_err!(other, "toStartOfDay"),
+            },
+            BuiltinScalarFunction::ToStartOfHour => match args[0].data_type(schema) {
+                Ok(DataType::Timestamp32(tz)) => {
+                    wrap_datetime_fn!(fn timestamp32_to_start_of_hour(Timestamp32Array, tz) -> Timestamp32Array)
+                }
+                Ok(DataType::Timestamp(_, tz)) => {
+                    wrap_timestamp64_fn!(fn timestamp64_to_start_of_hour(tz) -> Timestamp32Array)
+                }
+                other => wrap_type_err!(other, "toStartOfHour"),
+            },
+            BuiltinScalarFunction::ToStartOfMinute => match args[0].data_type(schema) {
+                Ok(DataType::Timestamp32(tz)) => {
+                    wrap_datetime_fn!(fn timestamp32_to_start_of_minute(Timestamp32Array, tz) -> Timestamp32Array)
+                }
+                Ok(DataType::Timestamp(_, tz)) => {
+                    wrap_timestamp64_fn!(fn timestamp64_to_start_of_minute(tz) -> Timestamp32Array)
+                }
+                other => wrap_type_err!(other, "toStartOfMinute"),
+            },
+            BuiltinScalarFunction::ToStartOfSecond => match args[0].data_type(schema) {
+                Ok(DataType::Timestamp(_, _)) => Arc::new(|args| {
+                    let array = args[0].clone().into_array(1);
+                    Ok(ColumnarValue::Array(timestamp64_to_start_of_second(
+                        &array,
+                    )?))
+                }),
+                other => wrap_type_err!(other, "toStartOfSecond"),
+            },
+            BuiltinScalarFunction::ToDateTime64(unit, tz) => {
+                let precision = timeunit_to_precision(unit);
+                let return_type = self.return_type(&[])?;
+                let tz = tz.map(|tz| tz.offset());
+                let ticks: ScalarFunctionImplementation = match args[0].data_type(schema)
+                {
+                    Ok(DataType::Utf8) => {
+                        wrap_string_fn!(fn utf8_to_datetime64(precision, tz) -> Int64Array)
+                    }
+                    Ok(DataType::LargeUtf8) => wrap_string_fn!(
+                        fn large_utf8_to_datetime64(precision, tz) -> Int64Array
+                    ),
+                    Ok(DataType::Date16) => {
+                        wrap_string_fn!(fn date16_to_datetime64(precision, tz) -> Int64Array)
+                    }
+                    Ok(DataType::Timestamp32(_)) => wrap_string_fn!(
+                        fn timestamp32_to_datetime64(precision) -> Int64Array
+                    ),
+                    Ok(DataType::Int64) => {
+                        wrap_string_fn!(fn int64_to_datetime64(precision) -> Int64Array)
+                    }
+                    //the casting converts the ticks between the time units
+                    Ok(DataType::Timestamp(_, _)) => Arc::new(|args| Ok(args[0].clone())),
+                    other => wrap_type_err!(other, "toDateTime64"),
+                };
+                Arc::new(move |args| {
+                    let array = ticks(args)?.into_array(1);
+                    Ok(ColumnarValue::Array(cast(&array, &return_type)?))
+                })
+            }
             BuiltinScalarFunction::EndsWith => match args[0].data_type(schema) {
                 Ok(DataType::Utf8) => wrap_string_fn!(fn utf8_ends_with -> BooleanArray),
                 Ok(DataType::LargeUtf8) => {
@@ -407,6 +650,48 @@ impl BuiltinScalarFunction {
                 }
                 other => wrap_type_err!(other, "UUIDNumToString"),
             },
+            BuiltinScalarFunction::Has => match args[0].data_type(schema) {
+                Ok(DataType::List(_)) => wrap_string_fn!(fn array_has -> BooleanArray),
+                other => wrap_type_err!(other, "has"),
+            },
+            BuiltinScalarFunction::Length => match args[0].data_type(schema) {
+                Ok(DataType::List(_)) => wrap_string_fn!(fn array_length -> UInt64Array),
+                Ok(DataType::Utf8) => wrap_string_fn!(fn utf8_length -> Int32Array),
+                Ok(DataType::LargeUtf8) => {
+                    wrap_string_fn!(fn large_utf8_length -> Int64Array)
+                }
+                other => wrap_type_err!(other, "length"),
+            },
+            BuiltinScalarFunction::IPv4NumToString => match args[0].data_type(schema) {
+                Ok(t) if is_integer(&t) => {
+                    wrap_string_fn!(fn ipv4_num_to_string -> GenericStringArray<i64>)
+                }
+                other => wrap_type_err!(other, "IPv4NumToString"),
+            },
+            BuiltinScalarFunction::IPv4StringToNum => {
+                wrap_string_fn!(fn ipv4_string_to_num -> UInt32Array)
+            }
+            BuiltinScalarFunction::ToIPv6 => {
+                wrap_string_fn!(fn string_to_ipv6 -> FixedSizeBinaryArray)
+            }
+            BuiltinScalarFunction::IsIPAddressInRange => {
+                for arg in args {
+                    match arg.data_type(schema) {
+                        Ok(DataType::Utf8 | DataType::LargeUtf8) => {}
+                        other => wrap_type_err!(other, "isIPAddressInRange"),
+                    }
+                }
+                wrap_string_fn!(fn is_ip_address_in_range -> BooleanArray)
+            }
+            BuiltinScalarFunction::IPv4CIDRToRange => {
+                for arg in args {
+                    match arg.data_type(schema) {
+                        Ok(t) if is_integer(&t) => {}
+                        other => wrap_type_err!(other, "IPv4CIDRToRange"),
+                    }
+                }
+                wrap_string_fn!(fn ipv4_cidr_to_range -> ListArray)
+            }
         };
 
         Ok(func)
@@ -451,6 +736,20 @@ impl BuiltinScalarFunction {
                 TypeSignature::Uniform(1, vec![DataType::Timestamp32(None)]),
                 Volatility::Immutable,
             ),
+            //NOTE the time zones of the timestamps are kept without the coercion
+            BuiltinScalarFunction::ToStartOfYear
+            | BuiltinScalarFunction::ToStartOfQuarter
+            | BuiltinScalarFunction::ToStartOfMonth
+            | BuiltinScalarFunction::ToStartOfDay
+            | BuiltinScalarFunction::ToStartOfHour
+            | BuiltinScalarFunction::ToStartOfMinute
+            | BuiltinScalarFunction::ToStartOfSecond => {
+                Signature::new(TypeSignature::Any(1), Volatility::Immutable)
+            }
+            BuiltinScalarFunction::ToDateTime64(_, _) => Signature::new(
+                TypeSignature::OneOf(vec![TypeSignature::Any(2), TypeSignature::Any(3)]),
+                Volatility::Immutable,
+            ),
             BuiltinScalarFunction::EndsWith => {
                 Signature::new(TypeSignature::Any(2), Volatility::Immutable)
             }
@@ -467,6 +766,24 @@ impl BuiltinScalarFunction {
                 TypeSignature::Uniform(1, vec![DataType::FixedSizeBinary(16)]),
                 Volatility::Immutable,
             ),
+            BuiltinScalarFunction::Has => {
+                Signature::new(TypeSignature::Any(2), Volatility::Immutable)
+            }
+            BuiltinScalarFunction::Length | BuiltinScalarFunction::IPv4NumToString => {
+                Signature::new(TypeSignature::Any(1), Volatility::Immutable)
+            }
+            BuiltinScalarFunction::IPv4StringToNum | BuiltinScalarFunction::ToIPv6 => {
+                Signature::new(
+                    TypeSignature::Uniform(1, vec![DataType::Utf8, DataType::LargeUtf8]),
+                    Volatility::Immutable,
+                )
+            }
+            //NOTE the strings of isIPAddressInRange are not coerced, which would lose
+            //the lengths of the large utf-8 strings
+            BuiltinScalarFunction::IsIPAddressInRange
+            | BuiltinScalarFunction::IPv4CIDRToRange => {
+                Signature::new(TypeSignature::Any(2), Volatility::Immutable)
+            }
         }
     }
 }
@@ -477,6 +794,34 @@ fn get_tz_offset_or_default(tz: Option<i32>) -> Result<i32> {
     )
 }
 
+fn parse_tz(tz: &Option<String>) -> Result<Option<TimeZoneId>> {
+    tz.as_deref()
+        .map(TimeZoneId::from_str)
+        .transpose()
+        .map_err(|e| DataFusionError::Plan(e.to_string()))
+}
+
+fn timeunit_to_precision(unit: &TimeUnit) -> u8 {
+    match unit {
+        TimeUnit::Second => 0,
+        TimeUnit::Millisecond => 3,
+        TimeUnit::Microsecond => 6,
+        TimeUnit::Nanosecond => 9,
+    }
+}
+
+fn ticks_per_second(data_type: &DataType) -> Result<i64> {
+    match data_type {
+        DataType::Timestamp(unit, _) => {
+            Ok(10_i64.pow(timeunit_to_precision(unit) as u32))
+        }
+        other => Err(DataFusionError::Internal(format!(
+            "could not take the ticks of {}",
+            other
+        ))),
+    }
+}
+
 fn handle_date_fn<T, U, F>(array: &PrimitiveArray<T>, f: F) -> Result<PrimitiveArray<U>>
 where
     T: ArrowPrimitiveType,
@@ -500,6 +845,24 @@ where
     Ok(array.iter().map(|x| f(x, tz)).collect())
 }
 
+/// Like `handle_timestamp_fn`, but the ticks of Timestamp array are given in seconds
+fn handle_timestamp64_fn<U, F>(
+    array: &ArrayRef,
+    tz: Option<i32>,
+    f: F,
+) -> Result<PrimitiveArray<U>>
+where
+    U: ArrowPrimitiveType,
+    F: Fn(Option<i32>, i32) -> Option<U::Native>,
+{
+    let ticks_per_second = ticks_per_second(array.data_type())?;
+    let ticks = cast(array, &DataType::Int64)?;
+    let ticks = downcast_array_args!(ticks, Int64Array);
+    handle_timestamp_fn(ticks, tz, |x, tz| {
+        f(x.map(|x| div_mod_floor(x, ticks_per_second).0 as i32), tz)
+    })
+}
+
 fn downcast_string<T: StringOffsetSizeTrait>(
     arg: &ArrayRef,
 ) -> Result<(&GenericStringArray<T>, usize)> {
@@ -658,6 +1021,201 @@ fn month_to_quarter(month: u8) -> u8 {
     (month - 1) / 3 + 1
 }
 
+fn days_to_start_of_year(days: i32) -> u16 {
+    let YMD { y, .. } = days_to_ymd(days);
+    ymd_to_days(YMD { y, m: 1, d: 1 })
+}
+
+fn days_to_start_of_quarter(days: i32) -> u16 {
+    let YMD { y, m, .. } = days_to_ymd(days);
+    ymd_to_days(YMD {
+        y,
+        m: (month_to_quarter(m) - 1) * 3 + 1,
+        d: 1,
+    })
+}
+
+fn days_to_start_of_month(days: i32) -> u16 {
+    let YMD { y, m, .. } = days_to_ymd(days);
+    ymd_to_days(YMD { y, m, d: 1 })
+}
+
+/// Rounds down the unixtime to the interval in seconds in the time zone
+fn unixtime_to_start_of(unixtime: i32, tz: i32, interval: i32) -> i32 {
+    unixtime - div_mod_floor(add_tz_offset(unixtime, tz), interval).1
+}
+
+/// Rounds down the dates to the first days of the years
+pub fn date16_to_start_of_year(array: &Date16Array) -> Result<Date16Array> {
+    handle_date_fn(array, |x| Some(days_to_start_of_year(x? as i32)))
+}
+/// Rounds down the dates to the first days of the quarters
+pub fn date16_to_start_of_quarter(array: &Date16Array) -> Result<Date16Array> {
+    handle_date_fn(array, |x| Some(days_to_start_of_quarter(x? as i32)))
+}
+/// Rounds down the dates to the first days of the months
+pub fn date16_to_start_of_month(array: &Date16Array) -> Result<Date16Array> {
+    handle_date_fn(array, |x| Some(days_to_start_of_month(x? as i32)))
+}
+/// Rounds down Timestamp32 array to the first days of the years
+pub fn timestamp32_to_start_of_year(
+    array: &Timestamp32Array,
+    tz: Option<i32>,
+) -> Result<Date16Array> {
+    handle_timestamp_fn(array, tz, |x, tz| {
+        Some(days_to_start_of_year(unixtime_to_days(x?, tz) as i32))
+    })
+}
+/// Rounds down Timestamp32 array to the first days of the quarters
+pub fn timestamp32_to_start_of_quarter(
+    array: &Timestamp32Array,
+    tz: Option<i32>,
+) -> Result<Date16Array> {
+    handle_timestamp_fn(array, tz, |x, tz| {
+        Some(days_to_start_of_quarter(unixtime_to_days(x?, tz) as i32))
+    })
+}
+/// Rounds down Timestamp32 array to the first days of the months
+pub fn timestamp32_to_start_of_month(
+    array: &Timestamp32Array,
+    tz: Option<i32>,
+) -> Result<Date16Array> {
+    handle_timestamp_fn(array, tz, |x, tz| {
+        Some(days_to_start_of_month(unixtime_to_days(x?, tz) as i32))
+    })
+}
+/// Rounds down Timestamp32 array to the starts of the days
+pub fn timestamp32_to_start_of_day(
+    array: &Timestamp32Array,
+    tz: Option<i32>,
+) -> Result<Timestamp32Array> {
+    handle_timestamp_fn(array, tz, |x, tz| {
+        Some(unixtime_to_start_of(x?, tz, 86_400))
+    })
+}
+/// Rounds down Timestamp32 array to the starts of the hours
+pub fn timestamp32_to_start_of_hour(
+    array: &Timestamp32Array,
+    tz: Option<i32>,
+) -> Result<Timestamp32Array> {
+    handle_timestamp_fn(array, tz, |x, tz| Some(unixtime_to_start_of(x?, tz, 3_600)))
+}
+/// Rounds down Timestamp32 array to the starts of the minutes
+pub fn timestamp32_to_start_of_minute(
+    array: &Timestamp32Array,
+    tz: Option<i32>,
+) -> Result<Timestamp32Array> {
+    handle_timestamp_fn(array, tz, |x, tz| Some(unixtime_to_start_of(x?, tz, 60)))
+}
+/// Rounds down Timestamp array to the first days of the years
+pub fn timestamp64_to_start_of_year(
+    array: &ArrayRef,
+    tz: Option<i32>,
+) -> Result<Date16Array> {
+    handle_timestamp64_fn(array, tz, |x, tz| {
+        Some(days_to_start_of_year(unixtime_to_days(x?, tz) as i32))
+    })
+}
+/// Rounds down Timestamp array to the first days of the quarters
+pub fn timestamp64_to_start_of_quarter(
+    array: &ArrayRef,
+    tz: Option<i32>,
+) -> Result<Date16Array> {
+    handle_timestamp64_fn(array, tz, |x, tz| {
+        Some(days_to_start_of_quarter(unixtime_to_days(x?, tz) as i32))
+    })
+}
+/// Rounds down Timestamp array to the first days of the months
+pub fn timestamp64_to_start_of_month(
+    array: &ArrayRef,
+    tz: Option<i32>,
+) -> Result<Date16Array> {
+    handle_timestamp64_fn(array, tz, |x, tz| {
+        Some(days_to_start_of_month(unixtime_to_days(x?, tz) as i32))
+    })
+}
+/// Rounds down Timestamp array to the starts of the days
+pub fn timestamp64_to_start_of_day(
+    array: &ArrayRef,
+    tz: Option<i32>,
+) -> Result<Timestamp32Array> {
+    handle_timestamp64_fn(array, tz, |x, tz| {
+        Some(unixtime_to_start_of(x?, tz, 86_400))
+    })
+}
+/// Rounds down Timestamp array to the starts of the hours
+pub fn timestamp64_to_start_of_hour(
+    array: &ArrayRef,
+    tz: Option<i32>,
+) -> Result<Timestamp32Array> {
+    handle_timestamp64_fn(array, tz, |x, tz| Some(unixtime_to_start_of(x?, tz, 3_600)))
+}
+/// Rounds down Timestamp array to the starts of the minutes
+pub fn timestamp64_to_start_of_minute(
+    array: &ArrayRef,
+    tz: Option<i32>,
+) -> Result<Timestamp32Array> {
+    handle_timestamp64_fn(array, tz, |x, tz| Some(unixtime_to_start_of(x?, tz, 60)))
+}
+/// Rounds down Timestamp array to the starts of the seconds, in the same type
+pub fn timestamp64_to_start_of_second(array: &ArrayRef) -> Result<ArrayRef> {
+    let ticks_per_second = ticks_per_second(array.data_type())?;
+    let ticks = cast(array, &DataType::Int64)?;
+    let ticks = downcast_array_args!(ticks, Int64Array);
+    let res: Int64Array =
+        handle_date_fn(ticks, |x| Some(x? - div_mod_floor(x?, ticks_per_second).1))?;
+    Ok(cast(&(Arc::new(res) as ArrayRef), array.data_type())?)
+}
+
+/// Parses the strings to the ticks of DateTime64 in the precision
+pub fn utf8_to_datetime64(
+    args: &[ArrayRef],
+    precision: u8,
+    tz: Option<i32>,
+) -> Result<Int64Array> {
+    let tz = get_tz_offset_or_default(tz)?;
+    handle_string_unary_fn::<i32, _, _>(&args[0], |s| {
+        parse_to_epoch64(s, precision, tz)
+            .map_err(|e| DataFusionError::Execution(e.to_string()))
+    })
+}
+/// Parses the large strings to the ticks of DateTime64 in the precision
+pub fn large_utf8_to_datetime64(
+    args: &[ArrayRef],
+    precision: u8,
+    tz: Option<i32>,
+) -> Result<Int64Array> {
+    let tz = get_tz_offset_or_default(tz)?;
+    handle_string_unary_fn::<i64, _, _>(&args[0], |s| {
+        parse_to_epoch64(s, precision, tz)
+            .map_err(|e| DataFusionError::Execution(e.to_string()))
+    })
+}
+/// Converts the dates to the ticks of DateTime64 in the precision
+pub fn date16_to_datetime64(
+    args: &[ArrayRef],
+    precision: u8,
+    tz: Option<i32>,
+) -> Result<Int64Array> {
+    let ticks_per_second = 10_i64.pow(precision as u32);
+    let array = downcast_array_args!(args[0], Date16Array);
+    handle_timestamp_fn(array, tz, |x, tz| {
+        Some(days_to_unixtime(x? as i32, tz) as i64 * ticks_per_second)
+    })
+}
+/// Converts Timestamp32 array to the ticks of DateTime64 in the precision
+pub fn timestamp32_to_datetime64(args: &[ArrayRef], precision: u8) -> Result<Int64Array> {
+    let ticks_per_second = 10_i64.pow(precision as u32);
+    let array = downcast_array_args!(args[0], Timestamp32Array);
+    handle_date_fn(array, |x| Some(x? as i64 * ticks_per_second))
+}
+/// Converts the seconds to the ticks of DateTime64 in the precision
+pub fn int64_to_datetime64(args: &[ArrayRef], precision: u8) -> Result<Int64Array> {
+    let ticks_per_second = 10_i64.pow(precision as u32);
+    let array = downcast_array_args!(args[0], Int64Array);
+    handle_date_fn(array, |x| Some(x? * ticks_per_second))
+}
+
 /// Returns true if string ends with suffix for utf-8.
 pub fn utf8_ends_with(args: &[ArrayRef]) -> Result<BooleanArray> {
     ends_with::<i32>(args)
@@ -820,3 +1378,284 @@ pub fn uuid_to_large_utf(arg: &FixedSizeBinaryArray) -> Result<GenericStringArra
     log::debug!("ret: {:?}", ret);
     ret
 }
+
+/// Strips the varint of the length before the bytes of a large utf-8 string.
+fn strip_varint_len(bs: &[u8]) -> &[u8] {
+    match decode_varint64(bs) {
+        Some((_, n)) => &bs[n..],
+        None => bs,
+    }
+}
+
+/// Returns the bytes of the string at `i`, without the length of large utf-8.
+fn string_bytes_at(arg: &ArrayRef, i: usize) -> Result<&[u8]> {
+    match arg.data_type() {
+        DataType::Utf8 => Ok(downcast_string::<i32>(arg)?.0.value(i).as_bytes()),
+        DataType::LargeUtf8 => Ok(strip_varint_len(
+            downcast_string::<i64>(arg)?.0.value(i).as_bytes(),
+        )),
+        other => Err(DataFusionError::Internal(format!(
+            "Unsupported data type {:?} of the element to find",
+            other
+        ))),
+    }
+}
+
+fn list_elem_field(typ: &DataType) -> Result<&Field> {
+    match typ {
+        DataType::List(f) => Ok(f),
+        other => Err(DataFusionError::Execution(format!(
+            "Unsupported data type {:?} of arrays",
+            other
+        ))),
+    }
+}
+
+/// Returns the numbers of the elements of the arrays.
+/// length([1, 2]) = 2
+pub fn array_length(args: &[ArrayRef]) -> Result<UInt64Array> {
+    let list = downcast_array_args!(args[0], ListArray);
+    Ok((0..list.len())
+        .map(|i| list.is_valid(i).then(|| list.value_length(i) as u64))
+        .collect())
+}
+
+/// Returns the numbers of the characters of the utf-8 strings, the same as
+/// `character_length`.
+pub fn utf8_length(args: &[ArrayRef]) -> Result<Int32Array> {
+    let (string_array, _) = downcast_string::<i32>(&args[0])?;
+    Ok(string_array
+        .iter()
+        .map(|s| s.map(|s| s.chars().count() as i32))
+        .collect())
+}
+
+/// Returns the numbers of the characters of the large utf-8 strings, the same as
+/// `character_length`.
+pub fn large_utf8_length(args: &[ArrayRef]) -> Result<Int64Array> {
+    let (string_array, _) = downcast_string::<i64>(&args[0])?;
+    Ok(string_array
+        .iter()
+        .map(|s| s.map(|s| s.chars().count() as i64))
+        .collect())
+}
+
+/// Returns true if the array contains the element.
+/// has([1, 2], 2) = true
+pub fn array_has(args: &[ArrayRef]) -> Result<BooleanArray> {
+    let list = downcast_array_args!(args[0], ListArray);
+    let elem_type = list_elem_field(list.data_type())?.data_type();
+    let needles = match elem_type {
+        DataType::LargeUtf8 | DataType::FixedSizeBinary(_) => args[1].clone(),
+        _ => cast(&args[1], elem_type)?,
+    };
+    let mut rt = Vec::with_capacity(list.len());
+    for i in 0..list.len() {
+        if list.is_null(i) || needles.is_null(i) {
+            rt.push(None);
+            continue;
+        }
+        let elems = list.value(i);
+        let found = match elem_type {
+            DataType::LargeUtf8 => {
+                let needle = string_bytes_at(&needles, i)?;
+                let (elems, _) = downcast_string::<i64>(&elems)?;
+                elems
+                    .iter()
+                    .flatten()
+                    .any(|e| strip_varint_len(e.as_bytes()) == needle)
+            }
+            DataType::FixedSizeBinary(_) => {
+                let needle = downcast_array_args!(needles, FixedSizeBinaryArray).value(i);
+                let elems = downcast_array_args!(elems, FixedSizeBinaryArray);
+                (0..elems.len()).any(|j| elems.is_valid(j) && elems.value(j) == needle)
+            }
+            _ => {
+                let needle = ScalarValue::try_from_array(&needles, i)?;
+                let mut found = false;
+                for j in 0..elems.len() {
+                    if ScalarValue::try_from_array(&elems, j)? == needle {
+                        found = true;
+                        break;
+                    }
+                }
+                found
+            }
+        };
+        rt.push(Some(found));
+    }
+    Ok(BooleanArray::from(rt))
+}
+
+/// Returns the schema after the arrays in the column `col` are joined, in which the
+/// column is of the elements.
+pub fn array_join_schema(schema: &Schema, col: usize) -> Result<Schema> {
+    let mut fields = schema.fields().clone();
+    let f = &fields[col];
+    let ef = list_elem_field(f.data_type())?;
+    fields[col] = Field::new(f.name(), ef.data_type().clone(), ef.is_nullable());
+    Ok(Schema::new(fields))
+}
+
+/// Joins the arrays in the column `col` of the batch, i.e. every row is repeated
+/// for each element of its array, which takes the place of the array. The rows of
+/// the empty arrays are dropped.
+/// arrayJoin([1, 2]) = 1, 2
+pub fn array_join(batch: &RecordBatch, col: usize) -> Result<RecordBatch> {
+    let schema = array_join_schema(&batch.schema(), col)?;
+    let list = downcast_array_args!(batch.column(col), ListArray);
+    let los = list.value_offsets();
+    let mut idxs = Vec::new();
+    let mut eidxs = Vec::new();
+    for i in 0..list.len() {
+        if list.is_null(i) {
+            continue;
+        }
+        for j in los[i]..los[i + 1] {
+            idxs.push(i as u32);
+            eidxs.push(j as u32);
+        }
+    }
+    let idxs = UInt32Array::from(idxs);
+    let eidxs = UInt32Array::from(eidxs);
+    let mut cols = Vec::with_capacity(batch.num_columns());
+    for (k, c) in batch.columns().iter().enumerate() {
+        cols.push(if k == col {
+            take(list.values().as_ref(), &eidxs, None)?
+        } else {
+            take(c.as_ref(), &idxs, None)?
+        });
+    }
+    Ok(RecordBatch::try_new(Arc::new(schema), cols)?)
+}
+
+fn is_integer(typ: &DataType) -> bool {
+    matches!(
+        typ,
+        DataType::UInt8
+            | DataType::UInt16
+            | DataType::UInt32
+            | DataType::UInt64
+            | DataType::Int8
+            | DataType::Int16
+            | DataType::Int32
+            | DataType::Int64
+    )
+}
+
+/// Parses the IPv4 or IPv6 address, of which IPv4 is mapped into IPv6.
+fn parse_ipv6(bs: &[u8]) -> Option<Ipv6Addr> {
+    match std::str::from_utf8(bs).ok()?.parse::<IpAddr>().ok()? {
+        IpAddr::V4(ip) => Some(ip.to_ipv6_mapped()),
+        IpAddr::V6(ip) => Some(ip),
+    }
+}
+
+/// Parses the network in the CIDR notation into the IPv6 address and the prefix
+/// length, of which IPv4 is mapped into IPv6.
+fn parse_ipv6_cidr(bs: &[u8]) -> Result<(u128, u32)> {
+    let err = || {
+        DataFusionError::Execution(format!(
+            "Error parsing '{}' as CIDR",
+            String::from_utf8_lossy(bs)
+        ))
+    };
+    let s = std::str::from_utf8(bs).map_err(|_| err())?;
+    let (addr, len) = s.split_once('/').ok_or_else(err)?;
+    let len = len.parse::<u32>().map_err(|_| err())?;
+    match addr.parse::<IpAddr>().map_err(|_| err())? {
+        IpAddr::V4(ip) if len <= 32 => Ok((ip.to_ipv6_mapped().into(), len + 96)),
+        IpAddr::V6(ip) if len <= 128 => Ok((ip.into(), len)),
+        _ => Err(err()),
+    }
+}
+
+/// Converts the IPv4 addresses in numbers to large utf8 strings.
+/// IPv4NumToString(3232235521) = '192.168.0.1'
+pub fn ipv4_num_to_string(args: &[ArrayRef]) -> Result<GenericStringArray<i64>> {
+    let nums = cast(&args[0], &DataType::UInt32)?;
+    let nums = downcast_array_args!(nums, UInt32Array);
+    Ok(nums
+        .iter()
+        .map(|n| {
+            n.map(|n| {
+                let s = Ipv4Addr::from(n).to_string();
+                //the length is less than 0x80, so it is the varint in one byte
+                format!("{}{}", char::from(s.len() as u8), s)
+            })
+        })
+        .collect())
+}
+
+/// Converts the IPv4 addresses in strings to numbers, 0 for the invalid.
+/// IPv4StringToNum('192.168.0.1') = 3232235521
+pub fn ipv4_string_to_num(args: &[ArrayRef]) -> Result<UInt32Array> {
+    let mut rt = Vec::with_capacity(args[0].len());
+    for i in 0..args[0].len() {
+        rt.push(if args[0].is_null(i) {
+            None
+        } else {
+            let s = std::str::from_utf8(string_bytes_at(&args[0], i)?).ok();
+            Some(s.and_then(|s| s.parse::<Ipv4Addr>().ok()).map_or(0, u32::from))
+        });
+    }
+    Ok(UInt32Array::from(rt))
+}
+
+/// Converts the IPv4 or IPv6 addresses in strings to IPv6 in 16 bytes, `::` for
+/// the invalid.
+/// toIPv6('192.168.0.1') = '::ffff:192.168.0.1'
+pub fn string_to_ipv6(args: &[ArrayRef]) -> Result<FixedSizeBinaryArray> {
+    let mut rt = Vec::with_capacity(args[0].len());
+    for i in 0..args[0].len() {
+        rt.push(if args[0].is_null(i) {
+            None
+        } else {
+            let ip = parse_ipv6(string_bytes_at(&args[0], i)?);
+            Some(ip.unwrap_or(Ipv6Addr::UNSPECIFIED).octets())
+        });
+    }
+    FixedSizeBinaryArray::try_from_sparse_iter(rt.into_iter())
+        .map_err(DataFusionError::ArrowError)
+}
+
+/// Returns true if the address is in the network in the CIDR notation.
+/// isIPAddressInRange('127.0.0.1', '127.0.0.0/8') = true
+pub fn is_ip_address_in_range(args: &[ArrayRef]) -> Result<BooleanArray> {
+    let mut rt = Vec::with_capacity(args[0].len());
+    for i in 0..args[0].len() {
+        if args[0].is_null(i) || args[1].is_null(i) {
+            rt.push(None);
+            continue;
+        }
+        let bs = string_bytes_at(&args[0], i)?;
+        let addr: u128 = parse_ipv6(bs)
+            .ok_or_else(|| {
+                DataFusionError::Execution(format!(
+                    "Error parsing '{}' as IP address",
+                    String::from_utf8_lossy(bs)
+                ))
+            })?
+            .into();
+        let (net, len) = parse_ipv6_cidr(string_bytes_at(&args[1], i)?)?;
+        rt.push(Some(len == 0 || (addr ^ net) >> (128 - len) == 0));
+    }
+    Ok(BooleanArray::from(rt))
+}
+
+/// Returns the lower and the upper bounds of the IPv4 network of the address and
+/// the prefix length, in an array.
+/// IPv4CIDRToRange(3232235777, 16) = [3232235520, 3232301055]
+pub fn ipv4_cidr_to_range(args: &[ArrayRef]) -> Result<ListArray> {
+    let addrs = cast(&args[0], &DataType::UInt32)?;
+    let addrs = downcast_array_args!(addrs, UInt32Array);
+    let lens = cast(&args[1], &DataType::UInt8)?;
+    let lens = downcast_array_args!(lens, UInt8Array);
+    Ok(ListArray::from_iter_primitive::<UInt32Type, _, _>(
+        addrs.iter().zip(lens.iter()).map(|(addr, len)| {
+            let (addr, len) = (addr?, len?.min(32) as u32);
+            let mask = u32::MAX.checked_shl(32 - len).unwrap_or(0);
+            Some(vec![Some(addr & mask), Some(addr | !mask)])
+        }),
+    ))
+}
@@ -23,7 +23,9 @@ use arrow::compute::kernels::arithmetic::{
     add, divide, divide_scalar, modulus, modulus_scalar, multiply, subtract,
 };
 use arrow::compute::kernels::boolean::{and_kleene, not, or_kleene};
-use arrow::compute::kernels::comparison::{eq, gt, gt_eq, lt, lt_eq, neq};
+use arrow::compute::kernels::comparison::{
+    eq, gt, gt_eq, lt, lt_eq, neq, no_simd_compare_op,
+};
 use arrow::compute::kernels::comparison::{
     eq_bool, eq_bool_scalar, gt_bool, gt_bool_scalar, gt_eq_bool, gt_eq_bool_scalar,
     lt_bool, lt_bool_scalar, lt_eq_bool, lt_eq_bool_scalar, neq_bool, neq_bool_scalar,
@@ -40,7 +42,12 @@ use arrow::compute::kernels::comparison::{
     lt_eq_utf8_scalar, lt_utf8_scalar, neq_utf8_scalar, nlike_utf8_scalar,
     regexp_is_match_utf8_scalar,
 };
-use arrow::datatypes::{ArrowNumericType, DataType, Schema, TimeUnit};
+use arrow::compute::kernels::wide::{
+    add_decimal, add_wrapping, compare_decimal, divide_checked, divide_decimal,
+    modulus_checked, multiply_decimal, multiply_wrapping, subtract_decimal,
+    subtract_wrapping,
+};
+use arrow::datatypes::{ArrowPrimitiveType, DataType, Schema, TimeUnit};
 use arrow::record_batch::RecordBatch;
 
 use crate::error::{DataFusionError, Result};
@@ -50,7 +57,8 @@ use crate::physical_plan::{ColumnarValue, PhysicalExpr};
 use crate::scalar::ScalarValue;
 
 use super::coercion::{
-    eq_coercion, like_coercion, numerical_coercion, order_coercion, string_coercion,
+    eq_coercion, is_wide_or_decimal, like_coercion, numerical_coercion, order_coercion,
+    string_coercion,
 };
 
 /// Invoke a compute kernel on a data array and a scalar value
@@ -357,16 +365,16 @@ macro_rules! binary_array_op_scalar {
             DataType::Float64 => compute_op_scalar!($LEFT, $RIGHT, $OP, Float64Array),
             DataType::Utf8 => compute_utf8_op_scalar!($LEFT, $RIGHT, $OP, StringArray),
             DataType::LargeUtf8 => compute_largeutf8_op_scalar!($LEFT, $RIGHT, $OP, LargeStringArray),
-            DataType::Timestamp(TimeUnit::Nanosecond, None) => {
+            DataType::Timestamp(TimeUnit::Nanosecond, _) => {
                 compute_op_scalar!($LEFT, $RIGHT, $OP, TimestampNanosecondArray)
             }
-            DataType::Timestamp(TimeUnit::Microsecond, None) => {
+            DataType::Timestamp(TimeUnit::Microsecond, _) => {
                 compute_op_scalar!($LEFT, $RIGHT, $OP, TimestampMicrosecondArray)
             }
-            DataType::Timestamp(TimeUnit::Millisecond, None) => {
+            DataType::Timestamp(TimeUnit::Millisecond, _) => {
                 compute_op_scalar!($LEFT, $RIGHT, $OP, TimestampMillisecondArray)
             }
-            DataType::Timestamp(TimeUnit::Second, None) => {
+            DataType::Timestamp(TimeUnit::Second, _) => {
                 compute_op_scalar!($LEFT, $RIGHT, $OP, TimestampSecondArray)
             }
             DataType::Date16 => {
@@ -405,16 +413,16 @@ macro_rules! binary_array_op {
             DataType::Float32 => compute_op!($LEFT, $RIGHT, $OP, Float32Array),
             DataType::Float64 => compute_op!($LEFT, $RIGHT, $OP, Float64Array),
             DataType::Utf8 => compute_utf8_op!($LEFT, $RIGHT, $OP, StringArray),
-            DataType::Timestamp(TimeUnit::Nanosecond, None) => {
+            DataType::Timestamp(TimeUnit::Nanosecond, _) => {
                 compute_op!($LEFT, $RIGHT, $OP, TimestampNanosecondArray)
             }
-            DataType::Timestamp(TimeUnit::Microsecond, None) => {
+            DataType::Timestamp(TimeUnit::Microsecond, _) => {
                 compute_op!($LEFT, $RIGHT, $OP, TimestampMicrosecondArray)
             }
-            DataType::Timestamp(TimeUnit::Millisecond, None) => {
+            DataType::Timestamp(TimeUnit::Millisecond, _) => {
                 compute_op!($LEFT, $RIGHT, $OP, TimestampMillisecondArray)
             }
-            DataType::Timestamp(TimeUnit::Second, None) => {
+            DataType::Timestamp(TimeUnit::Second, _) => {
                 compute_op!($LEFT, $RIGHT, $OP, TimestampSecondArray)
             }
             DataType::Date32 => {
@@ -432,6 +440,55 @@ macro_rules! binary_array_op {
     }};
 }
 
+/// Invoke a compute kernel on a pair of the wide integer arrays, which are not
+/// `ArrowNumericType`s so only work with the non-SIMD kernels
+macro_rules! binary_wide_array_op {
+    ($LEFT:expr, $RIGHT:expr, $OP:ident) => {{
+        match $LEFT.data_type() {
+            DataType::Int128 => compute_op!($LEFT, $RIGHT, $OP, Int128Array),
+            DataType::UInt128 => compute_op!($LEFT, $RIGHT, $OP, UInt128Array),
+            DataType::Int256 => compute_op!($LEFT, $RIGHT, $OP, Int256Array),
+            DataType::UInt256 => compute_op!($LEFT, $RIGHT, $OP, UInt256Array),
+            other => Err(DataFusionError::Internal(format!(
+                "Data type {:?} not supported for binary operation '{}' on wide arrays",
+                other,
+                stringify!($OP)
+            ))),
+        }
+    }};
+}
+
+/// Invoke the comparison `$CMP` on a pair of the wide integer arrays
+macro_rules! binary_wide_cmp_op {
+    ($LEFT:expr, $RIGHT:expr, $CMP:expr) => {{
+        match $LEFT.data_type() {
+            DataType::Int128 => compute_cmp_op!($LEFT, $RIGHT, $CMP, Int128Array),
+            DataType::UInt128 => compute_cmp_op!($LEFT, $RIGHT, $CMP, UInt128Array),
+            DataType::Int256 => compute_cmp_op!($LEFT, $RIGHT, $CMP, Int256Array),
+            DataType::UInt256 => compute_cmp_op!($LEFT, $RIGHT, $CMP, UInt256Array),
+            other => Err(DataFusionError::Internal(format!(
+                "Data type {:?} not supported for comparison on wide arrays",
+                other
+            ))),
+        }
+    }};
+}
+
+/// Invoke the comparison `$CMP` on a pair of arrays by the `no_simd_compare_op`
+macro_rules! compute_cmp_op {
+    ($LEFT:expr, $RIGHT:expr, $CMP:expr, $DT:ident) => {{
+        let ll = $LEFT
+            .as_any()
+            .downcast_ref::<$DT>()
+            .expect("compute_op failed to downcast array");
+        let rr = $RIGHT
+            .as_any()
+            .downcast_ref::<$DT>()
+            .expect("compute_op failed to downcast array");
+        Ok(Arc::new(no_simd_compare_op(ll, rr, $CMP)?))
+    }};
+}
+
 /// Invoke a boolean kernel on a pair of arrays
 macro_rules! boolean_op {
     ($LEFT:expr, $RIGHT:expr, $OP:ident) => {{
@@ -655,6 +712,8 @@ impl PhysicalExpr for BinaryExpr {
 
         // Attempt to use special kernels if one input is scalar and the other is an array
         let scalar_result = match (&left_value, &right_value) {
+            //NOTE the wide integers and the decimals have no scalar kernels
+            _ if is_wide_or_decimal(&left_data_type) => None,
             (ColumnarValue::Array(array), ColumnarValue::Scalar(scalar)) => {
                 // if left is array and right is literal - use scalar operations
                 self.evaluate_array_scalar(array, scalar)?
@@ -781,6 +840,9 @@ impl BinaryExpr {
         right: Arc<dyn Array>,
         right_data_type: &DataType,
     ) -> Result<ArrayRef> {
+        if is_wide_or_decimal(left_data_type) {
+            return self.evaluate_wide(left, right);
+        }
         match &self.op {
             Operator::Like => binary_string_array_op!(left, right, like),
             Operator::NotLike => binary_string_array_op!(left, right, nlike),
@@ -835,6 +897,74 @@ impl BinaryExpr {
             }
         }
     }
+
+    /// Evaluate the expression on the wide integers or the decimals, of which
+    /// the arithmetic wraps around or fails on overflow respectively
+    fn evaluate_wide(&self, left: ArrayRef, right: ArrayRef) -> Result<ArrayRef> {
+        if let DataType::Decimal(precision, scale) = *left.data_type() {
+            return self.evaluate_decimal(&left, &right, precision, scale);
+        }
+        match &self.op {
+            Operator::Lt => binary_wide_cmp_op!(left, right, |a, b| a < b),
+            Operator::LtEq => binary_wide_cmp_op!(left, right, |a, b| a <= b),
+            Operator::Gt => binary_wide_cmp_op!(left, right, |a, b| a > b),
+            Operator::GtEq => binary_wide_cmp_op!(left, right, |a, b| a >= b),
+            Operator::Eq => binary_wide_cmp_op!(left, right, |a, b| a == b),
+            Operator::NotEq => binary_wide_cmp_op!(left, right, |a, b| a != b),
+            Operator::IsDistinctFrom => {
+                binary_wide_array_op!(left, right, is_distinct_from)
+            }
+            Operator::IsNotDistinctFrom => {
+                binary_wide_array_op!(left, right, is_not_distinct_from)
+            }
+            Operator::Plus => binary_wide_array_op!(left, right, add_wrapping),
+            Operator::Minus => binary_wide_array_op!(left, right, subtract_wrapping),
+            Operator::Multiply => binary_wide_array_op!(left, right, multiply_wrapping),
+            Operator::Divide => binary_wide_array_op!(left, right, divide_checked),
+            Operator::Modulo => binary_wide_array_op!(left, right, modulus_checked),
+            op => Err(DataFusionError::Internal(format!(
+                "Cannot evaluate binary expression {:?} with type {:?}",
+                op,
+                left.data_type()
+            ))),
+        }
+    }
+
+    fn evaluate_decimal(
+        &self,
+        left: &ArrayRef,
+        right: &ArrayRef,
+        precision: usize,
+        scale: usize,
+    ) -> Result<ArrayRef> {
+        let ll = left
+            .as_any()
+            .downcast_ref::<DecimalArray>()
+            .expect("evaluate_decimal failed to downcast array");
+        let rr = right
+            .as_any()
+            .downcast_ref::<DecimalArray>()
+            .expect("evaluate_decimal failed to downcast array");
+        let result: ArrayRef = match &self.op {
+            Operator::Lt => Arc::new(compare_decimal(ll, rr, |a, b| a < b)?),
+            Operator::LtEq => Arc::new(compare_decimal(ll, rr, |a, b| a <= b)?),
+            Operator::Gt => Arc::new(compare_decimal(ll, rr, |a, b| a > b)?),
+            Operator::GtEq => Arc::new(compare_decimal(ll, rr, |a, b| a >= b)?),
+            Operator::Eq => Arc::new(compare_decimal(ll, rr, |a, b| a == b)?),
+            Operator::NotEq => Arc::new(compare_decimal(ll, rr, |a, b| a != b)?),
+            Operator::Plus => Arc::new(add_decimal(ll, rr, precision, scale)?),
+            Operator::Minus => Arc::new(subtract_decimal(ll, rr, precision, scale)?),
+            Operator::Multiply => Arc::new(multiply_decimal(ll, rr, precision, scale)?),
+            Operator::Divide => Arc::new(divide_decimal(ll, rr, precision, scale)?),
+            op => {
+                return Err(DataFusionError::NotImplemented(format!(
+                    "Binary expression {:?} on decimals is not supported",
+                    op
+                )))
+            }
+        };
+        Ok(result)
+    }
 }
 
 fn is_distinct_from<T>(
@@ -842,7 +972,7 @@ fn is_distinct_from<T>(
     right: &PrimitiveArray<T>,
 ) -> Result<BooleanArray>
 where
-    T: ArrowNumericType,
+    T: ArrowPrimitiveType,
 {
     Ok(left
         .iter()
@@ -867,7 +997,7 @@ fn is_not_distinct_from<T>(
     right: &PrimitiveArray<T>,
 ) -> Result<BooleanArray>
 where
-    T: ArrowNumericType,
+    T: ArrowPrimitiveType,
 {
     Ok(left
         .iter()
@@ -1446,6 +1576,55 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn wide_and_decimal_op() -> Result<()> {
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::Int128, false),
+            Field::new("b", DataType::Int64, false),
+            Field::new("c", DataType::Decimal(10, 2), false),
+            Field::new("d", DataType::Decimal(12, 4), false),
+        ]);
+        let a = Int128Array::from(vec![i128::MAX, -3, 1 << 100]);
+        let b = Int64Array::from(vec![1, 2, 3]);
+        let mut c = DecimalBuilder::new(3, 10, 2);
+        let mut d = DecimalBuilder::new(3, 12, 4);
+        for (x, y) in [(12345, 1234500), (-1, 1), (7, -70000)] {
+            c.append_value(x)?;
+            d.append_value(y)?;
+        }
+        let batch = RecordBatch::try_new(
+            Arc::new(schema.clone()),
+            vec![
+                Arc::new(a),
+                Arc::new(b),
+                Arc::new(c.finish()),
+                Arc::new(d.finish()),
+            ],
+        )?;
+
+        let eval = |l: &str, op: Operator, r: &str| -> Result<String> {
+            let expr = binary(col(l, &schema)?, op, col(r, &schema)?, &schema)?;
+            array_to_string(&expr.evaluate(&batch)?.into_array(batch.num_rows()))
+        };
+        assert_eq!(
+            eval("a", Operator::Plus, "b")?,
+            "-170141183460469231731687303715884105728\n-1\n1267650600228229401496703205379"
+        );
+        assert_eq!(eval("a", Operator::Lt, "b")?, "false\ntrue\nfalse");
+        assert_eq!(
+            eval("c", Operator::Plus, "d")?,
+            "246.9000\n-0.0099\n-6.9300"
+        );
+        assert_eq!(eval("c", Operator::Eq, "d")?, "true\nfalse\nfalse");
+        assert_eq!(eval("c", Operator::Minus, "b")?, "122.45\n-2.01\n-2.93");
+        assert_eq!(
+            eval("c", Operator::Divide, "d")?,
+            "1.0000\n-100.0000\n-0.0100"
+        );
+
+        Ok(())
+    }
+
     fn apply_arithmetic<T: ArrowNumericType>(
         schema: SchemaRef,
         data: Vec<ArrayRef>,
@@ -17,7 +17,7 @@
 
 //! Coercion rules used to coerce types to match existing expressions' implementations
 
-use arrow::datatypes::DataType;
+use arrow::datatypes::{DataType, TimeUnit};
 
 /// Determine if a DataType is signed numeric or not
 pub fn is_signed_numeric(dt: &DataType) -> bool {
@@ -109,6 +109,86 @@ pub fn temporal_coercion(lhs_type: &DataType, rhs_type: &DataType) -> Option<Dat
         (Date32, Utf8) => Some(Date32),
         (Utf8, Date64) => Some(Date64),
         (Date64, Utf8) => Some(Date64),
+        (Timestamp(lu, ltz), Timestamp(ru, rtz)) => {
+            let unit = if timeunit_nanos(lu) <= timeunit_nanos(ru) {
+                lu.clone()
+            } else {
+                ru.clone()
+            };
+            Some(Timestamp(unit, ltz.clone().or_else(|| rtz.clone())))
+        }
+        _ => None,
+    }
+}
+
+/// The nanoseconds of one tick of a time unit
+fn timeunit_nanos(unit: &TimeUnit) -> i64 {
+    match unit {
+        TimeUnit::Second => 1_000_000_000,
+        TimeUnit::Millisecond => 1_000_000,
+        TimeUnit::Microsecond => 1_000,
+        TimeUnit::Nanosecond => 1,
+    }
+}
+
+/// Determine if a DataType is a wide integer or a decimal, which are computed
+/// by the dedicated kernels
+pub fn is_wide_or_decimal(dt: &DataType) -> bool {
+    DataType::is_wide_integer(dt) || matches!(dt, DataType::Decimal(_, _))
+}
+
+/// The max number of the decimal digits of an integer type
+fn integer_digits(dt: &DataType) -> Option<usize> {
+    use arrow::datatypes::DataType::*;
+    match dt {
+        Int8 | UInt8 => Some(3),
+        Int16 | UInt16 => Some(5),
+        Int32 | UInt32 => Some(10),
+        Int64 => Some(19),
+        UInt64 => Some(20),
+        Int128 | UInt128 => Some(39),
+        _ => None,
+    }
+}
+
+/// Coercion rule for the wide integers and the decimals, of which at least one
+/// side is: the integers are widened to hold both sides, and the decimals keep
+/// the larger scale and integral digits of both sides. The floats win them all.
+fn wide_coercion(lhs_type: &DataType, rhs_type: &DataType) -> Option<DataType> {
+    use arrow::datatypes::DataType::*;
+    match (lhs_type, rhs_type) {
+        (Decimal(lp, ls), Decimal(rp, rs)) => {
+            let scale = *ls.max(rs);
+            let precision = (lp - ls).max(rp - rs) + scale;
+            Some(Decimal(precision.min(76), scale))
+        }
+        (Decimal(p, s), t) | (t, Decimal(p, s)) => match t {
+            Float32 | Float64 => Some(Float64),
+            _ => {
+                let digits = integer_digits(t)?;
+                Some(Decimal(((p - s).max(digits) + s).min(76), *s))
+            }
+        },
+        (Float32 | Float64, _) | (_, Float32 | Float64) => Some(Float64),
+        _ if !is_numeric(lhs_type) && !DataType::is_wide_integer(lhs_type) => None,
+        _ if !is_numeric(rhs_type) && !DataType::is_wide_integer(rhs_type) => None,
+        (Int256, _) | (_, Int256) => Some(Int256),
+        (UInt256, t) | (t, UInt256) => {
+            if is_signed_numeric(t) {
+                Some(Int256)
+            } else {
+                Some(UInt256)
+            }
+        }
+        (Int128, UInt128) | (UInt128, Int128) => Some(Int256),
+        (Int128, _) | (_, Int128) => Some(Int128),
+        (UInt128, t) | (t, UInt128) => {
+            if is_signed_numeric(t) {
+                Some(Int128)
+            } else {
+                Some(UInt128)
+            }
+        }
         _ => None,
     }
 }
@@ -119,6 +199,10 @@ pub fn temporal_coercion(lhs_type: &DataType, rhs_type: &DataType) -> Option<Dat
 pub fn numerical_coercion(lhs_type: &DataType, rhs_type: &DataType) -> Option<DataType> {
     use arrow::datatypes::DataType::*;
 
+    if is_wide_or_decimal(lhs_type) || is_wide_or_decimal(rhs_type) {
+        return wide_coercion(lhs_type, rhs_type);
+    }
+
     // error on any non-numeric type
     if !is_numeric(lhs_type) || !is_numeric(rhs_type) {
         return None;
@@ -197,4 +281,54 @@ mod tests {
         let rhs_type = Dictionary(Box::new(Int8), Box::new(Utf8));
         assert_eq!(dictionary_coercion(&lhs_type, &rhs_type), Some(Utf8));
     }
+
+    #[test]
+    fn test_wide_type_coersion() {
+        use DataType::*;
+
+        assert_eq!(numerical_coercion(&Int128, &Int64), Some(Int128));
+        assert_eq!(numerical_coercion(&UInt128, &UInt8), Some(UInt128));
+        assert_eq!(numerical_coercion(&UInt128, &Int8), Some(Int128));
+        assert_eq!(numerical_coercion(&UInt128, &Int128), Some(Int256));
+        assert_eq!(numerical_coercion(&UInt256, &UInt64), Some(UInt256));
+        assert_eq!(numerical_coercion(&Int32, &UInt256), Some(Int256));
+        assert_eq!(numerical_coercion(&Int256, &Float32), Some(Float64));
+        assert_eq!(numerical_coercion(&Int256, &Utf8), None);
+
+        assert_eq!(
+            numerical_coercion(&Decimal(10, 2), &Decimal(20, 6)),
+            Some(Decimal(20, 6))
+        );
+        assert_eq!(
+            numerical_coercion(&Decimal(76, 2), &Decimal(40, 10)),
+            Some(Decimal(76, 10))
+        );
+        assert_eq!(
+            numerical_coercion(&Int64, &Decimal(9, 2)),
+            Some(Decimal(21, 2))
+        );
+        assert_eq!(numerical_coercion(&Decimal(9, 2), &Float64), Some(Float64));
+        assert_eq!(numerical_coercion(&Decimal(9, 2), &Int256), None);
+    }
+
+    #[test]
+    fn test_timestamp_type_coersion() {
+        use DataType::*;
+
+        let tz = Some("+08:00".to_string());
+        assert_eq!(
+            temporal_coercion(
+                &Timestamp(TimeUnit::Second, None),
+                &Timestamp(TimeUnit::Millisecond, tz.clone())
+            ),
+            Some(Timestamp(TimeUnit::Millisecond, tz.clone()))
+        );
+        assert_eq!(
+            temporal_coercion(
+                &Timestamp(TimeUnit::Nanosecond, tz.clone()),
+                &Timestamp(TimeUnit::Microsecond, None)
+            ),
+            Some(Timestamp(TimeUnit::Nanosecond, tz))
+        );
+    }
 }
@@ -28,10 +28,11 @@ use arrow::compute;
 use arrow::datatypes::{DataType, TimeUnit};
 use arrow::{
     array::{
-        ArrayRef, Date32Array, Date64Array, Float32Array, Float64Array, Int16Array,
-        Int32Array, Int64Array, Int8Array, LargeStringArray, StringArray,
-        TimestampMicrosecondArray, TimestampMillisecondArray, TimestampNanosecondArray,
-        TimestampSecondArray, UInt16Array, UInt32Array, UInt64Array, UInt8Array,
+        ArrayRef, Date32Array, Date64Array, Float32Array, Float64Array, Int128Array,
+        Int16Array, Int256Array, Int32Array, Int64Array, Int8Array, LargeStringArray,
+        StringArray, TimestampMicrosecondArray, TimestampMillisecondArray,
+        TimestampNanosecondArray, TimestampSecondArray, UInt128Array, UInt16Array,
+        UInt256Array, UInt32Array, UInt64Array, UInt8Array,
     },
     datatypes::Field,
 };
@@ -166,11 +167,37 @@ macro_rules! typed_min_max_batch_decimal128 {
     }};
 }
 
+// Statically-typed version of min/max(array) -> ScalarValue for the decimals
+// which do not fit in i128.
+macro_rules! typed_min_max_batch_decimal256 {
+    ($VALUES:expr, $PRECISION:ident, $SCALE:ident, $OP:ident) => {{
+        let array = $VALUES.as_any().downcast_ref::<DecimalArray>().unwrap();
+        let value = (0..array.len())
+            .filter(|i| array.is_valid(*i))
+            .map(|i| array.value_i256(i))
+            .reduce(|a, b| a.$OP(b));
+        ScalarValue::Decimal256(value.map(Box::new), *$PRECISION, *$SCALE)
+    }};
+}
+
+// Statically-typed version of min/max(array) -> ScalarValue for the wide
+// integers, which have no SIMD kernels.
+macro_rules! typed_min_max_batch_wide {
+    ($VALUES:expr, $ARRAYTYPE:ident, $SCALAR:ident, $OP:ident) => {{
+        let array = $VALUES.as_any().downcast_ref::<$ARRAYTYPE>().unwrap();
+        let value = array.iter().flatten().reduce(|a, b| a.$OP(b));
+        ScalarValue::$SCALAR(value)
+    }};
+}
+
 // Statically-typed version of min/max(array) -> ScalarValue  for non-string types.
 // this is a macro to support both operations (min and max).
 macro_rules! min_max_batch {
     ($VALUES:expr, $OP:ident) => {{
         match $VALUES.data_type() {
+            DataType::Decimal(precision, scale) if *precision > 38 => {
+                typed_min_max_batch_decimal256!($VALUES, precision, scale, $OP)
+            }
             DataType::Decimal(precision, scale) => {
                 typed_min_max_batch_decimal128!($VALUES, precision, scale, $OP)
             }
@@ -189,6 +216,18 @@ macro_rules! min_max_batch {
             DataType::UInt32 => typed_min_max_batch!($VALUES, UInt32Array, UInt32, $OP),
             DataType::UInt16 => typed_min_max_batch!($VALUES, UInt16Array, UInt16, $OP),
             DataType::UInt8 => typed_min_max_batch!($VALUES, UInt8Array, UInt8, $OP),
+            DataType::Int128 => {
+                typed_min_max_batch_wide!($VALUES, Int128Array, Int128, $OP)
+            }
+            DataType::UInt128 => {
+                typed_min_max_batch_wide!($VALUES, UInt128Array, UInt128, $OP)
+            }
+            DataType::Int256 => {
+                typed_min_max_batch_wide!($VALUES, Int256Array, Int256, $OP)
+            }
+            DataType::UInt256 => {
+                typed_min_max_batch_wide!($VALUES, UInt256Array, UInt256, $OP)
+            }
             DataType::Timestamp(TimeUnit::Second, _) => {
                 typed_min_max_batch!($VALUES, TimestampSecondArray, TimestampSecond, $OP)
             }
@@ -301,6 +340,17 @@ macro_rules! min_max {
                 )));
                 }
             }
+            (
+                ScalarValue::Decimal256(lhsv, lhsp, lhss),
+                ScalarValue::Decimal256(rhsv, rhsp, rhss),
+            ) if lhsp == rhsp && lhss == rhss => ScalarValue::Decimal256(
+                match (lhsv, rhsv) {
+                    (Some(a), Some(b)) => Some(a.clone().$OP(b.clone())),
+                    (a, b) => a.clone().or_else(|| b.clone()),
+                },
+                *lhsp,
+                *lhss,
+            ),
             (ScalarValue::Float64(lhs), ScalarValue::Float64(rhs)) => {
                 typed_min_max!(lhs, rhs, Float64, $OP)
             }
@@ -331,6 +381,18 @@ macro_rules! min_max {
             (ScalarValue::Int8(lhs), ScalarValue::Int8(rhs)) => {
                 typed_min_max!(lhs, rhs, Int8, $OP)
             }
+            (ScalarValue::Int128(lhs), ScalarValue::Int128(rhs)) => {
+                typed_min_max!(lhs, rhs, Int128, $OP)
+            }
+            (ScalarValue::UInt128(lhs), ScalarValue::UInt128(rhs)) => {
+                typed_min_max!(lhs, rhs, UInt128, $OP)
+            }
+            (ScalarValue::Int256(lhs), ScalarValue::Int256(rhs)) => {
+                typed_min_max!(lhs, rhs, Int256, $OP)
+            }
+            (ScalarValue::UInt256(lhs), ScalarValue::UInt256(rhs)) => {
+                typed_min_max!(lhs, rhs, UInt256, $OP)
+            }
             (ScalarValue::Utf8(lhs), ScalarValue::Utf8(rhs)) => {
                 typed_min_max_string!(lhs, rhs, Utf8, $OP)
             }
@@ -552,6 +614,7 @@ mod tests {
     use arrow::array::DecimalBuilder;
     use arrow::datatypes::*;
     use arrow::record_batch::RecordBatch;
+    use std::str::FromStr;
 
     #[test]
     fn min_decimal() -> Result<()> {
@@ -705,6 +768,39 @@ mod tests {
         )
     }
 
+    #[test]
+    fn max_decimal256() -> Result<()> {
+        let big = i256::from_str("-12345678901234567890123456789012345678901").unwrap();
+        let mut decimal_builder = DecimalBuilder::new(3, 50, 2);
+        decimal_builder.append_i256(big)?;
+        decimal_builder.append_null()?;
+        decimal_builder.append_i256(big - i256::ONE)?;
+        let array: ArrayRef = Arc::new(decimal_builder.finish());
+        generic_test_op!(
+            array,
+            DataType::Decimal(50, 2),
+            Max,
+            ScalarValue::Decimal256(Some(Box::new(big)), 50, 2),
+            DataType::Decimal(50, 2)
+        )
+    }
+
+    #[test]
+    fn min_wide() -> Result<()> {
+        let a: ArrayRef = Arc::new(UInt256Array::from(vec![
+            Some(u256::MAX),
+            None,
+            Some(u256::ONE),
+        ]));
+        generic_test_op!(
+            a,
+            DataType::UInt256,
+            Min,
+            ScalarValue::UInt256(Some(u256::ONE)),
+            DataType::UInt256
+        )
+    }
+
     #[test]
     fn max_decimal_all_nulls() -> Result<()> {
         let mut decimal_builder = DecimalBuilder::new(5, 10, 0);
@@ -25,11 +25,12 @@ use crate::error::{DataFusionError, Result};
 use crate::physical_plan::{Accumulator, AggregateExpr, PhysicalExpr};
 use crate::scalar::ScalarValue;
 use arrow::compute;
-use arrow::datatypes::DataType;
+use arrow::datatypes::{i256, DataType};
 use arrow::{
     array::{
-        ArrayRef, Float32Array, Float64Array, Int16Array, Int32Array, Int64Array,
-        Int8Array, UInt16Array, UInt32Array, UInt64Array, UInt8Array,
+        Array, ArrayRef, DecimalArray, Float32Array, Float64Array, Int128Array,
+        Int16Array, Int256Array, Int32Array, Int64Array, Int8Array, UInt128Array,
+        UInt16Array, UInt256Array, UInt32Array, UInt64Array, UInt8Array,
     },
     datatypes::Field,
 };
@@ -56,6 +57,14 @@ pub fn sum_return_type(arg_type: &DataType) -> Result<DataType> {
         }
         DataType::Float32 => Ok(DataType::Float32),
         DataType::Float64 => Ok(DataType::Float64),
+        DataType::Int128 | DataType::UInt128 | DataType::Int256 | DataType::UInt256 => {
+            Ok(arg_type.clone())
+        }
+        //NOTE like ClickHouse, the sum of decimals is in Decimal128 or Decimal256
+        DataType::Decimal(precision, scale) if *precision <= 38 => {
+            Ok(DataType::Decimal(38, *scale))
+        }
+        DataType::Decimal(_, scale) => Ok(DataType::Decimal(76, *scale)),
         other => Err(DataFusionError::Plan(format!(
             "SUM does not support type \"{:?}\"",
             other
@@ -76,6 +85,11 @@ pub(crate) fn is_sum_support_arg_type(arg_type: &DataType) -> bool {
             | DataType::Int64
             | DataType::Float32
             | DataType::Float64
+            | DataType::Int128
+            | DataType::UInt128
+            | DataType::Int256
+            | DataType::UInt256
+            | DataType::Decimal(_, _)
     )
 }
 
@@ -153,6 +167,36 @@ macro_rules! typed_sum_delta_batch {
     }};
 }
 
+// returns the wrapping sum of a wide integer array, which has no SIMD kernel
+macro_rules! typed_wide_sum_delta_batch {
+    ($VALUES:expr, $ARRAYTYPE:ident, $SCALAR:ident) => {{
+        let array = $VALUES.as_any().downcast_ref::<$ARRAYTYPE>().unwrap();
+        let delta = array.iter().flatten().reduce(|a, b| a.wrapping_add(b));
+        ScalarValue::$SCALAR(delta)
+    }};
+}
+
+fn decimal_overflow() -> DataFusionError {
+    DataFusionError::Execution("Decimal math overflow on sum".to_string())
+}
+
+// sums the decimal array into a Decimal256, which is narrowed into the type of
+// the sum when adding to it
+fn sum_decimal_batch(values: &ArrayRef, scale: usize) -> Result<ScalarValue> {
+    let array = values.as_any().downcast_ref::<DecimalArray>().unwrap();
+    let mut delta = None;
+    for i in 0..array.len() {
+        if array.is_valid(i) {
+            let v = array.value_i256(i);
+            delta = Some(match delta {
+                None => v,
+                Some(d) => v.checked_add(d).ok_or_else(decimal_overflow)?,
+            });
+        }
+    }
+    Ok(ScalarValue::Decimal256(delta.map(Box::new), 76, scale))
+}
+
 // sums the array and returns a ScalarValue of its corresponding type.
 pub(super) fn sum_batch(values: &ArrayRef) -> Result<ScalarValue> {
     Ok(match values.data_type() {
@@ -166,6 +210,11 @@ pub(super) fn sum_batch(values: &ArrayRef) -> Result<ScalarValue> {
         DataType::UInt32 => typed_sum_delta_batch!(values, UInt32Array, UInt32),
         DataType::UInt16 => typed_sum_delta_batch!(values, UInt16Array, UInt16),
         DataType::UInt8 => typed_sum_delta_batch!(values, UInt8Array, UInt8),
+        DataType::Int128 => typed_wide_sum_delta_batch!(values, Int128Array, Int128),
+        DataType::UInt128 => typed_wide_sum_delta_batch!(values, UInt128Array, UInt128),
+        DataType::Int256 => typed_wide_sum_delta_batch!(values, Int256Array, Int256),
+        DataType::UInt256 => typed_wide_sum_delta_batch!(values, UInt256Array, UInt256),
+        DataType::Decimal(_, scale) => sum_decimal_batch(values, *scale)?,
         e => {
             return Err(DataFusionError::Internal(format!(
                 "Sum is not expected to receive the type {:?}",
@@ -187,6 +236,56 @@ macro_rules! typed_sum {
     }};
 }
 
+// returns the wrapping sum of two wide integer scalar values of the same type
+macro_rules! typed_wide_sum {
+    ($OLD_VALUE:expr, $DELTA:expr, $SCALAR:ident) => {{
+        ScalarValue::$SCALAR(match ($OLD_VALUE, $DELTA) {
+            (None, None) => None,
+            (Some(a), None) => Some(*a),
+            (None, Some(b)) => Some(*b),
+            (Some(a), Some(b)) => Some(a.wrapping_add(*b)),
+        })
+    }};
+}
+
+fn decimal_value(v: &ScalarValue) -> Option<(Option<i256>, usize)> {
+    match v {
+        ScalarValue::Decimal128(v, _, s) => Some((v.map(i256::from_half), *s)),
+        ScalarValue::Decimal256(v, _, s) => Some((v.as_deref().copied(), *s)),
+        _ => None,
+    }
+}
+
+// returns the sum of two decimal scalar values in the type of `lhs`, which errs
+// on overflow
+fn sum_decimal(lhs: &ScalarValue, rhs: &ScalarValue) -> Result<ScalarValue> {
+    let (a, b) = match (decimal_value(lhs), decimal_value(rhs)) {
+        (Some((a, ls)), Some((b, rs))) if ls == rs => (a, b),
+        _ => {
+            return Err(DataFusionError::Internal(format!(
+                "Sum is not expected to receive scalars of incompatible types {:?}",
+                (lhs, rhs)
+            )))
+        }
+    };
+    let v = match (a, b) {
+        (Some(a), Some(b)) => Some(a.checked_add(b).ok_or_else(decimal_overflow)?),
+        (a, b) => a.or(b),
+    };
+    Ok(match lhs {
+        ScalarValue::Decimal128(_, p, s) => ScalarValue::Decimal128(
+            v.map(|v| v.to_half().ok_or_else(decimal_overflow))
+                .transpose()?,
+            *p,
+            *s,
+        ),
+        ScalarValue::Decimal256(_, p, s) => {
+            ScalarValue::Decimal256(v.map(Box::new), *p, *s)
+        }
+        _ => unreachable!(),
+    })
+}
+
 pub(super) fn sum(lhs: &ScalarValue, rhs: &ScalarValue) -> Result<ScalarValue> {
     Ok(match (lhs, rhs) {
         // float64 coerces everything to f64
@@ -250,6 +349,23 @@ pub(super) fn sum(lhs: &ScalarValue, rhs: &ScalarValue) -> Result<ScalarValue> {
         (ScalarValue::Int64(lhs), ScalarValue::Int8(rhs)) => {
             typed_sum!(lhs, rhs, Int64, i64)
         }
+        // the wide integers wrap around on overflow
+        (ScalarValue::Int128(lhs), ScalarValue::Int128(rhs)) => {
+            typed_wide_sum!(lhs, rhs, Int128)
+        }
+        (ScalarValue::UInt128(lhs), ScalarValue::UInt128(rhs)) => {
+            typed_wide_sum!(lhs, rhs, UInt128)
+        }
+        (ScalarValue::Int256(lhs), ScalarValue::Int256(rhs)) => {
+            typed_wide_sum!(lhs, rhs, Int256)
+        }
+        (ScalarValue::UInt256(lhs), ScalarValue::UInt256(rhs)) => {
+            typed_wide_sum!(lhs, rhs, UInt256)
+        }
+        (
+            ScalarValue::Decimal128(..) | ScalarValue::Decimal256(..),
+            ScalarValue::Decimal128(..) | ScalarValue::Decimal256(..),
+        ) => sum_decimal(lhs, rhs)?,
         e => {
             return Err(DataFusionError::Internal(format!(
                 "Sum is not expected to receive a scalar {:?}",
@@ -380,6 +496,54 @@ mod tests {
         )
     }
 
+    #[test]
+    fn sum_i128_wrapping() -> Result<()> {
+        let a: ArrayRef =
+            Arc::new(Int128Array::from(vec![Some(i128::MAX), None, Some(2)]));
+        generic_test_op!(
+            a,
+            DataType::Int128,
+            Sum,
+            ScalarValue::Int128(Some(i128::MIN + 1)),
+            DataType::Int128
+        )
+    }
+
+    #[test]
+    fn sum_decimal() -> Result<()> {
+        let mut builder = arrow::array::DecimalBuilder::new(3, 10, 2);
+        builder.append_value(9_999_999_999)?;
+        builder.append_null()?;
+        builder.append_value(-1)?;
+        let a: ArrayRef = Arc::new(builder.finish());
+        generic_test_op!(
+            a,
+            DataType::Decimal(10, 2),
+            Sum,
+            ScalarValue::Decimal128(Some(9_999_999_998), 38, 2),
+            DataType::Decimal(38, 2)
+        )
+    }
+
+    #[test]
+    fn sum_decimal_overflow() -> Result<()> {
+        let lhs = ScalarValue::Decimal128(Some(i128::MAX), 38, 0);
+        let rhs = ScalarValue::Decimal128(Some(1), 38, 0);
+        assert!(sum(&lhs, &rhs).is_err());
+
+        let lhs =
+            ScalarValue::Decimal256(Some(Box::new(i256::from_half(i128::MAX))), 76, 0);
+        assert_eq!(
+            sum(&lhs, &rhs)?,
+            ScalarValue::Decimal256(
+                Some(Box::new(i256::from_half(i128::MAX) + i256::ONE)),
+                76,
+                0
+            )
+        );
+        Ok(())
+    }
+
     fn aggregate(
         batch: &RecordBatch,
         agg: Arc<dyn AggregateExpr>,
@@ -445,7 +445,7 @@ impl FromStr for BuiltinScalarFunction {
             "date_trunc" | "datetrunc" => BuiltinScalarFunction::DateTrunc,
             "initcap" => BuiltinScalarFunction::InitCap,
             "left" => BuiltinScalarFunction::Left,
-            "length" => BuiltinScalarFunction::CharacterLength,
+            "length" => clickhouse::BuiltinScalarFunction::Length.into(),
             "lower" => BuiltinScalarFunction::Lower,
             "lpad" => BuiltinScalarFunction::Lpad,
             "ltrim" => BuiltinScalarFunction::Ltrim,
@@ -479,6 +479,9 @@ impl FromStr for BuiltinScalarFunction {
             "trim" => BuiltinScalarFunction::Trim,
             "upper" => BuiltinScalarFunction::Upper,
             "regexp_match" => BuiltinScalarFunction::RegexpMatch,
+
+            // clickhouse array functions
+            "has" => clickhouse::BuiltinScalarFunction::Has.into(),
             _ => {
                 return Err(DataFusionError::Plan(format!(
                     "There is no built-in function named {}",
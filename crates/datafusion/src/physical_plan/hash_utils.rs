@@ -20,14 +20,16 @@
 use crate::error::{DataFusionError, Result};
 use ahash::{CallHasher, RandomState};
 use arrow::array::{
-    Array, ArrayRef, BooleanArray, Date32Array, Date64Array, DictionaryArray,
-    Float32Array, Float64Array, Int16Array, Int32Array, Int64Array, Int8Array,
-    LargeStringArray, StringArray, TimestampMicrosecondArray, TimestampMillisecondArray,
-    TimestampNanosecondArray, UInt16Array, UInt32Array, UInt64Array, UInt8Array,
+    Array, ArrayRef, BooleanArray, Date32Array, Date64Array, DecimalArray,
+    DictionaryArray, Float32Array, Float64Array, Int128Array, Int16Array, Int256Array,
+    Int32Array, Int64Array, Int8Array, LargeStringArray, StringArray,
+    TimestampMicrosecondArray, TimestampMillisecondArray, TimestampNanosecondArray,
+    TimestampSecondArray, UInt128Array, UInt16Array, UInt256Array, UInt32Array,
+    UInt64Array, UInt8Array,
 };
 use arrow::datatypes::{
-    ArrowDictionaryKeyType, ArrowNativeType, DataType, Int16Type, Int32Type, Int64Type,
-    Int8Type, TimeUnit, UInt16Type, UInt32Type, UInt64Type, UInt8Type,
+    i256, u256, ArrowDictionaryKeyType, ArrowNativeType, DataType, Int16Type, Int32Type,
+    Int64Type, Int8Type, TimeUnit, UInt16Type, UInt32Type, UInt64Type, UInt8Type,
 };
 use std::sync::Arc;
 
@@ -168,6 +170,27 @@ macro_rules! hash_array_float {
     };
 }
 
+/// Hash the values in a decimal array by their i256 representation, which does
+/// not panic like the i128 one for the large precisions
+fn create_hashes_decimal(
+    array: &ArrayRef,
+    random_state: &RandomState,
+    hashes_buffer: &mut Vec<u64>,
+    multi_col: bool,
+) {
+    let array = array.as_any().downcast_ref::<DecimalArray>().unwrap();
+    for (i, hash) in hashes_buffer.iter_mut().enumerate() {
+        if !array.is_null(i) {
+            let h = i256::get_hash(&array.value_i256(i), random_state);
+            *hash = if multi_col {
+                combine_hashes(h, *hash)
+            } else {
+                h
+            };
+        }
+    }
+}
+
 /// Hash the values in a dictionary array
 fn create_hashes_dictionary<K: ArrowDictionaryKeyType>(
     array: &ArrayRef,
@@ -331,6 +354,49 @@ pub fn create_hashes<'a>(
                     multi_col
                 );
             }
+            DataType::Int128 => {
+                hash_array_primitive!(
+                    Int128Array,
+                    col,
+                    i128,
+                    hashes_buffer,
+                    random_state,
+                    multi_col
+                );
+            }
+            DataType::UInt128 => {
+                hash_array_primitive!(
+                    UInt128Array,
+                    col,
+                    u128,
+                    hashes_buffer,
+                    random_state,
+                    multi_col
+                );
+            }
+            DataType::Int256 => {
+                hash_array_primitive!(
+                    Int256Array,
+                    col,
+                    i256,
+                    hashes_buffer,
+                    random_state,
+                    multi_col
+                );
+            }
+            DataType::UInt256 => {
+                hash_array_primitive!(
+                    UInt256Array,
+                    col,
+                    u256,
+                    hashes_buffer,
+                    random_state,
+                    multi_col
+                );
+            }
+            DataType::Decimal(_, _) => {
+                create_hashes_decimal(col, random_state, hashes_buffer, multi_col);
+            }
             DataType::Float32 => {
                 hash_array_float!(
                     Float32Array,
@@ -351,7 +417,17 @@ pub fn create_hashes<'a>(
                     multi_col
                 );
             }
-            DataType::Timestamp(TimeUnit::Millisecond, None) => {
+            DataType::Timestamp(TimeUnit::Second, _) => {
+                hash_array_primitive!(
+                    TimestampSecondArray,
+                    col,
+                    i64,
+                    hashes_buffer,
+                    random_state,
+                    multi_col
+                );
+            }
+            DataType::Timestamp(TimeUnit::Millisecond, _) => {
                 hash_array_primitive!(
                     TimestampMillisecondArray,
                     col,
@@ -361,7 +437,7 @@ pub fn create_hashes<'a>(
                     multi_col
                 );
             }
-            DataType::Timestamp(TimeUnit::Microsecond, None) => {
+            DataType::Timestamp(TimeUnit::Microsecond, _) => {
                 hash_array_primitive!(
                     TimestampMicrosecondArray,
                     col,
@@ -371,7 +447,7 @@ pub fn create_hashes<'a>(
                     multi_col
                 );
             }
-            DataType::Timestamp(TimeUnit::Nanosecond, None) => {
+            DataType::Timestamp(TimeUnit::Nanosecond, _) => {
                 hash_array_primitive!(
                     TimestampNanosecondArray,
                     col,
@@ -533,6 +609,27 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn create_hashes_for_wide_arrays() -> Result<()> {
+        let big = i256::ONE.checked_mul_pow10(45).unwrap();
+        let i256_arr: ArrayRef = Arc::new(Int256Array::from(vec![big, i256::ONE, big]));
+        let mut builder = arrow::array::DecimalBuilder::new(3, 50, 2);
+        for v in [big, i256::ONE, big] {
+            builder.append_i256(v)?;
+        }
+        let decimal_arr: ArrayRef = Arc::new(builder.finish());
+
+        let random_state = RandomState::with_seeds(0, 0, 0, 0);
+        for arr in [i256_arr, decimal_arr] {
+            let hashes_buff = &mut vec![0; arr.len()];
+            let hashes = create_hashes(&[arr], &random_state, hashes_buff)?;
+            assert_eq!(hashes[0], hashes[2]);
+            assert_ne!(hashes[0], hashes[1]);
+        }
+
+        Ok(())
+    }
+
     #[test]
     fn create_hashes_for_float_arrays() -> Result<()> {
         let f32_arr = Arc::new(Float32Array::from(vec![0.12, 0.5, 1f32, 444.7]));
@@ -202,7 +202,11 @@ pub fn can_coerce_from(type_into: &DataType, type_from: &DataType) -> bool {
                 | Float64
         ),
         Timestamp32(None) => matches!(type_from, Timestamp32(_)),
-        Timestamp(TimeUnit::Nanosecond, None) => matches!(type_from, Timestamp(_, None)),
+        Timestamp(unit, None) => match type_from {
+            Timestamp(from_unit, Some(_)) => from_unit == unit,
+            Timestamp(_, None) => *unit == TimeUnit::Nanosecond,
+            _ => false,
+        },
         Utf8 | LargeUtf8 => true,
         _ => false,
     }
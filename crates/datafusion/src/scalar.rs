@@ -45,6 +45,9 @@ pub enum ScalarValue {
     Float64(Option<f64>),
     /// 128bit decimal, using the i128 to represent the decimal
     Decimal128(Option<i128>, usize, usize),
+    /// 256bit decimal for the precisions over 38, using the i256 to represent the
+    /// decimal (boxed to reduce size_of(ScalarValue))
+    Decimal256(Option<Box<i256>>, usize, usize),
     /// signed 8bit int
     Int8(Option<i8>),
     /// signed 16bit int
@@ -61,6 +64,14 @@ pub enum ScalarValue {
     UInt32(Option<u32>),
     /// unsigned 64bit int
     UInt64(Option<u64>),
+    /// signed 128bit int
+    Int128(Option<i128>),
+    /// unsigned 128bit int
+    UInt128(Option<u128>),
+    /// signed 256bit int
+    Int256(Option<i256>),
+    /// unsigned 256bit int
+    UInt256(Option<u256>),
     /// utf-8 encoded string.
     Utf8(Option<String>),
     /// utf-8 encoded string representing a LargeString's arrow type.
@@ -106,6 +117,10 @@ impl PartialEq for ScalarValue {
                 v1.eq(v2) && p1.eq(p2) && s1.eq(s2)
             }
             (Decimal128(_, _, _), _) => false,
+            (Decimal256(v1, p1, s1), Decimal256(v2, p2, s2)) => {
+                v1.eq(v2) && p1.eq(p2) && s1.eq(s2)
+            }
+            (Decimal256(_, _, _), _) => false,
             (Boolean(v1), Boolean(v2)) => v1.eq(v2),
             (Boolean(_), _) => false,
             (Float32(v1), Float32(v2)) => {
@@ -136,6 +151,14 @@ impl PartialEq for ScalarValue {
             (UInt32(_), _) => false,
             (UInt64(v1), UInt64(v2)) => v1.eq(v2),
             (UInt64(_), _) => false,
+            (Int128(v1), Int128(v2)) => v1.eq(v2),
+            (Int128(_), _) => false,
+            (UInt128(v1), UInt128(v2)) => v1.eq(v2),
+            (UInt128(_), _) => false,
+            (Int256(v1), Int256(v2)) => v1.eq(v2),
+            (Int256(_), _) => false,
+            (UInt256(v1), UInt256(v2)) => v1.eq(v2),
+            (UInt256(_), _) => false,
             (Utf8(v1), Utf8(v2)) => v1.eq(v2),
             (Utf8(_), _) => false,
             (LargeUtf8(v1), LargeUtf8(v2)) => v1.eq(v2),
@@ -190,6 +213,14 @@ impl PartialOrd for ScalarValue {
                 }
             }
             (Decimal128(_, _, _), _) => None,
+            (Decimal256(v1, p1, s1), Decimal256(v2, p2, s2)) => {
+                if p1.eq(p2) && s1.eq(s2) {
+                    v1.partial_cmp(v2)
+                } else {
+                    None
+                }
+            }
+            (Decimal256(_, _, _), _) => None,
             (Boolean(v1), Boolean(v2)) => v1.partial_cmp(v2),
             (Boolean(_), _) => None,
             (Float32(v1), Float32(v2)) => {
@@ -220,6 +251,14 @@ impl PartialOrd for ScalarValue {
             (UInt32(_), _) => None,
             (UInt64(v1), UInt64(v2)) => v1.partial_cmp(v2),
             (UInt64(_), _) => None,
+            (Int128(v1), Int128(v2)) => v1.partial_cmp(v2),
+            (Int128(_), _) => None,
+            (UInt128(v1), UInt128(v2)) => v1.partial_cmp(v2),
+            (UInt128(_), _) => None,
+            (Int256(v1), Int256(v2)) => v1.partial_cmp(v2),
+            (Int256(_), _) => None,
+            (UInt256(v1), UInt256(v2)) => v1.partial_cmp(v2),
+            (UInt256(_), _) => None,
             (Utf8(v1), Utf8(v2)) => v1.partial_cmp(v2),
             (Utf8(_), _) => None,
             (LargeUtf8(v1), LargeUtf8(v2)) => v1.partial_cmp(v2),
@@ -281,6 +320,11 @@ impl std::hash::Hash for ScalarValue {
                 p.hash(state);
                 s.hash(state)
             }
+            Decimal256(v, p, s) => {
+                v.hash(state);
+                p.hash(state);
+                s.hash(state)
+            }
             Boolean(v) => v.hash(state),
             Float32(v) => {
                 let v = v.map(OrderedFloat);
@@ -298,6 +342,10 @@ impl std::hash::Hash for ScalarValue {
             UInt16(v) => v.hash(state),
             UInt32(v) => v.hash(state),
             UInt64(v) => v.hash(state),
+            Int128(v) => v.hash(state),
+            UInt128(v) => v.hash(state),
+            Int256(v) => v.hash(state),
+            UInt256(v) => v.hash(state),
             Utf8(v) => v.hash(state),
             LargeUtf8(v) => v.hash(state),
             Binary(v) => v.hash(state),
@@ -511,11 +559,16 @@ impl ScalarValue {
             ScalarValue::Int16(_) => DataType::Int16,
             ScalarValue::Int32(_) => DataType::Int32,
             ScalarValue::Int64(_) => DataType::Int64,
+            ScalarValue::Int128(_) => DataType::Int128,
+            ScalarValue::UInt128(_) => DataType::UInt128,
+            ScalarValue::Int256(_) => DataType::Int256,
+            ScalarValue::UInt256(_) => DataType::UInt256,
             ScalarValue::Timestamp32(_) => {
                 DataType::Timestamp32(None)
             }
             ScalarValue::Date16(_) => DataType::Date16,
-            ScalarValue::Decimal128(_, precision, scale) => {
+            ScalarValue::Decimal128(_, precision, scale)
+            | ScalarValue::Decimal256(_, precision, scale) => {
                 DataType::Decimal(*precision, *scale)
             }
             ScalarValue::TimestampSecond(_) => {
@@ -559,6 +612,8 @@ impl ScalarValue {
             | ScalarValue::Int16(None)
             | ScalarValue::Int32(None)
             | ScalarValue::Int64(None)
+            | ScalarValue::Int128(None)
+            | ScalarValue::Int256(None)
             | ScalarValue::Float32(None) => self.clone(),
             ScalarValue::Float64(Some(v)) => ScalarValue::Float64(Some(-v)),
             ScalarValue::Float32(Some(v)) => ScalarValue::Float32(Some(-v)),
@@ -566,9 +621,14 @@ impl ScalarValue {
             ScalarValue::Int16(Some(v)) => ScalarValue::Int16(Some(-v)),
             ScalarValue::Int32(Some(v)) => ScalarValue::Int32(Some(-v)),
             ScalarValue::Int64(Some(v)) => ScalarValue::Int64(Some(-v)),
+            ScalarValue::Int128(Some(v)) => ScalarValue::Int128(Some(-v)),
+            ScalarValue::Int256(Some(v)) => ScalarValue::Int256(Some(-*v)),
             ScalarValue::Decimal128(Some(v), precision, scale) => {
                 ScalarValue::Decimal128(Some(-v), *precision, *scale)
             }
+            ScalarValue::Decimal256(Some(v), precision, scale) => {
+                ScalarValue::Decimal256(Some(Box::new(-**v)), *precision, *scale)
+            }
             _ => panic!("Cannot run arithmetic negate on scalar value: {:?}", self),
         }
     }
@@ -586,6 +646,10 @@ impl ScalarValue {
                 | ScalarValue::Int16(None)
                 | ScalarValue::Int32(None)
                 | ScalarValue::Int64(None)
+                | ScalarValue::Int128(None)
+                | ScalarValue::UInt128(None)
+                | ScalarValue::Int256(None)
+                | ScalarValue::UInt256(None)
                 | ScalarValue::Float32(None)
                 | ScalarValue::Float64(None)
                 | ScalarValue::Date32(None)
@@ -598,6 +662,7 @@ impl ScalarValue {
                 | ScalarValue::TimestampNanosecond(None)
                 | ScalarValue::Struct(None, _)
                 | ScalarValue::Decimal128(None, _, _) // For decimal type, the value is null means ScalarValue::Decimal128 is null.
+                | ScalarValue::Decimal256(None, _, _)
         )
     }
 
@@ -779,6 +844,10 @@ impl ScalarValue {
             DataType::UInt16 => build_array_primitive!(UInt16Array, UInt16),
             DataType::UInt32 => build_array_primitive!(UInt32Array, UInt32),
             DataType::UInt64 => build_array_primitive!(UInt64Array, UInt64),
+            DataType::Int128 => build_array_primitive!(Int128Array, Int128),
+            DataType::UInt128 => build_array_primitive!(UInt128Array, UInt128),
+            DataType::Int256 => build_array_primitive!(Int256Array, Int256),
+            DataType::UInt256 => build_array_primitive!(UInt256Array, UInt256),
             DataType::Utf8 => build_array_string!(StringArray, Utf8),
             DataType::LargeUtf8 => build_array_string!(LargeStringArray, LargeUtf8),
             DataType::Binary => build_array_string!(BinaryArray, Binary),
@@ -905,25 +974,24 @@ impl ScalarValue {
         precision: &usize,
         scale: &usize,
     ) -> Result<DecimalArray> {
-        // collect the value as Option<i128>
+        // collect the value as Option<i256>
         let array = scalars
             .into_iter()
             .map(|element: ScalarValue| match element {
-                ScalarValue::Decimal128(v1, _, _) => v1,
+                ScalarValue::Decimal128(v1, _, _) => v1.map(i256::from_half),
+                ScalarValue::Decimal256(v1, _, _) => v1.map(|v| *v),
                 _ => unreachable!(),
             })
-            .collect::<Vec<Option<i128>>>();
+            .collect::<Vec<Option<i256>>>();
 
         // build the decimal array using the Decimal Builder
         let mut builder = DecimalBuilder::new(array.len(), *precision, *scale);
-        array.iter().for_each(|element| match element {
-            None => {
-                builder.append_null().unwrap();
+        for element in array {
+            match element {
+                None => builder.append_null()?,
+                Some(v) => builder.append_i256(v)?,
             }
-            Some(v) => {
-                builder.append_value(*v).unwrap();
-            }
-        });
+        }
         Ok(builder.finish())
     }
 
@@ -995,7 +1063,7 @@ impl ScalarValue {
     }
 
     fn build_decimal_array(
-        value: &Option<i128>,
+        value: Option<i256>,
         precision: &usize,
         scale: &usize,
         size: usize,
@@ -1008,9 +1076,8 @@ impl ScalarValue {
                 }
             }
             Some(v) => {
-                let v = *v;
                 for _i in 0..size {
-                    builder.append_value(v).unwrap();
+                    builder.append_i256(v).unwrap();
                 }
             }
         };
@@ -1021,6 +1088,11 @@ impl ScalarValue {
     pub fn to_array_of_size(&self, size: usize) -> ArrayRef {
         match self {
             ScalarValue::Decimal128(e, precision, scale) => {
+                let e = e.map(i256::from_half);
+                Arc::new(ScalarValue::build_decimal_array(e, precision, scale, size))
+            }
+            ScalarValue::Decimal256(e, precision, scale) => {
+                let e = e.as_deref().copied();
                 Arc::new(ScalarValue::build_decimal_array(e, precision, scale, size))
             }
             ScalarValue::Boolean(e) => {
@@ -1046,6 +1118,18 @@ impl ScalarValue {
             ScalarValue::UInt64(e) => {
                 build_array_from_option!(UInt64, UInt64Array, e, size)
             }
+            ScalarValue::Int128(e) => {
+                build_array_from_option!(Int128, Int128Array, e, size)
+            }
+            ScalarValue::UInt128(e) => {
+                build_array_from_option!(UInt128, UInt128Array, e, size)
+            }
+            ScalarValue::Int256(e) => {
+                build_array_from_option!(Int256, Int256Array, e, size)
+            }
+            ScalarValue::UInt256(e) => {
+                build_array_from_option!(UInt256, UInt256Array, e, size)
+            }
             ScalarValue::Timestamp32(e) => build_array_from_option!(
                 Timestamp32,
                 None,
@@ -1209,7 +1293,16 @@ impl ScalarValue {
         scale: &usize,
     ) -> ScalarValue {
         let array = array.as_any().downcast_ref::<DecimalArray>().unwrap();
-        if array.is_null(index) {
+        let is_null = array.is_null(index);
+        //NOTE the values of the precisions over 38 are out of the range of i128
+        if *precision > 38 {
+            let v = if is_null {
+                None
+            } else {
+                Some(Box::new(array.value_i256(index)))
+            };
+            ScalarValue::Decimal256(v, *precision, *scale)
+        } else if is_null {
             ScalarValue::Decimal128(None, *precision, *scale)
         } else {
             ScalarValue::Decimal128(Some(array.value(index)), *precision, *scale)
@@ -1238,6 +1331,10 @@ impl ScalarValue {
             DataType::Int32 => typed_cast!(array, index, Int32Array, Int32),
             DataType::Int16 => typed_cast!(array, index, Int16Array, Int16),
             DataType::Int8 => typed_cast!(array, index, Int8Array, Int8),
+            DataType::Int128 => typed_cast!(array, index, Int128Array, Int128),
+            DataType::UInt128 => typed_cast!(array, index, UInt128Array, UInt128),
+            DataType::Int256 => typed_cast!(array, index, Int256Array, Int256),
+            DataType::UInt256 => typed_cast!(array, index, UInt256Array, UInt256),
             DataType::Binary => typed_cast!(array, index, BinaryArray, Binary),
             DataType::LargeBinary => {
                 typed_cast!(array, index, LargeBinaryArray, LargeBinary)
@@ -1353,7 +1450,7 @@ impl ScalarValue {
     fn eq_array_decimal(
         array: &ArrayRef,
         index: usize,
-        value: &Option<i128>,
+        value: Option<i256>,
         precision: usize,
         scale: usize,
     ) -> bool {
@@ -1363,7 +1460,7 @@ impl ScalarValue {
         }
         match value {
             None => array.is_null(index),
-            Some(v) => !array.is_null(index) && array.value(index) == *v,
+            Some(v) => !array.is_null(index) && array.value_i256(index) == v,
         }
     }
 
@@ -1391,6 +1488,11 @@ impl ScalarValue {
 
         match self {
             ScalarValue::Decimal128(v, precision, scale) => {
+                let v = v.map(i256::from_half);
+                ScalarValue::eq_array_decimal(array, index, v, *precision, *scale)
+            }
+            ScalarValue::Decimal256(v, precision, scale) => {
+                let v = v.as_deref().copied();
                 ScalarValue::eq_array_decimal(array, index, v, *precision, *scale)
             }
             ScalarValue::Boolean(val) => {
@@ -1416,6 +1518,18 @@ impl ScalarValue {
             ScalarValue::UInt64(val) => {
                 eq_array_primitive!(array, index, UInt64Array, val)
             }
+            ScalarValue::Int128(val) => {
+                eq_array_primitive!(array, index, Int128Array, val)
+            }
+            ScalarValue::UInt128(val) => {
+                eq_array_primitive!(array, index, UInt128Array, val)
+            }
+            ScalarValue::Int256(val) => {
+                eq_array_primitive!(array, index, Int256Array, val)
+            }
+            ScalarValue::UInt256(val) => {
+                eq_array_primitive!(array, index, UInt256Array, val)
+            }
             ScalarValue::Utf8(val) => eq_array_primitive!(array, index, StringArray, val),
             ScalarValue::LargeUtf8(val) => {
                 eq_array_primitive!(array, index, LargeStringArray, val)
@@ -1515,6 +1629,10 @@ impl_scalar!(u8, UInt8);
 impl_scalar!(u16, UInt16);
 impl_scalar!(u32, UInt32);
 impl_scalar!(u64, UInt64);
+impl_scalar!(i128, Int128);
+impl_scalar!(u128, UInt128);
+impl_scalar!(i256, Int256);
+impl_scalar!(u256, UInt256);
 
 impl From<&str> for ScalarValue {
     fn from(value: &str) -> Self {
@@ -1615,6 +1733,10 @@ impl_try_from!(UInt8, u8);
 
 impl_try_from!(UInt32, u32);
 impl_try_from!(UInt64, u64);
+impl_try_from!(Int128, i128);
+impl_try_from!(UInt128, u128);
+impl_try_from!(Int256, i256);
+impl_try_from!(UInt256, u256);
 impl_try_from!(Float32, f32);
 impl_try_from!(Float64, f64);
 impl_try_from!(Boolean, bool);
@@ -1666,6 +1788,13 @@ impl TryFrom<&DataType> for ScalarValue {
             DataType::UInt16 => ScalarValue::UInt16(None),
             DataType::UInt32 => ScalarValue::UInt32(None),
             DataType::UInt64 => ScalarValue::UInt64(None),
+            DataType::Int128 => ScalarValue::Int128(None),
+            DataType::UInt128 => ScalarValue::UInt128(None),
+            DataType::Int256 => ScalarValue::Int256(None),
+            DataType::UInt256 => ScalarValue::UInt256(None),
+            DataType::Decimal(precision, scale) if *precision > 38 => {
+                ScalarValue::Decimal256(None, *precision, *scale)
+            }
             DataType::Decimal(precision, scale) => {
                 ScalarValue::Decimal128(None, *precision, *scale)
             }
@@ -1719,6 +1848,9 @@ impl fmt::Display for ScalarValue {
             ScalarValue::Decimal128(v, p, s) => {
                 write!(f, "{}", format!("{:?},{:?},{:?}", v, p, s))?;
             }
+            ScalarValue::Decimal256(v, p, s) => {
+                write!(f, "{}", format!("{:?},{:?},{:?}", v, p, s))?;
+            }
             ScalarValue::Timestamp32(e) => format_option!(f, e)?,
             ScalarValue::Date16(e) => format_option!(f, e)?,
             ScalarValue::Boolean(e) => format_option!(f, e)?,
@@ -1732,6 +1864,10 @@ impl fmt::Display for ScalarValue {
             ScalarValue::UInt16(e) => format_option!(f, e)?,
             ScalarValue::UInt32(e) => format_option!(f, e)?,
             ScalarValue::UInt64(e) => format_option!(f, e)?,
+            ScalarValue::Int128(e) => format_option!(f, e)?,
+            ScalarValue::UInt128(e) => format_option!(f, e)?,
+            ScalarValue::Int256(e) => format_option!(f, e)?,
+            ScalarValue::UInt256(e) => format_option!(f, e)?,
             ScalarValue::TimestampSecond(e) => format_option!(f, e)?,
             ScalarValue::TimestampMillisecond(e) => format_option!(f, e)?,
             ScalarValue::TimestampMicrosecond(e) => format_option!(f, e)?,
@@ -1798,6 +1934,7 @@ impl fmt::Debug for ScalarValue {
             ScalarValue::Timestamp32(_) => write!(f, "Timestamp32({})", self),
             ScalarValue::Date16(_) => write!(f, "Date16(\"{}\")", self),
             ScalarValue::Decimal128(_, _, _) => write!(f, "Decimal128({})", self),
+            ScalarValue::Decimal256(_, _, _) => write!(f, "Decimal256({})", self),
             ScalarValue::Boolean(_) => write!(f, "Boolean({})", self),
             ScalarValue::Float32(_) => write!(f, "Float32({})", self),
             ScalarValue::Float64(_) => write!(f, "Float64({})", self),
@@ -1809,6 +1946,10 @@ impl fmt::Debug for ScalarValue {
             ScalarValue::UInt16(_) => write!(f, "UInt16({})", self),
             ScalarValue::UInt32(_) => write!(f, "UInt32({})", self),
             ScalarValue::UInt64(_) => write!(f, "UInt64({})", self),
+            ScalarValue::Int128(_) => write!(f, "Int128({})", self),
+            ScalarValue::UInt128(_) => write!(f, "UInt128({})", self),
+            ScalarValue::Int256(_) => write!(f, "Int256({})", self),
+            ScalarValue::UInt256(_) => write!(f, "UInt256({})", self),
             ScalarValue::TimestampSecond(_) => write!(f, "TimestampSecond({})", self),
             ScalarValue::TimestampMillisecond(_) => {
                 write!(f, "TimestampMillisecond({})", self)
@@ -1990,6 +2131,44 @@ mod tests {
         );
     }
 
+    #[test]
+    fn scalar_wide_test() {
+        let big =
+            i256::from_str("-12345678901234567890123456789012345678901234").unwrap();
+        let value = ScalarValue::Decimal256(Some(Box::new(big)), 50, 4);
+        assert_eq!(DataType::Decimal(50, 4), value.get_datatype());
+        let array = value.to_array_of_size(3);
+        let decimals = array.as_any().downcast_ref::<DecimalArray>().unwrap();
+        assert_eq!(big, decimals.value_i256(2));
+        assert!(value.eq_array(&array, 1));
+        assert_eq!(value, ScalarValue::try_from_array(&array, 0).unwrap());
+        assert_eq!(
+            ScalarValue::Decimal256(Some(Box::new(-big)), 50, 4),
+            value.arithmetic_negate()
+        );
+        assert_eq!(
+            ScalarValue::Decimal256(None, 50, 4),
+            (&DataType::Decimal(50, 4)).try_into().unwrap()
+        );
+
+        let values = vec![
+            ScalarValue::Int128(Some(-1)),
+            ScalarValue::Int128(None),
+            ScalarValue::Int128(Some(i128::MAX)),
+        ];
+        let array = ScalarValue::iter_to_array(values.clone()).unwrap();
+        assert_eq!(DataType::Int128, *array.data_type());
+        for (i, v) in values.iter().enumerate() {
+            assert_eq!(*v, ScalarValue::try_from_array(&array, i).unwrap());
+        }
+
+        let value = ScalarValue::from(u256::MAX);
+        let array = value.to_array_of_size(2);
+        assert!(value.eq_array(&array, 1));
+        assert!(ScalarValue::UInt256(Some(u256::ZERO)) < value);
+        assert_eq!(u256::MAX, u256::try_from(value).unwrap());
+    }
+
     #[test]
     fn scalar_value_to_array_u64() {
         let value = ScalarValue::UInt64(Some(13u64));
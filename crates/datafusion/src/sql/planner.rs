@@ -2073,6 +2073,10 @@ pub fn convert_custom_data_type(sql: &SQLDataType) -> Result<DataType> {
                 "Int16" => Ok(DataType::Int16),
                 "Int32" => Ok(DataType::Int32),
                 "Int64" => Ok(DataType::Int64),
+                "Int128" => Ok(DataType::Int128),
+                "UInt128" => Ok(DataType::UInt128),
+                "Int256" => Ok(DataType::Int256),
+                "UInt256" => Ok(DataType::UInt256),
                 "Float64" => Ok(DataType::Float64),
                 data_type => {
                     return Err(DataFusionError::NotImplemented(format!(
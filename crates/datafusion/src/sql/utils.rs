@@ -519,10 +519,10 @@ pub(crate) fn make_decimal_type(
             )));
         }
         (Some(p), Some(s)) => {
-            // Arrow decimal is i128 meaning 38 maximum decimal digits
-            if p > 38 || s > p {
+            // Arrow decimal is up to i256 meaning 76 maximum decimal digits
+            if p > 76 || s > p {
                 return Err(DataFusionError::Internal(format!(
-                    "For decimal(precision, scale) precision must be less than or equal to 38 and scale can't be greater than precision. Got ({}, {})",
+                    "For decimal(precision, scale) precision must be less than or equal to 76 and scale can't be greater than precision. Got ({}, {})",
                     p, s
                 )));
             } else {
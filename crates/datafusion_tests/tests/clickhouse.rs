@@ -4,8 +4,16 @@
 mod tests {
     use arrow::array::FixedSizeBinaryArray;
     use arrow::{
-        array::{Array, LargeStringArray, PrimitiveArray, StringArray},
-        datatypes::{Date16Type, Int64Type, Timestamp32Type},
+        array::{
+            Array, ArrayRef, LargeStringArray, LargeStringBuilder, ListArray,
+            ListBuilder, PrimitiveArray, StringArray, TimestampMillisecondArray,
+            UInt16Array, UInt32Array, UInt8Array,
+        },
+        datatypes::{
+            DataType, Date16Type, Field, Int64Type, Schema, TimeUnit, Timestamp32Type,
+            UInt16Type,
+        },
+        record_batch::RecordBatch,
     };
     use base::datetimes::TimeZoneId;
     use datafusion::physical_plan::clickhouse::*;
@@ -248,6 +256,116 @@ mod tests {
         assert_eq!(1971, b.value(2));
     }
 
+    #[test]
+    fn test_to_start_of() {
+        // 2021-05-17
+        let a: PrimitiveArray<Date16Type> = vec![Some(18764), None].into();
+        assert_eq!(18628, date16_to_start_of_year(&a).unwrap().value(0));
+        assert_eq!(18718, date16_to_start_of_quarter(&a).unwrap().value(0));
+        assert_eq!(18748, date16_to_start_of_month(&a).unwrap().value(0));
+        assert_eq!(false, date16_to_start_of_month(&a).unwrap().is_valid(1));
+
+        // 2021-01-01 00:30:00 +08:00
+        let a: PrimitiveArray<Timestamp32Type> = vec![Some(1609432200)].into();
+        let tz = Some(8 * 3600);
+        assert_eq!(
+            18628,
+            timestamp32_to_start_of_year(&a, tz).unwrap().value(0)
+        );
+        assert_eq!(
+            18262,
+            timestamp32_to_start_of_year(&a, Some(0)).unwrap().value(0)
+        );
+        assert_eq!(
+            18628,
+            timestamp32_to_start_of_month(&a, tz).unwrap().value(0)
+        );
+        assert_eq!(
+            1609430400,
+            timestamp32_to_start_of_day(&a, tz).unwrap().value(0)
+        );
+        assert_eq!(
+            1609430400,
+            timestamp32_to_start_of_hour(&a, tz).unwrap().value(0)
+        );
+        assert_eq!(
+            1609432200,
+            timestamp32_to_start_of_minute(&a, tz).unwrap().value(0)
+        );
+
+        // 2021-01-01 00:30:00.123 +08:00
+        let a: ArrayRef = Arc::new(TimestampMillisecondArray::from_opt_vec(
+            vec![Some(1609432200123), Some(-1)],
+            Some("+08:00".to_string()),
+        ));
+        assert_eq!(
+            18628,
+            timestamp64_to_start_of_year(&a, tz).unwrap().value(0)
+        );
+        assert_eq!(
+            18628,
+            timestamp64_to_start_of_quarter(&a, tz).unwrap().value(0)
+        );
+        assert_eq!(
+            1609430400,
+            timestamp64_to_start_of_day(&a, tz).unwrap().value(0)
+        );
+        assert_eq!(
+            1609430400,
+            timestamp64_to_start_of_hour(&a, tz).unwrap().value(0)
+        );
+        assert_eq!(
+            1609432200,
+            timestamp64_to_start_of_minute(&a, tz).unwrap().value(0)
+        );
+        let b = timestamp64_to_start_of_second(&a).unwrap();
+        assert_eq!(a.data_type(), b.data_type());
+        let b = b
+            .as_any()
+            .downcast_ref::<TimestampMillisecondArray>()
+            .unwrap();
+        assert_eq!(1609432200000, b.value(0));
+        assert_eq!(-1000, b.value(1));
+    }
+
+    #[test]
+    fn test_to_datetime64() {
+        let f = BuiltinScalarFunction::to_datetime64(6, Some("+08:00")).unwrap();
+        assert_eq!(
+            DataType::Timestamp(TimeUnit::Microsecond, Some("+08:00".to_string())),
+            f.return_type(&[DataType::Utf8]).unwrap()
+        );
+        assert!(BuiltinScalarFunction::to_datetime64(2, None).is_err());
+
+        let a: ArrayRef = Arc::new(StringArray::from(vec![
+            Some("1970-01-01 00:00:01.5"),
+            None,
+            Some("1970-01-01 08:00:00"),
+        ]));
+        let b = utf8_to_datetime64(&[a.clone()], 3, Some(0)).unwrap();
+        assert_eq!(1500, b.value(0));
+        assert_eq!(false, b.is_valid(1));
+        assert_eq!(28_800_000, b.value(2));
+        let b = utf8_to_datetime64(&[a], 0, Some(8 * 3600)).unwrap();
+        assert_eq!(0, b.value(2));
+
+        let a: ArrayRef =
+            Arc::new(LargeStringArray::from(vec!["\u{14}1970-01-01 00:00:01."]));
+        assert!(large_utf8_to_datetime64(&[a], 3, Some(0)).is_err());
+
+        let a: ArrayRef = Arc::new(PrimitiveArray::<Date16Type>::from(vec![1]));
+        let b = date16_to_datetime64(&[a], 3, Some(0)).unwrap();
+        assert_eq!(86_400_000, b.value(0));
+
+        let a: ArrayRef = Arc::new(PrimitiveArray::<Timestamp32Type>::from(vec![1]));
+        let b = timestamp32_to_datetime64(&[a], 6).unwrap();
+        assert_eq!(1_000_000, b.value(0));
+
+        let a: ArrayRef = Arc::new(PrimitiveArray::<Int64Type>::from(vec![-1]));
+        let b = int64_to_datetime64(&[a], 9).unwrap();
+        assert_eq!(-1_000_000_000, b.value(0));
+    }
+
     #[test]
     fn stress_to_year() {
         let v: Vec<u16> = (0..4096).collect();
@@ -420,4 +538,117 @@ mod tests {
         }
         println!("ts: {:?}, s: {}", ts.elapsed(), s);
     }
+
+    #[test]
+    fn test_array_has_and_length() {
+        let a: ArrayRef =
+            Arc::new(ListArray::from_iter_primitive::<UInt16Type, _, _>(vec![
+                Some(vec![Some(1), Some(2)]),
+                Some(vec![]),
+                None,
+                Some(vec![Some(3)]),
+            ]));
+        let b = array_length(&[a.clone()]).unwrap();
+        assert_eq!(2, b.value(0));
+        assert_eq!(0, b.value(1));
+        assert_eq!(false, b.is_valid(2));
+        assert_eq!(1, b.value(3));
+
+        let n: ArrayRef = Arc::new(PrimitiveArray::<Int64Type>::from(vec![2; 4]));
+        let b = array_has(&[a, n]).unwrap();
+        assert_eq!(true, b.value(0));
+        assert_eq!(false, b.value(1));
+        assert_eq!(false, b.is_valid(2));
+        assert_eq!(false, b.value(3));
+
+        //the strings are prefixed by their lengths
+        let mut lb = ListBuilder::new(LargeStringBuilder::new(4));
+        lb.values().append_value("\x01a").unwrap();
+        lb.values().append_value("\x02bc").unwrap();
+        lb.append(true).unwrap();
+        lb.values().append_value("\x01b").unwrap();
+        lb.append(true).unwrap();
+        let a: ArrayRef = Arc::new(lb.finish());
+        let n: ArrayRef = Arc::new(StringArray::from(vec!["bc", "bc"]));
+        let b = array_has(&[a.clone(), n]).unwrap();
+        assert_eq!(true, b.value(0));
+        assert_eq!(false, b.value(1));
+        let n: ArrayRef = Arc::new(LargeStringArray::from(vec!["\x01a", "\x01b"]));
+        let b = array_has(&[a, n]).unwrap();
+        assert_eq!(true, b.value(0));
+        assert_eq!(true, b.value(1));
+    }
+
+    #[test]
+    fn test_array_join() {
+        let a = ListArray::from_iter_primitive::<UInt16Type, _, _>(vec![
+            Some(vec![Some(1), Some(2)]),
+            Some(vec![]),
+            None,
+            Some(vec![Some(3)]),
+        ]);
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::UInt16, false),
+            Field::new("a", a.data_type().clone(), true),
+        ]));
+        let id = UInt16Array::from(vec![10, 11, 12, 13]);
+        let batch =
+            RecordBatch::try_new(schema, vec![Arc::new(id), Arc::new(a)]).unwrap();
+        let b = array_join(&batch, 1).unwrap();
+        assert_eq!(&DataType::UInt16, b.schema().field(1).data_type());
+        assert_eq!(3, b.num_rows());
+        let id = b.column(0).as_any().downcast_ref::<UInt16Array>().unwrap();
+        assert_eq!(&[10, 10, 13], id.values());
+        let a = b.column(1).as_any().downcast_ref::<UInt16Array>().unwrap();
+        assert_eq!(&[1, 2, 3], a.values());
+    }
+
+    #[test]
+    fn test_ip_address() {
+        let a: ArrayRef = Arc::new(UInt32Array::from(vec![Some(3232235521), None]));
+        let b = ipv4_num_to_string(&[a]).unwrap();
+        assert_eq!("\u{b}192.168.0.1", b.value(0));
+        assert_eq!(false, b.is_valid(1));
+
+        let a: ArrayRef = Arc::new(StringArray::from(vec!["192.168.0.1", "err"]));
+        let b = ipv4_string_to_num(&[a]).unwrap();
+        assert_eq!(&[3232235521, 0], b.values());
+        let a: ArrayRef = Arc::new(LargeStringArray::from(vec!["\u{b}192.168.0.1"]));
+        let b = ipv4_string_to_num(&[a]).unwrap();
+        assert_eq!(3232235521, b.value(0));
+
+        let a: ArrayRef = Arc::new(StringArray::from(vec!["127.0.0.1", "::1", "err"]));
+        let b = string_to_ipv6(&[a]).unwrap();
+        assert_eq!(
+            &[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xff, 0xff, 127, 0, 0, 1],
+            b.value(0)
+        );
+        assert_eq!(1, b.value(1)[15]);
+        assert_eq!(&[0; 16], b.value(2));
+
+        let a: ArrayRef = Arc::new(LargeStringArray::from(vec![
+            "\u{9}127.0.0.1",
+            "\u{8}10.0.0.1",
+            "\u{b}2001:db8::1",
+        ]));
+        let n: ArrayRef = Arc::new(StringArray::from(vec!["127.0.0.0/8"; 3]));
+        let b = is_ip_address_in_range(&[a.clone(), n]).unwrap();
+        assert_eq!(true, b.value(0));
+        assert_eq!(false, b.value(1));
+        assert_eq!(false, b.value(2));
+        let n: ArrayRef = Arc::new(StringArray::from(vec!["2001:db8::/32"; 3]));
+        let b = is_ip_address_in_range(&[a.clone(), n]).unwrap();
+        assert_eq!(false, b.value(0));
+        assert_eq!(true, b.value(2));
+        let n: ArrayRef = Arc::new(StringArray::from(vec!["127.0.0.0/33"; 3]));
+        assert!(is_ip_address_in_range(&[a, n]).is_err());
+
+        let a: ArrayRef = Arc::new(UInt32Array::from(vec![Some(3232235777), None]));
+        let n: ArrayRef = Arc::new(UInt8Array::from(vec![16, 16]));
+        let b = ipv4_cidr_to_range(&[a, n]).unwrap();
+        let r = b.value(0);
+        let r = r.as_any().downcast_ref::<UInt32Array>().unwrap();
+        assert_eq!(&[3232235520, 3232301055], r.values());
+        assert_eq!(false, b.is_valid(1));
+    }
 }
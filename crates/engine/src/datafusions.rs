@@ -1,26 +1,44 @@
-use std::sync::Arc;
+use std::{
+    collections::{hash_map::Entry, HashMap, HashSet},
+    ops::RangeInclusive,
+    sync::Arc,
+};
 
 use arrow::{
     array::{
-        ArrayData, ArrayRef, Date16Array, DecimalArray, FixedSizeBinaryArray,
-        Float32Array, Float64Array, GenericStringArray, Int16Array, Int32Array,
-        Int64Array, Int8Array, Timestamp32Array, UInt16Array, UInt32Array, UInt64Array,
-        UInt8Array,
+        make_array, ArrayData, ArrayRef, BooleanArray, Date16Array, DecimalArray,
+        FixedSizeBinaryArray, Float32Array, Float64Array, GenericStringArray,
+        Int128Array, Int16Array, Int256Array, Int32Array, Int64Array, Int8Array,
+        ListArray, Timestamp32Array, UInt128Array, UInt16Array, UInt256Array,
+        UInt32Array, UInt64Array, UInt8Array,
     },
     buffer::Buffer,
+    compute::filter_record_batch,
     datatypes::{DataType, Field, Schema},
-    ffi::FFI_ArrowArray,
     record_batch::RecordBatch,
 };
-use base::contract;
-use datafusion::{datasource::MemTable, prelude::ExecutionContext};
-use lang::parse::{parse_where, TablesContext};
+use base::{codec::encode_varint64, contract};
+use datafusion::{
+    datasource::MemTable,
+    physical_plan::clickhouse::{array_join, array_join_schema},
+    prelude::{ExecutionConfig, ExecutionContext},
+    scalar::ScalarValue,
+};
+use lang::parse::{array_join_column, parse_where, TablesContext};
 use meta::{
+    codecs::decompress_in_ranges,
     store::{
-        parts::{CoPaInfo, PartStore},
-        sys::MetaStore,
+        parts::{
+            has_stats_minmax, is_sparse_indexable, stats_key_ranges, CoPaInfo, PartStore,
+            TableSnapshot,
+        },
+        sys::{IVec, MetaStore},
+        tablog::TabLog,
+    },
+    types::{
+        convert_numeric_values, gen_list_array_data, AggFunc, BqlType, ColumnInfo,
+        EngineType, EnumValues, Id, FIELD_BQL_TYPE,
     },
-    types::{BqlType, Id},
 };
 use tokio::runtime::Handle;
 
@@ -39,18 +57,28 @@ fn btype_to_arrow_type(typ: BqlType) -> EngineResult<DataType> {
         BqlType::Int(bits) if bits == 16 => Ok(DataType::Int16),
         BqlType::Int(bits) if bits == 32 => Ok(DataType::Int32),
         BqlType::Int(bits) if bits == 64 => Ok(DataType::Int64),
+        BqlType::Int(bits) if bits == 128 => Ok(DataType::Int128),
+        BqlType::UInt(bits) if bits == 128 => Ok(DataType::UInt128),
+        BqlType::Int256 => Ok(DataType::Int256),
+        BqlType::UInt256 => Ok(DataType::UInt256),
         BqlType::Float(bits) if bits == 16 => Ok(DataType::Float16),
         BqlType::Float(bits) if bits == 32 => Ok(DataType::Float32),
         BqlType::Float(bits) if bits == 64 => Ok(DataType::Float64),
         BqlType::DateTime => Ok(DataType::Timestamp32(None)),
         BqlType::DateTimeTz(tz) => Ok(DataType::Timestamp32(Some(tz))),
+        BqlType::DateTime64(_, _) => Ok(meta::types::btype_to_arrow_type(typ)?),
         BqlType::Date => Ok(DataType::Date16),
         BqlType::Decimal(p, s) => Ok(DataType::Decimal(p as usize, s as usize)),
+        BqlType::Enum8 => Ok(DataType::Int8),
+        BqlType::Enum16 => Ok(DataType::Int16),
         BqlType::String => Ok(DataType::LargeUtf8),
         BqlType::LowCardinalityString => Ok(DataType::UInt32),
         BqlType::LowCardinalityTinyText => Ok(DataType::UInt8),
         BqlType::FixedString(len) => Ok(DataType::FixedSizeBinary(len as i32)),
-        BqlType::Uuid => Ok(DataType::FixedSizeBinary(16)),
+        BqlType::Uuid | BqlType::Ipv6 => Ok(DataType::FixedSizeBinary(16)),
+        BqlType::Ipv4 => Ok(DataType::UInt32),
+        BqlType::AggregateFunction(_, _, _) => btype_to_arrow_type(typ.state_type()),
+        BqlType::Array(_, _) => Ok(meta::types::btype_to_arrow_type(typ)?),
         _ => Err(EngineError::UnsupportedBqlType),
     }
 }
@@ -61,50 +89,111 @@ pub(crate) fn run(
     current_db: &str,
     raw_query: &str,
     _query_id: &str,
-    tctx: TablesContext,
+    mut tctx: TablesContext,
     qs: &mut QueryState,
 ) -> EngineResult<Vec<RecordBatch>> {
     // let t = Instant::now();
     let mut ctx = ExecutionContext::new();
-    let tabs = tctx.tabs;
-    let cols = tctx.cols;
-    for tab in tabs {
+    //the tables queried with FINAL are collapsed, and the arrays joined by
+    //arrayJoin are joined when registered
+    let typs =
+        match register_tables(ms, ps, current_db, &tctx, None, qs, &mut ctx, false)? {
+            Some(typs) => typs,
+            None => return Ok(Vec::<RecordBatch>::new()),
+        };
+    tctx.rewrite_enum_names(|cn| typs.get(cn).and_then(|(_, ev)| ev.as_ref()))?;
+    let raw_query = tctx.rewrite_query(raw_query);
+    let res = collect_query(ctx, &raw_query)?;
+    annotate_types(res, &typs)
+}
+
+///run the query of a single table by the parts with ptk in ptks, in which the
+///deleted rows are kept, return one result of the same rows for each part keyed
+///by its ptk
+///
+///NOTE only the queries without filtering or aggregating are supported
+pub(crate) fn run_by_parts(
+    ms: &MetaStore,
+    ps: &PartStore,
+    current_db: &str,
+    raw_query: &str,
+    mut tctx: TablesContext,
+    ptks: RangeInclusive<u64>,
+    qs: &mut QueryState,
+) -> EngineResult<Vec<(u64, RecordBatch)>> {
+    if tctx.tabs.len() != 1 || !tctx.where_str.is_empty() {
+        return Err(EngineError::UnsupportedQuery);
+    }
+    //NOTE one partition keeps the results in the order of the parts
+    let conf = ExecutionConfig::new().with_target_partitions(1);
+    let mut ctx = ExecutionContext::with_config(conf);
+    let typs = match register_tables(
+        ms,
+        ps,
+        current_db,
+        &tctx,
+        Some(&ptks),
+        qs,
+        &mut ctx,
+        true,
+    )? {
+        Some(typs) => typs,
+        None => return Ok(Vec::new()),
+    };
+    tctx.rewrite_enum_names(|cn| typs.get(cn).and_then(|(_, ev)| ev.as_ref()))?;
+    let raw_query = tctx.rewrite_query(raw_query);
+    let res = annotate_types(collect_query(ctx, &raw_query)?, &typs)?;
+    let cps = match qs.copasss.first() {
+        Some(copass) => &copass[0],
+        None => return Ok(Vec::new()),
+    };
+    if res.len() != cps.len()
+        || res.iter().zip(cps).any(|(b, cpi)| b.num_rows() != cpi.size)
+    {
+        return Err(EngineError::UnexpectedDataLoadingError);
+    }
+    Ok(cps.iter().map(|cpi| cpi.ptk).zip(res).collect())
+}
+
+fn collect_query(
+    mut ctx: ExecutionContext,
+    raw_query: &str,
+) -> EngineResult<Vec<RecordBatch>> {
+    let res: EngineResult<Vec<RecordBatch>> = tokio::task::block_in_place(|| {
+        Handle::current().block_on(async move {
+            let df = ctx.sql(raw_query).await?;
+            let r = df.collect().await?;
+            Ok(r)
+        })
+    });
+    Ok(res?)
+}
+
+///register the tables in the query into ctx, only the parts with ptk in ptks
+///are scanned if some, return the ColumnTypes of the tables, or None if there
+///is no part to scan
+fn register_tables(
+    ms: &MetaStore,
+    ps: &PartStore,
+    current_db: &str,
+    tctx: &TablesContext,
+    ptks: Option<&RangeInclusive<u64>>,
+    qs: &mut QueryState,
+    ctx: &mut ExecutionContext,
+    with_deleted: bool,
+) -> EngineResult<Option<ColumnTypes>> {
+    let cols = &tctx.cols;
+    let mut typs = HashMap::new();
+    for &tab in &tctx.tabs {
         let qn1 = [current_db, &tab].join(".");
         let qtn = if tab.contains('.') { tab } else { &qn1 };
         let tid = ms.tid_by_qname(&qtn).ok_or(EngineError::TableNotExist)?;
-        //parts pruning
-        let ptk_range = match ms.get_table_info_partition_cols(tid)? {
-            Some(iv) => {
-                let ptc = unsafe { std::str::from_utf8_unchecked(&*iv) };
-                let pc = if ptc.ends_with(",") {
-                    &ptc[..ptc.len() - 1]
-                } else {
-                    &ptc
-                };
-                log::debug!("pc: {:?}", pc);
-                if (&cols).contains(pc) {
-                    match ms.get_table_info_partition_keys_expr(tid)? {
-                        Some(iv) if !tctx.where_str.is_empty() => {
-                            let ptk_expr = unsafe { std::str::from_utf8_unchecked(&*iv) };
-                            log::debug!("ptk_expr: {:?}", ptk_expr);
-                            parse_where(tctx.where_str, ptk_expr)?
-                        }
-                        _ => {
-                            vec![0..=u64::MAX]
-                        }
-                    }
-                } else {
-                    vec![0..=u64::MAX]
-                }
-            }
-            _ => vec![0..=u64::MAX],
+        let collapsing = if tctx.finals.contains(tab) {
+            Some(get_collapsing(ms, qtn, tid)?.ok_or(EngineError::FinalNotSupported)?)
+        } else {
+            None
         };
-        log::debug!("ptk_range: {:?}", ptk_range);
-        if ptk_range.len() == 0 {
-            return Ok(Vec::<RecordBatch>::new());
-        }
-
-        // *cid, ci.data_type
+        // *cid, ci
         let mut cis = Vec::new();
         let mut fields = Vec::new();
         if cols.len() != 0 && !tctx.has_select_all {
@@ -119,7 +208,7 @@ pub(crate) fn run(
                 if qcn.contains(qtn) {
                     if let Some(cid) = ms.cid_by_qname(&qcn) {
                         if let Some(ci) = ms.get_column_info(cid)? {
-                            cis.push((cid, ci.data_type));
+                            cis.push((cid, ci));
                             fields.push(Field::new(
                                 cn,
                                 btype_to_arrow_type(ci.data_type)?,
@@ -136,7 +225,7 @@ pub(crate) fn run(
                 log::debug!("current_db: {}, cn: {}", current_db, &tab);
                 let cs = ms.get_columns_by_qtn(qtn)?;
                 let (cn, cid, ci) = &cs[0];
-                cis.push((*cid, ci.data_type));
+                cis.push((*cid, *ci));
                 fields.push(Field::new(
                     cn.as_str(),
                     btype_to_arrow_type(ci.data_type)?,
@@ -145,7 +234,7 @@ pub(crate) fn run(
             } else if tctx.has_select_all {
                 let cs = ms.get_columns_by_qtn(qtn)?;
                 for (cn, cid, ci) in cs {
-                    cis.push((cid, ci.data_type));
+                    cis.push((cid, ci));
                     fields.push(Field::new(
                         cn.as_str(),
                         btype_to_arrow_type(ci.data_type)?,
@@ -156,11 +245,108 @@ pub(crate) fn run(
                 return Err(EngineError::UnsupportedQuery);
             }
         }
+        //NOTE the values of Enum are kept to be compared and sorted, and they
+        //     are decoded only at output, the types of IPv4, IPv6 and UUID are
+        //     kept for the headers of the results
+        for ((cid, ci), f) in cis.iter().zip(&fields) {
+            let typ = ci.data_type;
+            let ev = match typ {
+                BqlType::Enum8 | BqlType::Enum16 => ms.get_column_enum(*cid)?,
+                BqlType::Ipv4 | BqlType::Ipv6 | BqlType::Uuid => None,
+                _ => continue,
+            };
+            typs.insert(f.name().to_string(), (typ, ev));
+        }
+
+        //the tables without parts are registered by their batches directly
+        let engine = ms.get_table_info_engine(tid)?;
+        if matches!(engine, EngineType::Memory | EngineType::Log) {
+            let schema = Arc::new(Schema::new(fields));
+            let batches = match engine {
+                EngineType::Memory => match qs.mem_tables.get(&tid) {
+                    Some(bs) => project_mem_batches(ms, qtn, bs, &cis, &schema)?,
+                    None => Vec::new(),
+                },
+                _ => read_log_batches(ps, tid, &cis, &schema)?,
+            };
+            let (schema, batches) = join_arrays(schema, batches, &tctx.array_joins)?;
+            ctx.register_table(tab, Arc::new(MemTable::try_new(schema, vec![batches])?))?;
+            continue;
+        }
+
+        //parts pruning
+        let ptk_range = match ms.get_table_info_partition_cols(tid)? {
+            Some(iv) => {
+                let ptc = unsafe { std::str::from_utf8_unchecked(&*iv) };
+                let pc = if ptc.ends_with(",") {
+                    &ptc[..ptc.len() - 1]
+                } else {
+                    &ptc
+                };
+                log::debug!("pc: {:?}", pc);
+                if (&cols).contains(pc) {
+                    match ms.get_table_info_partition_keys_expr(tid)? {
+                        Some(iv) if !tctx.where_str.is_empty() => {
+                            let ptk_expr = unsafe { std::str::from_utf8_unchecked(&*iv) };
+                            log::debug!("ptk_expr: {:?}", ptk_expr);
+                            parse_where(tctx.where_str, ptk_expr)?
+                        }
+                        _ => {
+                            vec![0..=u64::MAX]
+                        }
+                    }
+                } else {
+                    vec![0..=u64::MAX]
+                }
+            }
+            _ => vec![0..=u64::MAX],
+        };
+        log::debug!("ptk_range: {:?}", ptk_range);
+        let ptk_range = if tctx.where_str.is_empty() || ptk_range.len() == 0 {
+            ptk_range
+        } else {
+            prune_by_stats(ms, ps, tid, qtn, &cols, tctx.where_str, ptk_range)?
+        };
+        let ptk_range: Vec<RangeInclusive<u64>> = match ptks {
+            Some(r) => ptk_range
+                .into_iter()
+                .map(|pr| *pr.start().max(r.start())..=*pr.end().min(r.end()))
+                .filter(|pr| pr.start() <= pr.end())
+                .collect(),
+            None => ptk_range,
+        };
+        if ptk_range.len() == 0 {
+            return Ok(None);
+        }
+
+        //the rows of every part are collapsed by the key(and version) column
+        if let Some(c) = &collapsing {
+            for cn in c.required_columns() {
+                index_or_push_column(ms, qtn, cn, &mut cis, &mut fields)?;
+            }
+        }
+
+        //NOTE the granules pruning, the mapping of the copas and the deletion
+        //     bitmaps are all of the parts in one snapshot, which may be
+        //     switched to another generation by a merge meanwhile
+        let mut cids: Vec<Id> = cis.iter().map(|(cid, _)| *cid).collect();
+        if let Some(pk) = ms.get_table_info_sort_key(tid)? {
+            cids.extend(ms.cid_by_qname([qtn, pk.as_str()].join(".")));
+        }
+        let snap = ps.snapshot_table(tid, &cids, &ptk_range)?;
+
+        //granules pruning
+        let row_ranges = if tctx.where_str.is_empty() || collapsing.is_some() {
+            None
+        } else {
+            prune_by_sparse_index(ms, ps, &snap, qtn, &cols, tctx.where_str)?
+        };
+        log::debug!("row_ranges: {:?}", row_ranges);
 
         let schema = Arc::new(Schema::new(fields));
         let copasss = &mut qs.copasss;
         let mut copass = Vec::new();
-        ps.fill_copainfos_int_by_ptk_range(&mut copass, tid, &cis, ptk_range)?;
+        ps.fill_copainfos_by_snapshot(&mut copass, &snap, &cis)?;
         if copass.len() > 0 {
             log::debug!(
                 "got {} copas, with {} copa per copas for {}",
@@ -168,7 +354,27 @@ pub(crate) fn run(
                 copass[0].len(),
                 tid,
             );
-            setup_tables(tab, schema, &mut ctx, &cis, &copass)?;
+            let defaults = cis
+                .iter()
+                .map(|(cid, _)| ms.get_column_default(*cid))
+                .collect::<Result<Vec<_>, _>>()?;
+            let dels = if with_deleted {
+                None
+            } else {
+                Some(snap.get_deletion_bitmaps())
+            };
+            setup_tables(
+                tab,
+                schema,
+                ctx,
+                &cis,
+                &defaults,
+                &copass,
+                row_ranges.as_ref(),
+                dels.as_ref(),
+                collapsing.as_ref(),
+                &tctx.array_joins,
+            )?;
             copasss.push(copass);
         }
     }
@@ -177,22 +383,96 @@ pub(crate) fn run(
     //     return Ok(res);
     // }
 
-    let res: EngineResult<Vec<RecordBatch>> = tokio::task::block_in_place(|| {
-        Handle::current().block_on(async move {
-            let df = ctx.sql(raw_query).await?;
-            let r = df.collect().await?;
-            Ok(r)
-        })
-    });
-    Ok(res?)
+    Ok(Some(typs))
+}
+
+///return the ptk ranges of the parts which can not be skipped by the stats of
+///the columns in the where clause
+fn prune_by_stats(
+    ms: &MetaStore,
+    ps: &PartStore,
+    tid: Id,
+    qtn: &str,
+    cols: &HashSet<&str>,
+    where_str: &str,
+    ptk_range: Vec<RangeInclusive<u64>>,
+) -> EngineResult<Vec<RangeInclusive<u64>>> {
+    let mut col_ranges = Vec::new();
+    for cn in cols.iter() {
+        if cn.contains('.') {
+            continue;
+        }
+        let cid = match ms.cid_by_qname([qtn, cn].join(".")) {
+            Some(cid) => cid,
+            None => continue,
+        };
+        let typ = match ms.get_column_info(cid)? {
+            Some(ci) if has_stats_minmax(ci.data_type) => ci.data_type,
+            _ => continue,
+        };
+        let key_ranges = parse_where(where_str, cn)?;
+        if key_ranges.len() == 1 && key_ranges[0] == (0..=u64::MAX) {
+            continue;
+        }
+        log::debug!("key_ranges of {}: {:?}", cn, key_ranges);
+        col_ranges.push((cid, stats_key_ranges(typ, key_ranges)));
+    }
+    if col_ranges.len() == 0 {
+        return Ok(ptk_range);
+    }
+    Ok(ps.prune_parts_by_stats(tid, &ptk_range, &col_ranges)?)
+}
+
+///return the row ranges to scan for each part(keyed by ptk) in the snapshot,
+///None if the where clause can not be pruned by the sparse index of primary
+///key
+fn prune_by_sparse_index(
+    ms: &MetaStore,
+    ps: &PartStore,
+    snap: &TableSnapshot,
+    qtn: &str,
+    cols: &HashSet<&str>,
+    where_str: &str,
+) -> EngineResult<Option<HashMap<u64, Vec<(usize, usize)>>>> {
+    let tid = snap.tid;
+    let pk = match ms.get_table_info_sort_key(tid)? {
+        Some(k) => k,
+        _ => return Ok(None),
+    };
+    let pk = pk.as_str();
+    if !cols.contains(pk) {
+        return Ok(None);
+    }
+    let pk_cid = ms
+        .cid_by_qname([qtn, pk].join("."))
+        .ok_or(EngineError::ColumnNotExist)?;
+    let ci = ms
+        .get_column_info(pk_cid)?
+        .ok_or(EngineError::ColumnInfoNotExist)?;
+    if !is_sparse_indexable(ci.data_type) {
+        return Ok(None);
+    }
+    let key_ranges = parse_where(where_str, pk)?;
+    log::debug!("key_ranges: {:?}", key_ranges);
+    if key_ranges.len() == 1 && key_ranges[0] == (0..=u64::MAX) {
+        return Ok(None);
+    }
+    let granularity = ms.get_table_info_index_granularity(tid)?;
+    let rngs = ps.get_row_ranges_by_pk_range(snap, pk_cid, &key_ranges, granularity)?;
+    Ok(Some(rngs))
 }
 
 fn setup_tables(
     tabname: &str,
     schema: Arc<Schema>,
     ctx: &mut ExecutionContext,
-    cis: &Vec<(Id, BqlType)>,
+    cis: &Vec<(Id, ColumnInfo)>,
+    defaults: &Vec<Option<IVec>>,
     copass: &Vec<Vec<CoPaInfo>>,
+    row_ranges: Option<&HashMap<u64, Vec<(usize, usize)>>>,
+    dels: Option<&HashMap<u64, Vec<u8>>>,
+    collapsing: Option<&Collapsing>,
+    array_joins: &HashSet<&str>,
 ) -> EngineResult<()> {
     contract!(copass.len() > 0, "copass should not be empty");
     contract!(
@@ -203,12 +483,21 @@ fn setup_tables(
     let np = copass[0].len();
     let mut batches = Vec::with_capacity(np);
     for i in 0..np {
+        let ptk = copass[0][i].ptk;
+        //NOTE only the rows in the ranges of the part are decompressed
+        let rngs = row_ranges.and_then(|rrs| rrs.get(&ptk));
         let mut cols: Vec<ArrayRef> = Vec::with_capacity(nc);
         for j in 0..nc {
             let copa = &copass[j];
             let cpi = &copa[i];
-            let typ = btype_to_arrow_type(cis[j].1)?;
-            let data = gen_arrow_arraydata(cpi, &typ)?;
+            let ci = &cis[j].1;
+            let typ = btype_to_arrow_type(ci.data_type)?;
+            let data = if cpi.base.rows > 0 {
+                let dft = defaults[j].as_deref();
+                gen_arrow_arraydata_with_base(cpi, &typ, ci, dft, rngs)?
+            } else {
+                gen_arrow_arraydata(cpi, &typ, ci, rngs)?
+            };
             match typ {
                 DataType::Int8 => {
                     cols.push(Arc::new(Int8Array::from(data)));
@@ -234,6 +523,18 @@ fn setup_tables(
                 DataType::UInt64 => {
                     cols.push(Arc::new(UInt64Array::from(data)));
                 }
+                DataType::Int128 => {
+                    cols.push(Arc::new(Int128Array::from(data)));
+                }
+                DataType::UInt128 => {
+                    cols.push(Arc::new(UInt128Array::from(data)));
+                }
+                DataType::Int256 => {
+                    cols.push(Arc::new(Int256Array::from(data)));
+                }
+                DataType::UInt256 => {
+                    cols.push(Arc::new(UInt256Array::from(data)));
+                }
                 // TODO: arrow::array does not have Float16Array
                 // DataType::Float16 => {
                 //     cols.push(Arc::new(Float16Array::from(data)));
@@ -247,6 +548,9 @@ fn setup_tables(
                 DataType::Timestamp32(_) => {
                     cols.push(Arc::new(Timestamp32Array::from(data)));
                 }
+                DataType::Timestamp(_, _) => {
+                    cols.push(make_array(data));
+                }
                 DataType::Date16 => {
                     cols.push(Arc::new(Date16Array::from(data)));
                 }
@@ -259,9 +563,11 @@ fn setup_tables(
                 DataType::FixedSizeBinary(_) => {
                     cols.push(Arc::new(FixedSizeBinaryArray::from(data)));
                 }
+                DataType::List(_) => {
+                    cols.push(Arc::new(ListArray::from(data)));
+                }
                 // DataType::Null => {}
                 // DataType::Boolean => {}
-                // DataType::Timestamp(_, _) => {}
                 // DataType::Date64 => {}
                 // DataType::Time32(_) => {}
                 // DataType::Time64(_) => {}
@@ -270,7 +576,6 @@ fn setup_tables(
                 // DataType::Binary => {}
                 // DataType::LargeBinary => {}
                 // DataType::Utf8 => {}
-                // DataType::List(_) => {}
                 // DataType::FixedSizeList(_, _) => {}
                 // DataType::LargeList(_) => {}
                 // DataType::Struct(_) => {}
@@ -280,47 +585,789 @@ fn setup_tables(
             }
         }
         let batch = RecordBatch::try_new(schema.clone(), cols)?;
-        batches.push(batch);
+        let keep = dels
+            .and_then(|dls| dls.get(&ptk))
+            .map(|bm| gen_keep_mask(bm, batch.num_rows()));
+        match rngs {
+            Some(rngs) => {
+                for (ofs, len) in rngs {
+                    let keep = keep.as_ref().map(|k| &k[*ofs..*ofs + *len]);
+                    batches.push(filter_deleted(batch.slice(*ofs, *len), keep)?);
+                }
+            }
+            None => {
+                let batch = filter_deleted(batch, keep.as_deref())?;
+                batches.push(match collapsing {
+                    Some(c) => collapse_batch(batch, c)?,
+                    None => batch,
+                });
+            }
+        }
     }
     // println!("batches.len: {}", batches.len());
-    ctx.register_table(
-        tabname,
-        Arc::new(MemTable::try_new(schema.clone(), vec![batches])?),
-    )?;
+    let (schema, batches) = join_arrays(schema, batches, array_joins)?;
+    ctx.register_table(tabname, Arc::new(MemTable::try_new(schema, vec![batches])?))?;
 
     Ok(())
 }
 
-fn gen_arrow_arraydata(cpi: &CoPaInfo, typ: &DataType) -> EngineResult<ArrayData> {
-    let dummy = Arc::new(FFI_ArrowArray::empty());
-    let buf = unsafe {
-        let ptr = std::ptr::NonNull::new(cpi.addr as *mut u8)
-            .ok_or(EngineError::UnwrapOptionError)?;
-        Buffer::from_unowned(ptr, cpi.len_in_bytes, dummy)
+///the types of the columns, which are not told by their arrow types, with the
+///names and values of Enum, by the names of the columns
+type ColumnTypes = HashMap<String, (BqlType, Option<EnumValues>)>;
+
+///set the full types of the columns in typs into the metadata of the fields
+///of the results by FIELD_BQL_TYPE
+fn annotate_types(
+    batches: Vec<RecordBatch>,
+    typs: &ColumnTypes,
+) -> EngineResult<Vec<RecordBatch>> {
+    let schema = match batches.first() {
+        Some(b) if !typs.is_empty() => b.schema(),
+        _ => return Ok(batches),
+    };
+    let fields = schema
+        .fields()
+        .iter()
+        .map(|f| -> EngineResult<Field> {
+            let mut f = f.clone();
+            //NOTE the results are of the columns of the same names and types
+            let (typ, ev) = match typs.get(f.name()) {
+                Some((typ, ev)) if &btype_to_arrow_type(*typ)? == f.data_type() => {
+                    (*typ, ev)
+                }
+                _ => return Ok(f),
+            };
+            let typ = match ev {
+                Some(ev) => ev.to_vec(typ)?,
+                None => typ.to_vec()?,
+            };
+            let mut md = f.metadata().clone().unwrap_or_default();
+            md.insert(
+                FIELD_BQL_TYPE.to_string(),
+                String::from_utf8_lossy(&typ).into_owned(),
+            );
+            f.set_metadata(Some(md));
+            Ok(f)
+        })
+        .collect::<EngineResult<_>>()?;
+    let schema = Arc::new(Schema::new(fields));
+    batches
+        .iter()
+        .map(|b| Ok(RecordBatch::try_new(schema.clone(), b.columns().to_vec())?))
+        .collect()
+}
+
+///replace the values of the Enum columns in the results with their names, for
+///the outputs in which the names are returned as strings
+pub fn decode_enums(batch: &RecordBatch) -> EngineResult<RecordBatch> {
+    let schema = batch.schema();
+    let mut fields = Vec::with_capacity(schema.fields().len());
+    let mut cols = Vec::with_capacity(schema.fields().len());
+    for (f, col) in schema.fields().iter().zip(batch.columns()) {
+        let ev = f
+            .metadata()
+            .as_ref()
+            .and_then(|md| md.get(FIELD_BQL_TYPE))
+            .filter(|typ| typ.starts_with("Enum"))
+            .map(|typ| EnumValues::from_bytes(typ.as_bytes()))
+            .transpose()?;
+        match ev {
+            Some((_, ev)) => {
+                fields.push(Field::new(f.name(), DataType::LargeUtf8, f.is_nullable()));
+                cols.push(decode_enum_array(col, &ev)?);
+            }
+            None => {
+                fields.push(f.clone());
+                cols.push(col.clone());
+            }
+        }
+    }
+    Ok(RecordBatch::try_new(Arc::new(Schema::new(fields)), cols)?)
+}
+
+fn decode_enum_array(col: &ArrayRef, ev: &EnumValues) -> EngineResult<ArrayRef> {
+    let vals: Vec<Option<i16>> = match col.data_type() {
+        DataType::Int8 => col
+            .as_any()
+            .downcast_ref::<Int8Array>()
+            .ok_or(EngineError::UnwrapOptionError)?
+            .iter()
+            .map(|v| v.map(|v| v as i16))
+            .collect(),
+        DataType::Int16 => col
+            .as_any()
+            .downcast_ref::<Int16Array>()
+            .ok_or(EngineError::UnwrapOptionError)?
+            .iter()
+            .collect(),
+        _ => return Err(EngineError::UnsupportedBqlType),
+    };
+    //NOTE the names are prefixed by their lengths like those of String
+    let mut om = Vec::with_capacity(vals.len() + 1);
+    let mut buf = Vec::new();
+    let mut nm = Vec::with_capacity(vals.len());
+    om.push(0i64);
+    for v in vals {
+        if let Some(v) = v {
+            //the values have been validated when they are inserted
+            let name = ev
+                .name_of(v)
+                .ok_or(EngineError::UnexpectedDataLoadingError)?;
+            let mut bs = [0u8; 10];
+            let n = encode_varint64(name.len() as u64, &mut bs);
+            buf.extend_from_slice(&bs[..n]);
+            buf.extend_from_slice(name.as_bytes());
+        }
+        nm.push(v.is_none() as u8);
+        om.push(buf.len() as i64);
+    }
+    let data = ArrayData::builder(DataType::LargeUtf8)
+        .len(nm.len())
+        .add_buffer(Buffer::from_slice_ref(&om))
+        .add_buffer(Buffer::from(buf));
+    let data = match gen_arrow_null_bitmap(&nm) {
+        Some((null_count, bitmap)) => {
+            data.null_count(null_count).null_bit_buffer(bitmap).build()
+        }
+        None => data.build(),
+    };
+    Ok(Arc::new(GenericStringArray::<i64>::from(data?)))
+}
+
+///add the column of the elements of the arrays for every column of schema
+///joined by arrayJoin, the rows are repeated for each element of their arrays
+fn join_arrays(
+    mut schema: Arc<Schema>,
+    mut batches: Vec<RecordBatch>,
+    array_joins: &HashSet<&str>,
+) -> EngineResult<(Arc<Schema>, Vec<RecordBatch>)> {
+    let mut cns: Vec<_> = array_joins.iter().collect();
+    cns.sort();
+    for cn in cns {
+        let idx = match schema.index_of(cn) {
+            Ok(idx) => idx,
+            Err(_) => continue,
+        };
+        let f = schema.field(idx);
+        let mut fields = schema.fields().clone();
+        fields.push(Field::new(
+            &array_join_column(cn),
+            f.data_type().clone(),
+            f.is_nullable(),
+        ));
+        let jc = fields.len() - 1;
+        let lschema = Arc::new(Schema::new(fields));
+        let jschema = Arc::new(array_join_schema(&lschema, jc)?);
+        batches = batches
+            .iter()
+            .map(|b| {
+                let mut cols = b.columns().to_vec();
+                cols.push(b.column(idx).clone());
+                let b = RecordBatch::try_new(lschema.clone(), cols)?;
+                Ok(array_join(&b, jc)?)
+            })
+            .collect::<EngineResult<_>>()?;
+        schema = jschema;
+    }
+    Ok((schema, batches))
+}
+
+///project the batches of all columns of a Memory table into the columns cis
+fn project_mem_batches(
+    ms: &MetaStore,
+    qtn: &str,
+    batches: &[RecordBatch],
+    cis: &Vec<(Id, ColumnInfo)>,
+    schema: &Arc<Schema>,
+) -> EngineResult<Vec<RecordBatch>> {
+    //NOTE the batches are of the columns in the order of get_columns_by_qtn
+    let cids: Vec<Id> = ms
+        .get_columns_by_qtn(qtn)?
+        .into_iter()
+        .map(|(_, cid, _)| cid)
+        .collect();
+    let idxs = cis
+        .iter()
+        .map(|(cid, _)| cids.iter().position(|c| c == cid))
+        .collect::<Option<Vec<_>>>()
+        .ok_or(EngineError::ColumnNotExist)?;
+    let mut rt = Vec::with_capacity(batches.len());
+    for batch in batches {
+        let cols = idxs.iter().map(|i| batch.column(*i).clone()).collect();
+        rt.push(RecordBatch::try_new(schema.clone(), cols)?);
+    }
+    Ok(rt)
+}
+
+///read the committed rows of the columns cis of a Log table as one batch
+fn read_log_batches(
+    ps: &PartStore,
+    tid: Id,
+    cis: &Vec<(Id, ColumnInfo)>,
+    schema: &Arc<Schema>,
+) -> EngineResult<Vec<RecordBatch>> {
+    let tl = TabLog::new(ps.get_part_dir(tid, 0)?, tid);
+    let cols: Vec<(Id, BqlType)> =
+        cis.iter().map(|(cid, ci)| (*cid, ci.data_type)).collect();
+    let chks = tl.read_columns(&cols)?;
+    if chks.first().map_or(true, |chk| chk.size == 0) {
+        return Ok(Vec::new());
+    }
+    let mut arrs = Vec::with_capacity(chks.len());
+    for (chk, (_, ci)) in chks.iter().zip(cis) {
+        arrs.push(chk.to_arrow_array(ci.data_type, ci.is_nullable)?);
+    }
+    Ok(vec![RecordBatch::try_new(schema.clone(), arrs)?])
+}
+
+///return the index of the column in the fields, the column is appended if it
+///is not in the fields
+fn index_or_push_column(
+    ms: &MetaStore,
+    qtn: &str,
+    cn: &str,
+    cis: &mut Vec<(Id, ColumnInfo)>,
+    fields: &mut Vec<Field>,
+) -> EngineResult<usize> {
+    if let Some(i) = fields.iter().position(|f| f.name() == cn) {
+        return Ok(i);
+    }
+    let cid = ms
+        .cid_by_qname([qtn, cn].join("."))
+        .ok_or(EngineError::ColumnNotExist)?;
+    let ci = ms
+        .get_column_info(cid)?
+        .ok_or(EngineError::ColumnInfoNotExist)?;
+    cis.push((cid, ci));
+    fields.push(Field::new(
+        cn,
+        btype_to_arrow_type(ci.data_type)?,
+        ci.is_nullable,
+    ));
+    Ok(fields.len() - 1)
+}
+
+///how the rows with the same primary key in a part are collapsed into one
+#[derive(Debug, Clone, PartialEq)]
+pub enum Collapsing {
+    ///keep the row of the max version, see `dedup_batch`
+    Replacing { key: String, ver: Option<String> },
+    ///merge the values of the columns by the functions, see `aggregate_batch`
+    Aggregating {
+        key: String,
+        aggs: Vec<(String, AggFunc)>,
+    },
+}
+
+impl Collapsing {
+    ///the columns should be in the batches to collapse
+    pub fn required_columns(&self) -> Vec<&str> {
+        match self {
+            Collapsing::Replacing { key, ver } => {
+                let mut cns = vec![key.as_str()];
+                cns.extend(ver.as_deref());
+                cns
+            }
+            Collapsing::Aggregating { key, .. } => vec![key.as_str()],
+        }
+    }
+}
+
+///return how the rows of the table are collapsed, None if the engine of the
+///table does not collapse rows
+///
+///NOTE the numeric columns are summed for SummingMergeTree if no column is
+///     given in its parameters
+pub fn get_collapsing(
+    ms: &MetaStore,
+    qtn: &str,
+    tid: Id,
+) -> EngineResult<Option<Collapsing>> {
+    let engine = ms.get_table_info_engine(tid)?;
+    if !matches!(
+        engine,
+        EngineType::ReplacingMergeTree
+            | EngineType::SummingMergeTree
+            | EngineType::AggregatingMergeTree
+    ) {
+        return Ok(None);
+    }
+    let key = match ms.get_table_info_sort_key(tid)? {
+        Some(k) => k,
+        _ => return Ok(None),
+    };
+    let params = ms.get_table_info_engine_params(tid)?;
+    if engine == EngineType::ReplacingMergeTree {
+        let ver = params.into_iter().next();
+        return Ok(Some(Collapsing::Replacing { key, ver }));
+    }
+    let mut aggs = Vec::new();
+    for (cn, _, ci) in ms.get_columns_by_qtn(qtn)? {
+        if cn == key {
+            continue;
+        }
+        let typ = ci.data_type;
+        match typ {
+            BqlType::AggregateFunction(f, _, _) => aggs.push((cn, f)),
+            BqlType::UInt(_) | BqlType::Int(_) | BqlType::Float(_)
+                if engine == EngineType::SummingMergeTree
+                    && (params.is_empty() || params.contains(&cn)) =>
+            {
+                aggs.push((cn, AggFunc::Sum))
+            }
+            _ => {}
+        }
+    }
+    Ok(Some(Collapsing::Aggregating { key, aggs }))
+}
+
+///collapse the rows with the same key in the batch, the columns not in the
+///batch are skipped
+pub fn collapse_batch(batch: RecordBatch, c: &Collapsing) -> EngineResult<RecordBatch> {
+    let schema = batch.schema();
+    match c {
+        Collapsing::Replacing { key, ver } => {
+            let ik = schema.index_of(key)?;
+            let iv = ver.as_ref().map(|v| schema.index_of(v)).transpose()?;
+            dedup_batch(batch, ik, iv)
+        }
+        Collapsing::Aggregating { key, aggs } => {
+            let ik = schema.index_of(key)?;
+            let aggs: Vec<(usize, AggFunc)> = aggs
+                .iter()
+                .filter_map(|(cn, f)| schema.index_of(cn).ok().map(|i| (i, *f)))
+                .collect();
+            aggregate_batch(batch, ik, &aggs)
+        }
+    }
+}
+
+///keep only the row of the max version among the rows with the same key in the
+///batch, the last one is kept if they have the same version or no version
+pub fn dedup_batch(
+    batch: RecordBatch,
+    key: usize,
+    ver: Option<usize>,
+) -> EngineResult<RecordBatch> {
+    let n = batch.num_rows();
+    let keys = batch.column(key);
+    let vers = ver.map(|i| batch.column(i));
+    let mut rows: HashMap<ScalarValue, (usize, Option<ScalarValue>)> = HashMap::new();
+    for i in 0..n {
+        let k = ScalarValue::try_from_array(keys, i)?;
+        let v = match vers {
+            Some(vs) => Some(ScalarValue::try_from_array(vs, i)?),
+            None => None,
+        };
+        match rows.entry(k) {
+            Entry::Occupied(mut e) => {
+                if e.get().1 <= v {
+                    e.insert((i, v));
+                }
+            }
+            Entry::Vacant(e) => {
+                e.insert((i, v));
+            }
+        }
+    }
+    if rows.len() == n {
+        return Ok(batch);
+    }
+    let mut keep = vec![false; n];
+    for (i, _) in rows.values() {
+        keep[*i] = true;
+    }
+    Ok(filter_record_batch(&batch, &BooleanArray::from(keep))?)
+}
+
+///collapse the rows with the same key in the batch into the first one of them,
+///of which the values of the columns in aggs are merged by the functions
+pub fn aggregate_batch(
+    batch: RecordBatch,
+    key: usize,
+    aggs: &[(usize, AggFunc)],
+) -> EngineResult<RecordBatch> {
+    let n = batch.num_rows();
+    let keys = batch.column(key);
+    let mut groups: HashMap<ScalarValue, usize> = HashMap::new();
+    let mut keep = vec![false; n];
+    //the merged states of the groups for every column in aggs
+    let mut states: Vec<Vec<ScalarValue>> = vec![Vec::new(); aggs.len()];
+    for i in 0..n {
+        let k = ScalarValue::try_from_array(keys, i)?;
+        let ng = groups.len();
+        let g = *groups.entry(k).or_insert(ng);
+        keep[i] = g == ng;
+        for ((ic, f), ss) in aggs.iter().zip(states.iter_mut()) {
+            let v = ScalarValue::try_from_array(batch.column(*ic), i)?;
+            if g == ng {
+                ss.push(v);
+            } else {
+                ss[g] = merge_states(*f, &ss[g], v)?;
+            }
+        }
+    }
+    if groups.len() == n {
+        return Ok(batch);
+    }
+    //NOTE the groups are in the order of their first rows
+    let batch = filter_record_batch(&batch, &BooleanArray::from(keep))?;
+    let mut cols = batch.columns().to_vec();
+    for ((ic, _), ss) in aggs.iter().zip(states) {
+        cols[*ic] = ScalarValue::iter_to_array(ss)?;
+    }
+    Ok(RecordBatch::try_new(batch.schema(), cols)?)
+}
+
+fn merge_states(
+    f: AggFunc,
+    s: &ScalarValue,
+    v: ScalarValue,
+) -> EngineResult<ScalarValue> {
+    if v.is_null() {
+        return Ok(s.clone());
+    } else if s.is_null() {
+        return Ok(v);
+    }
+    macro_rules! sum_of {
+        ($($INT:ident),*; $($FLOAT:ident),*) => {
+            match (s, v) {
+                $((ScalarValue::$INT(Some(a)), ScalarValue::$INT(Some(b))) => {
+                    ScalarValue::$INT(Some(a.wrapping_add(b)))
+                })*
+                $((ScalarValue::$FLOAT(Some(a)), ScalarValue::$FLOAT(Some(b))) => {
+                    ScalarValue::$FLOAT(Some(a + b))
+                })*
+                _ => return Err(EngineError::UnsupportedBqlType),
+            }
+        };
+    }
+    Ok(match f {
+        //NOTE integers are wrapped on overflow like ClickHouse
+        AggFunc::Sum => sum_of!(
+            Int8, Int16, Int32, Int64, Int128, Int256, UInt8, UInt16, UInt32, UInt64,
+            UInt128, UInt256; Float32, Float64
+        ),
+        AggFunc::Min if v < *s => v,
+        AggFunc::Max if v > *s => v,
+        AggFunc::AnyLast => v,
+        _ => s.clone(),
+    })
+}
+
+///return whether to keep each of the n rows of the part by its deletion bitmap
+fn gen_keep_mask(bm: &[u8], n: usize) -> Vec<bool> {
+    (0..n)
+        .map(|i| i / 8 >= bm.len() || bm[i / 8] & (1 << (i % 8)) == 0)
+        .collect()
+}
+
+fn filter_deleted(
+    batch: RecordBatch,
+    keep: Option<&[bool]>,
+) -> EngineResult<RecordBatch> {
+    match keep {
+        Some(keep) if keep.iter().any(|k| !k) => Ok(filter_record_batch(
+            &batch,
+            &BooleanArray::from(keep.to_vec()),
+        )?),
+        _ => Ok(batch),
+    }
+}
+
+///NOTE the rows of a compressed copa out of the row ranges(if any) are not
+///     decompressed but zeroed, so they should be sliced off by the caller
+fn gen_arrow_arraydata(
+    cpi: &CoPaInfo,
+    typ: &DataType,
+    ci: &ColumnInfo,
+    rngs: Option<&Vec<(usize, usize)>>,
+) -> EngineResult<ArrayData> {
+    let codec = ci.codec;
+    //NOTE the buffers are the views of the mappings of the copa, which are
+    //     kept until the buffers are dropped
+    let maps = cpi.maps.clone().ok_or(EngineError::UnwrapOptionError)?;
+    let buf = if codec.is_none() {
+        unsafe {
+            let ptr = std::ptr::NonNull::new(cpi.addr as *mut u8)
+                .ok_or(EngineError::UnwrapOptionError)?;
+            Buffer::from_custom_allocation(ptr, cpi.len_in_bytes, maps.clone())
+        }
+    } else {
+        let src = unsafe {
+            std::slice::from_raw_parts(cpi.addr as *const u8, cpi.len_in_bytes)
+        };
+        let width = ci.data_type.size_in_usize()?;
+        let all = [(0, cpi.size)];
+        let rngs = rngs.map_or(&all[..], |r| r.as_slice());
+        Buffer::from(decompress_in_ranges(codec, width, src, cpi.size, rngs)?)
     };
+    if matches!(typ, DataType::List(_)) {
+        if cpi.addr_om.is_null() {
+            return Err(EngineError::UnwrapOptionError);
+        }
+        let om = unsafe {
+            std::slice::from_raw_parts(cpi.addr_om as *const u64, cpi.size + 1)
+        };
+        return Ok(gen_list_array_data(ci.data_type, om, buf)?);
+    }
     let data = if matches!(typ, DataType::LargeUtf8) {
-        let dummy_om = Arc::new(FFI_ArrowArray::empty());
         let buf_om = unsafe {
             let ptr = std::ptr::NonNull::new(cpi.addr_om as *mut u8)
                 .ok_or(EngineError::UnwrapOptionError)?;
-            Buffer::from_unowned(ptr, CoPaInfo::len_in_bytes_om(cpi.size), dummy_om)
+            Buffer::from_custom_allocation(ptr, CoPaInfo::len_in_bytes_om(cpi.size), maps)
         };
         ArrayData::builder(typ.clone())
             .len(cpi.size)
             .add_buffer(buf_om)
             .add_buffer(buf)
-            .build()
     } else {
         ArrayData::builder(typ.clone())
             .len(cpi.size)
             .add_buffer(buf)
-            .build()
+    };
+    let nm = if cpi.addr_nm.is_null() {
+        None
+    } else {
+        Some(unsafe { std::slice::from_raw_parts(cpi.addr_nm as *const u8, cpi.size) })
+    };
+    let data = match nm.and_then(gen_arrow_null_bitmap) {
+        Some((null_count, bitmap)) => {
+            data.null_count(null_count).null_bit_buffer(bitmap).build()
+        }
+        None => data.build(),
     };
     Ok(data?)
 }
 
+///materialize the copa whose first base.rows rows are not in its file, they
+///are the default of the column or converted from the copa of base.src_cid
+fn gen_arrow_arraydata_with_base(
+    cpi: &CoPaInfo,
+    typ: &DataType,
+    ci: &ColumnInfo,
+    default: Option<&[u8]>,
+    rngs: Option<&Vec<(usize, usize)>>,
+) -> EngineResult<ArrayData> {
+    let base = cpi.base;
+    let nb = base.rows.min(cpi.size);
+    let nt = cpi.size - nb;
+    let has_src = base.src_cid != 0;
+    let mut nm = Vec::with_capacity(cpi.size);
+    //NOTE the implicit default of a Nullable column is NULL
+    let null_base = !has_src && ci.is_nullable && default.is_none();
+    if has_src && ci.is_nullable && !cpi.addr_src_nm.is_null() {
+        nm.extend_from_slice(unsafe {
+            std::slice::from_raw_parts(cpi.addr_src_nm as *const u8, nb)
+        });
+    } else {
+        nm.resize(nb, null_base as u8);
+    }
+    if cpi.addr_nm.is_null() {
+        nm.resize(cpi.size, 0);
+    } else {
+        nm.extend_from_slice(unsafe {
+            std::slice::from_raw_parts((cpi.addr_nm as *const u8).add(nb), nt)
+        });
+    }
+
+    let data = if ci.data_type.is_blob() {
+        //NOTE the default of Array is the empty array
+        let dft = default.unwrap_or(&[]);
+        let mut om = Vec::with_capacity(cpi.size + 1);
+        let mut buf = Vec::with_capacity(dft.len() * nb + cpi.len_in_bytes);
+        for i in 0..nb {
+            om.push((i * dft.len()) as u64);
+            buf.extend_from_slice(dft);
+        }
+        if cpi.addr_om.is_null() {
+            om.push(buf.len() as u64);
+        } else {
+            let fom = unsafe {
+                std::slice::from_raw_parts(cpi.addr_om as *const u64, cpi.size + 1)
+            };
+            let (s, e) = (fom[nb] as usize, fom[cpi.size] as usize);
+            let ofs = buf.len() as u64;
+            om.extend(fom[nb..].iter().map(|o| o - s as u64 + ofs));
+            buf.extend_from_slice(unsafe {
+                std::slice::from_raw_parts((cpi.addr as *const u8).add(s), e - s)
+            });
+        }
+        if matches!(typ, DataType::List(_)) {
+            return Ok(gen_list_array_data(ci.data_type, &om, Buffer::from(buf))?);
+        }
+        ArrayData::builder(typ.clone())
+            .len(cpi.size)
+            .add_buffer(Buffer::from_slice_ref(&om))
+            .add_buffer(Buffer::from(buf))
+    } else {
+        let width = ci.data_type.size_in_usize()?;
+        let mut buf = Vec::with_capacity(cpi.size * width);
+        if has_src {
+            let src = unsafe {
+                std::slice::from_raw_parts(
+                    cpi.addr_src as *const u8,
+                    nb * base.src_type.size_in_usize()?,
+                )
+            };
+            buf.extend(convert_numeric_values(src, base.src_type, ci.data_type)?);
+        } else {
+            match default {
+                Some(dft) if dft.len() == width => {
+                    for _ in 0..nb {
+                        buf.extend_from_slice(dft);
+                    }
+                }
+                _ => buf.resize(nb * width, 0),
+            }
+        }
+        if !cpi.addr.is_null() {
+            let src = unsafe {
+                std::slice::from_raw_parts(cpi.addr as *const u8, cpi.len_in_bytes)
+            };
+            if ci.codec.is_none() {
+                buf.extend_from_slice(&src[nb * width..cpi.size * width]);
+            } else {
+                //NOTE only the rows after the base are in the compressed blocks
+                let rngs: Vec<(usize, usize)> = match rngs {
+                    Some(rngs) => rngs
+                        .iter()
+                        .filter(|(ofs, len)| ofs + len > nb)
+                        .map(|(ofs, len)| {
+                            let s = ofs.saturating_sub(nb);
+                            (s, ofs + len - nb - s)
+                        })
+                        .collect(),
+                    None => vec![(0, nt)],
+                };
+                buf.extend(decompress_in_ranges(ci.codec, width, src, nt, &rngs)?);
+            }
+        }
+        buf.resize(cpi.size * width, 0);
+        ArrayData::builder(typ.clone())
+            .len(cpi.size)
+            .add_buffer(Buffer::from(buf))
+    };
+    let data = match gen_arrow_null_bitmap(&nm) {
+        Some((null_count, bitmap)) => {
+            data.null_count(null_count).null_bit_buffer(bitmap).build()
+        }
+        None => data.build(),
+    };
+    Ok(data?)
+}
+
+///convert the null map (1 for NULL) of a copa into the arrow validity
+///bitmap (1 for valid), or None if there is no NULL in the copa
+fn gen_arrow_null_bitmap(nm: &[u8]) -> Option<(usize, Buffer)> {
+    let null_count = nm.iter().filter(|&&b| b != 0).count();
+    if null_count == 0 {
+        return None;
+    }
+    let mut bitmap = vec![0u8; (nm.len() + 7) / 8];
+    for (i, &b) in nm.iter().enumerate() {
+        if b == 0 {
+            bitmap[i >> 3] |= 1 << (i & 7);
+        }
+    }
+    Some((null_count, Buffer::from(bitmap)))
+}
+
 #[cfg(test)]
 mod unit_tests {
+    use super::*;
+
     #[test]
     fn test() {}
+
+    #[test]
+    fn test_dedup_batch() -> EngineResult<()> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::UInt64, false),
+            Field::new("ver", DataType::UInt32, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(UInt64Array::from(vec![1, 2, 1, 3, 2, 1])),
+                Arc::new(UInt32Array::from(vec![5, 1, 3, 1, 1, 5])),
+            ],
+        )?;
+
+        let res = dedup_batch(batch.clone(), 0, Some(1))?;
+        let ids = res
+            .column(0)
+            .as_any()
+            .downcast_ref::<UInt64Array>()
+            .unwrap();
+        assert_eq!(ids.values(), &[3, 2, 1]);
+
+        let res = dedup_batch(batch.clone(), 0, None)?;
+        let vers = res
+            .column(1)
+            .as_any()
+            .downcast_ref::<UInt32Array>()
+            .unwrap();
+        assert_eq!(vers.values(), &[1, 1, 5]);
+
+        let res = dedup_batch(batch, 1, None)?;
+        assert_eq!(res.num_rows(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_aggregate_batch() -> EngineResult<()> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::UInt64, false),
+            Field::new("a", DataType::Int32, false),
+            Field::new("b", DataType::Float64, false),
+            Field::new("c", DataType::UInt8, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(UInt64Array::from(vec![2, 1, 2, 3, 1])),
+                Arc::new(Int32Array::from(vec![1, 2, 3, 4, i32::MAX])),
+                Arc::new(Float64Array::from(vec![1.5, 2.0, 0.5, 1.0, 3.0])),
+                Arc::new(UInt8Array::from(vec![7, 8, 9, 10, 11])),
+            ],
+        )?;
+
+        let res =
+            aggregate_batch(batch.clone(), 0, &[(1, AggFunc::Sum), (2, AggFunc::Max)])?;
+        let ids = res
+            .column(0)
+            .as_any()
+            .downcast_ref::<UInt64Array>()
+            .unwrap();
+        assert_eq!(ids.values(), &[2, 1, 3]);
+        let a = res.column(1).as_any().downcast_ref::<Int32Array>().unwrap();
+        assert_eq!(a.values(), &[4, i32::MIN + 1, 4]);
+        let b = res
+            .column(2)
+            .as_any()
+            .downcast_ref::<Float64Array>()
+            .unwrap();
+        assert_eq!(b.values(), &[1.5, 3.0, 1.0]);
+        //the values of the first rows are kept for the other columns
+        let c = res.column(3).as_any().downcast_ref::<UInt8Array>().unwrap();
+        assert_eq!(c.values(), &[7, 8, 10]);
+
+        let res = aggregate_batch(
+            batch.clone(),
+            0,
+            &[(1, AggFunc::Min), (3, AggFunc::AnyLast)],
+        )?;
+        let a = res.column(1).as_any().downcast_ref::<Int32Array>().unwrap();
+        assert_eq!(a.values(), &[1, 2, 4]);
+        let c = res.column(3).as_any().downcast_ref::<UInt8Array>().unwrap();
+        assert_eq!(c.values(), &[9, 11, 10]);
+
+        let c = Collapsing::Aggregating {
+            key: "c".to_string(),
+            aggs: vec![("x".to_string(), AggFunc::Sum)],
+        };
+        assert_eq!(collapse_batch(batch, &c)?.num_rows(), 5);
+        Ok(())
+    }
 }
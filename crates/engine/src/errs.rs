@@ -25,6 +25,9 @@ pub enum EngineError {
     #[error("Unsupported Query")]
     UnsupportedQuery,
 
+    #[error("Table engine does not support FINAL")]
+    FinalNotSupported,
+
     #[error(transparent)]
     WrappingDFError(#[from] datafusion::error::DataFusionError),
 
@@ -4,6 +4,7 @@ use arrow::record_batch::RecordBatch;
 use errs::EngineResult;
 use lang::parse::{self, Pair, Rule};
 use meta::store::{parts::PartStore, sys::MetaStore};
+use std::ops::RangeInclusive;
 use types::QueryState;
 
 pub mod datafusions;
@@ -26,3 +27,16 @@ pub fn run(
     log::debug!("projections - {:?}", tctx);
     datafusions::run(ms, ps, current_db, raw_query, query_id, tctx, qs)
 }
+
+pub fn run_by_parts(
+    ms: &MetaStore,
+    ps: &PartStore,
+    current_db: &str,
+    p: Pair<Rule>,
+    raw_query: &str,
+    ptks: RangeInclusive<u64>,
+    qs: &mut QueryState,
+) -> EngineResult<Vec<(u64, RecordBatch)>> {
+    let tctx = parse::parse_tables(p)?;
+    datafusions::run_by_parts(ms, ps, current_db, raw_query, tctx, ptks, qs)
+}
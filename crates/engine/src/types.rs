@@ -1,3 +1,6 @@
+use std::collections::HashMap;
+
+use arrow::record_batch::RecordBatch;
 use meta::{
     store::parts::CoPaInfo,
     types::{BqlType, Id},
@@ -9,6 +12,8 @@ pub struct QueryState {
     pub copasss: Vec<Vec<Vec<CoPaInfo>>>,
     pub tid: Id,
     pub cis: Vec<(Id, BqlType)>,
+    ///the batches of the Memory tables keyed by tid
+    pub mem_tables: HashMap<Id, Vec<RecordBatch>>,
 }
 
 unsafe impl Send for QueryState {}
@@ -24,6 +29,7 @@ impl Default for QueryState {
             copasss: Vec::new(),
             tid: 0,
             cis: Vec::new(),
+            mem_tables: HashMap::new(),
         }
     }
 }
@@ -49,7 +49,13 @@ const CID1: Id = TID + 2;
 #[tokio::test]
 #[ignore]
 async fn basic_query() -> EngineResult<()> {
-    let cis = vec![(CID0, BqlType::UInt(32))];
+    let cis = vec![(
+        CID0,
+        ColumnInfo {
+            data_type: BqlType::UInt(32),
+            ..Default::default()
+        },
+    )];
     let mut query_id = 1u64;
     let sql = "select sum(trip_id) from trips_lite";
     // let sql = "select sum(123*trip_id+456) from trips_lite where trip_id>666";
@@ -70,7 +76,7 @@ async fn basic_query() -> EngineResult<()> {
     Ok(())
 }
 
-fn init_copas2(cis: Vec<(Id, BqlType)>) -> Vec<Vec<CoPaInfo>> {
+fn init_copas2(cis: Vec<(Id, ColumnInfo)>) -> Vec<Vec<CoPaInfo>> {
     let parts_dir = "/jin/tmp/tb_schema";
     let data_dirs = vec!["/jin/tmp/tb_data".to_string()];
     let dd = &data_dirs;
@@ -84,7 +90,7 @@ fn init_copas2(cis: Vec<(Id, BqlType)>) -> Vec<Vec<CoPaInfo>> {
 
 async fn run_ker_test_arrow(
     query_id: &str,
-    cis: Vec<(Id, BqlType)>,
+    cis: Vec<(Id, ColumnInfo)>,
     exprs: Vec<&str>,
     expected_results_rows_wised: Vec<Vec<i64>>,
     filters_expr: Option<&str>,
@@ -43,6 +43,9 @@ pub enum LangError {
     #[error("Error when create table parsing")]
     CreateTableParsingError,
 
+    #[error("Error when alter table parsing")]
+    AlterTableParsingError,
+
     #[error("Error when query language parsing")]
     QueryLangParsingError,
 
@@ -57,4 +60,7 @@ pub enum LangError {
 
     #[error("Conflict condition when partition key pxpr parsing")]
     PartitionKeyExprParsingConflict,
+
+    #[error("Unknown element {0} for Enum")]
+    UnknownEnumName(String),
 }
@@ -5,14 +5,15 @@
 
 use base::contract;
 use base::errs::BaseError;
-use base::eval::eval_literal_u64;
-use meta::types::{BqlType, ColumnInfo, EngineType, Table};
+use base::eval::{eval_literal, eval_literal_u64};
+use meta::types::{BqlType, CodecType, ColumnInfo, EngineType, EnumValues, Table};
 pub use pest::iterators::Pair;
 pub use pest::iterators::Pairs;
+use pest::Span;
 use std::collections::HashSet;
 use std::mem::swap;
 use std::net::IpAddr;
-use std::ops::RangeInclusive;
+use std::ops::{Range, RangeInclusive};
 use std::vec;
 
 pub(crate) use pest::Parser;
@@ -107,6 +108,7 @@ pub fn parse_create_database(pair: Pair<Rule>) -> LangResult<DbInfo> {
     Ok(rt)
 }
 
+///NOTE also used for CHECK TABLE
 pub fn parse_optimize_table(pair: Pair<Rule>) -> LangResult<(Option<String>, String)> {
     let mut dbname = None;
     let mut tabname = String::new();
@@ -133,6 +135,85 @@ pub fn parse_optimize_table(pair: Pair<Rule>) -> LangResult<(Option<String>, Str
     Ok((dbname, tabname))
 }
 
+///ptk is None for all partitions of the table
+#[derive(Debug, Default, PartialEq)]
+pub struct OptimizeContext {
+    pub dbname: Option<String>,
+    pub tabname: String,
+    pub ptk: Option<u64>,
+    pub is_final: bool,
+}
+
+pub fn parse_optimize(pair: Pair<Rule>) -> LangResult<OptimizeContext> {
+    let (dbname, tabname) = parse_optimize_table(pair.clone())?;
+    let mut ctx = OptimizeContext {
+        dbname,
+        tabname,
+        ..Default::default()
+    };
+    for p in pair.into_inner() {
+        match p.as_rule() {
+            Rule::partition_expr => ctx.ptk = Some(eval_literal_u64(p.as_str().trim())?),
+            Rule::optimize_final => ctx.is_final = true,
+            _ => {}
+        }
+    }
+
+    Ok(ctx)
+}
+
+///'it''s' -> it's
+fn unquote_string_literal(lit: &str) -> String {
+    let lit = lit.trim();
+    lit[1..lit.len() - 1].replace("''", "'")
+}
+
+///tabname is None for the whole database
+#[derive(Debug, Default, PartialEq)]
+pub struct BackupContext {
+    pub dbname: Option<String>,
+    pub tabname: Option<String>,
+    pub path: String,
+}
+
+///NOTE also used for RESTORE
+pub fn parse_backup(pair: Pair<Rule>) -> LangResult<BackupContext> {
+    let mut ctx = BackupContext::default();
+    for p in pair.into_inner() {
+        match p.as_rule() {
+            Rule::backup_table => {
+                for p in p.into_inner() {
+                    if p.as_rule() == Rule::qualified_table_name {
+                        let qtn = p.as_str().trim();
+                        match qtn.split('.').collect::<Vec<&str>>()[..] {
+                            [dbn, tn] => {
+                                ctx.dbname = Some(dbn.to_owned());
+                                ctx.tabname = Some(tn.to_owned());
+                            }
+                            [tn] => ctx.tabname = Some(tn.to_owned()),
+                            _ => return Err(LangError::QueryLangParsingError),
+                        }
+                    }
+                }
+            }
+            Rule::backup_database => {
+                for p in p.into_inner() {
+                    if p.as_rule() == Rule::database_name {
+                        ctx.dbname = Some(p.as_str().trim().to_owned());
+                    }
+                }
+            }
+            Rule::backup_path => ctx.path = unquote_string_literal(p.as_str()),
+            _ => {}
+        }
+    }
+    if ctx.path.is_empty() {
+        return Err(LangError::QueryLangParsingError);
+    }
+
+    Ok(ctx)
+}
+
 pub fn parse_drop_database(pair: Pair<Rule>) -> LangResult<DbInfo> {
     let mut rt = DbInfo {
         dbname: String::new(),
@@ -405,6 +486,10 @@ impl CreateTabContext {
 
                         col.1.data_type = BqlType::from_str(typ)?;
                         col.1.is_nullable = true;
+                        if matches!(col.1.data_type, BqlType::Enum8 | BqlType::Enum16) {
+                            let (_, ev) = EnumValues::from_bytes(typ.as_bytes())?;
+                            self.tab.enum_values.push((col.0.clone(), ev));
+                        }
                     }
                     // Some(p) if p.as_rule() == Rule::decimal_type => {
                     //     col.1.data_type = BqlType::LowCardinalityString;
@@ -413,14 +498,52 @@ impl CreateTabContext {
                         if p.as_rule() == Rule::simple_type
                             || p.as_rule() == Rule::decimal_type
                             || p.as_rule() == Rule::datetime_type
-                            || p.as_rule() == Rule::fixed_string_type =>
+                            || p.as_rule() == Rule::fixed_string_type
+                            || p.as_rule() == Rule::aggregate_function_type
+                            || p.as_rule() == Rule::array_type =>
                     {
                         let typ = p.as_str().trim();
                         col.1.data_type = BqlType::from_str(typ)?;
                     }
+                    //the names and values are kept apart from the type
+                    Some(p) if p.as_rule() == Rule::enum_type => {
+                        let (typ, ev) =
+                            EnumValues::from_bytes(p.as_str().trim().as_bytes())?;
+                        col.1.data_type = typ;
+                        self.tab.enum_values.push((col.0.clone(), ev));
+                    }
                     _ => return Err(LangError::UnsupportedBqlTypeError),
                 }
             }
+            Rule::codec_spec => {
+                let col = self
+                    .tab
+                    .columns
+                    .last_mut()
+                    .ok_or(LangError::CreateTableParsingError)?;
+                let mut ps = pair.into_inner();
+                let name = ps
+                    .next()
+                    .ok_or(LangError::CreateTableParsingError)?
+                    .as_str()
+                    .trim();
+                let level = match ps.next() {
+                    Some(p) => Some(p.as_str().trim().parse::<u8>()?),
+                    None => None,
+                };
+                let mut codec = col.1.codec;
+                codec.add(CodecType::from_str(name)?, level)?;
+                col.1.codec = codec;
+            }
+            Rule::column_def => {
+                let col = self
+                    .tab
+                    .columns
+                    .last()
+                    .ok_or(LangError::CreateTableParsingError)?;
+                let codec = col.1.codec;
+                codec.validate(col.1.data_type)?;
+            }
             Rule::column_constraint => {
                 let col = self
                     .tab
@@ -440,9 +563,18 @@ impl CreateTabContext {
             }
             Rule::table_attr_engine => {
                 let ti = &mut self.tab.tab_info;
-                let eng_code = pair.into_inner().as_str().trim();
+                let eng_code = pair
+                    .into_inner()
+                    .next()
+                    .ok_or(LangError::CreateTableParsingError)?
+                    .as_str()
+                    .trim();
                 ti.engine = EngineType::from_str(eng_code)?;
             }
+            Rule::engine_param => {
+                let ti = &mut self.tab.tab_info;
+                ti.engine_params.push(pair.as_str().trim().to_string());
+            }
             Rule::qualified_name => {
                 let ti = &mut self.tab.tab_info;
                 let ptk = pair.into_inner().as_str().trim();
@@ -457,6 +589,38 @@ impl CreateTabContext {
                 //FIXME need more precise validations
                 ti.partition_keys_expr = part_keys_expr.to_string();
             }
+            Rule::table_attr_ttl => {
+                //NOTE only `col + INTERVAL n unit` is supported, not the general
+                //     expressions on the column
+                let ti = &mut self.tab.tab_info;
+                let mut ps = pair.into_inner();
+                let col = ps.next().ok_or(LangError::CreateTableParsingError)?;
+                let n = ps.next().ok_or(LangError::CreateTableParsingError)?;
+                let unit = ps.next().ok_or(LangError::CreateTableParsingError)?;
+                let unit_secs = match unit.as_str().to_ascii_lowercase().as_str() {
+                    "second" => 1,
+                    "minute" => 60,
+                    "hour" => 3600,
+                    "day" => 86400,
+                    "week" => 7 * 86400,
+                    _ => return Err(LangError::UnsupportedLangFeatureError),
+                };
+                ti.ttl_col = col.as_str().trim().to_string();
+                ti.ttl_secs = n
+                    .as_str()
+                    .trim()
+                    .parse::<u64>()?
+                    .checked_mul(unit_secs)
+                    .ok_or(LangError::CreateTableParsingError)?;
+            }
+            Rule::attr_pair => {
+                let ti = &mut self.tab.tab_info;
+                let mut kv = pair.into_inner();
+                let k = kv.next().ok_or(LangError::CreateTableParsingError)?;
+                let v = kv.next().ok_or(LangError::CreateTableParsingError)?;
+                ti.settings
+                    .insert(k.as_str().trim().to_string(), v.as_str().trim().to_string());
+            }
 
             _ => {}
         }
@@ -488,6 +652,247 @@ pub fn parse_create_table(pair: Pair<Rule>) -> LangResult<(Table, bool)> {
     Ok((ctx.tab, ctx.fallible))
 }
 
+#[derive(Debug, PartialEq)]
+pub enum AlterTableAction {
+    ///the DEFAULT literal is kept as its text, and the names and values are
+    ///kept in enum_values for Enum8/Enum16
+    AddColumn {
+        name: String,
+        info: ColumnInfo,
+        default: Option<String>,
+        enum_values: Option<EnumValues>,
+        fallible: bool,
+    },
+    DropColumn {
+        name: String,
+        fallible: bool,
+    },
+    RenameColumn {
+        from: String,
+        to: String,
+        fallible: bool,
+    },
+    ///only data_type and is_nullable of info are set
+    ModifyColumn {
+        name: String,
+        info: ColumnInfo,
+        fallible: bool,
+    },
+    ///the predicate is the text of the WHERE expression
+    Delete {
+        predicate: String,
+    },
+    ///the assignments are (column name, text of the expression)
+    Update {
+        assignments: Vec<(String, String)>,
+        predicate: String,
+    },
+    ///ptk is the evaluated value of the partition expression
+    DropPartition {
+        ptk: u64,
+    },
+    DetachPartition {
+        ptk: u64,
+    },
+    AttachPartition {
+        ptk: u64,
+    },
+    MovePartition {
+        ptk: u64,
+        dbname: Option<String>,
+        tabname: String,
+    },
+    MovePartitionToVolume {
+        ptk: u64,
+        volume: String,
+    },
+    ///move the parts into the dirs with more available space in their volumes
+    Rebalance,
+}
+
+#[derive(Debug, Default)]
+pub struct AlterTableContext {
+    pub dbname: Option<String>,
+    pub tabname: String,
+    pub actions: Vec<AlterTableAction>,
+}
+
+fn parse_alter_qualified_table_name(
+    pair: Pair<Rule>,
+) -> LangResult<(Option<String>, String)> {
+    let qtn = pair.as_str().trim();
+    let qtn: Vec<&str> = qtn.split('.').collect();
+    match qtn[..] {
+        [dbn, tn] => Ok((Some(dbn.to_owned()), tn.to_owned())),
+        [tn] => Ok((None, tn.to_owned())),
+        _ => Err(LangError::AlterTableParsingError),
+    }
+}
+
+pub fn parse_alter_table(pair: Pair<Rule>) -> LangResult<AlterTableContext> {
+    let mut ctx = AlterTableContext::default();
+    for p in pair.into_inner() {
+        match p.as_rule() {
+            Rule::qualified_table_name => {
+                let (dbname, tabname) = parse_alter_qualified_table_name(p)?;
+                ctx.dbname = dbname;
+                ctx.tabname = tabname;
+            }
+            _ => ctx.actions.push(parse_alter_table_action(p)?),
+        }
+    }
+    Ok(ctx)
+}
+
+fn parse_alter_table_action(pair: Pair<Rule>) -> LangResult<AlterTableAction> {
+    let r = pair.as_rule();
+    match r {
+        Rule::alter_delete | Rule::alter_update => {
+            return parse_alter_table_mutation(pair)
+        }
+        Rule::alter_drop_partition
+        | Rule::alter_detach_partition
+        | Rule::alter_attach_partition
+        | Rule::alter_move_partition => return parse_alter_table_partition(pair),
+        Rule::alter_rebalance => return Ok(AlterTableAction::Rebalance),
+        _ => {}
+    }
+    let mut fallible = true;
+    let mut names = Vec::new();
+    let mut default = None;
+    //NOTE reuse the parsing of column types and codecs in CREATE TABLE
+    let mut cctx = CreateTabContext {
+        tab: Default::default(),
+        fallible: true,
+    };
+    cctx.tab.columns.push(Default::default());
+    for p in pair.into_inner() {
+        match p.as_rule() {
+            Rule::if_exists | Rule::if_not_exists => fallible = false,
+            Rule::column_name => names.push(p.as_str().trim().to_string()),
+            Rule::type_name => cctx.parse(p)?,
+            Rule::column_def => {
+                for cp in p.into_inner() {
+                    match cp.as_rule() {
+                        Rule::column_name => names.push(cp.as_str().trim().to_string()),
+                        //only DEFAULT is supported for an added column
+                        Rule::column_constraint => match cp.into_inner().next() {
+                            Some(lit) if lit.as_rule() == Rule::literal => {
+                                default = Some(lit.as_str().trim().to_string());
+                            }
+                            _ => return Err(LangError::UnsupportedLangFeatureError),
+                        },
+                        _ => cctx.parse(cp)?,
+                    }
+                }
+            }
+            _ => return Err(LangError::AlterTableParsingError),
+        }
+    }
+    let info = cctx
+        .tab
+        .columns
+        .pop()
+        .ok_or(LangError::AlterTableParsingError)?
+        .1;
+    let mut names = names.into_iter();
+    let name = names.next().ok_or(LangError::AlterTableParsingError)?;
+    match r {
+        Rule::alter_add_column => {
+            let codec = info.codec;
+            codec.validate(info.data_type)?;
+            Ok(AlterTableAction::AddColumn {
+                name,
+                info,
+                default,
+                enum_values: cctx.tab.enum_values.pop().map(|(_, ev)| ev),
+                fallible,
+            })
+        }
+        Rule::alter_drop_column => Ok(AlterTableAction::DropColumn { name, fallible }),
+        Rule::alter_rename_column => Ok(AlterTableAction::RenameColumn {
+            from: name,
+            to: names.next().ok_or(LangError::AlterTableParsingError)?,
+            fallible,
+        }),
+        Rule::alter_modify_column => Ok(AlterTableAction::ModifyColumn {
+            name,
+            info,
+            fallible,
+        }),
+        _ => Err(LangError::AlterTableParsingError),
+    }
+}
+
+fn parse_alter_table_mutation(pair: Pair<Rule>) -> LangResult<AlterTableAction> {
+    let r = pair.as_rule();
+    let mut assignments = Vec::new();
+    let mut predicate = String::new();
+    for p in pair.into_inner() {
+        match p.as_rule() {
+            Rule::alter_update_assignment => {
+                let mut ps = p.into_inner();
+                let mut next_str = || -> LangResult<String> {
+                    let p = ps.next().ok_or(LangError::AlterTableParsingError)?;
+                    Ok(p.as_str().trim().to_string())
+                };
+                let cn = next_str()?;
+                let e = next_str()?;
+                assignments.push((cn, e));
+            }
+            Rule::where_clause => {
+                let p = p
+                    .into_inner()
+                    .next()
+                    .ok_or(LangError::AlterTableParsingError)?;
+                predicate.push_str(p.as_str().trim());
+            }
+            _ => return Err(LangError::AlterTableParsingError),
+        }
+    }
+    match r {
+        Rule::alter_delete => Ok(AlterTableAction::Delete { predicate }),
+        _ => Ok(AlterTableAction::Update {
+            assignments,
+            predicate,
+        }),
+    }
+}
+
+fn parse_alter_table_partition(pair: Pair<Rule>) -> LangResult<AlterTableAction> {
+    let r = pair.as_rule();
+    let mut ptk = None;
+    let mut target = None;
+    let mut volume = None;
+    for p in pair.into_inner() {
+        match p.as_rule() {
+            Rule::partition_expr => ptk = Some(eval_literal_u64(p.as_str().trim())?),
+            Rule::qualified_table_name => {
+                target = Some(parse_alter_qualified_table_name(p)?)
+            }
+            Rule::volume_name => volume = Some(unquote_string_literal(p.as_str())),
+            _ => return Err(LangError::AlterTableParsingError),
+        }
+    }
+    let ptk = ptk.ok_or(LangError::AlterTableParsingError)?;
+    match r {
+        Rule::alter_drop_partition => Ok(AlterTableAction::DropPartition { ptk }),
+        Rule::alter_detach_partition => Ok(AlterTableAction::DetachPartition { ptk }),
+        Rule::alter_attach_partition => Ok(AlterTableAction::AttachPartition { ptk }),
+        _ => match (target, volume) {
+            (Some((dbname, tabname)), _) => Ok(AlterTableAction::MovePartition {
+                ptk,
+                dbname,
+                tabname,
+            }),
+            (_, Some(volume)) => {
+                Ok(AlterTableAction::MovePartitionToVolume { ptk, volume })
+            }
+            _ => Err(LangError::AlterTableParsingError),
+        },
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct TablesContext<'a> {
     pub tabs: HashSet<&'a str>,
@@ -495,6 +900,15 @@ pub struct TablesContext<'a> {
     pub has_count_all: bool,
     pub has_select_all: bool,
     pub where_str: &'a str,
+    ///the tables queried with FINAL
+    pub finals: HashSet<&'a str>,
+    ///the columns of which the arrays are joined by arrayJoin
+    pub array_joins: HashSet<&'a str>,
+    ///the string literals compared with the columns, by the columns
+    col_strs: Vec<(&'a str, Span<'a>)>,
+    ///the ranges in the query to rewrite, and what they are rewritten into
+    rewrites: Vec<(Range<usize>, String)>,
+    start: usize,
 }
 
 impl<'a> TablesContext<'a> {
@@ -507,6 +921,34 @@ impl<'a> TablesContext<'a> {
             Rule::qualified_table_name => {
                 self.tabs.insert(pair.as_str().trim());
             }
+            Rule::join_table => {
+                let mut ps = pair.into_inner();
+                match (ps.next(), ps.next()) {
+                    (Some(t), Some(f))
+                        if t.as_rule() == Rule::qualified_table_name
+                            && f.as_rule() == Rule::table_final =>
+                    {
+                        self.finals.insert(t.as_str().trim());
+                        self.rewrite(f.as_span(), String::new());
+                    }
+                    _ => {}
+                }
+            }
+            Rule::func_call_expr => {
+                let sp = pair.as_span();
+                let mut ps = pair.into_inner();
+                match (ps.next(), ps.next(), ps.next()) {
+                    (Some(f), Some(a), None)
+                        if f.as_str().trim() == "arrayJoin"
+                            && is_column_name(a.as_str().trim()) =>
+                    {
+                        let cn = a.as_str().trim();
+                        self.array_joins.insert(cn);
+                        self.rewrite(sp, array_join_column(cn));
+                    }
+                    _ => {}
+                }
+            }
             Rule::qualified_name => {
                 self.cols.insert(pair.as_str().trim());
             }
@@ -519,15 +961,104 @@ impl<'a> TablesContext<'a> {
             Rule::where_clause => {
                 self.where_str = pair.as_str().trim();
             }
+            Rule::comp_expr_cmp => {
+                let mut ps = pair.into_inner();
+                if let (Some(l), Some(_), Some(r)) = (ps.next(), ps.next(), ps.next()) {
+                    self.add_col_strs(l.clone(), r.clone());
+                    self.add_col_strs(r, l);
+                }
+            }
+            Rule::in_expr | Rule::between_expr => {
+                let mut ps = pair.into_inner();
+                if let Some(c) = ps.next() {
+                    for p in ps {
+                        self.add_col_strs(c.clone(), p);
+                    }
+                }
+            }
             _ => {}
         }
 
         Ok(())
     }
+
+    //add the string literals in lits if col is a column
+    fn add_col_strs(&mut self, col: Pair<'a, Rule>, lits: Pair<'a, Rule>) {
+        let cn = col.as_str().trim();
+        let is_col = col
+            .into_inner()
+            .flatten()
+            .any(|p| p.as_rule() == Rule::qualified_name && p.as_str() == cn);
+        if !is_col {
+            return;
+        }
+        //NOTE the literals are the operand itself, or the array of in_expr
+        let s = lits.as_str().trim();
+        let in_array = lits.as_rule() == Rule::const_str_array;
+        for p in lits.into_inner().flatten() {
+            if p.as_rule() == Rule::string_literal && (in_array || p.as_str() == s) {
+                self.col_strs.push((cn, p.as_span()));
+            }
+        }
+    }
+
+    ///rewrite the names compared with the Enum columns into their values, so
+    ///that the values are compared and sorted like those in ClickHouse, the
+    ///names and values of the columns are given by enum_of
+    pub fn rewrite_enum_names<'b, F>(&mut self, enum_of: F) -> LangResult<()>
+    where
+        F: Fn(&str) -> Option<&'b EnumValues>,
+    {
+        for i in 0..self.col_strs.len() {
+            let (cn, sp) = self.col_strs[i].clone();
+            if let Some(ev) = enum_of(cn) {
+                let lit = sp.as_str();
+                let name = lit[1..lit.len() - 1].replace("''", "'");
+                let v = ev
+                    .value_of(name.as_bytes())
+                    .ok_or(LangError::UnknownEnumName(name))?;
+                self.rewrite(sp, v.to_string());
+            }
+        }
+        Ok(())
+    }
+
+    fn rewrite(&mut self, sp: Span, to: String) {
+        self.rewrites
+            .push((sp.start() - self.start..sp.end() - self.start, to));
+    }
+
+    ///return the query, which is the one parsed, without the FINAL modifiers
+    ///and with the arrayJoin calls replaced by the columns of the elements, and
+    ///the names of Enum replaced by their values if rewrite_enum_names
+    pub fn rewrite_query(&self, raw_query: &str) -> String {
+        let mut rws: Vec<_> = self.rewrites.iter().collect();
+        rws.sort_by_key(|(r, _)| r.start);
+        let mut q = String::with_capacity(raw_query.len());
+        let mut ofs = 0;
+        for (r, to) in rws {
+            q.push_str(&raw_query[ofs..r.start]);
+            q.push_str(to);
+            ofs = r.end;
+        }
+        q.push_str(&raw_query[ofs..]);
+        q
+    }
+}
+
+///the column of the elements of the arrays in the column cn joined by arrayJoin
+pub fn array_join_column(cn: &str) -> String {
+    ["__array_join_", cn].concat()
+}
+
+#[inline]
+fn is_column_name(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
 }
 
 pub fn parse_tables(pair: Pair<Rule>) -> LangResult<TablesContext> {
     let mut ctx = TablesContext {
+        start: pair.as_span().start(),
         ..Default::default()
     };
     ctx.parse(pair)?;
@@ -536,6 +1067,32 @@ pub fn parse_tables(pair: Pair<Rule>) -> LangResult<TablesContext> {
     Ok(ctx)
 }
 
+///the largest integer which an evaluated literal represents exactly
+const MAX_EXACT_LITERAL: f64 = 9007199254740992.0;
+
+///evaluates a literal compared with the ptk_expr, only the non-negative
+///integers which are exactly representable can be used to prune
+fn eval_ptk_literal(expr: &str) -> LangResult<u64> {
+    let v = eval_literal(expr)?;
+    if v >= 0.0 && v <= MAX_EXACT_LITERAL && v.fract() == 0.0 {
+        Ok(v as u64)
+    } else {
+        Err(LangError::PartitionKeyExprParsingUnsupported)
+    }
+}
+
+///the parts of start..=end except p
+fn ranges_except(p: u64, start: u64, end: u64) -> Vec<RangeInclusive<u64>> {
+    let mut v = vec![];
+    if let Some(hi) = p.checked_sub(1) {
+        v.push(start..=hi);
+    }
+    if let Some(lo) = p.checked_add(1) {
+        v.push(lo..=end);
+    }
+    v
+}
+
 #[derive(Debug, Default)]
 struct ParseWhereContext<'a> {
     pub ptk_expr: &'a str,
@@ -543,6 +1100,9 @@ struct ParseWhereContext<'a> {
     pub op: &'a str,
     pub state: ParseWhereState,
     pub ptk_hit: bool,
+    ///some comparison does not work on the ptk_expr
+    pub other_hit: bool,
+    pub cmp_operands: u8,
     pub depth: u8,
 }
 
@@ -571,6 +1131,16 @@ impl<'a> ParseWhereContext<'a> {
         match r {
             Rule::logical_val => self.depth -= 1,
             Rule::les_or_op => self.state = ParseWhereState::InTopOr,
+            Rule::les_not_op => {
+                return Err(LangError::PartitionKeyExprParsingUnsupported)
+            }
+            Rule::comp_expr => {
+                if !self.ptk_hit {
+                    self.other_hit = true;
+                }
+                self.ptk_hit = false;
+                self.cmp_operands = 0;
+            }
             Rule::les_and_op => match self.state {
                 ParseWhereState::InTopOr => {
                     return Err(LangError::PartitionKeyExprParsingUnsupported)
@@ -579,12 +1149,17 @@ impl<'a> ParseWhereContext<'a> {
             },
             Rule::comp_expr_cmp_operand => {
                 if self.depth <= 1 {
+                    self.cmp_operands += 1;
                     if self.ptk_expr == pair.as_str().trim() {
+                        //NOTE the ptk_expr is only supported as the left operand
+                        if self.cmp_operands > 1 {
+                            return Err(LangError::PartitionKeyExprParsingUnsupported);
+                        }
                         self.ptk_hit = true;
                     } else {
                         if self.ptk_hit == true {
                             let e = pair.as_str().trim();
-                            let p = eval_literal_u64(e)?;
+                            let p = eval_ptk_literal(e)?;
                             match self.op {
                                 "=" => match self.ptk_ranges.len() {
                                     0 => self.ptk_ranges.push(p..=p),
@@ -605,20 +1180,18 @@ impl<'a> ParseWhereContext<'a> {
                                     }
                                 },
                                 op @ "<>" | op @ "!=" => match self.ptk_ranges.len() {
-                                    0 => {
-                                        self.ptk_ranges.push(0..=p - 1);
-                                        self.ptk_ranges.push(p + 1..=u64::MAX);
-                                    }
+                                    0 => self.ptk_ranges = ranges_except(p, 0, u64::MAX),
                                     _ => match self.state {
                                         ParseWhereState::InTopAnd => {
                                             let mut v = vec![];
                                             self.ptk_ranges.retain(|r| {
                                                 let b = r.contains(&p);
                                                 if b {
-                                                    v = vec![
-                                                        *r.start()..=p - 1,
-                                                        p + 1..=*r.end(),
-                                                    ];
+                                                    v = ranges_except(
+                                                        p,
+                                                        *r.start(),
+                                                        *r.end(),
+                                                    );
                                                 }
                                                 !b
                                             });
@@ -634,19 +1207,19 @@ impl<'a> ParseWhereContext<'a> {
                                             {
                                                 self.ptk_ranges = vec![0..=u64::MAX];
                                             } else {
-                                                self.ptk_ranges.clear();
-                                                self.ptk_ranges.push(0..=p - 1);
-                                                self.ptk_ranges.push(p + 1..=u64::MAX);
+                                                self.ptk_ranges =
+                                                    ranges_except(p, 0, u64::MAX);
                                             }
                                         }
                                     },
                                 },
                                 op @ ">" | op @ ">=" => {
                                     let optrick = op.len() as u64 - 1;
+                                    let lo = p.checked_add(1 - optrick).ok_or(
+                                        LangError::PartitionKeyExprParsingUnsupported,
+                                    )?;
                                     match self.ptk_ranges.len() {
-                                        0 => self
-                                            .ptk_ranges
-                                            .push(p + 1 - optrick..=u64::MAX),
+                                        0 => self.ptk_ranges.push(lo..=u64::MAX),
                                         len @ _ => match self.state {
                                             ParseWhereState::InTopAnd => {
                                                 let mut conflicted_ct = 0;
@@ -654,11 +1227,9 @@ impl<'a> ParseWhereContext<'a> {
                                                 self.ptk_ranges.retain(|r| {
                                                     let b = r.contains(&p);
                                                     if b {
-                                                        v.push(
-                                                            p + 1 - optrick..=*r.end(),
-                                                        );
+                                                        v.push(lo..=*r.end());
                                                         false
-                                                    } else if *r.end() <= p - optrick {
+                                                    } else if *r.end() < lo {
                                                         conflicted_ct += 1;
                                                         false
                                                     } else {
@@ -675,11 +1246,9 @@ impl<'a> ParseWhereContext<'a> {
                                             ParseWhereState::InTopOr => {
                                                 let mut v = vec![];
                                                 self.ptk_ranges.retain(|r| {
-                                                    if *r.end() < p + 1 - optrick {
+                                                    if *r.end() < lo {
                                                         true
-                                                    } else if r
-                                                        .contains(&(p + 1 - optrick))
-                                                    {
+                                                    } else if r.contains(&lo) {
                                                         v.push(*r.start()..=u64::MAX);
                                                         false
                                                     } else {
@@ -689,11 +1258,12 @@ impl<'a> ParseWhereContext<'a> {
                                                 if v.len() > 0 {
                                                     self.ptk_ranges.extend(v);
                                                 }
-                                                if self.ptk_ranges.iter().all(|r| {
-                                                    !r.contains(&(p + 1 - optrick))
-                                                }) {
-                                                    self.ptk_ranges
-                                                        .push(p + 1 - optrick..=u64::MAX);
+                                                if self
+                                                    .ptk_ranges
+                                                    .iter()
+                                                    .all(|r| !r.contains(&lo))
+                                                {
+                                                    self.ptk_ranges.push(lo..=u64::MAX);
                                                 }
                                             }
                                         },
@@ -701,8 +1271,14 @@ impl<'a> ParseWhereContext<'a> {
                                 }
                                 op @ "<" | op @ "<=" => {
                                     let optrick = op.len() as u64 - 1;
+                                    let hi = p.checked_sub(1 - optrick).ok_or(
+                                        LangError::PartitionKeyExprParsingUnsupported,
+                                    )?;
+                                    let next = p.checked_add(1 - optrick).ok_or(
+                                        LangError::PartitionKeyExprParsingUnsupported,
+                                    )?;
                                     match self.ptk_ranges.len() {
-                                        0 => self.ptk_ranges.push(0..=p - 1 + optrick),
+                                        0 => self.ptk_ranges.push(0..=hi),
                                         len @ _ => match self.state {
                                             ParseWhereState::InTopAnd => {
                                                 let mut conflicted_ct = 0;
@@ -710,11 +1286,9 @@ impl<'a> ParseWhereContext<'a> {
                                                 self.ptk_ranges.retain(|r| {
                                                     let b = r.contains(&p);
                                                     if b {
-                                                        v.push(
-                                                            *r.start()..=p - 1 + optrick,
-                                                        );
+                                                        v.push(*r.start()..=hi);
                                                         false
-                                                    } else if *r.start() <= p + optrick {
+                                                    } else if *r.start() <= hi + 1 {
                                                         conflicted_ct += 1;
                                                         false
                                                     } else {
@@ -731,11 +1305,9 @@ impl<'a> ParseWhereContext<'a> {
                                             ParseWhereState::InTopOr => {
                                                 let mut v = vec![];
                                                 self.ptk_ranges.retain(|r| {
-                                                    if *r.start() > p + 1 - optrick {
+                                                    if *r.start() > next {
                                                         true
-                                                    } else if r
-                                                        .contains(&(p + 1 - optrick))
-                                                    {
+                                                    } else if r.contains(&next) {
                                                         v.push(0..=*r.end());
                                                         false
                                                     } else {
@@ -745,11 +1317,12 @@ impl<'a> ParseWhereContext<'a> {
                                                 if v.len() > 0 {
                                                     self.ptk_ranges.extend(v);
                                                 }
-                                                if self.ptk_ranges.iter().all(|r| {
-                                                    !r.contains(&(p + 1 - optrick))
-                                                }) {
-                                                    self.ptk_ranges
-                                                        .push(0..=p + 1 - optrick);
+                                                if self
+                                                    .ptk_ranges
+                                                    .iter()
+                                                    .all(|r| !r.contains(&next))
+                                                {
+                                                    self.ptk_ranges.push(0..=next);
                                                 }
                                             }
                                         },
@@ -772,10 +1345,11 @@ impl<'a> ParseWhereContext<'a> {
                 let children: Vec<_> = pair.clone().into_inner().collect();
                 contract!(children.len() == 3, "between expr has 3 sub exprs");
                 if self.ptk_expr == children[0].as_str().trim() {
+                    self.ptk_hit = true;
                     let low_str = children[1].as_str().trim();
-                    let mut low_val = eval_literal_u64(low_str)?;
+                    let mut low_val = eval_ptk_literal(low_str)?;
                     let high_str = children[2].as_str().trim();
-                    let mut high_val = eval_literal_u64(high_str)?;
+                    let mut high_val = eval_ptk_literal(high_str)?;
                     if high_val < low_val {
                         swap(&mut low_val, &mut high_val);
                     }
@@ -873,7 +1447,11 @@ pub fn parse_where(
     // println!("{:?}", ctx);
     match ctx.parse(pair) {
         Ok(_) => {
-            if ctx.ptk_ranges.iter().any(|r| r.start() > r.end()) {
+            //the rows which match the other comparisons in a top OR can not be
+            //pruned by the ptk_expr
+            if matches!(ctx.state, ParseWhereState::InTopOr) && ctx.other_hit {
+                Ok(vec![0..=u64::MAX])
+            } else if ctx.ptk_ranges.iter().any(|r| r.start() > r.end()) {
                 Ok(vec![])
             } else if ctx.ptk_ranges.len() == 0 {
                 Ok(vec![0..=u64::MAX])
@@ -1292,14 +1870,18 @@ mod unit_tests {
     // #[macro_export]
 
     use super::{
-        parse_create_database, parse_create_table, parse_host_comma_expr,
-        parse_host_range_expr, pretty_parse_tree, seek_to, BqlParser, RemoteAddr,
-        RemoteDbType, RemoteTableInfo, Rule, TablePlaceKind,
+        parse_alter_table, parse_backup, parse_create_database, parse_create_table,
+        parse_host_comma_expr, parse_host_range_expr, parse_optimize,
+        parse_optimize_table, pretty_parse_tree, seek_to, AlterTableAction,
+        BackupContext, BqlParser, OptimizeContext, RemoteAddr, RemoteDbType,
+        RemoteTableInfo, Rule, TablePlaceKind,
     };
     use base::datetimes::TimeZoneId;
-    use meta::types::BqlType;
+    use meta::types::{
+        AggFunc, BqlType, CodecType, ElemKind, EngineType, EnumValues, NumericKind,
+    };
     use pest::Parser;
-    use std::str::FromStr;
+    use std::{collections::HashMap, str::FromStr};
 
     #[test]
     pub fn test_parse_create_database() -> LangResult<()> {
@@ -1377,6 +1959,45 @@ mod unit_tests {
         assert_eq!(tctx.has_select_all, false);
         // println!("tctx: {:?}", tctx);
 
+        let ddl = "select id from db.tab FINAL where id > 1";
+        let tctx = parse_to_tabctx(ddl)?;
+        assert!(tctx.tabs.contains("db.tab"));
+        assert!(tctx.finals.contains("db.tab"));
+        assert_eq!(
+            tctx.rewrite_query(ddl),
+            "select id from db.tab  where id > 1"
+        );
+
+        let ddl = "select id from tab finals";
+        let tctx = parse_to_tabctx(ddl)?;
+        assert!(tctx.finals.is_empty());
+        assert_eq!(tctx.rewrite_query(ddl), ddl);
+
+        let ddl = "select id, arrayJoin(a) AS x from tab FINAL where has(b, 1)";
+        let tctx = parse_to_tabctx(ddl)?;
+        assert!(tctx.cols.contains("a"));
+        assert!(tctx.array_joins.contains("a"));
+        assert_eq!(tctx.array_joins.len(), 1);
+        assert_eq!(
+            tctx.rewrite_query(ddl),
+            "select id, __array_join_a AS x from tab  where has(b, 1)"
+        );
+
+        let ddl = "select e from tab where e = 'a' or 'it''s' < e or f = 'a' \
+                   or e in ('a', 'b') or e between 'a' and 'b' order by e";
+        let mut tctx = parse_to_tabctx(ddl)?;
+        let (_, ev) = EnumValues::from_bytes(b"Enum8('a' = 1, 'b' = -2, 'it''s' = 3)")?;
+        let enums: HashMap<_, _> = vec![("e".to_string(), ev)].into_iter().collect();
+        tctx.rewrite_enum_names(|cn| enums.get(cn))?;
+        assert_eq!(
+            tctx.rewrite_query(ddl),
+            "select e from tab where e = 1 or 3 < e or f = 'a' \
+                   or e in (1, -2) or e between 1 and -2 order by e"
+        );
+        let ddl = "select e from tab where e = 'c'";
+        let mut tctx = parse_to_tabctx(ddl)?;
+        assert!(tctx.rewrite_enum_names(|cn| enums.get(cn)).is_err());
+
         Ok(())
     }
 
@@ -1412,6 +2033,9 @@ mod unit_tests {
         assert_eq!(t.0.columns[2].1.data_type, BqlType::String);
         assert_eq!(t.0.columns[3].1.data_type, BqlType::Decimal(10, 5));
         assert_eq!(t.0.tab_info.partition_cols, "ds,");
+        assert_eq!(t.0.tab_info.settings.len(), 2);
+        assert_eq!(t.0.tab_info.settings["a"], "32768");
+        assert_eq!(t.0.tab_info.settings["b"], "0");
         for i in 0u32..4 {
             let od = t.0.columns[i as usize].1.ordinal;
             assert_eq!(od, i);
@@ -1421,7 +2045,9 @@ mod unit_tests {
         (
             trip_id UInt32,
             pickup_datetime DateTime,
-            pickup_datetime_tz DateTime( 'UTC')
+            pickup_datetime_tz DateTime( 'UTC'),
+            pickup_datetime_ms DateTime64(3),
+            pickup_datetime_us DateTime64( 6, 'Etc/GMT-8' )
         )
         ENGINE = BaseStorage"##;
         let ps = BqlParser::parse(Rule::cmd_list, ddl)
@@ -1435,7 +2061,7 @@ mod unit_tests {
         println!("{:?}", ct.clone().as_rule());
         let t = parse_create_table(ct)?;
         println!("{:?}", t);
-        assert_eq!(t.0.columns.len(), 3);
+        assert_eq!(t.0.columns.len(), 5);
         assert_eq!(t.0.columns[0].0, "trip_id"); //FIXME remove `` when parsing id
         assert_eq!(t.0.columns[0].1.data_type, BqlType::UInt(32));
         assert_eq!(t.0.columns[1].1.data_type, BqlType::DateTime);
@@ -1446,12 +2072,57 @@ mod unit_tests {
                     .map_err(|_| LangError::CreateTableParsingError)?
             )
         );
+        assert_eq!(t.0.columns[3].1.data_type, BqlType::DateTime64(3, None));
+        assert_eq!(t.0.columns[4].1.data_type, BqlType::DateTime64(6, Some(32)));
 
-        for i in 0u32..3 {
+        for i in 0u32..5 {
             let od = t.0.columns[i as usize].1.ordinal;
             assert_eq!(od, i);
         }
 
+        let ddl = r##"CREATE TABLE test_enum
+        (
+            a Enum8('x' = 1, 'y' = -2),
+            b Nullable(Enum16('it''s' = 1000)),
+            c UInt8
+        )
+        ENGINE = BaseStorage"##;
+        let ct = BqlParser::parse(Rule::cmd_list, ddl)
+            .map_err(|_| LangError::CreateTableParsingError)?
+            .next()
+            .ok_or(LangError::CreateTableParsingError)?;
+        let t = parse_create_table(ct)?;
+        assert_eq!(t.0.columns[0].1.data_type, BqlType::Enum8);
+        assert_eq!(t.0.columns[1].1.data_type, BqlType::Enum16);
+        assert!(t.0.columns[1].1.is_nullable);
+        assert_eq!(t.0.enum_values.len(), 2);
+        assert_eq!(t.0.enum_values[0].0, "a");
+        assert_eq!(t.0.enum_values[0].1.value_of(b"y"), Some(-2));
+        assert_eq!(t.0.enum_values[1].0, "b");
+        assert_eq!(t.0.enum_values[1].1.name_of(1000), Some("it's"));
+        assert!(BqlParser::parse(
+            Rule::cmd_list,
+            "CREATE TABLE t (a Enum8('x' = 200)) ENGINE = BaseStorage"
+        )
+        .map_err(|_| LangError::CreateTableParsingError)
+        .and_then(|mut ps| parse_create_table(ps.next().unwrap()))
+        .is_err());
+
+        let ddl = r##"CREATE TABLE test_ip
+        (
+            a IPv4,
+            b Nullable(IPv6)
+        )
+        ENGINE = BaseStorage"##;
+        let ct = BqlParser::parse(Rule::cmd_list, ddl)
+            .map_err(|_| LangError::CreateTableParsingError)?
+            .next()
+            .ok_or(LangError::CreateTableParsingError)?;
+        let t = parse_create_table(ct)?;
+        assert_eq!(t.0.columns[0].1.data_type, BqlType::Ipv4);
+        assert_eq!(t.0.columns[1].1.data_type, BqlType::Ipv6);
+        assert!(t.0.columns[1].1.is_nullable);
+
         let ddl = r##"create TABLE test_primary_key
         (
             a UInt32 PRIMARY KEY,
@@ -1469,6 +2140,436 @@ mod unit_tests {
         let t = parse_create_table(ct)?;
         assert_eq!(t.0.tab_info.primary_keys, "a,");
 
+        let ddl = r##"create TABLE test_codec
+        (
+            ts DateTime CODEC(DoubleDelta, LZ4),
+            val Float64 CODEC(Gorilla, ZSTD(3)),
+            cnt UInt64 codec(T64),
+            s String
+        )
+        ENGINE = BaseStorage
+        "##;
+        let ps = BqlParser::parse(Rule::cmd_list, ddl)
+            .map_err(|e| LangError::CreateTableParsingError)?;
+        let ct = ps
+            .into_iter()
+            .next()
+            .ok_or(LangError::CreateTableParsingError)?;
+        let t = parse_create_table(ct)?;
+        let codec = t.0.columns[0].1.codec;
+        assert_eq!(codec.transform, CodecType::DoubleDelta);
+        assert_eq!(codec.compression, CodecType::LZ4);
+        let codec = t.0.columns[1].1.codec;
+        assert_eq!(codec.transform, CodecType::Gorilla);
+        assert_eq!(codec.compression, CodecType::ZSTD);
+        assert_eq!(codec.level, 3);
+        let codec = t.0.columns[2].1.codec;
+        assert_eq!(codec.transform, CodecType::T64);
+        assert_eq!(codec.compression, CodecType::None);
+        assert!(t.0.columns[3].1.codec.is_none());
+
+        let ddl = "create TABLE test_codec (s String CODEC(LZ4))";
+        let ps = BqlParser::parse(Rule::cmd_list, ddl)
+            .map_err(|e| LangError::CreateTableParsingError)?;
+        let ct = ps
+            .into_iter()
+            .next()
+            .ok_or(LangError::CreateTableParsingError)?;
+        assert!(parse_create_table(ct).is_err());
+
+        let ddl = r##"create TABLE test_ttl
+        (
+            ts DateTime,
+            user_id UInt64
+        )
+        ENGINE = BaseStorage
+        PARTITION BY toYYYYMMDD(ts)
+        TTL ts + INTERVAL 90 DAY
+        SETTINGS a=1"##;
+        let ps = BqlParser::parse(Rule::cmd_list, ddl)
+            .map_err(|e| LangError::CreateTableParsingError)?;
+        let ct = ps
+            .into_iter()
+            .next()
+            .ok_or(LangError::CreateTableParsingError)?;
+        let t = parse_create_table(ct)?;
+        assert_eq!(t.0.tab_info.partition_cols, "ts,");
+        assert_eq!(t.0.tab_info.ttl_col, "ts");
+        assert_eq!(t.0.tab_info.ttl_secs, 90 * 86400);
+        assert_eq!(t.0.tab_info.settings["a"], "1");
+
+        let ddl = "create TABLE test_ttl (ts DateTime) TTL ts + INTERVAL 2 hour";
+        let ps = BqlParser::parse(Rule::cmd_list, ddl)
+            .map_err(|e| LangError::CreateTableParsingError)?;
+        let ct = ps
+            .into_iter()
+            .next()
+            .ok_or(LangError::CreateTableParsingError)?;
+        let t = parse_create_table(ct)?;
+        assert_eq!(t.0.tab_info.ttl_secs, 7200);
+
+        let ddl = "create TABLE test_rmt (id UInt64 PRIMARY KEY, ver UInt32) \
+            ENGINE = ReplacingMergeTree(ver)";
+        let ps = BqlParser::parse(Rule::cmd_list, ddl)
+            .map_err(|e| LangError::CreateTableParsingError)?;
+        let ct = ps
+            .into_iter()
+            .next()
+            .ok_or(LangError::CreateTableParsingError)?;
+        let t = parse_create_table(ct)?;
+        assert_eq!(t.0.tab_info.engine, EngineType::ReplacingMergeTree);
+        assert_eq!(t.0.tab_info.engine_params, vec!["ver".to_string()]);
+        assert_eq!(t.0.columns.len(), 2);
+
+        let ddl = "create TABLE test_smt (id UInt64 PRIMARY KEY, a Int32, b Float64) \
+            ENGINE = SummingMergeTree((a, b))";
+        let ps = BqlParser::parse(Rule::cmd_list, ddl)
+            .map_err(|e| LangError::CreateTableParsingError)?;
+        let ct = ps
+            .into_iter()
+            .next()
+            .ok_or(LangError::CreateTableParsingError)?;
+        let t = parse_create_table(ct)?;
+        assert_eq!(t.0.tab_info.engine, EngineType::SummingMergeTree);
+        assert_eq!(
+            t.0.tab_info.engine_params,
+            vec!["a".to_string(), "b".to_string()]
+        );
+
+        let ddl = "create TABLE test_amt (id UInt64 PRIMARY KEY, \
+            s AggregateFunction(sum, UInt64), m SimpleAggregateFunction(max, Float32)) \
+            ENGINE = AggregatingMergeTree";
+        let ps = BqlParser::parse(Rule::cmd_list, ddl)
+            .map_err(|e| LangError::CreateTableParsingError)?;
+        let ct = ps
+            .into_iter()
+            .next()
+            .ok_or(LangError::CreateTableParsingError)?;
+        let t = parse_create_table(ct)?;
+        assert_eq!(t.0.tab_info.engine, EngineType::AggregatingMergeTree);
+        assert_eq!(
+            t.0.columns[1].1.data_type,
+            BqlType::AggregateFunction(AggFunc::Sum, NumericKind::UInt, 64)
+        );
+        assert_eq!(
+            t.0.columns[2].1.data_type,
+            BqlType::AggregateFunction(AggFunc::Max, NumericKind::Float, 32)
+        );
+
+        for (eng, et) in [("Memory", EngineType::Memory), ("Log", EngineType::Log)] {
+            let ddl =
+                format!("create TABLE test_ml (a UInt8, s String) ENGINE = {}", eng);
+            let ps = BqlParser::parse(Rule::cmd_list, &ddl)
+                .map_err(|e| LangError::CreateTableParsingError)?;
+            let ct = ps
+                .into_iter()
+                .next()
+                .ok_or(LangError::CreateTableParsingError)?;
+            let t = parse_create_table(ct)?;
+            assert_eq!(t.0.tab_info.engine, et);
+            assert!(t.0.tab_info.engine_params.is_empty());
+        }
+
+        let ddl = "create TABLE test_arr (id UInt64, a Array(UInt16), \
+            s Array(String), f Array(FixedString(4)), t Array( DateTime ))";
+        let ps = BqlParser::parse(Rule::cmd_list, ddl)
+            .map_err(|e| LangError::CreateTableParsingError)?;
+        let ct = ps
+            .into_iter()
+            .next()
+            .ok_or(LangError::CreateTableParsingError)?;
+        let t = parse_create_table(ct)?;
+        assert_eq!(
+            t.0.columns[1].1.data_type,
+            BqlType::Array(ElemKind::UInt, 16)
+        );
+        assert_eq!(
+            t.0.columns[2].1.data_type,
+            BqlType::Array(ElemKind::String, 0)
+        );
+        assert_eq!(
+            t.0.columns[3].1.data_type,
+            BqlType::Array(ElemKind::FixedString, 4)
+        );
+        assert_eq!(
+            t.0.columns[4].1.data_type,
+            BqlType::Array(ElemKind::DateTime, 0)
+        );
+        let ddl = "create TABLE test_arr (a Array(Nullable(UInt16)))";
+        assert!(BqlParser::parse(Rule::cmd_list, ddl).is_err());
+
+        let ddl = "create TABLE test_wide (a Int128, b UInt128, c Int256, d UInt256, \
+            e Decimal128(4), f Nullable(Decimal256(10)), g Decimal(38, 2))";
+        let ps = BqlParser::parse(Rule::cmd_list, ddl)
+            .map_err(|e| LangError::CreateTableParsingError)?;
+        let ct = ps
+            .into_iter()
+            .next()
+            .ok_or(LangError::CreateTableParsingError)?;
+        let t = parse_create_table(ct)?;
+        let typs: Vec<BqlType> = t.0.columns.iter().map(|c| c.1.data_type).collect();
+        assert_eq!(
+            typs,
+            vec![
+                BqlType::Int(128),
+                BqlType::UInt(128),
+                BqlType::Int256,
+                BqlType::UInt256,
+                BqlType::Decimal(38, 4),
+                BqlType::Decimal(76, 10),
+                BqlType::Decimal(38, 2),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_parse_alter_table() -> LangResult<()> {
+        let ddl = r##"ALTER TABLE db.t
+            ADD COLUMN IF NOT EXISTS c Nullable(Int16) DEFAULT -1,
+            ADD COLUMN s String DEFAULT 'abc' CODEC(NONE),
+            DROP COLUMN d,
+            RENAME COLUMN IF EXISTS e TO f,
+            MODIFY COLUMN g Float64,
+            ADD COLUMN h Enum8('x' = 1, 'y' = 2) DEFAULT 'y'"##;
+        let mut ps = BqlParser::parse(Rule::alter_table, ddl)
+            .map_err(|_| LangError::AlterTableParsingError)?;
+        let ctx = parse_alter_table(ps.next().ok_or(LangError::AlterTableParsingError)?)?;
+        assert_eq!(ctx.dbname, Some("db".to_string()));
+        assert_eq!(ctx.tabname, "t");
+        assert_eq!(ctx.actions.len(), 6);
+        match &ctx.actions[0] {
+            AlterTableAction::AddColumn {
+                name,
+                info,
+                default,
+                enum_values,
+                fallible,
+            } => {
+                assert_eq!(name, "c");
+                assert_eq!(info.data_type, BqlType::Int(16));
+                assert!(info.is_nullable);
+                assert_eq!(default.as_deref(), Some("-1"));
+                assert!(enum_values.is_none());
+                assert!(!fallible);
+            }
+            _ => panic!("should be AddColumn"),
+        }
+        match &ctx.actions[1] {
+            AlterTableAction::AddColumn { name, default, .. } => {
+                assert_eq!(name, "s");
+                assert_eq!(default.as_deref(), Some("'abc'"));
+            }
+            _ => panic!("should be AddColumn"),
+        }
+        assert_eq!(
+            ctx.actions[2],
+            AlterTableAction::DropColumn {
+                name: "d".to_string(),
+                fallible: true
+            }
+        );
+        assert_eq!(
+            ctx.actions[3],
+            AlterTableAction::RenameColumn {
+                from: "e".to_string(),
+                to: "f".to_string(),
+                fallible: false
+            }
+        );
+        match &ctx.actions[4] {
+            AlterTableAction::ModifyColumn { name, info, .. } => {
+                assert_eq!(name, "g");
+                assert_eq!(info.data_type, BqlType::Float(64));
+            }
+            _ => panic!("should be ModifyColumn"),
+        }
+        match &ctx.actions[5] {
+            AlterTableAction::AddColumn {
+                info,
+                enum_values: Some(ev),
+                ..
+            } => {
+                assert_eq!(info.data_type, BqlType::Enum8);
+                assert_eq!(ev.value_of(b"y"), Some(2));
+            }
+            _ => panic!("should be AddColumn of Enum8"),
+        }
+
+        //a primary key can not be added
+        let ddl = "alter table t add column a UInt32 PRIMARY KEY";
+        let mut ps = BqlParser::parse(Rule::alter_table, ddl)
+            .map_err(|_| LangError::AlterTableParsingError)?;
+        assert!(
+            parse_alter_table(ps.next().ok_or(LangError::AlterTableParsingError)?)
+                .is_err()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_parse_alter_table_mutations() -> LangResult<()> {
+        let ddl = r##"ALTER TABLE t
+            DELETE WHERE a > 1 and s = 'x,y',
+            UPDATE b = b + 1, c = 'z' WHERE a < 0"##;
+        let mut ps = BqlParser::parse(Rule::alter_table, ddl)
+            .map_err(|_| LangError::AlterTableParsingError)?;
+        let ctx = parse_alter_table(ps.next().ok_or(LangError::AlterTableParsingError)?)?;
+        assert_eq!(ctx.dbname, None);
+        assert_eq!(
+            ctx.actions,
+            vec![
+                AlterTableAction::Delete {
+                    predicate: "a > 1 and s = 'x,y'".to_string(),
+                },
+                AlterTableAction::Update {
+                    assignments: vec![
+                        ("b".to_string(), "b + 1".to_string()),
+                        ("c".to_string(), "'z'".to_string()),
+                    ],
+                    predicate: "a < 0".to_string(),
+                },
+            ]
+        );
+
+        //a mutation must have the WHERE clause
+        let ddl = "alter table t delete";
+        assert!(BqlParser::parse(Rule::alter_table, ddl).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_parse_optimize_table() -> LangResult<()> {
+        let parse = |cmd: &str| -> LangResult<OptimizeContext> {
+            let mut ps = BqlParser::parse(Rule::optimize_table, cmd)
+                .map_err(|_| LangError::QueryLangParsingError)?;
+            parse_optimize(ps.next().ok_or(LangError::QueryLangParsingError)?)
+        };
+        assert_eq!(
+            parse("OPTIMIZE TABLE db.t")?,
+            OptimizeContext {
+                dbname: Some("db".to_string()),
+                tabname: "t".to_string(),
+                ptk: None,
+                is_final: false,
+            }
+        );
+        assert_eq!(
+            parse("optimize table t partition 202001 final")?,
+            OptimizeContext {
+                dbname: None,
+                tabname: "t".to_string(),
+                ptk: Some(202001),
+                is_final: true,
+            }
+        );
+        let ctx = parse("optimize table t FINAL")?;
+        assert_eq!((ctx.ptk, ctx.is_final), (None, true));
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_parse_check_table() -> LangResult<()> {
+        let mut ps = BqlParser::parse(Rule::check_table, "CHECK TABLE db.t")
+            .map_err(|_| LangError::QueryLangParsingError)?;
+        let (dbn, tn) =
+            parse_optimize_table(ps.next().ok_or(LangError::QueryLangParsingError)?)?;
+        assert_eq!(dbn, Some("db".to_string()));
+        assert_eq!(tn, "t");
+        assert!(BqlParser::parse(Rule::check_table, "check table").is_err());
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_parse_backup_restore() -> LangResult<()> {
+        let mut ps = BqlParser::parse(Rule::backup, "BACKUP TABLE db.t TO '/bak/it''s'")
+            .map_err(|_| LangError::QueryLangParsingError)?;
+        let ctx = parse_backup(ps.next().ok_or(LangError::QueryLangParsingError)?)?;
+        assert_eq!(
+            ctx,
+            BackupContext {
+                dbname: Some("db".to_string()),
+                tabname: Some("t".to_string()),
+                path: "/bak/it's".to_string(),
+            }
+        );
+        let mut ps = BqlParser::parse(Rule::restore, "restore database db from '/bak'")
+            .map_err(|_| LangError::QueryLangParsingError)?;
+        let ctx = parse_backup(ps.next().ok_or(LangError::QueryLangParsingError)?)?;
+        assert_eq!(
+            ctx,
+            BackupContext {
+                dbname: Some("db".to_string()),
+                tabname: None,
+                path: "/bak".to_string(),
+            }
+        );
+        assert!(BqlParser::parse(Rule::backup, "backup table t to /bak").is_err());
+        assert!(BqlParser::parse(Rule::restore, "restore table t to '/bak'").is_err());
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_parse_alter_table_partitions() -> LangResult<()> {
+        let ddl = r##"ALTER TABLE db.t
+            DROP PARTITION 202001,
+            DETACH PARTITION 2020*100+2,
+            ATTACH PARTITION 202002,
+            MOVE PARTITION 202003 TO TABLE db2.t2,
+            MOVE PARTITION 0 TO TABLE t3,
+            MOVE PARTITION 202001 TO VOLUME 'cold'"##;
+        let mut ps = BqlParser::parse(Rule::alter_table, ddl)
+            .map_err(|_| LangError::AlterTableParsingError)?;
+        let ctx = parse_alter_table(ps.next().ok_or(LangError::AlterTableParsingError)?)?;
+        assert_eq!(ctx.dbname, Some("db".to_string()));
+        assert_eq!(ctx.tabname, "t");
+        assert_eq!(
+            ctx.actions,
+            vec![
+                AlterTableAction::DropPartition { ptk: 202001 },
+                AlterTableAction::DetachPartition { ptk: 202002 },
+                AlterTableAction::AttachPartition { ptk: 202002 },
+                AlterTableAction::MovePartition {
+                    ptk: 202003,
+                    dbname: Some("db2".to_string()),
+                    tabname: "t2".to_string(),
+                },
+                AlterTableAction::MovePartition {
+                    ptk: 0,
+                    dbname: None,
+                    tabname: "t3".to_string(),
+                },
+                AlterTableAction::MovePartitionToVolume {
+                    ptk: 202001,
+                    volume: "cold".to_string(),
+                },
+            ]
+        );
+
+        //DROP COLUMN is not confused with DROP PARTITION
+        let ddl = "alter table t drop column partition_col";
+        let mut ps = BqlParser::parse(Rule::alter_table, ddl)
+            .map_err(|_| LangError::AlterTableParsingError)?;
+        let ctx = parse_alter_table(ps.next().ok_or(LangError::AlterTableParsingError)?)?;
+        assert!(matches!(
+            ctx.actions[0],
+            AlterTableAction::DropColumn { .. }
+        ));
+
+        let ddl = "alter table t move partition 1 to table";
+        assert!(BqlParser::parse(Rule::alter_table, ddl).is_err());
+        let ddl = "alter table t move partition 1 to volume cold";
+        assert!(BqlParser::parse(Rule::alter_table, ddl).is_err());
+
+        let ddl = "alter table t rebalance";
+        let mut ps = BqlParser::parse(Rule::alter_table, ddl)
+            .map_err(|_| LangError::AlterTableParsingError)?;
+        let ctx = parse_alter_table(ps.next().ok_or(LangError::AlterTableParsingError)?)?;
+        assert_eq!(ctx.actions, vec![AlterTableAction::Rebalance]);
+
         Ok(())
     }
 
@@ -2271,6 +3372,39 @@ CREATE TABLE test (col Int32)";
                 vec![0..=u64::MAX].into_iter().collect::<HashSet<_>>()
             );
 
+            // literals which are not non-negative integers
+            for c in [
+                "where a < 2.5",
+                "where a > -1",
+                "where a = 0.5",
+                "where a < 99999999999999999999",
+                "where a between -1 and 5",
+            ] {
+                let r = parse_where(c, "a").unwrap();
+                assert_eq!(r, vec![0..=u64::MAX], "{}", c);
+            }
+
+            // bounds at the ends of u64
+            let c = "where a < 0";
+            let r = parse_where(c, "a").unwrap();
+            assert_eq!(r, vec![0..=u64::MAX]);
+
+            let c = "where a <= 0";
+            let r = parse_where(c, "a").unwrap();
+            assert_eq!(r, vec![0..=0]);
+
+            let c = "where a != 0";
+            let r = parse_where(c, "a").unwrap();
+            assert_eq!(r, vec![1..=u64::MAX]);
+
+            let c = "where a > 10 and a <> 0";
+            let r = parse_where(c, "a").unwrap();
+            assert_eq!(r, vec![11..=u64::MAX]);
+
+            let c = "where a >= 0";
+            let r = parse_where(c, "a").unwrap();
+            assert_eq!(r, vec![0..=u64::MAX]);
+
             // between ... and ...
             let c = "where a between 1 and 5";
             let r = parse_where(c, "a").unwrap();
@@ -2369,6 +3503,31 @@ CREATE TABLE test (col Int32)";
                 r.into_iter().collect::<HashSet<_>>(),
                 vec![0..=u64::MAX].into_iter().collect::<HashSet<_>>()
             );
+
+            // ... on other columns ...
+            let c = "where a=1 and b=2";
+            let r = parse_where(c, "a").unwrap();
+            assert_eq!(r, vec![1..=1]);
+
+            let c = "where b=2 and a<10";
+            let r = parse_where(c, "a").unwrap();
+            assert_eq!(r, vec![0..=9]);
+
+            let c = "where a=1 or b=2";
+            let r = parse_where(c, "a").unwrap();
+            assert_eq!(r, vec![0..=u64::MAX]);
+
+            let c = "where b=2 or a between 1 and 5";
+            let r = parse_where(c, "a").unwrap();
+            assert_eq!(r, vec![0..=u64::MAX]);
+
+            let c = "where not a=1";
+            let r = parse_where(c, "a").unwrap();
+            assert_eq!(r, vec![0..=u64::MAX]);
+
+            let c = "where 1<a or a=5";
+            let r = parse_where(c, "a").unwrap();
+            assert_eq!(r, vec![0..=u64::MAX]);
         }
 
         #[test]
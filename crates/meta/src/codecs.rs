@@ -0,0 +1,405 @@
+use lzzzz::lz4;
+use num_traits::{NumCast, PrimInt, ToPrimitive, WrappingAdd, WrappingSub};
+
+use crate::{
+    errs::{MetaError, MetaResult},
+    types::{CodecType, ColumnCodec},
+};
+
+///
+///## Compressed CoPa:
+///
+///A compressed CoPa is a sequence of blocks, each insert appends one or more
+///blocks to the end of CoPa file:
+///
+/// | CLEN | RLEN | ROWS |   PAYLOAD   |
+///
+/// CLEN    - u32, length of payload in bytes
+/// RLEN    - u32, length of payload before compression(after transform)
+/// ROWS    - u32, num of rows in this block
+/// PAYLOAD - transformed then compressed data of ROWS rows
+///
+///Transforms are done on little endian fixed width words:
+/// Delta       - x[i] - x[i-1]
+/// DoubleDelta - delta of Delta
+/// Gorilla     - x[i] xor x[i-1], for slowly changing floats
+/// T64         - x[i] - min with unused high bytes cropped, prefixed by
+///               min(u64) and the num of kept bytes(u8)
+///
+pub const COMPRESSION_BLOCK_ROWS: usize = 64 * 1024;
+const SIZ_BLOCK_HEADER: usize = 12;
+
+trait Word: PrimInt + WrappingAdd + WrappingSub {
+    const WIDTH: usize;
+    fn read(bs: &[u8]) -> Self;
+    fn write(self, bs: &mut Vec<u8>);
+}
+
+macro_rules! impl_word {
+    ($($t:ty,)*) => {
+      $(
+        impl Word for $t {
+            const WIDTH: usize = std::mem::size_of::<$t>();
+            #[inline(always)]
+            fn read(bs: &[u8]) -> Self {
+                let mut b = [0u8; std::mem::size_of::<$t>()];
+                b.copy_from_slice(&bs[..Self::WIDTH]);
+                <$t>::from_le_bytes(b)
+            }
+            #[inline(always)]
+            fn write(self, bs: &mut Vec<u8>) {
+                bs.extend_from_slice(&self.to_le_bytes());
+            }
+        }
+      )*
+    }
+}
+
+impl_word! { u8,u16,u32,u64, }
+
+///compress fixed width data into blocks and append them into out
+pub fn compress_into(
+    codec: ColumnCodec,
+    width: usize,
+    data: &[u8],
+    out: &mut Vec<u8>,
+) -> MetaResult<()> {
+    for raw in data.chunks(COMPRESSION_BLOCK_ROWS * width) {
+        let rows = raw.len() / width;
+        let tdata = encode_transform(codec.transform, width, raw)?;
+        let payload = match codec.compression {
+            CodecType::LZ4 => {
+                let mut buf = vec![0u8; lz4::max_compressed_size(tdata.len())];
+                let n = lz4::compress(&tdata, &mut buf, lz4::ACC_LEVEL_DEFAULT)
+                    .map_err(|_| MetaError::CorruptedCompressedBlockError)?;
+                buf.truncate(n);
+                buf
+            }
+            CodecType::ZSTD => zstd::bulk::compress(&tdata, codec.level as i32)?,
+            _ => tdata.clone(),
+        };
+        out.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        out.extend_from_slice(&(tdata.len() as u32).to_le_bytes());
+        out.extend_from_slice(&(rows as u32).to_le_bytes());
+        out.extend_from_slice(&payload);
+    }
+    Ok(())
+}
+
+///decompress the first rows of blocks in src
+pub fn decompress(
+    codec: ColumnCodec,
+    width: usize,
+    src: &[u8],
+    rows: usize,
+) -> MetaResult<Vec<u8>> {
+    decompress_in_ranges(codec, width, src, rows, &[(0, rows)])
+}
+
+///decompress the first rows of blocks in src, but only the blocks with the
+///rows in the ranges of (offset, len), the rows of the other blocks are left
+///zeroed
+pub fn decompress_in_ranges(
+    codec: ColumnCodec,
+    width: usize,
+    src: &[u8],
+    rows: usize,
+    rngs: &[(usize, usize)],
+) -> MetaResult<Vec<u8>> {
+    let len_in_bytes = rows * width;
+    let mut out = Vec::with_capacity(len_in_bytes);
+    let mut bs = src;
+    while out.len() < len_in_bytes && bs.len() >= SIZ_BLOCK_HEADER {
+        let clen = u32::read(&bs[0..]) as usize;
+        let rlen = u32::read(&bs[4..]) as usize;
+        let brows = u32::read(&bs[8..]) as usize;
+        if bs.len() < SIZ_BLOCK_HEADER + clen {
+            return Err(MetaError::CorruptedCompressedBlockError);
+        }
+        let r0 = out.len() / width;
+        if !rngs
+            .iter()
+            .any(|(ofs, len)| *ofs < r0 + brows && r0 < ofs + len)
+        {
+            out.resize(out.len() + brows * width, 0);
+            bs = &bs[SIZ_BLOCK_HEADER + clen..];
+            continue;
+        }
+        let payload = &bs[SIZ_BLOCK_HEADER..SIZ_BLOCK_HEADER + clen];
+        let tdata = match codec.compression {
+            CodecType::LZ4 => {
+                let mut buf = vec![0u8; rlen];
+                lz4::decompress(payload, &mut buf)
+                    .map_err(|_| MetaError::CorruptedCompressedBlockError)?;
+                buf
+            }
+            CodecType::ZSTD => zstd::bulk::decompress(payload, rlen)?,
+            _ => payload.to_vec(),
+        };
+        decode_transform(codec.transform, width, &tdata, brows, &mut out)?;
+        bs = &bs[SIZ_BLOCK_HEADER + clen..];
+    }
+    if out.len() < len_in_bytes {
+        return Err(MetaError::CorruptedCompressedBlockError);
+    }
+    out.truncate(len_in_bytes);
+    Ok(out)
+}
+
+fn encode_transform(typ: CodecType, width: usize, raw: &[u8]) -> MetaResult<Vec<u8>> {
+    match typ {
+        CodecType::Delta
+        | CodecType::DoubleDelta
+        | CodecType::Gorilla
+        | CodecType::T64 => match width {
+            1 => Ok(encode_words::<u8>(typ, raw)),
+            2 => Ok(encode_words::<u16>(typ, raw)),
+            4 => Ok(encode_words::<u32>(typ, raw)),
+            8 => Ok(encode_words::<u64>(typ, raw)),
+            _ => Err(MetaError::InvalidCodecError(typ.name().to_string())),
+        },
+        _ => Ok(raw.to_vec()),
+    }
+}
+
+fn decode_transform(
+    typ: CodecType,
+    width: usize,
+    tdata: &[u8],
+    rows: usize,
+    out: &mut Vec<u8>,
+) -> MetaResult<()> {
+    match typ {
+        CodecType::Delta
+        | CodecType::DoubleDelta
+        | CodecType::Gorilla
+        | CodecType::T64 => match width {
+            1 => decode_words::<u8>(typ, tdata, rows, out),
+            2 => decode_words::<u16>(typ, tdata, rows, out),
+            4 => decode_words::<u32>(typ, tdata, rows, out),
+            8 => decode_words::<u64>(typ, tdata, rows, out),
+            _ => Err(MetaError::InvalidCodecError(typ.name().to_string())),
+        },
+        _ => {
+            if tdata.len() != rows * width {
+                return Err(MetaError::CorruptedCompressedBlockError);
+            }
+            out.extend_from_slice(tdata);
+            Ok(())
+        }
+    }
+}
+
+fn encode_words<T: Word>(typ: CodecType, raw: &[u8]) -> Vec<u8> {
+    let mut ws: Vec<T> = raw.chunks_exact(T::WIDTH).map(T::read).collect();
+    match typ {
+        CodecType::Delta => delta_encode(&mut ws),
+        CodecType::DoubleDelta => {
+            delta_encode(&mut ws);
+            delta_encode(&mut ws);
+        }
+        CodecType::Gorilla => {
+            for i in (1..ws.len()).rev() {
+                ws[i] = ws[i] ^ ws[i - 1];
+            }
+        }
+        CodecType::T64 => return t64_encode(&ws),
+        _ => {}
+    }
+    let mut bs = Vec::with_capacity(raw.len());
+    for w in ws {
+        w.write(&mut bs);
+    }
+    bs
+}
+
+fn decode_words<T: Word>(
+    typ: CodecType,
+    tdata: &[u8],
+    rows: usize,
+    out: &mut Vec<u8>,
+) -> MetaResult<()> {
+    let mut ws: Vec<T> = if typ == CodecType::T64 {
+        t64_decode(tdata, rows)?
+    } else {
+        if tdata.len() != rows * T::WIDTH {
+            return Err(MetaError::CorruptedCompressedBlockError);
+        }
+        tdata.chunks_exact(T::WIDTH).map(T::read).collect()
+    };
+    match typ {
+        CodecType::Delta => delta_decode(&mut ws),
+        CodecType::DoubleDelta => {
+            delta_decode(&mut ws);
+            delta_decode(&mut ws);
+        }
+        CodecType::Gorilla => {
+            for i in 1..ws.len() {
+                ws[i] = ws[i] ^ ws[i - 1];
+            }
+        }
+        _ => {}
+    }
+    for w in ws {
+        w.write(out);
+    }
+    Ok(())
+}
+
+#[inline(always)]
+fn delta_encode<T: Word>(ws: &mut Vec<T>) {
+    for i in (1..ws.len()).rev() {
+        ws[i] = ws[i].wrapping_sub(&ws[i - 1]);
+    }
+}
+
+#[inline(always)]
+fn delta_decode<T: Word>(ws: &mut Vec<T>) {
+    for i in 1..ws.len() {
+        ws[i] = ws[i].wrapping_add(&ws[i - 1]);
+    }
+}
+
+fn t64_encode<T: Word>(ws: &Vec<T>) -> Vec<u8> {
+    let min = ws.iter().min().copied().unwrap_or_else(T::zero);
+    let max = ws.iter().max().copied().unwrap_or_else(T::zero);
+    let span = max.wrapping_sub(&min).to_u64().unwrap_or(u64::MAX);
+    let nbytes = (8 - span.leading_zeros() as usize / 8).min(T::WIDTH);
+    let mut bs = Vec::with_capacity(9 + ws.len() * nbytes);
+    min.to_u64().unwrap_or_default().write(&mut bs);
+    bs.push(nbytes as u8);
+    for w in ws {
+        let d = w.wrapping_sub(&min).to_u64().unwrap_or_default();
+        bs.extend_from_slice(&d.to_le_bytes()[..nbytes]);
+    }
+    bs
+}
+
+fn t64_decode<T: Word>(tdata: &[u8], rows: usize) -> MetaResult<Vec<T>> {
+    if tdata.len() < 9 {
+        return Err(MetaError::CorruptedCompressedBlockError);
+    }
+    let min: T = NumCast::from(u64::read(tdata))
+        .ok_or(MetaError::CorruptedCompressedBlockError)?;
+    let nbytes = tdata[8] as usize;
+    let body = &tdata[9..];
+    if nbytes > T::WIDTH || body.len() != rows * nbytes {
+        return Err(MetaError::CorruptedCompressedBlockError);
+    }
+    let mut ws = Vec::with_capacity(rows);
+    for i in 0..rows {
+        let mut b = [0u8; 8];
+        b[..nbytes].copy_from_slice(&body[i * nbytes..(i + 1) * nbytes]);
+        let d: T = NumCast::from(u64::from_le_bytes(b))
+            .ok_or(MetaError::CorruptedCompressedBlockError)?;
+        ws.push(d.wrapping_add(&min));
+    }
+    Ok(ws)
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use base::mem::shape_vec_u8;
+
+    fn check_roundtrip(
+        codec: ColumnCodec,
+        width: usize,
+        data: &[u8],
+    ) -> MetaResult<usize> {
+        let mut cbs = Vec::new();
+        compress_into(codec, width, data, &mut cbs)?;
+        //append another insert
+        compress_into(codec, width, data, &mut cbs)?;
+        let rows = data.len() / width;
+        let dbs = decompress(codec, width, &cbs, rows * 2)?;
+        assert_eq!(&dbs[..data.len()], data);
+        assert_eq!(&dbs[data.len()..], data);
+        //rows in the last block may be not visible yet
+        let dbs = decompress(codec, width, &cbs, rows + 1)?;
+        assert_eq!(dbs.len(), (rows + 1) * width);
+        Ok(cbs.len())
+    }
+
+    #[test]
+    fn test_codecs_roundtrip() -> MetaResult<()> {
+        let ts: Vec<u32> = (0..200_000u32).map(|i| 1600000000 + i * 3).collect();
+        let ts = shape_vec_u8(ts);
+        let fs: Vec<f64> = (0..100_000)
+            .map(|i| 20.0 + (i / 100) as f64 * 0.5)
+            .collect();
+        let fs = shape_vec_u8(fs);
+        let ns: Vec<u64> = (0..100_000u64).map(|i| i % 1000 + 5_000_000).collect();
+        let ns = shape_vec_u8(ns);
+
+        for (t, c) in [
+            (CodecType::None, CodecType::LZ4),
+            (CodecType::None, CodecType::ZSTD),
+            (CodecType::Delta, CodecType::None),
+            (CodecType::Delta, CodecType::LZ4),
+            (CodecType::DoubleDelta, CodecType::ZSTD),
+            (CodecType::T64, CodecType::None),
+            (CodecType::T64, CodecType::LZ4),
+        ] {
+            let mut codec = ColumnCodec::default();
+            codec.add(t, None)?;
+            codec.add(c, None)?;
+            let clen = check_roundtrip(codec, 4, &ts)?;
+            if matches!(t, CodecType::Delta | CodecType::DoubleDelta)
+                && c != CodecType::None
+            {
+                assert!(clen * 10 < ts.len() * 2, "{:?}: {}", codec, clen);
+            }
+            check_roundtrip(codec, 8, &ns)?;
+        }
+
+        let mut codec = ColumnCodec::default();
+        codec.add(CodecType::Gorilla, None)?;
+        codec.add(CodecType::ZSTD, Some(3))?;
+        let clen = check_roundtrip(codec, 8, &fs)?;
+        assert!(clen * 10 < fs.len() * 2);
+
+        //T64 crops into 2 bytes for values in a span < 64K
+        let mut codec = ColumnCodec::default();
+        codec.add(CodecType::T64, None)?;
+        let mut cbs = Vec::new();
+        compress_into(codec, 8, &ns, &mut cbs)?;
+        assert!(cbs.len() < ns.len() / 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_decompress_in_ranges() -> MetaResult<()> {
+        let mut codec = ColumnCodec::default();
+        codec.add(CodecType::Delta, None)?;
+        codec.add(CodecType::LZ4, None)?;
+        let ns: Vec<u32> = (0..3 * COMPRESSION_BLOCK_ROWS as u32).collect();
+        let ns = shape_vec_u8(ns);
+        let mut cbs = Vec::new();
+        compress_into(codec, 4, &ns, &mut cbs)?;
+        let rows = ns.len() / 4;
+        //only the second block is decoded
+        let (ofs, len) = (COMPRESSION_BLOCK_ROWS + 10, 100);
+        let dbs = decompress_in_ranges(codec, 4, &cbs, rows, &[(ofs, len)])?;
+        assert_eq!(dbs.len(), ns.len());
+        let b1 = COMPRESSION_BLOCK_ROWS * 4..COMPRESSION_BLOCK_ROWS * 8;
+        assert_eq!(&dbs[b1.clone()], &ns[b1.clone()]);
+        assert!(dbs[..b1.start].iter().all(|b| *b == 0));
+        assert!(dbs[b1.end..].iter().all(|b| *b == 0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_decompress_corrupted() {
+        let codec = ColumnCodec {
+            transform: CodecType::None,
+            compression: CodecType::LZ4,
+            level: 0,
+        };
+        let mut cbs = Vec::new();
+        compress_into(codec, 4, &[1u8; 400], &mut cbs).unwrap();
+        assert!(decompress(codec, 4, &cbs[..cbs.len() - 1], 100).is_err());
+        assert!(decompress(codec, 4, &cbs, 101).is_err());
+    }
+}
@@ -17,7 +17,10 @@ use base::fs::validate_path;
 use serde::{Deserialize, Serialize};
 use std::{env, fs};
 
-use crate::errs::{MetaError, MetaResult};
+use crate::{
+    errs::{MetaError, MetaResult},
+    store::mmaps::DEFAULT_MMAP_CACHE_CAPACITY,
+};
 
 #[derive(Serialize, Deserialize, PartialEq, Debug)]
 pub struct Conf {
@@ -193,17 +196,124 @@ impl Default for Tls {
 #[derive(Serialize, Deserialize, PartialEq, Debug)]
 pub struct Storage {
     pub data_dirs_clickhouse: String,
+    ///interval between two runs of the background housekeeping, like the TTL
+    #[serde(default = "Storage::default_housekeeping_interval_secs")]
+    pub housekeeping_interval_secs: u64,
+    #[serde(default)]
+    pub durability: Durability,
+    ///interval between two syncs of the written data in the batch durability
+    #[serde(default = "Storage::default_durability_batch_interval_ms")]
+    pub durability_batch_interval_ms: u64,
+    ///max number of the copas of which the mappings are cached for queries,
+    ///0 to unmap the copas when every query finishes
+    #[serde(default = "Storage::default_mmap_cache_capacity")]
+    pub mmap_cache_capacity: usize,
+    ///interval between two runs of the background merges of the parts, 0 to
+    ///merge the parts only by OPTIMIZE TABLE
+    #[serde(default = "Storage::default_merge_interval_secs")]
+    pub merge_interval_secs: u64,
+    ///a part is merged in background after this num of writes into it
+    #[serde(default = "Storage::default_merge_min_runs")]
+    pub merge_min_runs: usize,
+    ///or after this ratio of its rows have been deleted
+    #[serde(default = "Storage::default_merge_min_deleted_ratio")]
+    pub merge_min_deleted_ratio: f64,
+    ///the parts of more rows are only merged by OPTIMIZE TABLE
+    #[serde(default = "Storage::default_merge_max_rows")]
+    pub merge_max_rows: usize,
+    ///the tiers after the default volume, in order
+    #[serde(default)]
+    pub volumes: Vec<Volume>,
+}
+
+///the volume of system.data_dirs, where the parts are written into
+pub const DEFAULT_VOLUME: &str = "default";
+
+///a named volume of the data dirs, the parts are moved among volumes as a
+///whole
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct Volume {
+    pub name: String,
+    pub data_dirs: Vec<String>,
+}
+
+///when the written data are synced to the disks
+#[derive(Serialize, Deserialize, PartialEq, Debug, Copy, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum Durability {
+    ///left to the OS and the meta stores, acknowledged inserts may be lost on
+    ///power failure
+    None,
+    ///synced in background every durability_batch_interval_ms, the inserts
+    ///acknowledged in the last interval may be lost
+    Batch,
+    ///synced before an insert is acknowledged
+    PerInsert,
+}
+
+impl Default for Durability {
+    fn default() -> Self {
+        Durability::None
+    }
+}
+
+impl Storage {
+    fn default_housekeeping_interval_secs() -> u64 {
+        60
+    }
+
+    fn default_durability_batch_interval_ms() -> u64 {
+        1000
+    }
+
+    fn default_mmap_cache_capacity() -> usize {
+        DEFAULT_MMAP_CACHE_CAPACITY
+    }
+
+    fn default_merge_interval_secs() -> u64 {
+        60
+    }
+
+    fn default_merge_min_runs() -> usize {
+        16
+    }
+
+    fn default_merge_min_deleted_ratio() -> f64 {
+        0.5
+    }
+
+    fn default_merge_max_rows() -> usize {
+        100_000_000
+    }
 }
 
 impl Default for Storage {
     fn default() -> Self {
         Storage {
             data_dirs_clickhouse: String::new(),
+            housekeeping_interval_secs: Storage::default_housekeeping_interval_secs(),
+            durability: Durability::default(),
+            durability_batch_interval_ms: Storage::default_durability_batch_interval_ms(),
+            mmap_cache_capacity: Storage::default_mmap_cache_capacity(),
+            merge_interval_secs: Storage::default_merge_interval_secs(),
+            merge_min_runs: Storage::default_merge_min_runs(),
+            merge_min_deleted_ratio: Storage::default_merge_min_deleted_ratio(),
+            merge_max_rows: Storage::default_merge_max_rows(),
+            volumes: Vec::new(),
         }
     }
 }
 
 impl Conf {
+    ///the data dirs of all volumes
+    pub fn get_all_data_dirs(&self) -> Vec<String> {
+        let mut dd = self.system.data_dirs.clone();
+        for v in &self.storage.volumes {
+            dd.extend_from_slice(&v.data_dirs);
+        }
+        dd
+    }
+
     pub fn load(load_path: Option<&str>) -> Option<Conf> {
         let conf_opt = match load_path {
             None => {
@@ -259,6 +369,11 @@ mod unit_tests {
             
             [storage]
             data_dirs_clickhouse = ""
+            durability = "per_insert"
+
+            [[storage.volumes]]
+            name = "cold"
+            data_dirs = ["/tmp/tb_data_cold"]
             
             # enable TCP service 
             [server.tcp]
@@ -276,6 +391,18 @@ mod unit_tests {
         "#,
         )
         .unwrap();
+        assert_eq!(conf0.storage.durability, super::Durability::PerInsert);
+        assert_eq!(conf0.storage.durability_batch_interval_ms, 1000);
+        assert_eq!(
+            conf0.storage.mmap_cache_capacity,
+            super::DEFAULT_MMAP_CACHE_CAPACITY
+        );
+        assert_eq!(conf0.storage.merge_min_runs, 16);
+        assert_eq!(conf0.storage.volumes[0].name, "cold");
+        assert_eq!(
+            conf0.get_all_data_dirs(),
+            vec!["/tmp/tb_data".to_string(), "/tmp/tb_data_cold".to_string()]
+        );
         println!("{}", toml::to_string_pretty(&conf0).unwrap());
         Conf::save(&conf0, None).unwrap();
         let conf1 = Conf::load(None).unwrap();
@@ -283,4 +410,38 @@ mod unit_tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_storage_durability() -> MetaResult<()> {
+        use super::Durability;
+        let parse = |storage: &str| {
+            toml::from_str::<Conf>(&format!(
+                r#"[system]
+                meta_dirs = ["/tmp/tb_schema"]
+                data_dirs = ["/tmp/tb_data"]
+                [storage]
+                data_dirs_clickhouse = ""
+                {}
+                [server]
+                ip_addr = "localhost"
+            "#,
+                storage
+            ))
+        };
+        let conf = parse("").unwrap();
+        assert_eq!(conf.storage.durability, Durability::None);
+        assert_eq!(conf.storage.durability_batch_interval_ms, 1000);
+        let conf = parse("durability = \"none\"").unwrap();
+        assert_eq!(conf.storage.durability, Durability::None);
+        let conf =
+            parse("durability = \"batch\"\ndurability_batch_interval_ms = 200").unwrap();
+        assert_eq!(conf.storage.durability, Durability::Batch);
+        assert_eq!(conf.storage.durability_batch_interval_ms, 200);
+        let conf = parse("durability = \"per_insert\"").unwrap();
+        assert_eq!(conf.storage.durability, Durability::PerInsert);
+        assert!(parse("durability = \"PerInsert\"").is_err());
+        assert!(parse("durability = \"always\"").is_err());
+
+        Ok(())
+    }
 }
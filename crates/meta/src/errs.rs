@@ -50,6 +50,12 @@ pub enum MetaError {
     #[error("Database has not existed")]
     DbNotExistedError,
 
+    #[error("Table has not existed")]
+    TabNotExistedError,
+
+    #[error("Column has not existed")]
+    ColNotExistedError,
+
     #[error("Error when converting {0} into BqlType")]
     UnknownBqlTypeConversionError(String),
 
@@ -62,15 +68,27 @@ pub enum MetaError {
     #[error("Unsupported Bql type error")]
     UnsupportedBqlTypeError,
 
+    #[error("Invalid codec: {0}")]
+    InvalidCodecError(String),
+
+    #[error("Compressed block of CoPa is corrupted")]
+    CorruptedCompressedBlockError,
+
     #[error("Error when converting str into EngineType enum")]
     UnknownEngineTypeConversionError,
 
+    #[error("Unsupported aggregate function for AggregateFunction type: {0}")]
+    UnsupportedAggregateFunctionError(String),
+
     #[error("Meta store got type is not expected")]
     StoreGotTypeNotExpectedError,
 
     #[error("No fixed size for dynamic sized data type")]
     NoFixedSizeDataTypeError,
 
+    #[error("Data of the chunk does not match its type")]
+    ChunkDataMismatchError,
+
     #[error("Too long length for String")]
     TooLongLengthForStringError,
 
@@ -111,6 +129,21 @@ pub enum MetaError {
 
     #[error("Option is None But should not")]
     OptionIsNoneButShouldNot,
+
+    #[error("Volume [{0}] does not exist")]
+    VolumeNotExistError(String),
+
+    #[error("Data dir [{0}] is not in the configurations")]
+    DataDirNotExistError(String),
+
+    #[error("Part log [{0}] is corrupted")]
+    CorruptedPartLogError(String),
+
+    #[error("Table log [{0}] is corrupted")]
+    CorruptedTableLogError(String),
+
+    #[error("The write into the part is aborted with a write failed before it")]
+    PartWriteAbortedError,
 }
 
 impl<T: std::fmt::Display> From<TransactionError<T>> for MetaError {
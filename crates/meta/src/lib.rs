@@ -1,5 +1,6 @@
 #![feature(once_cell)]
 
+pub mod codecs;
 pub mod confs;
 pub mod errs;
 pub mod store;
@@ -0,0 +1,263 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+};
+
+use base::{
+    mem::MemAddr,
+    mmap::{mm_file_ro, mm_unmap},
+};
+use libc::close;
+
+use crate::{errs::MetaResult, types::Id};
+
+///the default max number of the copas of which the mappings are cached
+///
+///NOTE a copa has up to three mappings(data, om and nm), so the default is
+///     far below the default vm.max_map_count(65530) of linux
+pub const DEFAULT_MMAP_CACHE_CAPACITY: usize = 4096;
+
+///The read-only mappings of the files of a copa
+///
+///The mappings are unmapped when the last reference is dropped, so a query
+///holds the references of the copas it scans until all its result buffers,
+///which are the views of the mappings, are dropped.
+#[derive(Debug)]
+pub struct CoPaMaps {
+    pub addr: MemAddr,
+    pub len: usize,
+    pub addr_om: MemAddr,
+    pub len_om: usize,
+    pub addr_nm: MemAddr,
+    pub len_nm: usize,
+}
+
+//NOTE the mappings are read-only
+unsafe impl Send for CoPaMaps {}
+unsafe impl Sync for CoPaMaps {}
+
+impl CoPaMaps {
+    pub fn empty() -> Self {
+        CoPaMaps {
+            addr: 0 as MemAddr,
+            len: 0,
+            addr_om: 0 as MemAddr,
+            len_om: 0,
+            addr_nm: 0 as MemAddr,
+            len_nm: 0,
+        }
+    }
+
+    ///map len bytes of the file of fd, then close the fd
+    pub(crate) fn map_fd(fd: u32, len: usize) -> MetaResult<MemAddr> {
+        let addr = mm_file_ro(fd, len);
+        unsafe {
+            close(fd as i32);
+        }
+        Ok(addr?)
+    }
+}
+
+impl Drop for CoPaMaps {
+    fn drop(&mut self) {
+        for (addr, len) in [
+            (self.addr, self.len),
+            (self.addr_om, self.len_om),
+            (self.addr_nm, self.len_nm),
+        ] {
+            if !addr.is_null() && len > 0 {
+                if let Err(e) = mm_unmap(addr, len) {
+                    log::warn!("failed to unmap a copa: {:?}", e);
+                }
+            }
+        }
+    }
+}
+
+struct MmapEntry {
+    gen: usize,
+    size: usize,
+    maps: Arc<CoPaMaps>,
+    tick: u64,
+}
+
+#[derive(Default)]
+struct MmapCacheInner {
+    capacity: usize,
+    tick: u64,
+    ents: HashMap<(Id, u64), MmapEntry>,
+    ///tick of the last use -> key of the entry
+    lru: BTreeMap<u64, (Id, u64)>,
+}
+
+impl MmapCacheInner {
+    fn remove(&mut self, key: &(Id, u64)) {
+        if let Some(e) = self.ents.remove(key) {
+            self.lru.remove(&e.tick);
+        }
+    }
+
+    fn evict(&mut self) {
+        while self.ents.len() > self.capacity {
+            match self.lru.iter().next().map(|(t, k)| (*t, *k)) {
+                Some((t, k)) => {
+                    self.lru.remove(&t);
+                    self.ents.remove(&k);
+                }
+                None => break,
+            }
+        }
+    }
+}
+
+///The LRU cache of the mappings of the copas keyed by (cid, ptk, gen, size)
+///
+///A copa is append-only, so the cached mappings of a copa are replaced when
+///the copa has grown or the part is switched to the files of another
+///generation. An entry evicted or replaced is unmapped after the queries which
+///are using it have finished.
+pub(crate) struct MmapCache {
+    inner: Mutex<MmapCacheInner>,
+}
+
+impl MmapCache {
+    pub fn new(capacity: usize) -> Self {
+        MmapCache {
+            inner: Mutex::new(MmapCacheInner {
+                capacity,
+                ..Default::default()
+            }),
+        }
+    }
+
+    pub fn set_capacity(&self, capacity: usize) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.capacity = capacity;
+        inner.evict();
+    }
+
+    ///get the mappings of the copa of the size in the files of the generation,
+    ///or map them by f
+    pub fn get_or_map(
+        &self,
+        cid: Id,
+        ptk: u64,
+        gen: usize,
+        size: usize,
+        f: impl FnOnce() -> MetaResult<CoPaMaps>,
+    ) -> MetaResult<Arc<CoPaMaps>> {
+        let key = (cid, ptk);
+        {
+            let mut inner = self.inner.lock().unwrap();
+            inner.tick += 1;
+            let tick = inner.tick;
+            if let Some(e) = inner.ents.get_mut(&key) {
+                if e.gen == gen && e.size == size {
+                    let old = e.tick;
+                    e.tick = tick;
+                    let maps = e.maps.clone();
+                    inner.lru.remove(&old);
+                    inner.lru.insert(tick, key);
+                    return Ok(maps);
+                }
+            }
+        }
+        //NOTE the files are mapped out of the lock
+        let maps = Arc::new(f()?);
+        let mut inner = self.inner.lock().unwrap();
+        if inner.capacity == 0 {
+            return Ok(maps);
+        }
+        if let Some(e) = inner.ents.get(&key) {
+            //a copa mapped by another query with a larger size or in a newer
+            //generation is kept
+            if e.gen > gen || (e.gen == gen && e.size >= size) {
+                return Ok(maps);
+            }
+        }
+        inner.remove(&key);
+        inner.tick += 1;
+        let tick = inner.tick;
+        inner.ents.insert(
+            key,
+            MmapEntry {
+                gen,
+                size,
+                maps: maps.clone(),
+                tick,
+            },
+        );
+        inner.lru.insert(tick, key);
+        inner.evict();
+        Ok(maps)
+    }
+
+    pub fn remove_copa(&self, cid: Id, ptk: u64) {
+        self.inner.lock().unwrap().remove(&(cid, ptk));
+    }
+
+    pub fn remove_column(&self, cid: Id) {
+        let mut inner = self.inner.lock().unwrap();
+        let keys: Vec<_> = inner.ents.keys().filter(|k| k.0 == cid).copied().collect();
+        for k in keys {
+            inner.remove(&k);
+        }
+    }
+
+    #[cfg(test)]
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().ents.len()
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    fn maps() -> MetaResult<CoPaMaps> {
+        Ok(CoPaMaps::empty())
+    }
+
+    #[test]
+    fn test_mmap_cache() -> MetaResult<()> {
+        let c = MmapCache::new(2);
+        let m0 = c.get_or_map(1, 1, 0, 10, maps)?;
+        let m1 = c.get_or_map(1, 1, 0, 10, || panic!("should hit"))?;
+        assert!(Arc::ptr_eq(&m0, &m1));
+
+        //the grown copa is mapped again
+        let m2 = c.get_or_map(1, 1, 0, 20, maps)?;
+        assert!(!Arc::ptr_eq(&m0, &m2));
+        assert_eq!(c.len(), 1);
+
+        //the copa in the files of the next generation is mapped again, and
+        //the one of the old generation mapped late does not replace it
+        let m4 = c.get_or_map(1, 1, 1, 20, maps)?;
+        assert!(!Arc::ptr_eq(&m2, &m4));
+        c.get_or_map(1, 1, 0, 30, maps)?;
+        let m5 = c.get_or_map(1, 1, 1, 20, || panic!("should hit"))?;
+        assert!(Arc::ptr_eq(&m4, &m5));
+        c.get_or_map(1, 1, 0, 20, maps)?;
+        c.get_or_map(1, 1, 1, 20, || panic!("should hit"))?;
+        assert_eq!(c.len(), 1);
+
+        //(1, 2) is the least recently used one
+        c.get_or_map(1, 2, 0, 10, maps)?;
+        c.get_or_map(1, 1, 1, 20, || panic!("should hit"))?;
+        c.get_or_map(2, 1, 0, 10, maps)?;
+        assert_eq!(c.len(), 2);
+        c.get_or_map(1, 1, 1, 20, || panic!("should hit"))?;
+        c.get_or_map(2, 1, 0, 10, || panic!("should hit"))?;
+
+        c.remove_column(1);
+        assert_eq!(c.len(), 1);
+        c.remove_copa(2, 1);
+        assert_eq!(c.len(), 0);
+
+        c.set_capacity(0);
+        let m3 = c.get_or_map(1, 1, 0, 10, maps)?;
+        assert_eq!(c.len(), 0);
+        assert_eq!(Arc::strong_count(&m3), 1);
+        Ok(())
+    }
+}
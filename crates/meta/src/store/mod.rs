@@ -1,2 +1,5 @@
+pub mod mmaps;
 pub mod parts;
+mod partlog;
 pub mod sys;
+pub mod tablog;
@@ -0,0 +1,288 @@
+use std::{
+    convert::TryInto,
+    fs::{self, File, OpenOptions},
+    io::{ErrorKind, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    errs::{MetaError, MetaResult},
+    types::Id,
+};
+
+const LOG_FILE: &str = "log";
+const CKPT_FILE: &str = "ckpt";
+const CKPT_TMP_FILE: &str = "ckpt.tmp";
+
+///the log is checkpointed when it grows over this size
+const CHECKPOINT_LOG_SIZE: u64 = 64 * 1024 * 1024; //FIXME configurable
+///the records of the checkpoint are split into frames of about this size
+const CHECKPOINT_FRAME_SIZE: usize = 1024 * 1024;
+
+///len and crc32c of the records
+const FRAME_HEADER_SIZE: usize = 8;
+///tree, op, key and len of the value
+const RECORD_HEADER_SIZE: usize = 22;
+
+///a change of an entry in a tree of the part catalog, the value is None for
+///a removal
+///
+///NOTE the value is the whole new value of the entry, so that the records
+///     can be replayed onto a checkpoint which already contains some of them
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Record {
+    pub tree: u8,
+    pub key: (Id, u64),
+    pub val: Option<Vec<u8>>,
+}
+
+impl Record {
+    ///layout: tree, 1 for put or 0 for removal, key, len of value, value
+    fn write_bytes(&self, bs: &mut Vec<u8>) {
+        let val = self.val.as_deref().unwrap_or_default();
+        bs.push(self.tree);
+        bs.push(self.val.is_some() as u8);
+        bs.extend_from_slice(&self.key.0.to_le_bytes());
+        bs.extend_from_slice(&self.key.1.to_le_bytes());
+        bs.extend_from_slice(&(val.len() as u32).to_le_bytes());
+        bs.extend_from_slice(val);
+    }
+
+    fn read_bytes(bs: &[u8], ofs: usize) -> Option<(Record, usize)> {
+        let hdr = bs.get(ofs..ofs + RECORD_HEADER_SIZE)?;
+        let k0 = u64::from_le_bytes(hdr[2..10].try_into().ok()?);
+        let k1 = u64::from_le_bytes(hdr[10..18].try_into().ok()?);
+        let len = u32::from_le_bytes(hdr[18..22].try_into().ok()?) as usize;
+        let end = ofs + RECORD_HEADER_SIZE + len;
+        let val = bs.get(ofs + RECORD_HEADER_SIZE..end)?;
+        let rec = Record {
+            tree: hdr[0],
+            key: (k0, k1),
+            val: if hdr[1] == 0 {
+                None
+            } else {
+                Some(val.to_vec())
+            },
+        };
+        Some((rec, end))
+    }
+}
+
+///layout: len of records, crc32c of records, records
+fn write_frame(recs: &[Record], bs: &mut Vec<u8>) {
+    let start = bs.len();
+    bs.extend_from_slice(&[0u8; FRAME_HEADER_SIZE]);
+    for r in recs {
+        r.write_bytes(bs);
+    }
+    let len = (bs.len() - start - FRAME_HEADER_SIZE) as u32;
+    let crc = crc32c::crc32c(&bs[start + FRAME_HEADER_SIZE..]);
+    bs[start..start + 4].copy_from_slice(&len.to_le_bytes());
+    bs[start + 4..start + FRAME_HEADER_SIZE].copy_from_slice(&crc.to_le_bytes());
+}
+
+///read the records of the frames in bs, return the offset after the last
+///intact frame
+fn read_frames(bs: &[u8], recs: &mut Vec<Record>) -> usize {
+    let mut ofs = 0;
+    while let Some(hdr) = bs.get(ofs..ofs + FRAME_HEADER_SIZE) {
+        let len = u32::from_le_bytes(hdr[0..4].try_into().unwrap()) as usize;
+        let crc = u32::from_le_bytes(hdr[4..8].try_into().unwrap());
+        let end = ofs + FRAME_HEADER_SIZE + len;
+        let frame = match bs.get(ofs + FRAME_HEADER_SIZE..end) {
+            Some(frame) if crc32c::crc32c(frame) == crc => frame,
+            _ => break,
+        };
+        let mut frs = Vec::new();
+        let mut o = 0;
+        while o < frame.len() {
+            match Record::read_bytes(frame, o) {
+                Some((r, e)) => {
+                    frs.push(r);
+                    o = e;
+                }
+                None => return ofs,
+            }
+        }
+        recs.append(&mut frs);
+        ofs = end;
+    }
+    ofs
+}
+
+///The append-only log of the part catalog
+///
+///The changes made by an operation of the part store are appended into the
+///log as one frame, which is replayed as a whole or not at all. A frame torn
+///by a crash is at the tail of the log, and discarded when the log is opened.
+///When the log grows too large, the whole catalog is written into a new
+///checkpoint and the log is truncated.
+pub(crate) struct PartLog {
+    dir: PathBuf,
+    file: File,
+    ///the len of the frames written into the file
+    len: u64,
+    ///the frames not written into the file yet
+    pending: Vec<u8>,
+    auto_flush: bool,
+    has_ckpt: bool,
+}
+
+impl PartLog {
+    ///open the log in dir, return it with the records of the checkpoint and
+    ///the log in order
+    pub fn open(dir: &Path, auto_flush: bool) -> MetaResult<(PartLog, Vec<Record>)> {
+        fs::create_dir_all(dir)?;
+        let mut recs = Vec::new();
+        let has_ckpt = match fs::read(dir.join(CKPT_FILE)) {
+            Ok(bs) => {
+                if read_frames(&bs, &mut recs) != bs.len() {
+                    return Err(MetaError::CorruptedPartLogError(
+                        dir.join(CKPT_FILE).display().to_string(),
+                    ));
+                }
+                true
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => false,
+            Err(e) => return Err(MetaError::WrappingIOError(e)),
+        };
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(dir.join(LOG_FILE))?;
+        let mut bs = Vec::new();
+        file.read_to_end(&mut bs)?;
+        let len = read_frames(&bs, &mut recs);
+        if len < bs.len() {
+            log::warn!(
+                "discard the torn tail of {} bytes of the part log",
+                bs.len() - len
+            );
+            file.set_len(len as u64)?;
+        }
+        let plog = PartLog {
+            dir: dir.to_path_buf(),
+            file,
+            len: len as u64,
+            pending: Vec::new(),
+            auto_flush,
+            has_ckpt,
+        };
+        Ok((plog, recs))
+    }
+
+    #[inline]
+    pub fn has_checkpoint(&self) -> bool {
+        self.has_ckpt
+    }
+
+    ///append the records as one frame, which is written into the file
+    ///immediately if auto flush, or else by `flush`
+    pub fn append(&mut self, recs: &[Record]) -> MetaResult<()> {
+        write_frame(recs, &mut self.pending);
+        if self.auto_flush {
+            self.write_pending()?;
+        }
+        Ok(())
+    }
+
+    fn write_pending(&mut self) -> MetaResult<()> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        if let Err(e) = self.file.write_all(&self.pending) {
+            //NOTE not to leave a torn frame before the frames appended later
+            self.file.set_len(self.len)?;
+            return Err(MetaError::WrappingIOError(e));
+        }
+        self.len += self.pending.len() as u64;
+        self.pending.clear();
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> MetaResult<()> {
+        self.write_pending()?;
+        self.file.sync_data()?;
+        Ok(())
+    }
+
+    ///NOTE the pending frames are not to be persisted by a checkpoint before
+    ///     they are flushed
+    #[inline]
+    pub fn should_checkpoint(&self) -> bool {
+        self.pending.is_empty() && self.len >= CHECKPOINT_LOG_SIZE
+    }
+
+    ///replace the checkpoint by the records of the whole catalog, then
+    ///truncate the log of which all changes are in the new checkpoint
+    pub fn checkpoint(&mut self, recs: impl Iterator<Item = Record>) -> MetaResult<()> {
+        let tmp_path = self.dir.join(CKPT_TMP_FILE);
+        let mut tmp = File::create(&tmp_path)?;
+        let mut frs = Vec::new();
+        let mut siz = 0;
+        let mut bs = Vec::new();
+        for r in recs {
+            siz += RECORD_HEADER_SIZE + r.val.as_ref().map_or(0, |v| v.len());
+            frs.push(r);
+            if siz >= CHECKPOINT_FRAME_SIZE {
+                write_frame(&frs, &mut bs);
+                tmp.write_all(&bs)?;
+                frs.clear();
+                bs.clear();
+                siz = 0;
+            }
+        }
+        if !frs.is_empty() {
+            write_frame(&frs, &mut bs);
+            tmp.write_all(&bs)?;
+        }
+        tmp.sync_all()?;
+        fs::rename(&tmp_path, self.dir.join(CKPT_FILE))?;
+        File::open(&self.dir)?.sync_all()?;
+        self.has_ckpt = true;
+
+        self.pending.clear();
+        self.file.set_len(0)?;
+        self.file.sync_data()?;
+        self.len = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    #[test]
+    fn test_read_frames() {
+        let r0 = Record {
+            tree: 1,
+            key: (2, 3),
+            val: Some(vec![4, 5]),
+        };
+        let r1 = Record {
+            tree: 6,
+            key: (7, u64::MAX),
+            val: None,
+        };
+        let mut bs = Vec::new();
+        write_frame(&[r0.clone(), r1.clone()], &mut bs);
+        let len = bs.len();
+        write_frame(&[r1.clone()], &mut bs);
+
+        let mut recs = Vec::new();
+        assert_eq!(read_frames(&bs, &mut recs), bs.len());
+        assert_eq!(recs, vec![r0.clone(), r1.clone(), r1.clone()]);
+
+        //the torn frame is discarded as a whole
+        let mut recs = Vec::new();
+        assert_eq!(read_frames(&bs[..bs.len() - 1], &mut recs), len);
+        assert_eq!(recs, vec![r0.clone(), r1.clone()]);
+
+        bs[len + FRAME_HEADER_SIZE] ^= 1;
+        let mut recs = Vec::new();
+        assert_eq!(read_frames(&bs, &mut recs), len);
+        assert_eq!(recs, vec![r0, r1]);
+    }
+}
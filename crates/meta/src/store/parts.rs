@@ -1,17 +1,26 @@
 use std::{
-    ffi::CStr,
+    collections::{btree_map, BTreeMap, HashMap, HashSet, VecDeque},
+    ffi::{CStr, CString},
+    fs,
     io::{Error, ErrorKind},
+    mem::size_of,
     ops::RangeInclusive,
-    thread::park_timeout,
-    time::Duration,
+    path::Path,
+    ptr,
+    sync::{Arc, Condvar, Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard},
 };
 
 use base::{bytes_cat, mem::MemAddr, mmap::mm_file_ro};
 use libc::close;
 
+use super::{
+    mmaps::{CoPaMaps, MmapCache, DEFAULT_MMAP_CACHE_CAPACITY},
+    partlog::{PartLog, Record},
+};
 use crate::{
+    confs::{Volume, DEFAULT_VOLUME},
     errs::{MetaError, MetaResult},
-    types::{AsBytes, BqlType, Id, IntoRef},
+    types::{AsBytes, BqlType, ColumnInfo, Id, IntoRef},
 };
 
 ///
@@ -23,8 +32,12 @@ use crate::{
 ///  from their creations.
 ///
 ///* Part Store:
-///  a implementation for the partition tree.
-///  currently to use sled is not performant and could be changed in the future
+///  a implementation for the partition tree. All infos of the parts are kept
+///  in an in-memory catalog, and every change of them is appended into the
+///  part log before it is applied. The log is replayed onto the last
+///  checkpoint of the catalog at startup, and checkpointed when it grows too
+///  large. The store in sled of the old versions is imported at the first
+///  startup.
 ///
 ///* Copa(Column Partition):
 ///  TB is columnar, so the partition is columnar, which is called as CoPa(is
@@ -34,6 +47,73 @@ use crate::{
 ///  All tables should specify the partition keys implicitly or explicitly from
 ///  their creations.
 ///
+///* Sparse Index:
+///  when a table has a primary key, rows of every insert are sorted by that
+///  key before being appended into the part. A sparse mark is persisted every
+///  `index_granularity` rows in a `pi` file next to the CoPa of the primary
+///  key column. The mark keeps the min/max key of its granule, so the scanner
+///  can skip granules which can not match a WHERE range on the key.
+///
+///* Null Map:
+///  a Nullable column has a `nm` file next to its CoPa. The file has one byte
+///  per row of the part, 1 for NULL and 0 for a value, the same as the null
+///  map in the ClickHouse native block.
+///
+///* CoPa Base:
+///  a column added by ALTER TABLE has no data for the rows which have been
+///  written into the parts before. The count of these rows is kept as the base
+///  of the copa, and they are read as the default of the column without
+///  rewriting the old data. A column modified to another type gets a new copa
+///  whose base rows are converted from the copa of the old column when read,
+///  until they are rewritten into the new copa in the background.
+///
+///* Deletion Bitmap:
+///  rows removed by the DELETE or UPDATE mutations are marked in the deletion
+///  bitmap of their part, one bit per row and 1 for a deleted row. The rows
+///  appended after the bitmap was set are out of the bitmap and not deleted.
+///  The scanner filters the deleted rows out, and OPTIMIZE TABLE rewrites the
+///  parts to physically drop them.
+///
+///* CoPa Stats:
+///  the min/max of the non-null values and the count of NULLs are merged into
+///  the stats of the copa by every insert. The scanner skips the parts whose
+///  stats can not match the WHERE ranges on a column. The stats only cover the
+///  rows inserted into the copa, so they are not used to skip the parts of
+///  which some rows are not counted(like the base rows of an altered column).
+///  The signed integers are kept with their sign bits flipped, so that their
+///  min/max are ordered as u64 too.
+///
+///* Part Write:
+///  a write into a part is recorded as an intent with the sizes of its copas
+///  before the data files are written. Then the sizes, stats of the copas and
+///  the committed rows of the part are published with the removal of the
+///  intent in one transaction. So an insert is either fully visible or not at
+///  all, and the intents left by a crash are rolled back at startup by
+///  truncating the files of their copas to the recorded sizes.
+///  The rows and the bytes of the writes into a part are reserved in order,
+///  then their files are written concurrently, and they are committed in the
+///  order of the reservations. The intent is the one of the first write in
+///  flight, so the writes after it are rolled back together.
+///
+///* CoPa Checksums:
+///  the files(the data, om and nm files) of a copa are checksummed by the
+///  crc32c of every `CRC_BLOCK_SIZE` bytes block. The checksums of the blocks
+///  changed by a write are updated with the commit of the write. CHECK TABLE
+///  verifies the files of all parts against their checksums.
+///
+///* Part Meta:
+///  all infos of a part kept in the part store(the rows, and the size, base,
+///  stats of its copas, and the deletion bitmap). The part meta is saved in a
+///  file next to the files of a detached part, and is registered back when the
+///  part is attached or moved into another table.
+///
+///* Part Generation:
+///  a merge writes the part into the files of the next generation of the part
+///  (with a `v<gen>` after the ptk in their names), then switches the part to
+///  them. So the queries which have taken the infos of the part before the
+///  switch still read the files of the old generation, which are unlinked
+///  after these queries have mapped them.
+///
 
 //FIXME move to types mod?
 #[derive(Debug)]
@@ -41,24 +121,82 @@ use crate::{
 pub struct CoPaInfo {
     pub addr: MemAddr,
     pub addr_om: MemAddr,
+    pub addr_nm: MemAddr,
     pub size: usize, //WARN size is not the len of bytes, it is the size of that copa
     pub len_in_bytes: usize,
+    pub ptk: u64,
+    pub base: CoPaBase,
+    ///the copa of base.src_cid and its null map, null if no src
+    pub addr_src: MemAddr,
+    pub addr_src_nm: MemAddr,
+    ///the mappings of the copa and the src copa, which are unmapped when the
+    ///last reference is dropped
+    pub maps: Option<Arc<CoPaMaps>>,
+    pub src_maps: Option<Arc<CoPaMaps>>,
+}
+
+///the infos of a part in the snapshot of its table
+#[derive(Debug)]
+pub struct PartSnapshot<'a> {
+    pub ptk: u64,
+    ///the committed rows of the part
+    pub rows: usize,
+    pub gen: usize,
+    pub dp: &'a String,
+    pub dels: Option<Vec<u8>>,
+    ///cid -> the size in bytes(None if no file) and the base of the copa
+    copas: HashMap<Id, (Option<usize>, CoPaBase)>,
+}
+
+///the infos of the parts of a table taken by one read of the catalog, so that
+///a query reads all columns of a part in the same generation, and prunes and
+///filters its rows by the sparse index and the deletion bitmap of that one
+///
+///NOTE the files of the generations in the snapshot are not unlinked until it
+///     is dropped
+pub struct TableSnapshot<'a> {
+    pub tid: Id,
+    pub parts: Vec<PartSnapshot<'a>>,
+    _mg: RwLockReadGuard<'a, ()>,
+}
+
+impl<'a> TableSnapshot<'a> {
+    ///return the deletion bitmaps of the parts keyed by ptk
+    pub fn get_deletion_bitmaps(&self) -> HashMap<u64, Vec<u8>> {
+        self.parts
+            .iter()
+            .filter_map(|p| p.dels.as_ref().map(|bm| (p.ptk, bm.clone())))
+            .collect()
+    }
+}
+
+///rows [0, rows) of a copa are not in its file, they are read as the default
+///of the column, or converted from the copa of src_cid(in src_type) if it is
+///not zero
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[repr(C, packed)]
+pub struct CoPaBase {
+    pub rows: usize,
+    pub src_cid: Id,
+    pub src_type: BqlType,
 }
 
+impl AsBytes for CoPaBase {}
+
 impl CoPaInfo {
     #[inline(always)]
     pub fn len_in_bytes(
         size: usize,
-        col_typ: BqlType,
-        cid: Id,
-        ptk: u64,
-        ps: &PartStore,
+        ci: &ColumnInfo,
+        siz_in_bytes: usize,
     ) -> MetaResult<usize> {
-        match col_typ {
-            BqlType::String => Ok(ps
-                .get_copa_siz_in_bytes_int_ptk(cid, ptk)?
-                .unwrap_or_default()),
-            _ => Ok(size * (col_typ.size_in_usize()?)),
+        let col_typ = ci.data_type;
+        let codec = ci.codec;
+        //NOTE the compressed copa is in blocks of its own
+        if col_typ.is_blob() || !codec.is_none() {
+            Ok(siz_in_bytes)
+        } else {
+            Ok(size * (col_typ.size_in_usize()?))
         }
     }
 
@@ -68,6 +206,25 @@ impl CoPaInfo {
     }
 }
 
+///map the null map of a copa, or return null address if the part has no
+///null map which means that there is no NULL in it
+fn map_null_map(nmpath: &Vec<u8>, size: usize) -> MetaResult<MemAddr> {
+    let nmfile =
+        CStr::from_bytes_with_nul(nmpath).map_err(|_| MetaError::GetPartInfoError)?;
+    unsafe {
+        let mut st: libc::stat = std::mem::zeroed();
+        if libc::stat(nmfile.as_ptr(), &mut st) < 0 || (st.st_size as usize) < size {
+            return Ok(0 as MemAddr);
+        }
+    }
+    let fd_nm = open(nmfile)?;
+    let addr_nm = mm_file_ro(fd_nm, size);
+    unsafe {
+        close(fd_nm as i32);
+    }
+    Ok(addr_nm?)
+}
+
 #[inline(always)]
 pub fn open_file_as_fd(fpath: &Vec<u8>) -> MetaResult<u32> {
     let part_file =
@@ -83,6 +240,20 @@ pub fn open_file_as_fd(fpath: &Vec<u8>) -> MetaResult<u32> {
 // }
 
 #[inline]
+///the available bytes of the file system of the dir
+pub fn get_available_space(dp: &str) -> MetaResult<u64> {
+    let path =
+        CString::new(dp).map_err(|_| MetaError::DataDirNotExistError(dp.to_string()))?;
+    let mut st: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(path.as_ptr(), &mut st) } != 0 {
+        return Err(MetaError::WrappingIOError(Error::last_os_error()));
+    }
+    Ok(st.f_bavail as u64 * st.f_frsize as u64)
+}
+
+///the function returning the available bytes of a data dir
+pub type SpaceFn = Box<dyn Fn(&str) -> MetaResult<u64> + Send + Sync>;
+
 pub fn ensure_table_path_existed(tid: Id, dp: &str) -> MetaResult<()> {
     //mkdir
     let mut bs_dn = [0u8; 12];
@@ -97,10 +268,26 @@ pub fn ensure_table_path_existed(tid: Id, dp: &str) -> MetaResult<()> {
     Ok(())
 }
 
+///the name of the file of the copa in the generation of the part, the files
+///of the generation 0 have no generation in their names
+pub fn get_part_file_name(cid: Id, ptk: u64, gen: usize) -> String {
+    if gen == 0 {
+        format!("{}_{}", cid, ptk)
+    } else {
+        format!("{}_{}v{}", cid, ptk, gen)
+    }
+}
+
 #[inline]
-pub fn get_part_path(tid: Id, cid: Id, ptk: u64, dp: &str) -> MetaResult<Vec<u8>> {
+pub fn get_part_path(
+    tid: Id,
+    cid: Id,
+    ptk: u64,
+    gen: usize,
+    dp: &str,
+) -> MetaResult<Vec<u8>> {
     //open file
-    let mut bs_fn = [0u8; 56];
+    let mut bs_fn = [0u8; 88];
     let mut n = itoa::write(&mut bs_fn[..], tid)?;
     bs_fn[n] = b'/';
     n += 1;
@@ -108,6 +295,11 @@ pub fn get_part_path(tid: Id, cid: Id, ptk: u64, dp: &str) -> MetaResult<Vec<u8>
     bs_fn[n] = b'_';
     n += 1;
     n += itoa::write(&mut bs_fn[n..], ptk)?;
+    if gen > 0 {
+        bs_fn[n] = b'v';
+        n += 1;
+        n += itoa::write(&mut bs_fn[n..], gen)?;
+    }
 
     // let fpath = bytes_cat!(dp.as_bytes(), b"/", &bs_fn[..=n]);
     let mut fpath = Vec::with_capacity(dp.len() + 1 + n + 1);
@@ -119,283 +311,1831 @@ pub fn get_part_path(tid: Id, cid: Id, ptk: u64, dp: &str) -> MetaResult<Vec<u8>
 
 #[inline]
 pub fn gen_ompath_from_part_path(part_path: &Vec<u8>) -> MetaResult<Vec<u8>> {
-    let mut ompath = Vec::with_capacity(part_path.len() + 2);
-    ompath.extend_from_slice(part_path);
-    let idx = ompath
+    gen_sidecar_path_from_part_path(part_path, b"om")
+}
+
+#[inline]
+pub fn gen_nmpath_from_part_path(part_path: &Vec<u8>) -> MetaResult<Vec<u8>> {
+    gen_sidecar_path_from_part_path(part_path, b"nm")
+}
+
+#[inline]
+pub fn gen_pipath_from_part_path(part_path: &Vec<u8>) -> MetaResult<Vec<u8>> {
+    gen_sidecar_path_from_part_path(part_path, b"pi")
+}
+
+///append the suffix of a sidecar file to the (nul terminated) part path
+#[inline]
+pub fn gen_sidecar_path_from_part_path(
+    part_path: &Vec<u8>,
+    suffix: &[u8],
+) -> MetaResult<Vec<u8>> {
+    let mut path = Vec::with_capacity(part_path.len() + suffix.len());
+    path.extend_from_slice(part_path);
+    let idx = path
         .iter()
         .position(|&e| e == 0)
         .ok_or(MetaError::OptionIsNoneButShouldNot)?;
-    ompath.insert(idx, b'm');
-    ompath.insert(idx, b'o');
-    Ok(ompath)
+    path.truncate(idx);
+    path.extend_from_slice(suffix);
+    path.push(0);
+    Ok(path)
+}
+
+pub const DEFAULT_INDEX_GRANULARITY: usize = 8192;
+
+///stats of the rows inserted into a copa
+///
+///NOTE min/max are only kept for the types of `has_stats_minmax`
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[repr(C)]
+pub struct CoPaStats {
+    pub min: u64,
+    pub max: u64,
+    pub nulls: u64,
+    pub rows: u64,
+}
+
+impl AsBytes for CoPaStats {}
+
+impl CoPaStats {
+    #[inline(always)]
+    pub fn has_values(&self) -> bool {
+        self.rows > self.nulls
+    }
+
+    pub fn merge(&mut self, other: &CoPaStats) {
+        if other.has_values() {
+            if self.has_values() {
+                self.min = self.min.min(other.min);
+                self.max = self.max.max(other.max);
+            } else {
+                self.min = other.min;
+                self.max = other.max;
+            }
+        }
+        self.nulls += other.nulls;
+        self.rows += other.rows;
+    }
+
+    ///whether some value of the copa may be in the key ranges
+    ///
+    ///NOTE NULLs never match a range
+    pub fn may_match(&self, key_ranges: &[RangeInclusive<u64>]) -> bool {
+        self.has_values()
+            && key_ranges
+                .iter()
+                .any(|r| *r.start() <= self.max && self.min <= *r.end())
+    }
+}
+
+///the infos of a copa in the meta of its part
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[repr(C)]
+pub struct CoPaMeta {
+    pub cid: Id,
+    ///0 if the copa has no file in the part
+    pub siz_in_bytes: usize,
+    pub base: CoPaBase,
+    pub stats: CoPaStats,
+}
+
+impl AsBytes for CoPaMeta {}
+
+///the crcs of the copas are in the same order of the copas
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PartMeta {
+    pub rows: usize,
+    pub copas: Vec<CoPaMeta>,
+    pub crcs: Vec<CoPaCrcs>,
+    pub dels: Vec<u8>,
+}
+
+impl PartMeta {
+    ///layout: rows, count of copas, copas, the crcs of the copas, then the
+    ///deletion bitmap to the end
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bs = Vec::new();
+        bs.extend_from_slice(self.rows.as_bytes());
+        bs.extend_from_slice(self.copas.len().as_bytes());
+        for cm in &self.copas {
+            bs.extend_from_slice(cm.as_bytes());
+        }
+        for cc in &self.crcs {
+            cc.write_bytes(&mut bs);
+        }
+        bs.extend_from_slice(&self.dels);
+        bs
+    }
+
+    pub fn from_bytes(bs: &[u8]) -> MetaResult<PartMeta> {
+        let (hdr, ofs) = read_unaligned_vec::<usize>(bs, 0, 2)?;
+        let (copas, mut end) = read_unaligned_vec::<CoPaMeta>(bs, ofs, hdr[1])?;
+        let mut crcs = Vec::with_capacity(copas.len());
+        for _ in 0..copas.len() {
+            let (cc, e) = CoPaCrcs::read_bytes(bs, end)?;
+            crcs.push(cc);
+            end = e;
+        }
+        Ok(PartMeta {
+            rows: hdr[0],
+            copas,
+            crcs,
+            dels: bs[end..].to_vec(),
+        })
+    }
+}
+
+///the size of the blocks of the part files which are checksummed one by one
+pub const CRC_BLOCK_SIZE: usize = 64 * 1024;
+
+///the crc32c of every block of the first len bytes of a part file
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FileCrcs {
+    pub len: usize,
+    pub crcs: Vec<u32>,
+}
+
+impl FileCrcs {
+    ///the offset of the first block changed by a write from ofs
+    #[inline]
+    pub fn block_start(&self, ofs: usize) -> usize {
+        ofs.min(self.len) / CRC_BLOCK_SIZE * CRC_BLOCK_SIZE
+    }
+
+    ///update the crcs by the bytes of the file from start, which should be
+    ///returned by `block_start`, to the new end of the file
+    pub fn update(&mut self, start: usize, bs: &[u8]) {
+        debug_assert!(start % CRC_BLOCK_SIZE == 0 && start <= self.len);
+        self.crcs.truncate(start / CRC_BLOCK_SIZE);
+        self.crcs
+            .extend(bs.chunks(CRC_BLOCK_SIZE).map(|b| crc32c::crc32c(b)));
+        self.len = start + bs.len();
+    }
+
+    ///return the indexes of the blocks mismatched with the first len bytes of
+    ///the file in bs
+    pub fn verify(&self, bs: &[u8]) -> Vec<usize> {
+        bs.chunks(CRC_BLOCK_SIZE)
+            .zip(&self.crcs)
+            .enumerate()
+            .filter(|(_, (b, c))| crc32c::crc32c(b) != **c)
+            .map(|(i, _)| i)
+            .collect()
+    }
+}
+
+///the crcs of the data, om and nm files of a copa
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CoPaCrcs {
+    pub files: [FileCrcs; 3],
+}
+
+impl CoPaCrcs {
+    pub const SUFFIXES: [&'static str; 3] = ["", "om", "nm"];
+
+    ///layout: len, count of crcs, crcs for every file
+    pub fn write_bytes(&self, bs: &mut Vec<u8>) {
+        for fc in self.files.iter() {
+            bs.extend_from_slice(fc.len.as_bytes());
+            bs.extend_from_slice(fc.crcs.len().as_bytes());
+            for c in &fc.crcs {
+                bs.extend_from_slice(&c.to_ne_bytes());
+            }
+        }
+    }
+
+    ///read from bs at ofs, return it with the offset after it
+    pub fn read_bytes(bs: &[u8], ofs: usize) -> MetaResult<(CoPaCrcs, usize)> {
+        let mut cc = CoPaCrcs::default();
+        let mut end = ofs;
+        for fc in cc.files.iter_mut() {
+            let (hdr, e) = read_unaligned_vec::<usize>(bs, end, 2)?;
+            let (crcs, e) = read_unaligned_vec::<u32>(bs, e, hdr[1])?;
+            fc.len = hdr[0];
+            fc.crcs = crcs;
+            end = e;
+        }
+        Ok((cc, end))
+    }
+}
+
+///the infos of a copa published by the commit of a write
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CoPaCommit {
+    pub cid: Id,
+    pub siz_in_bytes: usize,
+    ///of the written rows
+    pub stats: CoPaStats,
+    pub crcs: CoPaCrcs,
+}
+
+///read n values of T from bs at ofs, return them with the offset after them
+fn read_unaligned_vec<T: Copy>(
+    bs: &[u8],
+    ofs: usize,
+    n: usize,
+) -> MetaResult<(Vec<T>, usize)> {
+    let siz = size_of::<T>();
+    let end = n
+        .checked_mul(siz)
+        .and_then(|l| l.checked_add(ofs))
+        .filter(|e| *e <= bs.len())
+        .ok_or(MetaError::GetPartInfoError)?;
+    let p = bs.as_ptr();
+    //NOTE the bytes may be not aligned
+    let vs = (0..n)
+        .map(|i| unsafe { ptr::read_unaligned(p.add(ofs + i * siz) as *const T) })
+        .collect();
+    Ok((vs, end))
+}
+
+///the size of a copa before a write into its part
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[repr(C)]
+pub struct CoPaWrite {
+    pub cid: Id,
+    pub siz_in_bytes: usize,
+}
+
+impl AsBytes for CoPaWrite {}
+
+///the bytes of a copa written by a write
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum CoPaBytes {
+    ///the rows of the size in bytes, which are written at the offsets of their
+    ///rows
+    Fixed(usize),
+    ///the bytes appended after the writes before, for the blobs and the
+    ///compressed copas
+    Appended(usize),
+}
+
+///the intent of a write of rows [prid, prid + rows) into a part
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PartWrite {
+    pub prid: usize,
+    pub rows: usize,
+    pub copas: Vec<CoPaWrite>,
+}
+
+impl PartWrite {
+    ///layout: prid, rows, count of copas, then the copas
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bs = Vec::new();
+        bs.extend_from_slice(self.prid.as_bytes());
+        bs.extend_from_slice(self.rows.as_bytes());
+        bs.extend_from_slice(self.copas.len().as_bytes());
+        for cw in &self.copas {
+            bs.extend_from_slice(cw.as_bytes());
+        }
+        bs
+    }
+
+    pub fn from_bytes(bs: &[u8]) -> MetaResult<PartWrite> {
+        let (hdr, ofs) = read_unaligned_vec::<usize>(bs, 0, 3)?;
+        let (copas, _) = read_unaligned_vec::<CoPaWrite>(bs, ofs, hdr[2])?;
+        Ok(PartWrite {
+            prid: hdr[0],
+            rows: hdr[1],
+            copas,
+        })
+    }
+}
+
+///A mark of sparse index, one per `index_granularity` rows of a part
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[repr(C)]
+pub struct SparseMark {
+    pub min: u64,
+    pub max: u64,
+    ///num of rows which has been written into this granule, 0 for empty mark
+    pub rows: u64,
+}
+
+const SIZ_SPARSE_MARK: usize = std::mem::size_of::<SparseMark>();
+
+///only the keys which can be losslessly ordered as u64 are indexed now
+#[inline(always)]
+pub fn is_sparse_indexable(typ: BqlType) -> bool {
+    matches!(
+        typ,
+        BqlType::UInt(8 | 16 | 32 | 64)
+            | BqlType::Date
+            | BqlType::DateTime
+            | BqlType::DateTimeTz(_)
+    )
+}
+
+///the types of which min/max are kept in the copa stats
+#[inline(always)]
+pub fn has_stats_minmax(typ: BqlType) -> bool {
+    is_sparse_indexable(typ) || matches!(typ, BqlType::Int(8 | 16 | 32 | 64))
+}
+
+///the signed value with the sign bit flipped, which keeps the order as u64
+#[inline(always)]
+pub fn stats_key_of_signed(v: i64) -> u64 {
+    (v as u64) ^ (1 << 63)
+}
+
+///map the key ranges of the WHERE clause on a column of typ into the ranges
+///on its stats
+///
+///NOTE the key ranges starting from 0 may be unbounded below, and they cover
+///     the negative values of the signed integers
+pub fn stats_key_ranges(
+    typ: BqlType,
+    key_ranges: Vec<RangeInclusive<u64>>,
+) -> Vec<RangeInclusive<u64>> {
+    match typ {
+        BqlType::Int(_) => key_ranges
+            .into_iter()
+            .map(|r| {
+                let start = match *r.start() {
+                    0 => 0,
+                    s => stats_key_of_signed(s.min(i64::MAX as u64) as i64),
+                };
+                let end = stats_key_of_signed((*r.end()).min(i64::MAX as u64) as i64);
+                start..=end
+            })
+            .collect(),
+        _ => key_ranges,
+    }
+}
+
+///merge keys of rows in [prid, prid + keys.len()) into the marks of pi file
+///
+///WARN the caller should hold the table lock
+pub fn write_sparse_index(
+    pipath: &Vec<u8>,
+    prid: usize,
+    keys: &[u64],
+    granularity: usize,
+) -> MetaResult<()> {
+    if keys.len() == 0 {
+        return Ok(());
+    }
+    let g0 = prid / granularity;
+    let g1 = (prid + keys.len() - 1) / granularity;
+    let mut marks = vec![SparseMark::default(); g1 - g0 + 1];
+    let fd = open_file_as_fd(pipath)?;
+    unsafe {
+        let len_in_bytes = marks.len() * SIZ_SPARSE_MARK;
+        //holes or the end of file are read as the empty marks
+        libc::pread(
+            fd as i32,
+            marks.as_mut_ptr() as *mut libc::c_void,
+            len_in_bytes,
+            (g0 * SIZ_SPARSE_MARK) as i64,
+        );
+    }
+    for (i, k) in keys.iter().enumerate() {
+        let m = &mut marks[(prid + i) / granularity - g0];
+        if m.rows == 0 {
+            m.min = *k;
+            m.max = *k;
+        } else {
+            m.min = m.min.min(*k);
+            m.max = m.max.max(*k);
+        }
+        m.rows += 1;
+    }
+    let res = unsafe {
+        let len_in_bytes = marks.len() * SIZ_SPARSE_MARK;
+        let n = libc::pwrite(
+            fd as i32,
+            marks.as_ptr() as *const libc::c_void,
+            len_in_bytes,
+            (g0 * SIZ_SPARSE_MARK) as i64,
+        );
+        close(fd as i32);
+        n == len_in_bytes as isize
+    };
+    if res {
+        Ok(())
+    } else {
+        Err(MetaError::WrappingIOError(Error::last_os_error()))
+    }
+}
+
+///read marks which cover the first `size` rows of a part
+pub fn read_sparse_index(
+    pipath: &Vec<u8>,
+    size: usize,
+    granularity: usize,
+) -> MetaResult<Vec<SparseMark>> {
+    let nmarks = (size + granularity - 1) / granularity;
+    let mut marks = vec![SparseMark::default(); nmarks];
+    let fd = open_file_as_fd(pipath)?;
+    unsafe {
+        libc::pread(
+            fd as i32,
+            marks.as_mut_ptr() as *mut libc::c_void,
+            nmarks * SIZ_SPARSE_MARK,
+            0,
+        );
+        close(fd as i32);
+    }
+    Ok(marks)
+}
+
+///return the (offset, len) row ranges of a part which may match the key ranges
+///
+///NOTE empty marks(maybe written by an in-flight insert) are always kept
+pub fn prune_granules(
+    marks: &[SparseMark],
+    key_ranges: &[RangeInclusive<u64>],
+    granularity: usize,
+    size: usize,
+) -> Vec<(usize, usize)> {
+    let mut rngs: Vec<(usize, usize)> = Vec::new();
+    for (g, m) in marks.iter().enumerate() {
+        let hit = m.rows == 0
+            || key_ranges
+                .iter()
+                .any(|r| *r.start() <= m.max && m.min <= *r.end());
+        if !hit {
+            continue;
+        }
+        let ofs = g * granularity;
+        let len = granularity.min(size - ofs);
+        match rngs.last_mut() {
+            Some(r) if r.0 + r.1 == ofs => r.1 += len,
+            _ => rngs.push((ofs, len)),
+        }
+    }
+    rngs
+}
+
+const TREE_PARTS: u8 = 1;
+const TREE_PRIDS: u8 = 2;
+const TREE_PART_SIZE: u8 = 3;
+const TREE_BASES: u8 = 4;
+const TREE_DELS: u8 = 5;
+const TREE_STATS: u8 = 6;
+const TREE_WRITES: u8 = 7;
+const TREE_CRCS: u8 = 8;
+const TREE_LOCS: u8 = 9;
+const TREE_RUNS: u8 = 10;
+const TREE_GENS: u8 = 11;
+
+///the value of an entry in a tree of the part catalog, which is kept in the
+///part log by its bytes
+trait CatalogValue: Sized {
+    fn to_val(&self) -> Vec<u8>;
+    fn from_val(bs: &[u8]) -> MetaResult<Self>;
+}
+
+impl CatalogValue for usize {
+    fn to_val(&self) -> Vec<u8> {
+        self.as_bytes().to_vec()
+    }
+
+    fn from_val(bs: &[u8]) -> MetaResult<Self> {
+        Ok(read_unaligned_vec::<usize>(bs, 0, 1)?.0[0])
+    }
+}
+
+impl CatalogValue for CoPaBase {
+    fn to_val(&self) -> Vec<u8> {
+        self.as_bytes().to_vec()
+    }
+
+    fn from_val(bs: &[u8]) -> MetaResult<Self> {
+        Ok(read_unaligned_vec::<CoPaBase>(bs, 0, 1)?.0[0])
+    }
+}
+
+impl CatalogValue for CoPaStats {
+    fn to_val(&self) -> Vec<u8> {
+        self.as_bytes().to_vec()
+    }
+
+    fn from_val(bs: &[u8]) -> MetaResult<Self> {
+        Ok(read_unaligned_vec::<CoPaStats>(bs, 0, 1)?.0[0])
+    }
+}
+
+impl CatalogValue for Vec<u8> {
+    fn to_val(&self) -> Vec<u8> {
+        self.clone()
+    }
+
+    fn from_val(bs: &[u8]) -> MetaResult<Self> {
+        Ok(bs.to_vec())
+    }
+}
+
+impl CatalogValue for PartWrite {
+    fn to_val(&self) -> Vec<u8> {
+        self.to_bytes()
+    }
+
+    fn from_val(bs: &[u8]) -> MetaResult<Self> {
+        PartWrite::from_bytes(bs)
+    }
+}
+
+impl CatalogValue for CoPaCrcs {
+    fn to_val(&self) -> Vec<u8> {
+        let mut bs = Vec::new();
+        self.write_bytes(&mut bs);
+        bs
+    }
+
+    fn from_val(bs: &[u8]) -> MetaResult<Self> {
+        Ok(CoPaCrcs::read_bytes(bs, 0)?.0)
+    }
+}
+
+impl CatalogValue for String {
+    fn to_val(&self) -> Vec<u8> {
+        self.as_bytes().to_vec()
+    }
+
+    fn from_val(bs: &[u8]) -> MetaResult<Self> {
+        String::from_utf8(bs.to_vec()).map_err(|_| MetaError::GetPartInfoError)
+    }
+}
+
+#[inline]
+fn put<V: CatalogValue>(tree: u8, key: (Id, u64), v: &V) -> Record {
+    Record {
+        tree,
+        key,
+        val: Some(v.to_val()),
+    }
+}
+
+#[inline]
+fn del(tree: u8, key: (Id, u64)) -> Record {
+    Record {
+        tree,
+        key,
+        val: None,
+    }
+}
+
+///the records to remove all infos of the part, including the copas of cids
+fn clear_part_records(tid: Id, ptk: u64, cids: &[Id]) -> Vec<Record> {
+    let k = (tid, ptk);
+    let mut recs = vec![
+        del(TREE_PRIDS, k),
+        del(TREE_PART_SIZE, k),
+        del(TREE_DELS, k),
+        del(TREE_RUNS, k),
+    ];
+    for cid in cids {
+        let k = (*cid, ptk);
+        for tree in [TREE_PARTS, TREE_BASES, TREE_STATS, TREE_CRCS].iter() {
+            recs.push(del(*tree, k));
+        }
+    }
+    recs
+}
+
+///the records to register the part by its meta, the committed rows are set
+///at last to make the part visible
+fn part_meta_records(tid: Id, ptk: u64, pm: &PartMeta) -> Vec<Record> {
+    let k = (tid, ptk);
+    let mut recs = Vec::new();
+    for cm in &pm.copas {
+        let kc = (cm.cid, ptk);
+        if cm.siz_in_bytes > 0 {
+            recs.push(put(TREE_PARTS, kc, &cm.siz_in_bytes));
+        }
+        if cm.base != CoPaBase::default() {
+            recs.push(put(TREE_BASES, kc, &cm.base));
+        }
+        if cm.stats.rows > 0 {
+            recs.push(put(TREE_STATS, kc, &cm.stats));
+        }
+    }
+    for (cm, cc) in pm.copas.iter().zip(&pm.crcs) {
+        recs.push(put(TREE_CRCS, (cm.cid, ptk), cc));
+    }
+    if !pm.dels.is_empty() {
+        recs.push(put(TREE_DELS, k, &pm.dels));
+    }
+    recs.push(put(TREE_PART_SIZE, k, &pm.rows));
+    recs.push(put(TREE_PRIDS, k, &pm.rows));
+    recs
+}
+
+///the entries of id(tid or cid) with ptk in r
+#[inline]
+fn range_of<V>(
+    m: &BTreeMap<(Id, u64), V>,
+    id: Id,
+    r: RangeInclusive<u64>,
+) -> btree_map::Range<'_, (Id, u64), V> {
+    if r.start() <= r.end() {
+        m.range((id, *r.start())..=(id, *r.end()))
+    } else {
+        m.range((id, 0)..(id, 0))
+    }
+}
+
+#[inline]
+fn apply_to<V: CatalogValue>(
+    m: &mut BTreeMap<(Id, u64), V>,
+    key: (Id, u64),
+    val: Option<Vec<u8>>,
+) -> MetaResult<()> {
+    match val {
+        Some(bs) => {
+            m.insert(key, V::from_val(&bs)?);
+        }
+        None => {
+            m.remove(&key);
+        }
+    }
+    Ok(())
+}
+
+#[inline]
+fn dump<V: CatalogValue>(
+    tree: u8,
+    m: &BTreeMap<(Id, u64), V>,
+) -> impl Iterator<Item = Record> + '_ {
+    m.iter().map(move |(k, v)| put(tree, *k, v))
+}
+
+///the trees of the part catalog, all keyed by (tid, ptk) or (cid, ptk)
+#[derive(Default)]
+struct Catalog {
+    ///(cid, ptk) -> siz_in_bytes of copa
+    parts: BTreeMap<(Id, u64), usize>,
+    ///(tid, ptk) -> committed rows of part
+    prids: BTreeMap<(Id, u64), usize>,
+    ///(tid, ptk) -> reserved rows of part
+    part_size: BTreeMap<(Id, u64), usize>,
+    bases: BTreeMap<(Id, u64), CoPaBase>,
+    dels: BTreeMap<(Id, u64), Vec<u8>>,
+    stats: BTreeMap<(Id, u64), CoPaStats>,
+    writes: BTreeMap<(Id, u64), PartWrite>,
+    crcs: BTreeMap<(Id, u64), CoPaCrcs>,
+    ///(tid, ptk) -> data dir of part
+    locs: BTreeMap<(Id, u64), String>,
+    ///(tid, ptk) -> num of writes committed since part was created or merged
+    runs: BTreeMap<(Id, u64), usize>,
+    ///(tid, ptk) -> generation of the files of part, 0 if never merged
+    gens: BTreeMap<(Id, u64), usize>,
+}
+
+impl Catalog {
+    fn apply(&mut self, rec: Record) -> MetaResult<()> {
+        let Record { tree, key, val } = rec;
+        match tree {
+            TREE_PARTS => apply_to(&mut self.parts, key, val),
+            TREE_PRIDS => apply_to(&mut self.prids, key, val),
+            TREE_PART_SIZE => apply_to(&mut self.part_size, key, val),
+            TREE_BASES => apply_to(&mut self.bases, key, val),
+            TREE_DELS => apply_to(&mut self.dels, key, val),
+            TREE_STATS => apply_to(&mut self.stats, key, val),
+            TREE_WRITES => apply_to(&mut self.writes, key, val),
+            TREE_CRCS => apply_to(&mut self.crcs, key, val),
+            TREE_LOCS => apply_to(&mut self.locs, key, val),
+            TREE_RUNS => apply_to(&mut self.runs, key, val),
+            TREE_GENS => apply_to(&mut self.gens, key, val),
+            _ => Err(MetaError::CorruptedPartLogError(format!(
+                "unknown tree {}",
+                tree
+            ))),
+        }
+    }
+
+    ///the records to rebuild the whole catalog
+    fn records(&self) -> impl Iterator<Item = Record> + '_ {
+        dump(TREE_PARTS, &self.parts)
+            .chain(dump(TREE_PRIDS, &self.prids))
+            .chain(dump(TREE_PART_SIZE, &self.part_size))
+            .chain(dump(TREE_BASES, &self.bases))
+            .chain(dump(TREE_DELS, &self.dels))
+            .chain(dump(TREE_STATS, &self.stats))
+            .chain(dump(TREE_WRITES, &self.writes))
+            .chain(dump(TREE_CRCS, &self.crcs))
+            .chain(dump(TREE_LOCS, &self.locs))
+            .chain(dump(TREE_RUNS, &self.runs))
+            .chain(dump(TREE_GENS, &self.gens))
+    }
+
+    ///the records to remove the copas of the column
+    fn clear_column(&self, cid: Id, recs: &mut Vec<Record>) {
+        let all = 0..=u64::MAX;
+        recs.extend(
+            range_of(&self.parts, cid, all.clone()).map(|(k, _)| del(TREE_PARTS, *k)),
+        );
+        recs.extend(
+            range_of(&self.bases, cid, all.clone()).map(|(k, _)| del(TREE_BASES, *k)),
+        );
+        recs.extend(
+            range_of(&self.stats, cid, all.clone()).map(|(k, _)| del(TREE_STATS, *k)),
+        );
+        recs.extend(range_of(&self.crcs, cid, all).map(|(k, _)| del(TREE_CRCS, *k)));
+    }
+}
+
+///the trees of the sled based part store before the part log, with the
+///values in the same layouts of the catalog
+const SLED_TREES: [(&str, u8); 9] = [
+    ("pt", TREE_PARTS),
+    ("pr", TREE_PRIDS),
+    ("ps", TREE_PART_SIZE),
+    ("pb", TREE_BASES),
+    ("pd", TREE_DELS),
+    ("pm", TREE_STATS),
+    ("pw", TREE_WRITES),
+    ("pc", TREE_CRCS),
+    ("pl", TREE_LOCS),
+];
+
+fn import_sled_part_store(p0: &Path) -> MetaResult<Vec<Record>> {
+    let mdb = sled::Config::default()
+        .path(p0)
+        .open()
+        .map_err(|_| MetaError::OpenError)?;
+    let mut rt = Vec::new();
+    for (name, tree) in SLED_TREES.iter() {
+        let t = mdb.open_tree(name).map_err(|_| MetaError::OpenError)?;
+        for kv in t.iter() {
+            let (kbs, v) = kv.map_err(|_| MetaError::OpenError)?;
+            let (k0, k1) = *(&*kbs).into_ref::<(u64, u64)>();
+            rt.push(Record {
+                tree: *tree,
+                key: (k0.to_be(), k1.to_be()),
+                val: Some(v.to_vec()),
+            });
+        }
+    }
+    Ok(rt)
+}
+
+///the lock of a table, held exclusively by the changes of the parts and shared
+///by the writes
+#[derive(Debug, Default)]
+struct TableLock {
+    exclusive: bool,
+    shared: usize,
+    ///the exclusive ones waiting, which go before the shared ones
+    waiting: usize,
+}
+
+///the writes in flight into a part, in the order of their reservations
+#[derive(Debug, Default)]
+struct PartWrites {
+    pws: VecDeque<PartWrite>,
+    ///cid -> the size in bytes of the copa after the writes in flight
+    tails: HashMap<Id, usize>,
+    ///the first write failed, from which all writes in flight are rolled back
+    failed: Option<PartWrite>,
 }
 
 pub struct PartStore<'a> {
     data_dirs: &'a Vec<String>,
-    #[allow(dead_code)]
-    mdb: sled::Db,
-    tree_parts: sled::Tree,
-    tree_prids: sled::Tree,
-    tree_part_size: sled::Tree,
-    tree_locks: sled::Tree,
+    volumes: &'a [Volume],
+    cat: RwLock<Catalog>,
+    ///NOTE locked after the catalog
+    plog: Mutex<PartLog>,
+    auto_flush: bool,
+    ///the locks of the tables locked
+    locks: Mutex<HashMap<Id, TableLock>>,
+    cv_locks: Condvar,
+    ///the writes in flight of the parts
+    writes: Mutex<HashMap<(Id, u64), PartWrites>>,
+    cv_writes: Condvar,
+    ///mappings of the copas of the queries
+    mmaps: MmapCache,
+    ///held by the queries from taking the infos of the parts to mapping
+    ///their files
+    mapping: RwLock<()>,
+    ///`get_available_space` by default, replaced by the tests to place the
+    ///parts deterministically
+    space_fn: RwLock<SpaceFn>,
 }
 
 impl<'a> PartStore<'a> {
     pub fn new<T: AsRef<str>>(
         meta_dirs: &[T],
         data_dirs: &'a Vec<String>,
+    ) -> MetaResult<Self> {
+        PartStore::new_with_auto_flush(meta_dirs, data_dirs, true)
+    }
+
+    ///the part store without auto flush is only persisted by `flush`, so that
+    ///the data files can be synced before the infos of them are persisted
+    pub fn new_with_auto_flush<T: AsRef<str>>(
+        meta_dirs: &[T],
+        data_dirs: &'a Vec<String>,
+        auto_flush: bool,
+    ) -> MetaResult<Self> {
+        PartStore::new_with_volumes(meta_dirs, data_dirs, &[], auto_flush)
+    }
+
+    ///the data_dirs are of the default volume, and the volumes are the tiers
+    ///after it
+    pub fn new_with_volumes<T: AsRef<str>>(
+        meta_dirs: &[T],
+        data_dirs: &'a Vec<String>,
+        volumes: &'a [Volume],
+        auto_flush: bool,
     ) -> MetaResult<Self> {
         assert!(!meta_dirs.is_empty());
         assert!(!data_dirs.is_empty());
 
-        let p0 = [meta_dirs[0].as_ref(), "p0"].join("/");
-        let mdb = sled::Config::default()
-            .path(p0)
-            .cache_capacity(128 * 1024 * 1024) //FIXME configurable
-            .open()
-            .map_err(|_| MetaError::OpenError)?;
-        let tree_part_size = mdb.open_tree(b"ps").map_err(|_| MetaError::OpenError)?;
-        let tree_parts = mdb.open_tree(b"pt").map_err(|_| MetaError::OpenError)?;
-        let tree_prids = mdb.open_tree(b"pr").map_err(|_| MetaError::OpenError)?;
-        let tree_locks = mdb.open_tree(b"l").map_err(|_e| MetaError::OpenError)?;
-        tree_locks.clear().map_err(|_e| MetaError::OpenError)?;
+        let md = Path::new(meta_dirs[0].as_ref());
+        let (mut plog, recs) = PartLog::open(&md.join("p1"), auto_flush)?;
+        let mut cat = Catalog::default();
+        let fresh = recs.is_empty() && !plog.has_checkpoint();
+        for r in recs {
+            cat.apply(r)?;
+        }
+        //NOTE the store is imported once, the checkpoint is kept even empty
+        let p0 = md.join("p0");
+        if fresh && p0.exists() {
+            for r in import_sled_part_store(&p0)? {
+                cat.apply(r)?;
+            }
+            plog.checkpoint(cat.records())?;
+            log::info!("imported the part store in {}", p0.display());
+        }
 
         Ok(PartStore {
-            mdb,
             data_dirs,
-            tree_parts,
-            tree_prids,
-            tree_part_size,
-            tree_locks,
+            volumes,
+            cat: RwLock::new(cat),
+            plog: Mutex::new(plog),
+            auto_flush,
+            locks: Mutex::new(HashMap::new()),
+            cv_locks: Condvar::new(),
+            writes: Mutex::new(HashMap::new()),
+            cv_writes: Condvar::new(),
+            mmaps: MmapCache::new(DEFAULT_MMAP_CACHE_CAPACITY),
+            mapping: RwLock::new(()),
+            space_fn: RwLock::new(Box::new(get_available_space)),
         })
     }
 
-    //NOTE a table lock is implemented by acquire_lock, release_lock, is_locked
+    #[inline(always)]
+    fn read(&self) -> RwLockReadGuard<'_, Catalog> {
+        self.cat.read().unwrap()
+    }
+
+    #[inline(always)]
+    fn write(&self) -> RwLockWriteGuard<'_, Catalog> {
+        self.cat.write().unwrap()
+    }
+
+    ///log the records as one frame then apply them to the catalog, so that
+    ///they are persisted atomically
+    fn commit(&self, cat: &mut Catalog, recs: Vec<Record>) -> MetaResult<()> {
+        if recs.is_empty() {
+            return Ok(());
+        }
+        let mut plog = self.plog.lock().unwrap();
+        plog.append(&recs)?;
+        for r in recs {
+            //NOTE the copa is grown, rewritten or dropped
+            if r.tree == TREE_PARTS {
+                self.mmaps.remove_copa(r.key.0, r.key.1);
+            }
+            cat.apply(r)?;
+        }
+        if self.auto_flush && plog.should_checkpoint() {
+            plog.checkpoint(cat.records())?;
+        }
+        Ok(())
+    }
+
+    //NOTE a table lock is implemented by acquire_lock, release_lock, and is
+    //     shared by the writes by acquire_shared_lock, release_shared_lock
     pub fn acquire_lock(&self, tid: Id) -> MetaResult<()> {
-        let kbs = tid.to_be_bytes();
+        let mut locks = self.locks.lock().map_err(|_| MetaError::FailToLockTable)?;
+        locks.entry(tid).or_default().waiting += 1;
         loop {
-            if self.is_locked(tid)? {
-                park_timeout(Duration::from_micros(1)); //FIXME
+            let l = locks.entry(tid).or_default();
+            if !l.exclusive && l.shared == 0 {
+                l.waiting -= 1;
+                l.exclusive = true;
+                return Ok(());
             }
-            //try to lock
-            let res_cas = self
-                .tree_locks
-                .compare_and_swap(kbs, Some(&[0]), Some(&[1]))
+            locks = self
+                .cv_locks
+                .wait(locks)
                 .map_err(|_| MetaError::FailToLockTable)?;
-            match res_cas {
-                Ok(_) => break,
-                Err(_) => continue,
+        }
+    }
+
+    pub fn release_lock(&self, tid: Id) -> MetaResult<()> {
+        let mut locks = self.locks.lock().map_err(|_| MetaError::FailToLockTable)?;
+        if let Some(l) = locks.get_mut(&tid) {
+            l.exclusive = false;
+            if l.shared == 0 && l.waiting == 0 {
+                locks.remove(&tid);
             }
         }
+        self.cv_locks.notify_all();
         Ok(())
     }
 
-    pub fn release_lock(&self, tid: Id) -> MetaResult<()> {
-        self.tree_locks
-            .insert(tid.to_be_bytes(), &[0])
-            .map_err(|_| MetaError::GetPartInfoError)?;
+    ///lock the table shared with other writes, but exclusive with the holders
+    ///of `acquire_lock`
+    pub fn acquire_shared_lock(&self, tid: Id) -> MetaResult<()> {
+        let mut locks = self.locks.lock().map_err(|_| MetaError::FailToLockTable)?;
+        loop {
+            let l = locks.entry(tid).or_default();
+            if !l.exclusive && l.waiting == 0 {
+                l.shared += 1;
+                return Ok(());
+            }
+            locks = self
+                .cv_locks
+                .wait(locks)
+                .map_err(|_| MetaError::FailToLockTable)?;
+        }
+    }
+
+    pub fn release_shared_lock(&self, tid: Id) -> MetaResult<()> {
+        let mut locks = self.locks.lock().map_err(|_| MetaError::FailToLockTable)?;
+        if let Some(l) = locks.get_mut(&tid) {
+            l.shared = l.shared.saturating_sub(1);
+            if !l.exclusive && l.shared == 0 && l.waiting == 0 {
+                locks.remove(&tid);
+            }
+        }
+        self.cv_locks.notify_all();
+        Ok(())
+    }
+
+    ///reserve reserved_len rows in the part, return the first of them
+    #[inline]
+    pub fn get_prid_int_ptk(
+        &self,
+        tid: Id,
+        ptk: u64,
+        reserved_len: usize,
+    ) -> MetaResult<usize> {
+        let k = (tid, ptk);
+        let mut cat = self.write();
+        let prid = cat.part_size.get(&k).copied().unwrap_or_default();
+        self.commit(
+            &mut cat,
+            vec![put(TREE_PART_SIZE, k, &(prid + reserved_len))],
+        )?;
+        Ok(prid)
+    }
+
+    #[inline]
+    pub fn set_copa_size_int_ptk(
+        &self,
+        tid: Id,
+        ptk: u64,
+        part_size: usize,
+    ) -> MetaResult<()> {
+        let mut cat = self.write();
+        self.commit(&mut cat, vec![put(TREE_PRIDS, (tid, ptk), &part_size)])
+    }
+
+    //FIXME possibly add more metadata
+    #[inline]
+    pub fn insert_copa_int_ptk(
+        &self,
+        cid: Id,
+        ptk: u64,
+        siz_in_bytes: usize,
+    ) -> MetaResult<()> {
+        let mut cat = self.write();
+        self.commit(&mut cat, vec![put(TREE_PARTS, (cid, ptk), &siz_in_bytes)])
+    }
+
+    #[inline]
+    pub fn get_copa_siz_in_bytes_int_ptk(
+        &self,
+        cid: Id,
+        ptk: u64,
+    ) -> MetaResult<Option<usize>> {
+        Ok(self.read().parts.get(&(cid, ptk)).copied())
+    }
+
+    ///set the bases of the copas of cid for all existed parts of the table,
+    ///the rows which have been reserved for the writings become the base rows
+    ///
+    ///WARN the caller should hold the table lock
+    pub fn init_copa_bases(
+        &self,
+        tid: Id,
+        cid: Id,
+        src_cid: Id,
+        src_type: BqlType,
+    ) -> MetaResult<()> {
+        let mut cat = self.write();
+        let recs = range_of(&cat.part_size, tid, 0..=u64::MAX)
+            .filter(|(_, rows)| **rows > 0)
+            .map(|((_, ptk), rows)| {
+                let base = CoPaBase {
+                    rows: *rows,
+                    src_cid,
+                    src_type,
+                };
+                put(TREE_BASES, (cid, *ptk), &base)
+            })
+            .collect();
+        self.commit(&mut cat, recs)
+    }
+
+    #[inline]
+    pub fn set_copa_base(&self, cid: Id, ptk: u64, base: &CoPaBase) -> MetaResult<()> {
+        let mut cat = self.write();
+        self.commit(&mut cat, vec![put(TREE_BASES, (cid, ptk), base)])
+    }
+
+    #[inline]
+    pub fn get_copa_base(&self, cid: Id, ptk: u64) -> MetaResult<Option<CoPaBase>> {
+        Ok(self.read().bases.get(&(cid, ptk)).copied())
+    }
+
+    #[inline]
+    pub fn remove_copa_base(&self, cid: Id, ptk: u64) -> MetaResult<()> {
+        let mut cat = self.write();
+        self.commit(&mut cat, vec![del(TREE_BASES, (cid, ptk))])
+    }
+
+    ///return (ptk, base) of all copas of cid which have the base
+    pub fn get_copa_bases(&self, cid: Id) -> MetaResult<Vec<(u64, CoPaBase)>> {
+        let cat = self.read();
+        Ok(range_of(&cat.bases, cid, 0..=u64::MAX)
+            .map(|((_, ptk), base)| (*ptk, *base))
+            .collect())
+    }
+
+    ///return (cid, src_cid) of the columns which have copas converted from
+    ///another column but not rewritten
+    pub fn get_converting_cids(&self) -> MetaResult<Vec<(Id, Id)>> {
+        let mut rt: Vec<(Id, Id)> = Vec::new();
+        for ((cid, _), base) in self.read().bases.iter() {
+            let p = (*cid, base.src_cid);
+            if base.src_cid != 0 && !rt.contains(&p) {
+                rt.push(p);
+            }
+        }
+        Ok(rt)
+    }
+
+    ///return (ptk, committed rows) of all parts of the table
+    pub fn get_part_sizes(&self, tid: Id) -> MetaResult<Vec<(u64, usize)>> {
+        let cat = self.read();
+        Ok(range_of(&cat.prids, tid, 0..=u64::MAX)
+            .map(|((_, ptk), rows)| (*ptk, *rows))
+            .collect())
+    }
+
+    ///return the rows which have been reserved for the writings into the part
+    #[inline]
+    pub fn get_part_size_reserved(&self, tid: Id, ptk: u64) -> MetaResult<usize> {
+        Ok(self
+            .read()
+            .part_size
+            .get(&(tid, ptk))
+            .copied()
+            .unwrap_or_default())
+    }
+
+    #[inline]
+    pub fn get_deletion_bitmap(&self, tid: Id, ptk: u64) -> MetaResult<Option<Vec<u8>>> {
+        Ok(self.read().dels.get(&(tid, ptk)).cloned())
+    }
+
+    ///return the deletion bitmaps of all parts of the table keyed by ptk
+    pub fn get_deletion_bitmaps(&self, tid: Id) -> MetaResult<HashMap<u64, Vec<u8>>> {
+        let cat = self.read();
+        Ok(range_of(&cat.dels, tid, 0..=u64::MAX)
+            .map(|((_, ptk), bm)| (*ptk, bm.clone()))
+            .collect())
+    }
+
+    ///mark the rows set in bm as deleted in the deletion bitmap of the part
+    pub fn merge_deletion_bitmap(&self, tid: Id, ptk: u64, bm: &[u8]) -> MetaResult<()> {
+        let k = (tid, ptk);
+        let mut cat = self.write();
+        let mut new = cat.dels.get(&k).cloned().unwrap_or_default();
+        if new.len() < bm.len() {
+            new.resize(bm.len(), 0);
+        }
+        for (b, m) in new.iter_mut().zip(bm) {
+            *b |= *m;
+        }
+        self.commit(&mut cat, vec![put(TREE_DELS, k, &new)])
+    }
+
+    ///mark the rows set in bm as deleted like `merge_deletion_bitmap`, only if
+    ///the part is still of the generation gen and has not less than the rows
+    ///which bm was built from, return whether the rows have been marked
+    ///
+    ///NOTE a part rewritten or cleared since it was scanned keeps none of the
+    ///     row positions in bm, so its stale bm is rejected here
+    pub fn merge_deletion_bitmap_of(
+        &self,
+        tid: Id,
+        ptk: u64,
+        gen: usize,
+        rows: usize,
+        bm: &[u8],
+    ) -> MetaResult<bool> {
+        let k = (tid, ptk);
+        let mut cat = self.write();
+        let cur_gen = cat.gens.get(&k).copied().unwrap_or_default();
+        let cur_rows = cat.part_size.get(&k).copied().unwrap_or_default();
+        if cur_gen != gen || cur_rows < rows || bm.len() > (rows + 7) / 8 {
+            return Ok(false);
+        }
+        let mut new = cat.dels.get(&k).cloned().unwrap_or_default();
+        if new.len() < bm.len() {
+            new.resize(bm.len(), 0);
+        }
+        for (b, m) in new.iter_mut().zip(bm) {
+            *b |= *m;
+        }
+        self.commit(&mut cat, vec![put(TREE_DELS, k, &new)])?;
+        Ok(true)
+    }
+
+    ///merge the stats of the rows just inserted into the copa
+    pub fn merge_copa_stats(&self, cid: Id, ptk: u64, st: &CoPaStats) -> MetaResult<()> {
+        let k = (cid, ptk);
+        let mut cat = self.write();
+        let mut new = cat.stats.get(&k).copied().unwrap_or_default();
+        new.merge(st);
+        self.commit(&mut cat, vec![put(TREE_STATS, k, &new)])
+    }
+
+    #[inline]
+    pub fn get_copa_stats(&self, cid: Id, ptk: u64) -> MetaResult<Option<CoPaStats>> {
+        Ok(self.read().stats.get(&(cid, ptk)).copied())
+    }
+
+    ///return the ptk ranges of the parts in ptk_range which can not be skipped
+    ///by the stats of the columns against their key ranges
+    pub fn prune_parts_by_stats(
+        &self,
+        tid: Id,
+        ptk_range: &Vec<RangeInclusive<u64>>,
+        col_ranges: &[(Id, Vec<RangeInclusive<u64>>)],
+    ) -> MetaResult<Vec<RangeInclusive<u64>>> {
+        let cat = self.read();
+        let mut rngs: Vec<RangeInclusive<u64>> = Vec::new();
+        for r in ptk_range {
+            //the parts kept in a row are coalesced into one range
+            let mut last_kept = false;
+            for ((_, ptk), size) in range_of(&cat.prids, tid, r.clone()) {
+                let ptk = *ptk;
+                let kept = col_ranges.iter().all(|(cid, key_ranges)| {
+                    match cat.stats.get(&(*cid, ptk)) {
+                        Some(st) if st.rows as usize >= *size => st.may_match(key_ranges),
+                        _ => true,
+                    }
+                });
+                if kept {
+                    match rngs.last_mut() {
+                        Some(lr) if last_kept => *lr = *lr.start()..=ptk,
+                        _ => rngs.push(ptk..=ptk),
+                    }
+                }
+                last_kept = kept;
+            }
+        }
+        Ok(rngs)
+    }
+
+    ///remove all infos of the part, including the copas of cids in it
+    ///
+    ///WARN the caller should hold the table lock
+    pub fn clear_part(&self, tid: Id, ptk: u64, cids: &[Id]) -> MetaResult<()> {
+        let recs = clear_part_records(tid, ptk, cids);
+        let mut cat = self.write();
+        self.commit(&mut cat, recs)
+    }
+
+    ///place the part, reserve the rows and the bytes of the copas for a write
+    ///into it after the writes in flight, and record the intent of the write
+    ///if it is the first in flight, return the data dir of the part and the
+    ///write
+    ///
+    ///NOTE the files of the write are written out of any lock, then the write
+    ///     is ended by `commit_part_write` or `abort_part_write` after
+    ///     `wait_for_part_write`
+    ///
+    ///WARN the caller should hold the table lock, shared or not
+    pub fn reserve_part_write(
+        &self,
+        tid: Id,
+        ptk: u64,
+        rows: usize,
+        copas: &[(Id, CoPaBytes)],
+    ) -> MetaResult<(&'a String, PartWrite)> {
+        let k = (tid, ptk);
+        let mut writes = self.writes.lock().unwrap();
+        //the writes after a failed one are rolled back before new ones
+        while writes.get(&k).map_or(false, |w| w.failed.is_some()) {
+            writes = self.cv_writes.wait(writes).unwrap();
+        }
+        let dp = self.place_part(tid, ptk)?;
+        let w = writes.entry(k).or_default();
+        let mut cat = self.write();
+        let prid = cat.part_size.get(&k).copied().unwrap_or_default();
+        let mut pw = PartWrite {
+            prid,
+            rows,
+            copas: Vec::with_capacity(copas.len()),
+        };
+        let mut tails = Vec::with_capacity(copas.len());
+        for (cid, bytes) in copas {
+            let siz_in_bytes = match w.tails.get(cid) {
+                Some(siz) => *siz,
+                None => cat.parts.get(&(*cid, ptk)).copied().unwrap_or_default(),
+            };
+            let tail = match bytes {
+                CoPaBytes::Fixed(siz) => (prid + rows) * siz,
+                CoPaBytes::Appended(len) => siz_in_bytes + len,
+            };
+            pw.copas.push(CoPaWrite {
+                cid: *cid,
+                siz_in_bytes,
+            });
+            tails.push((*cid, tail));
+        }
+        let mut recs = vec![put(TREE_PART_SIZE, k, &(prid + rows))];
+        if w.pws.is_empty() {
+            recs.push(put(TREE_WRITES, k, &pw));
+        }
+        self.commit(&mut cat, recs)?;
+        w.tails.extend(tails);
+        w.pws.push_back(pw.clone());
+        Ok((dp, pw))
+    }
+
+    ///wait until the writes reserved before the write have ended, return
+    ///`PartWriteAbortedError` if one of them failed, then the write should be
+    ///aborted too
+    pub fn wait_for_part_write(
+        &self,
+        tid: Id,
+        ptk: u64,
+        pw: &PartWrite,
+    ) -> MetaResult<()> {
+        let k = (tid, ptk);
+        let mut writes = self.writes.lock().unwrap();
+        loop {
+            let w = writes.get(&k).ok_or(MetaError::CanNotFindPartError)?;
+            match w.pws.iter().position(|f| f.prid == pw.prid) {
+                Some(0) if w.failed.is_some() => {
+                    return Err(MetaError::PartWriteAbortedError);
+                }
+                Some(0) => return Ok(()),
+                Some(_) => {}
+                None => return Err(MetaError::CanNotFindPartError),
+            }
+            writes = self.cv_writes.wait(writes).unwrap();
+        }
+    }
+
+    ///publish the infos of the copas and the committed rows of the write
+    ///atomically, the intent is handed over to the next write in flight
+    ///
+    ///WARN the caller should hold the table lock, shared or not, and have
+    ///waited for the write by `wait_for_part_write`
+    pub fn commit_part_write(
+        &self,
+        tid: Id,
+        ptk: u64,
+        pw: &PartWrite,
+        copas: &[CoPaCommit],
+    ) -> MetaResult<()> {
+        let k = (tid, ptk);
+        let rows = pw.prid + pw.rows;
+        let mut writes = self.writes.lock().unwrap();
+        let mut cat = self.write();
+        let mut recs = Vec::with_capacity(copas.len() * 3 + 3);
+        for cc in copas {
+            let kc = (cc.cid, ptk);
+            let mut st = cat.stats.get(&kc).copied().unwrap_or_default();
+            st.merge(&cc.stats);
+            recs.push(put(TREE_PARTS, kc, &cc.siz_in_bytes));
+            recs.push(put(TREE_STATS, kc, &st));
+            recs.push(put(TREE_CRCS, kc, &cc.crcs));
+        }
+        let runs = cat.runs.get(&k).copied().unwrap_or_default();
+        recs.push(put(TREE_PRIDS, k, &rows));
+        recs.push(put(TREE_RUNS, k, &(runs + 1)));
+        let next = writes
+            .get(&k)
+            .and_then(|w| w.pws.iter().find(|f| f.prid > pw.prid));
+        match next {
+            Some(npw) => recs.push(put(TREE_WRITES, k, npw)),
+            None => recs.push(del(TREE_WRITES, k)),
+        }
+        self.commit(&mut cat, recs)?;
+        drop(cat);
+        if let Some(w) = writes.get_mut(&k) {
+            w.pws.retain(|f| f.prid != pw.prid);
+            if w.pws.is_empty() && w.failed.is_none() {
+                writes.remove(&k);
+            }
+        }
+        self.cv_writes.notify_all();
+        Ok(())
+    }
+
+    ///end the write which failed or is after a failed one, return the first
+    ///write failed if no write is in flight after this one, then the files of
+    ///the copas should be truncated to it and it should be rolled back by
+    ///`rollback_part_write`
+    ///
+    ///WARN the caller should have waited for the write by
+    ///     `wait_for_part_write`, so the writes before it have ended
+    pub fn abort_part_write(
+        &self,
+        tid: Id,
+        ptk: u64,
+        pw: &PartWrite,
+    ) -> MetaResult<Option<PartWrite>> {
+        let k = (tid, ptk);
+        let mut writes = self.writes.lock().unwrap();
+        let w = writes.entry(k).or_default();
+        if w.failed.is_none() {
+            w.failed = Some(pw.clone());
+        }
+        w.pws.retain(|f| f.prid != pw.prid);
+        let rt = if w.pws.is_empty() {
+            w.failed.clone()
+        } else {
+            None
+        };
+        self.cv_writes.notify_all();
+        Ok(rt)
+    }
+
+    ///drop the reservation and the intent of the write with the ones after it,
+    ///the files of the copas should have been truncated by the caller
+    ///
+    ///WARN the caller should hold the table lock, shared or not, and the
+    ///     writes after it should have been aborted
+    pub fn rollback_part_write(
+        &self,
+        tid: Id,
+        ptk: u64,
+        pw: &PartWrite,
+    ) -> MetaResult<()> {
+        let k = (tid, ptk);
+        let mut writes = self.writes.lock().unwrap();
+        let mut cat = self.write();
+        let recs = vec![put(TREE_PART_SIZE, k, &pw.prid), del(TREE_WRITES, k)];
+        let res = self.commit(&mut cat, recs);
+        drop(cat);
+        //NOTE the writes are not blocked by the failed one even if its
+        //     rollback is not persisted, which is left to the recovery
+        writes.remove(&k);
+        self.cv_writes.notify_all();
+        res
+    }
 
-        Ok(())
+    ///return the intents of the writes which have not been committed
+    pub fn get_part_writes(&self) -> MetaResult<Vec<(Id, u64, PartWrite)>> {
+        Ok(self
+            .read()
+            .writes
+            .iter()
+            .map(|((tid, ptk), pw)| (*tid, *ptk, pw.clone()))
+            .collect())
     }
 
     #[inline]
-    fn is_locked(&self, tid: Id) -> MetaResult<bool> {
-        let kbs = tid.to_be_bytes();
-        let res = self
-            .tree_locks
-            .get(kbs)
-            .map_err(|_| MetaError::FailToLockTable)?;
-        match res {
-            Some(iv) => {
-                let v = *(&*iv).into_ref::<u8>();
-                return Ok(v == 1);
-            }
-            None => {
-                let res_cas = self
-                    .tree_locks
-                    .compare_and_swap(kbs, None as Option<&[u8]>, Some(&[0]))
-                    .map_err(|_| MetaError::FailToLockTable)?;
-                match res_cas {
-                    Ok(_) => return Ok(true),
-                    Err(_) => {
-                        if let Some(iv) = self
-                            .tree_locks
-                            .get(kbs)
-                            .map_err(|_| MetaError::ShouldHasLockButNot)?
-                        {
-                            let v = *(&*iv).into_ref::<u8>();
-                            return Ok(v == 1);
-                        } else {
-                            return Err(MetaError::ShouldHasLockButNot);
-                        }
-                    }
-                }
-            }
-        }
+    pub fn get_copa_crcs(&self, cid: Id, ptk: u64) -> MetaResult<Option<CoPaCrcs>> {
+        Ok(self.read().crcs.get(&(cid, ptk)).cloned())
     }
 
-    #[inline]
-    pub fn get_prid_int_ptk(
+    pub fn set_copa_crcs(&self, cid: Id, ptk: u64, cc: &CoPaCrcs) -> MetaResult<()> {
+        let mut cat = self.write();
+        self.commit(&mut cat, vec![put(TREE_CRCS, (cid, ptk), cc)])
+    }
+
+    ///return the meta of the part with its copas of cids, or None if the part
+    ///does not exist
+    pub fn get_part_meta(
         &self,
         tid: Id,
         ptk: u64,
-        reserved_len: usize,
-    ) -> MetaResult<usize> {
-        let k = (tid.to_be(), ptk.to_be());
-        let kbs = k.as_bytes();
-        if let Ok(Some(v)) = self.tree_part_size.fetch_and_update(kbs, |old| {
-            let old_num = match old {
-                Some(bytes) => *bytes.into_ref::<usize>(),
-                None => 0,
-            };
-            Some((old_num + reserved_len).as_bytes().to_vec())
-        }) {
-            Ok(*(&*v).into_ref::<usize>())
-        } else {
-            Ok(0)
+        cids: &[Id],
+    ) -> MetaResult<Option<PartMeta>> {
+        let cat = self.read();
+        let rows = match cat.prids.get(&(tid, ptk)) {
+            Some(rows) => *rows,
+            None => return Ok(None),
+        };
+        let mut copas = Vec::with_capacity(cids.len());
+        let mut crcs = Vec::with_capacity(cids.len());
+        for cid in cids {
+            let kc = (*cid, ptk);
+            crcs.push(cat.crcs.get(&kc).cloned().unwrap_or_default());
+            copas.push(CoPaMeta {
+                cid: *cid,
+                siz_in_bytes: cat.parts.get(&kc).copied().unwrap_or_default(),
+                base: cat.bases.get(&kc).copied().unwrap_or_default(),
+                stats: cat.stats.get(&kc).copied().unwrap_or_default(),
+            });
         }
+        let dels = cat.dels.get(&(tid, ptk)).cloned().unwrap_or_default();
+        Ok(Some(PartMeta {
+            rows,
+            copas,
+            crcs,
+            dels,
+        }))
     }
 
-    #[inline]
-    pub fn set_copa_size_int_ptk(
+    ///register the part by its meta, the committed rows are set at last to
+    ///make the part visible
+    ///
+    ///WARN the caller should hold the table lock
+    pub fn put_part_meta(&self, tid: Id, ptk: u64, pm: &PartMeta) -> MetaResult<()> {
+        let recs = part_meta_records(tid, ptk, pm);
+        let mut cat = self.write();
+        self.commit(&mut cat, recs)
+    }
+
+    ///replace all infos of the part, including the copas of cids in it, by the
+    ///meta of the part merged from it, and switch the part to the files of the
+    ///generation gen atomically
+    ///
+    ///WARN the caller should hold the table lock
+    pub fn replace_part_meta(
         &self,
         tid: Id,
         ptk: u64,
-        part_size: usize,
+        cids: &[Id],
+        pm: &PartMeta,
+        gen: usize,
     ) -> MetaResult<()> {
-        let k = (tid.to_be(), ptk.to_be());
-        let kbs = k.as_bytes();
-        self.tree_prids
-            .insert(kbs, part_size.as_bytes())
-            .map_err(|_| MetaError::InsertPartInfoError)?;
-        Ok(())
+        let mut recs = clear_part_records(tid, ptk, cids);
+        recs.extend(part_meta_records(tid, ptk, pm));
+        recs.push(put(TREE_RUNS, (tid, ptk), &1usize));
+        recs.push(put(TREE_GENS, (tid, ptk), &gen));
+        let mut cat = self.write();
+        self.commit(&mut cat, recs)
     }
 
-    //FIXME possibly add more metadata
+    ///return the num of the writes committed into the part since it was
+    ///created or merged, 0 if unknown
     #[inline]
-    pub fn insert_copa_int_ptk(
-        &self,
-        cid: Id,
-        ptk: u64,
-        siz_in_bytes: usize,
-    ) -> MetaResult<()> {
-        let k = (cid.to_be(), ptk.to_be());
-        let kbs = k.as_bytes();
-        self.tree_parts
-            .insert(kbs, siz_in_bytes.as_bytes())
-            .map_err(|_| MetaError::InsertPartInfoError)?;
-        Ok(())
+    pub fn get_part_runs(&self, tid: Id, ptk: u64) -> MetaResult<usize> {
+        Ok(self
+            .read()
+            .runs
+            .get(&(tid, ptk))
+            .copied()
+            .unwrap_or_default())
     }
 
+    ///return the generation of the files of the part
+    ///
+    ///NOTE the generation is kept when the part is cleared, so that the part
+    ///     is written back or attached into the files of the same generation
     #[inline]
-    pub fn get_copa_siz_in_bytes_int_ptk(
+    pub fn get_part_gen(&self, tid: Id, ptk: u64) -> MetaResult<usize> {
+        Ok(self
+            .read()
+            .gens
+            .get(&(tid, ptk))
+            .copied()
+            .unwrap_or_default())
+    }
+
+    ///wait for the queries which have taken the infos of the parts but not
+    ///mapped their files yet, so that the files replaced before can be
+    ///unlinked(the files mapped are kept until unmapped)
+    pub fn wait_for_mappings(&self) {
+        drop(self.mapping.write().unwrap());
+    }
+
+    ///return the row ranges to scan for every part in the snapshot of the
+    ///table by the sparse index of its primary key column in the generation
+    ///of the part
+    pub fn get_row_ranges_by_pk_range(
         &self,
-        cid: Id,
-        ptk: u64,
-    ) -> MetaResult<Option<usize>> {
-        let k = (cid.to_be(), ptk.to_be());
-        let kbs = k.as_bytes();
-        let rt = self
-            .tree_parts
-            .get(kbs)
-            .map_err(|_| MetaError::GetPartInfoError)?
-            .map(|iv| *(&*iv).into_ref::<usize>());
-        Ok(rt)
+        snap: &TableSnapshot,
+        pk_cid: Id,
+        key_ranges: &Vec<RangeInclusive<u64>>,
+        granularity: usize,
+    ) -> MetaResult<HashMap<u64, Vec<(usize, usize)>>> {
+        let mut rngs = HashMap::new();
+        for p in &snap.parts {
+            if !matches!(p.copas.get(&pk_cid), Some((Some(_), _))) {
+                continue;
+            }
+            let fpath = get_part_path(snap.tid, pk_cid, p.ptk, p.gen, p.dp)?;
+            let pipath = gen_pipath_from_part_path(&fpath)?;
+            let marks = read_sparse_index(&pipath, p.rows, granularity)?;
+            let rs = prune_granules(&marks, key_ranges, granularity, p.rows);
+            rngs.insert(p.ptk, rs);
+        }
+        Ok(rngs)
+    }
+
+    ///the part is in the data dir recorded when it is placed, the parts not
+    ///placed yet are in the dirs of the default volume by the hash of ptk
+    pub fn get_part_dir(&self, tid: Id, ptk: u64) -> MetaResult<&'a String> {
+        self.part_dir_of(&self.read(), tid, ptk)
+    }
+
+    fn part_dir_of(&self, cat: &Catalog, tid: Id, ptk: u64) -> MetaResult<&'a String> {
+        match cat.locs.get(&(tid, ptk)) {
+            Some(dp) => self.find_data_dir(dp),
+            None => {
+                let dd = self.data_dirs;
+                use base::hash::Hasher;
+                let idx_dd = ptk.hash() as usize % dd.len();
+                Ok(&dd[idx_dd])
+            }
+        }
+    }
+
+    ///return the data dir of the part, or place the part into the dir with
+    ///the most available space of the default volume
+    ///
+    ///WARN the caller should hold the table lock
+    pub fn place_part(&self, tid: Id, ptk: u64) -> MetaResult<&'a String> {
+        let k = (tid, ptk);
+        let mut cat = self.write();
+        if let Some(dp) = cat.locs.get(&k) {
+            return self.find_data_dir(dp);
+        }
+        let dp = self.choose_volume_dir(DEFAULT_VOLUME)?;
+        self.commit(&mut cat, vec![put(TREE_LOCS, k, dp)])?;
+        Ok(dp)
+    }
+
+    ///NOTE the dir is kept when the part is cleared, so that the part is
+    ///     written back or attached in the same dir
+    ///
+    ///WARN the caller should hold the table lock
+    pub fn set_part_dir(&self, tid: Id, ptk: u64, dp: &str) -> MetaResult<()> {
+        let dp = self.find_data_dir(dp)?;
+        let mut cat = self.write();
+        self.commit(&mut cat, vec![put(TREE_LOCS, (tid, ptk), dp)])
+    }
+
+    pub fn get_volume_dirs(&self, vol: &str) -> MetaResult<&'a Vec<String>> {
+        if vol == DEFAULT_VOLUME {
+            return Ok(self.data_dirs);
+        }
+        self.volumes
+            .iter()
+            .find(|v| v.name == vol)
+            .map(|v| &v.data_dirs)
+            .ok_or_else(|| MetaError::VolumeNotExistError(vol.to_string()))
+    }
+
+    ///the available bytes of the data dir by the space function
+    pub fn get_dir_space(&self, dp: &str) -> MetaResult<u64> {
+        (self.space_fn.read().unwrap())(dp)
+    }
+
+    pub fn set_space_fn(&self, f: SpaceFn) {
+        *self.space_fn.write().unwrap() = f;
+    }
+
+    ///the data dir of the volume with the most available space
+    pub fn choose_volume_dir(&self, vol: &str) -> MetaResult<&'a String> {
+        let mut rt = None;
+        for dp in self.get_volume_dirs(vol)? {
+            let avail = self.get_dir_space(dp)?;
+            match rt {
+                Some((_, max)) if max >= avail => {}
+                _ => rt = Some((dp, avail)),
+            }
+        }
+        rt.map(|(dp, _)| dp)
+            .ok_or_else(|| MetaError::VolumeNotExistError(vol.to_string()))
+    }
+
+    pub fn get_part_volume(&self, tid: Id, ptk: u64) -> MetaResult<&'a str> {
+        let dp = self.get_part_dir(tid, ptk)?;
+        if self.data_dirs.contains(dp) {
+            return Ok(DEFAULT_VOLUME);
+        }
+        self.volumes
+            .iter()
+            .find(|v| v.data_dirs.contains(dp))
+            .map(|v| v.name.as_str())
+            .ok_or_else(|| MetaError::DataDirNotExistError(dp.to_string()))
+    }
+
+    fn find_data_dir(&self, dp: &str) -> MetaResult<&'a String> {
+        let data_dirs: &'a Vec<String> = self.data_dirs;
+        data_dirs
+            .iter()
+            .chain(self.volumes.iter().flat_map(|v| v.data_dirs.iter()))
+            .find(|d| d.as_str() == dp)
+            .ok_or_else(|| MetaError::DataDirNotExistError(dp.to_string()))
+    }
+
+    ///record the data dirs of the parts written before the dirs are recorded,
+    ///by the files of the parts found in the data dirs, return the count of
+    ///them
+    ///
+    ///NOTE the hash of ptk is not used because the data dirs may have been
+    ///     added since the parts were written
+    pub fn locate_unplaced_parts(&self) -> MetaResult<usize> {
+        let mut unplaced: HashSet<(Id, u64)> = {
+            let cat = self.read();
+            cat.part_size
+                .keys()
+                .filter(|k| !cat.locs.contains_key(k))
+                .copied()
+                .collect()
+        };
+        if unplaced.is_empty() {
+            return Ok(0);
+        }
+        let tids: HashSet<Id> = unplaced.iter().map(|(tid, _)| *tid).collect();
+        let mut n = 0;
+        let data_dirs: &'a Vec<String> = self.data_dirs;
+        let dirs = data_dirs
+            .iter()
+            .chain(self.volumes.iter().flat_map(|v| v.data_dirs.iter()));
+        for dp in dirs {
+            for tid in &tids {
+                let rd = match fs::read_dir(format!("{}/{}", dp, tid)) {
+                    Ok(rd) => rd,
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                    Err(e) => return Err(MetaError::WrappingIOError(e)),
+                };
+                for de in rd {
+                    let fname = de?.file_name();
+                    //{cid}_{ptk}[v{gen}]{sfx}
+                    let ptk = fname
+                        .to_str()
+                        .and_then(|s| s.split_once('_'))
+                        .and_then(|(_, s)| s.split(char::is_alphabetic).next())
+                        .and_then(|s| s.parse::<u64>().ok());
+                    if let Some(ptk) = ptk {
+                        if unplaced.remove(&(*tid, ptk)) {
+                            self.set_part_dir(*tid, ptk, dp)?;
+                            n += 1;
+                        }
+                    }
+                }
+            }
+        }
+        Ok(n)
     }
 
-    pub fn get_part_dir(&self, ptk: u64) -> &String {
-        let dd = self.data_dirs;
-        use base::hash::Hasher;
-        let idx_dd = ptk.hash() as usize % dd.len();
-        &dd[idx_dd]
+    ///take the snapshot of the parts of the table in the ptk ranges, with the
+    ///copas of cids in them, by one read of the catalog
+    pub fn snapshot_table(
+        &self,
+        tid: Id,
+        cids: &[Id],
+        ptk_range: &[RangeInclusive<u64>],
+    ) -> MetaResult<TableSnapshot<'_>> {
+        //NOTE the files are read and mapped out of the lock of the catalog, but
+        //     in the guard against the unlinking of the files replaced
+        let mg = self.mapping.read().unwrap();
+        let cat = self.read();
+        let mut parts = Vec::new();
+        for r in ptk_range {
+            for ((_, ptk), rows) in range_of(&cat.prids, tid, r.clone()) {
+                let k = (tid, *ptk);
+                let mut copas = HashMap::with_capacity(cids.len());
+                for cid in cids {
+                    let kc = (*cid, *ptk);
+                    let base = cat.bases.get(&kc).copied().unwrap_or_default();
+                    copas.insert(*cid, (cat.parts.get(&kc).copied(), base));
+                }
+                parts.push(PartSnapshot {
+                    ptk: *ptk,
+                    rows: *rows,
+                    gen: cat.gens.get(&k).copied().unwrap_or_default(),
+                    dp: self.part_dir_of(&cat, tid, *ptk)?,
+                    dels: cat.dels.get(&k).cloned(),
+                    copas,
+                });
+            }
+        }
+        Ok(TableSnapshot {
+            tid,
+            parts,
+            _mg: mg,
+        })
     }
 
     pub fn fill_copainfos_int_by_ptk_range(
         &self,
         copass_ret: &mut Vec<Vec<CoPaInfo>>,
         tid: Id,
-        cis: &Vec<(Id, BqlType)>,
+        cis: &Vec<(Id, ColumnInfo)>,
         ptk_range: Vec<RangeInclusive<u64>>,
     ) -> MetaResult<()> {
-        let tree_parts = &self.tree_parts;
-        let tree_prids = &self.tree_prids;
-
-        for (cid, col_typ) in cis {
-            for r in &ptk_range {
-                PartStore::fill_copainfos_int_by_ptk(
-                    self,
-                    tree_parts,
-                    tree_prids,
-                    copass_ret,
-                    tid,
-                    cid,
-                    col_typ,
-                    *r.start(),
-                    *r.end(),
-                )?;
-            }
+        let cids: Vec<Id> = cis.iter().map(|(cid, _)| *cid).collect();
+        let snap = self.snapshot_table(tid, &cids, &ptk_range)?;
+        self.fill_copainfos_by_snapshot(copass_ret, &snap, cis)
+    }
+
+    ///map the copas of the columns in the parts of the snapshot, the copas of
+    ///a column are pushed into copass_ret in the order of the parts
+    pub fn fill_copainfos_by_snapshot(
+        &self,
+        copass_ret: &mut Vec<Vec<CoPaInfo>>,
+        snap: &TableSnapshot,
+        cis: &Vec<(Id, ColumnInfo)>,
+    ) -> MetaResult<()> {
+        for (cid, ci) in cis {
+            self.fill_copainfos_of_column(copass_ret, snap, cid, ci)?;
         }
 
         Ok(())
     }
 
+    //NOTE iterate the parts of the table rather than the copas of the column,
+    //     because an added column has no copa for the parts written before
     #[inline(always)]
-    fn fill_copainfos_int_by_ptk(
+    fn fill_copainfos_of_column(
         &self,
-        tree_parts: &sled::Tree,
-        tree_prids: &sled::Tree,
         copass_ret: &mut Vec<Vec<CoPaInfo>>,
-        tid: Id,
+        snap: &TableSnapshot,
         cid: &u64,
-        col_typ: &BqlType,
-        ptk_s: u64,
-        ptk_e: u64,
+        ci: &ColumnInfo,
     ) -> Result<(), MetaError> {
-        let k = (cid.to_be(), ptk_s.to_be());
-        let kbs0 = k.as_bytes();
-        let k = (cid.to_be(), ptk_e.to_be());
-        let kbs1 = k.as_bytes();
-        let mut cps = Vec::new();
-        let it = tree_parts.range(kbs0..=kbs1);
-        for res in it {
-            if let Ok((kbs, _v)) = res {
-                let (_, ptk_be) = *(&*kbs).into_ref::<(u64, u64)>();
-                let k = (tid.to_be(), ptk_be);
-                let kbs = k.as_bytes();
-                let ptk = ptk_be.to_be();
-                let iv_part_siz = tree_prids
-                    .get(kbs)
-                    .map_err(|_| MetaError::GetPartInfoError)?
-                    .ok_or(MetaError::CanNotFindPartError)?;
-                let size = *(&*iv_part_siz).into_ref::<usize>();
-                let dp = self.get_part_dir(ptk);
-                let fpath = get_part_path(tid, *cid, ptk, dp)?;
-                // println!("fpath: {}", std::str::from_utf8(&fpath).unwrap());
-                let pfd = open_file_as_fd(&fpath)?;
-                let len_in_bytes =
-                    CoPaInfo::len_in_bytes(size, *col_typ, *cid, ptk, self)?;
+        let tid = snap.tid;
+        let mut parts = Vec::new();
+        for p in &snap.parts {
+            let (siz_in_bytes, base) = match p.copas.get(cid) {
+                Some(cp) => *cp,
+                None => return Err(MetaError::ColNotExistedError),
+            };
+            if siz_in_bytes.is_none() && base.rows == 0 {
+                continue;
+            }
+            parts.push((p.ptk, p.rows, siz_in_bytes, p.gen, base, p.dp));
+        }
+        let mut cps = Vec::with_capacity(parts.len());
+        for (ptk, size, siz_in_bytes, gen, base, dp) in parts {
+            let fpath = get_part_path(tid, *cid, ptk, gen, dp)?;
+            // println!("fpath: {}", std::str::from_utf8(&fpath).unwrap());
+            let col_typ = ci.data_type;
+            let maps = if let Some(siz_in_bytes) = siz_in_bytes {
+                let len_in_bytes = CoPaInfo::len_in_bytes(size, ci, siz_in_bytes)?;
                 // log::debug!("--- copar size: {}, len: {}", size, len_in_bytes);
-                let addr = mm_file_ro(pfd, len_in_bytes)?;
-                //issue#22 add om
-                let addr_om = if matches!(*col_typ, BqlType::String) {
-                    let ompath = gen_ompath_from_part_path(&fpath)?;
-                    let fd_om = open_file_as_fd(&ompath)?;
-                    mm_file_ro(fd_om, CoPaInfo::len_in_bytes_om(size))?
-                } else {
-                    0 as MemAddr
-                };
-                unsafe {
-                    close(pfd as i32);
-                }
-                cps.push(CoPaInfo {
-                    addr,
-                    addr_om,
-                    size,
-                    len_in_bytes,
-                })
+                self.mmaps.get_or_map(*cid, ptk, gen, size, || {
+                    let mut maps = CoPaMaps::empty();
+                    maps.addr = CoPaMaps::map_fd(open_file_as_fd(&fpath)?, len_in_bytes)?;
+                    maps.len = len_in_bytes;
+                    //issue#22 add om
+                    if col_typ.is_blob() {
+                        let ompath = gen_ompath_from_part_path(&fpath)?;
+                        let len_om = CoPaInfo::len_in_bytes_om(size);
+                        maps.addr_om =
+                            CoPaMaps::map_fd(open_file_as_fd(&ompath)?, len_om)?;
+                        maps.len_om = len_om;
+                    }
+                    if ci.is_nullable {
+                        let nmpath = gen_nmpath_from_part_path(&fpath)?;
+                        maps.addr_nm = map_null_map(&nmpath, size)?;
+                        maps.len_nm = size;
+                    }
+                    Ok(maps)
+                })?
             } else {
-                return Err(MetaError::GetPartInfoError);
-            }
+                Arc::new(CoPaMaps::empty())
+            };
+            //NOTE the src copa is not cached, it is mapped for the query only
+            let src_maps = if base.src_cid != 0 && base.rows > 0 {
+                let rows = base.rows.min(size);
+                let src_path = get_part_path(tid, base.src_cid, ptk, gen, dp)?;
+                let mut maps = CoPaMaps::empty();
+                let len = rows * base.src_type.size_in_usize()?;
+                maps.addr = CoPaMaps::map_fd(open_file_as_fd(&src_path)?, len)?;
+                maps.len = len;
+                let nmpath = gen_nmpath_from_part_path(&src_path)?;
+                maps.addr_nm = map_null_map(&nmpath, rows)?;
+                maps.len_nm = rows;
+                Some(Arc::new(maps))
+            } else {
+                None
+            };
+            cps.push(CoPaInfo {
+                addr: maps.addr,
+                addr_om: maps.addr_om,
+                addr_nm: maps.addr_nm,
+                size,
+                len_in_bytes: maps.len,
+                ptk,
+                base,
+                addr_src: src_maps.as_ref().map_or(0 as MemAddr, |m| m.addr),
+                addr_src_nm: src_maps.as_ref().map_or(0 as MemAddr, |m| m.addr_nm),
+                maps: Some(maps),
+                src_maps,
+            })
         }
         if cps.len() > 0 {
             copass_ret.push(cps);
@@ -403,144 +2143,103 @@ impl<'a> PartStore<'a> {
         Ok(())
     }
 
-    pub fn clear(&self, _tid: Id, _cids: &[Id]) -> MetaResult<()> {
-        //Clear the information of _tid and _cids
-        for r in self.tree_prids.iter().keys() {
-            let k = r.map_err(|_| MetaError::EntityDelError)?;
-            let kbs = &*k;
-            let (tid, _) = kbs.into_ref::<(u64, u64)>();
-            if *tid == _tid.to_be() {
-                let _ = self
-                    .tree_prids
-                    .remove(k)
-                    .map_err(|_| MetaError::EntityDelError)?;
-            }
+    pub fn clear(&self, tid: Id, cids: &[Id]) -> MetaResult<()> {
+        //Clear the information of tid and cids
+        let mut cat = self.write();
+        let all = 0..=u64::MAX;
+        let mut recs: Vec<Record> = Vec::new();
+        recs.extend(
+            range_of(&cat.prids, tid, all.clone()).map(|(k, _)| del(TREE_PRIDS, *k)),
+        );
+        recs.extend(
+            range_of(&cat.part_size, tid, all.clone())
+                .map(|(k, _)| del(TREE_PART_SIZE, *k)),
+        );
+        recs.extend(
+            range_of(&cat.dels, tid, all.clone()).map(|(k, _)| del(TREE_DELS, *k)),
+        );
+        recs.extend(
+            range_of(&cat.writes, tid, all.clone()).map(|(k, _)| del(TREE_WRITES, *k)),
+        );
+        recs.extend(
+            range_of(&cat.locs, tid, all.clone()).map(|(k, _)| del(TREE_LOCS, *k)),
+        );
+        recs.extend(
+            range_of(&cat.runs, tid, all.clone()).map(|(k, _)| del(TREE_RUNS, *k)),
+        );
+        recs.extend(range_of(&cat.gens, tid, all).map(|(k, _)| del(TREE_GENS, *k)));
+        for cid in cids {
+            cat.clear_column(*cid, &mut recs);
         }
+        self.commit(&mut cat, recs)
+    }
 
-        for r in self.tree_part_size.iter().keys() {
-            let k = r.map_err(|_| MetaError::EntityDelError)?;
-            let kbs = &*k;
-            let (tid, _) = kbs.into_ref::<(Id, Id)>();
-            if *tid == _tid.to_be() {
-                let _ = self
-                    .tree_part_size
-                    .remove(k)
-                    .map_err(|_| MetaError::EntityDelError)?;
-            }
-        }
+    ///remove the copa infos, bases and stats of a column
+    pub fn clear_column(&self, cid: Id) -> MetaResult<()> {
+        let mut cat = self.write();
+        let mut recs = Vec::new();
+        cat.clear_column(cid, &mut recs);
+        self.commit(&mut cat, recs)
+    }
 
-        for _cid in _cids {
-            let iter = self.tree_parts.scan_prefix(_cid.to_be_bytes());
-            for kv in iter {
-                let (k, _) = kv.map_err(|_| MetaError::EntityDelError)?;
-                self.tree_parts
-                    .remove(k)
-                    .map_err(|_| MetaError::EntityDelError)?;
-            }
+    ///unmap the cached copas of the columns after the queries using them
+    pub fn uncache_for_table(&self, _tid: Id, cids: &[Id]) -> MetaResult<()> {
+        for cid in cids {
+            self.mmaps.remove_column(*cid);
         }
-
         Ok(())
     }
 
-    //FIXME to rework
-    //TODO
-    pub fn uncache_for_table(&self, _tid: Id, _cids: &[Id]) -> MetaResult<()> {
-        Ok(())
+    ///set the max number of the copas of which the mappings are cached
+    pub fn set_mmap_cache_capacity(&self, capacity: usize) {
+        self.mmaps.set_capacity(capacity);
     }
 
-    //FIXME to rework
-    //TODO
+    ///persist the log, then checkpoint it if it is too large
     pub fn flush(&self) -> MetaResult<()> {
+        let should_checkpoint = {
+            let mut plog = self.plog.lock().unwrap();
+            plog.flush()?;
+            plog.should_checkpoint()
+        };
+        if should_checkpoint {
+            self.checkpoint()?;
+        }
+        Ok(())
+    }
+
+    fn checkpoint(&self) -> MetaResult<()> {
+        let cat = self.read();
+        let mut plog = self.plog.lock().unwrap();
+        //NOTE some changes may have been logged but not flushed since checked
+        if plog.should_checkpoint() {
+            plog.checkpoint(cat.records())?;
+        }
         Ok(())
     }
 
     #[allow(dead_code)]
     fn pretty_print(&self) -> MetaResult<()> {
-        let name = &*self.mdb.name();
-        println!("psdb: {}", unsafe { std::str::from_utf8_unchecked(&*name) });
+        let cat = self.read();
 
-        println!("====== dump tree_prids ======");
+        println!("====== dump prids ======");
         let mut ss = 0;
-        for r in self.tree_prids.iter() {
-            let (k, v) = r.map_err(|_| MetaError::InsertError)?;
-            let kbs = &*k;
-            let (k0, k1) = kbs.into_ref::<(usize, usize)>();
-            let prid = *(&*v).into_ref::<u64>();
-            println!("(tid, ptk): {:?}, prid: {}", (k0.to_be(), k1.to_be()), prid);
+        for ((tid, ptk), prid) in cat.prids.iter() {
+            println!("(tid, ptk): {:?}, prid: {}", (tid, ptk), prid);
             ss += prid;
         }
         println!("total num of rows: {}", ss);
 
-        println!("====== dump tree_part_size ======");
-        for r in self.tree_part_size.iter() {
-            let (k, v) = r.map_err(|_| MetaError::InsertError)?;
-            let kbs = &*k;
-            let (k0, k1) = kbs.into_ref::<(usize, usize)>();
-            // let k0 = u64::from_be_bytes(
-            //     kbs[..8].try_into().map_err(|_| MetaError::InsertError)?,
-            // );
-            // let k1 = u64::from_be_bytes(
-            //     kbs[8..].try_into().map_err(|_| MetaError::InsertError)?,
-            // );
-            println!(
-                "(tid, ptk): {:?}, size: {}",
-                (k0.to_be(), k1.to_be()),
-                (&*v).into_ref::<u64>()
-            );
+        println!("====== dump part_size ======");
+        for ((tid, ptk), size) in cat.part_size.iter() {
+            println!("(tid, ptk): {:?}, size: {}", (tid, ptk), size);
         }
 
-        println!("====== dump tree_parts ======");
-        for r in self.tree_parts.iter() {
-            let (k, v) = r.map_err(|_| MetaError::InsertError)?;
-            let kbs = &*k;
-            let (k0, k1) = kbs.into_ref::<(usize, usize)>();
-            // let k0 = u64::from_be_bytes(
-            //     kbs[..8].try_into().map_err(|_| MetaError::InsertError)?,
-            // );
-            // let k1 = u64::from_be_bytes(
-            //     kbs[8..].try_into().map_err(|_| MetaError::InsertError)?,
-            // );
-            println!(
-                "(cid, ptk): {:?}, value: {}",
-                (k0.to_be(), k1.to_be()),
-                (&*v).into_ref::<u64>()
-            );
+        println!("====== dump parts ======");
+        for ((cid, ptk), siz) in cat.parts.iter() {
+            println!("(cid, ptk): {:?}, value: {}", (cid, ptk), siz);
         }
 
-        // println!("====== dump tree_part_fd ======");
-        // for r in self.cache_part_fds.iter() {
-        //     let (k, v) = r.map_err(|_| MetaError::InsertError)?;
-        //     let kbs = &*k;
-        //     let k0 = u64::from_be_bytes(
-        //         kbs[..8].try_into().map_err(|_| MetaError::InsertError)?,
-        //     );
-        //     let k1 = u64::from_be_bytes(
-        //         kbs[8..].try_into().map_err(|_| MetaError::InsertError)?,
-        //     );
-        //     println!(
-        //         "(cid, ptk): {:?}, fd: {}",
-        //         (k0, k1),
-        //         (&*v).into_ref::<u64>()
-        //     );
-        // }
-
-        // println!("====== dump tree_part_fd_nm ======");
-        // for r in self.tree_part_fd_nm.iter() {
-        //     let (k, v) = r.map_err(|_| MetaError::InsertError)?;
-        //     println!("(cid, ptk): {:?}, fd_nm: {:?}", &*k, &*v);
-        // }
-
-        // println!("====== dump tree_part_fd_om ======");
-        // for r in self.tree_part_fd_om.iter() {
-        //     let (k, v) = r.map_err(|_| MetaError::InsertError)?;
-        //     println!(
-        //         "(cid, ptk): {:?}, fd_om: {:?}",
-        //         // unsafe { std::str::from_utf8_unchecked(&*k) },
-        //         &*k,
-        //         &*v
-        //     );
-        // }
-
         Ok(())
     }
 }
@@ -673,9 +2372,9 @@ mod unit_tests {
             let tid = 0;
             let cid = i;
             let ptk = 20200202;
-            let dp = ps.get_part_dir(ptk);
+            let dp = ps.get_part_dir(tid, ptk)?;
             ensure_table_path_existed(tid, dp)?;
-            let fpath = get_part_path(tid, cid, ptk, dp)?;
+            let fpath = get_part_path(tid, cid, ptk, 0, dp)?;
             let fd = open_file_as_fd(&fpath)?;
             assert!(fd > 0);
         }
@@ -683,9 +2382,9 @@ mod unit_tests {
             let tid = 1000;
             let cid = i;
             let ptk = 20200202;
-            let dp = ps.get_part_dir(ptk);
+            let dp = ps.get_part_dir(tid, ptk)?;
             ensure_table_path_existed(tid, dp)?;
-            let fpath = get_part_path(tid, cid, ptk, dp)?;
+            let fpath = get_part_path(tid, cid, ptk, 0, dp)?;
             let fd = open_file_as_fd(&fpath)?;
             assert!(fd > 0);
         }
@@ -725,7 +2424,7 @@ mod unit_tests {
         for tid in 0..10 {
             for cid in 0..100 {
                 for ptk in 0..1000 {
-                    let pp = get_part_path(tid, cid, ptk, data_dir.as_str())?;
+                    let pp = get_part_path(tid, cid, ptk, 0, data_dir.as_str())?;
                     let omp = gen_ompath_from_part_path(&pp)?;
                     let omp_len = omp.len();
                     assert_eq!(&omp[omp_len - 3..omp_len], b"om\0");
@@ -758,13 +2457,19 @@ mod unit_tests {
 
         //prepare test data
         let tid = 0; //faked
-        let cids = vec![(1u64, BqlType::UInt(32))]; //faked
+        let cids = vec![(
+            1u64,
+            ColumnInfo {
+                data_type: BqlType::UInt(32),
+                ..Default::default()
+            },
+        )]; //faked
         for (cid, _col_typ) in cids.iter() {
             for i in 0..10 {
                 let ptk = 20200101 + i;
-                let dp = ps.get_part_dir(ptk);
+                let dp = ps.get_part_dir(tid, ptk)?;
                 ensure_table_path_existed(tid, dp)?;
-                let fpath = get_part_path(tid, *cid, ptk, dp)?;
+                let fpath = get_part_path(tid, *cid, ptk, 0, dp)?;
                 let fd = open_file_as_fd(&fpath)?;
                 assert!(fd > 0);
                 ps.set_copa_size_int_ptk(tid, ptk, ptk as usize)?;
@@ -786,7 +2491,13 @@ mod unit_tests {
         }
         assert_eq!(ct_part, 5);
 
-        let cids = vec![(1u64, BqlType::UInt(32))]; //faked
+        let cids = vec![(
+            1u64,
+            ColumnInfo {
+                data_type: BqlType::UInt(32),
+                ..Default::default()
+            },
+        )]; //faked
         let mut cpss = Vec::new();
         ps.fill_copainfos_int_by_ptk_range(
             &mut cpss,
@@ -809,6 +2520,695 @@ mod unit_tests {
         Ok(())
     }
 
+    #[test]
+    fn test_copa_bases() -> MetaResult<()> {
+        let (parts_dir, data_dir) = prepare_dirs("/tmp/parts_test_pb")?;
+        let data_dirs = vec![data_dir];
+        let ps = PartStore::new(&[parts_dir], &data_dirs)?;
+
+        let tid = 1;
+        let (cid0, cid1) = (2, 3);
+        let ci = ColumnInfo {
+            data_type: BqlType::UInt(32),
+            ..Default::default()
+        };
+        for ptk in 0..3 {
+            let dp = ps.get_part_dir(tid, ptk)?;
+            ensure_table_path_existed(tid, dp)?;
+            let prid = ps.get_prid_int_ptk(tid, ptk, 10)?;
+            ps.insert_copa_int_ptk(cid0, ptk, (prid + 10) * 4)?;
+            ps.set_copa_size_int_ptk(tid, ptk, prid + 10)?;
+        }
+        //cid1 is added after the parts have been written
+        ps.init_copa_bases(tid, cid1, 0, BqlType::UnInit)?;
+        ps.get_prid_int_ptk(tid, 3, 10)?;
+        ps.set_copa_size_int_ptk(tid, 3, 10)?;
+        ps.insert_copa_int_ptk(cid0, 3, 40)?;
+        ps.insert_copa_int_ptk(cid1, 3, 40)?;
+
+        let bases = ps.get_copa_bases(cid1)?;
+        assert_eq!(bases.len(), 3);
+        assert!(bases.iter().all(|(_, b)| b.rows == 10));
+
+        let cis = vec![(cid0, ci), (cid1, ci)];
+        let mut cpss = Vec::new();
+        ps.fill_copainfos_int_by_ptk_range(&mut cpss, tid, &cis, vec![0..=u64::MAX])?;
+        assert_eq!(cpss.len(), 2);
+        assert_eq!(cpss[0].len(), 4);
+        assert_eq!(cpss[1].len(), 4);
+        for cp in &cpss[1][..3] {
+            assert!(cp.addr.is_null());
+            assert_eq!(cp.base.rows, cp.size);
+        }
+        assert!(!cpss[1][3].addr.is_null());
+        assert_eq!(cpss[1][3].base.rows, 0);
+
+        ps.clear_column(cid1)?;
+        assert!(ps.get_copa_bases(cid1)?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_deletion_bitmaps() -> MetaResult<()> {
+        let (parts_dir, data_dir) = prepare_dirs("/tmp/parts_test_pd")?;
+        let data_dirs = vec![data_dir];
+        let ps = PartStore::new(&[parts_dir], &data_dirs)?;
+
+        let (tid, cid) = (1, 2);
+        for ptk in 0..2 {
+            let prid = ps.get_prid_int_ptk(tid, ptk, 12)?;
+            ps.insert_copa_int_ptk(cid, ptk, (prid + 12) * 4)?;
+            ps.set_copa_size_int_ptk(tid, ptk, prid + 12)?;
+        }
+        assert_eq!(ps.get_part_size_reserved(tid, 1)?, 12);
+        assert_eq!(ps.get_deletion_bitmap(tid, 0)?, None);
+
+        ps.merge_deletion_bitmap(tid, 0, &[0b0000_0101])?;
+        ps.merge_deletion_bitmap(tid, 0, &[0b0000_0001, 0b0000_1000])?;
+        ps.merge_deletion_bitmap(tid, 1, &[0b1000_0000])?;
+        assert_eq!(
+            ps.get_deletion_bitmap(tid, 0)?,
+            Some(vec![0b0000_0101, 0b0000_1000])
+        );
+        let bms = ps.get_deletion_bitmaps(tid)?;
+        assert_eq!(bms.len(), 2);
+        assert_eq!(bms[&1], vec![0b1000_0000]);
+        assert!(ps.get_deletion_bitmaps(tid + 1)?.is_empty());
+
+        //the stale bitmaps of other generations or more rows are rejected
+        assert!(!ps.merge_deletion_bitmap_of(tid, 1, 1, 12, &[0b1])?);
+        assert!(!ps.merge_deletion_bitmap_of(tid, 1, 0, 13, &[0b1])?);
+        assert!(!ps.merge_deletion_bitmap_of(tid, 1, 0, 8, &[0b1, 0b1])?);
+        assert!(ps.merge_deletion_bitmap_of(tid, 1, 0, 12, &[0b1, 0b1])?);
+        assert_eq!(
+            ps.get_deletion_bitmap(tid, 1)?,
+            Some(vec![0b1000_0001, 0b1])
+        );
+
+        ps.clear_part(tid, 0, &[cid])?;
+        assert_eq!(ps.get_deletion_bitmap(tid, 0)?, None);
+        assert_eq!(ps.get_part_size_reserved(tid, 0)?, 0);
+        assert_eq!(ps.get_copa_siz_in_bytes_int_ptk(cid, 0)?, None);
+        assert_eq!(ps.get_copa_siz_in_bytes_int_ptk(cid, 1)?, Some(48));
+
+        ps.clear(tid, &[cid])?;
+        assert!(ps.get_deletion_bitmaps(tid)?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_copa_stats() -> MetaResult<()> {
+        let (parts_dir, data_dir) = prepare_dirs("/tmp/parts_test_pm")?;
+        let data_dirs = vec![data_dir];
+        let ps = PartStore::new(&[parts_dir], &data_dirs)?;
+
+        let (tid, cid) = (1, 2);
+        //part 0 in [10, 20], part 1 in [15, 30], part 2 is all NULL, and
+        //part 3 has no stats
+        let stss = [(10, 20, 0), (15, 30, 0), (0, 0, 4)];
+        for ptk in 0..4u64 {
+            let prid = ps.get_prid_int_ptk(tid, ptk, 4)?;
+            ps.set_copa_size_int_ptk(tid, ptk, prid + 4)?;
+            if let Some((min, max, nulls)) = stss.get(ptk as usize) {
+                let st = CoPaStats {
+                    min: *min,
+                    max: *max,
+                    nulls: *nulls,
+                    rows: 2,
+                };
+                ps.merge_copa_stats(cid, ptk, &st)?;
+                ps.merge_copa_stats(cid, ptk, &st)?;
+            }
+        }
+        assert_eq!(
+            ps.get_part_sizes(tid)?,
+            vec![(0, 4), (1, 4), (2, 4), (3, 4)]
+        );
+        let st = ps.get_copa_stats(cid, 1)?.unwrap();
+        assert_eq!((st.min, st.max, st.nulls, st.rows), (15, 30, 0, 4));
+        assert!(!ps.get_copa_stats(cid, 2)?.unwrap().has_values());
+
+        let all = vec![0..=u64::MAX];
+        let rngs = ps.prune_parts_by_stats(tid, &all, &[(cid, vec![0..=12])])?;
+        assert_eq!(rngs, vec![0..=0, 3..=3]);
+        let rngs = ps.prune_parts_by_stats(tid, &all, &[(cid, vec![16..=18])])?;
+        assert_eq!(rngs, vec![0..=1, 3..=3]);
+        let rngs = ps.prune_parts_by_stats(tid, &all, &[(cid, vec![25..=25])])?;
+        assert_eq!(rngs, vec![1..=1, 3..=3]);
+        let rngs = ps.prune_parts_by_stats(tid, &vec![1..=2], &[(cid, vec![40..=50])])?;
+        assert!(rngs.is_empty());
+
+        //the stats which do not cover all rows of the part can not skip it
+        ps.get_prid_int_ptk(tid, 0, 1)?;
+        ps.set_copa_size_int_ptk(tid, 0, 5)?;
+        let rngs = ps.prune_parts_by_stats(tid, &all, &[(cid, vec![25..=25])])?;
+        assert_eq!(rngs, vec![0..=1, 3..=3]);
+
+        ps.clear_part(tid, 1, &[cid])?;
+        assert_eq!(ps.get_copa_stats(cid, 1)?, None);
+        ps.clear_column(cid)?;
+        assert_eq!(ps.get_copa_stats(cid, 0)?, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_signed_stats_key_ranges() {
+        let st = |min: i64, max: i64| CoPaStats {
+            min: stats_key_of_signed(min),
+            max: stats_key_of_signed(max),
+            nulls: 0,
+            rows: 1,
+        };
+        let typ = BqlType::Int(32);
+        //a < 2, the negative values are covered
+        let lt2 = stats_key_ranges(typ, vec![0..=1]);
+        assert!(st(-5, -1).may_match(&lt2));
+        assert!(st(-5, 3).may_match(&lt2));
+        assert!(!st(2, 3).may_match(&lt2));
+        //a > 10
+        let gt10 = stats_key_ranges(typ, vec![11..=u64::MAX]);
+        assert!(!st(-100, 10).may_match(&gt10));
+        assert!(st(-100, 11).may_match(&gt10));
+        //a between 3 and 5
+        let btw = stats_key_ranges(typ, vec![3..=5]);
+        assert!(!st(-5, 2).may_match(&btw));
+        assert!(!st(6, 8).may_match(&btw));
+        assert!(st(4, 8).may_match(&btw));
+        //the unsigned ranges are kept
+        let typ = BqlType::UInt(32);
+        assert_eq!(stats_key_ranges(typ, vec![3..=5]), vec![3..=5]);
+    }
+
+    #[test]
+    fn test_part_meta() -> MetaResult<()> {
+        let (parts_dir, data_dir) = prepare_dirs("/tmp/parts_test_pmeta")?;
+        let data_dirs = vec![data_dir];
+        let ps = PartStore::new(&[parts_dir], &data_dirs)?;
+
+        let (tid, tid2, ptk) = (1, 2, 202001);
+        let cids = [3, 4];
+        assert_eq!(ps.get_part_meta(tid, ptk, &cids)?, None);
+
+        let prid = ps.get_prid_int_ptk(tid, ptk, 10)?;
+        ps.set_copa_size_int_ptk(tid, ptk, prid + 10)?;
+        ps.insert_copa_int_ptk(3, ptk, 80)?;
+        let base = CoPaBase {
+            rows: 6,
+            src_cid: 0,
+            src_type: BqlType::UnInit,
+        };
+        ps.set_copa_base(4, ptk, &base)?;
+        ps.insert_copa_int_ptk(4, ptk, 16)?;
+        let st = CoPaStats {
+            min: 1,
+            max: 9,
+            nulls: 0,
+            rows: 10,
+        };
+        ps.merge_copa_stats(3, ptk, &st)?;
+        ps.merge_deletion_bitmap(tid, ptk, &[0b101, 0])?;
+
+        let pm = ps.get_part_meta(tid, ptk, &cids)?.unwrap();
+        assert_eq!(pm.rows, 10);
+        assert_eq!(pm.copas[0].siz_in_bytes, 80);
+        assert_eq!(pm.copas[0].stats, st);
+        assert_eq!(pm.copas[1].base, base);
+        assert_eq!(pm.copas[1].stats, CoPaStats::default());
+        assert_eq!(pm.dels, vec![0b101, 0]);
+        assert_eq!(pm.crcs, vec![CoPaCrcs::default(); 2]);
+        let mut pm = pm;
+        pm.crcs[1].files[2].update(0, &[0u8; 10]);
+        assert_eq!(PartMeta::from_bytes(&pm.to_bytes())?, pm);
+        assert!(PartMeta::from_bytes(&pm.to_bytes()[..20]).is_err());
+
+        //register the part into another table with other cids
+        ps.clear_part(tid, ptk, &cids)?;
+        assert_eq!(ps.get_part_meta(tid, ptk, &cids)?, None);
+        let mut pm2 = pm.clone();
+        pm2.copas[0].cid = 5;
+        pm2.copas[1].cid = 6;
+        ps.put_part_meta(tid2, ptk, &pm2)?;
+        assert_eq!(ps.get_part_sizes(tid2)?, vec![(ptk, 10)]);
+        assert_eq!(ps.get_part_size_reserved(tid2, ptk)?, 10);
+        assert_eq!(ps.get_part_meta(tid2, ptk, &[5, 6])?, Some(pm2.clone()));
+        assert_eq!(ps.get_part_runs(tid2, ptk)?, 0);
+
+        //replace the part by the one merged without the deleted rows
+        let mut pm3 = pm2;
+        pm3.rows = 8;
+        pm3.dels = Vec::new();
+        pm3.copas[1].base = CoPaBase::default();
+        ps.replace_part_meta(tid2, ptk, &[5, 6], &pm3, 1)?;
+        assert_eq!(ps.get_part_sizes(tid2)?, vec![(ptk, 8)]);
+        assert_eq!(ps.get_part_meta(tid2, ptk, &[5, 6])?, Some(pm3));
+        assert_eq!(ps.get_copa_base(6, ptk)?, None);
+        assert_eq!(ps.get_part_runs(tid2, ptk)?, 1);
+        assert_eq!(ps.get_part_gen(tid2, ptk)?, 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_part_gens() -> MetaResult<()> {
+        let (parts_dir, data_dir) = prepare_dirs("/tmp/parts_test_gen")?;
+        let data_dirs = vec![data_dir.clone()];
+        let ps = PartStore::new(&[parts_dir], &data_dirs)?;
+
+        let (tid, cid, ptk) = (1, 2, 3);
+        let cis = vec![(
+            cid,
+            ColumnInfo {
+                data_type: BqlType::UInt(32),
+                ..Default::default()
+            },
+        )];
+        assert_eq!(get_part_file_name(cid, ptk, 0), "2_3");
+        assert_eq!(get_part_file_name(cid, ptk, 12), "2_3v12");
+        let fpath1 = get_part_path(tid, cid, ptk, 1, &data_dir)?;
+        assert_eq!(&fpath1[fpath1.len() - 6..], b"2_3v1\0");
+
+        //the part of the generation 0
+        ensure_table_path_existed(tid, &data_dir)?;
+        let fp0 = format!("{}/{}/{}", data_dir, tid, get_part_file_name(cid, ptk, 0));
+        fs::write(&fp0, 1u32.to_ne_bytes().repeat(4))?;
+        let prid = ps.get_prid_int_ptk(tid, ptk, 4)?;
+        ps.set_copa_size_int_ptk(tid, ptk, prid + 4)?;
+        ps.insert_copa_int_ptk(cid, ptk, 16)?;
+        let mut cpss = Vec::new();
+        ps.fill_copainfos_int_by_ptk_range(&mut cpss, tid, &cis, vec![ptk..=ptk])?;
+        assert_eq!(cpss[0][0].size, 4);
+
+        //switch the part to the merged files of the generation 1, the old
+        //files mapped are still readable after unlinked
+        let fp1 = format!("{}/{}/{}", data_dir, tid, get_part_file_name(cid, ptk, 1));
+        fs::write(&fp1, 2u32.to_ne_bytes().repeat(2))?;
+        let mut pm = ps.get_part_meta(tid, ptk, &[cid])?.unwrap();
+        pm.rows = 2;
+        pm.copas[0].siz_in_bytes = 8;
+        ps.replace_part_meta(tid, ptk, &[cid], &pm, 1)?;
+        ps.wait_for_mappings();
+        fs::remove_file(&fp0)?;
+        assert!(!Path::new(&fp0).exists());
+        let vs = unsafe { std::slice::from_raw_parts(cpss[0][0].addr as *const u32, 4) };
+        assert_eq!(vs, &[1u32; 4]);
+
+        let mut cpss = Vec::new();
+        ps.fill_copainfos_int_by_ptk_range(&mut cpss, tid, &cis, vec![ptk..=ptk])?;
+        assert_eq!(cpss[0][0].size, 2);
+        let vs = unsafe { std::slice::from_raw_parts(cpss[0][0].addr as *const u32, 2) };
+        assert_eq!(vs, &[2u32; 2]);
+
+        //the generation is kept when the part is cleared
+        ps.clear_part(tid, ptk, &[cid])?;
+        assert_eq!(ps.get_part_gen(tid, ptk)?, 1);
+        ps.clear(tid, &[cid])?;
+        assert_eq!(ps.get_part_gen(tid, ptk)?, 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_part_dirs() -> MetaResult<()> {
+        let (parts_dir, data_dir) = prepare_dirs("/tmp/parts_test_pl")?;
+        let cold_dir = "/tmp/parts_test_pl/cold".to_string();
+        create_dir_all(&cold_dir)?;
+        let data_dirs = vec![data_dir.clone()];
+        let volumes = vec![Volume {
+            name: "cold".to_string(),
+            data_dirs: vec![cold_dir.clone()],
+        }];
+        let ps = PartStore::new_with_volumes(&[parts_dir], &data_dirs, &volumes, true)?;
+
+        let (tid, cid, ptk) = (1, 2, 3);
+        assert_eq!(ps.place_part(tid, ptk)?, &data_dir);
+        assert_eq!(ps.get_part_volume(tid, ptk)?, DEFAULT_VOLUME);
+        ps.set_part_dir(tid, ptk, ps.choose_volume_dir("cold")?)?;
+        assert_eq!(ps.get_part_volume(tid, ptk)?, "cold");
+        assert_eq!(ps.place_part(tid, ptk)?, &cold_dir);
+        assert!(matches!(
+            ps.choose_volume_dir("hot"),
+            Err(MetaError::VolumeNotExistError(_))
+        ));
+        assert!(matches!(
+            ps.set_part_dir(tid, ptk, "/tmp/parts_test_pl/hot"),
+            Err(MetaError::DataDirNotExistError(_))
+        ));
+
+        //the dir is kept when the part is cleared
+        ps.clear_part(tid, ptk, &[cid])?;
+        assert_eq!(ps.get_part_dir(tid, ptk)?, &cold_dir);
+        ps.clear(tid, &[cid])?;
+        assert_eq!(ps.get_part_dir(tid, ptk)?, &data_dir);
+
+        //the parts written before the dirs are recorded are located by files
+        ps.get_prid_int_ptk(tid, ptk, 1)?;
+        ps.get_prid_int_ptk(tid, ptk + 1, 1)?;
+        ensure_table_path_existed(tid, &cold_dir)?;
+        fs::write(format!("{}/{}/{}_{}om", cold_dir, tid, cid, ptk), b"")?;
+        assert_eq!(ps.locate_unplaced_parts()?, 1);
+        assert_eq!(ps.get_part_dir(tid, ptk)?, &cold_dir);
+        assert_eq!(ps.locate_unplaced_parts()?, 0);
+        let fname = get_part_file_name(cid, ptk + 1, 1);
+        fs::write(format!("{}/{}/{}pi", cold_dir, tid, fname), b"")?;
+        assert_eq!(ps.locate_unplaced_parts()?, 1);
+        assert_eq!(ps.get_part_dir(tid, ptk + 1)?, &cold_dir);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_part_dirs_added() -> MetaResult<()> {
+        let (parts_dir, data_dir) = prepare_dirs("/tmp/parts_test_pl_added")?;
+        let parts_dirs = [parts_dir];
+        let (tid, cid) = (1, 2);
+        let data_dirs = vec![data_dir.clone()];
+        {
+            let ps = PartStore::new_with_volumes(&parts_dirs, &data_dirs, &[], true)?;
+            for ptk in 0..8 {
+                assert_eq!(ps.place_part(tid, ptk)?, &data_dir);
+            }
+            ps.flush()?;
+        }
+
+        //another data dir, on another file system if possible
+        let dp_added = if Path::new("/dev/shm").exists() {
+            "/dev/shm/parts_test_pl_added".to_string()
+        } else {
+            "/tmp/parts_test_pl_added/data_added".to_string()
+        };
+        if Path::new(&dp_added).exists() {
+            remove_dir_all(&dp_added)?;
+        }
+        create_dir_all(&dp_added)?;
+        let data_dirs = vec![data_dir.clone(), dp_added.clone()];
+        let ps = PartStore::new_with_volumes(&parts_dirs, &data_dirs, &[], true)?;
+        //the existing parts keep their recorded dir, though the hash of ptk
+        //may point to the added one
+        for ptk in 0..8 {
+            assert_eq!(ps.get_part_dir(tid, ptk)?, &data_dir);
+            assert_eq!(ps.place_part(tid, ptk)?, &data_dir);
+        }
+        //the new parts go into the dir with the most available space
+        let avails = [
+            get_available_space(&data_dir)?,
+            get_available_space(&dp_added)?,
+        ];
+        let dp_most = if avails[1] > avails[0] {
+            &dp_added
+        } else {
+            &data_dir
+        };
+        assert_eq!(ps.place_part(tid, 8)?, dp_most);
+        assert_eq!(ps.get_part_dir(tid, 8)?, dp_most);
+        //and stay there after cleared
+        ps.clear_part(tid, 8, &[cid])?;
+        assert_eq!(ps.get_part_dir(tid, 8)?, dp_most);
+        remove_dir_all(&dp_added)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_crcs() -> MetaResult<()> {
+        let mut bs = vec![7u8; CRC_BLOCK_SIZE * 2 + 100];
+        let mut fc = FileCrcs::default();
+        fc.update(fc.block_start(0), &bs[..CRC_BLOCK_SIZE + 10]);
+        assert_eq!(fc.len, CRC_BLOCK_SIZE + 10);
+        assert_eq!(fc.crcs.len(), 2);
+        //append from the middle of the last block
+        let start = fc.block_start(CRC_BLOCK_SIZE + 10);
+        assert_eq!(start, CRC_BLOCK_SIZE);
+        fc.update(start, &bs[start..]);
+        assert_eq!(fc.len, bs.len());
+        assert_eq!(fc.crcs.len(), 3);
+        let mut fc1 = FileCrcs::default();
+        fc1.update(0, &bs);
+        assert_eq!(fc, fc1);
+        assert!(fc.verify(&bs).is_empty());
+
+        bs[CRC_BLOCK_SIZE * 2 + 1] = 0;
+        assert_eq!(fc.verify(&bs), vec![2]);
+
+        let mut cc = CoPaCrcs::default();
+        cc.files[0] = fc;
+        cc.files[2].update(0, &[1, 2, 3]);
+        let mut cbs = vec![9u8];
+        cc.write_bytes(&mut cbs);
+        assert_eq!(CoPaCrcs::read_bytes(&cbs, 1)?, (cc, cbs.len()));
+        assert!(CoPaCrcs::read_bytes(&cbs[..cbs.len() - 1], 1).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_part_writes() -> MetaResult<()> {
+        let (parts_dir, data_dir) = prepare_dirs("/tmp/parts_test_pw")?;
+        let data_dirs = vec![data_dir];
+        let ps = PartStore::new(&[parts_dir], &data_dirs)?;
+
+        let (tid, ptk, cid) = (1, 7, 2);
+        let st = CoPaStats {
+            min: 3,
+            max: 5,
+            nulls: 0,
+            rows: 10,
+        };
+        let (_, pw) =
+            ps.reserve_part_write(tid, ptk, 10, &[(cid, CoPaBytes::Fixed(4))])?;
+        assert_eq!(
+            pw,
+            PartWrite {
+                prid: 0,
+                rows: 10,
+                copas: vec![CoPaWrite {
+                    cid,
+                    siz_in_bytes: 0,
+                }],
+            }
+        );
+        assert_eq!(PartWrite::from_bytes(&pw.to_bytes())?, pw);
+        assert_eq!(ps.get_part_writes()?, vec![(tid, ptk, pw.clone())]);
+        //not visible before the commit
+        assert!(ps.get_part_sizes(tid)?.is_empty());
+        let mut crcs = CoPaCrcs::default();
+        crcs.files[0].update(0, &[1u8; 40]);
+        let cc = CoPaCommit {
+            cid,
+            siz_in_bytes: 40,
+            stats: st,
+            crcs,
+        };
+        ps.wait_for_part_write(tid, ptk, &pw)?;
+        ps.commit_part_write(tid, ptk, &pw, &[cc.clone()])?;
+        assert!(ps.get_part_writes()?.is_empty());
+        assert_eq!(ps.get_part_sizes(tid)?, vec![(ptk, 10)]);
+        assert_eq!(ps.get_copa_siz_in_bytes_int_ptk(cid, ptk)?, Some(40));
+        assert_eq!(ps.get_copa_stats(cid, ptk)?, Some(st));
+        assert_eq!(ps.get_copa_crcs(cid, ptk)?, Some(cc.crcs));
+        assert_eq!(ps.get_part_runs(tid, ptk)?, 1);
+
+        let (_, pw) =
+            ps.reserve_part_write(tid, ptk, 5, &[(cid, CoPaBytes::Fixed(4))])?;
+        assert_eq!(pw.prid, 10);
+        assert_eq!(pw.copas[0].siz_in_bytes, 40);
+        assert_eq!(ps.abort_part_write(tid, ptk, &pw)?, Some(pw.clone()));
+        ps.rollback_part_write(tid, ptk, &pw)?;
+        assert!(ps.get_part_writes()?.is_empty());
+        assert_eq!(ps.get_part_size_reserved(tid, ptk)?, 10);
+        assert_eq!(ps.get_part_sizes(tid)?, vec![(ptk, 10)]);
+        assert_eq!(ps.get_copa_siz_in_bytes_int_ptk(cid, ptk)?, Some(40));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_part_writes_in_flight() -> MetaResult<()> {
+        let (parts_dir, data_dir) = prepare_dirs("/tmp/parts_test_pwf")?;
+        let data_dirs: &'static Vec<String> = Box::leak(Box::new(vec![data_dir]));
+        let ps = Arc::new(PartStore::new(&[parts_dir], data_dirs)?);
+
+        let (tid, ptk, cid0, cid1) = (1, 7, 2, 3);
+        let cbs = |len| {
+            [
+                (cid0, CoPaBytes::Fixed(8)),
+                (cid1, CoPaBytes::Appended(len)),
+            ]
+        };
+        let (_, pw0) = ps.reserve_part_write(tid, ptk, 2, &cbs(30))?;
+        let (_, pw1) = ps.reserve_part_write(tid, ptk, 3, &cbs(50))?;
+        //the bytes are reserved after the writes in flight
+        assert_eq!((pw1.prid, pw1.rows), (2, 3));
+        assert_eq!(pw1.copas[0].siz_in_bytes, 16);
+        assert_eq!(pw1.copas[1].siz_in_bytes, 30);
+        assert_eq!(ps.get_part_size_reserved(tid, ptk)?, 5);
+        //the intent is the one of the first write in flight
+        assert_eq!(ps.get_part_writes()?, vec![(tid, ptk, pw0.clone())]);
+
+        //the later write waits for the earlier one to be committed
+        let h = {
+            let ps = ps.clone();
+            std::thread::spawn(move || -> MetaResult<()> {
+                ps.wait_for_part_write(tid, ptk, &pw1)?;
+                let cc = |cid, siz_in_bytes| CoPaCommit {
+                    cid,
+                    siz_in_bytes,
+                    ..Default::default()
+                };
+                ps.commit_part_write(tid, ptk, &pw1, &[cc(cid0, 40), cc(cid1, 80)])
+            })
+        };
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        assert!(ps.get_part_sizes(tid)?.is_empty());
+        ps.wait_for_part_write(tid, ptk, &pw0)?;
+        ps.commit_part_write(tid, ptk, &pw0, &[])?;
+        h.join().unwrap()?;
+        assert!(ps.get_part_writes()?.is_empty());
+        assert_eq!(ps.get_part_sizes(tid)?, vec![(ptk, 5)]);
+        assert_eq!(ps.get_copa_siz_in_bytes_int_ptk(cid1, ptk)?, Some(80));
+
+        //the writes after a failed one are rolled back with it
+        let (_, pw2) = ps.reserve_part_write(tid, ptk, 1, &cbs(10))?;
+        let (_, pw3) = ps.reserve_part_write(tid, ptk, 1, &cbs(10))?;
+        assert_eq!(pw3.copas[1].siz_in_bytes, 90);
+        assert_eq!(ps.abort_part_write(tid, ptk, &pw2)?, None);
+        assert!(matches!(
+            ps.wait_for_part_write(tid, ptk, &pw3),
+            Err(MetaError::PartWriteAbortedError)
+        ));
+        assert_eq!(ps.abort_part_write(tid, ptk, &pw3)?, Some(pw2.clone()));
+        ps.rollback_part_write(tid, ptk, &pw2)?;
+        assert!(ps.get_part_writes()?.is_empty());
+        assert_eq!(ps.get_part_size_reserved(tid, ptk)?, 5);
+        let (_, pw4) = ps.reserve_part_write(tid, ptk, 1, &cbs(10))?;
+        assert_eq!((pw4.prid, pw4.copas[1].siz_in_bytes), (5, 80));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_part_log() -> MetaResult<()> {
+        use std::io::Write;
+
+        let (parts_dir, data_dir) = prepare_dirs("/tmp/parts_test_pl")?;
+        let data_dirs = vec![data_dir];
+        let (tid, ptk, cid) = (1, 7, 2);
+        let pw = PartWrite {
+            prid: 0,
+            rows: 10,
+            copas: vec![CoPaWrite {
+                cid,
+                siz_in_bytes: 0,
+            }],
+        };
+        let cc = CoPaCommit {
+            cid,
+            siz_in_bytes: 40,
+            stats: CoPaStats {
+                min: 1,
+                max: 2,
+                nulls: 0,
+                rows: 10,
+            },
+            crcs: CoPaCrcs::default(),
+        };
+        {
+            let ps = PartStore::new(&[&parts_dir], &data_dirs)?;
+            let cbs = [(cid, CoPaBytes::Fixed(4))];
+            assert_eq!(ps.reserve_part_write(tid, ptk, 10, &cbs)?.1, pw);
+            ps.wait_for_part_write(tid, ptk, &pw)?;
+            ps.commit_part_write(tid, ptk, &pw, &[cc.clone()])?;
+            let cat = ps.read();
+            ps.plog.lock().unwrap().checkpoint(cat.records())?;
+            drop(cat);
+            //replayed onto the checkpoint
+            ps.merge_deletion_bitmap(tid, ptk, &[0b101])?;
+            ps.reserve_part_write(tid, ptk, 10, &cbs)?;
+        }
+        //a torn frame at the tail of the log is discarded
+        let mut f = fs::OpenOptions::new()
+            .append(true)
+            .open(format!("{}/p1/log", parts_dir))?;
+        f.write_all(&[1, 2, 3])?;
+        drop(f);
+
+        let ps = PartStore::new(&[&parts_dir], &data_dirs)?;
+        assert_eq!(ps.get_part_sizes(tid)?, vec![(ptk, 10)]);
+        //the rows reserved by the write in flight are left to `recover_part_writes`
+        assert_eq!(ps.get_part_size_reserved(tid, ptk)?, 20);
+        assert_eq!(ps.get_copa_siz_in_bytes_int_ptk(cid, ptk)?, Some(40));
+        assert_eq!(ps.get_copa_stats(cid, ptk)?, Some(cc.stats));
+        assert_eq!(ps.get_copa_crcs(cid, ptk)?, Some(cc.crcs));
+        assert_eq!(ps.get_deletion_bitmap(tid, ptk)?, Some(vec![0b101]));
+        assert_eq!(ps.get_part_writes()?.len(), 1);
+        ps.clear(tid, &[cid])?;
+        drop(ps);
+
+        let ps = PartStore::new(&[&parts_dir], &data_dirs)?;
+        assert!(ps.get_part_sizes(tid)?.is_empty());
+        assert!(ps.get_part_writes()?.is_empty());
+        assert_eq!(ps.get_copa_stats(cid, ptk)?, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sparse_index() -> MetaResult<()> {
+        let (_parts_dir, data_dir) = prepare_dirs("/tmp/parts_test_pi")?;
+        let (tid, cid, ptk) = (1, 2, 3);
+        ensure_table_path_existed(tid, &data_dir)?;
+        let fpath = get_part_path(tid, cid, ptk, 0, &data_dir)?;
+        let pipath = gen_pipath_from_part_path(&fpath)?;
+        assert_eq!(&pipath[pipath.len() - 3..], b"pi\0");
+
+        //two inserts, the second one starts in the middle of granule 1
+        let keys0: Vec<u64> = (100..106).collect();
+        write_sparse_index(&pipath, 0, &keys0, 4)?;
+        let keys1: Vec<u64> = (0..6).collect();
+        write_sparse_index(&pipath, 6, &keys1, 4)?;
+
+        let marks = read_sparse_index(&pipath, 12, 4)?;
+        assert_eq!(marks.len(), 3);
+        assert_eq!(
+            marks[0],
+            SparseMark {
+                min: 100,
+                max: 103,
+                rows: 4
+            }
+        );
+        assert_eq!(
+            marks[1],
+            SparseMark {
+                min: 0,
+                max: 105,
+                rows: 4
+            }
+        );
+        assert_eq!(
+            marks[2],
+            SparseMark {
+                min: 2,
+                max: 5,
+                rows: 4
+            }
+        );
+
+        assert_eq!(prune_granules(&marks, &[0..=1], 4, 12), vec![(4, 4)]);
+        assert_eq!(prune_granules(&marks, &[101..=101], 4, 12), vec![(0, 8)]);
+        assert_eq!(prune_granules(&marks, &[3..=3], 4, 12), vec![(4, 8)]);
+        assert_eq!(prune_granules(&marks, &[200..=300], 4, 12), vec![]);
+        //partial last granule
+        assert_eq!(
+            prune_granules(&marks[..], &[0..=u64::MAX], 4, 10),
+            vec![(0, 10)]
+        );
+
+        Ok(())
+    }
+
     #[test]
     #[ignore]
     fn dump() -> MetaResult<()> {
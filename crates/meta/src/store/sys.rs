@@ -31,6 +31,8 @@ cid:value:pk_vaule []
 tree_cols:
 ----------
 cid - column_info
+cid, "df" - default value (bytes of the column type)
+cid, "em" - full type of Enum8/Enum16 with the names and values (string)
 
 tree_tabs:
 ----------
@@ -39,6 +41,7 @@ tid, "cr" - create_script (string)
 tid, "en" - engine (enum)
 tid, "pa" - partition_keys_expr
 tid, "pc" - partition_cols
+tid, "pk" - primary_keys
 tid, "se", settings.k - settings.v
 
 
@@ -55,6 +58,7 @@ CREATE TABLE nyc_taxi (
 use crate::to_qualified_key;
 
 use crate::errs::{MetaError, MetaResult};
+use crate::store::parts::DEFAULT_INDEX_GRANULARITY;
 use crate::types::*;
 
 use arrow::array::{LargeStringArray, LargeStringBuilder};
@@ -215,6 +219,11 @@ impl MetaStore {
     }
 
     //FIXME move all key_sys into dedicated tree_sys?
+    pub fn flush(&self) -> MetaResult<()> {
+        self.mdb.flush().map_err(|_e| MetaError::InsertError)?;
+        Ok(())
+    }
+
     pub fn new_db(&self, dbname: &str) -> MetaResult<Id> {
         let rt = self._new(dbname)?;
         let mut key_sd = vec![];
@@ -247,6 +256,15 @@ impl MetaStore {
         Ok(builder.finish())
     }
 
+    pub fn get_database_names(&self) -> MetaResult<Vec<String>> {
+        let mut rt = vec![];
+        for kv in self.tree0.scan_prefix(KEY_SYS_IDX_DBS) {
+            let (_, v) = kv.map_err(|_| MetaError::GetError)?;
+            rt.push(String::from_utf8_lossy(&v).into_owned());
+        }
+        Ok(rt)
+    }
+
     pub fn get_table_names(&self, dbname: &str) -> MetaResult<Vec<String>> {
         let tbn_iter = self.tree0.scan_prefix([KEY_SYS_IDX_TABS, dbname].join(""));
         let mut rt = vec![];
@@ -489,12 +507,28 @@ impl MetaStore {
             let k = ["se", &setting.0].join("");
             self.insert_table_info_kv(tid, k.as_str(), setting.1.as_str())?;
         }
+        if !ti.ttl_col.is_empty() {
+            self.insert_table_info_kv(tid, "tc", ti.ttl_col.as_str())?;
+            self.insert_table_info_kv(tid, "ts", ti.ttl_secs)?;
+        }
+        if !ti.engine_params.is_empty() {
+            self.insert_table_info_kv(tid, "ep", ti.engine_params.join(",").as_str())?;
+        }
         Ok(())
     }
 
     pub fn get_table_info_create_script(&self, tid: Id) -> MetaResult<Option<IVec>> {
         self._get_table_info(tid, "cr")
     }
+    pub fn set_table_info_create_script(&self, tid: Id, script: &str) -> MetaResult<()> {
+        let mut key: Vec<u8> = Vec::with_capacity(16);
+        key.extend_from_slice(to_key_id_order(tid).as_bytes());
+        key.extend_from_slice(b"cr");
+        self.tree_tabs
+            .insert(key, script.as_bytes())
+            .map_err(|_e| MetaError::InsertError)?;
+        Ok(())
+    }
     pub fn get_table_info_partition_keys_expr(
         &self,
         tid: Id,
@@ -504,6 +538,20 @@ impl MetaStore {
     pub fn get_table_info_partition_cols(&self, tid: Id) -> MetaResult<Option<IVec>> {
         self._get_table_info(tid, "pc")
     }
+    pub fn get_table_info_primary_keys(&self, tid: Id) -> MetaResult<Option<IVec>> {
+        self._get_table_info(tid, "pk")
+    }
+    ///return the first column of the primary keys (stored as "a,b,"), which is
+    ///the one the parts are sorted and sparse indexed by
+    pub fn get_table_info_sort_key(&self, tid: Id) -> MetaResult<Option<String>> {
+        Ok(self.get_table_info_primary_keys(tid)?.and_then(|iv| {
+            String::from_utf8_lossy(&iv)
+                .split(',')
+                .next()
+                .filter(|k| !k.is_empty())
+                .map(|k| k.to_string())
+        }))
+    }
     pub fn get_table_info_setting(
         &self,
         tid: Id,
@@ -513,6 +561,31 @@ impl MetaStore {
         self._get_table_info(tid, k.as_str())
     }
 
+    ///return (ttl_col, ttl_secs) of the table, None if it has no TTL
+    pub fn get_table_info_ttl(&self, tid: Id) -> MetaResult<Option<(String, u64)>> {
+        match self._get_table_info(tid, "tc")? {
+            Some(iv) => {
+                let col = String::from_utf8_lossy(&iv).into_owned();
+                let secs = self._get_table_info_prim_int::<u64>(tid, "ts")?;
+                Ok(Some((col, secs)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    pub fn get_table_info_index_granularity(&self, tid: Id) -> MetaResult<usize> {
+        let g = self
+            .get_table_info_setting(tid, "index_granularity")?
+            .and_then(|iv| {
+                std::str::from_utf8(&*iv)
+                    .ok()
+                    .and_then(|s| s.parse::<usize>().ok())
+            })
+            .filter(|g| *g > 0)
+            .unwrap_or(DEFAULT_INDEX_GRANULARITY);
+        Ok(g)
+    }
+
     fn _get_table_info(&self, tid: Id, k: &str) -> MetaResult<Option<IVec>> {
         let mut key: Vec<u8> = Vec::with_capacity(16);
         key.extend_from_slice(to_key_id_order(tid).as_bytes());
@@ -520,12 +593,21 @@ impl MetaStore {
         self.tree_tabs.get(key).map_err(|_e| MetaError::GetError)
     }
 
-    #[allow(dead_code)]
-    fn get_table_info_engine(&self, tid: Id) -> MetaResult<EngineType> {
+    pub fn get_table_info_engine(&self, tid: Id) -> MetaResult<EngineType> {
         self._get_table_info_prim_int::<u8>(tid, "en")
             .map(|e| EngineType::from(e))
     }
 
+    pub fn get_table_info_engine_params(&self, tid: Id) -> MetaResult<Vec<String>> {
+        match self._get_table_info(tid, "ep")? {
+            Some(iv) => Ok(String::from_utf8_lossy(&iv)
+                .split(',')
+                .map(|s| s.to_string())
+                .collect()),
+            None => Ok(Vec::new()),
+        }
+    }
+
     fn _get_table_info_prim_int<T: PrimInt>(&self, tid: Id, k: &str) -> MetaResult<T> {
         let mut key: Vec<u8> = Vec::with_capacity(16);
         key.extend_from_slice(to_key_id_order(tid).as_bytes());
@@ -634,6 +716,9 @@ impl MetaStore {
                     .insert(&cid.to_be_bytes(), col_info.as_bytes())
                     .map_err(|_| MetaError::InsertError)?;
                 debug_assert!(r.is_none());
+                if let Some((_, ev)) = tab.enum_values.iter().find(|e| e.0 == *colname) {
+                    self.set_column_enum(cid, col_info.data_type, ev)?;
+                }
             }
             Ok(tid)
         } else {
@@ -648,11 +733,16 @@ impl MetaStore {
             .map_err(|_e| MetaError::InsertError)?;
         if let Some(iv) = r {
             let bs = &*iv;
-            if std::mem::size_of::<ColumnInfo>() == bs.len() {
+            let siz = std::mem::size_of::<ColumnInfo>();
+            if siz == bs.len() {
                 let ci = bs.into_ref::<ColumnInfo>();
                 Ok(Some(*ci))
+            } else if bs.len() < siz {
+                //NOTE old version: missing trailing fields take their defaults
+                let mut ci = ColumnInfo::default();
+                ci.as_bytes_mut()[..bs.len()].copy_from_slice(bs);
+                Ok(Some(ci))
             } else {
-                //FIXME handle old version
                 Err(MetaError::GenericError)
             }
         } else {
@@ -660,6 +750,167 @@ impl MetaStore {
         }
     }
 
+    ///add a column after all existed columns of the table
+    pub fn add_column(
+        &self,
+        dbname: &str,
+        tabname: &str,
+        colname: &str,
+        ci: &ColumnInfo,
+    ) -> MetaResult<Id> {
+        let qtn = to_qualified_key!(dbname, tabname);
+        self.id(&qtn).ok_or(MetaError::TabNotExistedError)?;
+        let ordinal = self
+            .get_columns(dbname, tabname)?
+            .iter()
+            .map(|c| c.2.ordinal + 1)
+            .max()
+            .unwrap_or(0);
+        let cid = match self.new_col(dbname, tabname, colname) {
+            Err(MetaError::EntityExistedError(id)) => {
+                return Err(MetaError::ColExistedError(id))
+            }
+            r => r?,
+        };
+        let mut ci = *ci;
+        ci.ordinal = ordinal;
+        self.tree_cols
+            .insert(&cid.to_be_bytes(), ci.as_bytes())
+            .map_err(|_| MetaError::InsertError)?;
+        Ok(cid)
+    }
+
+    pub fn remove_column(
+        &self,
+        dbname: &str,
+        tabname: &str,
+        colname: &str,
+    ) -> MetaResult<Id> {
+        let qcn = to_qualified_key!(dbname, tabname, colname);
+        self.id(&qcn).ok_or(MetaError::ColNotExistedError)?;
+        self._del(&qcn)
+    }
+
+    pub fn rename_column(
+        &self,
+        dbname: &str,
+        tabname: &str,
+        from: &str,
+        to: &str,
+    ) -> MetaResult<Id> {
+        let qcn_from = to_qualified_key!(dbname, tabname, from);
+        let qcn_to = to_qualified_key!(dbname, tabname, to);
+        if let Some(id) = self.id(&qcn_to) {
+            return Err(MetaError::ColExistedError(id));
+        }
+        let cid = self
+            .tree0
+            .remove(qcn_from.as_str())
+            .map_err(|_| MetaError::EntityDelError)?
+            .map(|bs| *(&*bs).into_ref::<u64>())
+            .ok_or(MetaError::ColNotExistedError)?;
+        self.tree0
+            .insert(qcn_to.as_str(), cid.as_bytes())
+            .map_err(|_| MetaError::InsertError)?;
+        self.tree1
+            .insert(cid.to_be_bytes(), qcn_to.as_str())
+            .map_err(|_| MetaError::InsertError)?;
+        Ok(cid)
+    }
+
+    ///give the name of a column to a new column with the new column info,
+    ///return (old cid, new cid)
+    ///
+    ///NOTE the old column is detached from the name but its info is kept until
+    ///remove_detached_column, so that its data can still be read by the cid
+    pub fn replace_column(
+        &self,
+        dbname: &str,
+        tabname: &str,
+        colname: &str,
+        ci: &ColumnInfo,
+    ) -> MetaResult<(Id, Id)> {
+        let qcn = to_qualified_key!(dbname, tabname, colname);
+        let old_cid = self.id(&qcn).ok_or(MetaError::ColNotExistedError)?;
+        let old_ci = self
+            .get_column_info(old_cid)?
+            .ok_or(MetaError::EntityShouldExistButNot)?;
+        let cid = self.gen_id()?;
+        let mut ci = *ci;
+        ci.ordinal = old_ci.ordinal;
+        self.tree_cols
+            .insert(&cid.to_be_bytes(), ci.as_bytes())
+            .map_err(|_| MetaError::InsertError)?;
+        self.tree1
+            .insert(cid.to_be_bytes(), qcn.as_str())
+            .map_err(|_| MetaError::InsertError)?;
+        self.tree0
+            .insert(qcn.as_str(), cid.as_bytes())
+            .map_err(|_| MetaError::InsertError)?;
+        Ok((old_cid, cid))
+    }
+
+    pub fn remove_detached_column(&self, cid: Id) -> MetaResult<()> {
+        self.tree1
+            .remove(cid.to_be_bytes())
+            .map_err(|_| MetaError::EntityDelError)?;
+        for kv in self.tree_cols.scan_prefix(cid.to_be_bytes()) {
+            let (bs_k, _) = kv.map_err(|_| MetaError::EntityDelError)?;
+            self.tree_cols
+                .remove(bs_k)
+                .map_err(|_| MetaError::EntityDelError)?;
+        }
+        Ok(())
+    }
+
+    pub fn get_column_default(&self, cid: Id) -> MetaResult<Option<IVec>> {
+        let mut key = cid.to_be_bytes().to_vec();
+        key.extend_from_slice(b"df");
+        self.tree_cols.get(key).map_err(|_| MetaError::GetError)
+    }
+
+    pub fn set_column_default(&self, cid: Id, default: &[u8]) -> MetaResult<()> {
+        let mut key = cid.to_be_bytes().to_vec();
+        key.extend_from_slice(b"df");
+        self.tree_cols
+            .insert(key, default)
+            .map_err(|_| MetaError::InsertError)?;
+        Ok(())
+    }
+
+    pub fn remove_column_default(&self, cid: Id) -> MetaResult<()> {
+        let mut key = cid.to_be_bytes().to_vec();
+        key.extend_from_slice(b"df");
+        self.tree_cols
+            .remove(key)
+            .map_err(|_| MetaError::EntityDelError)?;
+        Ok(())
+    }
+
+    ///the names and values of the Enum8/Enum16 column
+    pub fn get_column_enum(&self, cid: Id) -> MetaResult<Option<EnumValues>> {
+        let mut key = cid.to_be_bytes().to_vec();
+        key.extend_from_slice(b"em");
+        match self.tree_cols.get(key).map_err(|_| MetaError::GetError)? {
+            Some(iv) => Ok(Some(EnumValues::from_bytes(&*iv)?.1)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn set_column_enum(
+        &self,
+        cid: Id,
+        typ: BqlType,
+        ev: &EnumValues,
+    ) -> MetaResult<()> {
+        let mut key = cid.to_be_bytes().to_vec();
+        key.extend_from_slice(b"em");
+        self.tree_cols
+            .insert(key, ev.to_vec(typ)?)
+            .map_err(|_| MetaError::InsertError)?;
+        Ok(())
+    }
+
     pub fn dbid_by_name<T: AsRef<str>>(&self, name: T) -> Option<Id> {
         self.id(name)
     }
@@ -863,16 +1114,20 @@ mod unit_tests {
             columns: vec![],
             tab_info: TableInfo {
                 create_script: "create_123456\n".to_string(),
-                engine: EngineType::BaseStorage,
+                engine: EngineType::ReplacingMergeTree,
                 partition_keys_expr: "toYYYYMM(ds)".to_string(),
                 partition_cols: "ds".to_string(),
-                primary_keys: String::new(),
+                primary_keys: "col1,col2,".to_string(),
                 settings: seq![
                     "a".to_string() => "1".to_string(),
                     "b".to_string() => "1".to_string(),
                     "b".to_string() => "2".to_string(),
                 ],
+                ttl_col: "ds".to_string(),
+                ttl_secs: 86400,
+                engine_params: vec!["col1".to_string()],
             },
+            enum_values: vec![],
         };
         for i in 1..=5 {
             t.columns.push((
@@ -882,6 +1137,7 @@ mod unit_tests {
                     is_primary_key: false,
                     is_nullable: true,
                     ordinal: i - 1,
+                    codec: Default::default(),
                 },
             ));
         }
@@ -931,7 +1187,9 @@ mod unit_tests {
         let cs = ms.get_table_info_create_script(tid)?.unwrap();
         assert_eq!(&*cs, b"create_123456\n");
         let eng = ms.get_table_info_engine(tid)?;
-        assert_eq!(eng, EngineType::BaseStorage);
+        assert_eq!(eng, EngineType::ReplacingMergeTree);
+        let eps = ms.get_table_info_engine_params(tid)?;
+        assert_eq!(eps, vec!["col1".to_string()]);
         let pakey = ms.get_table_info_partition_keys_expr(tid)?.unwrap();
         assert_eq!(&*pakey, b"toYYYYMM(ds)");
 
@@ -941,6 +1199,10 @@ mod unit_tests {
         assert_eq!(&*sa, b"2");
         let sa = ms.get_table_info_setting(tid, "c")?;
         assert!(sa.is_none());
+        let ttl = ms.get_table_info_ttl(tid)?;
+        assert_eq!(ttl, Some(("ds".to_string(), 86400)));
+        let pk = ms.get_table_info_sort_key(tid)?;
+        assert_eq!(pk, Some("col1".to_string()));
 
         //
         let bc = ms.get_all_databases()?;
@@ -969,6 +1231,85 @@ mod unit_tests {
         Ok(())
     }
 
+    #[test]
+    fn test_alter_columns() -> MetaResult<()> {
+        let tmp_dir = [temp_dir().to_str().unwrap(), "alter_cols"].join("/");
+        if Path::new(&tmp_dir).exists() {
+            remove_dir_all(&tmp_dir).unwrap();
+        }
+        std::fs::create_dir_all(&tmp_dir)?;
+        let ms = MetaStore::new(&[tmp_dir])?;
+        ms.new_db("db")?;
+        let mut t = Table {
+            name: "t".to_string(),
+            dbname: "db".to_string(),
+            ..Default::default()
+        };
+        for (i, cn) in ["a", "b"].iter().enumerate() {
+            t.columns.push((
+                cn.to_string(),
+                ColumnInfo {
+                    data_type: BqlType::UInt(32),
+                    ordinal: i as u32,
+                    ..Default::default()
+                },
+            ));
+        }
+        ms.create_table(&t)?;
+
+        let ci = ColumnInfo {
+            data_type: BqlType::Int(16),
+            ..Default::default()
+        };
+        let cid_c = ms.add_column("db", "t", "c", &ci)?;
+        assert_eq!(ms.get_column_info(cid_c)?.unwrap().ordinal, 2);
+        assert!(matches!(
+            ms.add_column("db", "t", "a", &ci),
+            Err(MetaError::ColExistedError(_))
+        ));
+        ms.set_column_default(cid_c, &7i16.to_le_bytes())?;
+        assert_eq!(&*ms.get_column_default(cid_c)?.unwrap(), &[7u8, 0]);
+        let (typ, ev) = EnumValues::from_bytes(b"Enum8('x' = 1, 'y' = 2)")?;
+        ms.set_column_enum(cid_c, typ, &ev)?;
+        assert_eq!(ms.get_column_enum(cid_c)?, Some(ev));
+        assert_eq!(
+            ms.get_column_enum(ms.cid_by_qname("db.t.a").unwrap())?,
+            None
+        );
+
+        let cid = ms.rename_column("db", "t", "c", "d")?;
+        assert_eq!(cid, cid_c);
+        assert!(ms.cid_by_qname("db.t.c").is_none());
+        assert_eq!(ms.cid_by_qname("db.t.d"), Some(cid_c));
+        assert!(ms.rename_column("db", "t", "d", "a").is_err());
+
+        let ci = ColumnInfo {
+            data_type: BqlType::Int(64),
+            ..Default::default()
+        };
+        let (old_cid, new_cid) = ms.replace_column("db", "t", "d", &ci)?;
+        assert_eq!(old_cid, cid_c);
+        assert_eq!(ms.cid_by_qname("db.t.d"), Some(new_cid));
+        let new_ci = ms.get_column_info(new_cid)?.unwrap();
+        assert_eq!(new_ci.data_type, BqlType::Int(64));
+        assert_eq!(new_ci.ordinal, 2);
+        assert!(ms.get_column_info(old_cid)?.is_some());
+        ms.remove_detached_column(old_cid)?;
+        assert!(ms.get_column_info(old_cid)?.is_none());
+        assert!(ms.get_column_default(old_cid)?.is_none());
+
+        ms.remove_column("db", "t", "b")?;
+        assert!(ms.remove_column("db", "t", "b").is_err());
+        let cns: Vec<String> = ms
+            .get_columns("db", "t")?
+            .into_iter()
+            .map(|c| c.0)
+            .collect();
+        assert_eq!(cns, vec!["a", "d"]);
+
+        Ok(())
+    }
+
     #[test]
     #[ignore]
     fn dump() -> MetaResult<()> {
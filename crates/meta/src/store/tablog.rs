@@ -0,0 +1,252 @@
+use std::{
+    collections::HashMap,
+    convert::TryInto,
+    fs::{self, File, OpenOptions},
+    io::{ErrorKind, Read, Seek, SeekFrom, Write},
+    path::PathBuf,
+};
+
+use crate::{
+    errs::{MetaError, MetaResult},
+    types::{BaseChunk, BqlType, Id},
+};
+
+const SIZES_FILE: &str = "sizes";
+const SIZES_TMP_FILE: &str = "sizes.tmp";
+
+///rows, len of data and whether the chunk has a null map
+const CHUNK_HEADER_SIZE: usize = 17;
+
+///The storage of a table of the Log engine
+///
+///The table has no partition, every column is kept in one file, into which the
+///chunks of the inserts are appended one after another. The sizes of the
+///column files are committed by replacing the sizes file after the chunks are
+///written, so only the committed chunks are read, and the chunks torn by a
+///crash are truncated by the next append.
+///
+///WARN the appends into a table should be serialized by the caller
+pub struct TabLog {
+    dir: PathBuf,
+}
+
+impl TabLog {
+    ///the log of the table tid in the data dir dp
+    pub fn new(dp: &str, tid: Id) -> Self {
+        TabLog {
+            dir: PathBuf::from(format!("{}/{}", dp, tid)),
+        }
+    }
+
+    fn column_path(&self, cid: Id) -> PathBuf {
+        self.dir.join(format!("{}.log", cid))
+    }
+
+    fn corrupted(&self) -> MetaError {
+        MetaError::CorruptedTableLogError(self.dir.display().to_string())
+    }
+
+    ///the committed sizes of the column files
+    pub fn read_sizes(&self) -> MetaResult<HashMap<Id, u64>> {
+        let s = match fs::read_to_string(self.dir.join(SIZES_FILE)) {
+            Ok(s) => s,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(HashMap::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut sizes = HashMap::new();
+        for l in s.lines() {
+            let mut kv = l.split_whitespace().map(|v| v.parse::<u64>());
+            match (kv.next(), kv.next()) {
+                (Some(Ok(cid)), Some(Ok(siz))) => sizes.insert(cid, siz),
+                _ => return Err(self.corrupted()),
+            };
+        }
+        Ok(sizes)
+    }
+
+    ///append the chunks of the same rows into the files of their columns, the
+    ///files and the sizes are synced if sync
+    pub fn append(&self, chunks: &[(Id, &BaseChunk)], sync: bool) -> MetaResult<()> {
+        fs::create_dir_all(&self.dir)?;
+        let mut sizes = self.read_sizes()?;
+        for (cid, chk) in chunks {
+            let siz = sizes.get(cid).copied().unwrap_or(0);
+            let mut f = OpenOptions::new()
+                .create(true)
+                .write(true)
+                .open(self.column_path(*cid))?;
+            //NOTE the uncommitted tail is left by a crash
+            f.set_len(siz)?;
+            let mut bs = Vec::new();
+            write_chunk(chk, &mut bs);
+            f.seek(SeekFrom::End(0))?;
+            f.write_all(&bs)?;
+            if sync {
+                f.sync_data()?;
+            }
+            sizes.insert(*cid, siz + bs.len() as u64);
+        }
+        let mut s = String::new();
+        for (cid, siz) in sizes {
+            s.push_str(&format!("{} {}\n", cid, siz));
+        }
+        let tmp = self.dir.join(SIZES_TMP_FILE);
+        let mut f = File::create(&tmp)?;
+        f.write_all(s.as_bytes())?;
+        if sync {
+            f.sync_all()?;
+        }
+        fs::rename(&tmp, self.dir.join(SIZES_FILE))?;
+        if sync {
+            File::open(&self.dir)?.sync_all()?;
+        }
+        Ok(())
+    }
+
+    ///read the committed rows of the columns, as one chunk for each column
+    pub fn read_columns(&self, cols: &[(Id, BqlType)]) -> MetaResult<Vec<BaseChunk>> {
+        let sizes = self.read_sizes()?;
+        let mut rt = Vec::with_capacity(cols.len());
+        for (cid, typ) in cols {
+            let siz = sizes.get(cid).copied().unwrap_or(0) as usize;
+            let mut bs = vec![0u8; siz];
+            if siz > 0 {
+                File::open(self.column_path(*cid))?.read_exact(&mut bs)?;
+            }
+            rt.push(read_chunks(&bs, *typ).ok_or_else(|| self.corrupted())?);
+        }
+        if rt.iter().any(|chk| chk.size != rt[0].size) {
+            return Err(self.corrupted());
+        }
+        Ok(rt)
+    }
+}
+
+///layout: rows, len of data, 1 if null map, null map, offset map of String,
+///data
+fn write_chunk(chk: &BaseChunk, bs: &mut Vec<u8>) {
+    let n = chk.size;
+    let nm = chk.null_map.as_ref().filter(|nm| nm.len() == n);
+    bs.extend_from_slice(&(n as u64).to_le_bytes());
+    bs.extend_from_slice(&(chk.data.len() as u64).to_le_bytes());
+    bs.push(nm.is_some() as u8);
+    if let Some(nm) = nm {
+        bs.extend_from_slice(nm);
+    }
+    if let Some(om) = &chk.offset_map {
+        for o in om {
+            bs.extend_from_slice(&o.to_le_bytes());
+        }
+    }
+    bs.extend_from_slice(&chk.data);
+}
+
+///concat the chunks in bs into one, the rows of the chunks without null map
+///are not NULL
+fn read_chunks(bs: &[u8], typ: BqlType) -> Option<BaseChunk> {
+    let is_blob = typ.is_blob();
+    let mut rt = BaseChunk {
+        btype: typ,
+        size: 0,
+        data: vec![],
+        null_map: None,
+        offset_map: if is_blob { Some(vec![0]) } else { None },
+        lc_dict_data: None,
+    };
+    let mut ofs = 0;
+    while ofs < bs.len() {
+        let hdr = bs.get(ofs..ofs + CHUNK_HEADER_SIZE)?;
+        let n = u64::from_le_bytes(hdr[0..8].try_into().unwrap()) as usize;
+        let len = u64::from_le_bytes(hdr[8..16].try_into().unwrap()) as usize;
+        let len_nm = if hdr[16] != 0 { n } else { 0 };
+        let len_om = if is_blob { (n + 1) * 4 } else { 0 };
+        let start = ofs + CHUNK_HEADER_SIZE;
+        let end = start + len_nm + len_om + len;
+        let chk = bs.get(start..end)?;
+        match (&mut rt.null_map, len_nm > 0) {
+            (Some(nm), true) => nm.extend_from_slice(&chk[..n]),
+            (Some(nm), false) => nm.resize(nm.len() + n, 0),
+            (None, true) => {
+                let mut nm = vec![0u8; rt.size];
+                nm.extend_from_slice(&chk[..n]);
+                rt.null_map = Some(nm);
+            }
+            (None, false) => {}
+        }
+        if let Some(om) = &mut rt.offset_map {
+            let base = rt.data.len() as u32;
+            let oms = &chk[len_nm..len_nm + len_om];
+            for i in 1..=n {
+                let o = u32::from_le_bytes(oms[i * 4..i * 4 + 4].try_into().unwrap());
+                om.push(base + o);
+            }
+        }
+        rt.data.extend_from_slice(&chk[len_nm + len_om..]);
+        rt.size += n;
+        ofs = end;
+    }
+    Some(rt)
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use std::path::Path;
+
+    fn chunk(typ: BqlType, n: usize, data: &[u8], om: Option<Vec<u32>>) -> BaseChunk {
+        BaseChunk {
+            btype: typ,
+            size: n,
+            data: data.to_vec(),
+            null_map: None,
+            offset_map: om,
+            lc_dict_data: None,
+        }
+    }
+
+    #[test]
+    fn test_tablog_append_and_read() -> MetaResult<()> {
+        let dp = "/tmp/tablog_test";
+        if Path::new(dp).exists() {
+            fs::remove_dir_all(dp)?;
+        }
+        let tl = TabLog::new(dp, 7);
+        let cols = [(1, BqlType::UInt(16)), (2, BqlType::String)];
+        let chks = tl.read_columns(&cols)?;
+        assert_eq!(chks[0].size, 0);
+        assert_eq!(chks[1].offset_map, Some(vec![0]));
+
+        let c1 = chunk(BqlType::UInt(16), 2, &[1, 0, 2, 0], None);
+        let c2 = chunk(BqlType::String, 2, b"\x01a\x02bc", Some(vec![0, 2, 5]));
+        tl.append(&[(1, &c1), (2, &c2)], true)?;
+        let mut c1 = chunk(BqlType::UInt(16), 1, &[3, 0], None);
+        c1.null_map = Some(vec![1]);
+        let c2 = chunk(BqlType::String, 1, b"\x01d", Some(vec![0, 2]));
+        tl.append(&[(1, &c1), (2, &c2)], false)?;
+
+        //the torn tail is not read, and truncated by the next append
+        let siz = tl.read_sizes()?[&1];
+        OpenOptions::new()
+            .append(true)
+            .open(tl.column_path(1))?
+            .write_all(&[9u8; 5])?;
+        let chks = tl.read_columns(&cols)?;
+        assert_eq!(chks[0].size, 3);
+        assert_eq!(chks[0].data, vec![1, 0, 2, 0, 3, 0]);
+        assert_eq!(chks[0].null_map, Some(vec![0, 0, 1]));
+        assert_eq!(chks[1].data, b"\x01a\x02bc\x01d".to_vec());
+        assert_eq!(chks[1].offset_map, Some(vec![0, 2, 5, 7]));
+        assert_eq!(chks[1].null_map, None);
+
+        let c1 = chunk(BqlType::UInt(16), 1, &[4, 0], None);
+        let c2 = chunk(BqlType::String, 1, b"", Some(vec![0, 0]));
+        tl.append(&[(1, &c1), (2, &c2)], false)?;
+        assert_eq!(fs::metadata(tl.column_path(1))?.len(), tl.read_sizes()?[&1]);
+        assert!(tl.read_sizes()?[&1] > siz);
+        let chks = tl.read_columns(&cols)?;
+        assert_eq!(chks[0].data, vec![1, 0, 2, 0, 3, 0, 4, 0]);
+        assert_eq!(chks[1].offset_map, Some(vec![0, 2, 5, 7, 7]));
+
+        Ok(())
+    }
+}
@@ -8,8 +8,13 @@ use std::{
     str::FromStr,
 };
 
-use arrow::datatypes::DataType;
+use arrow::{
+    array::{make_array, ArrayData, ArrayRef},
+    buffer::Buffer,
+    datatypes::{DataType, Field, TimeUnit},
+};
 use base::bytes_cat;
+use base::codec::decode_varint64;
 use base::datetimes::TimeZoneId;
 use base::strings::BytesTrim;
 use num_traits::PrimInt;
@@ -24,6 +29,7 @@ macro_rules! to_qualified_key {
     ($($e:expr),* $(,)*) => {[$($e),*].join(".")}
 }
 
+///NOTE new fields should be appended to the end for old stored ColumnInfos
 #[derive(PartialEq, Debug, Default, Copy, Clone)]
 #[repr(C, packed)]
 pub struct ColumnInfo {
@@ -31,6 +37,7 @@ pub struct ColumnInfo {
     pub is_primary_key: bool,
     pub is_nullable: bool,
     pub ordinal: u32,
+    pub codec: ColumnCodec,
 }
 
 //FIXME add ids to Tab/Col model?
@@ -40,6 +47,8 @@ pub struct Table {
     pub dbname: String,
     pub columns: Vec<(String, ColumnInfo)>,
     pub tab_info: TableInfo,
+    ///the names and values of the Enum8/Enum16 columns, by the column names
+    pub enum_values: Vec<(String, EnumValues)>,
 }
 
 impl Table {
@@ -60,6 +69,13 @@ pub struct TableInfo {
     pub partition_cols: String,
     pub primary_keys: String,
     pub settings: HashMap<String, String>,
+    ///rows expire when the Date/DateTime column plus the interval is not
+    ///after now, no TTL if empty
+    pub ttl_col: String,
+    pub ttl_secs: u64,
+    ///the column names in the parameters of the engine, e.g. the version
+    ///column of ReplacingMergeTree
+    pub engine_params: Vec<String>,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Hash, Eq)]
@@ -67,12 +83,30 @@ pub struct TableInfo {
 pub enum EngineType {
     Default = 0,
     BaseStorage = 1,
+    ///the rows with the same primary key in a part are collapsed into the one
+    ///of the max version(or the last written one) when the part is merged
+    ReplacingMergeTree = 2,
+    ///the rows with the same primary key in a part are collapsed into one row
+    ///with the numeric columns summed when the part is merged
+    SummingMergeTree = 3,
+    ///the rows with the same primary key in a part are collapsed into one row
+    ///with the states of the AggregateFunction columns merged
+    AggregatingMergeTree = 4,
+    ///the rows are kept in memory only, lost when the server is restarted
+    Memory = 5,
+    ///the rows are appended into one file per column without partitions
+    Log = 6,
 }
 
 impl EngineType {
     pub fn from_str(v: &str) -> MetaResult<Self> {
         match v {
             "BaseStorage" => Ok(EngineType::BaseStorage),
+            "ReplacingMergeTree" => Ok(EngineType::ReplacingMergeTree),
+            "SummingMergeTree" => Ok(EngineType::SummingMergeTree),
+            "AggregatingMergeTree" => Ok(EngineType::AggregatingMergeTree),
+            "Memory" => Ok(EngineType::Memory),
+            "Log" => Ok(EngineType::Log),
             _ => Err(MetaError::UnknownEngineTypeConversionError),
         }
     }
@@ -91,6 +125,173 @@ impl Default for EngineType {
     }
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Hash, Eq)]
+#[repr(u8)]
+pub enum CodecType {
+    None = 0,
+    LZ4 = 1,
+    ZSTD = 2,
+    Delta = 3,
+    DoubleDelta = 4,
+    Gorilla = 5,
+    T64 = 6,
+}
+
+impl CodecType {
+    pub fn from_str(v: &str) -> MetaResult<Self> {
+        match v.to_ascii_uppercase().as_str() {
+            "NONE" => Ok(CodecType::None),
+            "LZ4" => Ok(CodecType::LZ4),
+            "ZSTD" => Ok(CodecType::ZSTD),
+            "DELTA" => Ok(CodecType::Delta),
+            "DOUBLEDELTA" => Ok(CodecType::DoubleDelta),
+            "GORILLA" => Ok(CodecType::Gorilla),
+            "T64" => Ok(CodecType::T64),
+            _ => Err(MetaError::InvalidCodecError(v.to_string())),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CodecType::None => "NONE",
+            CodecType::LZ4 => "LZ4",
+            CodecType::ZSTD => "ZSTD",
+            CodecType::Delta => "Delta",
+            CodecType::DoubleDelta => "DoubleDelta",
+            CodecType::Gorilla => "Gorilla",
+            CodecType::T64 => "T64",
+        }
+    }
+
+    #[inline(always)]
+    pub fn is_compression(self) -> bool {
+        matches!(self, CodecType::LZ4 | CodecType::ZSTD)
+    }
+}
+
+impl Default for CodecType {
+    fn default() -> Self {
+        CodecType::None
+    }
+}
+
+///The codecs of a column, data are firstly transformed(Delta, DoubleDelta,
+///Gorilla, T64) then compressed(LZ4, ZSTD) in blocks
+#[derive(PartialEq, Debug, Default, Copy, Clone)]
+#[repr(C, packed)]
+pub struct ColumnCodec {
+    pub transform: CodecType,
+    pub compression: CodecType,
+    pub level: u8,
+}
+
+impl ColumnCodec {
+    #[inline(always)]
+    pub fn is_none(&self) -> bool {
+        self.transform == CodecType::None && self.compression == CodecType::None
+    }
+
+    ///add a codec into the chain, at most one transform and one compression
+    pub fn add(&mut self, typ: CodecType, level: Option<u8>) -> MetaResult<()> {
+        if typ == CodecType::None {
+            return Ok(());
+        }
+        if typ.is_compression() {
+            if self.compression != CodecType::None {
+                return Err(MetaError::InvalidCodecError(
+                    "more than one compression codec".to_string(),
+                ));
+            }
+            self.compression = typ;
+            self.level = match (typ, level) {
+                (CodecType::ZSTD, Some(l)) if l >= 1 && l <= 22 => l,
+                (CodecType::ZSTD, None) => 1,
+                (CodecType::LZ4, None) => 0,
+                _ => {
+                    return Err(MetaError::InvalidCodecError(format!(
+                        "invalid level for {}",
+                        typ.name()
+                    )))
+                }
+            };
+        } else {
+            if self.transform != CodecType::None || self.compression != CodecType::None {
+                //NOTE transform can only be the first codec in the chain
+                return Err(MetaError::InvalidCodecError(format!(
+                    "{} should be the first codec",
+                    typ.name()
+                )));
+            }
+            self.transform = typ;
+        }
+        Ok(())
+    }
+
+    ///the CODEC clause of the column, empty for no codec
+    pub fn to_vec(&self) -> Vec<u8> {
+        if self.is_none() {
+            return vec![];
+        }
+        let mut specs = vec![];
+        if self.transform != CodecType::None {
+            specs.push(self.transform.name().to_string());
+        }
+        match self.compression {
+            CodecType::ZSTD => specs.push(format!("ZSTD({})", self.level)),
+            CodecType::None => {}
+            c => specs.push(c.name().to_string()),
+        }
+        format!("CODEC({})", specs.join(", ")).into_bytes()
+    }
+
+    ///check whether the codec can be applied to the column type
+    pub fn validate(&self, typ: BqlType) -> MetaResult<()> {
+        if self.is_none() {
+            return Ok(());
+        }
+        let typ = typ.state_type();
+        let siz = match typ {
+            _ if typ.is_blob() => {
+                return Err(MetaError::InvalidCodecError(
+                    "codec for String or Array is not supported".to_string(),
+                ))
+            }
+            _ => typ.size()?,
+        };
+        let is_intlike = matches!(
+            typ,
+            BqlType::UInt(_)
+                | BqlType::Int(_)
+                | BqlType::Decimal(_, _)
+                | BqlType::Date
+                | BqlType::DateTime
+                | BqlType::DateTimeTz(_)
+                | BqlType::DateTime64(_, _)
+                | BqlType::Enum8
+                | BqlType::Enum16
+                | BqlType::Ipv4
+                | BqlType::LowCardinalityString
+                | BqlType::LowCardinalityTinyText
+        );
+        let ok = match self.transform {
+            CodecType::Delta | CodecType::DoubleDelta | CodecType::T64 => {
+                is_intlike && matches!(siz, 1 | 2 | 4 | 8)
+            }
+            CodecType::Gorilla => matches!(typ, BqlType::Float(32) | BqlType::Float(64)),
+            _ => true,
+        };
+        if ok {
+            Ok(())
+        } else {
+            Err(MetaError::InvalidCodecError(format!(
+                "{} can not be applied to {}",
+                self.transform.name(),
+                String::from_utf8_lossy(&typ.to_vec()?)
+            )))
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Hash, Eq)]
 #[repr(C)]
 pub enum BqlType {
@@ -108,6 +309,26 @@ pub enum BqlType {
     /// For backward compatibility, DateTime with timezone is appended here
     DateTimeTz(TimeZoneId),
     Uuid,
+    ///the intermediate states of the aggregate function over the values of
+    ///the numeric type of the kind and the bits
+    AggregateFunction(AggFunc, NumericKind, u8),
+    ///the arrays of the values of the element type of the kind, the u8 is
+    ///the bits of the numeric types or the length of FixedString
+    Array(ElemKind, u8),
+    ///the 256-bit integers, of which the bits do not fit in Int/UInt
+    Int256,
+    UInt256,
+    ///the precision, and the time zone in the quarters of an hour which keeps
+    ///BqlType in 8 bytes, None for the time zone of the server
+    DateTime64(u8, Option<i8>),
+    ///the values of the names, which are kept by the column in MetaStore since
+    ///they do not fit in BqlType
+    Enum8,
+    Enum16,
+    ///the IPv4 addresses in the u32 numbers, and the IPv6 addresses in 16
+    ///bytes in the network order like Uuid
+    Ipv4,
+    Ipv6,
 }
 
 impl Default for BqlType {
@@ -116,6 +337,63 @@ impl Default for BqlType {
     }
 }
 
+///the aggregate functions of which the states are just the values of the
+///argument type, so the states are merged by the functions themselves
+#[derive(Copy, Clone, Debug, PartialEq, Hash, Eq)]
+#[repr(u8)]
+pub enum AggFunc {
+    Sum = 0,
+    Min = 1,
+    Max = 2,
+    Any = 3,
+    AnyLast = 4,
+}
+
+impl AggFunc {
+    pub fn from_str(v: &str) -> MetaResult<Self> {
+        match v {
+            "sum" => Ok(AggFunc::Sum),
+            "min" => Ok(AggFunc::Min),
+            "max" => Ok(AggFunc::Max),
+            "any" => Ok(AggFunc::Any),
+            "anyLast" => Ok(AggFunc::AnyLast),
+            _ => Err(MetaError::UnsupportedAggregateFunctionError(v.to_string())),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            AggFunc::Sum => "sum",
+            AggFunc::Min => "min",
+            AggFunc::Max => "max",
+            AggFunc::Any => "any",
+            AggFunc::AnyLast => "anyLast",
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Hash, Eq)]
+#[repr(u8)]
+pub enum NumericKind {
+    UInt = 0,
+    Int = 1,
+    Float = 2,
+}
+
+///the kinds of the scalar types which can be the elements of Array
+#[derive(Copy, Clone, Debug, PartialEq, Hash, Eq)]
+#[repr(u8)]
+pub enum ElemKind {
+    UInt = 0,
+    Int = 1,
+    Float = 2,
+    Date = 3,
+    DateTime = 4,
+    String = 5,
+    FixedString = 6,
+    Uuid = 7,
+}
+
 // macro_rules! gen_str_to_bql {
 //     ( $item:ident, $($typ:ident,)*) => {
 //         match $item {
@@ -139,14 +417,23 @@ impl BqlType {
         match self {
             BqlType::Int(siz) => Ok(siz / 8),
             BqlType::UInt(siz) => Ok(siz / 8),
+            BqlType::Int256 | BqlType::UInt256 => Ok(32),
             BqlType::Float(siz) => Ok(siz / 8),
             BqlType::DateTime | BqlType::DateTimeTz(_) => Ok(4),
+            BqlType::DateTime64(_, _) => Ok(8),
             BqlType::Date => Ok(2),
+            BqlType::Enum8 => Ok(1),
+            BqlType::Enum16 => Ok(2),
+            BqlType::Ipv4 => Ok(4),
             BqlType::Decimal(p, _s) => {
                 if p < 10 {
                     Ok(4)
                 } else if p <= 18 {
                     Ok(8)
+                } else if p <= 38 {
+                    Ok(16)
+                } else if p <= 76 {
+                    Ok(32)
                 } else {
                     Err(MetaError::UnsupportedBqlTypeError)
                 }
@@ -154,7 +441,8 @@ impl BqlType {
             BqlType::LowCardinalityString => Ok(4),
             BqlType::LowCardinalityTinyText => Ok(1),
             BqlType::FixedString(siz) => Ok(siz),
-            BqlType::Uuid => Ok(16),
+            BqlType::Uuid | BqlType::Ipv6 => Ok(16),
+            BqlType::AggregateFunction(_, _, bits) => Ok(bits / 8),
             _ => Err(MetaError::NoFixedSizeDataTypeError),
         }
     }
@@ -163,6 +451,77 @@ impl BqlType {
         Ok(self.size()? as usize)
     }
 
+    ///the type of the values which the states of AggregateFunction are
+    ///stored as, or the type itself for others
+    pub fn state_type(self) -> BqlType {
+        match self {
+            BqlType::AggregateFunction(_, NumericKind::UInt, bits) => BqlType::UInt(bits),
+            BqlType::AggregateFunction(_, NumericKind::Int, bits) => BqlType::Int(bits),
+            BqlType::AggregateFunction(_, NumericKind::Float, bits) => {
+                BqlType::Float(bits)
+            }
+            typ => typ,
+        }
+    }
+
+    ///the type of the elements of Array, None for others
+    pub fn elem_type(self) -> Option<BqlType> {
+        match self {
+            BqlType::Array(kind, p) => Some(match kind {
+                ElemKind::UInt => BqlType::UInt(p),
+                ElemKind::Int => BqlType::Int(p),
+                ElemKind::Float => BqlType::Float(p),
+                ElemKind::Date => BqlType::Date,
+                ElemKind::DateTime => BqlType::DateTime,
+                ElemKind::String => BqlType::String,
+                ElemKind::FixedString => BqlType::FixedString(p),
+                ElemKind::Uuid => BqlType::Uuid,
+            }),
+            _ => None,
+        }
+    }
+
+    ///the Array of the elements of the type, None if the type can not be the
+    ///element
+    pub fn array_of(elem: BqlType) -> Option<BqlType> {
+        match elem {
+            BqlType::UInt(bits) => Some(BqlType::Array(ElemKind::UInt, bits)),
+            BqlType::Int(bits) => Some(BqlType::Array(ElemKind::Int, bits)),
+            BqlType::Float(bits) => Some(BqlType::Array(ElemKind::Float, bits)),
+            BqlType::Date => Some(BqlType::Array(ElemKind::Date, 0)),
+            BqlType::DateTime => Some(BqlType::Array(ElemKind::DateTime, 0)),
+            BqlType::String => Some(BqlType::Array(ElemKind::String, 0)),
+            BqlType::FixedString(len) => Some(BqlType::Array(ElemKind::FixedString, len)),
+            BqlType::Uuid => Some(BqlType::Array(ElemKind::Uuid, 0)),
+            _ => None,
+        }
+    }
+
+    ///whether the values are in variable lengths, which are stored with the
+    ///offset map like String
+    #[inline(always)]
+    pub fn is_blob(self) -> bool {
+        matches!(self, BqlType::String | BqlType::Array(_, _))
+    }
+
+    ///whether the values of this type can be converted into the values of
+    ///another convertible type, used by the MODIFY COLUMN
+    ///
+    ///NOTE the integers wider than 64 bits are not convertible for now
+    #[inline(always)]
+    pub fn is_numeric_convertible(self) -> bool {
+        matches!(
+            self,
+            BqlType::UInt(8 | 16 | 32 | 64)
+                | BqlType::Int(8 | 16 | 32 | 64)
+                | BqlType::Float(32)
+                | BqlType::Float(64)
+                | BqlType::Date
+                | BqlType::DateTime
+                | BqlType::DateTimeTz(_)
+        )
+    }
+
     pub fn to_vec(self) -> MetaResult<Vec<u8>> {
         match self {
             BqlType::UnInit => Ok(b"UnInit".to_vec()),
@@ -176,6 +535,8 @@ impl BqlType {
                 let n = itoa::write(&mut bi[..], len)?;
                 Ok(bytes_cat!(b"Int", &bi[..n]))
             }
+            BqlType::Int256 => Ok(b"Int256".to_vec()),
+            BqlType::UInt256 => Ok(b"UInt256".to_vec()),
             BqlType::Float(len) => {
                 let mut bi = [0u8; 4];
                 let n = itoa::write(&mut bi[..], len)?;
@@ -192,6 +553,20 @@ impl BqlType {
             BqlType::DateTimeTz(tz) => {
                 Ok(bytes_cat!(b"DateTime('", tz.name().as_bytes(), b"')"))
             }
+            BqlType::DateTime64(p, tz) => {
+                let mut bp = [0u8; 4];
+                let np = itoa::write(&mut bp[..], p)?;
+                match tz {
+                    Some(q) => Ok(bytes_cat!(
+                        b"DateTime64(",
+                        &bp[..np],
+                        b", '",
+                        TimeZoneId::from_quarters(q).name().as_bytes(),
+                        b"')"
+                    )),
+                    None => Ok(bytes_cat!(b"DateTime64(", &bp[..np], b")")),
+                }
+            }
             BqlType::Date => Ok(b"Date".to_vec()),
             BqlType::String => Ok(b"String".to_vec()),
             BqlType::LowCardinalityString | BqlType::LowCardinalityTinyText => {
@@ -203,6 +578,24 @@ impl BqlType {
                 Ok(bytes_cat!(b"FixedString(", &bi[..n], b")"))
             }
             BqlType::Uuid => Ok(b"UUID".to_vec()),
+            BqlType::Ipv4 => Ok(b"IPv4".to_vec()),
+            BqlType::Ipv6 => Ok(b"IPv6".to_vec()),
+            //NOTE the full types with the names are given by EnumValues::to_vec
+            BqlType::Enum8 => Ok(b"Enum8".to_vec()),
+            BqlType::Enum16 => Ok(b"Enum16".to_vec()),
+            //NOTE the states are the plain values, which is what the
+            //SimpleAggregateFunction of ClickHouse is on the wire
+            BqlType::AggregateFunction(f, _, _) => Ok(bytes_cat!(
+                b"SimpleAggregateFunction(",
+                f.name().as_bytes(),
+                b", ",
+                &self.state_type().to_vec()?,
+                b")"
+            )),
+            BqlType::Array(_, _) => {
+                let elem = self.elem_type().ok_or(MetaError::UnsupportedBqlTypeError)?;
+                Ok(bytes_cat!(b"Array(", &elem.to_vec()?, b")"))
+            }
         }
     }
 
@@ -216,16 +609,22 @@ impl BqlType {
             b"Int16" => Ok(BqlType::Int(16)),
             b"Int32" => Ok(BqlType::Int(32)),
             b"Int64" => Ok(BqlType::Int(64)),
+            b"Int128" => Ok(BqlType::Int(128)),
+            b"Int256" => Ok(BqlType::Int256),
             b"UInt8" => Ok(BqlType::UInt(8)),
             b"UInt16" => Ok(BqlType::UInt(16)),
             b"UInt32" => Ok(BqlType::UInt(32)),
             b"UInt64" => Ok(BqlType::UInt(64)),
+            b"UInt128" => Ok(BqlType::UInt(128)),
+            b"UInt256" => Ok(BqlType::UInt256),
             b"Float16" => Ok(BqlType::Float(16)),
             b"Float32" => Ok(BqlType::Float(32)),
             b"Float64" => Ok(BqlType::Float(64)),
             b"Date" => Ok(BqlType::Date),
             b"String" => Ok(BqlType::String),
             b"UUID" => Ok(BqlType::Uuid),
+            b"IPv4" => Ok(BqlType::Ipv4),
+            b"IPv6" => Ok(BqlType::Ipv6),
             b"LowCardinality(String)" => Ok(BqlType::LowCardinalityString),
             b"LowCardinality(TinyText)" => Ok(BqlType::LowCardinalityTinyText),
             datetime_item if datetime_item.starts_with(b"DateTime") => {
@@ -234,6 +633,25 @@ impl BqlType {
             decimal_item if decimal_item.starts_with(b"Decimal") => {
                 Self::_decimal_type(decimal_item)
             }
+            enum_item if enum_item.starts_with(b"Enum") => {
+                EnumValues::from_bytes(enum_item).map(|(typ, _)| typ)
+            }
+            agg_item if agg_item.starts_with(b"AggregateFunction") => {
+                Self::_aggregate_function_type(&agg_item[b"AggregateFunction".len()..])
+                    .ok_or_else(|| conversion_err!(item))
+            }
+            agg_item if agg_item.starts_with(b"SimpleAggregateFunction") => {
+                Self::_aggregate_function_type(
+                    &agg_item[b"SimpleAggregateFunction".len()..],
+                )
+                .ok_or_else(|| conversion_err!(item))
+            }
+            [b'A', b'r', b'r', b'a', b'y', b'(', elem @ .., b')'] => {
+                Self::from_bytes(elem.trim())
+                    .ok()
+                    .and_then(Self::array_of)
+                    .ok_or_else(|| conversion_err!(item))
+            }
             fixed_string_item if fixed_string_item.starts_with(b"FixedString") => {
                 match &fixed_string_item[b"FixedString".len()..] {
                     [b'(', len @ .., b')'] => Ok(BqlType::FixedString(
@@ -257,10 +675,55 @@ impl BqlType {
                 }
                 _ => Err(conversion_err!(datetime_item)),
             },
+            // DateTime64(p[, 'tz'])
+            [b'6', b'4', b'(', args @ .., b')'] => {
+                let mut args_iter = args.splitn(2, |v| *v == b',');
+                let p = args_iter
+                    .next()
+                    .and_then(Self::_parse_num)
+                    .ok_or_else(|| conversion_err!(datetime_item))?;
+                //NOTE the stored ticks are read zero-copy as the arrow time units,
+                //     so only the precisions of the units are supported locally
+                if !matches!(p, 0 | 3 | 6 | 9) {
+                    return Err(MetaError::UnsupportedBqlTypeError);
+                }
+                match args_iter.next().map(|tz| tz.trim()) {
+                    None => Ok(Self::DateTime64(p, None)),
+                    Some([b'\'', tz @ .., b'\'']) => {
+                        let tz = unsafe { std::str::from_utf8_unchecked(tz) };
+                        let tz = TimeZoneId::from_str(tz.trim())?;
+                        Ok(Self::DateTime64(p, Some(tz.quarters())))
+                    }
+                    _ => Err(conversion_err!(datetime_item)),
+                }
+            }
             _ => Err(conversion_err!(datetime_item)),
         }
     }
 
+    // (func, type) of AggregateFunction(func, type), only for numeric types
+    fn _aggregate_function_type(args: &[u8]) -> Option<Self> {
+        let args = match args {
+            [b'(', args @ .., b')'] => args,
+            _ => return None,
+        };
+        let i = args.iter().position(|b| *b == b',')?;
+        let f = std::str::from_utf8(args[..i].trim()).ok()?;
+        let f = AggFunc::from_str(f).ok()?;
+        match Self::from_bytes(args[i + 1..].trim()).ok()? {
+            BqlType::UInt(bits) => {
+                Some(BqlType::AggregateFunction(f, NumericKind::UInt, bits))
+            }
+            BqlType::Int(bits) => {
+                Some(BqlType::AggregateFunction(f, NumericKind::Int, bits))
+            }
+            BqlType::Float(bits) => {
+                Some(BqlType::AggregateFunction(f, NumericKind::Float, bits))
+            }
+            _ => None,
+        }
+    }
+
     fn _parse_num(bytes: &[u8]) -> Option<u8> {
         btoi::btou(bytes.trim()).ok()
     }
@@ -294,6 +757,16 @@ impl BqlType {
                 18,
                 Self::_parse_num(s).ok_or_else(|| conversion_err!(decimal_item))?,
             ),
+            // Decimal128(s) => Decimal(38, s)
+            [b'1', b'2', b'8', b'(', s @ .., b')'] => (
+                38,
+                Self::_parse_num(s).ok_or_else(|| conversion_err!(decimal_item))?,
+            ),
+            // Decimal256(s) => Decimal(76, s)
+            [b'2', b'5', b'6', b'(', s @ .., b')'] => (
+                76,
+                Self::_parse_num(s).ok_or_else(|| conversion_err!(decimal_item))?,
+            ),
             _ => return Err(conversion_err!(decimal_item)),
         };
         // Range of precision and scale:
@@ -309,6 +782,154 @@ impl BqlType {
     }
 }
 
+///the names and the values of Enum8/Enum16 in the order of their definition
+#[derive(PartialEq, Debug, Default, Clone)]
+pub struct EnumValues(pub Vec<(String, i16)>);
+
+impl EnumValues {
+    ///parse the type like Enum8('a' = 1, 'b' = 2) into the type and its names
+    ///and values, the names and the values should be unique
+    pub fn from_bytes(item: &[u8]) -> MetaResult<(BqlType, Self)> {
+        let (typ, mut rest) = match item {
+            [b'E', b'n', b'u', b'm', b'8', b'(', ps @ .., b')'] => (BqlType::Enum8, ps),
+            [b'E', b'n', b'u', b'm', b'1', b'6', b'(', ps @ .., b')'] => {
+                (BqlType::Enum16, ps)
+            }
+            _ => return Err(conversion_err!(item)),
+        };
+        let (min, max) = match typ {
+            BqlType::Enum8 => (i8::MIN as i16, i8::MAX as i16),
+            _ => (i16::MIN, i16::MAX),
+        };
+        let mut rt = Vec::new();
+        loop {
+            let (name, n) =
+                Self::_parse_name(rest.trim()).ok_or_else(|| conversion_err!(item))?;
+            rest = match rest.trim()[n..].trim() {
+                [b'=', rest @ ..] => rest,
+                _ => return Err(conversion_err!(item)),
+            };
+            let i = rest.iter().position(|b| *b == b',').unwrap_or(rest.len());
+            let v = btoi::btoi::<i16>(rest[..i].trim())
+                .ok()
+                .filter(|v| (min..=max).contains(v))
+                .ok_or_else(|| conversion_err!(item))?;
+            if rt.iter().any(|(n, u)| *n == name || *u == v) {
+                return Err(conversion_err!(item));
+            }
+            rt.push((name, v));
+            if i == rest.len() {
+                return Ok((typ, EnumValues(rt)));
+            }
+            rest = &rest[i + 1..];
+        }
+    }
+
+    //the quoted name at the start of bs and the length of its quoted form,
+    //the quotes are escaped by '' in sql or by \ on the wire
+    fn _parse_name(bs: &[u8]) -> Option<(String, usize)> {
+        if bs.first() != Some(&b'\'') {
+            return None;
+        }
+        let mut name = Vec::new();
+        let mut i = 1;
+        while i < bs.len() {
+            match (bs[i], bs.get(i + 1)) {
+                (b'\\', Some(b)) | (b'\'', Some(b @ b'\'')) => {
+                    name.push(*b);
+                    i += 2;
+                }
+                (b'\'', _) => return Some((String::from_utf8(name).ok()?, i + 1)),
+                (b, _) => {
+                    name.push(b);
+                    i += 1;
+                }
+            }
+        }
+        None
+    }
+
+    ///the full type of Enum8 or Enum16 with the names and the values
+    pub fn to_vec(&self, typ: BqlType) -> MetaResult<Vec<u8>> {
+        let mut rt = typ.to_vec()?;
+        rt.push(b'(');
+        for (i, (name, v)) in self.0.iter().enumerate() {
+            if i > 0 {
+                rt.extend_from_slice(b", ");
+            }
+            rt.push(b'\'');
+            for b in name.bytes() {
+                if b == b'\'' || b == b'\\' {
+                    rt.push(b'\\');
+                }
+                rt.push(b);
+            }
+            rt.extend_from_slice(b"' = ");
+            rt.extend_from_slice(v.to_string().as_bytes());
+        }
+        rt.push(b')');
+        Ok(rt)
+    }
+
+    pub fn value_of(&self, name: &[u8]) -> Option<i16> {
+        self.0
+            .iter()
+            .find(|(n, _)| n.as_bytes() == name)
+            .map(|(_, v)| *v)
+    }
+
+    pub fn name_of(&self, v: i16) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(_, u)| *u == v)
+            .map(|(n, _)| n.as_str())
+    }
+}
+
+///convert the little endian values of type `from` into the values of type
+///`to`, integers are truncated or extended like the CAST in ClickHouse
+pub fn convert_numeric_values(
+    src: &[u8],
+    from: BqlType,
+    to: BqlType,
+) -> MetaResult<Vec<u8>> {
+    if !from.is_numeric_convertible() || !to.is_numeric_convertible() {
+        return Err(MetaError::UnsupportedBqlTypeError);
+    }
+    let wf = from.size_in_usize()?;
+    let wt = to.size_in_usize()?;
+    let n = src.len() / wf;
+    let mut rt = Vec::with_capacity(n * wt);
+    let mut bs = [0u8; 8];
+    for i in 0..n {
+        bs[..wf].copy_from_slice(&src[i * wf..(i + 1) * wf]);
+        bs[wf..].fill(0);
+        let u = u64::from_le_bytes(bs);
+        let (iv, fv) = match from {
+            BqlType::Float(32) => {
+                let f = f32::from_bits(u as u32) as f64;
+                (f as i128, f)
+            }
+            BqlType::Float(_) => {
+                let f = f64::from_bits(u);
+                (f as i128, f)
+            }
+            BqlType::Int(_) => {
+                let sh = 64 - wf * 8;
+                let v = ((u << sh) as i64 >> sh) as i128;
+                (v, v as f64)
+            }
+            _ => (u as i128, u as f64),
+        };
+        match to {
+            BqlType::Float(32) => rt.extend_from_slice(&(fv as f32).to_le_bytes()),
+            BqlType::Float(_) => rt.extend_from_slice(&fv.to_le_bytes()),
+            _ => rt.extend_from_slice(&(iv as u64).to_le_bytes()[..wt]),
+        }
+    }
+    Ok(rt)
+}
+
 impl AsBytes for usize {}
 impl AsBytes for u64 {}
 impl AsBytes for u32 {}
@@ -494,6 +1115,155 @@ impl std::fmt::Debug for BaseChunk {
     }
 }
 
+impl BaseChunk {
+    ///convert the chunk into the arrow array of the column type typ, the NULLs
+    ///are kept only for the nullable column
+    ///
+    ///NOTE the values are not converted, so the types of which the values are
+    ///     stored differently from arrow, e.g. LowCardinality, are not supported
+    pub fn to_arrow_array(
+        &self,
+        typ: BqlType,
+        is_nullable: bool,
+    ) -> MetaResult<ArrayRef> {
+        let n = self.size;
+        let dt = btype_to_arrow_type(typ)?;
+        let data = match typ {
+            BqlType::String => {
+                let om = self
+                    .offset_map
+                    .as_ref()
+                    .filter(|om| om.len() == n + 1)
+                    .ok_or(MetaError::ChunkDataMismatchError)?;
+                let om: Vec<i64> = om.iter().map(|o| *o as i64).collect();
+                ArrayData::builder(dt)
+                    .len(n)
+                    .add_buffer(Buffer::from_slice_ref(&om))
+                    .add_buffer(Buffer::from_slice_ref(&self.data))
+            }
+            BqlType::Array(_, _) => {
+                let om = self
+                    .offset_map
+                    .as_ref()
+                    .filter(|om| om.len() == n + 1)
+                    .ok_or(MetaError::ChunkDataMismatchError)?;
+                let data = Buffer::from_slice_ref(&self.data);
+                return Ok(make_array(gen_list_array_data(typ, om, data)?));
+            }
+            BqlType::LowCardinalityString | BqlType::LowCardinalityTinyText => {
+                return Err(MetaError::UnsupportedBqlTypeError)
+            }
+            _ if self.data.len() != n * typ.size_in_usize()? => {
+                return Err(MetaError::ChunkDataMismatchError)
+            }
+            _ => ArrayData::builder(dt)
+                .len(n)
+                .add_buffer(Buffer::from_slice_ref(&self.data)),
+        };
+        let nm = self
+            .null_map
+            .as_ref()
+            .filter(|nm| is_nullable && nm.len() == n);
+        let data = match nm {
+            Some(nm) if nm.iter().any(|b| *b != 0) => {
+                //1 for NULL in the null map, but 1 for valid in arrow
+                let mut bitmap = vec![0u8; (n + 7) / 8];
+                for (i, b) in nm.iter().enumerate() {
+                    if *b == 0 {
+                        bitmap[i >> 3] |= 1 << (i & 7);
+                    }
+                }
+                data.null_bit_buffer(Buffer::from(bitmap))
+            }
+            _ => data,
+        };
+        Ok(make_array(data.build()?))
+    }
+}
+
+///the offsets of the values of the type in bs, i.e. the start of every value
+///and the end of the last one, None if bs is not just of the values
+///
+///NOTE the String values are in the wire format, i.e. prefixed by the varint
+///     of their lengths
+pub fn value_offsets(typ: BqlType, bs: &[u8]) -> Option<Vec<usize>> {
+    if typ == BqlType::String {
+        let mut rt = vec![0];
+        let mut ofs = 0;
+        while ofs < bs.len() {
+            let (len, n) = decode_varint64(&bs[ofs..])?;
+            ofs += n + len as usize;
+            rt.push(ofs);
+        }
+        return if ofs == bs.len() { Some(rt) } else { None };
+    }
+    let siz = typ.size_in_usize().ok().filter(|siz| *siz > 0)?;
+    if bs.len() % siz != 0 {
+        return None;
+    }
+    Some((0..=bs.len() / siz).map(|i| i * siz).collect())
+}
+
+///the indexes of the values which start at the offsets ofs in the value
+///offsets vos, None if any of ofs is not at the boundary of the values
+pub fn value_indexes<T: Copy + Into<u64>>(
+    vos: &[usize],
+    ofs: &[T],
+) -> Option<Vec<usize>> {
+    let mut rt = Vec::with_capacity(ofs.len());
+    let mut j = 0;
+    for o in ofs {
+        let o = (*o).into() as usize;
+        //NOTE the offsets are ascending
+        while j < vos.len() && vos[j] < o {
+            j += 1;
+        }
+        if vos.get(j) != Some(&o) {
+            return None;
+        }
+        rt.push(j);
+    }
+    Some(rt)
+}
+
+///the arrow data of the Array rows, of which the elements are the values in
+///the byte range ofs[i]..ofs[i+1] of the values
+pub fn gen_list_array_data<T: Copy + Into<u64>>(
+    typ: BqlType,
+    ofs: &[T],
+    values: Buffer,
+) -> MetaResult<ArrayData> {
+    let elem = typ.elem_type().ok_or(MetaError::UnsupportedBqlTypeError)?;
+    let n = ofs.len().saturating_sub(1);
+    let (start, end) = match (ofs.first(), ofs.last()) {
+        (Some(s), Some(e)) => ((*s).into() as usize, (*e).into() as usize),
+        _ => return Err(MetaError::ChunkDataMismatchError),
+    };
+    let bs = values
+        .as_slice()
+        .get(start..end)
+        .ok_or(MetaError::ChunkDataMismatchError)?;
+    let vos = value_offsets(elem, bs).ok_or(MetaError::ChunkDataMismatchError)?;
+    let rel: Vec<u64> = ofs.iter().map(|o| (*o).into() - start as u64).collect();
+    let idxs = value_indexes(&vos, &rel).ok_or(MetaError::ChunkDataMismatchError)?;
+    let idxs: Vec<i32> = idxs.into_iter().map(|i| i as i32).collect();
+    let nv = vos.len() - 1;
+    let child = ArrayData::builder(btype_to_arrow_type(elem)?).len(nv);
+    let child = if elem == BqlType::String {
+        let vos: Vec<i64> = vos.iter().map(|o| *o as i64).collect();
+        child
+            .add_buffer(Buffer::from_slice_ref(&vos))
+            .add_buffer(values.slice(start))
+    } else {
+        child.add_buffer(values.slice(start))
+    };
+    Ok(ArrayData::builder(btype_to_arrow_type(typ)?)
+        .len(n)
+        .add_buffer(Buffer::from_slice_ref(&idxs))
+        .add_child_data(child.build()?)
+        .build()?)
+}
+
 pub trait AsKey: AsBytes {
     fn as_key(self) -> Self;
 }
@@ -524,6 +1294,10 @@ impl AsKey for &[u8] {
     }
 }
 
+///the key in the metadata of an arrow field for the full type of the column,
+///which is not told by the arrow type, like Enum8('a' = 1)
+pub const FIELD_BQL_TYPE: &str = "bql_type";
+
 pub fn btype_to_arrow_type(typ: BqlType) -> MetaResult<DataType> {
     match typ {
         BqlType::UInt(bits) if bits == 8 => Ok(DataType::UInt8),
@@ -534,18 +1308,43 @@ pub fn btype_to_arrow_type(typ: BqlType) -> MetaResult<DataType> {
         BqlType::Int(bits) if bits == 16 => Ok(DataType::Int16),
         BqlType::Int(bits) if bits == 32 => Ok(DataType::Int32),
         BqlType::Int(bits) if bits == 64 => Ok(DataType::Int64),
+        BqlType::Int(bits) if bits == 128 => Ok(DataType::Int128),
+        BqlType::UInt(bits) if bits == 128 => Ok(DataType::UInt128),
+        BqlType::Int256 => Ok(DataType::Int256),
+        BqlType::UInt256 => Ok(DataType::UInt256),
         BqlType::Float(bits) if bits == 16 => Ok(DataType::Float16),
         BqlType::Float(bits) if bits == 32 => Ok(DataType::Float32),
         BqlType::Float(bits) if bits == 64 => Ok(DataType::Float64),
         BqlType::DateTime => Ok(DataType::Timestamp32(None)),
         BqlType::DateTimeTz(tz) => Ok(DataType::Timestamp32(Some(tz))),
+        BqlType::DateTime64(p, tz) => Ok(DataType::Timestamp(
+            match p {
+                0 => TimeUnit::Second,
+                3 => TimeUnit::Millisecond,
+                6 => TimeUnit::Microsecond,
+                _ => TimeUnit::Nanosecond,
+            },
+            tz.map(|q| TimeZoneId::from_quarters(q).name().to_string()),
+        )),
         BqlType::Date => Ok(DataType::Date16),
         BqlType::Decimal(p, s) => Ok(DataType::Decimal(p as usize, s as usize)),
+        BqlType::Enum8 => Ok(DataType::Int8),
+        BqlType::Enum16 => Ok(DataType::Int16),
         BqlType::String => Ok(DataType::LargeUtf8),
         BqlType::LowCardinalityString => Ok(DataType::UInt32),
         BqlType::LowCardinalityTinyText => Ok(DataType::UInt8),
         BqlType::FixedString(len) => Ok(DataType::FixedSizeBinary(len as i32)),
-        BqlType::Uuid => Ok(DataType::FixedSizeBinary(16)),
+        BqlType::Uuid | BqlType::Ipv6 => Ok(DataType::FixedSizeBinary(16)),
+        BqlType::Ipv4 => Ok(DataType::UInt32),
+        BqlType::AggregateFunction(_, _, _) => btype_to_arrow_type(typ.state_type()),
+        BqlType::Array(_, _) => {
+            let elem = typ.elem_type().ok_or(MetaError::UnsupportedBqlTypeError)?;
+            Ok(DataType::List(Box::new(Field::new(
+                "item",
+                btype_to_arrow_type(elem)?,
+                false,
+            ))))
+        }
         _ => Err(MetaError::UnknownBqlTypeConversionError(
             String::from_utf8(typ.to_vec().unwrap()).unwrap_or("Unknwon".to_string()),
         )),
@@ -624,6 +1423,11 @@ mod unit_tests {
             BqlType::Decimal(18, 4)
         );
         assert_eq!(BqlType::from_str("Decimal64(9)")?, BqlType::Decimal(18, 9));
+        assert_eq!(BqlType::from_str("Decimal128(4)")?, BqlType::Decimal(38, 4));
+        assert_eq!(
+            BqlType::from_str("Decimal256(20)")?,
+            BqlType::Decimal(76, 20)
+        );
         assert_eq!(
             BqlType::from_str("Decimal64( 18  )")?,
             BqlType::Decimal(18, 18)
@@ -632,6 +1436,10 @@ mod unit_tests {
         assert_eq!(BqlType::from_str("Int32")?, BqlType::Int(32));
         assert_eq!(BqlType::from_str("UInt32")?, BqlType::UInt(32));
         assert_eq!(BqlType::from_str("UInt64")?, BqlType::UInt(64));
+        assert_eq!(BqlType::from_str("Int128")?, BqlType::Int(128));
+        assert_eq!(BqlType::from_str("UInt128")?, BqlType::UInt(128));
+        assert_eq!(BqlType::from_str("Int256")?, BqlType::Int256);
+        assert_eq!(BqlType::from_str("UInt256")?, BqlType::UInt256);
         assert_eq!(BqlType::from_str("Float16")?, BqlType::Float(16));
         assert_eq!(BqlType::from_str("Float32")?, BqlType::Float(32));
         assert_eq!(BqlType::from_str("Float64")?, BqlType::Float(64));
@@ -692,7 +1500,54 @@ mod unit_tests {
             BqlType::from_str("DateTime('Invalid timezone')"),
             Err(_)
         ));
+        assert_eq!(
+            BqlType::from_str("DateTime64(3)")?,
+            BqlType::DateTime64(3, None)
+        );
+        assert_eq!(
+            BqlType::from_str("DateTime64( 6, 'Etc/GMT-8' )")?,
+            BqlType::DateTime64(6, Some(32))
+        );
+        assert!(matches!(BqlType::from_str("DateTime64"), Err(_)));
+        assert!(matches!(BqlType::from_str("DateTime64(2)"), Err(_)));
+        assert!(matches!(BqlType::from_str("DateTime64(3, UTC)"), Err(_)));
         assert_eq!(BqlType::from_str("UUID")?, BqlType::Uuid);
+        assert_eq!(BqlType::from_str("IPv4")?, BqlType::Ipv4);
+        assert_eq!(BqlType::from_str("IPv6")?, BqlType::Ipv6);
+        assert_eq!(
+            BqlType::from_str("AggregateFunction(sum, UInt64)")?,
+            BqlType::AggregateFunction(AggFunc::Sum, NumericKind::UInt, 64)
+        );
+        assert_eq!(
+            BqlType::from_str("SimpleAggregateFunction(anyLast,Float32)")?,
+            BqlType::AggregateFunction(AggFunc::AnyLast, NumericKind::Float, 32)
+        );
+        assert!(matches!(
+            BqlType::from_str("AggregateFunction(uniq, UInt64)"),
+            Err(_)
+        ));
+        assert!(matches!(
+            BqlType::from_str("AggregateFunction(max, String)"),
+            Err(_)
+        ));
+        assert!(matches!(
+            BqlType::from_str("AggregateFunction(max)"),
+            Err(_)
+        ));
+        assert_eq!(
+            BqlType::from_str("Array(UInt16)")?,
+            BqlType::Array(ElemKind::UInt, 16)
+        );
+        assert_eq!(
+            BqlType::from_str("Array(FixedString(3))")?,
+            BqlType::Array(ElemKind::FixedString, 3)
+        );
+        assert_eq!(
+            BqlType::from_str("Array( String )")?,
+            BqlType::Array(ElemKind::String, 0)
+        );
+        assert!(matches!(BqlType::from_str("Array(Array(Int8))"), Err(_)));
+        assert!(matches!(BqlType::from_str("Array(Decimal(9,2))"), Err(_)));
 
         Ok(())
     }
@@ -705,6 +1560,9 @@ mod unit_tests {
         assert_eq!(b"Int32".to_vec(), BqlType::Int(32).to_vec()?);
         assert_eq!(b"UInt32".to_vec(), BqlType::UInt(32).to_vec()?);
         assert_eq!(b"UInt64".to_vec(), BqlType::UInt(64).to_vec()?);
+        assert_eq!(b"Int128".to_vec(), BqlType::Int(128).to_vec()?);
+        assert_eq!(b"UInt256".to_vec(), BqlType::UInt256.to_vec()?);
+        assert_eq!(b"Decimal(76,2)".to_vec(), BqlType::Decimal(76, 2).to_vec()?);
         assert_eq!(b"Float16".to_vec(), BqlType::Float(16).to_vec()?);
         assert_eq!(b"Float32".to_vec(), BqlType::Float(32).to_vec()?);
         assert_eq!(b"Float64".to_vec(), BqlType::Float(64).to_vec()?);
@@ -721,6 +1579,14 @@ mod unit_tests {
             b"DateTime('+08:00')".to_vec(),
             BqlType::DateTimeTz(TimeZoneId::from_str("Etc/GMT-8")?).to_vec()?
         );
+        assert_eq!(
+            b"DateTime64(3)".to_vec(),
+            BqlType::DateTime64(3, None).to_vec()?
+        );
+        assert_eq!(
+            b"DateTime64(9, '+08:00')".to_vec(),
+            BqlType::DateTime64(9, Some(32)).to_vec()?
+        );
         // FIXME: do not support timezone with DST
         // assert_eq!(
         //     b"DateTime('-07:00')".to_vec(),
@@ -736,6 +1602,50 @@ mod unit_tests {
             BqlType::FixedString(0xff).to_vec()?
         );
         assert_eq!(b"UUID".to_vec(), BqlType::Uuid.to_vec()?);
+        assert_eq!(b"IPv4".to_vec(), BqlType::Ipv4.to_vec()?);
+        assert_eq!(b"IPv6".to_vec(), BqlType::Ipv6.to_vec()?);
+        assert_eq!(
+            b"SimpleAggregateFunction(min, Int32)".to_vec(),
+            BqlType::AggregateFunction(AggFunc::Min, NumericKind::Int, 32).to_vec()?
+        );
+        assert_eq!(
+            b"Array(DateTime)".to_vec(),
+            BqlType::Array(ElemKind::DateTime, 0).to_vec()?
+        );
+        assert_eq!(
+            b"Array(Float64)".to_vec(),
+            BqlType::Array(ElemKind::Float, 64).to_vec()?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_enum_values() -> MetaResult<()> {
+        assert_eq!(
+            BqlType::from_str("Enum8('a' = 1, 'b' = 2)")?,
+            BqlType::Enum8
+        );
+        let (typ, ev) = EnumValues::from_bytes(b"Enum16('x,y'=-1000,'it''s' = 7)")?;
+        assert_eq!(typ, BqlType::Enum16);
+        assert_eq!(ev.value_of(b"x,y"), Some(-1000));
+        assert_eq!(ev.value_of(b"it's"), Some(7));
+        assert_eq!(ev.value_of(b"z"), None);
+        assert_eq!(ev.name_of(7), Some("it's"));
+        assert_eq!(ev.name_of(0), None);
+        assert_eq!(
+            ev.to_vec(typ)?,
+            b"Enum16('x,y' = -1000, 'it\\'s' = 7)".to_vec()
+        );
+        assert_eq!(EnumValues::from_bytes(&ev.to_vec(typ)?)?, (typ, ev));
+
+        assert!(BqlType::from_str("Enum8").is_err());
+        assert!(BqlType::from_str("Enum8()").is_err());
+        assert!(BqlType::from_str("Enum8('a' = 128)").is_err());
+        assert!(BqlType::from_str("Enum8('a' = 1, 'a' = 2)").is_err());
+        assert!(BqlType::from_str("Enum8('a' = 1, 'b' = 1)").is_err());
+        assert!(BqlType::from_str("Enum16('a' = 1,)").is_err());
+        assert!(BqlType::from_str("Enum16(a = 1)").is_err());
 
         Ok(())
     }
@@ -775,8 +1685,196 @@ mod unit_tests {
         println!("columns: {:?}", tab.columns);
     }
 
+    #[test]
+    fn test_column_codec() -> MetaResult<()> {
+        let mut codec = ColumnCodec::default();
+        assert!(codec.is_none());
+        codec.add(CodecType::from_str("delta")?, None)?;
+        codec.add(CodecType::from_str("ZSTD")?, Some(3))?;
+        assert_eq!(
+            codec,
+            ColumnCodec {
+                transform: CodecType::Delta,
+                compression: CodecType::ZSTD,
+                level: 3
+            }
+        );
+        assert!(codec.validate(BqlType::DateTime).is_ok());
+        assert!(codec.validate(BqlType::Float(64)).is_err());
+        assert!(codec.validate(BqlType::String).is_err());
+        assert!(codec.add(CodecType::LZ4, None).is_err());
+
+        let mut codec = ColumnCodec::default();
+        codec.add(CodecType::LZ4, None)?;
+        assert!(codec.add(CodecType::T64, None).is_err());
+        assert!(codec.validate(BqlType::FixedString(3)).is_ok());
+
+        let mut codec = ColumnCodec::default();
+        codec.add(CodecType::Gorilla, None)?;
+        assert!(codec.validate(BqlType::Float(32)).is_ok());
+        assert!(codec.validate(BqlType::UInt(32)).is_err());
+        assert!(ColumnCodec::default()
+            .add(CodecType::ZSTD, Some(23))
+            .is_err());
+        assert!(CodecType::from_str("Snappy").is_err());
+
+        let mut codec = ColumnCodec::default();
+        codec.add(CodecType::Delta, None)?;
+        codec.add(CodecType::ZSTD, Some(3))?;
+        assert_eq!(codec.to_vec(), b"CODEC(Delta, ZSTD(3))".to_vec());
+        assert!(ColumnCodec::default().to_vec().is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_convert_numeric_values() -> MetaResult<()> {
+        let src: Vec<u8> = [-1i16, 300, 7]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        let rt = convert_numeric_values(&src, BqlType::Int(16), BqlType::Int(64))?;
+        assert_eq!(
+            rt,
+            [-1i64, 300, 7]
+                .iter()
+                .flat_map(|v| v.to_le_bytes())
+                .collect::<Vec<u8>>()
+        );
+        let rt = convert_numeric_values(&src, BqlType::Int(16), BqlType::UInt(8))?;
+        assert_eq!(rt, vec![255u8, 44, 7]);
+        let rt = convert_numeric_values(&src, BqlType::Int(16), BqlType::Float(64))?;
+        assert_eq!(
+            rt,
+            [-1f64, 300., 7.]
+                .iter()
+                .flat_map(|v| v.to_le_bytes())
+                .collect::<Vec<u8>>()
+        );
+
+        let src: Vec<u8> = [1.9f32, -2.5]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        let rt = convert_numeric_values(&src, BqlType::Float(32), BqlType::Int(32))?;
+        assert_eq!(
+            rt,
+            [1i32, -2]
+                .iter()
+                .flat_map(|v| v.to_le_bytes())
+                .collect::<Vec<u8>>()
+        );
+
+        assert!(convert_numeric_values(&[], BqlType::String, BqlType::UInt(8)).is_err());
+        assert!(convert_numeric_values(&[], BqlType::UInt(8), BqlType::Uuid).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_chunk_to_arrow_array() -> MetaResult<()> {
+        use arrow::array::{
+            Array, DecimalArray, LargeStringArray, ListArray, UInt16Array,
+        };
+
+        let chk = BaseChunk {
+            btype: BqlType::UInt(16),
+            size: 3,
+            data: vec![1, 0, 2, 0, 3, 0],
+            null_map: Some(vec![0, 1, 0]),
+            offset_map: None,
+            lc_dict_data: None,
+        };
+        let arr = chk.to_arrow_array(BqlType::UInt(16), true)?;
+        let arr = arr.as_any().downcast_ref::<UInt16Array>().unwrap();
+        assert_eq!(arr.iter().collect::<Vec<_>>(), vec![Some(1), None, Some(3)]);
+        let arr = chk.to_arrow_array(BqlType::UInt(16), false)?;
+        assert_eq!(arr.null_count(), 0);
+        assert!(chk.to_arrow_array(BqlType::UInt(32), false).is_err());
+
+        let chk = BaseChunk {
+            btype: BqlType::String,
+            size: 2,
+            data: b"abcd".to_vec(),
+            null_map: None,
+            offset_map: Some(vec![0, 1, 4]),
+            lc_dict_data: None,
+        };
+        let arr = chk.to_arrow_array(BqlType::String, false)?;
+        let arr = arr.as_any().downcast_ref::<LargeStringArray>().unwrap();
+        assert_eq!(arr.value(0), "a");
+        assert_eq!(arr.value(1), "bcd");
+
+        let typ = BqlType::Array(ElemKind::UInt, 16);
+        let chk = BaseChunk {
+            btype: typ,
+            size: 3,
+            data: vec![1, 0, 2, 0, 3, 0],
+            null_map: None,
+            offset_map: Some(vec![0, 4, 4, 6]),
+            lc_dict_data: None,
+        };
+        let arr = chk.to_arrow_array(typ, false)?;
+        let arr = arr.as_any().downcast_ref::<ListArray>().unwrap();
+        assert_eq!(arr.len(), 3);
+        let vals = arr.value(0);
+        let vals = vals.as_any().downcast_ref::<UInt16Array>().unwrap();
+        assert_eq!(vals.values(), &[1, 2]);
+        assert_eq!(arr.value_length(1), 0);
+        assert_eq!(arr.value_length(2), 1);
+
+        let typ = BqlType::Array(ElemKind::String, 0);
+        let chk = BaseChunk {
+            btype: typ,
+            size: 2,
+            data: b"\x01a\x02bc\x00".to_vec(),
+            null_map: None,
+            offset_map: Some(vec![0, 2, 6]),
+            lc_dict_data: None,
+        };
+        let arr = chk.to_arrow_array(typ, false)?;
+        let arr = arr.as_any().downcast_ref::<ListArray>().unwrap();
+        assert_eq!(arr.value_length(0), 1);
+        assert_eq!(arr.value_length(1), 2);
+        let vals = arr.value(1);
+        let vals = vals.as_any().downcast_ref::<LargeStringArray>().unwrap();
+        assert_eq!(vals.value(0), "\x02bc");
+        assert_eq!(vals.value(1), "\x00");
+        //NOTE the offsets not at the boundaries of the elements
+        let mut chk = chk;
+        chk.offset_map = Some(vec![0, 1, 6]);
+        assert!(chk.to_arrow_array(typ, false).is_err());
+
+        let typ = BqlType::Decimal(20, 2);
+        let mut data = (-12345i128).to_le_bytes().to_vec();
+        data.extend_from_slice(&7i128.to_le_bytes());
+        let chk = BaseChunk {
+            btype: typ,
+            size: 2,
+            data,
+            null_map: None,
+            offset_map: None,
+            lc_dict_data: None,
+        };
+        let arr = chk.to_arrow_array(typ, false)?;
+        let arr = arr.as_any().downcast_ref::<DecimalArray>().unwrap();
+        assert_eq!(arr.value_as_string(0), "-123.45");
+        assert_eq!(arr.value_as_string(1), "0.07");
+
+        Ok(())
+    }
+
     #[test]
     fn test_type_sizes() {
         show_option_size!(BqlType);
+        //NOTE ColumnInfos are stored as raw bytes
+        assert_eq!(mem::size_of::<BqlType>(), 8);
+
+        assert_eq!(BqlType::Int(128).size().unwrap(), 16);
+        assert_eq!(BqlType::UInt256.size().unwrap(), 32);
+        assert_eq!(BqlType::Decimal(9, 2).size().unwrap(), 4);
+        assert_eq!(BqlType::Decimal(18, 2).size().unwrap(), 8);
+        assert_eq!(BqlType::Decimal(38, 2).size().unwrap(), 16);
+        assert_eq!(BqlType::Decimal(76, 2).size().unwrap(), 32);
     }
 }
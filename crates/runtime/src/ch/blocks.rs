@@ -5,7 +5,10 @@ use bytes::{Buf, BufMut, BytesMut};
 use clickhouse_rs_cityhash_sys::city_hash_128;
 use lzzzz::lz4;
 
-use meta::types::{BaseChunk, BqlType};
+use meta::{
+    errs::MetaError,
+    types::{value_indexes, value_offsets, BaseChunk, BqlType},
+};
 
 use crate::ch::codecs::{new_mut_slice_at, BytesDecoder, BytesEncoder, BytesExt};
 use crate::ch::protocol::{ServerCodes, LZ4_COMPRESSION_METHOD};
@@ -301,11 +304,13 @@ impl Block {
     pub fn get_block_header(&self) -> Self {
         let mut blk = BaseDataBlock::default();
         for col in &self.data.columns {
-            blk.columns.push(BaseColumn::new_block_header(
+            let mut hc = BaseColumn::new_block_header(
                 col.name.clone(),
                 col.data.btype,
                 col.data.null_map.is_some(),
-            ));
+            );
+            hc.enum_values = col.enum_values.clone();
+            blk.columns.push(hc);
         }
         blk.ncols = blk.columns.len();
         blk.nrows = 0; //for empty data case
@@ -394,15 +399,31 @@ impl std::fmt::Debug for Block {
 impl BytesEncoder for BaseColumn {
     fn encode(&self, bs: &mut BytesMut) -> BaseRtResult<()> {
         bs.write_varbytes(&self.name);
+        let typ = match &self.enum_values {
+            Some(ev) => ev.to_vec(self.data.btype)?,
+            None => self.data.btype.to_vec()?,
+        };
         if self.data.null_map.is_none() {
-            bs.write_varbytes(&self.data.btype.to_vec()?);
+            bs.write_varbytes(&typ);
         } else {
-            let bs_nlltyp = &self.data.btype.to_vec()?;
-            bs.write_varbytes(
-                bytes_cat!(b"Nullable(", bs_nlltyp.as_slice(), b")").as_slice(),
-            );
+            bs.write_varbytes(bytes_cat!(b"Nullable(", typ.as_slice(), b")").as_slice());
             bs.extend_from_slice(&self.data.null_map.as_ref().unwrap());
         }
+        if let Some(elem) = self.data.btype.elem_type() {
+            //the cumulative counts of the elements of the rows
+            let om = match &self.data.offset_map {
+                Some(om) => &om[..],
+                //NOTE the block header has no rows
+                None if self.data.size == 0 => &[0][..],
+                None => return Err(MetaError::ChunkDataMismatchError.into()),
+            };
+            let idxs = value_offsets(elem, &self.data.data)
+                .and_then(|vos| value_indexes(&vos, om.get(1..)?))
+                .ok_or(MetaError::ChunkDataMismatchError)?;
+            for i in idxs {
+                bs.put_u64_le(i as u64);
+            }
+        }
         bs.extend_from_slice(&self.data.data);
 
         Ok(())
@@ -426,7 +447,51 @@ fn decode_to_column(
     } else {
         None
     };
-    if btype == BqlType::String {
+    if let Some(elem) = btype.elem_type() {
+        //the cumulative counts of the elements of the rows, then the elements
+        bs.ensure_enough_bytes_to_read(nrows * 8)?;
+        let cnts: Vec<usize> = (0..nrows).map(|_| bs.get_u64_le() as usize).collect();
+        let ne = cnts.last().copied().unwrap_or(0);
+        let oss = bs.as_ptr();
+        if elem == BqlType::String {
+            for _ in 0..ne {
+                let slen = bs.read_varint()? as usize;
+                bs.ensure_enough_bytes_to_read(slen)?;
+                bs.advance(slen);
+            }
+        } else {
+            let len_data = ne * elem.size_in_usize()?;
+            bs.ensure_enough_bytes_to_read(len_data)?;
+            bs.advance(len_data);
+        }
+        let bc_data = unsafe {
+            let len = bs.as_ptr().offset_from(oss) as usize;
+            slice::from_raw_parts(oss, len).to_vec()
+        };
+        //gen offset_map of the rows from the offsets of the elements
+        let vos = value_offsets(elem, &bc_data)
+            .ok_or(BaseRtError::InvalidWireFormatInColumn)?;
+        let mut os_map = Vec::with_capacity(nrows + 1);
+        os_map.push(0);
+        for i in 0..nrows {
+            if i > 0 && cnts[i] < cnts[i - 1] {
+                return Err(BaseRtError::InvalidWireFormatInColumn);
+            }
+            os_map.push(vos[cnts[i]] as u32);
+        }
+        Ok(BaseColumn {
+            name,
+            data: BaseChunk {
+                btype,
+                size: nrows,
+                data: bc_data,
+                null_map,
+                offset_map: Some(os_map),
+                lc_dict_data: None,
+            },
+            enum_values: None,
+        })
+    } else if btype == BqlType::String {
         //gen offset_map for strings
         let mut os_map = vec![];
         let oss = bs.as_ptr();
@@ -452,6 +517,7 @@ fn decode_to_column(
                 offset_map: Some(os_map),
                 lc_dict_data: None,
             },
+            enum_values: None,
         })
     } else {
         let len_data = nrows * btype.size_in_usize()?;
@@ -468,6 +534,7 @@ fn decode_to_column(
                 offset_map: None,
                 lc_dict_data: None,
             },
+            enum_values: None,
         })
     }
 }
@@ -530,6 +597,7 @@ impl BytesDecoder<BaseColumn> for &[u8] {
                         offset_map: Some(os_map),
                         lc_dict_data: Some(lcd_data),
                     },
+                    enum_values: None,
                 })
             }
             [b'N', b'u', b'l', b'l', b'a', b'b', b'l', b'e', b'(', bt @ .., b')'] => {
@@ -558,7 +626,7 @@ mod unit_tests {
 
     use super::*;
     use bytes::BytesMut;
-    use meta::types::BqlType;
+    use meta::types::{BqlType, ElemKind, EnumValues};
 
     #[test]
     fn test_block_encode() -> BaseRtResult<()> {
@@ -577,6 +645,7 @@ mod unit_tests {
                 offset_map: None,
                 lc_dict_data: None,
             },
+            enum_values: None,
         };
         blk.data.ncols = 1;
         blk.data.nrows = 1;
@@ -677,6 +746,26 @@ mod unit_tests {
         Ok(())
     }
 
+    #[test]
+    fn test_enum_header_encode() -> BaseRtResult<()> {
+        let (typ, ev) = EnumValues::from_bytes(b"Enum8('a' = 1, 'it''s' = -2)")?;
+        let mut col = BaseColumn::new_block_header(b"e".to_vec(), typ, true);
+        col.enum_values = Some(ev);
+        let mut bs = BytesMut::new();
+        col.encode(&mut bs)?;
+        let full = b"Nullable(Enum8('a' = 1, 'it\\'s' = -2))";
+        let mut expected_bs = vec![0x01, b'e', full.len() as u8];
+        expected_bs.extend_from_slice(full);
+        assert_eq!(&bs[..], &expected_bs[..]);
+
+        let mut rb = &bs[..];
+        let col = rb.decode_column(0)?;
+        assert_eq!(col.data.btype, BqlType::Enum8);
+        assert!(col.data.null_map.is_some());
+
+        Ok(())
+    }
+
     #[test]
     fn test_block_decode() -> BaseRtResult<()> {
         let mut bs = BytesMut::with_capacity(4);
@@ -694,6 +783,7 @@ mod unit_tests {
                 offset_map: None,
                 lc_dict_data: None,
             },
+            enum_values: None,
         };
         blk.data.ncols = 1;
         blk.data.nrows = 1;
@@ -723,6 +813,88 @@ mod unit_tests {
         Ok(())
     }
 
+    #[test]
+    fn test_block_decode_nullable() -> BaseRtResult<()> {
+        let mut bs = BytesMut::with_capacity(4);
+
+        let mut blk: Block = Default::default();
+
+        let col: BaseColumn = BaseColumn {
+            name: b"col_1".to_vec(),
+            data: BaseChunk {
+                btype: BqlType::UInt(32),
+                size: 3,
+                data: vec![1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                null_map: Some(vec![0, 1, 0]),
+                offset_map: None,
+                lc_dict_data: None,
+            },
+            enum_values: None,
+        };
+        blk.data.ncols = 1;
+        blk.data.nrows = 3;
+        blk.data.columns.push(col);
+
+        blk.encode_to(&mut bs, None)?;
+
+        bs.split_to(2); //skip msg protocol header
+        let mut blk2 = Block::default();
+        blk2.decode_from(&mut &bs[..])?;
+
+        let c2 = &blk2.data.columns[0].data;
+        assert_eq!(c2.btype, BqlType::UInt(32));
+        assert_eq!(c2.null_map, Some(vec![0, 1, 0]));
+        //NULL is not the same as 0
+        assert_eq!(c2.data, blk.data.columns[0].data.data);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_block_decode_array() -> BaseRtResult<()> {
+        for (btype, data, om) in [
+            (
+                BqlType::Array(ElemKind::UInt, 16),
+                vec![1, 0, 2, 0, 3, 0],
+                vec![0, 4, 4, 6],
+            ),
+            (
+                BqlType::Array(ElemKind::String, 0),
+                b"\x01a\x00\x02bc".to_vec(),
+                vec![0, 3, 3, 6],
+            ),
+        ] {
+            let mut bs = BytesMut::with_capacity(4);
+            let mut blk: Block = Default::default();
+            blk.data.ncols = 1;
+            blk.data.nrows = 3;
+            blk.data.columns.push(BaseColumn {
+                name: b"col_1".to_vec(),
+                data: BaseChunk {
+                    btype,
+                    size: 3,
+                    data: data.clone(),
+                    null_map: None,
+                    offset_map: Some(om.clone()),
+                    lc_dict_data: None,
+                },
+                enum_values: None,
+            });
+            blk.encode_to(&mut bs, None)?;
+
+            bs.split_to(2); //skip msg protocol header
+            let mut blk2 = Block::default();
+            blk2.decode_from(&mut &bs[..])?;
+
+            let c2 = &blk2.data.columns[0].data;
+            assert_eq!(c2.btype, btype);
+            assert_eq!(c2.data, data);
+            assert_eq!(c2.offset_map, Some(om));
+        }
+
+        Ok(())
+    }
+
     #[test]
     fn test_block_decode_with_compression() -> BaseRtResult<()> {
         let mut bs = BytesMut::with_capacity(4);
@@ -740,6 +912,7 @@ mod unit_tests {
                 offset_map: None,
                 lc_dict_data: None,
             },
+            enum_values: None,
         };
         blk.data.ncols = 1;
         blk.data.nrows = col.data.size;
@@ -802,6 +975,7 @@ mod unit_tests {
                 offset_map: None,
                 lc_dict_data: None,
             },
+            enum_values: None,
         };
         blk.data.ncols = 1;
         blk.data.nrows = col.data.size;
@@ -356,7 +356,10 @@ fn response_query(
             Ok(())
         }
         Ok(
-            BaseCommandKind::Create | BaseCommandKind::Drop | BaseCommandKind::Optimize,
+            BaseCommandKind::Create
+            | BaseCommandKind::Drop
+            | BaseCommandKind::Optimize
+            | BaseCommandKind::Alter,
         ) => Ok(()),
         Ok(BaseCommandKind::InsertFormatInlineValues(mut blk, qtn, tid)) => {
             let write = WRITE.get().unwrap();
@@ -137,6 +137,33 @@ pub enum BaseRtError {
     #[error("Multiple primary keys are not supported now")]
     MultiplePrimaryKeyNotSupported,
 
+    #[error("Primary key or partition key column can not be altered")]
+    KeyColumnCanNotBeAltered,
+
+    #[error("Unsupported column modification: {0}")]
+    UnsupportedColumnModification(String),
+
+    #[error("TTL column should be a Date or DateTime column of the table")]
+    InvalidTTLColumn,
+
+    #[error("Partition does not exist")]
+    PartitionNotExist,
+
+    #[error("Partition has existed")]
+    PartitionExisted,
+
+    #[error("Unsupported partition operation: {0}")]
+    UnsupportedPartitionOperation(String),
+
+    #[error("Backup has existed in {0}")]
+    BackupExisted(String),
+
+    #[error("Invalid backup: {0}")]
+    InvalidBackup(String),
+
+    #[error("Invalid table engine: {0}")]
+    InvalidTableEngine(String),
+
     #[error(transparent)]
     WrappingBaseError(#[from] base::errs::BaseError),
 
@@ -221,6 +248,15 @@ impl BaseRtError {
             BaseRtError::InsertIntoValueParsingError => 413,
             BaseRtError::ShouldNotReachHere => 414,
             BaseRtError::MultiplePrimaryKeyNotSupported => 415,
+            BaseRtError::KeyColumnCanNotBeAltered => 416,
+            BaseRtError::UnsupportedColumnModification(_) => 417,
+            BaseRtError::InvalidTTLColumn => 418,
+            BaseRtError::PartitionNotExist => 419,
+            BaseRtError::PartitionExisted => 420,
+            BaseRtError::UnsupportedPartitionOperation(_) => 421,
+            BaseRtError::BackupExisted(_) => 422,
+            BaseRtError::InvalidBackup(_) => 423,
+            BaseRtError::InvalidTableEngine(_) => 424,
         }
     }
 }
@@ -1,12 +1,16 @@
 use arrow::{
-    array::{LargeStringArray, LargeStringBuilder},
-    datatypes::{DataType, Field, Schema},
+    array::{
+        Array, ArrayRef, BooleanArray, Date16Array, LargeStringArray, LargeStringBuilder,
+        Timestamp32Array, UInt64Array, UInt8Array,
+    },
+    compute::{cast, filter_record_batch},
+    datatypes::{i256, u256, DataType, Field, Schema},
     record_batch::RecordBatch,
 };
 use base::{bytes_cat, datetimes::parse_to_days};
 use base::{
     codec::encode_ascii_bytes_vec_short,
-    datetimes::{parse_to_epoch, TimeZoneId},
+    datetimes::{parse_to_epoch, parse_to_epoch64, TimeZoneId},
     mem::SyncPointer,
     strings::s,
 };
@@ -19,39 +23,63 @@ use client::prelude::PoolBuilder;
 use dashmap::DashMap;
 use lang::parse::RemoteAddr;
 use lang::parse::{
-    parse_command, parse_create_database, parse_create_table, parse_desc_table,
-    parse_drop_database, parse_drop_table, parse_insert_into, parse_optimize_table,
-    parse_show_create_table, parse_table_place, seek_to_sub_cmd, Pair, Rule,
-    TablePlaceKind, TablePlaceKindContext,
+    parse_alter_table, parse_backup, parse_command, parse_create_database,
+    parse_create_table, parse_desc_table, parse_drop_database, parse_drop_table,
+    parse_insert_into, parse_optimize, parse_optimize_table, parse_show_create_table,
+    parse_table_place, seek_to_sub_cmd, AlterTableAction, Pair, Rule, TablePlaceKind,
+    TablePlaceKindContext,
 };
 use meta::{
-    confs::Conf,
+    confs::{Conf, Durability, DEFAULT_VOLUME},
     errs::MetaError,
-    store::{parts::PartStore, sys::MetaStore},
+    store::{
+        parts::{
+            ensure_table_path_existed, get_part_file_name, CoPaCrcs, PartMeta, PartStore,
+        },
+        sys::MetaStore,
+    },
     toml,
-    types::{BaseChunk, BqlType, Id},
+    types::{
+        btype_to_arrow_type, convert_numeric_values, BaseChunk, BqlType, ColumnInfo,
+        EngineType, EnumValues, Id, Table,
+    },
 };
 use mysql::{Compression, OptsBuilder, Pool as MyPool, SslOpts};
 use std::time::Duration;
 use std::{
     convert::TryInto,
     env,
-    fs::remove_dir_all,
+    fs::{
+        copy, create_dir_all, hard_link, metadata, read, read_dir, read_to_string,
+        remove_dir_all, remove_file, rename, write, File, OpenOptions,
+    },
     lazy::{SyncLazy, SyncOnceCell},
+    ops::RangeInclusive,
     panic::panic_any,
     path::{Path, PathBuf},
     pin::Pin,
     str::FromStr,
     sync::Mutex,
-    time::Instant,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+use std::{
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    os::unix::fs::FileExt,
+    sync::Arc,
 };
-use std::{net::IpAddr, sync::Arc};
 
 use crate::{
     errs::{BaseRtError, BaseRtResult},
+    read::query_by_parts,
     types::{BaseColumn, BaseDataBlock, BaseServerConn, BaseWriteAware},
+    write::{
+        recover_part_writes, sync_for_durability, sync_part_files, write_block,
+        write_block_locked, write_copa_base_materialized, write_merged_part,
+        DURABILITY_SYNC_LOCK,
+    },
 };
 use datafusion::physical_plan::clickhouse::DEFAULT_TIMEZONE;
+use engine::datafusions::{collapse_batch, get_collapsing};
 use lang::parse::RemoteTableInfo;
 
 pub static READ: SyncOnceCell<
@@ -155,15 +183,26 @@ pub static BMS: SyncLazy<BaseMgmtSys> = SyncLazy::new(|| {
         )
     };
     //check existence
-    for dd in &conf.system.data_dirs {
+    for dd in &conf.get_all_data_dirs() {
         if !Path::new(dd).exists() {
             panic_any(format!("data_dir [{}] does not existed", dd))
         }
     }
+    let mut vns = vec![DEFAULT_VOLUME];
+    for v in &conf.storage.volumes {
+        if vns.contains(&v.name.as_str()) || v.data_dirs.is_empty() {
+            panic_any(format!(
+                "volume [{}] is duplicated or has no data_dirs",
+                v.name
+            ))
+        }
+        vns.push(&v.name);
+    }
 
     log::info!("confs to use:");
     log::info!("system.meta_dirs: {:?}", &conf.system.meta_dirs);
     log::info!("system.data_dirs: {:?}", &conf.system.data_dirs);
+    log::info!("storage.volumes: {:?}", &conf.storage.volumes);
 
     let conf = Box::new(conf);
     let mut bms = BaseMgmtSys::from_conf(Box::leak(conf)).unwrap();
@@ -172,6 +211,9 @@ pub static BMS: SyncLazy<BaseMgmtSys> = SyncLazy::new(|| {
     bms
 });
 
+///the actions of the background housekeeping are logged into this table
+pub const HOUSEKEEPING_LOG_TABLE: &str = "system.housekeeping_log";
+
 pub static EXPR_JIT: SyncLazy<Mutex<jit::JIT>> =
     SyncLazy::new(|| Mutex::new(jit::JIT::default()));
 
@@ -187,6 +229,7 @@ pub enum BaseCommandKind {
     InsertFormatCSV(BaseDataBlock, String, Id),
     InsertFormatSelectValue(Vec<BaseDataBlock>, String, Id),
     Optimize,
+    Alter,
 }
 
 impl Default for BaseCommandKind {
@@ -211,6 +254,8 @@ pub struct BaseMgmtSys<'a> {
     pub remote_db_pool: DashMap<RemoteAddr, Pool>,
     pub remote_mysql_pool: DashMap<RemoteAddr, MyPool>,
     pub ptk_exprs_reg: DashMap<Id, SyncPointer<u8>, BuildPtkExprsHasher>,
+    ///the batches of the Memory tables keyed by tid
+    pub mem_tables: DashMap<Id, Vec<RecordBatch>>,
     pub timezone: TimeZoneId,
     pub timezone_name: String,
 }
@@ -220,7 +265,21 @@ impl<'a> BaseMgmtSys<'a> {
         let ms_path = conf.system.meta_dirs.as_slice();
         let meta_store =
             MetaStore::new(ms_path).map_err(|e| BaseRtError::WrappingMetaError(e))?;
-        let part_store = PartStore::new(ms_path, &conf.system.data_dirs)?;
+        //NOTE the part store is flushed after the data are synced in batch
+        let part_store = PartStore::new_with_volumes(
+            ms_path,
+            &conf.system.data_dirs,
+            &conf.storage.volumes,
+            conf.storage.durability != Durability::Batch,
+        )?;
+        part_store.set_mmap_cache_capacity(conf.storage.mmap_cache_capacity);
+        let n = part_store.locate_unplaced_parts()?;
+        if n > 0 {
+            log::info!("recorded the data dirs of {} parts", n);
+        }
+        //NOTE the writes interrupted by a crash are rolled back before any write
+        recover_part_writes(&part_store)?;
+        recover_part_merges(&part_store, &conf.get_all_data_dirs())?;
         let timezone = match &conf.system.timezone {
             Some(tz_name) => TimeZoneId::from_str(&tz_name)?,
             _ => TimeZoneId::from_local().unwrap_or_default(),
@@ -240,23 +299,29 @@ impl<'a> BaseMgmtSys<'a> {
                 )
                 ENGINE = BaseStorage
                 ";
-                let p = BaseMgmtSys::parse_cmd_as_pair(cmd)?;
-                let (mut t, _fallible) = parse_create_table(p)
-                    .map_err(|e| BaseRtError::WrappingLangError(e))?;
-                t.tab_info.create_script = cmd.to_string();
-                let res = meta_store.create_table(&t);
-                match res {
-                    Err(e) => {
-                        return Err(BaseRtError::WrappingMetaError(e));
-                    }
-                    _ => log::info!("database `system` created"), //[dbid={}]
-                }
+                create_system_table(&meta_store, cmd)?;
+                log::info!("database `system` created"); //[dbid={}]
             }
             Err(MetaError::EntityExistedError(tid)) => {
                 log::info!("database `system` existed") //[dbid={}]
             }
             Err(e) => return Err(BaseRtError::WrappingMetaError(e)),
         }
+        //NOTE created here for the systems which have been created before it
+        if meta_store.tid_by_qname(HOUSEKEEPING_LOG_TABLE).is_none() {
+            let cmd = "CREATE TABLE system.housekeeping_log
+                (
+                    event_time DateTime,
+                    db_name String,
+                    table_name String,
+                    action String,
+                    part_key UInt64,
+                    rows UInt64
+                )
+                ENGINE = BaseStorage
+                ";
+            create_system_table(&meta_store, cmd)?;
+        }
         let res = meta_store.new_db("default");
         match res {
             Ok(tid) => log::info!("database `default` created"), //[dbid={}]
@@ -278,6 +343,7 @@ impl<'a> BaseMgmtSys<'a> {
             remote_db_pool: DashMap::new(),
             remote_mysql_pool: DashMap::new(),
             ptk_exprs_reg,
+            mem_tables: DashMap::new(),
             timezone,
             timezone_name,
         })
@@ -544,10 +610,13 @@ impl<'a> BaseMgmtSys<'a> {
 
         let mut builder_name = LargeStringBuilder::new(len);
         let mut builder_type = LargeStringBuilder::new(len);
-        for (name0, _, col_info) in col_infos.into_iter() {
+        for (name0, cid, col_info) in col_infos.into_iter() {
             let mut name = Vec::with_capacity(len);
             let mut dtype = Vec::with_capacity(len * 3);
-            let mut data = col_info.data_type.to_vec()?;
+            let mut data = match ms.get_column_enum(cid)? {
+                Some(ev) => ev.to_vec(col_info.data_type)?,
+                None => col_info.data_type.to_vec()?,
+            };
             data = if col_info.is_nullable {
                 bytes_cat!(b"Nullable(", &data, b")")
             } else {
@@ -669,6 +738,29 @@ impl<'a> BaseMgmtSys<'a> {
         if BaseMgmtSys::has_mulit_cols(t.tab_info.primary_keys.as_str()) {
             return Err(BaseRtError::MultiplePrimaryKeyNotSupported);
         }
+        let ttl_col = t.tab_info.ttl_col.as_str();
+        if !ttl_col.is_empty()
+            && !t.columns.iter().any(|(cn, ci)| {
+                cn == ttl_col
+                    && matches!(
+                        ci.data_type,
+                        BqlType::Date | BqlType::DateTime | BqlType::DateTimeTz(_)
+                    )
+            })
+        {
+            return Err(BaseRtError::InvalidTTLColumn);
+        }
+        if t.columns.iter().any(|(cn, ci)| {
+            matches!(ci.data_type, BqlType::Array(_, _))
+                && (ci.is_nullable
+                    || ci.is_primary_key
+                    || cn == t.tab_info.partition_cols.as_str())
+        }) {
+            return Err(BaseRtError::UnsupportedFunctionality2(
+                "Nullable Array or Array as the key",
+            ));
+        }
+        check_table_engine(&t)?;
 
         let ms = &self.meta_store;
         let res = ms.create_table(&t);
@@ -831,8 +923,9 @@ impl<'a> BaseMgmtSys<'a> {
             }
             Err(_) => return Ok(BaseCommandKind::Drop), //non-fallible
             Ok((tid, cids)) => {
+                self.mem_tables.remove(&tid);
                 //remove all data
-                let dd = &self.conf.system.data_dirs;
+                let dd = &self.conf.get_all_data_dirs();
                 for dir in dd {
                     let res = remove_dir_all(format!("{}/{}", dir, tid));
                     // .map_err(|e| )?;
@@ -879,9 +972,10 @@ impl<'a> BaseMgmtSys<'a> {
                 let cids = ms.get_column_ids(qtn.as_str())?;
                 ps.acquire_lock(tid)?;
                 ps.clear(tid, &cids)?;
+                self.mem_tables.remove(&tid);
                 ps.release_lock(tid)?;
                 //remove all data
-                let dd = &self.conf.system.data_dirs;
+                let dd = &self.conf.get_all_data_dirs();
                 for dir in dd {
                     let res = remove_dir_all(format!("{}/{}", dir, tid));
                     if let Err(e) = res {
@@ -919,224 +1013,2575 @@ impl<'a> BaseMgmtSys<'a> {
         }
     }
 
-    //WARN debug only
     pub fn command_optimize_table(
         &self,
         p: Pair<Rule>,
         current_db: &str,
     ) -> BaseRtResult<BaseCommandKind> {
+        let ctx = parse_optimize(p).map_err(|e| BaseRtError::WrappingLangError(e))?;
+        let dbn = ctx.dbname.as_deref().unwrap_or(current_db);
+        let tn = ctx.tabname.as_str();
+        let qtn = [dbn, tn].join(".");
+        let ms = &self.meta_store;
+        let tid = ms.tid_by_qname(&qtn).ok_or(BaseRtError::TableNotExist)?;
+        if let Some((ttl_col, ttl_secs)) = ms.get_table_info_ttl(tid)? {
+            self.expire_table(dbn, tn, tid, &ttl_col, ttl_secs, now_in_secs())?;
+        }
+        let ptks = match ctx.ptk {
+            Some(ptk) => ptk..=ptk,
+            None => 0..=u64::MAX,
+        };
+        self.optimize_table(dbn, tn, tid, ptks, ctx.is_final)?;
+
+        Ok(BaseCommandKind::Optimize)
+    }
+
+    ///verify the files of every part of the table against their checksums,
+    ///return (part_key, is_passed, message) for the parts
+    pub fn command_check_table(
+        &self,
+        p: Pair<Rule>,
+        current_db: &str,
+    ) -> BaseRtResult<RecordBatch> {
         let (dbn_opt, tn) =
             parse_optimize_table(p).map_err(|e| BaseRtError::WrappingLangError(e))?;
-        let _qtn = if let Some(dbn) = dbn_opt {
-            [dbn.as_str(), tn.as_str()].join(".")
-        } else {
-            [current_db, tn.as_str()].join(".")
-        };
+        let dbn = dbn_opt.as_deref().unwrap_or(current_db);
+        let qtn = [dbn, tn.as_str()].join(".");
         let ms = &self.meta_store;
         let ps = &self.part_store;
-        //FIXME _qtn is not used...
-        ps.flush()?;
+        let tid = ms.tid_by_qname(&qtn).ok_or(BaseRtError::TableNotExist)?;
+        let mut col_infos = ms.get_columns(dbn, &tn)?;
+        col_infos.sort_unstable_by_key(|c| c.2.ordinal);
 
-        Ok(BaseCommandKind::Optimize)
+        let mut ptks = Vec::new();
+        let mut passed = Vec::new();
+        let mut msgs = LargeStringBuilder::new(0);
+        for (ptk, _) in ps.get_part_sizes(tid)? {
+            //NOTE the part is not changed by the writes when checking
+            ps.acquire_lock(tid)?;
+            let res = ps
+                .get_part_dir(tid, ptk)
+                .map_err(BaseRtError::WrappingMetaError)
+                .and_then(|dp| {
+                    let tp = format!("{}/{}", dp, tid);
+                    check_part_files(ps, &tp, ptk, ps.get_part_gen(tid, ptk)?, &col_infos)
+                });
+            ps.release_lock(tid)?;
+            let errs = res?;
+            ptks.push(ptk);
+            passed.push(errs.is_empty() as u8);
+            msgs.append_value(errs.join("; "))?;
+        }
+
+        let schema = Schema::new(vec![
+            Field::new("part_key", DataType::UInt64, false),
+            Field::new("is_passed", DataType::UInt8, false),
+            Field::new("message", DataType::LargeUtf8, false),
+        ]);
+        let cols: Vec<ArrayRef> = vec![
+            Arc::new(UInt64Array::from(ptks)),
+            Arc::new(UInt8Array::from(passed)),
+            Arc::new(msgs.finish()),
+        ];
+        Ok(RecordBatch::try_new(Arc::new(schema), cols)?)
     }
 
-    pub fn command_query(
+    ///back up the table, or all tables of the database, into `{path}/{table}`
+    ///as a point-in-time snapshot: the part files are hard-linked(or copied if
+    ///they may be rewritten in place) and the create script and the part metas
+    ///are exported there
+    pub fn command_backup(
         &self,
         p: Pair<Rule>,
         current_db: &str,
-        query_id: &str,
-        // raw_query: String,
     ) -> BaseRtResult<BaseCommandKind> {
-        let ctx = parse_table_place(p.clone())?;
-        match ctx.place_kind {
-            TablePlaceKind::Local => {
-                let read = READ.get().unwrap();
-                let blks =
-                    read(&self.meta_store, &self.part_store, query_id, current_db, p)?;
-                Ok(BaseCommandKind::Query(blks))
+        let ctx = parse_backup(p).map_err(|e| BaseRtError::WrappingLangError(e))?;
+        let dbn = ctx.dbname.as_deref().unwrap_or(current_db);
+        let ms = &self.meta_store;
+        let ps = &self.part_store;
+        ms.dbid_by_name(dbn).ok_or(BaseRtError::DatabaseNotExist)?;
+        let tns = match &ctx.tabname {
+            Some(tn) => vec![tn.clone()],
+            None => ms.get_table_names(dbn)?,
+        };
+        let mut tabs = Vec::with_capacity(tns.len());
+        for tn in tns {
+            let tid = match ms.tid_by_qname([dbn, tn.as_str()].join(".")) {
+                Some(tid) => tid,
+                //NOTE the tables of the databases prefixed by dbn are listed too
+                None if ctx.tabname.is_none() => continue,
+                None => return Err(BaseRtError::TableNotExist),
+            };
+            //NOTE the rows of Memory or Log tables are not in the parts
+            if matches!(
+                ms.get_table_info_engine(tid)?,
+                EngineType::Memory | EngineType::Log
+            ) {
+                return Err(BaseRtError::UnsupportedFunctionality2(
+                    "BACKUP of the tables of Memory or Log engine is not supported",
+                ));
             }
-            TablePlaceKind::Remote(remote_tb_info) => {
-                log::debug!("successfully parsed remote query to {:?} ", remote_tb_info);
-                let remote_read = REMOTE_READ.get().unwrap();
-                let blks = remote_read(remote_tb_info, p.as_str(), false)?;
+            let bp = format!("{}/{}", ctx.path, tn);
+            if Path::new(&bp).exists() {
+                return Err(BaseRtError::BackupExisted(bp));
+            }
+            tabs.push((tid, tn, bp));
+        }
 
-                Ok(BaseCommandKind::Query(blks))
+        //NOTE all tables are locked in the order of tids during the backup for
+        //     the snapshot is consistent among them
+        tabs.sort_unstable_by_key(|t| t.0);
+        let mut res = Ok(());
+        let mut n_locked = 0;
+        for (tid, _, _) in &tabs {
+            res = ps
+                .acquire_lock(*tid)
+                .map_err(|e| BaseRtError::WrappingMetaError(e));
+            if res.is_err() {
+                break;
             }
+            n_locked += 1;
+        }
+        if res.is_ok() {
+            res = tabs.iter().try_for_each(|(tid, tn, bp)| {
+                self.backup_table_locked(dbn, tn, *tid, bp)
+            });
         }
+        for (tid, _, _) in tabs[..n_locked].iter().rev() {
+            ps.release_lock(*tid)?;
+        }
+        res?;
+
+        Ok(BaseCommandKind::Create) //FIXME Create like but not Create semantic
     }
 
-    fn parse_cmd_as_pair(cmds: &str) -> BaseRtResult<Pair<Rule>> {
-        let ps = parse_command(cmds).map_err(|e| BaseRtError::WrappingLangError(e))?;
-        let mut ps: Vec<_> = ps.into_iter().collect();
-        if ps.len() != 1 {
-            return Err(BaseRtError::MultiqueryNotSupportedError);
+    ///restore the table, or all tables of the database, from the backup in
+    ///`{path}/{table}`, the tables are re-created and their parts registered
+    ///under the new ids
+    pub fn command_restore(
+        &self,
+        p: Pair<Rule>,
+        current_db: &str,
+    ) -> BaseRtResult<BaseCommandKind> {
+        let ctx = parse_backup(p).map_err(|e| BaseRtError::WrappingLangError(e))?;
+        let dbn = ctx.dbname.as_deref().unwrap_or(current_db);
+        let ms = &self.meta_store;
+        let tns = match &ctx.tabname {
+            Some(tn) => vec![tn.clone()],
+            None => {
+                if ms.dbid_by_name(dbn).is_none() {
+                    ms.new_db(dbn)?;
+                }
+                let mut tns = Vec::new();
+                for e in read_dir(&ctx.path)? {
+                    let e = e?;
+                    if e.path().join("create.sql").exists() {
+                        tns.push(e.file_name().to_string_lossy().into_owned());
+                    }
+                }
+                tns.sort_unstable();
+                tns
+            }
+        };
+        ms.dbid_by_name(dbn).ok_or(BaseRtError::DatabaseNotExist)?;
+        for tn in tns {
+            self.restore_table(dbn, &tn, &format!("{}/{}", ctx.path, tn))?;
         }
-        let p = ps.remove(0);
-        log::debug!("successfully parsed command: {} ", cmds);
-        let p = seek_to_sub_cmd(p.into_inner())
-            .map_err(|e| BaseRtError::WrappingLangError(e))?;
-        Ok(p)
+
+        Ok(BaseCommandKind::Create)
     }
 
-    /*
-    commands should have an auth mech
-    */
-    pub fn run_commands<T>(
+    //WARN the table lock should be held
+    fn backup_table_locked(
         &self,
-        cmds: String,
-        cctx: &mut T,
-    ) -> BaseRtResult<BaseCommandKind>
-    where
-        T: BaseServerConn,
-    {
-        // let ps = parse_command(cmds)
-        //     .map_err(|e| BaseRtError::WrappingLangError(e))?;
-        // let mut ps: Vec<_> = ps.into_iter().collect();
-        // if ps.len() != 1 {
-        //     return Err(BaseRtError::MultiqueryNotSupportedError);
-        // }
-        // let p = ps.remove(0);
-        // log::debug!("successfully parsed command: {} ", cmds);
-        // let p = seek_to_sub_cmd(&mut p.into_inner())
-        //     .map_err(|e| BaseRtError::WrappingLangError(e))?;
-        let p = BaseMgmtSys::parse_cmd_as_pair(&cmds)?;
-        // log::debug!("cctx: {:p}, cctx.current_db: {:p},{}",
-        //                cctx, &cctx.current_db, &cctx.current_db);
-        match p.as_rule() {
-            Rule::show_databases => {
-                let blk = self.command_show_databases()?;
-                Ok(BaseCommandKind::Query(vec![blk]))
-            }
-            Rule::show_tables => {
-                let blk = self.command_show_tables(cctx.get_db())?;
-                Ok(BaseCommandKind::Query(vec![blk]))
-            }
-            Rule::show_create_table => {
-                let blk = self.command_show_create_table(p, cctx.get_db())?;
-                Ok(BaseCommandKind::Query(vec![blk]))
-            }
-            Rule::desc_table => {
-                let blk = self.command_desc_table(p, cctx.get_db())?;
-                Ok(BaseCommandKind::Query(vec![blk]))
-            }
-            Rule::create_database => self
-                .command_create_database(p)
-                .map(|e| BaseCommandKind::Create),
-            Rule::create_table => self
-                .command_create_table(p, cctx.get_db(), cmds.as_str())
-                .map(|e| BaseCommandKind::Create),
-            Rule::use_db => {
-                let dbn = self.command_use_db(p)?;
-                cctx.set_db(dbn);
-                return Ok(BaseCommandKind::Create); //FIXME Create like but not Create semantic
-            }
-            Rule::drop_database => {
-                return self.command_drop_database(p);
-            }
-            Rule::drop_table => {
-                return self.command_drop_table(p, cctx.get_db());
-            }
-            Rule::truncate_table => {
-                return self.command_truncate_table(p, cctx.get_db());
-            }
-            Rule::optimize_table => {
-                return self.command_optimize_table(p, cctx.get_db());
-            }
-            Rule::insert_into => {
-                return self.command_insert_into(p, cctx);
-            }
-            Rule::query => {
-                let query_id = cctx.get_query_id();
-                let timer = Instant::now();
-                let rt = self.command_query(p, cctx.get_db(), query_id);
-                log::debug!("process query: {} in {:?}", query_id, timer.elapsed());
-                return rt;
-            }
-            _ => return Err(BaseRtError::UnsupportedCommand),
+        dbn: &str,
+        tn: &str,
+        tid: Id,
+        bp: &str,
+    ) -> BaseRtResult<()> {
+        let ms = &self.meta_store;
+        let ps = &self.part_store;
+        let mut col_infos = ms.get_columns(dbn, tn)?;
+        col_infos.sort_unstable_by_key(|c| c.2.ordinal);
+        let cids: Vec<Id> = col_infos.iter().map(|c| c.1).collect();
+        let script = ms
+            .get_table_info_create_script(tid)?
+            .ok_or(BaseRtError::TableNotExist)?;
+        create_dir_all(bp)?;
+        write(format!("{}/create.sql", bp), &*script)?;
+        let cns: Vec<&str> = col_infos.iter().map(|c| c.0.as_str()).collect();
+        write(format!("{}/columns", bp), cns.join("\n"))?;
+        for (ptk, _) in ps.get_part_sizes(tid)? {
+            let pm = match self.get_movable_part_meta(tid, ptk, &cids) {
+                Err(BaseRtError::PartitionNotExist) => continue,
+                res => res?,
+            };
+            let tp = format!("{}/{}", ps.get_part_dir(tid, ptk)?, tid);
+            let gen = ps.get_part_gen(tid, ptk)?;
+            backup_part_files(&tp, bp, ptk, gen, &pm)?;
+            write(format!("{}/{}.part", bp, ptk), pm.to_bytes())?;
         }
+        Ok(())
     }
-}
 
-fn command_insert_into_gen_header(
-    tab: &meta::types::Table,
-    qtn: &String,
-    ms: &MetaStore,
-    header: &mut BaseDataBlock,
-    dbn: &str,
-    tn: &str,
-) -> Result<(), BaseRtError> {
-    if tab.columns.len() != 0 {
-        //insert into some columns
-        for (cn, _) in &tab.columns {
-            let qcn = [qtn.as_str(), &cn].join(".");
-            let cid = ms.cid_by_qname(&qcn).ok_or(BaseRtError::ColumnNotExist)?;
-            let ci = ms
-                .get_column_info(cid)?
-                .ok_or(BaseRtError::SchemaInfoShouldExistButNot)?;
-            header.columns.push(BaseColumn::new_block_header(
-                cn.as_bytes().to_vec(),
-                ci.data_type,
-                ci.is_nullable,
+    fn restore_table(&self, dbn: &str, tn: &str, bp: &str) -> BaseRtResult<()> {
+        let ms = &self.meta_store;
+        let ps = &self.part_store;
+        let read_backup_file = |name: &str| {
+            read_to_string(format!("{}/{}", bp, name)).map_err(|e| match e.kind() {
+                std::io::ErrorKind::NotFound => {
+                    BaseRtError::InvalidBackup(format!("no {} in {}", name, bp))
+                }
+                _ => BaseRtError::WrappingIoError(e),
+            })
+        };
+        let script = read_backup_file("create.sql")?;
+        let cns = read_backup_file("columns")?;
+        let qtn = [dbn, tn].join(".");
+        if let Some(tid) = ms.tid_by_qname(&qtn) {
+            return Err(BaseRtError::WrappingMetaError(
+                MetaError::EntityExistedError(tid),
             ));
         }
-    } else {
-        //insert into all columns
+        let script = rename_table_in_create_script(&script, &qtn)?;
+        let p = BaseMgmtSys::parse_cmd_as_pair(&script)?;
+        self.command_create_table(p, dbn, &script)?;
+
+        let tid = ms.tid_by_qname(&qtn).ok_or(BaseRtError::TableNotExist)?;
         let mut col_infos = ms.get_columns(dbn, tn)?;
-        //NOTE ch client relays on the order of cols to match that being inserted into
         col_infos.sort_unstable_by_key(|c| c.2.ordinal);
-        for (cn, _, ci) in col_infos {
-            header.columns.push(BaseColumn::new_block_header(
-                cn.as_bytes().to_vec(),
-                ci.data_type,
-                ci.is_nullable,
-            ));
+        let cids: Vec<Id> = col_infos.iter().map(|c| c.1).collect();
+        let res = if !col_infos.iter().map(|c| c.0.as_str()).eq(cns.split('\n')) {
+            Err(BaseRtError::InvalidBackup(format!(
+                "columns in {} do not match the create script",
+                bp
+            )))
+        } else {
+            ps.acquire_lock(tid)?;
+            let res = self.restore_parts_locked(tid, &cids, bp);
+            ps.release_lock(tid)?;
+            res
+        };
+        //the re-created table is removed with the parts restored before
+        if let Err(e) = res {
+            if let Err(e_rm) = self.remove_restored_table(dbn, tn, tid, &cids) {
+                log::error!("fail to remove the restored table {}: {}", qtn, e_rm);
+            }
+            return Err(e);
         }
+        Ok(())
     }
-    header.ncols = header.columns.len();
-    header.nrows = 0; //for empty data case
-    Ok(())
-}
 
-// Adapted from `tokenize_single_quoted_string()` from sqlparse
-fn parse_single_quoted_str(s: &str) -> BaseRtResult<String> {
-    if s.starts_with("'") {
-        let mut ret = String::new();
-        let mut chars = s[1..].chars().peekable();
-        while let Some(&ch) = chars.peek() {
-            match ch {
-                '\'' => {
-                    chars.next(); // consume
-                    let escaped_quote = chars.peek().map(|c| *c == '\'').unwrap_or(false);
-                    if escaped_quote {
-                        ret.push('\'');
-                        chars.next();
-                    } else {
-                        return Ok(ret);
-                    }
-                }
-                _ => {
-                    chars.next(); // consume
-                    ret.push(ch);
+    fn remove_restored_table(
+        &self,
+        dbn: &str,
+        tn: &str,
+        tid: Id,
+        cids: &[Id],
+    ) -> BaseRtResult<()> {
+        let ps = &self.part_store;
+        ps.acquire_lock(tid)?;
+        let res = ps.clear(tid, cids);
+        ps.release_lock(tid)?;
+        res?;
+        self.meta_store.remove_table(dbn, tn)?;
+        //NOTE the queries may have taken the parts before they are cleared
+        ps.wait_for_mappings();
+        for dir in &self.conf.get_all_data_dirs() {
+            match remove_dir_all(format!("{}/{}", dir, tid)) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                    return Err(BaseRtError::WrappingIoError(e));
                 }
+                _ => {}
             }
         }
+        ps.uncache_for_table(tid, cids)?;
+        Ok(())
     }
-    Err(BaseRtError::InvalidStringConversion(s.as_bytes().to_vec()))
-}
 
-fn parse_literal_as_bytes(lit: &str, btyp: BqlType) -> BaseRtResult<Vec<u8>> {
-    let mut rt = Vec::new();
-    match btyp {
-        BqlType::UInt(bits) => match bits {
-            64 => {
-                let v = lit
-                    .parse::<u64>()
-                    .map_err(|_e| BaseRtError::InsertIntoValueParsingError)?
-                    .to_le_bytes();
-                rt.extend(&v);
+    //WARN the table lock should be held
+    fn restore_parts_locked(&self, tid: Id, cids: &[Id], bp: &str) -> BaseRtResult<()> {
+        let ps = &self.part_store;
+        for e in read_dir(bp)? {
+            let e = e?;
+            let fname = e.file_name();
+            let ptk = match fname
+                .to_str()
+                .and_then(|s| s.strip_suffix(".part"))
+                .and_then(|s| s.parse::<u64>().ok())
+            {
+                Some(ptk) => ptk,
+                None => continue,
+            };
+            let mut pm = PartMeta::from_bytes(&read(e.path())?)?;
+            if pm.copas.len() != cids.len() {
+                return Err(BaseRtError::InvalidBackup(format!(
+                    "part {} in {} does not match the columns",
+                    ptk, bp
+                )));
             }
-            32 => {
-                let v = lit
+            let dp = ps.place_part(tid, ptk)?;
+            let gen = ps.get_part_gen(tid, ptk)?;
+            ensure_table_path_existed(tid, dp)?;
+            copy_part_files(bp, &format!("{}/{}", dp, tid), ptk, gen, &pm, cids)?;
+            for (cm, cid) in pm.copas.iter_mut().zip(cids) {
+                cm.cid = *cid;
+            }
+            ps.put_part_meta(tid, ptk, &pm)?;
+        }
+        Ok(())
+    }
+
+    pub fn command_alter_table(
+        &self,
+        p: Pair<Rule>,
+        current_db: &str,
+    ) -> BaseRtResult<BaseCommandKind> {
+        let ctx = parse_alter_table(p).map_err(|e| BaseRtError::WrappingLangError(e))?;
+        let dbn = ctx.dbname.as_deref().unwrap_or(current_db);
+        let tn = ctx.tabname.as_str();
+        let ms = &self.meta_store;
+        let ps = &self.part_store;
+        ms.dbid_by_name(dbn).ok_or(BaseRtError::DatabaseNotExist)?;
+        let qtn = [dbn, tn].join(".");
+        let tid = ms.tid_by_qname(&qtn).ok_or(BaseRtError::TableNotExist)?;
+        if matches!(
+            ms.get_table_info_engine(tid)?,
+            EngineType::Memory | EngineType::Log
+        ) {
+            return Err(BaseRtError::UnsupportedFunctionality2(
+                "ALTER on the tables of Memory or Log engine is not supported",
+            ));
+        }
+
+        let mut script = ms
+            .get_table_info_create_script(tid)?
+            .map(|iv| String::from_utf8_lossy(&iv).into_owned())
+            .unwrap_or_default();
+        let mut convs = Vec::new();
+        let mut res = Ok(());
+        for act in &ctx.actions {
+            res = match act {
+                //NOTE the mutations write the updated rows with the table lock
+                AlterTableAction::Delete { .. } | AlterTableAction::Update { .. } => {
+                    self.mutate_table(dbn, tn, tid, act)
+                }
+                //NOTE MOVE PARTITION holds the locks of both tables
+                AlterTableAction::DropPartition { .. }
+                | AlterTableAction::DetachPartition { .. }
+                | AlterTableAction::AttachPartition { .. }
+                | AlterTableAction::MovePartition { .. }
+                | AlterTableAction::MovePartitionToVolume { .. } => {
+                    self.alter_table_partition(dbn, tn, tid, act)
+                }
+                //NOTE the parts are moved one by one with the table lock
+                AlterTableAction::Rebalance => self.rebalance_table(dbn, tn, tid),
+                _ => {
+                    ps.acquire_lock(tid)?;
+                    let res = self.alter_table_locked(
+                        dbn,
+                        tn,
+                        tid,
+                        act,
+                        &mut script,
+                        &mut convs,
+                    );
+                    ps.release_lock(tid)?;
+                    res
+                }
+            };
+            if res.is_err() {
+                break;
+            }
+        }
+        //NOTE the actions before the failed one have taken effect
+        ps.acquire_lock(tid)?;
+        let res_cr = ms.set_table_info_create_script(tid, &script);
+        ps.release_lock(tid)?;
+        for (old, new) in convs {
+            spawn_column_conversion(tid, old, new);
+        }
+        res?;
+        res_cr?;
+
+        Ok(BaseCommandKind::Alter)
+    }
+
+    fn is_key_column(&self, tid: Id, colname: &str) -> BaseRtResult<bool> {
+        let ms = &self.meta_store;
+        let keys = [
+            ms.get_table_info_primary_keys(tid)?,
+            ms.get_table_info_partition_cols(tid)?,
+        ];
+        Ok(keys.iter().flatten().any(|ks| {
+            String::from_utf8_lossy(ks)
+                .split(',')
+                .any(|k| k.trim() == colname)
+        }))
+    }
+
+    //WARN the table lock should be held
+    fn alter_table_locked(
+        &self,
+        dbn: &str,
+        tn: &str,
+        tid: Id,
+        act: &AlterTableAction,
+        script: &mut String,
+        convs: &mut Vec<(Id, Id)>,
+    ) -> BaseRtResult<()> {
+        let ms = &self.meta_store;
+        let ps = &self.part_store;
+        let cid_of = |cn: &str, fallible: bool| -> BaseRtResult<Option<Id>> {
+            match ms.cid_by_qname([dbn, tn, cn].join(".")) {
+                None if fallible => Err(BaseRtError::ColumnNotExist),
+                cid_opt => Ok(cid_opt),
+            }
+        };
+        match act {
+            AlterTableAction::AddColumn {
+                name,
+                info,
+                default,
+                enum_values,
+                fallible,
+            } => {
+                let ev = enum_values.as_ref();
+                if matches!(info.data_type, BqlType::Enum8 | BqlType::Enum16)
+                    && ev.is_none()
+                {
+                    return Err(BaseRtError::SchemaInfoShouldExistButNot);
+                }
+                let dft = match default {
+                    Some(lit) if lit.eq_ignore_ascii_case("null") => {
+                        if !info.is_nullable {
+                            return Err(BaseRtError::UnsupportedColumnModification(
+                                "NULL default for non-Nullable column".to_string(),
+                            ));
+                        }
+                        None
+                    }
+                    Some(lit) => Some(match info.data_type {
+                        BqlType::String => parse_single_quoted_str(lit)?.into_bytes(),
+                        typ @ (BqlType::Enum8 | BqlType::Enum16) => {
+                            let name = parse_single_quoted_str(lit)?;
+                            let v = ev
+                                .and_then(|ev| ev.value_of(name.as_bytes()))
+                                .ok_or_else(|| {
+                                    BaseRtError::InvalidStringConversion(
+                                        name.into_bytes(),
+                                    )
+                                })?;
+                            v.to_le_bytes()[..typ.size_in_usize()?].to_vec()
+                        }
+                        BqlType::LowCardinalityString
+                        | BqlType::LowCardinalityTinyText
+                        | BqlType::Uuid
+                        | BqlType::Array(_, _) => {
+                            return Err(BaseRtError::UnsupportedColumnModification(
+                                format!("default for {:?} column", info.data_type),
+                            ))
+                        }
+                        typ => parse_literal_as_bytes(lit, typ)?,
+                    }),
+                    None => None,
+                };
+                let cid = match ms.add_column(dbn, tn, name, info) {
+                    Err(MetaError::ColExistedError(_)) if !fallible => return Ok(()),
+                    res => res?,
+                };
+                if let Some(dft) = dft {
+                    ms.set_column_default(cid, &dft)?;
+                }
+                if let Some(ev) = ev {
+                    ms.set_column_enum(cid, info.data_type, ev)?;
+                }
+                //existed rows take the default
+                ps.init_copa_bases(tid, cid, 0, BqlType::UnInit)?;
+                let def = gen_column_def(name, info, ev, default.as_deref())?;
+                alter_column_defs(script, |defs| defs.push(def));
+            }
+            AlterTableAction::DropColumn { name, fallible } => {
+                let cid = match cid_of(name.as_str(), *fallible)? {
+                    Some(cid) => cid,
+                    None => return Ok(()),
+                };
+                if self.is_key_column(tid, name)? {
+                    return Err(BaseRtError::KeyColumnCanNotBeAltered);
+                }
+                if ms.get_column_ids(&[dbn, tn].join("."))?.len() <= 1 {
+                    return Err(BaseRtError::UnsupportedColumnModification(
+                        "drop the only column of table".to_string(),
+                    ));
+                }
+                //the old column of an unfinished modification is dropped together
+                let mut srcs: Vec<Id> = ps
+                    .get_copa_bases(cid)?
+                    .into_iter()
+                    .map(|(_, base)| base.src_cid)
+                    .filter(|&src| src != 0)
+                    .collect();
+                srcs.sort_unstable();
+                srcs.dedup();
+                for src in srcs {
+                    ms.remove_detached_column(src)?;
+                    self.remove_column_data(tid, src)?;
+                }
+                ms.remove_column(dbn, tn, name)?;
+                self.remove_column_data(tid, cid)?;
+                alter_column_defs(script, |defs| {
+                    defs.retain(|d| !is_column_def_of(d, name))
+                });
+            }
+            AlterTableAction::RenameColumn { from, to, fallible } => {
+                if cid_of(from.as_str(), *fallible)?.is_none() {
+                    return Ok(());
+                }
+                if self.is_key_column(tid, from)? {
+                    return Err(BaseRtError::KeyColumnCanNotBeAltered);
+                }
+                ms.rename_column(dbn, tn, from, to)?;
+                alter_column_defs(script, |defs| {
+                    for d in defs.iter_mut().filter(|d| is_column_def_of(d, from)) {
+                        let n = column_def_name_len(d);
+                        d.replace_range(..n, to);
+                    }
+                });
+            }
+            AlterTableAction::ModifyColumn {
+                name,
+                info,
+                fallible,
+            } => {
+                let cid = match cid_of(name.as_str(), *fallible)? {
+                    Some(cid) => cid,
+                    None => return Ok(()),
+                };
+                if self.is_key_column(tid, name)? {
+                    return Err(BaseRtError::KeyColumnCanNotBeAltered);
+                }
+                let old_ci = ms
+                    .get_column_info(cid)?
+                    .ok_or(BaseRtError::SchemaInfoShouldExistButNot)?;
+                if old_ci.data_type == info.data_type
+                    && old_ci.is_nullable == info.is_nullable
+                {
+                    return Ok(());
+                }
+                if !old_ci.data_type.is_numeric_convertible()
+                    || !info.data_type.is_numeric_convertible()
+                {
+                    return Err(BaseRtError::UnsupportedColumnModification(format!(
+                        "{:?} to {:?}",
+                        old_ci.data_type, info.data_type
+                    )));
+                }
+                if !old_ci.codec.is_none() {
+                    return Err(BaseRtError::UnsupportedColumnModification(
+                        "column with codec".to_string(),
+                    ));
+                }
+                //the rows of added or converting columns are materialized in
+                //the old type before the conversion
+                let old_dft = ms.get_column_default(cid)?;
+                for (ptk, base) in ps.get_copa_bases(cid)? {
+                    let dft = old_dft.as_deref();
+                    write_copa_base_materialized(ps, tid, cid, &old_ci, ptk, &base, dft)?;
+                }
+                let dft = match ms.get_column_default(cid)? {
+                    Some(iv) => Some(convert_numeric_values(
+                        &iv,
+                        old_ci.data_type,
+                        info.data_type,
+                    )?),
+                    None => None,
+                };
+                let (old, new) = ms.replace_column(dbn, tn, name, info)?;
+                if let Some(dft) = dft {
+                    ms.set_column_default(new, &dft)?;
+                }
+                ps.init_copa_bases(tid, new, old, old_ci.data_type)?;
+                convs.push((old, new));
+                let typ = gen_column_type(info, None)?;
+                alter_column_defs(script, |defs| {
+                    for d in defs.iter_mut().filter(|d| is_column_def_of(d, name)) {
+                        let n = column_def_name_len(d);
+                        let (s, e) = column_def_type_range(&d[n..]);
+                        d.replace_range(n + s..n + e, &typ);
+                    }
+                });
+            }
+            _ => return Err(BaseRtError::ShouldNotReachHere),
+        }
+        Ok(())
+    }
+
+    fn alter_table_partition(
+        &self,
+        dbn: &str,
+        tn: &str,
+        tid: Id,
+        act: &AlterTableAction,
+    ) -> BaseRtResult<()> {
+        let ms = &self.meta_store;
+        let ps = &self.part_store;
+        let mut col_infos = ms.get_columns(dbn, tn)?;
+        col_infos.sort_unstable_by_key(|c| c.2.ordinal);
+        let cids: Vec<Id> = col_infos.iter().map(|c| c.1).collect();
+        match act {
+            AlterTableAction::DropPartition { ptk } => {
+                ps.acquire_lock(tid)?;
+                let res = self.drop_partition_locked(tid, *ptk, &cids);
+                ps.release_lock(tid)?;
+                res
+            }
+            AlterTableAction::DetachPartition { ptk } => {
+                ps.acquire_lock(tid)?;
+                let res = self.detach_partition_locked(tid, *ptk, &cids);
+                ps.release_lock(tid)?;
+                res
+            }
+            AlterTableAction::AttachPartition { ptk } => {
+                ps.acquire_lock(tid)?;
+                let res = self.attach_partition_locked(tid, *ptk, &cids);
+                ps.release_lock(tid)?;
+                res
+            }
+            AlterTableAction::MovePartition {
+                ptk,
+                dbname,
+                tabname,
+            } => {
+                let dbn_to = dbname.as_deref().unwrap_or(dbn);
+                let qtn_to = [dbn_to, tabname.as_str()].join(".");
+                let tid_to =
+                    ms.tid_by_qname(&qtn_to).ok_or(BaseRtError::TableNotExist)?;
+                if tid_to == tid {
+                    return Err(BaseRtError::UnsupportedPartitionOperation(
+                        "move partition into the same table".to_string(),
+                    ));
+                }
+                let mut cols_to = ms.get_columns(dbn_to, tabname)?;
+                cols_to.sort_unstable_by_key(|c| c.2.ordinal);
+                self.check_same_structure(tid, &col_infos, tid_to, &cols_to)?;
+                let cids_to: Vec<Id> = cols_to.iter().map(|c| c.1).collect();
+                //NOTE the locks are acquired in the order of tids to avoid the
+                //     deadlock with a reverse moving
+                let (l0, l1) = if tid < tid_to {
+                    (tid, tid_to)
+                } else {
+                    (tid_to, tid)
+                };
+                ps.acquire_lock(l0)?;
+                ps.acquire_lock(l1)?;
+                let res = self.move_partition_locked(tid, &cids, tid_to, &cids_to, *ptk);
+                ps.release_lock(l1)?;
+                ps.release_lock(l0)?;
+                res
+            }
+            AlterTableAction::MovePartitionToVolume { ptk, volume } => {
+                ps.acquire_lock(tid)?;
+                let res = self.move_partition_to_volume_locked(tid, *ptk, &cids, volume);
+                ps.release_lock(tid)?;
+                res
+            }
+            _ => Err(BaseRtError::ShouldNotReachHere),
+        }
+    }
+
+    ///return the meta of a part which can be dropped or moved as a whole
+    ///
+    ///WARN the table lock should be held
+    fn get_movable_part_meta(
+        &self,
+        tid: Id,
+        ptk: u64,
+        cids: &[Id],
+    ) -> BaseRtResult<PartMeta> {
+        let ps = &self.part_store;
+        let pm = ps
+            .get_part_meta(tid, ptk, cids)?
+            .ok_or(BaseRtError::PartitionNotExist)?;
+        //the rows reserved by an insert are not committed until it finishes
+        if ps.get_part_size_reserved(tid, ptk)? != pm.rows {
+            return Err(BaseRtError::UnsupportedPartitionOperation(
+                "partition is being written".to_string(),
+            ));
+        }
+        if pm.copas.iter().any(|cm| cm.base.src_cid != 0) {
+            return Err(BaseRtError::UnsupportedPartitionOperation(
+                "partition has columns being converted".to_string(),
+            ));
+        }
+        Ok(pm)
+    }
+
+    ///return if the part exists in the table, which is replaced as a whole by
+    ///the part moved or attached into the table
+    ///
+    ///WARN the table lock should be held
+    fn is_part_replaced(&self, tid: Id, ptk: u64, cids: &[Id]) -> BaseRtResult<bool> {
+        match self.get_movable_part_meta(tid, ptk, cids) {
+            Ok(_) => Ok(true),
+            //the rows reserved by an insert are not committed until it finishes
+            Err(BaseRtError::PartitionNotExist)
+                if self.part_store.get_part_size_reserved(tid, ptk)? > 0 =>
+            {
+                Err(BaseRtError::UnsupportedPartitionOperation(
+                    "partition is being written".to_string(),
+                ))
+            }
+            Err(BaseRtError::PartitionNotExist) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    //WARN the table lock should be held
+    fn drop_partition_locked(&self, tid: Id, ptk: u64, cids: &[Id]) -> BaseRtResult<()> {
+        let ps = &self.part_store;
+        match self.get_movable_part_meta(tid, ptk, cids) {
+            //NOTE to drop a partition which does not exist is a no-op
+            Err(BaseRtError::PartitionNotExist) => return Ok(()),
+            res => res?,
+        };
+        ps.clear_part(tid, ptk, cids)?;
+        self.remove_part_files(tid, ptk, cids)
+    }
+
+    ///move the files of the part into the detached dir of the table, with its
+    ///part meta saved in `{ptk}.part` there
+    ///
+    ///WARN the table lock should be held
+    fn detach_partition_locked(
+        &self,
+        tid: Id,
+        ptk: u64,
+        cids: &[Id],
+    ) -> BaseRtResult<()> {
+        let ps = &self.part_store;
+        let pm = self.get_movable_part_meta(tid, ptk, cids)?;
+        let tp = format!("{}/{}", ps.get_part_dir(tid, ptk)?, tid);
+        let dtp = format!("{}/detached", tp);
+        create_dir_all(&dtp)?;
+        let mp = format!("{}/{}.part", dtp, ptk);
+        if Path::new(&mp).exists() {
+            return Err(BaseRtError::PartitionExisted);
+        }
+        write(&mp, pm.to_bytes())?;
+        let cid_pairs: Vec<(Id, Id)> = cids.iter().map(|c| (*c, *c)).collect();
+        let gen = ps.get_part_gen(tid, ptk)?;
+        rename_part_files(&tp, &dtp, ptk, gen, 0, &cid_pairs)?;
+        ps.clear_part(tid, ptk, cids)?;
+        Ok(())
+    }
+
+    //WARN the table lock should be held
+    fn attach_partition_locked(
+        &self,
+        tid: Id,
+        ptk: u64,
+        cids: &[Id],
+    ) -> BaseRtResult<()> {
+        let ps = &self.part_store;
+        let tp = format!("{}/{}", ps.get_part_dir(tid, ptk)?, tid);
+        let dtp = format!("{}/detached", tp);
+        let mp = format!("{}/{}.part", dtp, ptk);
+        let bs = match read(&mp) {
+            Ok(bs) => bs,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(BaseRtError::PartitionNotExist);
+            }
+            Err(e) => return Err(BaseRtError::WrappingIoError(e)),
+        };
+        let pm = PartMeta::from_bytes(&bs)?;
+        //FIXME the detached part can not be attached after the columns of the
+        //      table have been altered
+        if pm.copas.len() != cids.len()
+            || pm.copas.iter().zip(cids).any(|(cm, cid)| cm.cid != *cid)
+        {
+            return Err(BaseRtError::UnsupportedPartitionOperation(
+                "columns of the table have been altered since detached".to_string(),
+            ));
+        }
+        let cid_pairs: Vec<(Id, Id)> = cids.iter().map(|c| (*c, *c)).collect();
+        let gen = ps.get_part_gen(tid, ptk)?;
+        if !self.is_part_replaced(tid, ptk, cids)? {
+            rename_part_files(&dtp, &tp, ptk, 0, gen, &cid_pairs)?;
+            ps.put_part_meta(tid, ptk, &pm)?;
+            remove_file(&mp)?;
+            return Ok(());
+        }
+        //NOTE the files are linked into the next generation, so the part in
+        //     the table is switched to the attached one atomically
+        let gen_to = gen + 1;
+        remove_part_files_in(&tp, ptk, gen_to, cids)?;
+        link_part_files(&dtp, &tp, ptk, 0, gen_to, &cid_pairs)?;
+        if self.conf.storage.durability != Durability::None {
+            sync_part_files(ps.get_part_dir(tid, ptk)?, tid, ptk, gen_to, cids)?;
+        }
+        ps.replace_part_meta(tid, ptk, cids, &pm, gen_to)?;
+        ps.flush()?;
+        remove_file(&mp)?;
+        remove_part_files_in(&dtp, ptk, 0, cids)?;
+        //the replaced files are removed after the queries have mapped them
+        ps.wait_for_mappings();
+        remove_part_files_in(&tp, ptk, gen, cids)
+    }
+
+    ///the columns(in the order of ordinals), partition and primary keys and
+    ///the index granularity of two tables should be the same to move parts
+    fn check_same_structure(
+        &self,
+        tid: Id,
+        cols: &[(String, Id, ColumnInfo)],
+        tid_to: Id,
+        cols_to: &[(String, Id, ColumnInfo)],
+    ) -> BaseRtResult<()> {
+        let ms = &self.meta_store;
+        let err = |what: &str| {
+            Err(BaseRtError::UnsupportedPartitionOperation(format!(
+                "tables have different {}",
+                what
+            )))
+        };
+        if cols.len() != cols_to.len() {
+            return err("columns");
+        }
+        for (c, ct) in cols.iter().zip(cols_to) {
+            let (ci, cit) = (
+                ColumnInfo { ordinal: 0, ..c.2 },
+                ColumnInfo { ordinal: 0, ..ct.2 },
+            );
+            if c.0 != ct.0 || ci != cit {
+                return err("columns");
+            }
+            if ms.get_column_default(c.1)? != ms.get_column_default(ct.1)? {
+                return err("column defaults");
+            }
+        }
+        if ms.get_table_info_partition_keys_expr(tid)?
+            != ms.get_table_info_partition_keys_expr(tid_to)?
+        {
+            return err("partition keys");
+        }
+        if ms.get_table_info_primary_keys(tid)?
+            != ms.get_table_info_primary_keys(tid_to)?
+        {
+            return err("primary keys");
+        }
+        if ms.get_table_info_index_granularity(tid)?
+            != ms.get_table_info_index_granularity(tid_to)?
+        {
+            return err("index granularities");
+        }
+        Ok(())
+    }
+
+    //WARN the locks of both tables should be held
+    fn move_partition_locked(
+        &self,
+        tid: Id,
+        cids: &[Id],
+        tid_to: Id,
+        cids_to: &[Id],
+        ptk: u64,
+    ) -> BaseRtResult<()> {
+        let ps = &self.part_store;
+        let mut pm = self.get_movable_part_meta(tid, ptk, cids)?;
+        //NOTE the part is moved into the same dir of the table_to, or into
+        //     the dir and the next generation of the part replaced there, so
+        //     the replaced part is switched to the moved one atomically
+        let replaced = self.is_part_replaced(tid_to, ptk, cids_to)?;
+        let dp = ps.get_part_dir(tid, ptk)?;
+        let dp_to = if replaced {
+            ps.get_part_dir(tid_to, ptk)?
+        } else {
+            dp
+        };
+        ensure_table_path_existed(tid_to, dp_to)?;
+        let (tp, tp_to) = (format!("{}/{}", dp, tid), format!("{}/{}", dp_to, tid_to));
+        let (gen, gen_to) = (ps.get_part_gen(tid, ptk)?, ps.get_part_gen(tid_to, ptk)?);
+        let gen_new = if replaced { gen_to + 1 } else { gen_to };
+        //the files are linked into the table_to and synced before switching,
+        //the files left by a move interrupted before switching are replaced
+        remove_part_files_in(&tp_to, ptk, gen_new, cids_to)?;
+        let cid_pairs: Vec<(Id, Id)> =
+            cids.iter().copied().zip(cids_to.iter().copied()).collect();
+        link_part_files(&tp, &tp_to, ptk, gen, gen_new, &cid_pairs)?;
+        if self.conf.storage.durability != Durability::None {
+            sync_part_files(dp_to, tid_to, ptk, gen_new, cids_to)?;
+        }
+        for (cm, cid_to) in pm.copas.iter_mut().zip(cids_to) {
+            cm.cid = *cid_to;
+        }
+        if replaced {
+            ps.replace_part_meta(tid_to, ptk, cids_to, &pm, gen_new)?;
+        } else {
+            ps.set_part_dir(tid_to, ptk, dp)?;
+            ps.put_part_meta(tid_to, ptk, &pm)?;
+        }
+        ps.clear_part(tid, ptk, cids)?;
+        //the old files are removed only after the switching is persisted and
+        //the queries which have taken the part have mapped them
+        ps.flush()?;
+        ps.wait_for_mappings();
+        if replaced {
+            remove_part_files_in(&tp_to, ptk, gen_to, cids_to)?;
+        }
+        remove_part_files_in(&tp, ptk, gen, cids)
+    }
+
+    //WARN the table lock should be held
+    fn move_partition_to_volume_locked(
+        &self,
+        tid: Id,
+        ptk: u64,
+        cids: &[Id],
+        vol: &str,
+    ) -> BaseRtResult<()> {
+        let ps = &self.part_store;
+        self.get_movable_part_meta(tid, ptk, cids)?;
+        let dp_to = ps.choose_volume_dir(vol)?;
+        if ps.get_part_volume(tid, ptk)? == vol {
+            return Ok(());
+        }
+        self.move_part_dir_locked(tid, ptk, cids, dp_to)
+    }
+
+    ///move the parts of the table into the dirs with more available space in
+    ///their volumes
+    fn rebalance_table(&self, dbn: &str, tn: &str, tid: Id) -> BaseRtResult<()> {
+        let ms = &self.meta_store;
+        let ps = &self.part_store;
+        let cids: Vec<Id> = ms.get_columns(dbn, tn)?.iter().map(|c| c.1).collect();
+        for (ptk, _) in ps.get_part_sizes(tid)? {
+            ps.acquire_lock(tid)?;
+            let res = self.rebalance_part_locked(tid, ptk, &cids);
+            ps.release_lock(tid)?;
+            match res {
+                //NOTE the parts being written or converted are left
+                Err(BaseRtError::UnsupportedPartitionOperation(_))
+                | Err(BaseRtError::PartitionNotExist) => {}
+                res => res?,
+            }
+        }
+        Ok(())
+    }
+
+    //WARN the table lock should be held
+    fn rebalance_part_locked(&self, tid: Id, ptk: u64, cids: &[Id]) -> BaseRtResult<()> {
+        let ps = &self.part_store;
+        self.get_movable_part_meta(tid, ptk, cids)?;
+        let dp = ps.get_part_dir(tid, ptk)?;
+        let dp_to = ps.choose_volume_dir(ps.get_part_volume(tid, ptk)?)?;
+        if dp == dp_to {
+            return Ok(());
+        }
+        //the part is moved only if the dirs are more balanced after moving
+        let gen = ps.get_part_gen(tid, ptk)?;
+        let siz = get_part_files_size(&format!("{}/{}", dp, tid), ptk, gen, cids)?;
+        if ps.get_dir_space(dp_to)? <= ps.get_dir_space(dp)? + siz {
+            return Ok(());
+        }
+        self.move_part_dir_locked(tid, ptk, cids, dp_to)
+    }
+
+    ///link or copy the files of the part into the dir, then switch the part
+    ///to the dir before removing the old files
+    ///
+    ///WARN the table lock should be held
+    fn move_part_dir_locked(
+        &self,
+        tid: Id,
+        ptk: u64,
+        cids: &[Id],
+        dp_to: &str,
+    ) -> BaseRtResult<()> {
+        let ps = &self.part_store;
+        let dp = ps.get_part_dir(tid, ptk)?;
+        if dp == dp_to {
+            return Ok(());
+        }
+        ensure_table_path_existed(tid, dp_to)?;
+        let (tp, tp_to) = (format!("{}/{}", dp, tid), format!("{}/{}", dp_to, tid));
+        let gen = ps.get_part_gen(tid, ptk)?;
+        //NOTE the files left by a move interrupted before switching are
+        //     replaced here
+        remove_part_files_in(&tp_to, ptk, gen, cids)?;
+        let cid_pairs: Vec<(Id, Id)> = cids.iter().map(|c| (*c, *c)).collect();
+        link_part_files(&tp, &tp_to, ptk, gen, gen, &cid_pairs)?;
+        if self.conf.storage.durability != Durability::None {
+            sync_part_files(dp_to, tid, ptk, gen, cids)?;
+        }
+        ps.set_part_dir(tid, ptk, dp_to)?;
+        //the old files are removed only after the switching is persisted and
+        //the queries which have taken the old dir have mapped them
+        ps.flush()?;
+        ps.wait_for_mappings();
+        remove_part_files_in(&tp, ptk, gen, cids)
+    }
+
+    ///apply the DELETE or UPDATE mutation to the rows which have been written,
+    ///the matched rows are marked in the deletion bitmaps of their parts and
+    ///the updated rows are appended as new rows
+    fn mutate_table(
+        &self,
+        dbn: &str,
+        tn: &str,
+        tid: Id,
+        act: &AlterTableAction,
+    ) -> BaseRtResult<()> {
+        let ms = &self.meta_store;
+        let ps = &self.part_store;
+        let mut col_infos = ms.get_columns(dbn, tn)?;
+        col_infos.sort_unstable_by_key(|c| c.2.ordinal);
+        let (predicate, assignments) = match act {
+            AlterTableAction::Delete { predicate } => (predicate, None),
+            AlterTableAction::Update {
+                assignments,
+                predicate,
+            } => (predicate, Some(assignments)),
+            _ => return Err(BaseRtError::ShouldNotReachHere),
+        };
+        let mut projs = vec![["(", predicate, ")"].concat()];
+        match assignments {
+            //NOTE at least one column of the table should be scanned
+            None => projs.push(col_infos[0].0.clone()),
+            Some(asgns) => {
+                for (cn, _) in asgns {
+                    if !col_infos.iter().any(|c| &c.0 == cn) {
+                        return Err(BaseRtError::ColumnNotExist);
+                    }
+                    if self.is_key_column(tid, cn)? {
+                        return Err(BaseRtError::KeyColumnCanNotBeAltered);
+                    }
+                }
+                if col_infos.iter().any(|c| {
+                    matches!(
+                        c.2.data_type,
+                        BqlType::LowCardinalityString | BqlType::LowCardinalityTinyText
+                    )
+                }) {
+                    return Err(BaseRtError::UnsupportedColumnModification(
+                        "update on table with LowCardinality column".to_string(),
+                    ));
+                }
+                for (cn, _, _) in &col_infos {
+                    match asgns.iter().find(|(c, _)| c == cn) {
+                        Some((_, e)) => projs.push(["(", e, ") AS ", cn].concat()),
+                        None => projs.push(cn.clone()),
+                    }
+                }
+            }
+        }
+        let sql = ["SELECT ", &projs.join(", "), " FROM ", tn].concat();
+        //NOTE the rows are scanned, marked and appended under the table lock,
+        //     so that no write, merge or mutation changes the parts in between
+        let _dg = DURABILITY_SYNC_LOCK.read().unwrap();
+        ps.acquire_lock(tid)?;
+        let is_update = assignments.is_some();
+        let res = self.mutate_table_locked(dbn, tn, tid, &col_infos, &sql, is_update);
+        ps.release_lock(tid)?;
+        res
+    }
+
+    //WARN the table lock should be held
+    fn mutate_table_locked(
+        &self,
+        dbn: &str,
+        tn: &str,
+        tid: Id,
+        col_infos: &[(String, Id, ColumnInfo)],
+        sql: &str,
+        is_update: bool,
+    ) -> BaseRtResult<()> {
+        let ms = &self.meta_store;
+        let ps = &self.part_store;
+        let p = BaseMgmtSys::parse_cmd_as_pair(sql)?;
+        let res = query_by_parts(ms, ps, dbn, p, 0..=u64::MAX)?;
+
+        let mut bms = Vec::new();
+        let mut blks = Vec::new();
+        for (ptk, batch) in res {
+            let pred = batch
+                .column(0)
+                .as_any()
+                .downcast_ref::<BooleanArray>()
+                .ok_or(BaseRtError::UnsupportedColumnModification(
+                    "non-boolean predicate".to_string(),
+                ))?;
+            let old = ps.get_deletion_bitmap(tid, ptk)?.unwrap_or_default();
+            let n = pred.len();
+            let mut bm = vec![0u8; (n + 7) / 8];
+            let mut matched = Vec::with_capacity(n);
+            for i in 0..n {
+                let deleted = i / 8 < old.len() && old[i / 8] & (1 << (i % 8)) != 0;
+                let m = !deleted && pred.is_valid(i) && pred.value(i);
+                if m {
+                    bm[i / 8] |= 1 << (i % 8);
+                }
+                matched.push(m);
+            }
+            if !matched.iter().any(|m| *m) {
+                continue;
+            }
+            if is_update {
+                let batch = filter_record_batch(&batch, &BooleanArray::from(matched))?;
+                let mut fields = Vec::with_capacity(col_infos.len());
+                let mut cols = Vec::with_capacity(col_infos.len());
+                for (j, (cn, _, ci)) in col_infos.iter().enumerate() {
+                    let col = batch.column(j + 1);
+                    //NOTE the names assigned to Enum are converted when written
+                    let typ = match (ci.data_type, col.data_type()) {
+                        (BqlType::Enum8 | BqlType::Enum16, DataType::LargeUtf8) => {
+                            DataType::LargeUtf8
+                        }
+                        (typ, _) => btype_to_arrow_type(typ)?,
+                    };
+                    cols.push(if col.data_type() != &typ {
+                        cast(col, &typ)?
+                    } else {
+                        col.clone()
+                    });
+                    fields.push(Field::new(cn, typ, ci.is_nullable));
+                }
+                blks.push(RecordBatch::try_new(Arc::new(Schema::new(fields)), cols)?);
+            }
+            bms.push((ptk, bm));
+        }
+
+        //NOTE the updated rows are appended before the old ones are deleted
+        let qtn = [dbn, tn].join(".");
+        for b in blks {
+            let mut blk: BaseDataBlock = b.try_into()?;
+            write_block_locked(&mut blk, &qtn, tid)?;
+        }
+        for (ptk, bm) in bms {
+            ps.merge_deletion_bitmap(tid, ptk, &bm)?;
+        }
+        Ok(())
+    }
+
+    ///merge the parts which have deleted rows to drop these rows physically
+    fn purge_deleted_rows(&self, dbn: &str, tn: &str, tid: Id) -> BaseRtResult<()> {
+        for ptk in self.part_store.get_deletion_bitmaps(tid)?.keys() {
+            //NOTE the part changed during the merge is left to the next time
+            self.merge_part(dbn, tn, tid, *ptk)?;
+        }
+        Ok(())
+    }
+
+    ///merge the parts of the table with ptk in ptks, only the parts which have
+    ///been written more than once, have deleted rows or have rows of added
+    ///columns not written are merged unless is_final
+    fn optimize_table(
+        &self,
+        dbn: &str,
+        tn: &str,
+        tid: Id,
+        ptks: RangeInclusive<u64>,
+        is_final: bool,
+    ) -> BaseRtResult<()> {
+        let ps = &self.part_store;
+        let cids: Vec<Id> = self
+            .get_sorted_columns(dbn, tn)?
+            .iter()
+            .map(|c| c.1)
+            .collect();
+        let now = now_in_secs();
+        for (ptk, _) in ps.get_part_sizes(tid)? {
+            if !ptks.contains(&ptk) {
+                continue;
+            }
+            if !is_final {
+                let pm = match ps.get_part_meta(tid, ptk, &cids)? {
+                    Some(pm) => pm,
+                    None => continue,
+                };
+                let merged = ps.get_part_runs(tid, ptk)? <= 1
+                    && pm.dels.iter().all(|b| *b == 0)
+                    && pm.copas.iter().all(|cm| cm.base.rows == 0);
+                if merged {
+                    continue;
+                }
+            }
+            if let Some(rows) = self.merge_part(dbn, tn, tid, ptk)? {
+                self.log_housekeeping(dbn, tn, "MergePart", ptk, rows, now)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn get_sorted_columns(
+        &self,
+        dbn: &str,
+        tn: &str,
+    ) -> BaseRtResult<Vec<(String, Id, ColumnInfo)>> {
+        let mut col_infos = self.meta_store.get_columns(dbn, tn)?;
+        col_infos.sort_unstable_by_key(|c| c.2.ordinal);
+        Ok(col_infos)
+    }
+
+    ///rewrite the part into contiguous files without its deleted rows, then
+    ///switch the part to them atomically, return the rows of the merged part,
+    ///or None if the part has been changed during the merge
+    ///
+    ///The merged files are staged in the merging dir of the data dir of the
+    ///part, named in the next generation of the part. The meta of the merged
+    ///part is persisted there as the commit point of the switch, so that the
+    ///switch interrupted by a crash is rolled forward by `recover_part_merges`
+    ///at startup. The queries which have taken the part before the switch keep
+    ///reading the files of the old generation.
+    fn merge_part(
+        &self,
+        dbn: &str,
+        tn: &str,
+        tid: Id,
+        ptk: u64,
+    ) -> BaseRtResult<Option<usize>> {
+        let ms = &self.meta_store;
+        let ps = &self.part_store;
+        let col_infos = self.get_sorted_columns(dbn, tn)?;
+        let cns: Vec<&str> = col_infos.iter().map(|c| c.0.as_str()).collect();
+        let cids: Vec<Id> = col_infos.iter().map(|c| c.1).collect();
+        let pm = match ps.get_part_meta(tid, ptk, &cids)? {
+            Some(pm) => pm,
+            None => return Ok(None),
+        };
+        //the part with the columns being converted is merged after them
+        if pm.copas.iter().any(|cm| cm.base.src_cid != 0) {
+            return Ok(None);
+        }
+        let dp = ps.get_part_dir(tid, ptk)?;
+        let gen = ps.get_part_gen(tid, ptk)? + 1;
+        let sql = ["SELECT ", &cns.join(", "), " FROM ", tn].concat();
+        let p = BaseMgmtSys::parse_cmd_as_pair(&sql)?;
+        let batch = match query_by_parts(ms, ps, dbn, p, ptk..=ptk)?.pop() {
+            Some((_, batch)) if batch.num_rows() == pm.rows => batch,
+            _ => return Ok(None),
+        };
+        let bm = &pm.dels;
+        let batch = if bm.iter().any(|b| *b != 0) {
+            let keep: Vec<bool> = (0..pm.rows)
+                .map(|i| i / 8 >= bm.len() || bm[i / 8] & (1 << (i % 8)) == 0)
+                .collect();
+            filter_record_batch(&batch, &BooleanArray::from(keep))?
+        } else {
+            batch
+        };
+        let qtn = [dbn, tn].join(".");
+        let batch = match get_collapsing(ms, &qtn, tid)? {
+            Some(c) => collapse_batch(batch, &c)?,
+            None => batch,
+        };
+
+        let mdp = merging_dir_of(dp);
+        let mtp = format!("{}/{}", mdp, tid);
+        if Path::new(&merge_commit_path(&mtp, ptk, gen)).exists() {
+            log::warn!(
+                "the merge of part {} of table {} is left to be recovered",
+                ptk,
+                tid
+            );
+            return Ok(None);
+        }
+        //NOTE the files left by a merge interrupted before committing are
+        //     replaced here
+        remove_part_files_in(&mtp, ptk, gen, &cids)?;
+        let mpm = if batch.num_rows() > 0 {
+            let blk: BaseDataBlock = batch.try_into()?;
+            let mpm = write_merged_part(&blk, ptk, gen, ms, &qtn, tid, &mdp)?;
+            if self.conf.storage.durability != Durability::None {
+                sync_part_files(&mdp, tid, ptk, gen, &cids)?;
+            }
+            Some(mpm)
+        } else {
+            None
+        };
+
+        ps.acquire_lock(tid)?;
+        let res = self.switch_merged_part_locked(
+            dbn,
+            tn,
+            tid,
+            ptk,
+            dp,
+            &pm,
+            mpm.as_ref().map(|mpm| (mpm, gen)),
+        );
+        ps.release_lock(tid)?;
+        //NOTE the files staged are kept for the switch failed after committing
+        if let Ok(None) = res {
+            remove_part_files_in(&mtp, ptk, gen, &cids)?;
+        }
+        res
+    }
+
+    ///switch the part to the merged part staged in the generation if the part
+    ///is still the one merged from, the part is removed if no row is left
+    ///
+    ///WARN the table lock should be held
+    fn switch_merged_part_locked(
+        &self,
+        dbn: &str,
+        tn: &str,
+        tid: Id,
+        ptk: u64,
+        dp: &str,
+        pm: &PartMeta,
+        mpm: Option<(&PartMeta, usize)>,
+    ) -> BaseRtResult<Option<usize>> {
+        let ps = &self.part_store;
+        let cids: Vec<Id> = pm.copas.iter().map(|cm| cm.cid).collect();
+        let col_infos = self.get_sorted_columns(dbn, tn)?;
+        if col_infos.iter().map(|c| c.1).ne(cids.iter().copied())
+            || ps.get_part_dir(tid, ptk)? != dp
+            || ps.get_part_size_reserved(tid, ptk)? != pm.rows
+            || ps.get_part_meta(tid, ptk, &cids)?.as_ref() != Some(pm)
+        {
+            return Ok(None);
+        }
+        match mpm {
+            Some((mpm, gen)) => {
+                let mtp = format!("{}/{}", merging_dir_of(dp), tid);
+                write_file_synced(&merge_commit_path(&mtp, ptk, gen), &mpm.to_bytes())?;
+                switch_part_files(ps, dp, tid, ptk, gen, mpm)?;
+                Ok(Some(mpm.rows))
+            }
+            None => {
+                ps.clear_part(tid, ptk, &cids)?;
+                self.remove_part_files(tid, ptk, &cids)?;
+                Ok(Some(0))
+            }
+        }
+    }
+
+    ///remove the part if its reserved rows are still the size, return whether
+    ///it has been removed
+    fn remove_part_of_size(
+        &self,
+        tid: Id,
+        ptk: u64,
+        size: usize,
+        cids: &[Id],
+    ) -> BaseRtResult<bool> {
+        let ps = &self.part_store;
+        ps.acquire_lock(tid)?;
+        let res = match ps.get_part_size_reserved(tid, ptk) {
+            Ok(n) if n == size => ps
+                .clear_part(tid, ptk, cids)
+                .map_err(BaseRtError::WrappingMetaError)
+                .and_then(|_| self.remove_part_files(tid, ptk, cids))
+                .map(|_| true),
+            Ok(_) => Ok(false),
+            Err(e) => Err(BaseRtError::WrappingMetaError(e)),
+        };
+        ps.release_lock(tid)?;
+        res
+    }
+
+    ///run the background housekeeping once, which expires the rows of the
+    ///tables with TTL now
+    pub fn run_housekeeping(&self) -> BaseRtResult<()> {
+        let ms = &self.meta_store;
+        let now = now_in_secs();
+        for dbn in ms.get_database_names()? {
+            if dbn == "system" {
+                continue;
+            }
+            for tn in ms.get_table_names(&dbn)? {
+                let tid = match ms.tid_by_qname([dbn.as_str(), tn.as_str()].join(".")) {
+                    Some(tid) => tid,
+                    None => continue,
+                };
+                if let Some((ttl_col, ttl_secs)) = ms.get_table_info_ttl(tid)? {
+                    let res = self.expire_table(&dbn, &tn, tid, &ttl_col, ttl_secs, now);
+                    if let Err(e) = res {
+                        log::error!("fail to expire the rows of {}.{}: {}", dbn, tn, e);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    ///run the background merges once, which merge the parts written too many
+    ///times or with too many deleted rows
+    pub fn run_merges(&self) -> BaseRtResult<()> {
+        let ms = &self.meta_store;
+        for dbn in ms.get_database_names()? {
+            if dbn == "system" {
+                continue;
+            }
+            for tn in ms.get_table_names(&dbn)? {
+                let tid = match ms.tid_by_qname([dbn.as_str(), tn.as_str()].join(".")) {
+                    Some(tid) => tid,
+                    None => continue,
+                };
+                if let Err(e) = self.merge_table(&dbn, &tn, tid) {
+                    log::error!("fail to merge the parts of {}.{}: {}", dbn, tn, e);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn merge_table(&self, dbn: &str, tn: &str, tid: Id) -> BaseRtResult<()> {
+        let ps = &self.part_store;
+        let st = &self.conf.storage;
+        let now = now_in_secs();
+        //NOTE each insert is sorted by the key alone, so a part of a table with
+        //      primary key is merged(and sorted as a whole) once it has two runs
+        let min_runs = if self.meta_store.get_table_info_sort_key(tid)?.is_some() {
+            2
+        } else {
+            st.merge_min_runs
+        };
+        for (ptk, size) in ps.get_part_sizes(tid)? {
+            if size == 0 || size > st.merge_max_rows {
+                continue;
+            }
+            let ndels: usize = ps
+                .get_deletion_bitmap(tid, ptk)?
+                .map(|bm| bm.iter().map(|b| b.count_ones() as usize).sum())
+                .unwrap_or_default();
+            let many_dels =
+                ndels > 0 && ndels as f64 >= size as f64 * st.merge_min_deleted_ratio;
+            if ps.get_part_runs(tid, ptk)? < min_runs && !many_dels {
+                continue;
+            }
+            if let Some(rows) = self.merge_part(dbn, tn, tid, ptk)? {
+                self.log_housekeeping(dbn, tn, "MergePart", ptk, rows, now)?;
+            }
+        }
+        Ok(())
+    }
+
+    ///drop the parts of which all rows have expired by the TTL, and rewrite
+    ///the parts of which some rows have expired
+    fn expire_table(
+        &self,
+        dbn: &str,
+        tn: &str,
+        tid: Id,
+        ttl_col: &str,
+        ttl_secs: u64,
+        now: u64,
+    ) -> BaseRtResult<()> {
+        let ms = &self.meta_store;
+        let ps = &self.part_store;
+        if now < ttl_secs {
+            return Ok(());
+        }
+        let qtn = [dbn, tn].join(".");
+        let cid = ms
+            .cid_by_qname([qtn.as_str(), ttl_col].join("."))
+            .ok_or(BaseRtError::ColumnNotExist)?;
+        let ci = ms
+            .get_column_info(cid)?
+            .ok_or(BaseRtError::SchemaInfoShouldExistButNot)?;
+        //the rows whose value of ttl_col is not after the bound have expired
+        let bound = match ci.data_type {
+            BqlType::Date => (now - ttl_secs) / 86400,
+            BqlType::DateTime | BqlType::DateTimeTz(_) => now - ttl_secs,
+            _ => return Err(BaseRtError::InvalidTTLColumn),
+        };
+        let cids: Vec<Id> = ms.get_columns(dbn, tn)?.iter().map(|c| c.1).collect();
+
+        //the parts which can not be decided by the stats are scanned
+        let mut scans = Vec::new();
+        for (ptk, size) in ps.get_part_sizes(tid)? {
+            match ps.get_copa_stats(cid, ptk)? {
+                Some(st) if st.rows as usize >= size => {
+                    if !st.has_values() || st.min > bound {
+                        continue;
+                    }
+                    if st.nulls == 0 && st.max <= bound {
+                        if self.remove_part_of_size(tid, ptk, size, &cids)? {
+                            self.log_housekeeping(dbn, tn, "DropPart", ptk, size, now)?;
+                        }
+                        continue;
+                    }
+                    scans.push(ptk);
+                }
+                _ => scans.push(ptk),
+            }
+        }
+        if scans.is_empty() {
+            return Ok(());
+        }
+        let sql = ["SELECT ", ttl_col, " FROM ", tn].concat();
+        //NOTE the rows are scanned and marked under the table lock, so that no
+        //     write, merge or mutation changes the parts in between
+        let _dg = DURABILITY_SYNC_LOCK.read().unwrap();
+        ps.acquire_lock(tid)?;
+        let res = self.expire_rows_locked(dbn, tid, ci.data_type, &sql, &scans, bound);
+        ps.release_lock(tid)?;
+        drop(_dg);
+        let expired = res?;
+        if expired.is_empty() {
+            return Ok(());
+        }
+        self.purge_deleted_rows(dbn, tn, tid)?;
+        for (ptk, nexp) in expired {
+            self.log_housekeeping(dbn, tn, "RewritePart", ptk, nexp, now)?;
+        }
+        Ok(())
+    }
+
+    ///mark the rows of the parts in scans whose value of the TTL column is not
+    ///after the bound as deleted, return the expired rows of every part
+    ///
+    ///WARN the table lock should be held
+    fn expire_rows_locked(
+        &self,
+        dbn: &str,
+        tid: Id,
+        ttl_type: BqlType,
+        sql: &str,
+        scans: &[u64],
+        bound: u64,
+    ) -> BaseRtResult<Vec<(u64, usize)>> {
+        let ms = &self.meta_store;
+        let ps = &self.part_store;
+        let p = BaseMgmtSys::parse_cmd_as_pair(sql)?;
+        let res = query_by_parts(ms, ps, dbn, p, 0..=u64::MAX)?;
+        let mut expired = Vec::new();
+        for (ptk, batch) in res {
+            if !scans.contains(&ptk) {
+                continue;
+            }
+            let gen = ps.get_part_gen(tid, ptk)?;
+            let col = batch.column(0);
+            let old = ps.get_deletion_bitmap(tid, ptk)?.unwrap_or_default();
+            let n = col.len();
+            let mut bm = vec![0u8; (n + 7) / 8];
+            let mut nexp = 0;
+            for i in 0..n {
+                let deleted = i / 8 < old.len() && old[i / 8] & (1 << (i % 8)) != 0;
+                if deleted || col.is_null(i) {
+                    continue;
+                }
+                let v = match ttl_type {
+                    BqlType::Date => col
+                        .as_any()
+                        .downcast_ref::<Date16Array>()
+                        .ok_or(BaseRtError::InvalidTTLColumn)?
+                        .value(i) as u64,
+                    _ => col
+                        .as_any()
+                        .downcast_ref::<Timestamp32Array>()
+                        .ok_or(BaseRtError::InvalidTTLColumn)?
+                        .value(i) as u32 as u64,
+                };
+                if v <= bound {
+                    bm[i / 8] |= 1 << (i % 8);
+                    nexp += 1;
+                }
+            }
+            if nexp == 0 {
+                continue;
+            }
+            if ps.merge_deletion_bitmap_of(tid, ptk, gen, n, &bm)? {
+                expired.push((ptk, nexp));
+            } else {
+                log::warn!("part {} of table {} changed during expiring", ptk, tid);
+            }
+        }
+        Ok(expired)
+    }
+
+    fn log_housekeeping(
+        &self,
+        dbn: &str,
+        tn: &str,
+        action: &str,
+        ptk: u64,
+        rows: usize,
+        now: u64,
+    ) -> BaseRtResult<()> {
+        log::info!("{} {} rows of part {} in {}.{}", action, rows, ptk, dbn, tn);
+        let tid = self
+            .meta_store
+            .tid_by_qname(HOUSEKEEPING_LOG_TABLE)
+            .ok_or(BaseRtError::TableNotExist)?;
+        let schema = Schema::new(vec![
+            Field::new("event_time", DataType::Timestamp32(None), false),
+            Field::new("db_name", DataType::LargeUtf8, false),
+            Field::new("table_name", DataType::LargeUtf8, false),
+            Field::new("action", DataType::LargeUtf8, false),
+            Field::new("part_key", DataType::UInt64, false),
+            Field::new("rows", DataType::UInt64, false),
+        ]);
+        let cols: Vec<ArrayRef> = vec![
+            Arc::new(Timestamp32Array::from(vec![now as i32])),
+            Arc::new(LargeStringArray::from(vec![dbn])),
+            Arc::new(LargeStringArray::from(vec![tn])),
+            Arc::new(LargeStringArray::from(vec![action])),
+            Arc::new(UInt64Array::from(vec![ptk])),
+            Arc::new(UInt64Array::from(vec![rows as u64])),
+        ];
+        let batch = RecordBatch::try_new(Arc::new(schema), cols)?;
+        let mut blk: BaseDataBlock = batch.try_into()?;
+        write_block(&mut blk, HOUSEKEEPING_LOG_TABLE, tid)
+    }
+
+    ///remove the files of the part after the queries which have taken the part
+    ///have mapped them
+    fn remove_part_files(&self, tid: Id, ptk: u64, cids: &[Id]) -> BaseRtResult<()> {
+        let ps = &self.part_store;
+        let (dp, gen) = (ps.get_part_dir(tid, ptk)?, ps.get_part_gen(tid, ptk)?);
+        ps.wait_for_mappings();
+        remove_part_files_in(&format!("{}/{}", dp, tid), ptk, gen, cids)
+    }
+
+    ///remove the part infos and the part files of the column
+    fn remove_column_data(&self, tid: Id, cid: Id) -> BaseRtResult<()> {
+        self.part_store.clear_column(cid)?;
+        //the queries which have taken the copas of the column before may still
+        //map its files, so unlink them after the mappings are done
+        self.part_store.wait_for_mappings();
+        let prefix = format!("{}_", cid);
+        let dd = &self.conf.get_all_data_dirs();
+        for dir in dd {
+            let rd = match read_dir(format!("{}/{}", dir, tid)) {
+                Ok(rd) => rd,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(BaseRtError::WrappingIoError(e)),
+            };
+            for de in rd {
+                let de = de?;
+                if de.file_name().to_string_lossy().starts_with(&prefix) {
+                    remove_file(de.path())?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    ///rewrite the base rows of a modified column from its old column, then
+    ///remove the old column
+    fn convert_column(&self, tid: Id, old: Id, new: Id) -> BaseRtResult<()> {
+        let ms = &self.meta_store;
+        let ps = &self.part_store;
+        let ci = match ms.get_column_info(new)? {
+            Some(ci) => ci,
+            None => return Ok(()), //dropped
+        };
+        for (ptk, _) in ps.get_copa_bases(new)? {
+            ps.acquire_lock(tid)?;
+            //NOTE the base may have been materialized by a modification since
+            let res = match ps.get_copa_base(new, ptk) {
+                Ok(Some(base)) if base.src_cid == old => {
+                    write_copa_base_materialized(ps, tid, new, &ci, ptk, &base, None)
+                }
+                Ok(_) => Ok(()),
+                Err(e) => Err(e.into()),
+            };
+            ps.release_lock(tid)?;
+            res?;
+        }
+        ms.remove_detached_column(old)?;
+        self.remove_column_data(tid, old)?;
+        log::debug!("column {} has been converted into column {}", old, new);
+        Ok(())
+    }
+
+    ///resume the conversions of modified columns which are interrupted
+    pub fn resume_column_conversions(&self) -> BaseRtResult<()> {
+        let ms = &self.meta_store;
+        for (cid, src_cid) in self.part_store.get_converting_cids()? {
+            let qcn = match ms.full_colname(cid) {
+                Some(qcn) => String::from_utf8_lossy(&qcn).into_owned(),
+                None => continue,
+            };
+            let tid = qcn
+                .rsplitn(2, '.')
+                .nth(1)
+                .and_then(|qtn| ms.tid_by_qname(qtn))
+                .ok_or(BaseRtError::SchemaInfoShouldExistButNot)?;
+            spawn_column_conversion(tid, src_cid, cid);
+        }
+        Ok(())
+    }
+
+    pub fn command_query(
+        &self,
+        p: Pair<Rule>,
+        current_db: &str,
+        query_id: &str,
+        // raw_query: String,
+    ) -> BaseRtResult<BaseCommandKind> {
+        let ctx = parse_table_place(p.clone())?;
+        match ctx.place_kind {
+            TablePlaceKind::Local => {
+                let read = READ.get().unwrap();
+                let blks =
+                    read(&self.meta_store, &self.part_store, query_id, current_db, p)?;
+                Ok(BaseCommandKind::Query(blks))
+            }
+            TablePlaceKind::Remote(remote_tb_info) => {
+                log::debug!("successfully parsed remote query to {:?} ", remote_tb_info);
+                let remote_read = REMOTE_READ.get().unwrap();
+                let blks = remote_read(remote_tb_info, p.as_str(), false)?;
+
+                Ok(BaseCommandKind::Query(blks))
+            }
+        }
+    }
+
+    fn parse_cmd_as_pair(cmds: &str) -> BaseRtResult<Pair<Rule>> {
+        let ps = parse_command(cmds).map_err(|e| BaseRtError::WrappingLangError(e))?;
+        let mut ps: Vec<_> = ps.into_iter().collect();
+        if ps.len() != 1 {
+            return Err(BaseRtError::MultiqueryNotSupportedError);
+        }
+        let p = ps.remove(0);
+        log::debug!("successfully parsed command: {} ", cmds);
+        let p = seek_to_sub_cmd(p.into_inner())
+            .map_err(|e| BaseRtError::WrappingLangError(e))?;
+        Ok(p)
+    }
+
+    /*
+    commands should have an auth mech
+    */
+    pub fn run_commands<T>(
+        &self,
+        cmds: String,
+        cctx: &mut T,
+    ) -> BaseRtResult<BaseCommandKind>
+    where
+        T: BaseServerConn,
+    {
+        // let ps = parse_command(cmds)
+        //     .map_err(|e| BaseRtError::WrappingLangError(e))?;
+        // let mut ps: Vec<_> = ps.into_iter().collect();
+        // if ps.len() != 1 {
+        //     return Err(BaseRtError::MultiqueryNotSupportedError);
+        // }
+        // let p = ps.remove(0);
+        // log::debug!("successfully parsed command: {} ", cmds);
+        // let p = seek_to_sub_cmd(&mut p.into_inner())
+        //     .map_err(|e| BaseRtError::WrappingLangError(e))?;
+        let p = BaseMgmtSys::parse_cmd_as_pair(&cmds)?;
+        // log::debug!("cctx: {:p}, cctx.current_db: {:p},{}",
+        //                cctx, &cctx.current_db, &cctx.current_db);
+        match p.as_rule() {
+            Rule::show_databases => {
+                let blk = self.command_show_databases()?;
+                Ok(BaseCommandKind::Query(vec![blk]))
+            }
+            Rule::show_tables => {
+                let blk = self.command_show_tables(cctx.get_db())?;
+                Ok(BaseCommandKind::Query(vec![blk]))
+            }
+            Rule::show_create_table => {
+                let blk = self.command_show_create_table(p, cctx.get_db())?;
+                Ok(BaseCommandKind::Query(vec![blk]))
+            }
+            Rule::desc_table => {
+                let blk = self.command_desc_table(p, cctx.get_db())?;
+                Ok(BaseCommandKind::Query(vec![blk]))
+            }
+            Rule::create_database => self
+                .command_create_database(p)
+                .map(|e| BaseCommandKind::Create),
+            Rule::create_table => self
+                .command_create_table(p, cctx.get_db(), cmds.as_str())
+                .map(|e| BaseCommandKind::Create),
+            Rule::use_db => {
+                let dbn = self.command_use_db(p)?;
+                cctx.set_db(dbn);
+                return Ok(BaseCommandKind::Create); //FIXME Create like but not Create semantic
+            }
+            Rule::drop_database => {
+                return self.command_drop_database(p);
+            }
+            Rule::drop_table => {
+                return self.command_drop_table(p, cctx.get_db());
+            }
+            Rule::truncate_table => {
+                return self.command_truncate_table(p, cctx.get_db());
+            }
+            Rule::optimize_table => {
+                return self.command_optimize_table(p, cctx.get_db());
+            }
+            Rule::check_table => {
+                let blk = self.command_check_table(p, cctx.get_db())?;
+                Ok(BaseCommandKind::Query(vec![blk]))
+            }
+            Rule::backup => {
+                return self.command_backup(p, cctx.get_db());
+            }
+            Rule::restore => {
+                return self.command_restore(p, cctx.get_db());
+            }
+            Rule::alter_table => {
+                return self.command_alter_table(p, cctx.get_db());
+            }
+            Rule::insert_into => {
+                return self.command_insert_into(p, cctx);
+            }
+            Rule::query => {
+                let query_id = cctx.get_query_id();
+                let timer = Instant::now();
+                let rt = self.command_query(p, cctx.get_db(), query_id);
+                log::debug!("process query: {} in {:?}", query_id, timer.elapsed());
+                return rt;
+            }
+            _ => return Err(BaseRtError::UnsupportedCommand),
+        }
+    }
+}
+
+///sync the written data in background for the batch durability
+pub fn spawn_durability_sync() {
+    let storage = &BMS.conf.storage;
+    if storage.durability != Durability::Batch {
+        return;
+    }
+    let ms = storage.durability_batch_interval_ms.max(1);
+    std::thread::spawn(move || loop {
+        std::thread::sleep(Duration::from_millis(ms));
+        let res = sync_for_durability(
+            &BMS.conf.get_all_data_dirs(),
+            &BMS.meta_store,
+            &BMS.part_store,
+        );
+        if let Err(e) = res {
+            log::error!("fail to sync the written data: {}", e);
+        }
+    });
+}
+
+///run the housekeeping periodically in the background
+pub fn spawn_housekeeping() {
+    let secs = BMS.conf.storage.housekeeping_interval_secs.max(1);
+    //NOTE the queries of the housekeeping are run in the runtime of the caller
+    let handle = tokio::runtime::Handle::try_current().ok();
+    std::thread::spawn(move || {
+        let _eg = handle.as_ref().map(|h| h.enter());
+        loop {
+            std::thread::sleep(Duration::from_secs(secs));
+            if let Err(e) = BMS.run_housekeeping() {
+                log::error!("fail to run the housekeeping: {}", e);
+            }
+        }
+    });
+}
+
+///merge the parts periodically in the background, no background merge if
+///merge_interval_secs is 0
+pub fn spawn_merges() {
+    let secs = BMS.conf.storage.merge_interval_secs;
+    if secs == 0 {
+        return;
+    }
+    let handle = tokio::runtime::Handle::try_current().ok();
+    std::thread::spawn(move || {
+        let _eg = handle.as_ref().map(|h| h.enter());
+        loop {
+            std::thread::sleep(Duration::from_secs(secs));
+            if let Err(e) = BMS.run_merges() {
+                log::error!("fail to run the merges: {}", e);
+            }
+        }
+    });
+}
+
+#[inline]
+fn now_in_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+fn check_table_engine(t: &Table) -> BaseRtResult<()> {
+    let ti = &t.tab_info;
+    match ti.engine {
+        EngineType::ReplacingMergeTree => {
+            if ti.primary_keys.is_empty() {
+                return Err(BaseRtError::InvalidTableEngine(
+                    "ReplacingMergeTree needs a primary key".to_string(),
+                ));
+            }
+            if ti.engine_params.len() > 1 {
+                return Err(BaseRtError::InvalidTableEngine(
+                    "ReplacingMergeTree takes at most one version column".to_string(),
+                ));
+            }
+            if let Some(ver) = ti.engine_params.first() {
+                let is_valid = t.columns.iter().any(|(cn, ci)| {
+                    cn == ver
+                        && !ci.is_nullable
+                        && matches!(
+                            ci.data_type,
+                            BqlType::UInt(_)
+                                | BqlType::Int(_)
+                                | BqlType::Date
+                                | BqlType::DateTime
+                                | BqlType::DateTimeTz(_)
+                        )
+                });
+                if !is_valid {
+                    return Err(BaseRtError::InvalidTableEngine(format!(
+                        "version column {} should be an integer, Date or DateTime \
+                         column of the table",
+                        ver
+                    )));
+                }
+            }
+        }
+        EngineType::SummingMergeTree | EngineType::AggregatingMergeTree => {
+            if ti.primary_keys.is_empty() {
+                return Err(BaseRtError::InvalidTableEngine(format!(
+                    "{:?} needs a primary key",
+                    ti.engine
+                )));
+            }
+            if ti.engine == EngineType::AggregatingMergeTree
+                && !ti.engine_params.is_empty()
+            {
+                return Err(BaseRtError::InvalidTableEngine(
+                    "AggregatingMergeTree takes no parameter".to_string(),
+                ));
+            }
+            for cn in ti.engine_params.iter() {
+                let is_valid = t.columns.iter().any(|(n, ci)| {
+                    n == cn
+                        && !ci.is_primary_key
+                        && matches!(
+                            ci.data_type,
+                            BqlType::UInt(_)
+                                | BqlType::Int(_)
+                                | BqlType::Float(_)
+                                | BqlType::AggregateFunction(_, _, _)
+                        )
+                });
+                if !is_valid {
+                    return Err(BaseRtError::InvalidTableEngine(format!(
+                        "column {} to sum should be a numeric non-key column \
+                         of the table",
+                        cn
+                    )));
+                }
+            }
+        }
+        EngineType::Memory | EngineType::Log => {
+            if !ti.engine_params.is_empty()
+                || !ti.partition_cols.is_empty()
+                || !ti.primary_keys.is_empty()
+                || !ti.ttl_col.is_empty()
+            {
+                return Err(BaseRtError::InvalidTableEngine(format!(
+                    "{:?} takes no parameter, partition key, primary key or TTL",
+                    ti.engine
+                )));
+            }
+            //NOTE the rows are kept as arrow arrays without any conversion
+            if let Some((cn, _)) = t.columns.iter().find(|(_, ci)| {
+                matches!(
+                    ci.data_type,
+                    BqlType::LowCardinalityString | BqlType::LowCardinalityTinyText
+                )
+            }) {
+                return Err(BaseRtError::InvalidTableEngine(format!(
+                    "the type of column {} is not supported by {:?}",
+                    cn, ti.engine
+                )));
+            }
+        }
+        _ if !ti.engine_params.is_empty() => {
+            return Err(BaseRtError::InvalidTableEngine(
+                "the engine takes no parameter".to_string(),
+            ));
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn create_system_table(ms: &MetaStore, cmd: &str) -> BaseRtResult<()> {
+    let p = BaseMgmtSys::parse_cmd_as_pair(cmd)?;
+    let (mut t, _fallible) =
+        parse_create_table(p).map_err(|e| BaseRtError::WrappingLangError(e))?;
+    t.tab_info.create_script = cmd.to_string();
+    ms.create_table(&t)
+        .map_err(|e| BaseRtError::WrappingMetaError(e))?;
+    Ok(())
+}
+
+///verify the files of the copas of the part in the table dir tp, return the
+///messages of the damaged files
+///
+///WARN the table lock should be held
+fn check_part_files(
+    ps: &PartStore,
+    tp: &str,
+    ptk: u64,
+    gen: usize,
+    col_infos: &[(String, Id, ColumnInfo)],
+) -> BaseRtResult<Vec<String>> {
+    let mut errs = Vec::new();
+    for (cn, cid, _) in col_infos {
+        let cc = match ps.get_copa_crcs(*cid, ptk)? {
+            Some(cc) => cc,
+            None => continue,
+        };
+        for (fc, sfx) in cc.files.iter().zip(CoPaCrcs::SUFFIXES.iter()) {
+            if fc.len == 0 {
+                continue;
+            }
+            let fname = [get_part_file_name(*cid, ptk, gen).as_str(), sfx].concat();
+            let mut bs = vec![0u8; fc.len];
+            let res = File::open(format!("{}/{}", tp, fname))
+                .and_then(|f| f.read_exact_at(&mut bs, 0));
+            match res {
+                Ok(_) => {
+                    let blks = fc.verify(&bs);
+                    if !blks.is_empty() {
+                        errs.push(format!(
+                            "blocks {:?} of file {} of column {} are damaged",
+                            blks, fname, cn
+                        ));
+                    }
+                }
+                Err(e)
+                    if matches!(
+                        e.kind(),
+                        std::io::ErrorKind::NotFound | std::io::ErrorKind::UnexpectedEof
+                    ) =>
+                {
+                    errs.push(format!(
+                        "file {} of column {} is missing or truncated",
+                        fname, cn
+                    ));
+                }
+                Err(e) => return Err(BaseRtError::WrappingIoError(e)),
+            }
+        }
+    }
+    Ok(errs)
+}
+
+///the dir under a data dir to stage the files of the parts being merged
+const MERGING_DIR: &str = "merging";
+
+#[inline]
+fn merging_dir_of(dp: &str) -> String {
+    format!("{}/{}", dp, MERGING_DIR)
+}
+
+///write the file by renaming the synced temp file to it
+fn write_file_synced(path: &str, bs: &[u8]) -> BaseRtResult<()> {
+    let tmp = [path, ".tmp"].concat();
+    let f = File::create(&tmp)?;
+    f.write_all_at(bs, 0)?;
+    f.sync_all()?;
+    rename(&tmp, path)?;
+    if let Some(dir) = Path::new(path).parent() {
+        File::open(dir)?.sync_all()?;
+    }
+    Ok(())
+}
+
+///the commit file of the merge of the part into the generation, the merges
+///committed by the old versions have no generation in the names
+fn merge_commit_path(mtp: &str, ptk: u64, gen: usize) -> String {
+    if gen == 0 {
+        format!("{}/{}.part", mtp, ptk)
+    } else {
+        format!("{}/{}v{}.part", mtp, ptk, gen)
+    }
+}
+
+///rename the staged files of the merged part in the generation into the table
+///dir, then switch the part to them by its meta, and unlink the files of the
+///old generation after the queries which have taken the part before have
+///mapped them, before removing the commit file of the merge
+///
+///NOTE the files which have been renamed or removed are skipped, so a switch
+///     interrupted by a crash can be redone
+///
+///WARN the table lock should be held
+fn switch_part_files(
+    ps: &PartStore,
+    dp: &str,
+    tid: Id,
+    ptk: u64,
+    gen: usize,
+    pm: &PartMeta,
+) -> BaseRtResult<()> {
+    let mtp = format!("{}/{}", merging_dir_of(dp), tid);
+    let tp = format!("{}/{}", dp, tid);
+    let cids: Vec<Id> = pm.copas.iter().map(|cm| cm.cid).collect();
+    let cid_pairs: Vec<(Id, Id)> = cids.iter().map(|cid| (*cid, *cid)).collect();
+    rename_part_files(&mtp, &tp, ptk, gen, gen, &cid_pairs)?;
+    File::open(&tp)?.sync_all()?;
+    ps.replace_part_meta(tid, ptk, &cids, pm, gen)?;
+    ps.flush()?;
+    if gen > 0 {
+        ps.wait_for_mappings();
+        remove_part_files_in(&tp, ptk, gen - 1, &cids)?;
+    }
+    remove_file(merge_commit_path(&mtp, ptk, gen))?;
+    Ok(())
+}
+
+///redo the switches of the merged parts which have been committed before a
+///crash, then remove the files staged for the merges
+fn recover_part_merges(ps: &PartStore, data_dirs: &[String]) -> BaseRtResult<()> {
+    for dp in data_dirs {
+        let mdp = merging_dir_of(dp);
+        let rd = match read_dir(&mdp) {
+            Ok(rd) => rd,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(BaseRtError::WrappingIoError(e)),
+        };
+        for de in rd {
+            let de = de?;
+            let tid: Id = match de.file_name().to_string_lossy().parse() {
+                Ok(tid) => tid,
+                Err(_) => continue,
+            };
+            for fe in read_dir(de.path())? {
+                let fe = fe?;
+                let fname = fe.file_name().to_string_lossy().to_string();
+                let (ptk, gen) = match fname.strip_suffix(".part") {
+                    Some(stem) => {
+                        let mut it = stem.splitn(2, 'v');
+                        let ptk = it.next().map(str::parse::<u64>);
+                        let gen = it.next().map_or(Ok(0), str::parse::<usize>);
+                        match (ptk, gen) {
+                            (Some(Ok(ptk)), Ok(gen)) => (ptk, gen),
+                            _ => continue,
+                        }
+                    }
+                    None => continue,
+                };
+                let pm = PartMeta::from_bytes(&read(fe.path())?)?;
+                switch_part_files(ps, dp, tid, ptk, gen, &pm)?;
+                log::info!("redone the merge of part {} of table {}", ptk, tid);
+            }
+        }
+        remove_dir_all(&mdp)?;
+    }
+    Ok(())
+}
+
+///rename the files of the part from the copas of the first cids in the
+///generation gen in the dir to the copas of the second cids in the generation
+///gen_to in the dir_to
+fn rename_part_files(
+    dir: &str,
+    dir_to: &str,
+    ptk: u64,
+    gen: usize,
+    gen_to: usize,
+    cid_pairs: &[(Id, Id)],
+) -> BaseRtResult<()> {
+    for (cid, cid_to) in cid_pairs {
+        let (fname, fname_to) = (
+            get_part_file_name(*cid, ptk, gen),
+            get_part_file_name(*cid_to, ptk, gen_to),
+        );
+        for sfx in ["", "om", "nm", "pi"].iter() {
+            let res = rename(
+                format!("{}/{}{}", dir, fname, sfx),
+                format!("{}/{}{}", dir_to, fname_to, sfx),
+            );
+            if let Err(e) = res {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    return Err(BaseRtError::WrappingIoError(e));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+///the total size of the files of the part in the table dir tp
+fn get_part_files_size(tp: &str, ptk: u64, gen: usize, cids: &[Id]) -> BaseRtResult<u64> {
+    let mut siz = 0;
+    for cid in cids {
+        let fname = get_part_file_name(*cid, ptk, gen);
+        for sfx in ["", "om", "nm", "pi"].iter() {
+            match metadata(format!("{}/{}{}", tp, fname, sfx)) {
+                Ok(md) => siz += md.len(),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(BaseRtError::WrappingIoError(e)),
+            }
+        }
+    }
+    Ok(siz)
+}
+
+///remove the files of the part in the generation in the table dir tp
+fn remove_part_files_in(tp: &str, ptk: u64, gen: usize, cids: &[Id]) -> BaseRtResult<()> {
+    for cid in cids {
+        let fname = get_part_file_name(*cid, ptk, gen);
+        for sfx in ["", "om", "nm", "pi"].iter() {
+            let res = remove_file(format!("{}/{}{}", tp, fname, sfx));
+            if let Err(e) = res {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    return Err(BaseRtError::WrappingIoError(e));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+///hard-link the files of the part in the generation gen into the generation
+///gen_to (of the paired columns) in the dir_to, fall back to copying when the
+///dir_to is on another device
+fn link_part_files(
+    dir: &str,
+    dir_to: &str,
+    ptk: u64,
+    gen: usize,
+    gen_to: usize,
+    cid_pairs: &[(Id, Id)],
+) -> BaseRtResult<()> {
+    for (cid, cid_to) in cid_pairs {
+        let (fname, fname_to) = (
+            get_part_file_name(*cid, ptk, gen),
+            get_part_file_name(*cid_to, ptk, gen_to),
+        );
+        for sfx in ["", "om", "nm", "pi"].iter() {
+            let (src, dst) = (
+                format!("{}/{}{}", dir, fname, sfx),
+                format!("{}/{}{}", dir_to, fname_to, sfx),
+            );
+            if let Err(e) = link_or_copy_file(&src, &dst) {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    return Err(BaseRtError::WrappingIoError(e));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+///hard-link the file, fall back to copying when the dst is on another device
+fn link_or_copy_file(src: &str, dst: &str) -> std::io::Result<()> {
+    match hard_link(src, dst) {
+        Err(e) if e.raw_os_error() == Some(libc::EXDEV) => copy(src, dst).map(|_| ()),
+        res => res,
+    }
+}
+
+///hard-link the files of the part in the generation gen into the backup dir
+///bp, except the ones which may be rewritten in place after and are copied:
+///the pi file of which the last mark is merged by the writes, and the data and
+///nm files of the copas whose base rows are materialized by ALTER
+fn backup_part_files(
+    tp: &str,
+    bp: &str,
+    ptk: u64,
+    gen: usize,
+    pm: &PartMeta,
+) -> BaseRtResult<()> {
+    for cm in &pm.copas {
+        let (fname, fname_to) = (
+            get_part_file_name(cm.cid, ptk, gen),
+            get_part_file_name(cm.cid, ptk, 0),
+        );
+        let has_base = cm.base.rows > 0;
+        for sfx in ["", "om", "nm", "pi"].iter() {
+            let (src, dst) = (
+                format!("{}/{}{}", tp, fname, sfx),
+                format!("{}/{}{}", bp, fname_to, sfx),
+            );
+            let res = match *sfx {
+                "pi" => copy(&src, &dst).map(|_| ()),
+                "" | "nm" if has_base => copy(&src, &dst).map(|_| ()),
+                _ => link_or_copy_file(&src, &dst),
+            };
+            if let Err(e) = res {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    return Err(BaseRtError::WrappingIoError(e));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+///copy the files of the part in the backup dir into the copas of the cids_to
+///in the generation gen_to in the dir_to
+fn copy_part_files(
+    dir: &str,
+    dir_to: &str,
+    ptk: u64,
+    gen_to: usize,
+    pm: &PartMeta,
+    cids_to: &[Id],
+) -> BaseRtResult<()> {
+    for ((cm, cc), cid_to) in pm.copas.iter().zip(&pm.crcs).zip(cids_to) {
+        let fname_to = get_part_file_name(*cid_to, ptk, gen_to);
+        for (i, sfx) in ["", "om", "nm", "pi"].iter().enumerate() {
+            let dst = format!("{}/{}{}", dir_to, fname_to, sfx);
+            match copy(format!("{}/{}_{}{}", dir, cm.cid, ptk, sfx), &dst) {
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                res => res?,
+            };
+            //NOTE the hard-linked files may have been appended by the table
+            //     after the snapshot, while the pi file and the files rewritten
+            //     in place are copied by the backup
+            let len = match i {
+                0 => cm.siz_in_bytes,
+                1 | 2 => cc.files[i].len,
+                _ => 0,
+            };
+            if len > 0 {
+                OpenOptions::new()
+                    .write(true)
+                    .open(&dst)?
+                    .set_len(len as u64)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+///replace the qualified table name in the create script with the qtn
+fn rename_table_in_create_script(script: &str, qtn: &str) -> BaseRtResult<String> {
+    let p = BaseMgmtSys::parse_cmd_as_pair(script)?;
+    if p.as_rule() != Rule::create_table {
+        return Err(BaseRtError::InvalidBackup(format!(
+            "not a create script: {}",
+            script
+        )));
+    }
+    let span = p
+        .into_inner()
+        .find(|p| p.as_rule() == Rule::qualified_table_name)
+        .ok_or(BaseRtError::ShouldNotReachHere)?
+        .as_span();
+    Ok([&script[..span.start()], qtn, &script[span.end()..]].concat())
+}
+
+fn spawn_column_conversion(tid: Id, old: Id, new: Id) {
+    std::thread::spawn(move || {
+        if let Err(e) = BMS.convert_column(tid, old, new) {
+            log::error!("fail to convert column {} into column {}: {}", old, new, e);
+        }
+    });
+}
+
+fn gen_column_type(ci: &ColumnInfo, ev: Option<&EnumValues>) -> BaseRtResult<String> {
+    let typ = match ev {
+        Some(ev) => ev.to_vec(ci.data_type)?,
+        None => ci.data_type.to_vec()?,
+    };
+    let typ = if ci.is_nullable {
+        bytes_cat!(b"Nullable(", &typ, b")")
+    } else {
+        typ
+    };
+    Ok(String::from_utf8_lossy(&typ).into_owned())
+}
+
+fn gen_column_def(
+    name: &str,
+    ci: &ColumnInfo,
+    ev: Option<&EnumValues>,
+    default: Option<&str>,
+) -> BaseRtResult<String> {
+    let mut def = [name, " ", &gen_column_type(ci, ev)?].concat();
+    if let Some(lit) = default {
+        def.push_str(" DEFAULT ");
+        def.push_str(lit);
+    }
+    if !ci.codec.is_none() {
+        def.push(' ');
+        def.push_str(&String::from_utf8_lossy(&ci.codec.to_vec()));
+    }
+    Ok(def)
+}
+
+///return the positions of the parentheses which enclose the column definitions
+///in the create script, and the definitions
+fn split_column_defs(script: &str) -> Option<(usize, usize, Vec<String>)> {
+    let lp = script.find('(')?;
+    let mut defs = Vec::new();
+    let mut depth = 0;
+    let mut quote = None;
+    let mut start = lp + 1;
+    for (i, c) in script[lp + 1..].char_indices() {
+        let i = lp + 1 + i;
+        match (quote, c) {
+            (Some(q), _) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'') | (None, '`') | (None, '"') => quote = Some(c),
+            (None, '(') => depth += 1,
+            (None, ')') if depth > 0 => depth -= 1,
+            (None, ')') | (None, ',') if depth == 0 => {
+                let def = script[start..i].trim();
+                if def.len() > 0 {
+                    defs.push(def.to_string());
+                }
+                if c == ')' {
+                    return Some((lp, i, defs));
+                }
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+///apply f to the column definitions of the create script
+///
+///NOTE the script is kept as it is if it can not be understood
+fn alter_column_defs<F: FnOnce(&mut Vec<String>)>(script: &mut String, f: F) {
+    if let Some((lp, rp, mut defs)) = split_column_defs(script) {
+        f(&mut defs);
+        let cols = ["(\n    ", &defs.join(",\n    "), "\n)"].concat();
+        script.replace_range(lp..=rp, &cols);
+    }
+}
+
+fn column_def_name_len(def: &str) -> usize {
+    if def.starts_with('`') {
+        def[1..].find('`').map(|i| i + 2).unwrap_or(def.len())
+    } else {
+        def.find(char::is_whitespace).unwrap_or(def.len())
+    }
+}
+
+fn is_column_def_of(def: &str, colname: &str) -> bool {
+    let n = column_def_name_len(def);
+    def[..n].trim_matches('`') == colname.trim_matches('`')
+}
+
+///return the range of the type in the column definition after its name
+fn column_def_type_range(rest: &str) -> (usize, usize) {
+    let s = rest.len() - rest.trim_start().len();
+    let mut depth = 0;
+    for (i, c) in rest[s..].char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return (s, s + i + 1);
+                }
+            }
+            c if depth == 0 && !(c.is_alphanumeric() || c == '_') => return (s, s + i),
+            _ => {}
+        }
+    }
+    (s, rest.len())
+}
+
+fn command_insert_into_gen_header(
+    tab: &meta::types::Table,
+    qtn: &String,
+    ms: &MetaStore,
+    header: &mut BaseDataBlock,
+    dbn: &str,
+    tn: &str,
+) -> Result<(), BaseRtError> {
+    if tab.columns.len() != 0 {
+        //insert into some columns
+        for (cn, _) in &tab.columns {
+            let qcn = [qtn.as_str(), &cn].join(".");
+            let cid = ms.cid_by_qname(&qcn).ok_or(BaseRtError::ColumnNotExist)?;
+            let ci = ms
+                .get_column_info(cid)?
+                .ok_or(BaseRtError::SchemaInfoShouldExistButNot)?;
+            let mut hc = BaseColumn::new_block_header(
+                cn.as_bytes().to_vec(),
+                ci.data_type,
+                ci.is_nullable,
+            );
+            hc.enum_values = ms.get_column_enum(cid)?;
+            header.columns.push(hc);
+        }
+    } else {
+        //insert into all columns
+        let mut col_infos = ms.get_columns(dbn, tn)?;
+        //NOTE ch client relays on the order of cols to match that being inserted into
+        col_infos.sort_unstable_by_key(|c| c.2.ordinal);
+        for (cn, cid, ci) in col_infos {
+            let mut hc = BaseColumn::new_block_header(
+                cn.as_bytes().to_vec(),
+                ci.data_type,
+                ci.is_nullable,
+            );
+            hc.enum_values = ms.get_column_enum(cid)?;
+            header.columns.push(hc);
+        }
+    }
+    header.ncols = header.columns.len();
+    header.nrows = 0; //for empty data case
+    Ok(())
+}
+
+///the type of the column in the blocks of the inline values to insert
+///
+///NOTE the Enum values are inserted by their names (or the values in text) in
+///     String, which are converted into the values when writing the block
+fn insert_type_of(typ: BqlType) -> BqlType {
+    match typ {
+        BqlType::Enum8 | BqlType::Enum16 => BqlType::String,
+        _ => typ,
+    }
+}
+
+// Adapted from `tokenize_single_quoted_string()` from sqlparse
+fn parse_single_quoted_str(s: &str) -> BaseRtResult<String> {
+    if s.starts_with("'") {
+        let mut ret = String::new();
+        let mut chars = s[1..].chars().peekable();
+        while let Some(&ch) = chars.peek() {
+            match ch {
+                '\'' => {
+                    chars.next(); // consume
+                    let escaped_quote = chars.peek().map(|c| *c == '\'').unwrap_or(false);
+                    if escaped_quote {
+                        ret.push('\'');
+                        chars.next();
+                    } else {
+                        return Ok(ret);
+                    }
+                }
+                _ => {
+                    chars.next(); // consume
+                    ret.push(ch);
+                }
+            }
+        }
+    }
+    Err(BaseRtError::InvalidStringConversion(s.as_bytes().to_vec()))
+}
+
+fn parse_literal_as_bytes(lit: &str, btyp: BqlType) -> BaseRtResult<Vec<u8>> {
+    let mut rt = Vec::new();
+    //the values of AggregateFunction are the states
+    match btyp.state_type() {
+        BqlType::UInt(bits) => match bits {
+            128 => {
+                let v = lit
+                    .parse::<u128>()
+                    .map_err(|_e| BaseRtError::InsertIntoValueParsingError)?
+                    .to_le_bytes();
+                rt.extend(&v);
+            }
+            64 => {
+                let v = lit
+                    .parse::<u64>()
+                    .map_err(|_e| BaseRtError::InsertIntoValueParsingError)?
+                    .to_le_bytes();
+                rt.extend(&v);
+            }
+            32 => {
+                let v = lit
                     .parse::<u32>()
                     .map_err(|_e| BaseRtError::InsertIntoValueParsingError)?
                     .to_le_bytes();
@@ -1159,6 +3604,13 @@ fn parse_literal_as_bytes(lit: &str, btyp: BqlType) -> BaseRtResult<Vec<u8>> {
             _ => return Err(BaseRtError::UnsupportedValueConversion),
         },
         BqlType::Int(bits) => match bits {
+            128 => {
+                let v = lit
+                    .parse::<i128>()
+                    .map_err(|_e| BaseRtError::InsertIntoValueParsingError)?
+                    .to_le_bytes();
+                rt.extend(&v);
+            }
             64 => {
                 let v = lit
                     .parse::<i64>()
@@ -1189,6 +3641,20 @@ fn parse_literal_as_bytes(lit: &str, btyp: BqlType) -> BaseRtResult<Vec<u8>> {
             }
             _ => return Err(BaseRtError::UnsupportedValueConversion),
         },
+        BqlType::Int256 => {
+            let v = lit
+                .parse::<i256>()
+                .map_err(|_e| BaseRtError::InsertIntoValueParsingError)?
+                .to_le_bytes();
+            rt.extend(&v);
+        }
+        BqlType::UInt256 => {
+            let v = lit
+                .parse::<u256>()
+                .map_err(|_e| BaseRtError::InsertIntoValueParsingError)?
+                .to_le_bytes();
+            rt.extend(&v);
+        }
         BqlType::DateTimeTz(tz) => {
             let lit = parse_single_quoted_str(lit)?;
             let tz_offset = tz.offset();
@@ -1203,6 +3669,21 @@ fn parse_literal_as_bytes(lit: &str, btyp: BqlType) -> BaseRtResult<Vec<u8>> {
             let v = ut.to_le_bytes();
             rt.extend(&v);
         }
+        BqlType::DateTime64(p, tz) => {
+            //the numbers are the ticks themselves
+            let ticks = match lit.parse::<i64>() {
+                Ok(ticks) => ticks,
+                Err(_) => {
+                    let lit = parse_single_quoted_str(lit)?;
+                    let tz_offset = match tz {
+                        Some(q) => TimeZoneId::from_quarters(q).offset(),
+                        None => BMS.timezone.offset(),
+                    };
+                    parse_to_epoch64(&lit, p, tz_offset)?
+                }
+            };
+            rt.extend(&ticks.to_le_bytes());
+        }
         BqlType::Date => {
             let lit = parse_single_quoted_str(lit)?;
             let ut = parse_to_days(&lit)?;
@@ -1226,24 +3707,22 @@ fn parse_literal_as_bytes(lit: &str, btyp: BqlType) -> BaseRtResult<Vec<u8>> {
             }
             _ => return Err(BaseRtError::UnsupportedValueConversion),
         },
-        BqlType::Decimal(p, s) => {
+        BqlType::Decimal(_, s) => {
             if let Ok(n) = BigDecimal::from_str(lit) {
                 let n = n.with_scale(s as u64 as i64);
                 let mut bs = n.into_bigint_and_exponent().0.to_signed_bytes_le();
                 // https://clickhouse.tech/docs/en/sql-reference/data-types/decimal/
-                if p >= 1 && p <= 9 {
-                    if bs.len() < 4 {
-                        bs.extend(vec![0; 8 - bs.len()]);
-                    } else {
-                        return Err(BaseRtError::UnsupportedValueConversion);
-                    }
-                } else if p >= 10 && p <= 18 {
-                    if bs.len() < 8 {
-                        bs.extend(vec![0; 8 - bs.len()]);
-                    } else {
-                        return Err(BaseRtError::UnsupportedValueConversion);
-                    }
+                //NOTE the values are in 4, 8, 16 or 32 bytes by the precision
+                let siz = btyp.size_in_usize()?;
+                if bs.len() > siz {
+                    return Err(BaseRtError::UnsupportedValueConversion);
                 }
+                let ext = if bs.last().map_or(false, |b| *b & 0x80 != 0) {
+                    0xFF
+                } else {
+                    0
+                };
+                bs.resize(siz, ext);
                 rt.extend(bs);
             } else {
                 return Err(BaseRtError::UnsupportedValueConversion);
@@ -1261,6 +3740,27 @@ fn parse_literal_as_bytes(lit: &str, btyp: BqlType) -> BaseRtResult<Vec<u8>> {
             rt.extend(s.into_bytes());
             rt.extend(vec![0; len as usize - s_len]);
         }
+        BqlType::Ipv4 => {
+            //the numbers are taken as they are
+            let v = match lit.parse::<u32>() {
+                Ok(v) => v,
+                Err(_) => parse_single_quoted_str(lit)?
+                    .parse::<Ipv4Addr>()
+                    .map_err(|_e| BaseRtError::InsertIntoValueParsingError)?
+                    .into(),
+            };
+            rt.extend(&v.to_le_bytes());
+        }
+        BqlType::Ipv6 => {
+            //NOTE the IPv4 addresses are mapped into IPv6 like ClickHouse
+            let v = match parse_single_quoted_str(lit)?.parse::<IpAddr>() {
+                Ok(IpAddr::V4(ip)) => ip.to_ipv6_mapped(),
+                Ok(IpAddr::V6(ip)) => ip,
+                Err(_) => return Err(BaseRtError::InsertIntoValueParsingError),
+            };
+            rt.extend(&v.octets());
+        }
+        BqlType::Array(_, _) => return Err(BaseRtError::UnsupportedValueConversion),
         // LowCardinalityString,
         _ => todo!(),
     }
@@ -1268,6 +3768,15 @@ fn parse_literal_as_bytes(lit: &str, btyp: BqlType) -> BaseRtResult<Vec<u8>> {
     Ok(rt)
 }
 
+#[inline]
+fn null_value_as_bytes(btyp: BqlType) -> BaseRtResult<Vec<u8>> {
+    match btyp {
+        //empty varbytes
+        BqlType::String => Ok(vec![0]),
+        _ => Ok(vec![0; btyp.size_in_usize()?]),
+    }
+}
+
 fn command_insert_into_gen_block(
     tab: &meta::types::Table,
     qtn: &String,
@@ -1326,16 +3835,30 @@ fn command_insert_into_gen_block(
         let nc = col_infos.len();
         for (cn, _, ci) in col_infos {
             let name = cn.as_bytes().to_vec();
-            let btype = ci.data_type;
+            let typ = ci.data_type;
+            let btype = insert_type_of(typ);
             //rows to cols
             if nc != rows[0].len() {
                 return Err(BaseRtError::InvalidFormatForInsertIntoValueList);
             }
             let mut data: Vec<u8> = Vec::new();
             let mut om_data: Vec<u32> = Vec::new();
+            let mut nm_data: Vec<u8> = Vec::new();
             for i in 0..nr {
                 let lit = &rows[i][ic];
-                let bs = parse_literal_as_bytes(lit, btype)?;
+                let is_null = lit.eq_ignore_ascii_case("null");
+                if is_null && !ci.is_nullable {
+                    return Err(BaseRtError::InsertIntoValueParsingError);
+                }
+                //NOTE NULL takes the default value in data
+                let bs = if is_null {
+                    null_value_as_bytes(btype)?
+                } else if btype != typ && !lit.starts_with('\'') {
+                    parse_literal_as_bytes(&["'", lit, "'"].concat(), btype)?
+                } else {
+                    parse_literal_as_bytes(lit, btype)?
+                };
+                nm_data.push(is_null as u8);
                 if matches!(btype, BqlType::String) {
                     om_data.push(data.len().try_into().unwrap());
                 }
@@ -1351,7 +3874,7 @@ fn command_insert_into_gen_block(
                     btype,
                     size: 0,
                     data,
-                    null_map: if ci.is_nullable { Some(vec![]) } else { None }, //FIXME
+                    null_map: if ci.is_nullable { Some(nm_data) } else { None },
                     offset_map: if matches!(btype, BqlType::String) {
                         Some(om_data)
                     } else {
@@ -1359,6 +3882,7 @@ fn command_insert_into_gen_block(
                     },
                     lc_dict_data: None,
                 },
+                enum_values: None,
             });
             ic += 1;
         }
@@ -1371,7 +3895,11 @@ fn command_insert_into_gen_block(
 
 #[cfg(test)]
 mod unit_tests {
-    use super::BaseMgmtSys;
+    use super::{
+        alter_column_defs, column_def_name_len, column_def_type_range, is_column_def_of,
+        parse_literal_as_bytes, BaseMgmtSys,
+    };
+    use meta::types::BqlType;
 
     #[test]
     fn test_has_mulit_cols() {
@@ -1384,4 +3912,89 @@ mod unit_tests {
             true
         );
     }
+
+    #[test]
+    fn test_alter_column_defs() {
+        let mut script = "CREATE TABLE t (a UInt32, `b c` Nullable(Decimal(9,2)), \
+                          s String DEFAULT 'x,(y' CODEC(LZ4)) ENGINE = BaseStorage \
+                          PARTITION BY a"
+            .to_string();
+        alter_column_defs(&mut script, |defs| {
+            assert_eq!(defs.len(), 3);
+            assert!(is_column_def_of(&defs[1], "b c"));
+            assert!(is_column_def_of(&defs[2], "s"));
+            assert!(!is_column_def_of(&defs[0], "b"));
+            defs.retain(|d| !is_column_def_of(d, "b c"));
+            defs.push("d Int8".to_string());
+        });
+        assert_eq!(
+            script,
+            "CREATE TABLE t (\n    a UInt32,\n    s String DEFAULT 'x,(y' CODEC(LZ4),\n    \
+             d Int8\n) ENGINE = BaseStorage PARTITION BY a"
+        );
+
+        let d = "`b c` Nullable(Decimal(9,2)) DEFAULT 1";
+        let n = column_def_name_len(d);
+        assert_eq!(&d[..n], "`b c`");
+        let (s, e) = column_def_type_range(&d[n..]);
+        assert_eq!(&d[n + s..n + e], "Nullable(Decimal(9,2))");
+        let d = "a UInt32";
+        let n = column_def_name_len(d);
+        let (s, e) = column_def_type_range(&d[n..]);
+        assert_eq!(&d[n + s..n + e], "UInt32");
+
+        let mut script = "bad script".to_string();
+        alter_column_defs(&mut script, |defs| defs.clear());
+        assert_eq!(script, "bad script");
+    }
+
+    #[test]
+    fn test_parse_wide_literals() {
+        let bs = parse_literal_as_bytes("-1.5", BqlType::Decimal(9, 2)).unwrap();
+        assert_eq!(bs, (-150i32).to_le_bytes());
+        let bs = parse_literal_as_bytes("12.34", BqlType::Decimal(18, 3)).unwrap();
+        assert_eq!(bs, 12340i64.to_le_bytes());
+        let bs = parse_literal_as_bytes("-0.01", BqlType::Decimal(38, 2)).unwrap();
+        assert_eq!(bs, (-1i128).to_le_bytes());
+        let bs = parse_literal_as_bytes("1", BqlType::Decimal(76, 0)).unwrap();
+        assert_eq!(bs.len(), 32);
+        assert_eq!(bs[0], 1);
+        assert!(parse_literal_as_bytes("1e10", BqlType::Decimal(9, 0)).is_err());
+
+        let bs = parse_literal_as_bytes("-2", BqlType::Int(128)).unwrap();
+        assert_eq!(bs, (-2i128).to_le_bytes());
+        let bs = parse_literal_as_bytes("-2", BqlType::Int256).unwrap();
+        assert_eq!(bs[0], 0xFE);
+        assert!(bs[1..].iter().all(|b| *b == 0xFF));
+        assert!(parse_literal_as_bytes("-2", BqlType::UInt256).is_err());
+    }
+
+    #[test]
+    fn test_parse_datetime64_literals() {
+        let typ = BqlType::DateTime64(3, Some(0));
+        let bs = parse_literal_as_bytes("'1970-01-01 00:00:01.25'", typ).unwrap();
+        assert_eq!(bs, 1250i64.to_le_bytes());
+        let bs = parse_literal_as_bytes("1250", typ).unwrap();
+        assert_eq!(bs, 1250i64.to_le_bytes());
+        let typ = BqlType::DateTime64(6, Some(32));
+        let bs = parse_literal_as_bytes("'1970-01-01 08:00:00.000001'", typ).unwrap();
+        assert_eq!(bs, 1i64.to_le_bytes());
+        assert!(parse_literal_as_bytes("'1970-01-01 00:00:01.x'", typ).is_err());
+    }
+
+    #[test]
+    fn test_parse_ip_literals() {
+        let bs = parse_literal_as_bytes("'192.168.0.1'", BqlType::Ipv4).unwrap();
+        assert_eq!(bs, 0xC0A80001u32.to_le_bytes());
+        let bs = parse_literal_as_bytes("3232235521", BqlType::Ipv4).unwrap();
+        assert_eq!(bs, 0xC0A80001u32.to_le_bytes());
+        assert!(parse_literal_as_bytes("'192.168.0.256'", BqlType::Ipv4).is_err());
+
+        let bs = parse_literal_as_bytes("'2001:db8::1'", BqlType::Ipv6).unwrap();
+        assert_eq!(bs[..4], [0x20, 0x01, 0x0d, 0xb8]);
+        assert_eq!(bs[15], 1);
+        let bs = parse_literal_as_bytes("'127.0.0.1'", BqlType::Ipv6).unwrap();
+        assert_eq!(bs[10..], [0xff, 0xff, 127, 0, 0, 1]);
+        assert!(parse_literal_as_bytes("'2001:db8::x'", BqlType::Ipv6).is_err());
+    }
 }
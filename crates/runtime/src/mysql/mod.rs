@@ -2,10 +2,14 @@ extern crate server_mysql;
 
 use async_trait::async_trait;
 use chrono::{Duration, NaiveDate, NaiveDateTime};
-use std::{convert::TryInto, io, mem};
+use std::{io, str::FromStr};
 use tokio::io::AsyncWrite;
 
-use arrow::{array, datatypes::DataType, record_batch::RecordBatch};
+use arrow::{
+    array,
+    datatypes::{DataType, TimeUnit},
+    record_batch::RecordBatch,
+};
 use log;
 use server_mysql::{
     AsyncMysqlShim, Column, ColumnFlags, ColumnType, ErrorKind, InitWriter, ParamParser,
@@ -13,6 +17,7 @@ use server_mysql::{
 };
 
 use base::datetimes::*;
+use engine::datafusions::decode_enums;
 
 use crate::{
     errs::{BaseRtError, BaseRtResult},
@@ -110,7 +115,8 @@ impl<W: io::Write + AsyncWrite + Send> AsyncMysqlShim<W> for MysqlConn {
             Ok(
                 BaseCommandKind::Create
                 | BaseCommandKind::Drop
-                | BaseCommandKind::Optimize,
+                | BaseCommandKind::Optimize
+                | BaseCommandKind::Alter,
             ) => {
                 log::debug!("Query Success:1");
                 results.completed(0, 0)?;
@@ -154,7 +160,8 @@ fn write_blks<W: io::Write>(
     if blks.len() > 1 {
         return Err(BaseRtError::UnexpectedMessage);
     }
-    let blk = &blks[0];
+    //the values of Enum are returned by their names
+    let blk = &decode_enums(&blks[0])?;
     let columns: Vec<Column> = blk
         .schema()
         .fields()
@@ -177,6 +184,11 @@ fn write_blks<W: io::Write>(
     let mut writer = results.start(&columns)?;
     for row in 0..blk.num_rows() {
         for col in blk.columns() {
+            if col.is_null(row) {
+                log::debug!("Write result on row {}: NULL", row);
+                writer.write_col(None::<u8>)?;
+                continue;
+            }
             match col.data_type() {
                 DataType::Int8 => {
                     let val = col
@@ -279,6 +291,37 @@ fn write_blks<W: io::Write>(
                     log::debug!("Write result on row {}: {:?}", row, val);
                     writer.write_col(val)?;
                 }
+                DataType::Timestamp(unit, tz) => {
+                    let val = match unit {
+                        TimeUnit::Second => col
+                            .as_any()
+                            .downcast_ref::<array::TimestampSecondArray>()
+                            .unwrap()
+                            .value_as_datetime(row),
+                        TimeUnit::Millisecond => col
+                            .as_any()
+                            .downcast_ref::<array::TimestampMillisecondArray>()
+                            .unwrap()
+                            .value_as_datetime(row),
+                        TimeUnit::Microsecond => col
+                            .as_any()
+                            .downcast_ref::<array::TimestampMicrosecondArray>()
+                            .unwrap()
+                            .value_as_datetime(row),
+                        TimeUnit::Nanosecond => col
+                            .as_any()
+                            .downcast_ref::<array::TimestampNanosecondArray>()
+                            .unwrap()
+                            .value_as_datetime(row),
+                    };
+                    let tz_offset = match tz {
+                        Some(tz) => TimeZoneId::from_str(tz)?.offset(),
+                        None => 0,
+                    };
+                    let val = val.map(|v| v + Duration::seconds(tz_offset as i64));
+                    log::debug!("Write result on row {}: {:?}", row, val);
+                    writer.write_col(val)?;
+                }
                 DataType::Date16 => {
                     let val = col
                         .as_any()
@@ -290,29 +333,54 @@ fn write_blks<W: io::Write>(
                     log::debug!("Write result on row {}: {:?}", row, val);
                     writer.write_col(val)?;
                 }
-                DataType::Decimal(p, scale) => {
-                    let buf = col.data().buffers()[0].as_slice();
-                    let val = if *p >= 1 && *p <= 9 {
-                        // Decimal32
-                        let idx = row * mem::size_of::<i32>();
-                        i32::from_le_bytes(
-                            buf[idx..(idx + mem::size_of::<i32>())].try_into().unwrap(),
-                        ) as i64
-                    } else if *p >= 10 && *p <= 18 {
-                        // Decimal64
-                        let idx = row * mem::size_of::<i64>();
-                        i64::from_le_bytes(
-                            buf[idx..(idx + mem::size_of::<i64>())].try_into().unwrap(),
-                        )
-                    } else {
-                        return Err(BaseRtError::ResponseWriteError(
-                            "Failed to convert Decimal data".to_string(),
-                        ));
-                    };
-                    let mut val = format!("{}", val);
-                    if *scale != 0 {
-                        val.insert(val.len() - scale, '.')
-                    }
+                DataType::Decimal(_, _) => {
+                    let val = col
+                        .as_any()
+                        .downcast_ref::<array::DecimalArray>()
+                        .unwrap()
+                        .value_as_string(row);
+                    log::debug!("Write result on row {}: {:?}", row, val);
+                    writer.write_col(val)?;
+                }
+                //NOTE the wide integers are sent as the decimal strings
+                DataType::Int128 => {
+                    let val = col
+                        .as_any()
+                        .downcast_ref::<array::Int128Array>()
+                        .unwrap()
+                        .value(row)
+                        .to_string();
+                    log::debug!("Write result on row {}: {:?}", row, val);
+                    writer.write_col(val)?;
+                }
+                DataType::UInt128 => {
+                    let val = col
+                        .as_any()
+                        .downcast_ref::<array::UInt128Array>()
+                        .unwrap()
+                        .value(row)
+                        .to_string();
+                    log::debug!("Write result on row {}: {:?}", row, val);
+                    writer.write_col(val)?;
+                }
+                DataType::Int256 => {
+                    let val = col
+                        .as_any()
+                        .downcast_ref::<array::Int256Array>()
+                        .unwrap()
+                        .value(row)
+                        .to_string();
+                    log::debug!("Write result on row {}: {:?}", row, val);
+                    writer.write_col(val)?;
+                }
+                DataType::UInt256 => {
+                    let val = col
+                        .as_any()
+                        .downcast_ref::<array::UInt256Array>()
+                        .unwrap()
+                        .value(row)
+                        .to_string();
+                    log::debug!("Write result on row {}: {:?}", row, val);
                     writer.write_col(val)?;
                 }
                 DataType::LargeUtf8 => {
@@ -352,9 +420,14 @@ fn arrow_type_to_mysql_type(typ: &DataType) -> BaseRtResult<ColumnType> {
         DataType::Int64 | DataType::UInt64 => Ok(ColumnType::MYSQL_TYPE_LONGLONG),
         DataType::Float32 => Ok(ColumnType::MYSQL_TYPE_FLOAT),
         DataType::Float64 => Ok(ColumnType::MYSQL_TYPE_DOUBLE),
-        DataType::Timestamp32(_) => Ok(ColumnType::MYSQL_TYPE_DATETIME),
+        DataType::Timestamp32(_) | DataType::Timestamp(_, _) => {
+            Ok(ColumnType::MYSQL_TYPE_DATETIME)
+        }
         DataType::Date16 => Ok(ColumnType::MYSQL_TYPE_DATE),
         DataType::Decimal(_, _) => Ok(ColumnType::MYSQL_TYPE_DECIMAL),
+        DataType::Int128 | DataType::UInt128 | DataType::Int256 | DataType::UInt256 => {
+            Ok(ColumnType::MYSQL_TYPE_DECIMAL)
+        }
         DataType::LargeUtf8 => Ok(ColumnType::MYSQL_TYPE_VARCHAR),
         DataType::FixedSizeBinary(len) => Ok(ColumnType::MYSQL_TYPE_TINY),
         _ => Err(BaseRtError::UnsupportedConversionToBqlType),
@@ -4,7 +4,10 @@ use crate::{
     types::{BaseColumn, BaseDataBlock},
 };
 use arrow::{
-    array::ArrayData, buffer::Buffer, datatypes::DataType, record_batch::RecordBatch,
+    array::ArrayData,
+    buffer::Buffer,
+    datatypes::{DataType, TimeUnit},
+    record_batch::RecordBatch,
 };
 use arrow::{array::*, datatypes::Field, datatypes::Schema};
 use base::datetimes::TimeZoneId;
@@ -16,10 +19,10 @@ use engine::{remote, types::QueryState};
 use lang::parse::{Pair, RemoteAddr, RemoteDbType, RemoteTableInfo, Rule};
 use meta::{
     store::{parts::PartStore, sys::MetaStore},
-    types::btype_to_arrow_type,
+    types::{btype_to_arrow_type, FIELD_BQL_TYPE},
 };
 use mysql::{OptsBuilder, Pool as MyPool};
-use std::{sync::Arc, time::Instant};
+use std::{ops::RangeInclusive, sync::Arc, time::Instant};
 
 pub fn query(
     ms: &MetaStore,
@@ -32,6 +35,12 @@ pub fn query(
     let query_id = query_id.replace("-", "_");
     let raw_query = p.as_str().to_string();
     let mut qs = QueryState::default();
+    //NOTE the batches are shared with the Memory tables, no data copied
+    qs.mem_tables = BMS
+        .mem_tables
+        .iter()
+        .map(|e| (*e.key(), e.value().clone()))
+        .collect();
 
     // debug_assert!(ret == 0);
     let res = engine::run(
@@ -49,6 +58,24 @@ pub fn query(
     Ok(res)
 }
 
+///query the rows of every part of a table with ptk in ptks, the deleted rows
+///included
+pub fn query_by_parts(
+    ms: &MetaStore,
+    ps: &PartStore,
+    current_db: &str,
+    p: Pair<Rule>,
+    ptks: RangeInclusive<u64>,
+) -> BaseRtResult<Vec<(u64, RecordBatch)>> {
+    let timer = Instant::now();
+    let raw_query = p.as_str().to_string();
+    let mut qs = QueryState::default();
+    let res = engine::run_by_parts(ms, ps, current_db, p, &raw_query, ptks, &mut qs)?;
+    log::debug!("query by parts run time cost {:?}", timer.elapsed());
+
+    Ok(res)
+}
+
 const DEFAULT_REMOTE_PORT: u16 = 9528;
 const DEFAULT_REMOTE_COMPRESSION: &str = "lz4";
 const DEFAULT_REMOTE_POOL_MIN_SIZE: u16 = 1;
@@ -180,9 +207,10 @@ pub fn remote_query(
                 .map(|addr| {
                     let pool = &*ps.get(&addr).unwrap();
                     match remote::run(pool, &sql) {
-                        Ok(blks) => Ok(blks
+                        Ok(blks) => blks
                             .into_iter()
-                            .map(|b| serverblock_to_recordbatch(b).unwrap())),
+                            .map(serverblock_to_recordbatch)
+                            .collect::<BaseRtResult<Vec<_>>>(),
                         Err(err) => Err(BaseRtError::WrappingEngineError(err)),
                     }
                 })
@@ -211,6 +239,7 @@ pub fn remote_query(
                                 let col = BaseColumn {
                                     name: col.col_name,
                                     data: col.data,
+                                    enum_values: None,
                                 };
                                 blk.columns.push(col);
                             });
@@ -230,8 +259,8 @@ pub fn remote_query(
 // TODO FIXME: The best approach is to unify the server and client block definitions
 // to avoid conversions
 #[inline]
-fn sqltype_to_arrowtype(sqltype: SqlType) -> DataType {
-    match sqltype {
+fn sqltype_to_arrowtype(sqltype: SqlType) -> BaseRtResult<DataType> {
+    Ok(match sqltype {
         SqlType::UInt8 => DataType::UInt8,
         SqlType::UInt16 => DataType::UInt16,
         SqlType::UInt32 => DataType::UInt32,
@@ -241,7 +270,7 @@ fn sqltype_to_arrowtype(sqltype: SqlType) -> DataType {
         SqlType::Int32 => DataType::Int32,
         SqlType::Int64 => DataType::Int64,
         SqlType::String => DataType::LargeUtf8,
-        SqlType::FixedString(i) => DataType::LargeBinary,
+        SqlType::FixedString(i) => DataType::FixedSizeBinary(i as i32),
         SqlType::Float32 => DataType::Float32,
         SqlType::Float64 => DataType::Float64,
         SqlType::Date => DataType::Date16,
@@ -251,17 +280,64 @@ fn sqltype_to_arrowtype(sqltype: SqlType) -> DataType {
             }
             None => DataType::Timestamp32(None),
         },
-        SqlType::DateTime64(id, tz) => {
-            DataType::Timestamp32(Some(TimeZoneId(TimeZoneId::calc_offset_of_tz(tz))))
-        }
+        SqlType::DateTime64(p, tz) => DataType::Timestamp(
+            match p {
+                //NOTE the ticks are scaled up to the unit in scale_datetime64_ticks
+                0 => TimeUnit::Second,
+                1..=3 => TimeUnit::Millisecond,
+                4..=6 => TimeUnit::Microsecond,
+                _ => TimeUnit::Nanosecond,
+            },
+            Some(
+                TimeZoneId(TimeZoneId::calc_offset_of_tz(tz))
+                    .name()
+                    .to_string(),
+            ),
+        ),
         SqlType::Decimal(x, y) => DataType::Decimal(x.into(), y.into()),
         SqlType::LowCardinality => DataType::UInt8,
-        SqlType::Ipv4
-        | SqlType::Ipv6
-        | SqlType::Uuid
-        | SqlType::Enum8
-        | SqlType::Enum16
-        | SqlType::Array => unimplemented!(),
+        //NOTE the values of Enum are returned as is, as the local Enum columns
+        SqlType::Enum8 => DataType::Int8,
+        SqlType::Enum16 => DataType::Int16,
+        SqlType::Ipv4 => DataType::UInt32,
+        SqlType::Ipv6 | SqlType::Uuid => DataType::FixedSizeBinary(16),
+        //NOTE the nested columns of Array are not decoded in the client block
+        SqlType::Array => return Err(BaseRtError::UnsupportedValueConversion),
+    })
+}
+
+///set the arrow validity bitmap (1 for valid) from the null map (1 for NULL)
+fn with_null_bitmap(
+    data: ArrayDataBuilder,
+    null_map: Option<&Vec<u8>>,
+) -> ArrayDataBuilder {
+    match null_map {
+        Some(nm) if nm.iter().any(|&b| b != 0) => {
+            let mut bitmap = vec![0u8; (nm.len() + 7) / 8];
+            let mut null_count = 0;
+            for (i, &b) in nm.iter().enumerate() {
+                if b == 0 {
+                    bitmap[i >> 3] |= 1 << (i & 7);
+                } else {
+                    null_count += 1;
+                }
+            }
+            data.null_count(null_count)
+                .null_bit_buffer(Buffer::from(bitmap))
+        }
+        _ => data,
+    }
+}
+
+///scale the ticks of DateTime64(p) up to the arrow time unit of p
+fn scale_datetime64_ticks(data: &mut [u8], p: u8) {
+    let m = 10i64.pow((3 - p as u32 % 3) % 3);
+    if m == 1 {
+        return;
+    }
+    for t in data.chunks_exact_mut(8) {
+        let v = i64::from_le_bytes([t[0], t[1], t[2], t[3], t[4], t[5], t[6], t[7]]);
+        t.copy_from_slice(&(v * m).to_le_bytes());
     }
 }
 
@@ -273,15 +349,31 @@ fn serverblock_to_recordbatch(b: ServerBlock) -> BaseRtResult<RecordBatch> {
     let nrows = b.rows as usize;
 
     for mut c in b.columns {
-        let arrow_type = sqltype_to_arrowtype(c.header.field.get_sqltype());
+        let arrow_type = sqltype_to_arrowtype(c.header.field.get_sqltype())?;
 
-        fields.push(Field::new(
+        let mut field = Field::new(
             &c.header.name,
             arrow_type.clone(),
             c.header.field.is_nullable(),
-        ));
+        );
+        //the addresses and UUIDs are returned with their types like the local ones
+        let typ = match c.header.field.get_sqltype() {
+            SqlType::Ipv4 => Some("IPv4"),
+            SqlType::Ipv6 => Some("IPv6"),
+            SqlType::Uuid => Some("UUID"),
+            _ => None,
+        };
+        if let Some(typ) = typ {
+            let md = vec![(FIELD_BQL_TYPE.to_string(), typ.to_string())];
+            field.set_metadata(Some(md.into_iter().collect()));
+        }
+        fields.push(field);
 
-        let data = unsafe { c.data.into_bytes() };
+        let null_map = c.data.null_map();
+        let mut data = unsafe { c.data.into_bytes() };
+        if let SqlType::DateTime64(p, _) = c.header.field.get_sqltype() {
+            scale_datetime64_ticks(&mut data, p);
+        }
 
         let buf = Buffer::from_slice_ref(&data);
 
@@ -297,13 +389,12 @@ fn serverblock_to_recordbatch(b: ServerBlock) -> BaseRtResult<RecordBatch> {
                 .len(nrows)
                 .add_buffer(buf_om)
                 .add_buffer(buf)
-                .build()
         } else {
             ArrayData::builder(arrow_type.clone())
                 .len(nrows)
                 .add_buffer(buf)
-                .build()
-        }?;
+        };
+        let data = with_null_bitmap(data, null_map.as_ref()).build()?;
         match arrow_type {
             DataType::Int8 => {
                 cols.push(Arc::new(Int8Array::from(data)));
@@ -338,6 +429,9 @@ fn serverblock_to_recordbatch(b: ServerBlock) -> BaseRtResult<RecordBatch> {
             DataType::Timestamp32(_) => {
                 cols.push(Arc::new(Timestamp32Array::from(data)));
             }
+            DataType::Timestamp(_, _) => {
+                cols.push(make_array(data));
+            }
             DataType::Date16 => {
                 cols.push(Arc::new(Date16Array::from(data)));
             }
@@ -380,7 +474,6 @@ fn basedatablock_to_recordbatch(b: BaseDataBlock) -> BaseRtResult<RecordBatch> {
                     .len(nrows)
                     .add_buffer(buf_om)
                     .add_buffer(buf)
-                    .build()
             } else {
                 // Must give an offset map
                 todo!();
@@ -389,8 +482,8 @@ fn basedatablock_to_recordbatch(b: BaseDataBlock) -> BaseRtResult<RecordBatch> {
             ArrayData::builder(arrow_type.clone())
                 .len(nrows)
                 .add_buffer(buf)
-                .build()
-        }?;
+        };
+        let data = with_null_bitmap(data, c.data.null_map.as_ref()).build()?;
         match arrow_type {
             DataType::Int8 => {
                 cols.push(Arc::new(Int8Array::from(data)));
@@ -416,6 +509,18 @@ fn basedatablock_to_recordbatch(b: BaseDataBlock) -> BaseRtResult<RecordBatch> {
             DataType::UInt64 => {
                 cols.push(Arc::new(UInt64Array::from(data)));
             }
+            DataType::Int128 => {
+                cols.push(Arc::new(Int128Array::from(data)));
+            }
+            DataType::UInt128 => {
+                cols.push(Arc::new(UInt128Array::from(data)));
+            }
+            DataType::Int256 => {
+                cols.push(Arc::new(Int256Array::from(data)));
+            }
+            DataType::UInt256 => {
+                cols.push(Arc::new(UInt256Array::from(data)));
+            }
             DataType::Float32 => {
                 cols.push(Arc::new(Float32Array::from(data)));
             }
@@ -425,6 +530,9 @@ fn basedatablock_to_recordbatch(b: BaseDataBlock) -> BaseRtResult<RecordBatch> {
             DataType::Timestamp32(_) => {
                 cols.push(Arc::new(Timestamp32Array::from(data)));
             }
+            DataType::Timestamp(_, _) => {
+                cols.push(make_array(data));
+            }
             DataType::Date16 => {
                 cols.push(Arc::new(Date16Array::from(data)));
             }
@@ -1,17 +1,25 @@
-use std::{convert::TryFrom, fmt::Debug, intrinsics::copy_nonoverlapping, slice};
-
-use arrow::{array::LargeStringArray, datatypes::DataType, record_batch::RecordBatch};
+use std::{
+    convert::TryFrom, fmt::Debug, intrinsics::copy_nonoverlapping, slice, str::FromStr,
+};
+
+use arrow::{
+    array::{ArrayRef, LargeStringArray, ListArray},
+    datatypes::{DataType, TimeUnit},
+    record_batch::RecordBatch,
+};
 use base::{codec::encode_varint64, datetimes::TimeZoneId};
 
 use bytes::{Buf, BufMut, BytesMut};
 use client::prelude::{types::SqlType, ServerBlock};
-use meta::types::{BaseChunk, BqlType};
+use meta::types::{BaseChunk, BqlType, EnumValues, FIELD_BQL_TYPE};
 
 use crate::errs::{BaseRtError, BaseRtResult};
 
 pub struct BaseColumn {
     pub name: Vec<u8>,
     pub data: BaseChunk,
+    ///the names and values of Enum8/Enum16, which are in the type of header
+    pub enum_values: Option<EnumValues>,
 }
 
 #[derive(Default)]
@@ -38,33 +46,13 @@ impl BaseDataBlock {
         self.columns = vec![];
     }
 
+    ///the null map (1 for NULL, 0 for value) from the validity of an arrow array
     #[inline(always)]
-    pub fn null_map_from_data(
-        btype: &BqlType,
-        offsets: Option<&Vec<u32>>,
-        data: &Vec<u8>,
-    ) -> Option<Vec<u8>> {
-        if !matches!(btype, BqlType::String) {
-            Some(
-                data.chunks(btype.size_in_usize().unwrap())
-                    .map(|c| if c.into_iter().all(|&b| b == 0) { 1 } else { 0 })
-                    .collect::<Vec<_>>(),
-            )
+    pub fn null_map_from_array(col: &ArrayRef) -> Vec<u8> {
+        if col.null_count() == 0 {
+            vec![0u8; col.len()]
         } else {
-            // Todo: test the performance
-            let offsets = offsets.as_ref().unwrap();
-            let null_map = offsets[..(offsets.len() - 1)]
-                .iter()
-                .map(|offset| {
-                    let offset = *offset as usize;
-                    if data[offset..offset + 1][0] == 0 {
-                        1
-                    } else {
-                        0
-                    }
-                })
-                .collect::<Vec<_>>();
-            Some(null_map)
+            (0..col.len()).map(|i| col.is_null(i) as u8).collect()
         }
     }
 }
@@ -80,19 +68,74 @@ fn arrow_type_to_btype(typ: &DataType) -> BaseRtResult<BqlType> {
         DataType::Int16 => Ok(BqlType::Int(16)),
         DataType::Int32 => Ok(BqlType::Int(32)),
         DataType::Int64 => Ok(BqlType::Int(64)),
+        DataType::Int128 => Ok(BqlType::Int(128)),
+        DataType::UInt128 => Ok(BqlType::UInt(128)),
+        DataType::Int256 => Ok(BqlType::Int256),
+        DataType::UInt256 => Ok(BqlType::UInt256),
         DataType::Float16 => Ok(BqlType::Float(16)),
         DataType::Float32 => Ok(BqlType::Float(32)),
         DataType::Float64 => Ok(BqlType::Float(64)),
         DataType::Timestamp32(None) => Ok(BqlType::DateTime),
         DataType::Timestamp32(Some(tz)) => Ok(BqlType::DateTimeTz(*tz)),
+        DataType::Timestamp(unit, tz) => {
+            let p = match unit {
+                TimeUnit::Second => 0,
+                TimeUnit::Millisecond => 3,
+                TimeUnit::Microsecond => 6,
+                TimeUnit::Nanosecond => 9,
+            };
+            let tz = match tz {
+                Some(tz) => Some(TimeZoneId::from_str(tz)?.quarters()),
+                None => None,
+            };
+            Ok(BqlType::DateTime64(p, tz))
+        }
         DataType::Date16 => Ok(BqlType::Date),
         DataType::Decimal(p, s) => Ok(BqlType::Decimal(*p as u8, *s as u8)),
         DataType::LargeUtf8 => Ok(BqlType::String),
         DataType::FixedSizeBinary(len) => Ok(BqlType::FixedString(*len as u8)),
+        DataType::List(f) => BqlType::array_of(arrow_type_to_btype(f.data_type())?)
+            .ok_or(BaseRtError::UnsupportedConversionToBqlType),
         _ => Err(BaseRtError::UnsupportedConversionToBqlType),
     }
 }
 
+///the elements of the rows of a list array, and the offsets of the rows in
+///them like those of String
+fn list_array_as_blob(
+    col: &ArrayRef,
+    btype: BqlType,
+) -> BaseRtResult<(Vec<u8>, Vec<u32>)> {
+    let arr = col
+        .as_any()
+        .downcast_ref::<ListArray>()
+        .ok_or(BaseRtError::FailToUnwrapOpt)?;
+    let elem = btype.elem_type().ok_or(BaseRtError::FailToUnwrapOpt)?;
+    let vals = arr.values();
+    let los = arr.value_offsets();
+    //NOTE the offsets of the list are the indexes of the elements in vals
+    let (buf, bos): (_, Vec<usize>) = if elem == BqlType::String {
+        let sa = vals
+            .as_any()
+            .downcast_ref::<LargeStringArray>()
+            .ok_or(BaseRtError::FailToUnwrapOpt)?;
+        let vos = sa.value_offsets();
+        let bos = los.iter().map(|o| vos[*o as usize] as usize).collect();
+        (&vals.data().buffers()[1], bos)
+    } else {
+        let siz = elem.size_in_usize()?;
+        let bos = los
+            .iter()
+            .map(|o| (vals.offset() + *o as usize) * siz)
+            .collect();
+        (&vals.data().buffers()[0], bos)
+    };
+    let ofs_s = bos.first().copied().ok_or(BaseRtError::FailToUnwrapOpt)?;
+    let ofs_e = bos.last().copied().ok_or(BaseRtError::FailToUnwrapOpt)?;
+    let data = buf.as_slice()[ofs_s..ofs_e].to_vec();
+    Ok((data, bos.iter().map(|o| (o - ofs_s) as u32).collect()))
+}
+
 impl TryFrom<RecordBatch> for BaseDataBlock {
     type Error = BaseRtError;
 
@@ -105,8 +148,39 @@ impl TryFrom<RecordBatch> for BaseDataBlock {
         blk.ncols = ncols;
         for i in 0..ncols {
             let btype = arrow_type_to_btype(fields[i].data_type())?;
+            //the full types of Enum, IPv4, IPv6 and UUID are kept in the metadata
+            //by the engine, they can not be told from their arrow types
+            let (btype, enum_values) = match fields[i]
+                .metadata()
+                .as_ref()
+                .and_then(|md| md.get(FIELD_BQL_TYPE))
+            {
+                Some(typ) if typ.starts_with("Enum") => {
+                    let (typ, ev) = EnumValues::from_bytes(typ.as_bytes())?;
+                    (typ, Some(ev))
+                }
+                Some(typ) => (BqlType::from_bytes(typ.as_bytes())?, None),
+                None => (btype, None),
+            };
             let name = fields[i].name().as_bytes().to_vec();
             let col = &cols[i];
+            if matches!(btype, BqlType::Array(_, _)) {
+                let (data, offsets) = list_array_as_blob(col, btype)?;
+                blk.nrows = col.len();
+                blk.columns.push(BaseColumn {
+                    name,
+                    data: BaseChunk {
+                        btype,
+                        size: col.len(),
+                        data,
+                        null_map: None,
+                        offset_map: Some(offsets),
+                        lc_dict_data: None,
+                    },
+                    enum_values: None,
+                });
+                continue;
+            }
             let cd = col.data();
             // let array = col.as_any().downcast_ref::<array::Int64Array>().unwrap().values();
             let buf = if matches!(btype, BqlType::String) {
@@ -115,38 +189,34 @@ impl TryFrom<RecordBatch> for BaseDataBlock {
                 &col.data().buffers()[0]
             };
             // log::debug!("cd.get_array_memory_size(): {}", cd.get_array_memory_size());
-            let (len_in_bytes, offsets) = if matches!(btype, BqlType::String) {
-                let arr = col.as_any().downcast_ref::<LargeStringArray>().unwrap();
-                let ofs = arr
-                    .value_offsets()
-                    .last()
-                    .copied()
-                    .ok_or(BaseRtError::FailToUnwrapOpt)?;
-
-                (
-                    ofs as usize,
-                    Some(arr.value_offsets().iter().map(|o| *o as u32).collect()),
-                )
-            } else {
-                (btype.size_in_usize()? * col.len(), None)
-            };
+            //NOTE the array may be a slice of a batch, so starts from its offset
+            let (ofs_in_bytes, len_in_bytes, offsets) =
+                if matches!(btype, BqlType::String) {
+                    let arr = col.as_any().downcast_ref::<LargeStringArray>().unwrap();
+                    let vos = arr.value_offsets();
+                    let ofs_s =
+                        vos.first().copied().ok_or(BaseRtError::FailToUnwrapOpt)?;
+                    let ofs_e =
+                        vos.last().copied().ok_or(BaseRtError::FailToUnwrapOpt)?;
+                    (
+                        ofs_s as usize,
+                        (ofs_e - ofs_s) as usize,
+                        Some(vos.iter().map(|o| (*o - ofs_s) as u32).collect()),
+                    )
+                } else {
+                    let siz = btype.size_in_usize()?;
+                    (siz * cd.offset(), siz * col.len(), None)
+                };
             let data = unsafe {
-                std::slice::from_raw_parts(buf.as_ptr(), len_in_bytes).to_vec()
+                std::slice::from_raw_parts(buf.as_ptr().add(ofs_in_bytes), len_in_bytes)
+                    .to_vec()
             };
             blk.nrows = col.len(); //FIXME all rows are in same size
 
-            let null_map = if !matches!(btype, BqlType::String) {
-                if fields[i].is_nullable() {
-                    BaseDataBlock::null_map_from_data(&btype, offsets.as_ref(), &data)
-                } else {
-                    None
-                }
+            let null_map = if fields[i].is_nullable() {
+                Some(BaseDataBlock::null_map_from_array(col))
             } else {
-                if fields[i].is_nullable() {
-                    BaseDataBlock::null_map_from_data(&btype, offsets.as_ref(), &data)
-                } else {
-                    None
-                }
+                None
             };
 
             blk.columns.push(BaseColumn {
@@ -162,6 +232,7 @@ impl TryFrom<RecordBatch> for BaseDataBlock {
                     // pub lc_dict_size: usize,
                     lc_dict_data: None,
                 },
+                enum_values,
             });
         }
         Ok(blk)
@@ -188,6 +259,7 @@ impl BaseColumn {
                 offset_map: None,
                 lc_dict_data: None,
             },
+            enum_values: None,
         }
     }
 
@@ -403,8 +475,8 @@ fn read_raw_varint64_slow(bs: &mut &[u8]) -> BaseRtResult<u64> {
 // TODO FIXME: The best approach is to unify the server and client block definitions
 // to avoid conversions
 #[inline]
-fn sqltype_to_bqltype(sqltype: SqlType) -> BqlType {
-    match sqltype {
+fn sqltype_to_bqltype(sqltype: SqlType) -> BaseRtResult<BqlType> {
+    Ok(match sqltype {
         SqlType::UInt8 => BqlType::UInt(8),
         SqlType::UInt16 => BqlType::UInt(16),
         SqlType::UInt32 => BqlType::UInt(32),
@@ -424,24 +496,28 @@ fn sqltype_to_bqltype(sqltype: SqlType) -> BqlType {
             }
             None => BqlType::DateTime,
         },
-        SqlType::DateTime64(id, tz) => {
-            BqlType::DateTimeTz(TimeZoneId(TimeZoneId::calc_offset_of_tz(tz)))
-        }
+        SqlType::DateTime64(p, tz) => BqlType::DateTime64(
+            p,
+            Some(TimeZoneId(TimeZoneId::calc_offset_of_tz(tz)).quarters()),
+        ),
         SqlType::Decimal(x, y) => BqlType::Decimal(x, y),
         SqlType::LowCardinality => BqlType::LowCardinalityTinyText,
         SqlType::Uuid => BqlType::Uuid,
-        SqlType::Ipv4
-        | SqlType::Ipv6
-        | SqlType::Enum8
-        | SqlType::Enum16
-        | SqlType::Array => unimplemented!(),
-    }
+        SqlType::Enum8 => BqlType::Enum8,
+        SqlType::Enum16 => BqlType::Enum16,
+        SqlType::Ipv4 => BqlType::Ipv4,
+        SqlType::Ipv6 => BqlType::Ipv6,
+        //NOTE the nested columns of Array are not decoded in the client block
+        SqlType::Array => return Err(BaseRtError::UnsupportedValueConversion),
+    })
 }
 
 // TODO FIXME: The best approach is to unify the server and client block definitions
 // to avoid conversions
-impl From<ServerBlock> for BaseDataBlock {
-    fn from(b: ServerBlock) -> Self {
+impl TryFrom<ServerBlock> for BaseDataBlock {
+    type Error = BaseRtError;
+
+    fn try_from(b: ServerBlock) -> Result<Self, Self::Error> {
         let mut new_blk = BaseDataBlock::default();
         let nrows = b.rows as usize;
 
@@ -449,16 +525,12 @@ impl From<ServerBlock> for BaseDataBlock {
         new_blk.nrows = nrows;
 
         for mut c in b.columns {
-            let btype = sqltype_to_bqltype(c.header.field.get_sqltype());
+            let btype = sqltype_to_bqltype(c.header.field.get_sqltype())?;
             let offset_map = c.data.offset_map();
             let data = unsafe { c.data.into_bytes() };
             let field = c.header.field;
             let null_map = if field.is_nullable() {
-                if !matches!(btype, BqlType::String) {
-                    BaseDataBlock::null_map_from_data(&btype, None, &data)
-                } else {
-                    BaseDataBlock::null_map_from_data(&btype, offset_map.as_ref(), &data)
-                }
+                Some(c.data.null_map().unwrap_or_else(|| vec![0u8; nrows]))
             } else {
                 None
             };
@@ -473,10 +545,11 @@ impl From<ServerBlock> for BaseDataBlock {
             let column = BaseColumn {
                 name: c.header.name.as_bytes().to_vec(),
                 data: chunk,
+                enum_values: None,
             };
 
             new_blk.columns.push(column);
         }
-        new_blk
+        Ok(new_blk)
     }
 }
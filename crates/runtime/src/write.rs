@@ -1,20 +1,39 @@
 use std::{
     collections::HashMap,
+    fs::{File, OpenOptions},
     intrinsics::{copy_nonoverlapping, unlikely},
-    mem, slice,
+    lazy::SyncLazy,
+    mem,
+    os::unix::fs::FileExt,
+    slice,
+    sync::{Arc, RwLock},
 };
 
-use base::mem::shape_slice;
+use arrow::{
+    datatypes::{Field, Schema},
+    record_batch::RecordBatch,
+};
+use base::{codec::decode_varint64, mem::shape_slice};
 use libc::{c_void, close};
 use meta::{
+    codecs::compress_into,
+    confs::Durability,
     store::{
         parts::{
-            ensure_table_path_existed, gen_ompath_from_part_path, get_part_path,
-            open_file_as_fd, PartStore,
+            ensure_table_path_existed, gen_nmpath_from_part_path,
+            gen_ompath_from_part_path, gen_pipath_from_part_path, get_part_file_name,
+            get_part_path, has_stats_minmax, is_sparse_indexable, open_file_as_fd,
+            stats_key_of_signed, write_sparse_index, CoPaBase, CoPaBytes, CoPaCommit,
+            CoPaCrcs, CoPaMeta, CoPaStats, CoPaWrite, FileCrcs, PartMeta, PartStore,
+            PartWrite,
         },
         sys::MetaStore,
+        tablog::TabLog,
+    },
+    types::{
+        btype_to_arrow_type, convert_numeric_values, BaseChunk, BqlType, ColumnInfo,
+        EngineType, Id,
     },
-    types::{BqlType, Id},
 };
 
 use crate::{
@@ -27,6 +46,27 @@ pub fn write_block(
     blk: &mut BaseDataBlock,
     tab_ins: &str,
     tid_ins: Id,
+) -> BaseRtResult<()> {
+    write_block_with(blk, tab_ins, tid_ins, false)
+}
+
+///write the block into the table of which the lock has been held
+///
+///WARN the caller should hold DURABILITY_SYNC_LOCK for read before the table
+///lock, as the writes do
+pub(crate) fn write_block_locked(
+    blk: &mut BaseDataBlock,
+    tab_ins: &str,
+    tid_ins: Id,
+) -> BaseRtResult<()> {
+    write_block_with(blk, tab_ins, tid_ins, true)
+}
+
+fn write_block_with(
+    blk: &mut BaseDataBlock,
+    tab_ins: &str,
+    tid_ins: Id,
+    locked: bool,
 ) -> BaseRtResult<()> {
     debug_assert!(tab_ins.len() > 0);
     debug_assert!(tid_ins > 0);
@@ -34,6 +74,15 @@ pub fn write_block(
     let ms = &BMS.meta_store;
     let ps = &BMS.part_store;
     log::debug!("tab_ins: {}, insert block: {:?}", tab_ins, blk);
+    convert_enum_chunks(blk, ms, tab_ins)?;
+
+    match ms.get_table_info_engine(tid_ins)? {
+        EngineType::Memory => return write_mem_table(blk, ms, tab_ins, tid_ins),
+        EngineType::Log => {
+            return write_log_table(blk, ms, ps, tab_ins, tid_ins, locked);
+        }
+        _ => {}
+    }
 
     //scan for batching
     let ptks = ms
@@ -55,14 +104,227 @@ pub fn write_block(
         return Err(BaseRtError::TooManyPartsPerBlock);
     }
 
+    let pk = get_pk_info(blk, ms, tid_ins)?;
+    let cis = get_column_infos(blk, ms, tab_ins)?;
+    let dur = BMS.conf.storage.durability;
     for (ptk, idxs) in parts {
-        if has_blob_type_column(blk) {
-            write_part_locked(blk, ptk, idxs, ms, ps, tab_ins, tid_ins)?;
-        } else {
-            write_part(blk, ptk, idxs, ms, ps, tab_ins, tid_ins)?;
+        //rows of the part are sorted by the primary key
+        let idxs = match pk {
+            Some((ipk, _)) => sort_idxs_by_key(&idxs, &blk.columns[ipk].data),
+            None => idxs,
+        };
+        if locked {
+            write_part(blk, ptk, idxs, pk, &cis, ms, ps, tab_ins, tid_ins, dur)?;
+            continue;
         }
+        //NOTE the writes share the lock of the table, so they run concurrently
+        //     with each other but not with the changes of the parts
+        let _dg = DURABILITY_SYNC_LOCK.read().unwrap();
+        ps.acquire_shared_lock(tid_ins)?;
+        let res = write_part(blk, ptk, idxs, pk, &cis, ms, ps, tab_ins, tid_ins, dur);
+        ps.release_shared_lock(tid_ins)?;
+        res?;
     }
+    //the files have been synced before the commits of the parts
+    if dur == Durability::PerInsert {
+        ps.flush()?;
+        ms.flush()?;
+    }
+
+    Ok(())
+}
+
+///convert the chunks of the Enum columns into the values of their names, the
+///names (or the values in text) of String chunks are looked up and the values
+///of integer chunks are validated
+fn convert_enum_chunks(
+    blk: &mut BaseDataBlock,
+    ms: &MetaStore,
+    tab_ins: &str,
+) -> BaseRtResult<()> {
+    let nrows = blk.nrows;
+    for (cn, cid, ci) in ms.get_columns_by_qtn(tab_ins)? {
+        let typ = ci.data_type;
+        if !matches!(typ, BqlType::Enum8 | BqlType::Enum16) {
+            continue;
+        }
+        let chk = match blk.columns.iter_mut().find(|c| c.name == cn.as_bytes()) {
+            Some(col) => &mut col.data,
+            None => continue,
+        };
+        let ev = ms
+            .get_column_enum(cid)?
+            .ok_or(BaseRtError::SchemaInfoShouldExistButNot)?;
+        let width = typ.size_in_usize()?;
+        let nm = chk.null_map.as_ref().filter(|nm| nm.len() == nrows);
+        let mut data = Vec::with_capacity(nrows * width);
+        for i in 0..nrows {
+            //NOTE NULL takes 0 in data
+            if nm.map_or(false, |nm| nm[i] != 0) {
+                data.extend_from_slice(&[0u8; 2][..width]);
+                continue;
+            }
+            let v = match chk.btype {
+                BqlType::String => {
+                    let om = chk
+                        .offset_map
+                        .as_ref()
+                        .filter(|om| om.len() > nrows)
+                        .ok_or(BaseRtError::InvalidWireFormatInColumn)?;
+                    let bs = chk
+                        .data
+                        .get(om[i] as usize..om[i + 1] as usize)
+                        .ok_or(BaseRtError::InvalidWireFormatInColumn)?;
+                    let (len, n) = decode_varint64(bs)
+                        .filter(|(len, n)| n + *len as usize == bs.len())
+                        .ok_or(BaseRtError::InvalidWireFormatInColumn)?;
+                    let name = &bs[n..n + len as usize];
+                    ev.value_of(name)
+                        .or_else(|| {
+                            std::str::from_utf8(name)
+                                .ok()
+                                .and_then(|s| s.parse::<i16>().ok())
+                                .filter(|v| ev.name_of(*v).is_some())
+                        })
+                        .ok_or_else(|| {
+                            BaseRtError::InvalidStringConversion(name.to_vec())
+                        })?
+                }
+                BqlType::Enum8 | BqlType::Enum16 | BqlType::Int(8 | 16)
+                    if chk.btype.size_in_usize()? == width =>
+                {
+                    let bs = chk
+                        .data
+                        .get(i * width..(i + 1) * width)
+                        .ok_or(BaseRtError::InvalidWireFormatInColumn)?;
+                    let v = match bs {
+                        [b] => *b as i8 as i16,
+                        _ => i16::from_le_bytes([bs[0], bs[1]]),
+                    };
+                    if ev.name_of(v).is_none() {
+                        return Err(BaseRtError::InvalidStringConversion(
+                            v.to_string().into_bytes(),
+                        ));
+                    }
+                    v
+                }
+                _ => return Err(BaseRtError::UnsupportedValueConversion),
+            };
+            data.extend_from_slice(&v.to_le_bytes()[..width]);
+        }
+        chk.btype = typ;
+        chk.data = data;
+        chk.offset_map = None;
+    }
+    Ok(())
+}
+
+///the chunks of all columns of the table in the order of get_columns_by_qtn
+fn get_table_chunks<'b>(
+    blk: &'b BaseDataBlock,
+    ms: &MetaStore,
+    tab_ins: &str,
+) -> BaseRtResult<Vec<(String, Id, ColumnInfo, &'b BaseChunk)>> {
+    let mut rt = Vec::with_capacity(blk.ncols);
+    for (cn, cid, ci) in ms.get_columns_by_qtn(tab_ins)? {
+        let col = blk
+            .columns
+            .iter()
+            .find(|c| c.name == cn.as_bytes())
+            .ok_or(BaseRtError::ColumnNotExist)?;
+        rt.push((cn, cid, ci, &col.data));
+    }
+    Ok(rt)
+}
+
+///append the block as a batch of all columns into the Memory table
+fn write_mem_table(
+    blk: &BaseDataBlock,
+    ms: &MetaStore,
+    tab_ins: &str,
+    tid_ins: Id,
+) -> BaseRtResult<()> {
+    let chks = get_table_chunks(blk, ms, tab_ins)?;
+    let mut fields = Vec::with_capacity(chks.len());
+    let mut arrs = Vec::with_capacity(chks.len());
+    for (cn, _, ci, chk) in chks {
+        fields.push(Field::new(
+            &cn,
+            btype_to_arrow_type(ci.data_type)?,
+            ci.is_nullable,
+        ));
+        arrs.push(chk.to_arrow_array(ci.data_type, ci.is_nullable)?);
+    }
+    let batch = RecordBatch::try_new(Arc::new(Schema::new(fields)), arrs)?;
+    //NOTE the appends are serialized by the entry of the table, no table lock
+    BMS.mem_tables.entry(tid_ins).or_default().push(batch);
+
+    Ok(())
+}
+
+///append the chunks of the block into the column files of the Log table
+fn write_log_table(
+    blk: &BaseDataBlock,
+    ms: &MetaStore,
+    ps: &PartStore,
+    tab_ins: &str,
+    tid_ins: Id,
+    locked: bool,
+) -> BaseRtResult<()> {
+    let chks = get_table_chunks(blk, ms, tab_ins)?;
+    let chks: Vec<(Id, &BaseChunk)> = chks
+        .into_iter()
+        .map(|(_, cid, _, chk)| (cid, chk))
+        .collect();
+    let tl = TabLog::new(ps.get_part_dir(tid_ins, 0)?, tid_ins);
+    let sync = BMS.conf.storage.durability == Durability::PerInsert;
+    if locked {
+        return Ok(tl.append(&chks, sync)?);
+    }
+    //NOTE the appends into a table are serialized by its lock, as the column
+    //     files and the sizes file are shared by all of them
+    let _dg = DURABILITY_SYNC_LOCK.read().unwrap();
+    ps.acquire_lock(tid_ins)?;
+    let res = tl.append(&chks, sync);
+    ps.release_lock(tid_ins)?;
+    res?;
+
+    Ok(())
+}
+
+///held exclusively by the batch sync, so that no commit of the part writes
+///can be persisted without its data files synced
+pub(crate) static DURABILITY_SYNC_LOCK: SyncLazy<RwLock<()>> =
+    SyncLazy::new(|| RwLock::new(()));
+
+///sync the file systems of the data dirs, then flush the meta stores
+pub(crate) fn sync_for_durability(
+    data_dirs: &[String],
+    ms: &MetaStore,
+    ps: &PartStore,
+) -> BaseRtResult<()> {
+    let _dg = DURABILITY_SYNC_LOCK.write().unwrap();
+    for dd in data_dirs {
+        sync_fs_of(dd)?;
+    }
+    ps.flush()?;
+    ms.flush()?;
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn sync_fs_of(dir: &str) -> BaseRtResult<()> {
+    use std::os::unix::io::AsRawFd;
+    let f = File::open(dir)?;
+    if unsafe { libc::syncfs(f.as_raw_fd()) } != 0 {
+        return Err(BaseRtError::WrappingIoError(std::io::Error::last_os_error()));
+    }
+    Ok(())
+}
 
+#[cfg(not(target_os = "linux"))]
+fn sync_fs_of(_dir: &str) -> BaseRtResult<()> {
+    unsafe { libc::sync() };
     Ok(())
 }
 
@@ -178,6 +440,160 @@ fn gen_part_idxs<T: 'static + Sized + Copy>(
     Ok(parts)
 }
 
+///return the index of primary key column in blk and the index granularity,
+///None if the table has no primary key or its type is not indexable
+fn get_pk_info(
+    blk: &BaseDataBlock,
+    ms: &MetaStore,
+    tid: Id,
+) -> BaseRtResult<Option<(usize, usize)>> {
+    let pk = match ms.get_table_info_sort_key(tid)? {
+        Some(k) => k,
+        _ => return Ok(None),
+    };
+    for i in 0..blk.ncols {
+        let col = &blk.columns[i];
+        if &col.name[..] == pk.as_bytes() && is_sparse_indexable(col.data.btype) {
+            let granularity = ms.get_table_info_index_granularity(tid)?;
+            return Ok(Some((i, granularity)));
+        }
+    }
+    Ok(None)
+}
+
+fn get_column_infos(
+    blk: &BaseDataBlock,
+    ms: &MetaStore,
+    tab_ins: &str,
+) -> BaseRtResult<Vec<ColumnInfo>> {
+    let mut cis = Vec::with_capacity(blk.ncols);
+    for i in 0..blk.ncols {
+        let cname = unsafe { std::str::from_utf8_unchecked(&blk.columns[i].name) };
+        let qcn = [tab_ins, cname].join(".");
+        let cid = ms.cid_by_qname(qcn).ok_or(BaseRtError::ColumnNotExist)?;
+        let ci = ms
+            .get_column_info(cid)?
+            .ok_or(BaseRtError::SchemaInfoShouldExistButNot)?;
+        cis.push(ci);
+    }
+    Ok(cis)
+}
+
+#[inline(always)]
+fn key_as_u64(cchk: &BaseChunk, i: usize) -> u64 {
+    let cdata = &cchk.data;
+    match cchk.btype {
+        BqlType::UInt(8) => cdata[i] as u64,
+        BqlType::UInt(16) | BqlType::Date => shape_slice::<u16>(cdata)[i] as u64,
+        BqlType::UInt(32) | BqlType::DateTime | BqlType::DateTimeTz(_) => {
+            shape_slice::<u32>(cdata)[i] as u64
+        }
+        BqlType::UInt(64) => shape_slice::<u64>(cdata)[i],
+        _ => unreachable!("only sparse indexable keys here"),
+    }
+}
+
+///the value of the row i of the column in the order of its copa stats
+#[inline(always)]
+fn stats_key_as_u64(cchk: &BaseChunk, i: usize) -> u64 {
+    let cdata = &cchk.data;
+    match cchk.btype {
+        BqlType::Int(8) => stats_key_of_signed(cdata[i] as i8 as i64),
+        BqlType::Int(16) => stats_key_of_signed(shape_slice::<i16>(cdata)[i] as i64),
+        BqlType::Int(32) => stats_key_of_signed(shape_slice::<i32>(cdata)[i] as i64),
+        BqlType::Int(64) => stats_key_of_signed(shape_slice::<i64>(cdata)[i]),
+        _ => key_as_u64(cchk, i),
+    }
+}
+
+///gather the stats of the rows in idxs of the column
+fn gen_copa_stats(cchk: &BaseChunk, idxs: &Vec<(u32, u32)>, pt_len: usize) -> CoPaStats {
+    let mut st = CoPaStats {
+        rows: pt_len as u64,
+        ..Default::default()
+    };
+    let nm = cchk.null_map.as_ref().filter(|nm| nm.len() > 0);
+    let has_minmax = has_stats_minmax(cchk.btype);
+    if nm.is_none() && !has_minmax {
+        return st;
+    }
+    let mut has_values = false;
+    for r in idxs {
+        for i in r.0 as usize..=r.1 as usize {
+            if nm.map_or(false, |nm| nm[i] != 0) {
+                st.nulls += 1;
+            } else if has_minmax {
+                let k = stats_key_as_u64(cchk, i);
+                if has_values {
+                    st.min = st.min.min(k);
+                    st.max = st.max.max(k);
+                } else {
+                    st.min = k;
+                    st.max = k;
+                    has_values = true;
+                }
+            }
+        }
+    }
+    st
+}
+
+///stable sort rows in idxs by the key column, then coalesce them into ranges
+///
+///NOTE only the rows of one insert are sorted, a part with several runs is
+///     sorted as a whole when merged, the sparse marks keep min/max of all runs
+fn sort_idxs_by_key(idxs: &Vec<(u32, u32)>, cchk: &BaseChunk) -> Vec<(u32, u32)> {
+    let mut rows = Vec::with_capacity(count_len(idxs));
+    for r in idxs {
+        rows.extend(r.0..=r.1);
+    }
+    rows.sort_by_key(|i| key_as_u64(cchk, *i as usize));
+    let mut sorted: Vec<(u32, u32)> = Vec::with_capacity(idxs.len());
+    for i in rows {
+        match sorted.last_mut() {
+            Some(r) if (r.1 + 1) == i => r.1 = i,
+            _ => sorted.push((i, i)),
+        }
+    }
+    sorted
+}
+
+///the keys of the rows in idxs of the primary key column
+fn gather_keys(cchk: &BaseChunk, idxs: &Vec<(u32, u32)>) -> Vec<u64> {
+    let mut keys = Vec::with_capacity(count_len(idxs));
+    for r in idxs {
+        for i in r.0..=r.1 {
+            keys.push(key_as_u64(cchk, i as usize));
+        }
+    }
+    keys
+}
+
+///merge the keys into the marks of the pi file, and fdatasync it if sync
+///
+///WARN the writes before should have been ended, as the marks of the granule
+///     shared with them are merged
+fn write_part_sparse_index(
+    keys: &[u64],
+    ptk: u64,
+    prid: usize,
+    granularity: usize,
+    pk_cid: Id,
+    dp: &str,
+    gen: usize,
+    tid: Id,
+    sync: bool,
+) -> BaseRtResult<()> {
+    let fpath = get_part_path(tid, pk_cid, ptk, gen, dp)?;
+    let pipath = gen_pipath_from_part_path(&fpath)?;
+    write_sparse_index(&pipath, prid, keys, granularity)?;
+    if sync {
+        let fp = format!("{}/{}/{}pi", dp, tid, get_part_file_name(pk_cid, ptk, gen));
+        File::open(fp)?.sync_data()?;
+    }
+    Ok(())
+}
+
 #[inline(always)]
 fn count_len(idxs: &Vec<(u32, u32)>) -> usize {
     let mut blen = 0usize;
@@ -242,64 +658,187 @@ fn gather_into_blob_buf(
     (bb, om)
 }
 
-#[inline(always)]
-fn has_blob_type_column(blk: &BaseDataBlock) -> bool {
-    for i in 0..blk.ncols {
-        let col = &blk.columns[i];
-        let cchk = &col.data;
-        let ctyp = cchk.btype;
-        if matches!(ctyp, BqlType::String) {
-            return true;
-        }
-    }
-    return false;
-}
-
-#[inline(always)]
+///write the rows in idxs of the block into the part with the commit protocol
+///of the part store: the rows and the bytes of the write are reserved with its
+///intent recorded before the files are written, and the sizes, stats and
+///committed rows of the part are published together after, in the order of
+///the reservations. The files are written out of any lock, so the writes into
+///a table run concurrently. The write failed halfway is rolled back at once
+///with the writes after it, and the one interrupted by a crash is rolled back
+///by `recover_part_writes` at startup.
+///
+///WARN the caller should hold the table lock, shared or not
 fn write_part(
     blk: &BaseDataBlock,
     ptk: u64,
     idxs: Vec<(u32, u32)>,
+    pk: Option<(usize, usize)>,
+    cis: &Vec<ColumnInfo>,
     ms: &MetaStore,
     ps: &PartStore,
     tab_ins: &str,
     tid: Id,
+    durability: Durability,
 ) -> BaseRtResult<()> {
-    let dp = ps.get_part_dir(ptk);
-    ensure_table_path_existed(tid, dp)?;
-
     let pt_len = count_len(&idxs);
-    let prid = ps.get_prid_int_ptk(tid, ptk, pt_len)?;
-    for i in 0..blk.ncols {
-        let col = &blk.columns[i];
+    let bufs = gather_copa_bufs(blk, &idxs, cis)?;
+    let keys = pk.map(|(ipk, _)| gather_keys(&blk.columns[ipk].data, &idxs));
+    let mut cbs = Vec::with_capacity(blk.ncols);
+    for (col, buf) in blk.columns.iter().zip(&bufs) {
         let cname = unsafe { std::str::from_utf8_unchecked(&col.name) }; //FIXME
         let qcn = [tab_ins, cname].join(".");
         let cid = ms.cid_by_qname(qcn).ok_or(BaseRtError::ColumnNotExist)?;
-        let fpath = get_part_path(tid, cid, ptk, dp)?;
-        let fd = open_file_as_fd(&fpath)?;
+        cbs.push((cid, buf.bytes));
+    }
+    let (dp, pw) = ps.reserve_part_write(tid, ptk, pt_len, &cbs)?;
+    let gen = ps.get_part_gen(tid, ptk)?;
+    let res = ensure_table_path_existed(tid, dp)
+        .map_err(BaseRtError::from)
+        .and_then(|_| dump_copa_bufs(&bufs, &pw, ptk, dp, gen, tid))
+        .and_then(|_| {
+            if durability == Durability::PerInsert {
+                let cids: Vec<Id> = pw.copas.iter().map(|cw| cw.cid).collect();
+                sync_part_files(dp, tid, ptk, gen, &cids)?;
+            }
+            Ok(())
+        });
+    //NOTE the crcs and the sparse marks are merged with the ones of the writes
+    //     before, so they are updated in the turn of the write, which is waited
+    //     for even if the files are failed to write
+    let res = ps
+        .wait_for_part_write(tid, ptk, &pw)
+        .map_err(BaseRtError::from)
+        .and(res)
+        .and_then(|_| {
+            let mut crcss = Vec::with_capacity(pw.copas.len());
+            for cw in &pw.copas {
+                crcss.push(ps.get_copa_crcs(cw.cid, ptk)?.unwrap_or_default());
+            }
+            let copas = gen_copa_commits(&bufs, &pw, crcss, ptk, dp, gen, tid)?;
+            //NOTE the pi file is written after the files synced above, so it
+            //     is synced on its own before the commit
+            if let (Some((ipk, granularity)), Some(keys)) = (pk, &keys) {
+                let pk_cid = pw.copas[ipk].cid;
+                write_part_sparse_index(
+                    keys,
+                    ptk,
+                    pw.prid,
+                    granularity,
+                    pk_cid,
+                    dp,
+                    gen,
+                    tid,
+                    durability == Durability::PerInsert,
+                )?;
+            }
+            Ok(ps.commit_part_write(tid, ptk, &pw, &copas)?)
+        });
+    if let Err(e) = res {
+        if let Some(fpw) = ps.abort_part_write(tid, ptk, &pw)? {
+            //NOTE the reservation is rolled back even if the files are not
+            //     truncated, the bytes left after the sizes are overwritten by
+            //     the writes after
+            let tr = truncate_part_files(dp, tid, ptk, gen, &fpw);
+            ps.rollback_part_write(tid, ptk, &fpw)?;
+            tr?;
+        }
+        return Err(e);
+    }
 
-        let cchk = &col.data;
-        let ctyp = cchk.btype;
-        let cdata = &cchk.data;
+    Ok(())
+}
 
-        //FIXME nm, om
-        //write
-        let ctyp_siz = ctyp.size_in_usize()?;
-        let pt_len_in_bytes = pt_len * ctyp_siz;
-        let offset_in_bytes = prid * ctyp_siz;
-        //FIXME gather into bb
-        let bb = gather_into_buf(&idxs, pt_len_in_bytes, cdata.as_ptr(), ctyp_siz);
-        dump_buf(
-            fd,
-            offset_in_bytes,
-            pt_len_in_bytes,
-            bb.as_ptr() as *const c_void,
+///roll back the part writes which have not been committed before a crash
+pub(crate) fn recover_part_writes(ps: &PartStore) -> BaseRtResult<()> {
+    for (tid, ptk, pw) in ps.get_part_writes()? {
+        let (dp, gen) = (ps.get_part_dir(tid, ptk)?, ps.get_part_gen(tid, ptk)?);
+        truncate_part_files(dp, tid, ptk, gen, &pw)?;
+        ps.rollback_part_write(tid, ptk, &pw)?;
+        log::info!(
+            "rolled back the write of rows [{}, {}) into part {} of table {}",
+            pw.prid,
+            pw.prid + pw.rows,
+            ptk,
+            tid
         );
+    }
+    Ok(())
+}
 
-        ps.insert_copa_int_ptk(cid, ptk, offset_in_bytes + pt_len_in_bytes)?;
+///update the crcs of the part file after the bytes [ofs, len) are written
+fn update_file_crcs(
+    fc: &mut FileCrcs,
+    fpath: &str,
+    ofs: usize,
+    len: usize,
+) -> BaseRtResult<()> {
+    let start = fc.block_start(ofs);
+    let mut bs = vec![0u8; len - start];
+    File::open(fpath)?.read_exact_at(&mut bs, start as u64)?;
+    fc.update(start, &bs);
+    Ok(())
+}
+
+///fdatasync the files of the copas of the part in the generation and the dir
+///of them
+pub(crate) fn sync_part_files(
+    dp: &str,
+    tid: Id,
+    ptk: u64,
+    gen: usize,
+    cids: &[Id],
+) -> BaseRtResult<()> {
+    let tp = format!("{}/{}", dp, tid);
+    for cid in cids {
+        let fp = format!("{}/{}", tp, get_part_file_name(*cid, ptk, gen));
+        for sfx in ["", "om", "nm", "pi"].iter() {
+            match File::open([fp.as_str(), sfx].concat()) {
+                Ok(f) => f.sync_data()?,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(BaseRtError::WrappingIoError(e)),
+            }
+        }
     }
-    ps.set_copa_size_int_ptk(tid, ptk, prid + pt_len)?;
+    //the entries of the files created
+    File::open(&tp)?.sync_all()?;
+    Ok(())
+}
 
+///truncate the files of the copas in the write to their sizes before it
+///
+///NOTE the marks of the sparse index are kept, which may be wider than the
+///     committed keys but never narrower
+fn truncate_part_files(
+    dp: &str,
+    tid: Id,
+    ptk: u64,
+    gen: usize,
+    pw: &PartWrite,
+) -> BaseRtResult<()> {
+    //the offset map of n rows has n + 1 offsets
+    let om_len = if pw.prid > 0 {
+        (pw.prid + 1) * mem::size_of::<u64>()
+    } else {
+        0
+    };
+    for cw in &pw.copas {
+        let fp = format!("{}/{}/{}", dp, tid, get_part_file_name(cw.cid, ptk, gen));
+        for (sfx, len) in [("", cw.siz_in_bytes), ("om", om_len), ("nm", pw.prid)].iter()
+        {
+            match OpenOptions::new()
+                .write(true)
+                .open([fp.as_str(), sfx].concat())
+            {
+                Ok(f) => {
+                    if f.metadata()?.len() > *len as u64 {
+                        f.set_len(*len as u64)?;
+                    }
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(BaseRtError::WrappingIoError(e)),
+            }
+        }
+    }
     Ok(())
 }
 
@@ -335,91 +874,321 @@ unsafe fn fallocate(
     libc::fallocate(fd, mode, offset_in_bytes, pt_len_in_bytes) == 0
 }
 
-fn dump_buf(fd: u32, offset_in_bytes: usize, pt_len_in_bytes: usize, buf: *const c_void) {
-    unsafe {
+fn dump_buf(
+    fd: u32,
+    offset_in_bytes: usize,
+    pt_len_in_bytes: usize,
+    buf: *const c_void,
+) -> BaseRtResult<()> {
+    let n = unsafe {
         fallocate(fd as i32, 0, offset_in_bytes as i64, pt_len_in_bytes as i64);
-        libc::pwrite(fd as i32, buf, pt_len_in_bytes, offset_in_bytes as i64);
+        let n = libc::pwrite(fd as i32, buf, pt_len_in_bytes, offset_in_bytes as i64);
         close(fd as i32);
+        n
+    };
+    if n < 0 {
+        return Err(BaseRtError::WrappingIoError(std::io::Error::last_os_error()));
     }
+    if n as usize != pt_len_in_bytes {
+        return Err(BaseRtError::WrappingIoError(std::io::Error::new(
+            std::io::ErrorKind::WriteZero,
+            "part file is written partially",
+        )));
+    }
+    Ok(())
 }
 
-//TODO slow path, to boost the performance when we want
-// #[inline(always)]
-fn write_part_locked(
+fn read_buf(fpath: &Vec<u8>, buf: &mut [u8]) -> BaseRtResult<()> {
+    let fd = open_file_as_fd(fpath)?;
+    let n = unsafe {
+        let n = libc::pread(fd as i32, buf.as_mut_ptr() as *mut c_void, buf.len(), 0);
+        close(fd as i32);
+        n
+    };
+    if n < 0 {
+        return Err(BaseRtError::WrappingIoError(std::io::Error::last_os_error()));
+    }
+    Ok(())
+}
+
+///rewrite the base rows of the copa of a numeric column into its own file,
+///which are converted from the copa of the old column of a modified column, or
+///the default of an added column
+///
+///WARN the caller should hold the table lock
+pub(crate) fn write_copa_base_materialized(
+    ps: &PartStore,
+    tid: Id,
+    cid: Id,
+    ci: &ColumnInfo,
+    ptk: u64,
+    base: &CoPaBase,
+    default: Option<&[u8]>,
+) -> BaseRtResult<()> {
+    let dp = ps.get_part_dir(tid, ptk)?;
+    let gen = ps.get_part_gen(tid, ptk)?;
+    let rows = base.rows;
+    let (data, nm) = if base.src_cid != 0 {
+        let src_path = get_part_path(tid, base.src_cid, ptk, gen, dp)?;
+        let mut src = vec![0u8; rows * base.src_type.size_in_usize()?];
+        read_buf(&src_path, &mut src)?;
+        let data = convert_numeric_values(&src, base.src_type, ci.data_type)?;
+        //NOTE the old copa without null map has no NULLs
+        let mut nm = vec![0u8; rows];
+        if ci.is_nullable {
+            read_buf(&gen_nmpath_from_part_path(&src_path)?, &mut nm)?;
+        }
+        (data, nm)
+    } else {
+        let siz = ci.data_type.size_in_usize()?;
+        let data = match default {
+            Some(dft) if dft.len() == siz => dft.repeat(rows),
+            _ => vec![0u8; rows * siz],
+        };
+        //NOTE the implicit default of a Nullable column is NULL
+        let null_base = ci.is_nullable && default.is_none();
+        (data, vec![null_base as u8; rows])
+    };
+
+    let fpath = get_part_path(tid, cid, ptk, gen, dp)?;
+    let fd = open_file_as_fd(&fpath)?;
+    dump_buf(fd, 0, data.len(), data.as_ptr() as *const c_void)?;
+    if ci.is_nullable {
+        let fd_nm = open_file_as_fd(&gen_nmpath_from_part_path(&fpath)?)?;
+        dump_buf(fd_nm, 0, rows, nm.as_ptr() as *const c_void)?;
+    }
+
+    let siz_in_bytes = ps
+        .get_copa_siz_in_bytes_int_ptk(cid, ptk)?
+        .unwrap_or_default();
+    let new_siz_in_bytes = siz_in_bytes.max(data.len());
+    let fp = format!("{}/{}/{}", dp, tid, get_part_file_name(cid, ptk, gen));
+    let mut crcs = ps.get_copa_crcs(cid, ptk)?.unwrap_or_default();
+    update_file_crcs(&mut crcs.files[0], &fp, 0, new_siz_in_bytes)?;
+    if ci.is_nullable {
+        let len = crcs.files[2].len.max(rows);
+        update_file_crcs(&mut crcs.files[2], &[fp.as_str(), "nm"].concat(), 0, len)?;
+    }
+    ps.set_copa_crcs(cid, ptk, &crcs)?;
+    ps.insert_copa_int_ptk(cid, ptk, new_siz_in_bytes)?;
+    ps.remove_copa_base(cid, ptk)?;
+    Ok(())
+}
+
+///write the rows of the block as a whole part into the files of the generation
+///in the table dir under dp, which are sorted by the primary key, return the
+///meta of the written part
+///
+///NOTE the files of the part should not exist in the dir
+pub(crate) fn write_merged_part(
     blk: &BaseDataBlock,
     ptk: u64,
-    idxs: Vec<(u32, u32)>,
+    gen: usize,
     ms: &MetaStore,
-    ps: &PartStore,
     tab_ins: &str,
     tid: Id,
-) -> BaseRtResult<()> {
-    let dp = ps.get_part_dir(ptk);
+    dp: &str,
+) -> BaseRtResult<PartMeta> {
+    debug_assert!(blk.nrows > 0);
     ensure_table_path_existed(tid, dp)?;
-
-    let pt_len = count_len(&idxs);
-
-    ps.acquire_lock(tid)?;
-    let prid = ps.get_prid_int_ptk(tid, ptk, pt_len)?;
-    for i in 0..blk.ncols {
-        let col = &blk.columns[i];
-        let cname = unsafe { std::str::from_utf8_unchecked(&col.name) }; //FIXME
+    let pk = get_pk_info(blk, ms, tid)?;
+    let cis = get_column_infos(blk, ms, tab_ins)?;
+    let idxs = vec![(0, (blk.nrows - 1) as u32)];
+    let idxs = match pk {
+        Some((ipk, _)) => sort_idxs_by_key(&idxs, &blk.columns[ipk].data),
+        None => idxs,
+    };
+    let mut copas = Vec::with_capacity(blk.ncols);
+    for col in &blk.columns {
+        let cname = unsafe { std::str::from_utf8_unchecked(&col.name) };
         let qcn = [tab_ins, cname].join(".");
         let cid = ms.cid_by_qname(qcn).ok_or(BaseRtError::ColumnNotExist)?;
+        copas.push(CoPaWrite {
+            cid,
+            siz_in_bytes: 0,
+        });
+    }
+    let pw = PartWrite {
+        prid: 0,
+        rows: blk.nrows,
+        copas,
+    };
+    let bufs = gather_copa_bufs(blk, &idxs, &cis)?;
+    dump_copa_bufs(&bufs, &pw, ptk, dp, gen, tid)?;
+    let crcss = vec![CoPaCrcs::default(); blk.ncols];
+    let ccs = gen_copa_commits(&bufs, &pw, crcss, ptk, dp, gen, tid)?;
+    if let Some((ipk, granularity)) = pk {
+        let keys = gather_keys(&blk.columns[ipk].data, &idxs);
+        let pk_cid = pw.copas[ipk].cid;
+        //NOTE the merged part is synced as a whole by the caller
+        write_part_sparse_index(&keys, ptk, 0, granularity, pk_cid, dp, gen, tid, false)?;
+    }
+    let mut pm = PartMeta {
+        rows: blk.nrows,
+        ..Default::default()
+    };
+    for cc in ccs {
+        pm.copas.push(CoPaMeta {
+            cid: cc.cid,
+            siz_in_bytes: cc.siz_in_bytes,
+            base: CoPaBase::default(),
+            stats: cc.stats,
+        });
+        pm.crcs.push(cc.crcs);
+    }
+    Ok(pm)
+}
 
-        let cchk = &col.data;
-        let ctyp = cchk.btype;
+///the rows of a write into a copa, which are gathered before the write is
+///reserved
+struct CoPaBuf {
+    ///the rows in the size of the type, or the bytes appended for the blobs
+    ///and the compressed
+    data: Vec<u8>,
+    bytes: CoPaBytes,
+    ///the offsets of the blobs in data, which are written after the size of
+    ///the copa before the write
+    om: Option<Vec<usize>>,
+    nm: Option<Vec<u8>>,
+    stats: CoPaStats,
+}
 
-        match ctyp {
-            BqlType::String => {
-                let cdata = &cchk.data;
+fn gather_copa_bufs(
+    blk: &BaseDataBlock,
+    idxs: &Vec<(u32, u32)>,
+    cis: &Vec<ColumnInfo>,
+) -> BaseRtResult<Vec<CoPaBuf>> {
+    let pt_len = count_len(idxs);
+    let mut rt = Vec::with_capacity(blk.ncols);
+    for i in 0..blk.ncols {
+        let cchk = &blk.columns[i].data;
+        let ctyp = cchk.btype;
+        let cdata = &cchk.data;
+        let (data, bytes, om) = match ctyp {
+            _ if ctyp.is_blob() => {
                 let omdata = cchk.offset_map.as_ref().unwrap();
-                let siz_in_bytes = ps
-                    .get_copa_siz_in_bytes_int_ptk(cid, ptk)?
-                    .unwrap_or_default();
-                let (bb, om) =
-                    gather_into_blob_buf(&idxs, cdata.as_ptr(), omdata, siz_in_bytes);
-
-                let fpath = get_part_path(tid, cid, ptk, dp)?;
-                let fd = open_file_as_fd(&fpath)?;
-                let ompath = gen_ompath_from_part_path(&fpath)?;
-                let fd_om = open_file_as_fd(&ompath)?;
-                dump_buf(fd, siz_in_bytes, bb.len(), bb.as_ptr() as *const c_void);
-                dump_buf(
-                    fd_om,
-                    prid * mem::size_of::<u64>(),
-                    om.len() * mem::size_of::<u64>(),
-                    om.as_ptr() as *const c_void,
-                );
-
-                ps.insert_copa_int_ptk(cid, ptk, siz_in_bytes + bb.len())?;
+                let (bb, om) = gather_into_blob_buf(idxs, cdata.as_ptr(), omdata, 0);
+                let len = bb.len();
+                (bb, CoPaBytes::Appended(len), Some(om))
+            }
+            //compressed copas are appended like blobs
+            _ if !cis[i].codec.is_none() => {
+                let ctyp_siz = ctyp.size_in_usize()?;
+                let pt_len_in_bytes = pt_len * ctyp_siz;
+                let bb = gather_into_buf(idxs, pt_len_in_bytes, cdata.as_ptr(), ctyp_siz);
+                let mut cb = Vec::new();
+                compress_into(cis[i].codec, ctyp_siz, &bb, &mut cb)?;
+                let len = cb.len();
+                (cb, CoPaBytes::Appended(len), None)
             }
             _ => {
-                let cdata = &cchk.data;
                 let ctyp_siz = ctyp.size_in_usize()?;
                 let pt_len_in_bytes = pt_len * ctyp_siz;
-                let offset_in_bytes = prid * ctyp_siz;
-                let bb =
-                    gather_into_buf(&idxs, pt_len_in_bytes, cdata.as_ptr(), ctyp_siz);
-
-                let fpath = get_part_path(tid, cid, ptk, dp)?;
-                let fd = open_file_as_fd(&fpath)?;
-                dump_buf(
-                    fd,
-                    offset_in_bytes,
-                    pt_len_in_bytes,
-                    bb.as_ptr() as *const c_void,
-                );
-
-                ps.insert_copa_int_ptk(cid, ptk, offset_in_bytes + pt_len_in_bytes)?;
+                let bb = gather_into_buf(idxs, pt_len_in_bytes, cdata.as_ptr(), ctyp_siz);
+                (bb, CoPaBytes::Fixed(ctyp_siz), None)
             }
+        };
+        //a block without null map (from non-Nullable client type) has no NULLs
+        let nm = if cis[i].is_nullable {
+            Some(match &cchk.null_map {
+                Some(nm) if nm.len() > 0 => gather_into_buf(idxs, pt_len, nm.as_ptr(), 1),
+                _ => vec![0u8; pt_len],
+            })
+        } else {
+            None
+        };
+        rt.push(CoPaBuf {
+            data,
+            bytes,
+            om,
+            nm,
+            stats: gen_copa_stats(cchk, idxs, pt_len),
+        });
+    }
+    Ok(rt)
+}
+
+///write the gathered rows into the files of the copas at the offsets reserved
+///for the write
+fn dump_copa_bufs(
+    bufs: &[CoPaBuf],
+    pw: &PartWrite,
+    ptk: u64,
+    dp: &str,
+    gen: usize,
+    tid: Id,
+) -> BaseRtResult<()> {
+    let prid = pw.prid;
+    for (buf, cw) in bufs.iter().zip(&pw.copas) {
+        let fpath = get_part_path(tid, cw.cid, ptk, gen, dp)?;
+        let ofs = match buf.bytes {
+            CoPaBytes::Fixed(siz) => prid * siz,
+            CoPaBytes::Appended(_) => cw.siz_in_bytes,
+        };
+        let fd = open_file_as_fd(&fpath)?;
+        dump_buf(fd, ofs, buf.data.len(), buf.data.as_ptr() as *const c_void)?;
+        if let Some(om) = &buf.om {
+            //NOTE the last offset is the first one of the next write
+            let om: Vec<usize> = om.iter().map(|o| o + cw.siz_in_bytes).collect();
+            let fd_om = open_file_as_fd(&gen_ompath_from_part_path(&fpath)?)?;
+            dump_buf(
+                fd_om,
+                prid * mem::size_of::<u64>(),
+                om.len() * mem::size_of::<u64>(),
+                om.as_ptr() as *const c_void,
+            )?;
+        }
+        if let Some(nm) = &buf.nm {
+            let fd_nm = open_file_as_fd(&gen_nmpath_from_part_path(&fpath)?)?;
+            dump_buf(fd_nm, prid, nm.len(), nm.as_ptr() as *const c_void)?;
         }
     }
-    ps.release_lock(tid)?;
+    Ok(())
+}
 
-    ps.set_copa_size_int_ptk(tid, ptk, prid + pt_len)?;
+///return the infos of the copas published by the commit of the write, whose
+///crcs are updated from crcss before it
+///
+///WARN the writes before should have been ended
+fn gen_copa_commits(
+    bufs: &[CoPaBuf],
+    pw: &PartWrite,
+    crcss: Vec<CoPaCrcs>,
+    ptk: u64,
+    dp: &str,
+    gen: usize,
+    tid: Id,
+) -> BaseRtResult<Vec<CoPaCommit>> {
+    let (prid, pt_len) = (pw.prid, pw.rows);
+    let mut rt = Vec::with_capacity(pw.copas.len());
+    for ((buf, cw), mut crcs) in bufs.iter().zip(&pw.copas).zip(crcss) {
+        let fp = format!("{}/{}/{}", dp, tid, get_part_file_name(cw.cid, ptk, gen));
+        let (ofs, siz_in_bytes) = match buf.bytes {
+            CoPaBytes::Fixed(siz) => (prid * siz, (prid + pt_len) * siz),
+            CoPaBytes::Appended(len) => (cw.siz_in_bytes, cw.siz_in_bytes + len),
+        };
+        update_file_crcs(&mut crcs.files[0], &fp, ofs, siz_in_bytes)?;
+        if let Some(om) = &buf.om {
+            update_file_crcs(
+                &mut crcs.files[1],
+                &[fp.as_str(), "om"].concat(),
+                prid * mem::size_of::<u64>(),
+                (prid + om.len()) * mem::size_of::<u64>(),
+            )?;
+        }
+        if buf.nm.is_some() {
+            let nmp = [fp.as_str(), "nm"].concat();
+            update_file_crcs(&mut crcs.files[2], &nmp, prid, prid + pt_len)?;
+        }
+        rt.push(CoPaCommit {
+            cid: cw.cid,
+            siz_in_bytes,
+            stats: buf.stats,
+            crcs,
+        });
+    }
 
-    Ok(())
+    Ok(rt)
 }
 
 #[cfg(test)]
@@ -510,6 +1279,7 @@ mod unit_tests {
                         is_primary_key: true,
                         is_nullable: false,
                         ordinal: 0,
+                        codec: Default::default(),
                     },
                 ),
                 (
@@ -519,6 +1289,7 @@ mod unit_tests {
                         is_primary_key: false,
                         is_nullable: false,
                         ordinal: 0,
+                        codec: Default::default(),
                     },
                 ),
             ],
@@ -529,7 +1300,11 @@ mod unit_tests {
                 partition_cols: "col2,".to_string(),
                 primary_keys: String::new(),
                 settings: Default::default(),
+                ttl_col: String::new(),
+                ttl_secs: 0,
+                engine_params: vec![],
             },
+            enum_values: vec![],
         };
 
         let ms = &BMS.meta_store;
@@ -625,6 +1400,7 @@ mod unit_tests {
                 offset_map: None,
                 lc_dict_data: None,
             },
+            enum_values: None,
         };
         let col2: BaseColumn = BaseColumn {
             name: b"col2".to_vec(),
@@ -636,6 +1412,7 @@ mod unit_tests {
                 offset_map: None,
                 lc_dict_data: None,
             },
+            enum_values: None,
         };
         blk.columns.push(col1);
         blk.columns.push(col2);
@@ -687,6 +1464,189 @@ mod unit_tests {
         Ok(())
     }
 
+    #[test]
+    fn test_truncate_part_files() -> BaseRtResult<()> {
+        let dp = "/tmp/write_test_truncate";
+        let (tid, ptk) = (1, 3);
+        if Path::new(dp).exists() {
+            remove_dir_all(dp)?;
+        }
+        fs::create_dir_all(format!("{}/{}", dp, tid))?;
+        let fp = |cid: Id, sfx: &str| format!("{}/{}/{}_{}{}", dp, tid, cid, ptk, sfx);
+        //copa 2 is a String column with 4 committed rows and 6 torn rows
+        fs::write(fp(2, ""), vec![1u8; 100])?;
+        fs::write(fp(2, "om"), vec![0u8; 11 * 8])?;
+        fs::write(fp(3, "nm"), vec![0u8; 10])?;
+        let pw = PartWrite {
+            prid: 4,
+            rows: 6,
+            copas: vec![
+                CoPaWrite {
+                    cid: 2,
+                    siz_in_bytes: 40,
+                },
+                CoPaWrite {
+                    cid: 3,
+                    siz_in_bytes: 16,
+                },
+            ],
+        };
+        truncate_part_files(dp, tid, ptk, 0, &pw)?;
+        let len = |p: String| fs::metadata(p).map(|m| m.len());
+        assert_eq!(len(fp(2, ""))?, 40);
+        assert_eq!(len(fp(2, "om"))?, 5 * 8);
+        assert_eq!(len(fp(3, "nm"))?, 4);
+        assert!(!Path::new(&fp(3, "")).exists());
+
+        //the files shorter than the sizes are kept
+        let pw = PartWrite {
+            prid: 8,
+            rows: 1,
+            copas: pw.copas,
+        };
+        truncate_part_files(dp, tid, ptk, 0, &pw)?;
+        assert_eq!(len(fp(2, ""))?, 40);
+
+        Ok(())
+    }
+
+    ///the meta store and part store in the dirs under tmp_dir with the table
+    ///test_db.test_tab of the UInt64 primary key col1
+    fn prepare_stores<'a>(
+        tmp_dir: &str,
+        data_dirs: &'a Vec<String>,
+        durability: Durability,
+    ) -> BaseRtResult<(MetaStore, PartStore<'a>, Id)> {
+        if Path::new(tmp_dir).exists() {
+            remove_dir_all(tmp_dir)?;
+        }
+        let meta_dirs = [format!("{}/meta", tmp_dir)];
+        fs::create_dir_all(&meta_dirs[0])?;
+        fs::create_dir_all(&data_dirs[0])?;
+        let ms = MetaStore::new(&meta_dirs)?;
+        //NOTE the part store is flushed after the data are synced in batch
+        let auto_flush = durability != Durability::Batch;
+        let ps = PartStore::new_with_volumes(&meta_dirs, data_dirs, &[], auto_flush)?;
+        let t = Table {
+            name: "test_tab".to_string(),
+            dbname: "test_db".to_string(),
+            columns: vec![(
+                "col1".to_string(),
+                ColumnInfo {
+                    data_type: BqlType::UInt(64),
+                    is_primary_key: true,
+                    is_nullable: false,
+                    ordinal: 0,
+                    codec: Default::default(),
+                },
+            )],
+            tab_info: TableInfo {
+                create_script: "not_important_here\n".to_string(),
+                engine: EngineType::BaseStorage,
+                primary_keys: "col1".to_string(),
+                ..Default::default()
+            },
+            enum_values: vec![],
+        };
+        ms.new_db(&t.dbname)?;
+        let tid = ms.create_table(&t)?;
+        Ok((ms, ps, tid))
+    }
+
+    #[test]
+    fn test_write_part_durability() -> BaseRtResult<()> {
+        let qtn = "test_db.test_tab";
+        let durs = [Durability::None, Durability::Batch, Durability::PerInsert];
+        for (i, dur) in durs.iter().enumerate() {
+            let tmp_dir = format!("/tmp/write_test_durability_{}", i);
+            let data_dirs = vec![format!("{}/data", tmp_dir)];
+            let (ms, ps, tid) = prepare_stores(&tmp_dir, &data_dirs, *dur)?;
+            let mut blk = BaseDataBlock::default();
+            blk.columns.push(BaseColumn {
+                name: b"col1".to_vec(),
+                data: BaseChunk {
+                    btype: BqlType::UInt(64),
+                    size: 3,
+                    data: shape_vec_u8(vec![3u64, 1, 2]),
+                    null_map: None,
+                    offset_map: None,
+                    lc_dict_data: None,
+                },
+                enum_values: None,
+            });
+            blk.ncols = 1;
+            blk.nrows = 3;
+            let cis = get_column_infos(&blk, &ms, qtn)?;
+            let pk = get_pk_info(&blk, &ms, tid)?;
+            let idxs = sort_idxs_by_key(&vec![(0, 2)], &blk.columns[0].data);
+            write_part(&blk, 0, idxs, pk, &cis, &ms, &ps, qtn, tid, *dur)?;
+            if *dur == Durability::Batch {
+                sync_for_durability(&data_dirs, &ms, &ps)?;
+            }
+
+            //the part is committed under every policy
+            assert!(ps.get_part_writes()?.is_empty(), "{:?}", dur);
+            assert_eq!(ps.get_part_sizes(tid)?, vec![(0, 3)], "{:?}", dur);
+            let cid = ms.cid_by_qname([qtn, "col1"].join(".")).unwrap();
+            let fp =
+                format!("{}/{}/{}", data_dirs[0], tid, get_part_file_name(cid, 0, 0));
+            let data = fs::read(fp)?;
+            assert_eq!(shape_slice::<u64>(&data), &[1, 2, 3], "{:?}", dur);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sync_for_durability_lock() -> BaseRtResult<()> {
+        use std::sync::{
+            atomic::{AtomicBool, Ordering},
+            mpsc, Arc,
+        };
+        let synced = Arc::new(AtomicBool::new(false));
+        let (tx, rx) = mpsc::channel();
+        //the writes hold the lock for read until their parts are committed
+        let dg = DURABILITY_SYNC_LOCK.read().unwrap();
+        let h = {
+            let synced = synced.clone();
+            std::thread::spawn(move || -> BaseRtResult<()> {
+                let tmp_dir = "/tmp/write_test_durability_lock";
+                let data_dirs = vec![format!("{}/data", tmp_dir)];
+                let (ms, ps, _) = prepare_stores(tmp_dir, &data_dirs, Durability::Batch)?;
+                tx.send(()).unwrap();
+                sync_for_durability(&data_dirs, &ms, &ps)?;
+                synced.store(true, Ordering::SeqCst);
+                Ok(())
+            })
+        };
+        rx.recv().unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(200));
+        assert!(!synced.load(Ordering::SeqCst));
+        drop(dg);
+        h.join().unwrap()?;
+        assert!(synced.load(Ordering::SeqCst));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sort_idxs_by_key() -> BaseRtResult<()> {
+        let keys: Vec<u32> = vec![5, 3, 4, 9, 1, 2, 0, 8];
+        let cchk = BaseChunk {
+            btype: BqlType::UInt(32),
+            size: keys.len(),
+            data: shape_vec_u8(keys),
+            null_map: None,
+            offset_map: None,
+            lc_dict_data: None,
+        };
+        let idxs = vec![(0u32, 2u32), (4u32, 5u32), (7u32, 7u32)];
+        let sorted = sort_idxs_by_key(&idxs, &cchk);
+        assert_eq!(sorted, vec![(4, 5), (1, 2), (0, 0), (7, 7)]);
+        assert_eq!(count_len(&sorted), count_len(&idxs));
+        Ok(())
+    }
+
     #[test]
     fn test_gen_part_idxs() -> BaseRtResult<()> {
         let bms_path = "/tmp/xxx";
@@ -0,0 +1,598 @@
+use std::{convert::TryInto, env, fs, net::Ipv6Addr, path::Path, sync::Once};
+
+use arrow::{
+    array::{Array, UInt64Array, UInt8Array},
+    record_batch::RecordBatch,
+};
+use baselog::{Config, LevelFilter, TermLogger, TerminalMode};
+use meta::{
+    confs::Conf,
+    store::parts::{get_available_space, get_part_file_name, SpaceFn},
+    types::{BqlType, Id},
+};
+use runtime::{
+    ch::protocol::ConnCtx,
+    errs::BaseRtResult,
+    mgmt::{BaseCommandKind, BMS, READ, WRITE},
+    read::query,
+    types::BaseDataBlock,
+    write::write_block,
+};
+use test_utils::prepare_empty_tmp_dir;
+
+static INIT: Once = Once::new();
+
+///the global BMS used by the writes is configured by the conf in the tmp dir,
+///so the tests share it and every test works in its own database
+fn prepare_bms() {
+    INIT.call_once(|| {
+        #[allow(unused_must_use)]
+        {
+            TermLogger::init(LevelFilter::Info, Config::default(), TerminalMode::Mixed);
+        }
+        let dir = prepare_empty_tmp_dir(Some("/tmp/storage_test"));
+        let (meta_dir, data_dir) = (format!("{}/meta", dir), format!("{}/data", dir));
+        fs::create_dir_all(&meta_dir).unwrap();
+        fs::create_dir_all(&data_dir).unwrap();
+        let data_dir1 = format!("{}/data1", dir);
+        fs::create_dir_all(&data_dir1).unwrap();
+        let conf: Conf = toml::from_str(&format!(
+            r#"[system]
+            meta_dirs = ["{}"]
+            data_dirs = ["{}", "{}"]
+            [server]
+            ip_addr = "127.0.0.1"
+        "#,
+            meta_dir, data_dir, data_dir1
+        ))
+        .unwrap();
+        let conf_path = format!("{}/test.conf", dir);
+        Conf::save(&conf, Some(conf_path.as_str())).unwrap();
+        env::set_var("BASE_DBG_CONF_OVERRIDE", &conf_path);
+        READ.get_or_init(|| query);
+        WRITE.get_or_init(|| write_block);
+    });
+}
+
+///run the command like the server, the inserted rows are written at once
+fn exec(cmd: &str) -> BaseRtResult<Vec<RecordBatch>> {
+    let mut cctx = ConnCtx::default();
+    match BMS.run_commands(cmd.to_string(), &mut cctx)? {
+        BaseCommandKind::InsertFormatInlineValues(mut blk, qtn, tid) => {
+            write_block(&mut blk, &qtn, tid)?;
+            Ok(vec![])
+        }
+        BaseCommandKind::Query(bs) => Ok(bs),
+        _ => Ok(vec![]),
+    }
+}
+
+///the values of the UInt64 columns of the query
+fn query_u64s(sql: &str) -> BaseRtResult<Vec<Vec<u64>>> {
+    let bs = exec(sql)?;
+    let ncols = bs.first().map_or(0, |b| b.num_columns());
+    let mut cols = vec![vec![]; ncols];
+    for b in bs {
+        for (i, col) in cols.iter_mut().enumerate() {
+            let arr = b.column(i).as_any().downcast_ref::<UInt64Array>().unwrap();
+            col.extend((0..arr.len()).map(|j| arr.value(j)));
+        }
+    }
+    Ok(cols)
+}
+
+///prepare the BMS with the database of the test, and run the ddls in it
+fn prepare_db(dbn: &str, ddls: &[&str]) -> BaseRtResult<()> {
+    prepare_bms();
+    exec(&format!("create database {}", dbn))?;
+    for ddl in ddls {
+        exec(ddl)?;
+    }
+    Ok(())
+}
+
+///if the file of the copa in the generation is in the dir of the part
+fn part_file_exists(tid: Id, cid: Id, ptk: u64, gen: usize) -> BaseRtResult<bool> {
+    let dp = BMS.part_store.get_part_dir(tid, ptk)?;
+    let fname = get_part_file_name(cid, ptk, gen);
+    Ok(Path::new(&format!("{}/{}/{}", dp, tid, fname)).exists())
+}
+
+///the num of the housekeeping logs of the action on the tables of the database
+fn count_housekeeping_logs(dbn: &str, action: &str) -> BaseRtResult<u64> {
+    let logs = query_u64s(&format!(
+        "select count(*) from system.housekeeping_log \
+         where db_name = '{}' and action = '{}'",
+        dbn, action
+    ))?;
+    Ok(logs[0][0])
+}
+
+fn tid_of(qtn: &str) -> Id {
+    BMS.meta_store.tid_by_qname(qtn).unwrap()
+}
+
+fn cid_of(qcn: &str) -> Id {
+    BMS.meta_store.cid_by_qname(qcn).unwrap()
+}
+
+#[test]
+fn test_optimize_table() -> BaseRtResult<()> {
+    prepare_db(
+        "st_optimize",
+        &["create table st_optimize.t (id UInt64 PRIMARY KEY, v UInt64)"],
+    )?;
+    let ps = &BMS.part_store;
+    exec("insert into st_optimize.t values (5, 50), (1, 10)")?;
+    exec("insert into st_optimize.t values (4, 40), (2, 20)")?;
+    exec("insert into st_optimize.t values (3, 30)")?;
+    let tid = tid_of("st_optimize.t");
+    let cid = cid_of("st_optimize.t.id");
+    assert_eq!(ps.get_part_runs(tid, 0)?, 3);
+    assert_eq!(ps.get_part_gen(tid, 0)?, 0);
+    let sorted = vec![vec![1, 2, 3, 4, 5], vec![10, 20, 30, 40, 50]];
+    assert_eq!(
+        query_u64s("select id, v from st_optimize.t order by id")?,
+        sorted
+    );
+
+    exec("optimize table st_optimize.t")?;
+    assert_eq!(ps.get_part_runs(tid, 0)?, 1);
+    assert_eq!(ps.get_part_gen(tid, 0)?, 1);
+    //the merged part is sorted by the primary key as a whole
+    assert_eq!(query_u64s("select id, v from st_optimize.t")?, sorted);
+    assert_eq!(
+        query_u64s("select count(*) from st_optimize.t")?,
+        vec![vec![5]]
+    );
+    //the files of the old generation are unlinked after the switch
+    assert!(!part_file_exists(tid, cid, 0, 0)?);
+    assert!(part_file_exists(tid, cid, 0, 1)?);
+
+    //the merged part is appended and merged again
+    exec("insert into st_optimize.t values (0, 0)")?;
+    assert_eq!(
+        query_u64s("select count(*) from st_optimize.t")?,
+        vec![vec![6]]
+    );
+    exec("optimize table st_optimize.t")?;
+    assert_eq!(ps.get_part_gen(tid, 0)?, 2);
+    assert_eq!(
+        query_u64s("select id from st_optimize.t")?,
+        vec![vec![0, 1, 2, 3, 4, 5]]
+    );
+    assert_eq!(count_housekeeping_logs("st_optimize", "MergePart")?, 2);
+
+    Ok(())
+}
+
+#[test]
+fn test_modify_added_column() -> BaseRtResult<()> {
+    prepare_db(
+        "st_modify",
+        &["create table st_modify.t (id UInt64 PRIMARY KEY)"],
+    )?;
+    exec("insert into st_modify.t values (1), (2)")?;
+    exec("alter table st_modify.t add column c UInt32 default 7")?;
+    exec("insert into st_modify.t values (3, 9)")?;
+    let cid = cid_of("st_modify.t.c");
+    assert_eq!(
+        BMS.part_store.get_copa_base(cid, 0)?.map(|b| b.rows),
+        Some(2)
+    );
+
+    //the default rows of the added column are materialized before the conversion
+    exec("alter table st_modify.t modify column c UInt16")?;
+    assert_eq!(BMS.part_store.get_copa_base(cid, 0)?, None);
+    //the rows of the converting column are materialized again
+    exec("alter table st_modify.t modify column c UInt64")?;
+    assert_eq!(
+        query_u64s("select id, c from st_modify.t order by id")?,
+        vec![vec![1, 2, 3], vec![7, 7, 9]]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_mutate_with_concurrent_writes() -> BaseRtResult<()> {
+    prepare_db(
+        "st_mutate",
+        &["create table st_mutate.t (id UInt64 PRIMARY KEY, v UInt64)"],
+    )?;
+    let vals: Vec<String> = (0..100).map(|i| format!("({}, 0)", i)).collect();
+    exec(&format!(
+        "insert into st_mutate.t values {}",
+        vals.join(", ")
+    ))?;
+
+    let writer = std::thread::spawn(|| -> BaseRtResult<()> {
+        for i in 100..120 {
+            exec(&format!("insert into st_mutate.t values ({}, 0)", i))?;
+        }
+        Ok(())
+    });
+    let merger = std::thread::spawn(|| -> BaseRtResult<()> {
+        for _ in 0..5 {
+            exec("optimize table st_mutate.t")?;
+        }
+        Ok(())
+    });
+    for _ in 0..10 {
+        exec("alter table st_mutate.t update v = v + 1 where id < 100")?;
+    }
+    writer.join().unwrap()?;
+    merger.join().unwrap()?;
+
+    //every update marks and appends the same rows exactly once
+    assert_eq!(
+        query_u64s("select count(*) from st_mutate.t")?,
+        vec![vec![120]]
+    );
+    assert_eq!(
+        query_u64s("select count(*), sum(v) from st_mutate.t where id < 100")?,
+        vec![vec![100], vec![1000]]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_query_while_merging() -> BaseRtResult<()> {
+    prepare_db(
+        "st_snapshot",
+        &[
+            "create table st_snapshot.t (id UInt64 PRIMARY KEY, v UInt64) \
+             SETTINGS index_granularity=2",
+        ],
+    )?;
+    let merger = std::thread::spawn(|| -> BaseRtResult<()> {
+        for i in 0..20u64 {
+            //the rows re-sorted by the merge are purged of the deleted ones
+            let j = 100 - i;
+            exec(&format!(
+                "insert into st_snapshot.t values ({}, {}), ({}, {})",
+                j,
+                j * 10,
+                i,
+                i * 10
+            ))?;
+            exec(&format!(
+                "alter table st_snapshot.t delete where id = {}",
+                j
+            ))?;
+            exec("optimize table st_snapshot.t")?;
+        }
+        Ok(())
+    });
+    //the columns, the row ranges and the deletion bitmap of a part are all of
+    //the generation taken by the query
+    for _ in 0..200 {
+        let cols = query_u64s("select id, v from st_snapshot.t where id < 50")?;
+        if let [ids, vs] = &cols[..] {
+            for (id, v) in ids.iter().zip(vs) {
+                assert!(*id < 50);
+                assert_eq!(*v, id * 10);
+            }
+        }
+    }
+    merger.join().unwrap()?;
+    assert_eq!(
+        query_u64s("select id from st_snapshot.t where id < 50 order by id")?,
+        vec![(0..20).collect::<Vec<u64>>()]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_prune_by_signed_stats() -> BaseRtResult<()> {
+    prepare_db(
+        "st_signed",
+        &["create table st_signed.t (id UInt64, v Int32, p UInt64) PARTITION BY p"],
+    )?;
+    exec("insert into st_signed.t values (1, -5, 1), (2, -1, 1), (3, 3, 2), (4, 20, 3)")?;
+    //the parts of the negative values are not skipped by the ranges from 0
+    assert_eq!(
+        query_u64s("select id from st_signed.t where v < 2 order by id")?,
+        vec![vec![1, 2]]
+    );
+    assert_eq!(
+        query_u64s("select id from st_signed.t where v > 2 order by id")?,
+        vec![vec![3, 4]]
+    );
+    assert_eq!(
+        query_u64s("select id from st_signed.t where v between 1 and 10")?,
+        vec![vec![3]]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_ttl_expiry() -> BaseRtResult<()> {
+    prepare_db(
+        "st_ttl",
+        &[
+            "create table st_ttl.t (id UInt64 PRIMARY KEY, p UInt64, ts DateTime) \
+             PARTITION BY p TTL ts + INTERVAL 30 DAY",
+        ],
+    )?;
+    //all rows of the part of p = 1 have expired, some of the part of p = 2
+    exec(
+        "insert into st_ttl.t values (1, 1, '2020-01-01 00:00:00'), \
+         (2, 1, '2020-01-02 00:00:00'), (3, 2, '2020-01-01 00:00:00'), \
+         (4, 2, '2100-01-01 00:00:00')",
+    )?;
+    let tid = tid_of("st_ttl.t");
+    assert_eq!(BMS.part_store.get_part_sizes(tid)?.len(), 2);
+
+    exec("optimize table st_ttl.t")?;
+    let sizes = BMS.part_store.get_part_sizes(tid)?;
+    assert_eq!(sizes.iter().map(|s| s.1).collect::<Vec<_>>(), vec![1]);
+    assert_eq!(
+        query_u64s("select id, p from st_ttl.t")?,
+        vec![vec![4], vec![2]]
+    );
+    for action in &["DropPart", "RewritePart"] {
+        assert_eq!(count_housekeeping_logs("st_ttl", action)?, 1);
+    }
+
+    //the live rows are kept by the later expiries
+    exec("optimize table st_ttl.t")?;
+    assert_eq!(
+        query_u64s("select id, p from st_ttl.t")?,
+        vec![vec![4], vec![2]]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_move_partition() -> BaseRtResult<()> {
+    prepare_db(
+        "st_move",
+        &[
+            "create table st_move.a (id UInt64 PRIMARY KEY, p UInt64) PARTITION BY p",
+            "create table st_move.b (id UInt64 PRIMARY KEY, p UInt64) PARTITION BY p",
+        ],
+    )?;
+    exec("insert into st_move.a values (1, 1), (2, 1), (3, 2)")?;
+    let (tid, tid_to) = (tid_of("st_move.a"), tid_of("st_move.b"));
+    let ps = &BMS.part_store;
+    let ptks: Vec<u64> = ps.get_part_sizes(tid)?.iter().map(|s| s.0).collect();
+    assert_eq!(ptks.len(), 2);
+
+    exec("alter table st_move.a move partition 1 to table st_move.b")?;
+    assert_eq!(query_u64s("select id from st_move.a")?, vec![vec![3]]);
+    assert_eq!(
+        query_u64s("select id, p from st_move.b order by id")?,
+        vec![vec![1, 2], vec![1, 1]]
+    );
+    //the files are linked into the table_to, then unlinked from the table
+    let ptk = ps.get_part_sizes(tid_to)?[0].0;
+    let (cid, cid_to) = (cid_of("st_move.a.id"), cid_of("st_move.b.id"));
+    assert!(!part_file_exists(tid, cid, ptk, 0)?);
+    assert!(part_file_exists(tid_to, cid_to, ptk, 0)?);
+
+    //the part can be merged and moved back
+    exec("insert into st_move.b values (4, 1)")?;
+    exec("optimize table st_move.b")?;
+    exec("alter table st_move.b move partition 1 to table st_move.a")?;
+    assert_eq!(
+        query_u64s("select id from st_move.a order by id")?,
+        vec![vec![1, 2, 3, 4]]
+    );
+    assert!(ps.get_part_sizes(tid_to)?.is_empty());
+
+    //the partition existed in the table_to is replaced by the moved one
+    exec("insert into st_move.b values (5, 1)")?;
+    let gen_to = ps.get_part_gen(tid_to, ptk)?;
+    exec("alter table st_move.a move partition 1 to table st_move.b")?;
+    assert_eq!(
+        query_u64s("select id from st_move.b order by id")?,
+        vec![vec![1, 2, 4]]
+    );
+    assert_eq!(ps.get_part_gen(tid_to, ptk)?, gen_to + 1);
+    assert!(!part_file_exists(tid_to, cid_to, ptk, gen_to)?);
+
+    //so is the partition existed in the table by the attached one
+    exec("alter table st_move.b detach partition 1")?;
+    exec("insert into st_move.b values (6, 1)")?;
+    exec("alter table st_move.b attach partition 1")?;
+    assert_eq!(
+        query_u64s("select id from st_move.b order by id")?,
+        vec![vec![1, 2, 4]]
+    );
+    assert_eq!(ps.get_part_gen(tid_to, ptk)?, gen_to + 2);
+
+    Ok(())
+}
+
+#[test]
+fn test_restore_failed() -> BaseRtResult<()> {
+    prepare_db(
+        "st_restore",
+        &["create table st_restore.t (id UInt64 PRIMARY KEY, p UInt64) PARTITION BY p"],
+    )?;
+    exec("insert into st_restore.t values (1, 1), (2, 2), (3, 2)")?;
+    let bp = prepare_empty_tmp_dir(Some("/tmp/storage_test_backup"));
+    let bp = format!("{}/t", bp);
+    exec(&format!("backup table st_restore.t to '{}'", bp))?;
+
+    //the re-created table is removed when any part fails to be restored
+    let bad = format!("{}/99.part", bp);
+    fs::write(&bad, b"bad")?;
+    assert!(exec(&format!("restore table st_restore.t2 from '{}'", bp)).is_err());
+    assert_eq!(BMS.meta_store.tid_by_qname("st_restore.t2"), None);
+
+    fs::remove_file(&bad)?;
+    exec(&format!("restore table st_restore.t2 from '{}'", bp))?;
+    assert_eq!(
+        query_u64s("select id, p from st_restore.t2 order by id")?,
+        vec![vec![1, 2, 3], vec![1, 2, 2]]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_backup_memory_and_log_tables() -> BaseRtResult<()> {
+    prepare_db(
+        "st_backup_ml",
+        &[
+            "create table st_backup_ml.m (id UInt64) ENGINE = Memory",
+            "create table st_backup_ml.l (id UInt64) ENGINE = Log",
+        ],
+    )?;
+    exec("insert into st_backup_ml.l values (1)")?;
+    let bp = prepare_empty_tmp_dir(Some("/tmp/storage_test_backup_ml"));
+    //the rows of them are not in the parts, so they are rejected
+    for tn in ["m", "l"].iter() {
+        let cmd = format!("backup table st_backup_ml.{} to '{}/{}'", tn, bp, tn);
+        assert!(exec(&cmd).is_err());
+        assert!(!Path::new(&format!("{}/{}", bp, tn)).exists());
+    }
+    assert!(exec(&format!("backup database st_backup_ml to '{}'", bp)).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_restore_after_rewrites() -> BaseRtResult<()> {
+    prepare_db(
+        "st_backup",
+        &["create table st_backup.t (id UInt64 PRIMARY KEY) SETTINGS index_granularity=4"],
+    )?;
+    exec("insert into st_backup.t values (1), (2)")?;
+    exec("alter table st_backup.t add column c UInt32 default 7")?;
+    exec("insert into st_backup.t values (3, 9)")?;
+    let bp = prepare_empty_tmp_dir(Some("/tmp/storage_test_backup_rewrites"));
+    let bp = format!("{}/t", bp);
+    exec(&format!("backup table st_backup.t to '{}'", bp))?;
+
+    //the last mark of the pi file and the base rows of c are rewritten in place
+    exec("insert into st_backup.t values (4, 10)")?;
+    exec("alter table st_backup.t modify column c UInt16")?;
+
+    exec(&format!("restore table st_backup.t2 from '{}'", bp))?;
+    let bs = exec("check table st_backup.t2")?;
+    let mut n = 0;
+    for b in bs {
+        let passed = b.column(1).as_any().downcast_ref::<UInt8Array>().unwrap();
+        assert!((0..passed.len()).all(|i| passed.value(i) == 1));
+        n += passed.len();
+    }
+    assert_eq!(n, 1);
+    assert_eq!(
+        query_u64s("select id from st_backup.t2 where id > 2 order by id")?,
+        vec![vec![3]]
+    );
+    assert_eq!(
+        query_u64s("select count(*) from st_backup.t2 where c = 7")?,
+        vec![vec![2]]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_rebalance_table() -> BaseRtResult<()> {
+    prepare_db(
+        "st_rebalance",
+        &["create table st_rebalance.t (id UInt64 PRIMARY KEY, v UInt64)"],
+    )?;
+    let tid = tid_of("st_rebalance.t");
+    let ps = &BMS.part_store;
+    let dds = &BMS.conf.system.data_dirs;
+    //the available spaces of the data dirs are faked to place the parts
+    let more_space_in = |dp: &str| -> SpaceFn {
+        let dp = dp.to_string();
+        Box::new(move |d: &str| Ok(if d == dp { 1 << 40 } else { 1 << 30 }))
+    };
+    ps.set_space_fn(more_space_in(&dds[0]));
+    exec("insert into st_rebalance.t values (1, 10), (2, 20)")?;
+    //the new part is placed into the dir with the most available space
+    assert_eq!(ps.get_part_dir(tid, 0)?, &dds[0]);
+
+    //the other dir has more available space now
+    ps.set_space_fn(more_space_in(&dds[1]));
+    exec("alter table st_rebalance.t rebalance")?;
+    assert_eq!(ps.get_part_dir(tid, 0)?, &dds[1]);
+    assert_eq!(fs::read_dir(format!("{}/{}", dds[0], tid))?.count(), 0);
+    assert_eq!(
+        query_u64s("select id, v from st_rebalance.t")?,
+        vec![vec![1, 2], vec![10, 20]]
+    );
+    //the moved part is written and merged in the dir
+    exec("insert into st_rebalance.t values (0, 0)")?;
+    exec("optimize table st_rebalance.t")?;
+    assert_eq!(ps.get_part_dir(tid, 0)?, &dds[1]);
+    assert_eq!(
+        query_u64s("select id, v from st_rebalance.t")?,
+        vec![vec![0, 1, 2], vec![0, 10, 20]]
+    );
+    ps.set_space_fn(Box::new(get_available_space));
+
+    Ok(())
+}
+
+#[test]
+fn test_enum_columns() -> BaseRtResult<()> {
+    prepare_db(
+        "st_enum",
+        &["create table st_enum.t (id UInt64, e Enum8('b' = 1, 'a' = 2))"],
+    )?;
+    exec("insert into st_enum.t values (1, 'a'), (2, 'b'), (3, 'a')")?;
+    //the values are compared and sorted, not the names
+    let ids = query_u64s("select id from st_enum.t where e < 'a' order by id")?;
+    assert_eq!(ids, vec![vec![2]]);
+    let ids = query_u64s("select id from st_enum.t order by e, id")?;
+    assert_eq!(ids, vec![vec![2, 1, 3]]);
+    //the values are returned with the full type in the header
+    let bs = exec("select e from st_enum.t where e = 'b'")?;
+    let blk: BaseDataBlock = bs[0].clone().try_into()?;
+    let col = &blk.columns[0];
+    assert_eq!(col.data.btype, BqlType::Enum8);
+    assert_eq!(col.data.data, vec![1]);
+    let typ = col.enum_values.as_ref().unwrap().to_vec(BqlType::Enum8)?;
+    assert_eq!(typ, b"Enum8('b' = 1, 'a' = 2)".to_vec());
+    assert!(exec("select id from st_enum.t where e = 'c'").is_err());
+
+    //the names and values of the added column are kept
+    exec("alter table st_enum.t add column f Enum16('x' = 300, 'y' = 400) default 'y'")?;
+    exec("insert into st_enum.t values (4, 'b', 'x')")?;
+    let ids = query_u64s("select id from st_enum.t where f = 'y' order by id")?;
+    assert_eq!(ids, vec![vec![1, 2, 3]]);
+    let ids = query_u64s("select id from st_enum.t where f in ('x') order by id")?;
+    assert_eq!(ids, vec![vec![4]]);
+    let ev = BMS
+        .meta_store
+        .get_column_enum(cid_of("st_enum.t.f"))?
+        .unwrap();
+    assert_eq!(ev.value_of(b"x"), Some(300));
+
+    Ok(())
+}
+
+#[test]
+fn test_ip_columns() -> BaseRtResult<()> {
+    prepare_db(
+        "st_ip",
+        &["create table st_ip.t (id UInt64, a IPv4, b IPv6)"],
+    )?;
+    exec("insert into st_ip.t values (1, '192.168.0.1', '2001:db8::1')")?;
+    //the addresses are returned with their types, not as UInt32 and FixedString(16)
+    let bs = exec("select a, b from st_ip.t")?;
+    let blk: BaseDataBlock = bs[0].clone().try_into()?;
+    let (a, b) = (&blk.columns[0].data, &blk.columns[1].data);
+    assert_eq!(a.btype, BqlType::Ipv4);
+    assert_eq!(a.btype.to_vec()?, b"IPv4".to_vec());
+    assert_eq!(a.data, 0xC0A80001u32.to_le_bytes().to_vec());
+    assert_eq!(b.btype, BqlType::Ipv6);
+    assert_eq!(b.btype.to_vec()?, b"IPv6".to_vec());
+    let ip: Ipv6Addr = "2001:db8::1".parse().unwrap();
+    assert_eq!(b.data, ip.octets().to_vec());
+
+    Ok(())
+}
@@ -5,7 +5,10 @@ use server_mysql::*;
 use tokio::net::TcpListener;
 
 use runtime::{
-    mgmt::{BMS, READ, REMOTE_READ, WRITE},
+    mgmt::{
+        spawn_durability_sync, spawn_housekeeping, spawn_merges, BMS, READ, REMOTE_READ,
+        WRITE,
+    },
     mysql::MysqlConn,
     read::{query, remote_query},
     write::write_block,
@@ -41,6 +44,12 @@ async fn main() {
     READ.get_or_init(|| query);
     WRITE.get_or_init(|| write_block);
     REMOTE_READ.get_or_init(|| remote_query);
+    if let Err(e) = BMS.resume_column_conversions() {
+        log::error!("fail to resume the conversions of columns: {}", e);
+    }
+    spawn_housekeeping();
+    spawn_durability_sync();
+    spawn_merges();
 
     let conf = &BMS.conf;
     let tcp_server_conf = conf.server.tcp.as_ref().unwrap();
@@ -14,7 +14,10 @@ use futures::{future, FutureExt};
 use log::info;
 use meta::confs::{Tcp, Tls};
 use runtime::{
-    mgmt::{BMS, READ, REMOTE_READ, WRITE},
+    mgmt::{
+        spawn_durability_sync, spawn_housekeeping, spawn_merges, BMS, READ, REMOTE_READ,
+        WRITE,
+    },
     read::{query, remote_query},
     write::write_block,
 };
@@ -62,6 +65,12 @@ async fn main() -> io::Result<()> {
     READ.get_or_init(|| query);
     WRITE.get_or_init(|| write_block);
     REMOTE_READ.get_or_init(|| remote_query);
+    if let Err(e) = BMS.resume_column_conversions() {
+        log::error!("fail to resume the conversions of columns: {}", e);
+    }
+    spawn_housekeeping();
+    spawn_durability_sync();
+    spawn_merges();
 
     // start servers
     let mut servers = vec![];